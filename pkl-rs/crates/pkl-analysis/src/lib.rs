@@ -0,0 +1,334 @@
+//! Static analysis of Pkl modules for tools like editors: what the names in a module refer to, across the modules it
+//! imports, amends, and extends.
+//!
+//! The [`Analyzer`] keeps the analysis of the modules it's told about and of the modules they import, which it reads
+//! when they're first needed. Analysis never evaluates anything, so it works on modules with errors, using what the
+//! parser's error recovery makes of them. The types it tells of declarations are the ones they declare, or else the
+//! ones their values have as far as [`types`] can tell from the source alone.
+
+#![forbid(unsafe_code)]
+
+pub mod lint;
+pub mod scope;
+pub mod symbols;
+mod types;
+
+use std::collections::HashMap;
+
+use pkl_ast::uri::has_scheme;
+use pkl_lexer::token::Span;
+
+use scope::{Declaration, ModuleAnalysis, Target};
+use types::TypeOf;
+
+/// How many modules a member is looked up through, following the modules that each amends or extends, before giving up
+/// on a cycle.
+const MAX_DEPTH: usize = 32;
+
+/// What a declaration declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Module,
+    Import,
+    Class,
+    TypeAlias,
+    Property,
+    Method,
+    /// A parameter of a method, lambda, object body, `let`, or `for` generator
+    Parameter,
+    TypeParameter,
+}
+
+/// Where a name is declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionSite {
+    /// The URI of the module that declares it
+    pub uri: String,
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The whole declaration
+    pub span: Span,
+    pub name_span: Span,
+}
+
+/// Reads the source of the module at a URI, if there is one.
+type Reader = Box<dyn Fn(&str) -> Option<String>>;
+
+/// The analysis of a set of modules.
+pub struct Analyzer {
+    modules: HashMap<String, Option<ModuleAnalysis>>,
+    reader: Reader,
+}
+
+impl Default for Analyzer {
+    /// An analyzer that reads the `file:` modules that are imported from disk.
+    fn default() -> Self {
+        Analyzer::with_reader(|uri| std::fs::read_to_string(uri.strip_prefix("file://")?).ok())
+    }
+}
+
+impl Analyzer {
+    /// An analyzer that reads the modules that are imported with `reader`.
+    pub fn with_reader(reader: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        Analyzer { modules: HashMap::new(), reader: Box::new(reader) }
+    }
+
+    /// Analyzes the module at `uri` again, whose source is now `source`.
+    pub fn update(&mut self, uri: &str, source: &str) {
+        self.modules.insert(uri.to_string(), Some(ModuleAnalysis::new(uri, source)));
+    }
+
+    /// Forgets the module at `uri`, which is read again if it's needed later.
+    pub fn remove(&mut self, uri: &str) {
+        self.modules.remove(uri);
+    }
+
+    /// The analysis of the module at `uri`, reading it if it hasn't been yet.
+    pub fn module(&mut self, uri: &str) -> Option<&ModuleAnalysis> {
+        if !self.modules.contains_key(uri) {
+            let analysis = (self.reader)(uri).map(|source| ModuleAnalysis::new(uri, &source));
+            self.modules.insert(uri.to_string(), analysis);
+        }
+        self.modules[uri].as_ref()
+    }
+
+    /// Where the name at `span` of the module at `uri` is declared, which is the declaration itself if the name is
+    /// the one it declares.
+    pub fn resolve(&mut self, uri: &str, span: Span) -> Option<DefinitionSite> {
+        let module = self.module(uri)?;
+        let contains = |outer: Span| outer.start <= span.start && span.end <= outer.end;
+        if let Some(index) = module.declarations.iter().position(|declaration| contains(declaration.name_span)) {
+            return Some(site(module, index));
+        }
+        let reference = module.references.iter().find(|reference| contains(reference.span))?;
+        let target = reference.target.clone();
+        self.target(uri, &target)
+    }
+
+    /// The names of all the modules analyzed so far that refer to the declaration at `site`, as the URI of the
+    /// module and the span of the name.
+    pub fn references(&mut self, site: &DefinitionSite) -> Vec<(String, Span)> {
+        let mut references = Vec::new();
+        let mut members = Vec::new();
+        for module in self.modules.values().flatten() {
+            for reference in &module.references {
+                match &reference.target {
+                    Target::Local(index) => {
+                        if module.uri == site.uri && module.declarations[*index].name_span == site.name_span {
+                            references.push((module.uri.clone(), reference.span));
+                        }
+                    }
+                    Target::Member { name, .. } if *name == site.name => {
+                        members.push((module.uri.clone(), reference.span, reference.target.clone()));
+                    }
+                    Target::Member { .. } => {}
+                }
+            }
+        }
+        for (uri, span, target) in members {
+            if self.target(&uri, &target).as_ref() == Some(site) {
+                references.push((uri, span));
+            }
+        }
+        references.sort_by(|(a, a_span), (b, b_span)| (a, a_span.start).cmp(&(b, b_span.start)));
+        references
+    }
+
+    /// The type of the declaration at `site`, or of what it returns if it's a method.
+    ///
+    /// A property without a type of its own, which amends or overrides the property of the module that its module
+    /// amends or extends, has the type of that property.
+    pub fn type_of(&mut self, site: &DefinitionSite) -> Option<String> {
+        let mut site = site.clone();
+        for _ in 0..MAX_DEPTH {
+            site = match &self.declaration(&site)?.ty {
+                Some(TypeOf::Known(ty)) => return Some(ty.clone()),
+                Some(TypeOf::Of(target)) => {
+                    let target = target.clone();
+                    self.target(&site.uri, &target)?
+                }
+                None => self.overridden(&site)?,
+            };
+        }
+        None
+    }
+
+    /// The doc comment of the declaration at `site`, or of the property it amends or overrides if it has none.
+    pub fn doc(&mut self, site: &DefinitionSite) -> Option<String> {
+        let mut site = site.clone();
+        for _ in 0..MAX_DEPTH {
+            if let Some(doc) = &self.declaration(&site)?.doc {
+                return Some(doc.clone());
+            }
+            site = self.overridden(&site)?;
+        }
+        None
+    }
+
+    /// How the declaration at `site` reads, like `function greet(name: String): String`, or `name: String` for a
+    /// property or parameter with the type that [`type_of`](Analyzer::type_of) tells.
+    pub fn signature(&mut self, site: &DefinitionSite) -> String {
+        if let Some(signature) = self.declaration(site).and_then(|declaration| declaration.signature.clone()) {
+            return signature;
+        }
+        match self.type_of(site) {
+            Some(ty) => format!("{}: {ty}", site.name),
+            None => site.name.clone(),
+        }
+    }
+
+    fn declaration(&mut self, site: &DefinitionSite) -> Option<&Declaration> {
+        let module = self.module(&site.uri)?;
+        module.declarations.iter().find(|declaration| declaration.name_span == site.name_span)
+    }
+
+    /// The property of the module that the module of `site` amends or extends, which the module property at `site`
+    /// amends or overrides.
+    fn overridden(&mut self, site: &DefinitionSite) -> Option<DefinitionSite> {
+        let declaration = self.declaration(site)?;
+        if declaration.parent.is_some() || declaration.kind != SymbolKind::Property {
+            return None;
+        }
+        let parent = self.module(&site.uri)?.parent.clone()?;
+        self.member(&parent, &site.name, SymbolKind::Property)
+    }
+
+    fn target(&mut self, uri: &str, target: &Target) -> Option<DefinitionSite> {
+        match target {
+            Target::Local(index) => Some(site(self.module(uri)?, *index)),
+            Target::Member { uri: member_uri, name, kind } if member_uri == uri => {
+                let parent = self.module(uri)?.parent.clone()?;
+                self.member(&parent, name, *kind)
+            }
+            Target::Member { uri, name, kind } => self.member(uri, name, *kind),
+        }
+    }
+
+    /// The declaration of the member named `name` of the module at `uri`, or of the modules it amends or extends.
+    fn member(&mut self, uri: &str, name: &str, kind: SymbolKind) -> Option<DefinitionSite> {
+        let mut uri = uri.to_string();
+        for _ in 0..MAX_DEPTH {
+            let module = self.module(&uri)?;
+            if let Some(index) = module.member(name, kind) {
+                return Some(site(module, index));
+            }
+            uri = module.parent.clone()?;
+        }
+        None
+    }
+}
+
+fn site(module: &ModuleAnalysis, index: usize) -> DefinitionSite {
+    let declaration = &module.declarations[index];
+    DefinitionSite {
+        uri: module.uri.clone(),
+        name: declaration.name.clone(),
+        kind: declaration.kind,
+        span: declaration.span,
+        name_span: declaration.name_span,
+    }
+}
+
+/// Resolves the URI of an imported module, relative to the URI of the importing module, removing the `.` and `..`
+/// segments of its path. URIs with a scheme, like `pkl:math`, are left as they are.
+pub fn resolve_uri(base: &str, uri: &str) -> Option<String> {
+    if has_scheme(uri) {
+        return Some(uri.to_string());
+    }
+    // the path of `file:///a/b.pkl` or `https://example.com/a/b.pkl` starts at the first `/` after the authority
+    let authority = base.find("://")? + 3;
+    let path_start = authority + base[authority..].find('/')?;
+    let (root, path) = base.split_at(path_start);
+    let directory = path.rsplit_once('/').map_or("", |(directory, _)| directory);
+    let path = if uri.starts_with('/') { uri.to_string() } else { format!("{directory}/{uri}") };
+
+    let mut segments = Vec::new();
+    for segment in path.split('/').skip(1) {
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(format!("{root}/{}", segments.join("/")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn analyzer(files: &[(&str, &'static str)]) -> Analyzer {
+        let files: HashMap<String, &str> = files.iter().map(|&(uri, source)| (uri.to_string(), source)).collect();
+        Analyzer::with_reader(move |uri| files.get(uri).map(|source| source.to_string()))
+    }
+
+    #[test]
+    fn across_modules() {
+        let birds = "class Bird { name: String }\nfunction make() = new Bird {}\n";
+        let base = "amends \"birds.pkl\"\nimport \"../lib/util.pkl\"\n";
+        let util = "greeting = \"hi\"\n";
+        let mut analyzer = analyzer(&[
+            ("file:///pkl/birds.pkl", birds),
+            ("file:///pkl/base.pkl", base),
+            ("file:///lib/util.pkl", util),
+        ]);
+        let source = "amends \"base.pkl\"\nimport \"birds.pkl\"\nx: birds.Bird = make()\ny = util.greeting\n";
+        analyzer.update("file:///pkl/a.pkl", source);
+
+        let at = |text: &str| {
+            let start = source.find(text).unwrap();
+            Span::new(start, start + text.len())
+        };
+        let bird = analyzer.resolve("file:///pkl/a.pkl", at("Bird")).unwrap();
+        let bird = (bird.uri.as_str(), bird.name.as_str(), bird.kind);
+        assert_eq!(bird, ("file:///pkl/birds.pkl", "Bird", SymbolKind::Class));
+        // `make` is inherited from `birds.pkl` through `base.pkl`
+        let make = analyzer.resolve("file:///pkl/a.pkl", at("make")).unwrap();
+        assert_eq!((make.uri.as_str(), make.name_span.start), ("file:///pkl/birds.pkl", birds.find("make").unwrap()));
+        // `util` isn't imported by this module, and `base.pkl`'s imports aren't members
+        assert_eq!(analyzer.resolve("file:///pkl/a.pkl", at("util")), None);
+
+        let name = analyzer.resolve("file:///pkl/a.pkl", at("x")).unwrap();
+        assert_eq!(name.kind, SymbolKind::Property);
+        let references = analyzer.references(&make);
+        assert_eq!(references, [("file:///pkl/a.pkl".to_string(), at("make"))]);
+    }
+
+    #[test]
+    fn types_and_docs() {
+        let base = "/// The port to listen on.\nport: Int\nclass Server { host: String }\n";
+        let mut analyzer = analyzer(&[("file:///base.pkl", base)]);
+        let source = "amends \"base.pkl\"\n\
+                      port = 8080\n\
+                      /// The main instance.\n\
+                      server = new Server {}\n\
+                      host = server.host\n\
+                      timeout = 5.s * 2\n\
+                      function connect(retries: Int): Boolean = retries > 0\n\
+                      connected = connect(3)\n";
+        analyzer.update("file:///a.pkl", source);
+        let mut describe = |name: &str| {
+            let start = source.find(name).unwrap();
+            let site = analyzer.resolve("file:///a.pkl", Span::new(start, start + name.len())).unwrap();
+            (analyzer.signature(&site), analyzer.doc(&site))
+        };
+        assert_eq!(describe("port"), ("port: Int".to_string(), Some("The port to listen on.".to_string())));
+        assert_eq!(describe("server"), ("server: Server".to_string(), Some("The main instance.".to_string())));
+        assert_eq!(describe("timeout").0, "timeout: Duration");
+        assert_eq!(describe("connect").0, "function connect(retries: Int): Boolean");
+        assert_eq!(describe("connected").0, "connected: Boolean");
+        // the type of `server.host` would take knowing what `server` is
+        assert_eq!(describe("host").0, "host");
+    }
+
+    #[test]
+    fn resolves_uris() {
+        assert_eq!(resolve_uri("file:///a/b/c.pkl", "../d.pkl").as_deref(), Some("file:///a/d.pkl"));
+        assert_eq!(resolve_uri("file:///a/b/c.pkl", "./e/f.pkl").as_deref(), Some("file:///a/b/e/f.pkl"));
+        assert_eq!(resolve_uri("https://example.com/x/y.pkl", "/z.pkl").as_deref(), Some("https://example.com/z.pkl"));
+        assert_eq!(resolve_uri("file:///a.pkl", "pkl:math").as_deref(), Some("pkl:math"));
+        assert_eq!(resolve_uri("repl:text", "a.pkl"), None);
+    }
+}
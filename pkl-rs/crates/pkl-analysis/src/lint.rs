@@ -0,0 +1,199 @@
+//! Lints: things in a module that are allowed but likely mistakes, like an import that nothing uses.
+//!
+//! Each [`Rule`] has a [`Level`] in a [`LintConfig`], which is a warning unless it's configured to be an error or
+//! off. Like the rest of analysis, linting works from the source alone, so it also works on modules that don't
+//! evaluate.
+
+use std::collections::HashMap;
+
+use pkl_lexer::token::Span;
+
+use crate::scope::Target;
+use crate::{Analyzer, SymbolKind};
+
+/// What a lint checks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// An import that nothing in the module refers to
+    UnusedImport,
+    /// A `local` property or method that nothing in its scope refers to
+    UnusedLocal,
+    /// A parameter or `local` member with the name of a parameter or `local` member of a scope around it
+    ShadowedName,
+    /// A reference to a member with a `@Deprecated` annotation
+    Deprecated,
+    /// A `when` generator whose condition is the same every time, like `when (true)`
+    ConstantCondition,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 5] =
+        [Rule::UnusedImport, Rule::UnusedLocal, Rule::ShadowedName, Rule::Deprecated, Rule::ConstantCondition];
+
+    /// The name it's configured by, like `unused-import`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::UnusedImport => "unused-import",
+            Rule::UnusedLocal => "unused-local",
+            Rule::ShadowedName => "shadowed-name",
+            Rule::Deprecated => "deprecated",
+            Rule::ConstantCondition => "constant-condition",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+/// How much a lint matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// It isn't checked
+    Off,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "off" => Some(Level::Off),
+            "warning" => Some(Level::Warning),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+/// The level of each rule.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Rule, Level>,
+}
+
+impl LintConfig {
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels.get(&rule).copied().unwrap_or(Level::Warning)
+    }
+
+    pub fn set(&mut self, rule: Rule, level: Level) {
+        self.levels.insert(rule, level);
+    }
+
+    /// Sets the level of a rule from a setting like `unused-import=error`.
+    pub fn parse_setting(&mut self, setting: &str) -> Result<(), String> {
+        let (rule, level) = setting.split_once('=').ok_or_else(|| format!("expected `rule=level`, found `{setting}`"))?;
+        let rule = Rule::from_name(rule.trim()).ok_or_else(|| format!("unknown lint rule `{}`", rule.trim()))?;
+        let level = Level::from_name(level.trim())
+            .ok_or_else(|| format!("unknown lint level `{}`, expected `off`, `warning`, or `error`", level.trim()))?;
+        self.set(rule, level);
+        Ok(())
+    }
+}
+
+/// A problem that a lint found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub rule: Rule,
+    pub level: Level,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Analyzer {
+    /// The lints of the module at `uri` whose rules aren't off, in the order of the source.
+    ///
+    /// Deprecated members are looked up in the modules they're declared in, which are read if they haven't been.
+    pub fn lint(&mut self, uri: &str, config: &LintConfig) -> Vec<Lint> {
+        let Some(module) = self.module(uri) else { return Vec::new() };
+        let mut found: Vec<(Rule, Span, String)> = Vec::new();
+
+        let used = |index: usize| module.references.iter().any(|reference| reference.target == Target::Local(index));
+        for (index, declaration) in module.declarations.iter().enumerate() {
+            let name = &declaration.name;
+            if declaration.kind == SymbolKind::Import && !used(index) {
+                found.push((Rule::UnusedImport, declaration.span, format!("the import `{name}` is never used")));
+            } else if declaration.local && !used(index) {
+                let what = if declaration.kind == SymbolKind::Method { "method" } else { "property" };
+                let message = format!("the local {what} `{name}` is never used");
+                found.push((Rule::UnusedLocal, declaration.name_span, message));
+            }
+        }
+        for &(index, shadowed) in &module.shadows {
+            let declaration = &module.declarations[index];
+            let line = module.lines.line_col(module.declarations[shadowed].name_span.start).line + 1;
+            let message = format!("`{}` shadows the declaration of the same name on line {line}", declaration.name);
+            found.push((Rule::ShadowedName, declaration.name_span, message));
+        }
+        for &span in &module.constant_conditions {
+            found.push((Rule::ConstantCondition, span, "the condition of this `when` is always the same".to_string()));
+        }
+
+        if config.level(Rule::Deprecated) != Level::Off {
+            let references: Vec<_> = module.references.iter().map(|r| (r.span, r.target.clone())).collect();
+            for (span, target) in references {
+                let Some(site) = self.target(uri, &target) else { continue };
+                let Some(declaration) = self.declaration(&site) else { continue };
+                let Some(message) = &declaration.deprecated else { continue };
+                found.push((Rule::Deprecated, span, message.clone()));
+            }
+        }
+
+        let mut lints: Vec<Lint> = found
+            .into_iter()
+            .map(|(rule, span, message)| Lint { rule, level: config.level(rule), span, message })
+            .filter(|lint| lint.level != Level::Off)
+            .collect();
+        lints.sort_by_key(|lint| (lint.span.start, lint.span.end));
+        lints
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lints(source: &str, files: &[(&str, &'static str)], config: &LintConfig) -> Vec<(Rule, String)> {
+        let files: HashMap<String, &str> = files.iter().map(|&(uri, source)| (uri.to_string(), source)).collect();
+        let mut analyzer = Analyzer::with_reader(move |uri| files.get(uri).map(|source| source.to_string()));
+        analyzer.update("file:///a.pkl", source);
+        let lints = analyzer.lint("file:///a.pkl", config);
+        lints.into_iter().map(|lint| (lint.rule, lint.message)).collect()
+    }
+
+    #[test]
+    fn rules() {
+        let birds = "@Deprecated { since = \"0.2\"; message = \"use `make`\"; replaceWith = \"make()\" }\n\
+                     function create() = 1\n";
+        let source = "import \"birds.pkl\"\n\
+                      import \"pkl:math\"\n\
+                      local unused = 1\n\
+                      local used = 2\n\
+                      x = birds.create() + used\n\
+                      y { local n = 1; z = (n) -> n; when (1 + 1 == 2) { w = n } }\n";
+        assert_eq!(
+            lints(source, &[("file:///birds.pkl", birds)], &LintConfig::default()),
+            [
+                (Rule::UnusedImport, "the import `math` is never used".to_string()),
+                (Rule::UnusedLocal, "the local property `unused` is never used".to_string()),
+                (Rule::Deprecated, "`create` is deprecated since 0.2: use `make` (replace it with `make()`)".into()),
+                (Rule::ShadowedName, "`n` shadows the declaration of the same name on line 6".to_string()),
+                (Rule::ConstantCondition, "the condition of this `when` is always the same".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn configuration() {
+        let mut config = LintConfig::default();
+        config.parse_setting("unused-import=off").unwrap();
+        config.parse_setting("unused-local = error").unwrap();
+        assert_eq!(config.parse_setting("unused"), Err("expected `rule=level`, found `unused`".to_string()));
+        assert_eq!(config.parse_setting("unusual=off"), Err("unknown lint rule `unusual`".to_string()));
+        let source = "import \"pkl:math\"\nlocal function f() = 1\n";
+        let unused = (Rule::UnusedLocal, "the local method `f` is never used".to_string());
+        assert_eq!(lints(source, &[], &config), [unused]);
+        assert_eq!(config.level(Rule::UnusedLocal), Level::Error);
+    }
+}
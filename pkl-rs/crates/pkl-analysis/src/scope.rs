@@ -0,0 +1,675 @@
+//! Name resolution within a module: which declaration each name in it refers to.
+//!
+//! Names are looked up through the scopes around them, innermost first: the parameters of lets, lambdas, `for`
+//! generators, and methods, then the members of the object bodies and classes they are in, then the module's members
+//! and imports. Members of a scope are visible throughout it, wherever they are declared, since Pkl members can refer
+//! to each other in any order. Names that aren't found are taken to be members of the module that this one amends or
+//! extends, if any.
+//!
+//! Properties, methods, and types are in separate namespaces, so `foo` and `foo()` may refer to different members.
+
+use oxc_allocator::Allocator;
+use pkl_ast::uri::import_name;
+use pkl_ast::visit::{walk, Visit};
+use pkl_ast::{
+    Annotation, ClassDecl, ClassMember, ClassMethod, ClassProperty, Deprecation, DocComment, Expr, Ident, ModifierKind,
+    Modifiers, ModuleMember, ObjectBody, ObjectMember, ObjectProperty, Parameter, Type, TypeAlias, TypeParameter,
+    WhenGenerator,
+};
+use pkl_lexer::line_index::LineIndex;
+use pkl_lexer::token::Span;
+
+use crate::types::{infer, TypeOf};
+use crate::{resolve_uri, SymbolKind};
+
+/// A declaration of a name in a module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The whole declaration
+    pub span: Span,
+    pub name_span: Span,
+    /// The index of the declaration this one is inside of, like the class of a property or the method of a parameter
+    pub parent: Option<usize>,
+    /// The text of its doc comment, which is Markdown
+    pub doc: Option<String>,
+    /// How it reads, like `function greet(name: String): String`, for declarations other than properties and
+    /// parameters, which are shown with their types instead
+    pub(crate) signature: Option<String>,
+    /// Its type, or the type of what a method returns
+    pub(crate) ty: Option<TypeOf>,
+    /// Whether it's a `local` member
+    pub(crate) local: bool,
+    /// What its `@Deprecated` annotation says, as a message like ``"`create` is deprecated: use `make`"``
+    pub deprecated: Option<String>,
+}
+
+/// What a name refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Target {
+    /// A declaration of the same module, by its index
+    Local(usize),
+    /// A member of another module, which is only looked up when it's asked for
+    Member { uri: String, name: String, kind: SymbolKind },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Reference {
+    pub(crate) span: Span,
+    pub(crate) target: Target,
+}
+
+/// The declarations of a module and what its names refer to.
+#[derive(Debug, Clone)]
+pub struct ModuleAnalysis {
+    pub uri: String,
+    /// The name the module declares with a `module` clause, or else the name of its file without the extension
+    pub name: String,
+    /// The name of the `module` clause, or an empty span at the start of the module if it has none
+    pub name_span: Span,
+    /// The whole module
+    pub span: Span,
+    pub lines: LineIndex,
+    pub declarations: Vec<Declaration>,
+    pub(crate) references: Vec<Reference>,
+    /// The resolved URI of the module this one amends or extends
+    pub(crate) parent: Option<String>,
+    /// The parameters and `local` members that have the name of a parameter or `local` member of a scope around
+    /// them, by the indices of the declarations and of the ones they shadow
+    pub(crate) shadows: Vec<(usize, usize)>,
+    /// The conditions of `when` generators that don't depend on anything, like `when (true)`
+    pub(crate) constant_conditions: Vec<Span>,
+}
+
+impl ModuleAnalysis {
+    pub fn new(uri: &str, source: &str) -> Self {
+        let alloc = Allocator::default();
+        let module = pkl_parser::parse_module(&alloc, source).node;
+        let parent = module.header.extends.as_ref().and_then(|extends| resolve_uri(uri, extends.uri.value));
+        let mut binder = Binder {
+            uri,
+            source,
+            declarations: Vec::new(),
+            references: Vec::new(),
+            imports: Vec::new(),
+            scopes: Vec::new(),
+            parent: None,
+            extended: parent.is_some(),
+            shadows: Vec::new(),
+            constant_conditions: Vec::new(),
+        };
+
+        let mut scope = Vec::new();
+        for import in module.header.imports.iter() {
+            let (name, name_span) = match &import.alias {
+                Some(alias) => (alias.name, alias.span),
+                None => (import_name(import.uri.value), import.uri.span),
+            };
+            let index = binder.declare(name, SymbolKind::Import, import.span, name_span);
+            binder.declarations[index].signature = Some(binder.text(import.span));
+            if let Some(uri) = resolve_uri(uri, import.uri.value).filter(|_| !import.glob) {
+                binder.imports.push((index, uri));
+            }
+            scope.push(index);
+        }
+        let members: Vec<usize> = module.members.iter().map(|member| binder.declare_module_member(member)).collect();
+        scope.extend(&members);
+        binder.scopes.push(scope);
+
+        binder.visit_module_header(&module.header);
+        for (member, index) in module.members.iter().zip(members) {
+            binder.parent = Some(index);
+            binder.visit_module_member(member);
+        }
+
+        let (name, name_span) = match &module.header.name {
+            Some(name) => (name.parts.iter().map(|part| part.name).collect::<Vec<_>>().join("."), name.span),
+            None => (import_name(uri).to_string(), Span::new(0, 0)),
+        };
+        ModuleAnalysis {
+            uri: uri.to_string(),
+            name,
+            name_span,
+            span: Span::new(0, source.len()),
+            lines: LineIndex::new(source),
+            declarations: binder.declarations,
+            references: binder.references,
+            parent,
+            shadows: binder.shadows,
+            constant_conditions: binder.constant_conditions,
+        }
+    }
+
+    /// The member of the module named `name` of the namespace of `kind`, which other modules can refer to.
+    pub fn member(&self, name: &str, kind: SymbolKind) -> Option<usize> {
+        self.declarations.iter().position(|declaration| {
+            declaration.parent.is_none()
+                && declaration.kind != SymbolKind::Import
+                && declaration.name == name
+                && Namespace::of(declaration.kind) == Namespace::of(kind)
+        })
+    }
+}
+
+/// The namespaces that names are looked up in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Namespace {
+    Value,
+    Method,
+    Type,
+}
+
+impl Namespace {
+    fn of(kind: SymbolKind) -> Namespace {
+        match kind {
+            SymbolKind::Method => Namespace::Method,
+            SymbolKind::Class | SymbolKind::TypeAlias | SymbolKind::TypeParameter => Namespace::Type,
+            SymbolKind::Module | SymbolKind::Import | SymbolKind::Property | SymbolKind::Parameter => Namespace::Value,
+        }
+    }
+
+    fn includes(self, kind: SymbolKind) -> bool {
+        // classes are values too, as in `x is Bird` and `Bird.simpleName`, and imports qualify type names
+        Namespace::of(kind) == self
+            || self == Namespace::Value && kind == SymbolKind::Class
+            || self == Namespace::Type && kind == SymbolKind::Import
+    }
+
+    /// The kind of member of another module that a name of this namespace refers to.
+    fn member_kind(self) -> SymbolKind {
+        match self {
+            Namespace::Value => SymbolKind::Property,
+            Namespace::Method => SymbolKind::Method,
+            Namespace::Type => SymbolKind::Class,
+        }
+    }
+}
+
+struct Binder<'u> {
+    uri: &'u str,
+    source: &'u str,
+    declarations: Vec<Declaration>,
+    references: Vec<Reference>,
+    /// The resolved URIs of the imports, by the index of their declarations
+    imports: Vec<(usize, String)>,
+    /// The declarations that each scope around the node being visited binds, innermost last
+    scopes: Vec<Vec<usize>>,
+    /// The declaration being visited
+    parent: Option<usize>,
+    /// Whether the module amends or extends another, whose members it inherits
+    extended: bool,
+    shadows: Vec<(usize, usize)>,
+    constant_conditions: Vec<Span>,
+}
+
+impl Binder<'_> {
+    fn declare(&mut self, name: &str, kind: SymbolKind, span: Span, name_span: Span) -> usize {
+        let parent = self.parent;
+        let (doc, signature, ty, local, deprecated) = (None, None, None, false, None);
+        let name = name.to_string();
+        let declaration = Declaration { name, kind, span, name_span, parent, doc, signature, ty, local, deprecated };
+        self.declarations.push(declaration);
+        self.declarations.len() - 1
+    }
+
+    /// Declares a member of a module, class, or object, with its doc comment.
+    fn declare_member(&mut self, name: &Ident, kind: SymbolKind, span: Span, doc: &Option<DocComment>) -> usize {
+        let index = self.declare(name.name, kind, span, name.span);
+        self.declarations[index].doc = doc.as_ref().map(|doc| doc.lines.join("\n"));
+        index
+    }
+
+    /// Records whether the member declared at `index` is `local` and whether it's deprecated.
+    fn mark(&mut self, index: usize, modifiers: &Modifiers, annotations: &[Annotation]) {
+        let declaration = &mut self.declarations[index];
+        declaration.local = modifiers.has(ModifierKind::Local);
+        let deprecation = Deprecation::of(annotations);
+        declaration.deprecated = deprecation.map(|deprecation| deprecation.describe(&declaration.name));
+    }
+
+    fn declare_module_member(&mut self, member: &ModuleMember) -> usize {
+        match member {
+            ModuleMember::Class(class) => {
+                let index = self.declare_member(&class.name, SymbolKind::Class, class.span, &class.doc);
+                // the header of the class, up to its body
+                let header = Span::new(class.name.span.start, class.span.end);
+                let end = self.text(header).find('{').map_or(class.span.end, |body| header.start + body);
+                let signature = format!("class {}", self.text(Span::new(header.start, end)));
+                self.declarations[index].signature = Some(signature);
+                self.mark(index, &class.modifiers, &class.annotations);
+                index
+            }
+            ModuleMember::TypeAlias(alias) => {
+                let index = self.declare_member(&alias.name, SymbolKind::TypeAlias, alias.span, &alias.doc);
+                let signature = format!("typealias {}", self.text(Span::new(alias.name.span.start, alias.span.end)));
+                self.declarations[index].signature = Some(signature);
+                self.mark(index, &alias.modifiers, &alias.annotations);
+                index
+            }
+            ModuleMember::Property(property) => self.declare_class_property(property),
+            ModuleMember::Method(method) => self.declare_class_method(method),
+        }
+    }
+
+    fn declare_class_property(&mut self, property: &ClassProperty) -> usize {
+        let index = self.declare_member(&property.name, SymbolKind::Property, property.span, &property.doc);
+        self.mark(index, &property.modifiers, &property.annotations);
+        index
+    }
+
+    fn declare_class_method(&mut self, method: &ClassMethod) -> usize {
+        let index = self.declare_member(&method.name, SymbolKind::Method, method.span, &method.doc);
+        let end = method.body.as_ref().map_or(method.span.end, |body| body.span().start);
+        self.declarations[index].signature = Some(self.method_signature(&method.name, end));
+        self.mark(index, &method.modifiers, &method.annotations);
+        index
+    }
+
+    /// The signature of a method with the name `name` whose body starts at `end`.
+    fn method_signature(&self, name: &Ident, end: usize) -> String {
+        let header = self.text(Span::new(name.span.start, end));
+        format!("function {}", header.trim_end().trim_end_matches('=').trim_end())
+    }
+
+    fn declare_params<'p, 'a: 'p>(&mut self, params: impl IntoIterator<Item = &'p Parameter<'a>>) -> Vec<usize> {
+        let params = params.into_iter().filter_map(|param| param.name.as_ref().map(|name| (param, name)));
+        let params: Vec<_> = params.collect();
+        params
+            .into_iter()
+            .map(|(param, name)| {
+                let index = self.declare(name.name, SymbolKind::Parameter, param.span, name.span);
+                self.declarations[index].ty = param.ty.as_ref().map(|ty| TypeOf::Known(self.text(ty.span())));
+                index
+            })
+            .collect()
+    }
+
+    fn declare_type_params(&mut self, params: &[TypeParameter]) -> Vec<usize> {
+        let kind = SymbolKind::TypeParameter;
+        let params = params.iter().map(|param| {
+            let index = self.declare(param.name.name, kind, param.span, param.name.span);
+            self.declarations[index].signature = Some(self.text(param.span));
+            index
+        });
+        params.collect()
+    }
+
+    /// The source of a span, with each run of whitespace in it made a single space.
+    fn text(&self, span: Span) -> String {
+        let text = &self.source[span.start..span.end];
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// The declared type of the declaration being visited, or else the type of its value.
+    fn type_of(&self, ty: Option<&Type>, value: Option<&Expr>) -> Option<TypeOf> {
+        match (ty, value) {
+            (Some(ty), _) => Some(TypeOf::Known(self.text(ty.span()))),
+            (None, Some(value)) => infer(value, &|expr| self.target_of(expr), &|span| self.text(span)),
+            (None, None) => None,
+        }
+    }
+
+    /// Gives the declaration being visited a type.
+    fn set_type(&mut self, ty: Option<TypeOf>) {
+        let index = self.parent.expect("a member is visited inside its declaration");
+        self.declarations[index].ty = ty;
+    }
+
+    /// What the name that is `expr`, or the member name of it, refers to.
+    fn target_of(&self, expr: &Expr) -> Option<Target> {
+        match expr {
+            Expr::Ident(ident) => self.resolve_name(ident.name, Namespace::Value),
+            Expr::Member(member) => self.resolve_member(&member.receiver, member.name.name, Namespace::Value),
+            Expr::Call(call) => match &call.receiver {
+                Some(receiver) => self.resolve_member(receiver, call.name.name, Namespace::Method),
+                None => self.resolve_name(call.name.name, Namespace::Method),
+            },
+            _ => None,
+        }
+    }
+
+    /// Visits a node inside a scope of its own, which binds `scope`.
+    fn scoped(&mut self, scope: Vec<usize>, visit: impl FnOnce(&mut Self)) {
+        let shadowing = |declaration: &Declaration| declaration.local || declaration.kind == SymbolKind::Parameter;
+        for &index in &scope {
+            let declaration = &self.declarations[index];
+            if !shadowing(declaration) {
+                continue;
+            }
+            let shadowed = self.lookup(&declaration.name, Namespace::of(declaration.kind));
+            if let Some(shadowed) = shadowed.filter(|&shadowed| shadowing(&self.declarations[shadowed])) {
+                self.shadows.push((index, shadowed));
+            }
+        }
+        self.scopes.push(scope);
+        visit(self);
+        self.scopes.pop();
+    }
+
+    /// Visits a node as part of a declaration, whose index becomes the parent of the declarations inside it.
+    fn inside(&mut self, parent: usize, visit: impl FnOnce(&mut Self)) {
+        let outer = self.parent.replace(parent);
+        visit(self);
+        self.parent = outer;
+    }
+
+    fn lookup(&self, name: &str, namespace: Namespace) -> Option<usize> {
+        let mut scopes = self.scopes.iter().rev();
+        scopes.find_map(|scope| {
+            let declaration = |&index: &usize| &self.declarations[index];
+            scope.iter().copied().find(|index| {
+                let declaration = declaration(index);
+                declaration.name == name && namespace.includes(declaration.kind)
+            })
+        })
+    }
+
+    fn resolve_name(&self, name: &str, namespace: Namespace) -> Option<Target> {
+        match self.lookup(name, namespace) {
+            Some(index) => Some(Target::Local(index)),
+            None if self.extended => Some(inherited(self.uri, name, namespace)),
+            None => None,
+        }
+    }
+
+    /// Records what the unqualified name at `span` refers to.
+    fn refer(&mut self, name: &str, span: Span, namespace: Namespace) -> Option<usize> {
+        let target = self.resolve_name(name, namespace)?;
+        let local = match target {
+            Target::Local(index) => Some(index),
+            Target::Member { .. } => None,
+        };
+        self.references.push(Reference { span, target });
+        local
+    }
+
+    /// What the member name of the value of `receiver` refers to, if the receiver is an import or `module`.
+    fn resolve_member(&self, receiver: &Expr, name: &str, namespace: Namespace) -> Option<Target> {
+        match receiver {
+            Expr::Ident(ident) => {
+                let index = self.lookup(ident.name, Namespace::Value)?;
+                let (_, uri) = self.imports.iter().find(|(import, _)| *import == index)?;
+                Some(inherited(uri, name, namespace))
+            }
+            Expr::Module(_) => {
+                let scope = &self.scopes[0];
+                let found = scope.iter().copied().find(|&index| {
+                    let declaration = &self.declarations[index];
+                    declaration.name == name && Namespace::of(declaration.kind) == namespace
+                });
+                match found {
+                    Some(index) => Some(Target::Local(index)),
+                    None if self.extended => Some(inherited(self.uri, name, namespace)),
+                    None => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Records what the member name at `span` of the value of `receiver` refers to.
+    fn refer_member(&mut self, receiver: &Expr, name: &str, span: Span, namespace: Namespace) {
+        if let Some(target) = self.resolve_member(receiver, name, namespace) {
+            self.references.push(Reference { span, target });
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Binder<'_> {
+    fn visit_class(&mut self, class: &ClassDecl<'a>) {
+        let mut scope = self.declare_type_params(&class.type_params);
+        let members: Vec<usize> = class
+            .members
+            .iter()
+            .map(|member| match member {
+                ClassMember::Property(property) => self.declare_class_property(property),
+                ClassMember::Method(method) => self.declare_class_method(method),
+            })
+            .collect();
+        scope.extend(&members);
+
+        self.scoped(scope, |binder| {
+            for annotation in class.annotations.iter() {
+                binder.visit_annotation(annotation);
+            }
+            if let Some(extends) = &class.extends {
+                binder.visit_type(extends);
+            }
+            for (member, index) in class.members.iter().zip(members) {
+                binder.inside(index, |binder| binder.visit_class_member(member));
+            }
+        });
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty<'a>) {
+        self.set_type(self.type_of(property.ty.as_ref(), property.value.as_ref()));
+        walk::walk_class_property(self, property);
+    }
+
+    fn visit_class_method(&mut self, method: &ClassMethod<'a>) {
+        let mut scope = self.declare_type_params(&method.type_params);
+        scope.extend(self.declare_params(method.params.iter()));
+        self.scoped(scope, |binder| {
+            binder.set_type(binder.type_of(method.return_type.as_ref(), method.body.as_ref()));
+            walk::walk_class_method(binder, method);
+        });
+    }
+
+    fn visit_type_alias(&mut self, alias: &TypeAlias<'a>) {
+        let scope = self.declare_type_params(&alias.type_params);
+        self.scoped(scope, |binder| walk::walk_type_alias(binder, alias));
+    }
+
+    fn visit_object_body(&mut self, body: &ObjectBody<'a>) {
+        let mut scope = self.declare_params(body.params.iter());
+        let members: Vec<Option<usize>> = body
+            .members
+            .iter()
+            .map(|member| match member {
+                ObjectMember::Property(property) => {
+                    let index = self.declare_member(&property.name, SymbolKind::Property, property.span, &property.doc);
+                    self.mark(index, &property.modifiers, &[]);
+                    Some(index)
+                }
+                ObjectMember::Method(method) => {
+                    let index = self.declare_member(&method.name, SymbolKind::Method, method.span, &method.doc);
+                    let signature = self.method_signature(&method.name, method.body.span().start);
+                    self.declarations[index].signature = Some(signature);
+                    self.mark(index, &method.modifiers, &[]);
+                    Some(index)
+                }
+                _ => None,
+            })
+            .collect();
+        scope.extend(members.iter().flatten());
+
+        self.scoped(scope, |binder| {
+            for (member, index) in body.members.iter().zip(members) {
+                match (member, index) {
+                    (ObjectMember::Method(method), Some(index)) => binder.inside(index, |binder| {
+                        let scope = binder.declare_params(method.params.iter());
+                        binder.scoped(scope, |binder| {
+                            binder.set_type(binder.type_of(method.return_type.as_ref(), Some(&method.body)));
+                            walk::walk_object_method(binder, method);
+                        });
+                    }),
+                    (member, Some(index)) => binder.inside(index, |binder| binder.visit_object_member(member)),
+                    (member, None) => binder.visit_object_member(member),
+                }
+            }
+        });
+    }
+
+    fn visit_object_property(&mut self, property: &ObjectProperty<'a>) {
+        self.set_type(self.type_of(property.ty.as_ref(), property.value.as_ref()));
+        walk::walk_object_property(self, property);
+    }
+
+    fn visit_for_generator(&mut self, generator: &pkl_ast::ForGenerator<'a>) {
+        self.visit_expr(&generator.iterable);
+        for param in generator.key.iter().chain([&generator.value]) {
+            self.visit_parameter(param);
+        }
+        let scope = self.declare_params(generator.key.iter().chain([&generator.value]));
+        self.scoped(scope, |binder| binder.visit_object_body(&generator.body));
+    }
+
+    fn visit_when_generator(&mut self, generator: &WhenGenerator<'a>) {
+        if is_constant(&generator.condition) {
+            self.constant_conditions.push(generator.condition.span());
+        }
+        walk::walk_when_generator(self, generator);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Ident(ident) => {
+                self.refer(ident.name, ident.span, Namespace::Value);
+            }
+            Expr::Member(member) => {
+                self.visit_expr(&member.receiver);
+                self.refer_member(&member.receiver, member.name.name, member.name.span, Namespace::Value);
+            }
+            Expr::Call(call) => {
+                match &call.receiver {
+                    Some(receiver) => {
+                        self.visit_expr(receiver);
+                        self.refer_member(receiver, call.name.name, call.name.span, Namespace::Method);
+                    }
+                    None => {
+                        self.refer(call.name.name, call.name.span, Namespace::Method);
+                    }
+                }
+                for arg in call.args.iter() {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::Let(let_expr) => {
+                if let Some(ty) = &let_expr.param.ty {
+                    self.visit_type(ty);
+                }
+                self.visit_expr(&let_expr.value);
+                let ty = self.type_of(let_expr.param.ty.as_ref(), Some(&let_expr.value));
+                let scope = self.declare_params([&let_expr.param]);
+                if let Some(&index) = scope.first() {
+                    self.declarations[index].ty = ty;
+                }
+                self.scoped(scope, |binder| binder.visit_expr(&let_expr.body));
+            }
+            Expr::Lambda(lambda) => {
+                let scope = self.declare_params(lambda.params.iter());
+                self.scoped(scope, |binder| walk::walk_expr(binder, expr));
+            }
+            _ => walk::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_type(&mut self, ty: &Type<'a>) {
+        let Type::Named(named) = ty else { return walk::walk_type(self, ty) };
+        match named.name.parts.as_slice() {
+            [name] => {
+                self.refer(name.name, name.span, Namespace::Type);
+            }
+            [import, name] => {
+                if let Some(index) = self.refer(import.name, import.span, Namespace::Type) {
+                    if let Some((_, uri)) = self.imports.iter().find(|(import, _)| *import == index) {
+                        let target = inherited(uri, name.name, Namespace::Type);
+                        self.references.push(Reference { span: name.span, target });
+                    }
+                }
+            }
+            _ => {}
+        }
+        for arg in named.args.iter() {
+            self.visit_type(arg);
+        }
+    }
+}
+
+/// A reference to the member named `name` of the namespace `namespace` of the module at `uri`, or of the module it
+/// amends or extends if it's the module being analyzed.
+fn inherited(uri: &str, name: &str, namespace: Namespace) -> Target {
+    Target::Member { uri: uri.to_string(), name: name.to_string(), kind: namespace.member_kind() }
+}
+
+/// Whether an expression is made only of literals, so that it always has the same value.
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Null(_) | Expr::Bool(..) | Expr::Int(..) | Expr::Float(..) => true,
+        Expr::String(string) => string.as_constant().is_some(),
+        Expr::Unary(unary) => is_constant(&unary.operand),
+        Expr::Binary(binary) => is_constant(&binary.left) && is_constant(&binary.right),
+        Expr::Parenthesized(parenthesized) => is_constant(&parenthesized.expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The names each name in `source` refers to, as the text of the reference and where the declaration is.
+    fn references(source: &str) -> Vec<(&str, Option<usize>)> {
+        let analysis = ModuleAnalysis::new("file:///a.pkl", source);
+        let references = analysis.references.iter().map(|reference| {
+            let declaration = match &reference.target {
+                Target::Local(index) => Some(analysis.declarations[*index].name_span.start),
+                Target::Member { .. } => None,
+            };
+            (&source[reference.span.start..reference.span.end], declaration)
+        });
+        references.collect()
+    }
+
+    #[test]
+    fn scopes() {
+        let source = "x = 1\n\
+                      function f(x) = x\n\
+                      y = let (x = x) x\n\
+                      z { x = 2; w = x }\n\
+                      l = (x) -> f(x)\n";
+        let param = source.find("f(x)").unwrap() + 2;
+        let let_param = source.find("(x =").unwrap() + 1;
+        let body = source.find("x = 2").unwrap();
+        let lambda = source.find("(x) ->").unwrap() + 1;
+        assert_eq!(
+            references(source),
+            [
+                ("x", Some(param)),
+                ("x", Some(0)),
+                ("x", Some(let_param)),
+                ("x", Some(body)),
+                ("f", Some(source.find("f(").unwrap())),
+                ("x", Some(lambda)),
+            ]
+        );
+    }
+
+    #[test]
+    fn types_and_imports() {
+        let source = "import \"birds.pkl\" as b\n\
+                      class Box<T> {\n  value: T\n  other: Box<String>\n}\n\
+                      x: b.Bird = b.make()\n\
+                      y = module.x\n";
+        let analysis = ModuleAnalysis::new("file:///dir/a.pkl", source);
+        let references = analysis.references.iter();
+        let targets: Vec<_> = references.map(|r| (&source[r.span.start..r.span.end], &r.target)).collect();
+        let bird = inherited("file:///dir/birds.pkl", "Bird", Namespace::Type);
+        let make = inherited("file:///dir/birds.pkl", "make", Namespace::Method);
+        assert_eq!(
+            targets,
+            [
+                ("T", &Target::Local(4)),
+                ("Box", &Target::Local(1)),
+                ("b", &Target::Local(0)),
+                ("Bird", &bird),
+                ("b", &Target::Local(0)),
+                ("make", &make),
+                ("x", &Target::Local(2)),
+            ]
+        );
+        assert_eq!(analysis.member("Box", SymbolKind::Class), Some(1));
+        assert_eq!(analysis.member("x", SymbolKind::Property), Some(2));
+    }
+}
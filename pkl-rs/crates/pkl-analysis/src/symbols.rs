@@ -0,0 +1,185 @@
+//! The outline of a module, and an index of the symbols of many modules for finding them by name.
+
+use std::collections::HashMap;
+
+use pkl_lexer::token::Span;
+
+use crate::scope::ModuleAnalysis;
+use crate::SymbolKind;
+
+/// A declaration in the outline of a module, with the declarations inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The whole declaration
+    pub span: Span,
+    pub name_span: Span,
+    pub children: Vec<Symbol>,
+}
+
+impl ModuleAnalysis {
+    /// The classes, typealiases, properties, and methods of the module, in source order, with the members declared
+    /// inside each of them. Imports, parameters, and type parameters are left out.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut children: Vec<Vec<Symbol>> = vec![Vec::new(); self.declarations.len()];
+        let mut symbols = Vec::new();
+        // a declaration comes after the one it's inside of, so the children of each are done before it is
+        for (index, declaration) in self.declarations.iter().enumerate().rev() {
+            if !outlined(declaration.kind) {
+                continue;
+            }
+            let symbol = Symbol {
+                name: declaration.name.clone(),
+                kind: declaration.kind,
+                span: declaration.span,
+                name_span: declaration.name_span,
+                children: std::mem::take(&mut children[index]).into_iter().rev().collect(),
+            };
+            // members inside of a parameter, like those of a lambda's body, belong to what the parameter is of
+            let mut parent = declaration.parent;
+            while let Some(index) = parent.filter(|&index| !outlined(self.declarations[index].kind)) {
+                parent = self.declarations[index].parent;
+            }
+            match parent {
+                Some(parent) => children[parent].push(symbol),
+                None => symbols.push(symbol),
+            }
+        }
+        symbols.reverse();
+        symbols
+    }
+}
+
+fn outlined(kind: SymbolKind) -> bool {
+    matches!(kind, SymbolKind::Class | SymbolKind::TypeAlias | SymbolKind::Property | SymbolKind::Method)
+}
+
+/// A symbol that a module declares where other modules can refer to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The URI of the module that declares it
+    pub uri: String,
+    pub span: Span,
+    pub name_span: Span,
+    /// The name of the class or module it's a member of
+    pub container: Option<String>,
+}
+
+/// The modules, classes, typealiases, and the members of modules and classes that a set of modules declare, for
+/// finding them by name.
+///
+/// The symbols of each module are replaced whenever it changes, without touching those of the others.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    modules: HashMap<String, Vec<IndexedSymbol>>,
+}
+
+impl SymbolIndex {
+    /// Indexes the symbols of a module, replacing those it had.
+    pub fn update(&mut self, module: &ModuleAnalysis) {
+        let indexed = |symbol: &Symbol, container: &str| IndexedSymbol {
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            uri: module.uri.clone(),
+            span: symbol.span,
+            name_span: symbol.name_span,
+            container: Some(container.to_string()),
+        };
+        let mut symbols = vec![IndexedSymbol {
+            name: module.name.clone(),
+            kind: SymbolKind::Module,
+            uri: module.uri.clone(),
+            span: module.span,
+            name_span: module.name_span,
+            container: None,
+        }];
+        for symbol in module.symbols() {
+            symbols.push(indexed(&symbol, &module.name));
+            if symbol.kind == SymbolKind::Class {
+                symbols.extend(symbol.children.iter().map(|member| indexed(member, &symbol.name)));
+            }
+        }
+        self.modules.insert(module.uri.clone(), symbols);
+    }
+
+    pub fn remove(&mut self, uri: &str) {
+        self.modules.remove(uri);
+    }
+
+    /// The symbols whose names contain the characters of `query` in order, ignoring case, with the best matches
+    /// first: those that start with it, then those that contain it, then the rest.
+    pub fn search(&self, query: &str) -> Vec<&IndexedSymbol> {
+        let query = query.to_lowercase();
+        let mut found: Vec<(usize, &IndexedSymbol)> = self
+            .modules
+            .values()
+            .flatten()
+            .filter_map(|symbol| Some((rank(&symbol.name.to_lowercase(), &query)?, symbol)))
+            .collect();
+        found.sort_by(|(a_rank, a), (b_rank, b)| {
+            (a_rank, &a.name, &a.uri, a.span.start).cmp(&(b_rank, &b.name, &b.uri, b.span.start))
+        });
+        found.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+}
+
+/// How well `name` matches `query`, lower being better, if it does at all.
+fn rank(name: &str, query: &str) -> Option<usize> {
+    if name.starts_with(query) {
+        return Some(0);
+    }
+    if name.contains(query) {
+        return Some(1);
+    }
+    let mut chars = name.chars();
+    query.chars().all(|c| chars.any(|n| n == c)).then_some(2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outline(symbols: &[Symbol]) -> Vec<String> {
+        let outline = symbols.iter().map(|symbol| match outline(&symbol.children).as_slice() {
+            [] => symbol.name.clone(),
+            children => format!("{}({})", symbol.name, children.join(" ")),
+        });
+        outline.collect()
+    }
+
+    #[test]
+    fn outlines() {
+        let source = "import \"a.pkl\"\n\
+                      class Bird {\n  name: String\n  function greet(other) = \"hi\"\n}\n\
+                      typealias Name = String\n\
+                      bird { name = \"x\"; local f = (x) -> new { y = x } }\n\
+                      function make(n) = n\n";
+        let module = ModuleAnalysis::new("file:///birds.pkl", source);
+        assert_eq!(outline(&module.symbols()), ["Bird(name greet)", "Name", "bird(name f(y))", "make"]);
+    }
+
+    #[test]
+    fn searches_incrementally() {
+        let mut index = SymbolIndex::default();
+        index.update(&ModuleAnalysis::new("file:///a.pkl", "class Bird { wingspan: Int }\nbirds = 1\n"));
+        index.update(&ModuleAnalysis::new("file:///b.pkl", "module zoo.Animals\nbig = new { bird = 1 }\n"));
+        let search = |index: &SymbolIndex, query: &str| -> Vec<(String, Option<String>)> {
+            let found = index.search(query).into_iter();
+            found.map(|symbol| (symbol.name.clone(), symbol.container.clone())).collect()
+        };
+        let named = |name: &str, container: Option<&str>| (name.to_string(), container.map(str::to_string));
+
+        // the `bird` of the object isn't a member of the module
+        let found = [named("Bird", Some("a")), named("big", Some("zoo.Animals")), named("birds", Some("a"))];
+        assert_eq!(search(&index, "bi"), found);
+        assert_eq!(search(&index, "wsp"), [named("wingspan", Some("Bird"))]);
+        assert_eq!(search(&index, "zoo"), [named("zoo.Animals", None)]);
+
+        index.update(&ModuleAnalysis::new("file:///a.pkl", "birdie = 2\n"));
+        index.remove("file:///b.pkl");
+        assert_eq!(search(&index, "bi"), [named("birdie", Some("a"))]);
+    }
+}
@@ -0,0 +1,151 @@
+//! Lightweight type inference: the type of a value as far as it can be told from the expression alone, without
+//! evaluating it.
+//!
+//! Literals, `new`, and operators have the types they always have. A name has the type of the declaration it refers
+//! to, which is only looked up when it's asked for, since it may be in another module.
+
+use pkl_ast::{BinaryOp, Expr, UnaryOp};
+use pkl_lexer::token::Span;
+
+use crate::scope::Target;
+
+/// The type of a declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TypeOf {
+    /// A type as it's written, like `Listing<String>`
+    Known(String),
+    /// The type of the declaration a name refers to
+    Of(Target),
+}
+
+const DURATION_UNITS: [&str; 7] = ["ns", "us", "ms", "s", "min", "h", "d"];
+const DATA_SIZE_UNITS: [&str; 11] = ["b", "kb", "kib", "mb", "mib", "gb", "gib", "tb", "tib", "pb", "pib"];
+
+/// The classes of `pkl:base` whose instances are made by calling a method of the same name, like `List(1, 2)`.
+const CONSTRUCTORS: [&str; 5] = ["List", "Set", "Map", "Pair", "Regex"];
+
+/// The type of the value of `expr`, if it can be told. `target` is what a name, or the name of a member of an import,
+/// refers to, and `text` is the source of a span.
+pub(crate) fn infer(
+    expr: &Expr,
+    target: &dyn Fn(&Expr) -> Option<Target>,
+    text: &dyn Fn(Span) -> String,
+) -> Option<TypeOf> {
+    let known = |name: &str| Some(TypeOf::Known(name.to_string()));
+    let infer = |expr: &Expr| infer(expr, target, text);
+
+    match expr {
+        Expr::Null(_) => known("Null"),
+        Expr::Bool(..) => known("Boolean"),
+        Expr::Int(..) => known("Int"),
+        Expr::Float(..) => known("Float"),
+        Expr::String(_) => known("String"),
+        Expr::Member(member) if matches!(member.receiver, Expr::Int(..) | Expr::Float(..)) => {
+            if DURATION_UNITS.contains(&member.name.name) {
+                known("Duration")
+            } else if DATA_SIZE_UNITS.contains(&member.name.name) {
+                known("DataSize")
+            } else {
+                None
+            }
+        }
+        Expr::Call(call) if call.receiver.is_none() && CONSTRUCTORS.contains(&call.name.name) => known(call.name.name),
+        Expr::Ident(_) | Expr::Member(_) | Expr::Call(_) => target(expr).map(TypeOf::Of),
+        Expr::Unary(unary) => match unary.op {
+            UnaryOp::Not => known("Boolean"),
+            UnaryOp::Neg => infer(&unary.operand),
+        },
+        Expr::Binary(binary) => {
+            use BinaryOp::*;
+            match binary.op {
+                Lt | LtEq | Gt | GtEq | Eq | NotEq | And | Or => known("Boolean"),
+                Pipe | NullCoalesce => None,
+                op => {
+                    let (Some(TypeOf::Known(left)), Some(TypeOf::Known(right))) =
+                        (infer(&binary.left), infer(&binary.right))
+                    else {
+                        return None;
+                    };
+                    arithmetic(op, &left, &right).map(|ty| TypeOf::Known(ty.to_string()))
+                }
+            }
+        }
+        Expr::Is(_) => known("Boolean"),
+        Expr::As(test) => Some(TypeOf::Known(text(test.ty.span()))),
+        Expr::If(if_expr) => {
+            let then = infer(&if_expr.then)?;
+            (infer(&if_expr.otherwise).as_ref() == Some(&then)).then_some(then)
+        }
+        Expr::Lambda(lambda) => known(&format!("Function{}", lambda.params.len())),
+        Expr::New(new) => match &new.ty {
+            Some(ty) => Some(TypeOf::Known(text(ty.span()))),
+            None => known("Dynamic"),
+        },
+        Expr::Amend(amend) => infer(&amend.parent),
+        Expr::NonNull(non_null) => match infer(&non_null.operand)? {
+            TypeOf::Known(ty) => Some(TypeOf::Known(ty.trim_end_matches('?').to_string())),
+            ty => Some(ty),
+        },
+        Expr::Throw(_) => known("nothing"),
+        Expr::Trace(keyword) => infer(&keyword.value),
+        Expr::Parenthesized(parenthesized) => infer(&parenthesized.expr),
+        _ => None,
+    }
+}
+
+/// The type of the result of an arithmetic operator on values of the types `left` and `right`.
+fn arithmetic<'t>(op: BinaryOp, left: &'t str, right: &'t str) -> Option<&'t str> {
+    let number = |ty: &str| matches!(ty, "Int" | "Float");
+    let quantity = |ty: &str| matches!(ty, "Duration" | "DataSize");
+    match (op, left, right) {
+        (BinaryOp::Add, "String", "String") => Some("String"),
+        (BinaryOp::Div, "Int", "Int") => Some("Float"),
+        (BinaryOp::IntDiv, "Int", "Int") => Some("Int"),
+        (_, left, right) if number(left) && number(right) => Some(if left == right { left } else { "Float" }),
+        (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Rem, left, right) if quantity(left) && left == right => Some(left),
+        (BinaryOp::Div, left, right) if quantity(left) && left == right => Some("Float"),
+        (BinaryOp::Mul | BinaryOp::Div, left, right) if quantity(left) && number(right) => Some(left),
+        (BinaryOp::Mul, left, right) if number(left) && quantity(right) => Some(right),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+
+    use super::*;
+
+    fn infer_expr(source: &str) -> Option<String> {
+        let alloc = Allocator::default();
+        let expr = pkl_parser::parse_expr(&alloc, source).node;
+        let text = |span: Span| source[span.start..span.end].to_string();
+        match infer(&expr, &|_| None, &text)? {
+            TypeOf::Known(ty) => Some(ty),
+            TypeOf::Of(_) => None,
+        }
+    }
+
+    #[test]
+    fn infers_from_expressions() {
+        let cases = [
+            ("1 + 2", Some("Int")),
+            ("1 / 2", Some("Float")),
+            ("1 + 2.5", Some("Float")),
+            ("\"a\" + \"b\"", Some("String")),
+            ("3.min * 2", Some("Duration")),
+            ("5.mb", Some("DataSize")),
+            ("x < 1", Some("Boolean")),
+            ("if (x) 1 else 2", Some("Int")),
+            ("if (x) 1 else \"a\"", None),
+            ("new Listing<String> {}", Some("Listing<String>")),
+            ("(x) -> x", Some("Function1")),
+            ("List(1, 2)", Some("List")),
+            ("x as Int?", Some("Int?")),
+            ("x", None),
+        ];
+        for (source, ty) in cases {
+            assert_eq!(infer_expr(source).as_deref(), ty, "{source}");
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use oxc_allocator::{Allocator, Box, Vec};
+
+/// Allocates AST nodes in an arena.
+#[derive(Clone, Copy)]
+pub struct AstBuilder<'a> {
+    pub alloc: &'a Allocator,
+}
+
+impl<'a> AstBuilder<'a> {
+    pub fn new(alloc: &'a Allocator) -> Self {
+        AstBuilder { alloc }
+    }
+
+    #[inline]
+    pub fn boxed<T>(&self, value: T) -> Box<'a, T> {
+        Box(self.alloc.alloc(value))
+    }
+
+    #[inline]
+    pub fn vec<T>(&self) -> Vec<'a, T> {
+        Vec::new_in(self.alloc)
+    }
+
+    #[inline]
+    pub fn vec_from_iter<T>(&self, iter: impl IntoIterator<Item = T>) -> Vec<'a, T> {
+        Vec::from_iter_in(iter, self.alloc)
+    }
+
+    /// Copies a string into the arena.
+    #[inline]
+    pub fn str(&self, value: &str) -> &'a str {
+        self.alloc.alloc_str(value)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Annotation, DocComment, Expr, Ident, Modifiers, ObjectBody, QualifiedName, Span, StringConstant, Type, Vec};
+
+/// A parsed `.pkl` file.
+#[derive(Debug)]
+pub struct Module<'a> {
+    pub span: Span,
+    pub header: ModuleHeader<'a>,
+    pub members: Vec<'a, ModuleMember<'a>>,
+}
+
+/// Everything that precedes a module's members: its name, the module it amends or extends, and its imports.
+///
+/// This is all a resolver needs to know about a module's dependencies.
+#[derive(Debug)]
+pub struct ModuleHeader<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub annotations: Vec<'a, Annotation<'a>>,
+    pub modifiers: Modifiers<'a>,
+    /// The `module com.example.Foo` clause
+    pub name: Option<QualifiedName<'a>>,
+    pub extends: Option<ModuleExtends<'a>>,
+    pub imports: Vec<'a, Import<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtendsKind {
+    Amends,
+    Extends,
+}
+
+/// `amends "..."` or `extends "..."`
+#[derive(Debug)]
+pub struct ModuleExtends<'a> {
+    pub span: Span,
+    pub kind: ExtendsKind,
+    pub uri: StringConstant<'a>,
+}
+
+/// `import "..."`, `import* "..."`, optionally followed by `as alias`
+#[derive(Debug)]
+pub struct Import<'a> {
+    pub span: Span,
+    /// Whether this is an `import*` of every module matching a glob pattern
+    pub glob: bool,
+    pub uri: StringConstant<'a>,
+    pub alias: Option<Ident<'a>>,
+}
+
+#[derive(Debug)]
+pub enum ModuleMember<'a> {
+    Class(ClassDecl<'a>),
+    TypeAlias(TypeAlias<'a>),
+    Property(ClassProperty<'a>),
+    Method(ClassMethod<'a>),
+}
+
+impl ModuleMember<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            ModuleMember::Class(class) => class.span,
+            ModuleMember::TypeAlias(alias) => alias.span,
+            ModuleMember::Property(property) => property.span,
+            ModuleMember::Method(method) => method.span,
+        }
+    }
+}
+
+/// `class Name<T> extends Parent { ... }`
+#[derive(Debug)]
+pub struct ClassDecl<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub annotations: Vec<'a, Annotation<'a>>,
+    pub modifiers: Modifiers<'a>,
+    pub name: Ident<'a>,
+    pub type_params: Vec<'a, TypeParameter<'a>>,
+    pub extends: Option<Type<'a>>,
+    pub members: Vec<'a, ClassMember<'a>>,
+}
+
+#[derive(Debug)]
+pub enum ClassMember<'a> {
+    Property(ClassProperty<'a>),
+    Method(ClassMethod<'a>),
+}
+
+impl ClassMember<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            ClassMember::Property(property) => property.span,
+            ClassMember::Method(method) => method.span,
+        }
+    }
+}
+
+/// A property of a class or module: `name: Type`, `name: Type = value`, `name = value`, or `name { ... }`.
+#[derive(Debug)]
+pub struct ClassProperty<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub annotations: Vec<'a, Annotation<'a>>,
+    pub modifiers: Modifiers<'a>,
+    pub name: Ident<'a>,
+    pub ty: Option<Type<'a>>,
+    pub value: Option<Expr<'a>>,
+    /// Object bodies amending the inherited value, as in `name { ... }`
+    pub bodies: Vec<'a, ObjectBody<'a>>,
+}
+
+/// `function name<T>(params): Type = body`
+#[derive(Debug)]
+pub struct ClassMethod<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub annotations: Vec<'a, Annotation<'a>>,
+    pub modifiers: Modifiers<'a>,
+    pub name: Ident<'a>,
+    pub type_params: Vec<'a, TypeParameter<'a>>,
+    pub params: Vec<'a, Parameter<'a>>,
+    pub return_type: Option<Type<'a>>,
+    /// Missing for `external` and `abstract` methods
+    pub body: Option<Expr<'a>>,
+}
+
+/// `typealias Name<T> = Type`
+#[derive(Debug)]
+pub struct TypeAlias<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub annotations: Vec<'a, Annotation<'a>>,
+    pub modifiers: Modifiers<'a>,
+    pub name: Ident<'a>,
+    pub type_params: Vec<'a, TypeParameter<'a>>,
+    pub ty: Type<'a>,
+}
+
+/// A parameter of a method, lambda, `let`, or `for`: `name` or `name: Type`.
+///
+/// A `_` parameter is unnamed, so its `name` is `None`.
+#[derive(Debug)]
+pub struct Parameter<'a> {
+    pub span: Span,
+    pub name: Option<Ident<'a>>,
+    pub ty: Option<Type<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variance {
+    In,
+    Out,
+}
+
+/// A generic type parameter, like the `out T` of `class Box<out T>`.
+#[derive(Debug)]
+pub struct TypeParameter<'a> {
+    pub span: Span,
+    pub variance: Option<Variance>,
+    pub name: Ident<'a>,
+}
@@ -0,0 +1,361 @@
+use crate::{Box, Ident, ObjectBody, Parameter, Span, StringConstant, Type, Vec};
+
+#[derive(Debug)]
+pub enum Expr<'a> {
+    /// `null`
+    Null(Span),
+    /// `true` or `false`
+    Bool(Span, bool),
+    Int(Span, i64),
+    Float(Span, f64),
+    String(Box<'a, StringLiteral<'a>>),
+    /// `this`
+    This(Span),
+    /// `outer`
+    Outer(Span),
+    /// `module`
+    Module(Span),
+    /// An unqualified name like `foo`, resolved against the enclosing scopes
+    Ident(Ident<'a>),
+    /// `receiver.name` or `receiver?.name`
+    Member(Box<'a, MemberExpr<'a>>),
+    /// `name(args)`, `receiver.name(args)`, or `receiver?.name(args)`
+    Call(Box<'a, CallExpr<'a>>),
+    /// `super.name` or `super.name(args)`
+    Super(Box<'a, SuperExpr<'a>>),
+    /// `receiver[index]`, or `super[index]` if the receiver is `None`
+    Subscript(Box<'a, SubscriptExpr<'a>>),
+    /// `-operand` or `!operand`
+    Unary(Box<'a, UnaryExpr<'a>>),
+    /// `operand!!`
+    NonNull(Box<'a, NonNullExpr<'a>>),
+    Binary(Box<'a, BinaryExpr<'a>>),
+    /// `value is Type`
+    Is(Box<'a, TypeTestExpr<'a>>),
+    /// `value as Type`
+    As(Box<'a, TypeTestExpr<'a>>),
+    /// `if (condition) then else otherwise`
+    If(Box<'a, IfExpr<'a>>),
+    /// `let (name = value) body`
+    Let(Box<'a, LetExpr<'a>>),
+    /// `(params) -> body`
+    Lambda(Box<'a, LambdaExpr<'a>>),
+    /// `new Type { ... }` or `new { ... }`
+    New(Box<'a, NewExpr<'a>>),
+    /// `parent { ... }`, creating an object that amends `parent`
+    Amend(Box<'a, AmendExpr<'a>>),
+    /// `throw(message)`
+    Throw(Box<'a, UnaryKeywordExpr<'a>>),
+    /// `trace(value)`
+    Trace(Box<'a, UnaryKeywordExpr<'a>>),
+    /// `import("...")` or `import*("...")`
+    Import(Box<'a, ImportExpr<'a>>),
+    /// `read(uri)`, `read?(uri)`, or `read*(uri)`
+    Read(Box<'a, ReadExpr<'a>>),
+    /// `(expr)`
+    Parenthesized(Box<'a, ParenthesizedExpr<'a>>),
+    /// An expression that couldn't be parsed. The parser has reported why.
+    Error(Span),
+}
+
+impl Expr<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Null(span)
+            | Expr::Bool(span, _)
+            | Expr::Int(span, _)
+            | Expr::Float(span, _)
+            | Expr::This(span)
+            | Expr::Outer(span)
+            | Expr::Module(span)
+            | Expr::Error(span) => *span,
+            Expr::String(expr) => expr.span,
+            Expr::Ident(ident) => ident.span,
+            Expr::Member(expr) => expr.span,
+            Expr::Call(expr) => expr.span,
+            Expr::Super(expr) => expr.span,
+            Expr::Subscript(expr) => expr.span,
+            Expr::Unary(expr) => expr.span,
+            Expr::NonNull(expr) => expr.span,
+            Expr::Binary(expr) => expr.span,
+            Expr::Is(expr) | Expr::As(expr) => expr.span,
+            Expr::If(expr) => expr.span,
+            Expr::Let(expr) => expr.span,
+            Expr::Lambda(expr) => expr.span,
+            Expr::New(expr) => expr.span,
+            Expr::Amend(expr) => expr.span,
+            Expr::Throw(expr) | Expr::Trace(expr) => expr.span,
+            Expr::Import(expr) => expr.span,
+            Expr::Read(expr) => expr.span,
+            Expr::Parenthesized(expr) => expr.span,
+        }
+    }
+}
+
+/// A string literal, split into text and interpolations.
+#[derive(Debug)]
+pub struct StringLiteral<'a> {
+    pub span: Span,
+    pub multiline: bool,
+    pub parts: Vec<'a, StringPart<'a>>,
+}
+
+impl<'a> StringLiteral<'a> {
+    /// The content of the string if it has no interpolations.
+    pub fn as_constant(&self) -> Option<&'a str> {
+        match self.parts.as_slice() {
+            [] => Some(""),
+            [StringPart::Text(_, text)] => Some(text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StringPart<'a> {
+    /// Literal text, with escapes replaced and (for multiline strings) indentation stripped
+    Text(Span, &'a str),
+    /// `\(expr)`
+    Interpolation(Expr<'a>),
+}
+
+#[derive(Debug)]
+pub struct MemberExpr<'a> {
+    pub span: Span,
+    pub receiver: Expr<'a>,
+    pub name: Ident<'a>,
+    /// Whether this is `?.`, which yields `null` for a `null` receiver
+    pub null_safe: bool,
+}
+
+#[derive(Debug)]
+pub struct CallExpr<'a> {
+    pub span: Span,
+    /// `None` for unqualified calls, which are resolved against the enclosing scopes
+    pub receiver: Option<Expr<'a>>,
+    pub name: Ident<'a>,
+    pub args: Vec<'a, Expr<'a>>,
+    pub null_safe: bool,
+}
+
+#[derive(Debug)]
+pub struct SuperExpr<'a> {
+    pub span: Span,
+    pub name: Ident<'a>,
+    /// The arguments, if this is a method call
+    pub args: Option<Vec<'a, Expr<'a>>>,
+}
+
+#[derive(Debug)]
+pub struct SubscriptExpr<'a> {
+    pub span: Span,
+    pub receiver: Option<Expr<'a>>,
+    pub index: Expr<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// `-`
+    Neg,
+    /// `!`
+    Not,
+}
+
+#[derive(Debug)]
+pub struct UnaryExpr<'a> {
+    pub span: Span,
+    pub op: UnaryOp,
+    pub operand: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct NonNullExpr<'a> {
+    pub span: Span,
+    pub operand: Expr<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    /// `**`
+    Pow,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `~/`
+    IntDiv,
+    /// `%`
+    Rem,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `<`
+    Lt,
+    /// `<=`
+    LtEq,
+    /// `>`
+    Gt,
+    /// `>=`
+    GtEq,
+    /// `==`
+    Eq,
+    /// `!=`
+    NotEq,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+    /// `|>`
+    Pipe,
+    /// `??`
+    NullCoalesce,
+}
+
+impl BinaryOp {
+    /// The operator as written in source.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryOp::Pow => "**",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::IntDiv => "~/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Lt => "<",
+            BinaryOp::LtEq => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::GtEq => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Pipe => "|>",
+            BinaryOp::NullCoalesce => "??",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BinaryExpr<'a> {
+    pub span: Span,
+    pub op: BinaryOp,
+    pub left: Expr<'a>,
+    pub right: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct TypeTestExpr<'a> {
+    pub span: Span,
+    pub value: Expr<'a>,
+    pub ty: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct IfExpr<'a> {
+    pub span: Span,
+    pub condition: Expr<'a>,
+    pub then: Expr<'a>,
+    pub otherwise: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct LetExpr<'a> {
+    pub span: Span,
+    pub param: Parameter<'a>,
+    pub value: Expr<'a>,
+    pub body: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct LambdaExpr<'a> {
+    pub span: Span,
+    pub params: Vec<'a, Parameter<'a>>,
+    pub body: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct NewExpr<'a> {
+    pub span: Span,
+    /// `None` for `new { ... }`, whose type is inferred from the context
+    pub ty: Option<Type<'a>>,
+    pub body: ObjectBody<'a>,
+}
+
+#[derive(Debug)]
+pub struct AmendExpr<'a> {
+    pub span: Span,
+    pub parent: Expr<'a>,
+    pub body: ObjectBody<'a>,
+}
+
+#[derive(Debug)]
+pub struct UnaryKeywordExpr<'a> {
+    pub span: Span,
+    pub value: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImportExpr<'a> {
+    pub span: Span,
+    pub glob: bool,
+    pub uri: StringConstant<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadKind {
+    /// `read`, which fails if the resource doesn't exist
+    Read,
+    /// `read?`, which yields `null` if the resource doesn't exist
+    ReadOrNull,
+    /// `read*`, which reads every resource matching a glob pattern
+    ReadGlob,
+}
+
+#[derive(Debug)]
+pub struct ReadExpr<'a> {
+    pub span: Span,
+    pub kind: ReadKind,
+    pub uri: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct ParenthesizedExpr<'a> {
+    pub span: Span,
+    pub expr: Expr<'a>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Allocator, AstBuilder};
+
+    #[test]
+    fn spans_of_boxed_nodes() {
+        let alloc = Allocator::default();
+        let ast = AstBuilder::new(&alloc);
+
+        let sum = Expr::Binary(ast.boxed(BinaryExpr {
+            span: Span::new(0, 5),
+            op: BinaryOp::Add,
+            left: Expr::Int(Span::new(0, 1), 1),
+            right: Expr::Int(Span::new(4, 5), 2),
+        }));
+
+        assert_eq!(sum.span(), Span::new(0, 5));
+        let Expr::Binary(binary) = &sum else { unreachable!() };
+        assert_eq!(binary.right.span(), Span::new(4, 5));
+    }
+
+    #[test]
+    fn constant_strings() {
+        let alloc = Allocator::default();
+        let ast = AstBuilder::new(&alloc);
+        let mut parts = ast.vec();
+        parts.push(StringPart::Text(Span::new(1, 4), "abc"));
+
+        let literal = StringLiteral { span: Span::new(0, 5), multiline: false, parts };
+        assert_eq!(literal.as_constant(), Some("abc"));
+    }
+}
@@ -0,0 +1,169 @@
+//! The syntax tree of a Pkl module.
+//!
+//! Nodes are allocated in an [`Allocator`] and borrow their names and string contents from it (or from the source
+//! text), so a whole tree is freed at once by dropping the allocator. Since arena memory is never dropped, nodes only
+//! hold arena [`Box`]es and [`Vec`]s and `&str`s, never types with their own heap allocations.
+//!
+//! Every node carries the [`Span`] of the source text it was parsed from.
+
+mod builder;
+mod decl;
+mod expr;
+mod object;
+mod types;
+pub mod uri;
+pub mod visit;
+
+pub use builder::AstBuilder;
+pub use decl::*;
+pub use expr::*;
+pub use object::*;
+pub use oxc_allocator::{Allocator, Box, Vec};
+pub use pkl_lexer::token::Span;
+pub use types::*;
+
+/// A name, such as a property, class, or parameter name.
+///
+/// Quoted identifiers are stored without their backticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ident<'a> {
+    pub span: Span,
+    pub name: &'a str,
+}
+
+/// A dot-separated name like `com.example.Foo` or `base.Listing`.
+#[derive(Debug)]
+pub struct QualifiedName<'a> {
+    pub span: Span,
+    pub parts: Vec<'a, Ident<'a>>,
+}
+
+/// A string literal without interpolations, as used for module URIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringConstant<'a> {
+    pub span: Span,
+    pub value: &'a str,
+}
+
+/// The lines of a `///` doc comment, without the slashes.
+#[derive(Debug)]
+pub struct DocComment<'a> {
+    pub span: Span,
+    pub lines: Vec<'a, &'a str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierKind {
+    Abstract,
+    Open,
+    External,
+    Local,
+    Hidden,
+    Fixed,
+    Const,
+}
+
+impl ModifierKind {
+    /// The keyword the modifier is written as.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModifierKind::Abstract => "abstract",
+            ModifierKind::Open => "open",
+            ModifierKind::External => "external",
+            ModifierKind::Local => "local",
+            ModifierKind::Hidden => "hidden",
+            ModifierKind::Fixed => "fixed",
+            ModifierKind::Const => "const",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modifier {
+    pub span: Span,
+    pub kind: ModifierKind,
+}
+
+/// The modifiers of a declaration, in source order.
+#[derive(Debug)]
+pub struct Modifiers<'a>(pub Vec<'a, Modifier>);
+
+impl<'a> Modifiers<'a> {
+    pub fn has(&self, kind: ModifierKind) -> bool {
+        self.0.iter().any(|modifier| modifier.kind == kind)
+    }
+
+    /// The modifier of the given kind, if present, e.g. to point a diagnostic at it.
+    pub fn get(&self, kind: ModifierKind) -> Option<&Modifier> {
+        self.0.iter().find(|modifier| modifier.kind == kind)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// `@Name` or `@Name { ... }`, attached to the declaration that follows it.
+#[derive(Debug)]
+pub struct Annotation<'a> {
+    pub span: Span,
+    pub name: QualifiedName<'a>,
+    pub body: Option<ObjectBody<'a>>,
+}
+
+impl Annotation<'_> {
+    pub fn is_deprecated(&self) -> bool {
+        self.name.parts.last().is_some_and(|name| name.name == "Deprecated")
+    }
+}
+
+/// What a `@Deprecated` annotation says about the member it's on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deprecation<'a> {
+    pub message: Option<&'a str>,
+    /// The version that deprecated the member
+    pub since: Option<&'a str>,
+    /// The code to use instead of the member
+    pub replace_with: Option<&'a str>,
+}
+
+impl<'a> Deprecation<'a> {
+    /// The deprecation of a member with `annotations`, if one of them is `@Deprecated`. Its properties are read if
+    /// they're strings without interpolation.
+    pub fn of(annotations: &[Annotation<'a>]) -> Option<Self> {
+        annotations.iter().find(|annotation| annotation.is_deprecated()).map(Deprecation::read)
+    }
+
+    /// What a `@Deprecated` annotation says.
+    pub fn read(annotation: &Annotation<'a>) -> Self {
+        let mut deprecation = Deprecation::default();
+        for member in annotation.body.iter().flat_map(|body| body.members.iter()) {
+            let ObjectMember::Property(property) = member else { continue };
+            let Some(Expr::String(string)) = &property.value else { continue };
+            let field = match property.name.name {
+                "message" => &mut deprecation.message,
+                "since" => &mut deprecation.since,
+                "replaceWith" => &mut deprecation.replace_with,
+                _ => continue,
+            };
+            *field = string.as_constant();
+        }
+        deprecation
+    }
+
+    /// A message saying that the member `name` is deprecated, with what the annotation says about it, like
+    /// ``"`create` is deprecated since 0.26: use `make` (replace it with `make()`)"``.
+    pub fn describe(&self, name: &str) -> String {
+        let mut text = format!("`{name}` is deprecated");
+        if let Some(since) = self.since {
+            text.push_str(&format!(" since {since}"));
+        }
+        if let Some(message) = self.message.filter(|message| !message.is_empty()) {
+            text.push_str(&format!(": {message}"));
+        }
+        if let Some(replacement) = self.replace_with {
+            text.push_str(&format!(" (replace it with `{replacement}`)"));
+        }
+        text
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{DocComment, Expr, Ident, Modifiers, Parameter, Span, Type, Vec};
+
+/// `{ members }`, used by `new`, amend expressions, and amending properties.
+///
+/// Bodies of objects with a default function may declare parameters: `default { key -> ... }`.
+#[derive(Debug)]
+pub struct ObjectBody<'a> {
+    pub span: Span,
+    pub params: Vec<'a, Parameter<'a>>,
+    pub members: Vec<'a, ObjectMember<'a>>,
+}
+
+#[derive(Debug)]
+pub enum ObjectMember<'a> {
+    /// `name = value`, `name { ... }`, or a `local` property
+    Property(ObjectProperty<'a>),
+    /// A `local function` inside an object body
+    Method(ObjectMethod<'a>),
+    /// `[key] = value` or `[key] { ... }`
+    Entry(ObjectEntry<'a>),
+    /// A bare expression, which is an element of a `Listing` or `Dynamic`
+    Element(ObjectElement<'a>),
+    /// `[[predicate]] = value` or `[[predicate]] { ... }`, amending every member for which the predicate holds
+    MemberPredicate(ObjectMemberPredicate<'a>),
+    /// `...expr` or `...?expr`
+    Spread(ObjectSpread<'a>),
+    /// `for (key, value in iterable) { ... }`
+    For(ForGenerator<'a>),
+    /// `when (condition) { ... } else { ... }`
+    When(WhenGenerator<'a>),
+}
+
+impl ObjectMember<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            ObjectMember::Property(member) => member.span,
+            ObjectMember::Method(member) => member.span,
+            ObjectMember::Entry(member) => member.span,
+            ObjectMember::Element(member) => member.span,
+            ObjectMember::MemberPredicate(member) => member.span,
+            ObjectMember::Spread(member) => member.span,
+            ObjectMember::For(member) => member.span,
+            ObjectMember::When(member) => member.span,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ObjectProperty<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub modifiers: Modifiers<'a>,
+    pub name: Ident<'a>,
+    /// Only `local` properties can declare a type
+    pub ty: Option<Type<'a>>,
+    pub value: Option<Expr<'a>>,
+    pub bodies: Vec<'a, ObjectBody<'a>>,
+}
+
+#[derive(Debug)]
+pub struct ObjectMethod<'a> {
+    pub span: Span,
+    pub doc: Option<DocComment<'a>>,
+    pub modifiers: Modifiers<'a>,
+    pub name: Ident<'a>,
+    pub params: Vec<'a, Parameter<'a>>,
+    pub return_type: Option<Type<'a>>,
+    pub body: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct ObjectEntry<'a> {
+    pub span: Span,
+    pub key: Expr<'a>,
+    pub value: Option<Expr<'a>>,
+    pub bodies: Vec<'a, ObjectBody<'a>>,
+}
+
+#[derive(Debug)]
+pub struct ObjectElement<'a> {
+    pub span: Span,
+    pub value: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct ObjectMemberPredicate<'a> {
+    pub span: Span,
+    pub predicate: Expr<'a>,
+    pub value: Option<Expr<'a>>,
+    pub bodies: Vec<'a, ObjectBody<'a>>,
+}
+
+#[derive(Debug)]
+pub struct ObjectSpread<'a> {
+    pub span: Span,
+    /// Whether this is `...?`, which spreads nothing if the value is `null`
+    pub nullable: bool,
+    pub value: Expr<'a>,
+}
+
+/// `for (value in iterable) { ... }` or `for (key, value in iterable) { ... }`
+#[derive(Debug)]
+pub struct ForGenerator<'a> {
+    pub span: Span,
+    pub key: Option<Parameter<'a>>,
+    pub value: Parameter<'a>,
+    pub iterable: Expr<'a>,
+    pub body: ObjectBody<'a>,
+}
+
+#[derive(Debug)]
+pub struct WhenGenerator<'a> {
+    pub span: Span,
+    pub condition: Expr<'a>,
+    pub body: ObjectBody<'a>,
+    pub else_body: Option<ObjectBody<'a>>,
+}
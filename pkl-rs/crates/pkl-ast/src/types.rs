@@ -0,0 +1,83 @@
+use crate::{Box, Expr, QualifiedName, Span, StringConstant, Vec};
+
+/// A type, as written in annotations like `name: Type`, `is Type`, and `typealias`.
+#[derive(Debug)]
+pub enum Type<'a> {
+    /// `unknown`
+    Unknown(Span),
+    /// `nothing`
+    Nothing(Span),
+    /// `module`, the type of the enclosing module
+    Module(Span),
+    /// `"literal"`, which only admits that exact string
+    StringLiteral(StringConstant<'a>),
+    /// `Name` or `Name<Args>`
+    Named(Box<'a, NamedType<'a>>),
+    /// `Type?`
+    Nullable(Box<'a, NullableType<'a>>),
+    /// `A | B | *C`
+    Union(Box<'a, UnionType<'a>>),
+    /// `(A, B) -> C`
+    Function(Box<'a, FunctionType<'a>>),
+    /// `Type(constraint, ...)`
+    Constrained(Box<'a, ConstrainedType<'a>>),
+    /// `(Type)`
+    Parenthesized(Box<'a, ParenthesizedType<'a>>),
+}
+
+impl Type<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Type::Unknown(span) | Type::Nothing(span) | Type::Module(span) => *span,
+            Type::StringLiteral(literal) => literal.span,
+            Type::Named(ty) => ty.span,
+            Type::Nullable(ty) => ty.span,
+            Type::Union(ty) => ty.span,
+            Type::Function(ty) => ty.span,
+            Type::Constrained(ty) => ty.span,
+            Type::Parenthesized(ty) => ty.span,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NamedType<'a> {
+    pub span: Span,
+    pub name: QualifiedName<'a>,
+    pub args: Vec<'a, Type<'a>>,
+}
+
+#[derive(Debug)]
+pub struct NullableType<'a> {
+    pub span: Span,
+    pub inner: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct UnionType<'a> {
+    pub span: Span,
+    pub members: Vec<'a, Type<'a>>,
+    /// Index of the member marked with `*` as the union's default, if any
+    pub default: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct FunctionType<'a> {
+    pub span: Span,
+    pub params: Vec<'a, Type<'a>>,
+    pub ret: Type<'a>,
+}
+
+/// A type with constraints, each an expression evaluated with `this` bound to the value being checked.
+#[derive(Debug)]
+pub struct ConstrainedType<'a> {
+    pub span: Span,
+    pub base: Type<'a>,
+    pub constraints: Vec<'a, Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct ParenthesizedType<'a> {
+    pub span: Span,
+    pub inner: Type<'a>,
+}
@@ -0,0 +1,28 @@
+//! What the syntax of the URIs of imports says, before they're resolved.
+
+/// Whether a URI starts with a scheme like `https:`, rather than being a path.
+pub fn has_scheme(uri: &str) -> bool {
+    let Some((scheme, _)) = uri.split_once(':') else { return false };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// The name an import is known by without an `as` clause: the last segment of its URI, without the extension.
+pub fn import_name(uri: &str) -> &str {
+    let name = uri.rsplit(['/', ':']).next().unwrap_or(uri);
+    name.strip_suffix(".pkl").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn schemes_and_names() {
+        assert!(has_scheme("https://example.com/a.pkl") && has_scheme("pkl:math") && has_scheme("modulepath:/a.pkl"));
+        assert!(!has_scheme("birds/a.pkl") && !has_scheme("1a:b") && !has_scheme("a b:c"));
+        assert_eq!(import_name("package://example.com/birds@1.0.0#/Bird.pkl"), "Bird");
+        assert_eq!(import_name("pkl:math"), "math");
+        assert_eq!(import_name("../birds"), "birds");
+    }
+}
@@ -0,0 +1,587 @@
+//! Traversal of the syntax tree.
+//!
+//! [`Visit`] walks a tree by shared reference and [`VisitMut`] by mutable reference. Each `visit_*` method defaults to
+//! the `walk_*` function of the same name in [`walk`] (or [`walk_mut`]), which visits the node's children, so an
+//! implementation only overrides the methods for the nodes it cares about, calling the `walk_*` function to keep
+//! descending:
+//!
+//! ```
+//! use pkl_ast::visit::{walk, Visit};
+//! use pkl_ast::Expr;
+//!
+//! /// Counts the expressions in a tree.
+//! struct ExprCounter(usize);
+//!
+//! impl<'a> Visit<'a> for ExprCounter {
+//!     fn visit_expr(&mut self, expr: &Expr<'a>) {
+//!         self.0 += 1;
+//!         walk::walk_expr(self, expr);
+//!     }
+//! }
+//! ```
+//!
+//! Both traits are generated from the same definition, so they always cover the same nodes.
+
+use crate::*;
+
+macro_rules! visitor {
+    ($(#[$doc:meta])* $Visit:ident, $walk:ident, $iter:ident, $($mut:tt)?) => {
+        $(#[$doc])*
+        pub trait $Visit<'a>: Sized {
+            fn visit_module(&mut self, module: &$($mut)? Module<'a>) {
+                $walk::walk_module(self, module);
+            }
+
+            fn visit_module_header(&mut self, header: &$($mut)? ModuleHeader<'a>) {
+                $walk::walk_module_header(self, header);
+            }
+
+            fn visit_module_extends(&mut self, _extends: &$($mut)? ModuleExtends<'a>) {}
+
+            fn visit_import(&mut self, import: &$($mut)? Import<'a>) {
+                $walk::walk_import(self, import);
+            }
+
+            fn visit_annotation(&mut self, annotation: &$($mut)? Annotation<'a>) {
+                $walk::walk_annotation(self, annotation);
+            }
+
+            fn visit_module_member(&mut self, member: &$($mut)? ModuleMember<'a>) {
+                $walk::walk_module_member(self, member);
+            }
+
+            fn visit_class(&mut self, class: &$($mut)? ClassDecl<'a>) {
+                $walk::walk_class(self, class);
+            }
+
+            fn visit_class_member(&mut self, member: &$($mut)? ClassMember<'a>) {
+                $walk::walk_class_member(self, member);
+            }
+
+            fn visit_class_property(&mut self, property: &$($mut)? ClassProperty<'a>) {
+                $walk::walk_class_property(self, property);
+            }
+
+            fn visit_class_method(&mut self, method: &$($mut)? ClassMethod<'a>) {
+                $walk::walk_class_method(self, method);
+            }
+
+            fn visit_type_alias(&mut self, alias: &$($mut)? TypeAlias<'a>) {
+                $walk::walk_type_alias(self, alias);
+            }
+
+            fn visit_type_parameter(&mut self, param: &$($mut)? TypeParameter<'a>) {
+                $walk::walk_type_parameter(self, param);
+            }
+
+            fn visit_parameter(&mut self, param: &$($mut)? Parameter<'a>) {
+                $walk::walk_parameter(self, param);
+            }
+
+            fn visit_object_body(&mut self, body: &$($mut)? ObjectBody<'a>) {
+                $walk::walk_object_body(self, body);
+            }
+
+            fn visit_object_member(&mut self, member: &$($mut)? ObjectMember<'a>) {
+                $walk::walk_object_member(self, member);
+            }
+
+            fn visit_object_property(&mut self, property: &$($mut)? ObjectProperty<'a>) {
+                $walk::walk_object_property(self, property);
+            }
+
+            fn visit_object_method(&mut self, method: &$($mut)? ObjectMethod<'a>) {
+                $walk::walk_object_method(self, method);
+            }
+
+            fn visit_object_entry(&mut self, entry: &$($mut)? ObjectEntry<'a>) {
+                $walk::walk_object_entry(self, entry);
+            }
+
+            fn visit_object_element(&mut self, element: &$($mut)? ObjectElement<'a>) {
+                $walk::walk_object_element(self, element);
+            }
+
+            fn visit_object_member_predicate(&mut self, predicate: &$($mut)? ObjectMemberPredicate<'a>) {
+                $walk::walk_object_member_predicate(self, predicate);
+            }
+
+            fn visit_object_spread(&mut self, spread: &$($mut)? ObjectSpread<'a>) {
+                $walk::walk_object_spread(self, spread);
+            }
+
+            fn visit_for_generator(&mut self, generator: &$($mut)? ForGenerator<'a>) {
+                $walk::walk_for_generator(self, generator);
+            }
+
+            fn visit_when_generator(&mut self, generator: &$($mut)? WhenGenerator<'a>) {
+                $walk::walk_when_generator(self, generator);
+            }
+
+            fn visit_expr(&mut self, expr: &$($mut)? Expr<'a>) {
+                $walk::walk_expr(self, expr);
+            }
+
+            fn visit_string_literal(&mut self, literal: &$($mut)? StringLiteral<'a>) {
+                $walk::walk_string_literal(self, literal);
+            }
+
+            fn visit_type(&mut self, ty: &$($mut)? Type<'a>) {
+                $walk::walk_type(self, ty);
+            }
+
+            fn visit_qualified_name(&mut self, name: &$($mut)? QualifiedName<'a>) {
+                $walk::walk_qualified_name(self, name);
+            }
+
+            /// Visits any name: of a declaration, a parameter, or a reference to a property or method.
+            fn visit_ident(&mut self, _ident: &$($mut)? Ident<'a>) {}
+        }
+
+        /// The default implementations of the visitor's methods, which visit the children of a node in source order.
+        pub mod $walk {
+            use super::*;
+
+            pub fn walk_module<'a, V: $Visit<'a>>(visitor: &mut V, module: &$($mut)? Module<'a>) {
+                visitor.visit_module_header(&$($mut)? module.header);
+                for member in module.members.$iter() {
+                    visitor.visit_module_member(member);
+                }
+            }
+
+            pub fn walk_module_header<'a, V: $Visit<'a>>(visitor: &mut V, header: &$($mut)? ModuleHeader<'a>) {
+                for annotation in header.annotations.$iter() {
+                    visitor.visit_annotation(annotation);
+                }
+                if let Some(name) = &$($mut)? header.name {
+                    visitor.visit_qualified_name(name);
+                }
+                if let Some(extends) = &$($mut)? header.extends {
+                    visitor.visit_module_extends(extends);
+                }
+                for import in header.imports.$iter() {
+                    visitor.visit_import(import);
+                }
+            }
+
+            pub fn walk_import<'a, V: $Visit<'a>>(visitor: &mut V, import: &$($mut)? Import<'a>) {
+                if let Some(alias) = &$($mut)? import.alias {
+                    visitor.visit_ident(alias);
+                }
+            }
+
+            pub fn walk_annotation<'a, V: $Visit<'a>>(visitor: &mut V, annotation: &$($mut)? Annotation<'a>) {
+                visitor.visit_qualified_name(&$($mut)? annotation.name);
+                if let Some(body) = &$($mut)? annotation.body {
+                    visitor.visit_object_body(body);
+                }
+            }
+
+            pub fn walk_module_member<'a, V: $Visit<'a>>(visitor: &mut V, member: &$($mut)? ModuleMember<'a>) {
+                match member {
+                    ModuleMember::Class(class) => visitor.visit_class(class),
+                    ModuleMember::TypeAlias(alias) => visitor.visit_type_alias(alias),
+                    ModuleMember::Property(property) => visitor.visit_class_property(property),
+                    ModuleMember::Method(method) => visitor.visit_class_method(method),
+                }
+            }
+
+            pub fn walk_class<'a, V: $Visit<'a>>(visitor: &mut V, class: &$($mut)? ClassDecl<'a>) {
+                for annotation in class.annotations.$iter() {
+                    visitor.visit_annotation(annotation);
+                }
+                visitor.visit_ident(&$($mut)? class.name);
+                for param in class.type_params.$iter() {
+                    visitor.visit_type_parameter(param);
+                }
+                if let Some(extends) = &$($mut)? class.extends {
+                    visitor.visit_type(extends);
+                }
+                for member in class.members.$iter() {
+                    visitor.visit_class_member(member);
+                }
+            }
+
+            pub fn walk_class_member<'a, V: $Visit<'a>>(visitor: &mut V, member: &$($mut)? ClassMember<'a>) {
+                match member {
+                    ClassMember::Property(property) => visitor.visit_class_property(property),
+                    ClassMember::Method(method) => visitor.visit_class_method(method),
+                }
+            }
+
+            pub fn walk_class_property<'a, V: $Visit<'a>>(visitor: &mut V, property: &$($mut)? ClassProperty<'a>) {
+                for annotation in property.annotations.$iter() {
+                    visitor.visit_annotation(annotation);
+                }
+                visitor.visit_ident(&$($mut)? property.name);
+                if let Some(ty) = &$($mut)? property.ty {
+                    visitor.visit_type(ty);
+                }
+                if let Some(value) = &$($mut)? property.value {
+                    visitor.visit_expr(value);
+                }
+                for body in property.bodies.$iter() {
+                    visitor.visit_object_body(body);
+                }
+            }
+
+            pub fn walk_class_method<'a, V: $Visit<'a>>(visitor: &mut V, method: &$($mut)? ClassMethod<'a>) {
+                for annotation in method.annotations.$iter() {
+                    visitor.visit_annotation(annotation);
+                }
+                visitor.visit_ident(&$($mut)? method.name);
+                for param in method.type_params.$iter() {
+                    visitor.visit_type_parameter(param);
+                }
+                for param in method.params.$iter() {
+                    visitor.visit_parameter(param);
+                }
+                if let Some(ty) = &$($mut)? method.return_type {
+                    visitor.visit_type(ty);
+                }
+                if let Some(body) = &$($mut)? method.body {
+                    visitor.visit_expr(body);
+                }
+            }
+
+            pub fn walk_type_alias<'a, V: $Visit<'a>>(visitor: &mut V, alias: &$($mut)? TypeAlias<'a>) {
+                for annotation in alias.annotations.$iter() {
+                    visitor.visit_annotation(annotation);
+                }
+                visitor.visit_ident(&$($mut)? alias.name);
+                for param in alias.type_params.$iter() {
+                    visitor.visit_type_parameter(param);
+                }
+                visitor.visit_type(&$($mut)? alias.ty);
+            }
+
+            pub fn walk_type_parameter<'a, V: $Visit<'a>>(visitor: &mut V, param: &$($mut)? TypeParameter<'a>) {
+                visitor.visit_ident(&$($mut)? param.name);
+            }
+
+            pub fn walk_parameter<'a, V: $Visit<'a>>(visitor: &mut V, param: &$($mut)? Parameter<'a>) {
+                if let Some(name) = &$($mut)? param.name {
+                    visitor.visit_ident(name);
+                }
+                if let Some(ty) = &$($mut)? param.ty {
+                    visitor.visit_type(ty);
+                }
+            }
+
+            pub fn walk_object_body<'a, V: $Visit<'a>>(visitor: &mut V, body: &$($mut)? ObjectBody<'a>) {
+                for param in body.params.$iter() {
+                    visitor.visit_parameter(param);
+                }
+                for member in body.members.$iter() {
+                    visitor.visit_object_member(member);
+                }
+            }
+
+            pub fn walk_object_member<'a, V: $Visit<'a>>(visitor: &mut V, member: &$($mut)? ObjectMember<'a>) {
+                match member {
+                    ObjectMember::Property(property) => visitor.visit_object_property(property),
+                    ObjectMember::Method(method) => visitor.visit_object_method(method),
+                    ObjectMember::Entry(entry) => visitor.visit_object_entry(entry),
+                    ObjectMember::Element(element) => visitor.visit_object_element(element),
+                    ObjectMember::MemberPredicate(predicate) => visitor.visit_object_member_predicate(predicate),
+                    ObjectMember::Spread(spread) => visitor.visit_object_spread(spread),
+                    ObjectMember::For(generator) => visitor.visit_for_generator(generator),
+                    ObjectMember::When(generator) => visitor.visit_when_generator(generator),
+                }
+            }
+
+            pub fn walk_object_property<'a, V: $Visit<'a>>(visitor: &mut V, property: &$($mut)? ObjectProperty<'a>) {
+                visitor.visit_ident(&$($mut)? property.name);
+                if let Some(ty) = &$($mut)? property.ty {
+                    visitor.visit_type(ty);
+                }
+                if let Some(value) = &$($mut)? property.value {
+                    visitor.visit_expr(value);
+                }
+                for body in property.bodies.$iter() {
+                    visitor.visit_object_body(body);
+                }
+            }
+
+            pub fn walk_object_method<'a, V: $Visit<'a>>(visitor: &mut V, method: &$($mut)? ObjectMethod<'a>) {
+                visitor.visit_ident(&$($mut)? method.name);
+                for param in method.params.$iter() {
+                    visitor.visit_parameter(param);
+                }
+                if let Some(ty) = &$($mut)? method.return_type {
+                    visitor.visit_type(ty);
+                }
+                visitor.visit_expr(&$($mut)? method.body);
+            }
+
+            pub fn walk_object_entry<'a, V: $Visit<'a>>(visitor: &mut V, entry: &$($mut)? ObjectEntry<'a>) {
+                visitor.visit_expr(&$($mut)? entry.key);
+                if let Some(value) = &$($mut)? entry.value {
+                    visitor.visit_expr(value);
+                }
+                for body in entry.bodies.$iter() {
+                    visitor.visit_object_body(body);
+                }
+            }
+
+            pub fn walk_object_element<'a, V: $Visit<'a>>(visitor: &mut V, element: &$($mut)? ObjectElement<'a>) {
+                visitor.visit_expr(&$($mut)? element.value);
+            }
+
+            pub fn walk_object_member_predicate<'a, V: $Visit<'a>>(
+                visitor: &mut V,
+                predicate: &$($mut)? ObjectMemberPredicate<'a>,
+            ) {
+                visitor.visit_expr(&$($mut)? predicate.predicate);
+                if let Some(value) = &$($mut)? predicate.value {
+                    visitor.visit_expr(value);
+                }
+                for body in predicate.bodies.$iter() {
+                    visitor.visit_object_body(body);
+                }
+            }
+
+            pub fn walk_object_spread<'a, V: $Visit<'a>>(visitor: &mut V, spread: &$($mut)? ObjectSpread<'a>) {
+                visitor.visit_expr(&$($mut)? spread.value);
+            }
+
+            pub fn walk_for_generator<'a, V: $Visit<'a>>(visitor: &mut V, generator: &$($mut)? ForGenerator<'a>) {
+                if let Some(key) = &$($mut)? generator.key {
+                    visitor.visit_parameter(key);
+                }
+                visitor.visit_parameter(&$($mut)? generator.value);
+                visitor.visit_expr(&$($mut)? generator.iterable);
+                visitor.visit_object_body(&$($mut)? generator.body);
+            }
+
+            pub fn walk_when_generator<'a, V: $Visit<'a>>(visitor: &mut V, generator: &$($mut)? WhenGenerator<'a>) {
+                visitor.visit_expr(&$($mut)? generator.condition);
+                visitor.visit_object_body(&$($mut)? generator.body);
+                if let Some(body) = &$($mut)? generator.else_body {
+                    visitor.visit_object_body(body);
+                }
+            }
+
+            pub fn walk_expr<'a, V: $Visit<'a>>(visitor: &mut V, expr: &$($mut)? Expr<'a>) {
+                match expr {
+                    Expr::Null(_)
+                    | Expr::Bool(..)
+                    | Expr::Int(..)
+                    | Expr::Float(..)
+                    | Expr::This(_)
+                    | Expr::Outer(_)
+                    | Expr::Module(_)
+                    | Expr::Import(_)
+                    | Expr::Error(_) => {}
+                    Expr::String(literal) => visitor.visit_string_literal(literal),
+                    Expr::Ident(ident) => visitor.visit_ident(ident),
+                    Expr::Member(member) => {
+                        visitor.visit_expr(&$($mut)? member.receiver);
+                        visitor.visit_ident(&$($mut)? member.name);
+                    }
+                    Expr::Call(call) => {
+                        if let Some(receiver) = &$($mut)? call.receiver {
+                            visitor.visit_expr(receiver);
+                        }
+                        visitor.visit_ident(&$($mut)? call.name);
+                        for arg in call.args.$iter() {
+                            visitor.visit_expr(arg);
+                        }
+                    }
+                    Expr::Super(sup) => {
+                        visitor.visit_ident(&$($mut)? sup.name);
+                        if let Some(args) = &$($mut)? sup.args {
+                            for arg in args.$iter() {
+                                visitor.visit_expr(arg);
+                            }
+                        }
+                    }
+                    Expr::Subscript(subscript) => {
+                        if let Some(receiver) = &$($mut)? subscript.receiver {
+                            visitor.visit_expr(receiver);
+                        }
+                        visitor.visit_expr(&$($mut)? subscript.index);
+                    }
+                    Expr::Unary(unary) => visitor.visit_expr(&$($mut)? unary.operand),
+                    Expr::NonNull(non_null) => visitor.visit_expr(&$($mut)? non_null.operand),
+                    Expr::Binary(binary) => {
+                        visitor.visit_expr(&$($mut)? binary.left);
+                        visitor.visit_expr(&$($mut)? binary.right);
+                    }
+                    Expr::Is(test) | Expr::As(test) => {
+                        visitor.visit_expr(&$($mut)? test.value);
+                        visitor.visit_type(&$($mut)? test.ty);
+                    }
+                    Expr::If(if_expr) => {
+                        visitor.visit_expr(&$($mut)? if_expr.condition);
+                        visitor.visit_expr(&$($mut)? if_expr.then);
+                        visitor.visit_expr(&$($mut)? if_expr.otherwise);
+                    }
+                    Expr::Let(let_expr) => {
+                        visitor.visit_parameter(&$($mut)? let_expr.param);
+                        visitor.visit_expr(&$($mut)? let_expr.value);
+                        visitor.visit_expr(&$($mut)? let_expr.body);
+                    }
+                    Expr::Lambda(lambda) => {
+                        for param in lambda.params.$iter() {
+                            visitor.visit_parameter(param);
+                        }
+                        visitor.visit_expr(&$($mut)? lambda.body);
+                    }
+                    Expr::New(new) => {
+                        if let Some(ty) = &$($mut)? new.ty {
+                            visitor.visit_type(ty);
+                        }
+                        visitor.visit_object_body(&$($mut)? new.body);
+                    }
+                    Expr::Amend(amend) => {
+                        visitor.visit_expr(&$($mut)? amend.parent);
+                        visitor.visit_object_body(&$($mut)? amend.body);
+                    }
+                    Expr::Throw(keyword) | Expr::Trace(keyword) => visitor.visit_expr(&$($mut)? keyword.value),
+                    Expr::Read(read) => visitor.visit_expr(&$($mut)? read.uri),
+                    Expr::Parenthesized(parenthesized) => visitor.visit_expr(&$($mut)? parenthesized.expr),
+                }
+            }
+
+            pub fn walk_string_literal<'a, V: $Visit<'a>>(visitor: &mut V, literal: &$($mut)? StringLiteral<'a>) {
+                for part in literal.parts.$iter() {
+                    if let StringPart::Interpolation(expr) = part {
+                        visitor.visit_expr(expr);
+                    }
+                }
+            }
+
+            pub fn walk_type<'a, V: $Visit<'a>>(visitor: &mut V, ty: &$($mut)? Type<'a>) {
+                match ty {
+                    Type::Unknown(_) | Type::Nothing(_) | Type::Module(_) | Type::StringLiteral(_) => {}
+                    Type::Named(named) => {
+                        visitor.visit_qualified_name(&$($mut)? named.name);
+                        for arg in named.args.$iter() {
+                            visitor.visit_type(arg);
+                        }
+                    }
+                    Type::Nullable(nullable) => visitor.visit_type(&$($mut)? nullable.inner),
+                    Type::Union(union) => {
+                        for member in union.members.$iter() {
+                            visitor.visit_type(member);
+                        }
+                    }
+                    Type::Function(function) => {
+                        for param in function.params.$iter() {
+                            visitor.visit_type(param);
+                        }
+                        visitor.visit_type(&$($mut)? function.ret);
+                    }
+                    Type::Constrained(constrained) => {
+                        visitor.visit_type(&$($mut)? constrained.base);
+                        for constraint in constrained.constraints.$iter() {
+                            visitor.visit_expr(constraint);
+                        }
+                    }
+                    Type::Parenthesized(parenthesized) => visitor.visit_type(&$($mut)? parenthesized.inner),
+                }
+            }
+
+            pub fn walk_qualified_name<'a, V: $Visit<'a>>(visitor: &mut V, name: &$($mut)? QualifiedName<'a>) {
+                for part in name.parts.$iter() {
+                    visitor.visit_ident(part);
+                }
+            }
+        }
+    };
+}
+
+visitor!(
+    /// Walks a syntax tree by shared reference.
+    Visit,
+    walk,
+    iter,
+);
+
+visitor!(
+    /// Walks a syntax tree by mutable reference, e.g. to rewrite parts of it in place.
+    VisitMut,
+    walk_mut,
+    iter_mut,
+    mut
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `answer = x + 1`
+    fn module<'a>(ast: AstBuilder<'a>) -> Module<'a> {
+        let ident = |start, name: &'a str| Ident { span: Span::new(start, start + name.len()), name };
+        let value = Expr::Binary(ast.boxed(BinaryExpr {
+            span: Span::new(9, 14),
+            op: BinaryOp::Add,
+            left: Expr::Ident(ident(9, "x")),
+            right: Expr::Int(Span::new(13, 14), 1),
+        }));
+        let property = ClassProperty {
+            span: Span::new(0, 14),
+            doc: None,
+            annotations: ast.vec(),
+            modifiers: Modifiers(ast.vec()),
+            name: ident(0, "answer"),
+            ty: None,
+            value: Some(value),
+            bodies: ast.vec(),
+        };
+
+        Module {
+            span: Span::new(0, 14),
+            header: ModuleHeader {
+                span: Span::new(0, 0),
+                doc: None,
+                annotations: ast.vec(),
+                modifiers: Modifiers(ast.vec()),
+                name: None,
+                extends: None,
+                imports: ast.vec(),
+            },
+            members: ast.vec_from_iter([ModuleMember::Property(property)]),
+        }
+    }
+
+    #[test]
+    fn visits_every_ident() {
+        struct Names<'a>(std::vec::Vec<&'a str>);
+
+        impl<'a> Visit<'a> for Names<'a> {
+            fn visit_ident(&mut self, ident: &Ident<'a>) {
+                self.0.push(ident.name);
+            }
+        }
+
+        let alloc = Allocator::default();
+        let mut names = Names(std::vec::Vec::new());
+        names.visit_module(&module(AstBuilder::new(&alloc)));
+
+        assert_eq!(names.0, ["answer", "x"]);
+    }
+
+    #[test]
+    fn rewrites_in_place() {
+        struct DoubleInts;
+
+        impl<'a> VisitMut<'a> for DoubleInts {
+            fn visit_expr(&mut self, expr: &mut Expr<'a>) {
+                if let Expr::Int(_, value) = expr {
+                    *value *= 2;
+                }
+                walk_mut::walk_expr(self, expr);
+            }
+        }
+
+        let alloc = Allocator::default();
+        let mut module = module(AstBuilder::new(&alloc));
+        DoubleInts.visit_module(&mut module);
+
+        let ModuleMember::Property(property) = &module.members[0] else { unreachable!() };
+        let Some(Expr::Binary(sum)) = &property.value else { unreachable!() };
+        assert!(matches!(sum.right, Expr::Int(_, 2)));
+    }
+}
@@ -0,0 +1,248 @@
+use pkl_ast::*;
+
+use crate::Codegen;
+
+/// Whether a member is a property with nothing attached and no object bodies, which is laid out without a blank
+/// line between it and the neighbouring properties of the same kind.
+fn is_simple_property(property: &ClassProperty) -> bool {
+    property.doc.is_none() && property.annotations.is_empty() && property.bodies.is_empty()
+}
+
+impl Codegen {
+    pub(crate) fn print_module(&mut self, module: &Module) {
+        let header = &module.header;
+        let mut section = false;
+
+        if header.name.is_some() || header.extends.is_some() {
+            self.print_doc(&header.doc);
+            self.print_annotations(&header.annotations);
+        }
+        if let Some(name) = &header.name {
+            self.print_modifiers(&header.modifiers);
+            self.push("module ");
+            self.print_qualified_name(name);
+            self.newline();
+            section = true;
+        }
+        if let Some(extends) = &header.extends {
+            if section {
+                self.newline();
+            }
+            self.push(match extends.kind {
+                ExtendsKind::Amends => "amends ",
+                ExtendsKind::Extends => "extends ",
+            });
+            self.print_string_constant(&extends.uri);
+            self.newline();
+            section = true;
+        }
+        if !header.imports.is_empty() {
+            if section {
+                self.newline();
+            }
+            for import in &header.imports {
+                self.push(if import.glob { "import* " } else { "import " });
+                self.print_string_constant(&import.uri);
+                if let Some(alias) = &import.alias {
+                    self.push(" as ");
+                    self.print_ident(alias);
+                }
+                self.newline();
+            }
+            section = true;
+        }
+
+        let mut previous_simple = None;
+        for member in &module.members {
+            let simple = matches!(member, ModuleMember::Property(property) if is_simple_property(property));
+            if section && !(simple && previous_simple == Some(true)) {
+                self.newline();
+            }
+            self.print_module_member(member);
+            self.newline();
+            section = true;
+            previous_simple = Some(simple);
+        }
+    }
+
+    fn print_module_member(&mut self, member: &ModuleMember) {
+        match member {
+            ModuleMember::Class(class) => self.print_class(class),
+            ModuleMember::TypeAlias(alias) => self.print_type_alias(alias),
+            ModuleMember::Property(property) => self.print_class_property(property),
+            ModuleMember::Method(method) => self.print_class_method(method),
+        }
+    }
+
+    fn print_class(&mut self, class: &ClassDecl) {
+        self.print_doc(&class.doc);
+        self.print_annotations(&class.annotations);
+        self.print_modifiers(&class.modifiers);
+        self.push("class ");
+        self.print_ident(&class.name);
+        self.print_type_parameters(&class.type_params);
+        if let Some(extends) = &class.extends {
+            self.push(" extends ");
+            self.print_type(extends);
+        }
+        if class.members.is_empty() {
+            return;
+        }
+
+        self.push(" {");
+        self.indent();
+        let mut previous_simple = None;
+        for member in &class.members {
+            let simple = matches!(member, ClassMember::Property(property) if is_simple_property(property));
+            if previous_simple.is_some() && !(simple && previous_simple == Some(true)) {
+                self.newline();
+            }
+            self.newline();
+            match member {
+                ClassMember::Property(property) => self.print_class_property(property),
+                ClassMember::Method(method) => self.print_class_method(method),
+            }
+            previous_simple = Some(simple);
+        }
+        self.dedent();
+        self.newline();
+        self.push("}");
+    }
+
+    fn print_type_alias(&mut self, alias: &TypeAlias) {
+        self.print_doc(&alias.doc);
+        self.print_annotations(&alias.annotations);
+        self.print_modifiers(&alias.modifiers);
+        self.push("typealias ");
+        self.print_ident(&alias.name);
+        self.print_type_parameters(&alias.type_params);
+        self.push(" = ");
+        self.print_type(&alias.ty);
+    }
+
+    fn print_class_property(&mut self, property: &ClassProperty) {
+        self.print_doc(&property.doc);
+        self.print_annotations(&property.annotations);
+        self.print_modifiers(&property.modifiers);
+        self.print_ident(&property.name);
+        if let Some(ty) = &property.ty {
+            self.push(": ");
+            self.print_type(ty);
+        }
+        self.print_member_value(&property.value, &property.bodies);
+    }
+
+    fn print_class_method(&mut self, method: &ClassMethod) {
+        self.print_doc(&method.doc);
+        self.print_annotations(&method.annotations);
+        self.print_modifiers(&method.modifiers);
+        self.push("function ");
+        self.print_ident(&method.name);
+        self.print_type_parameters(&method.type_params);
+        self.push("(");
+        self.print_list(&method.params, Self::print_parameter);
+        self.push(")");
+        if let Some(ty) = &method.return_type {
+            self.push(": ");
+            self.print_type(ty);
+        }
+        if let Some(body) = &method.body {
+            self.push(" = ");
+            self.print_expr(body, 0);
+        }
+    }
+
+    fn print_type_parameters(&mut self, params: &[TypeParameter]) {
+        if params.is_empty() {
+            return;
+        }
+
+        self.push("<");
+        self.print_list(params, |codegen, param| {
+            match param.variance {
+                Some(Variance::In) => codegen.push("in "),
+                Some(Variance::Out) => codegen.push("out "),
+                None => {}
+            }
+            codegen.print_ident(&param.name);
+        });
+        self.push(">");
+    }
+
+    fn print_doc(&mut self, doc: &Option<DocComment>) {
+        let Some(doc) = doc else { return };
+        for line in doc.lines.iter() {
+            self.push("///");
+            if !line.is_empty() {
+                self.push(" ");
+                self.push(line);
+            }
+            self.newline();
+        }
+    }
+
+    fn print_annotations(&mut self, annotations: &[Annotation]) {
+        for annotation in annotations {
+            self.push("@");
+            self.print_qualified_name(&annotation.name);
+            if let Some(body) = &annotation.body {
+                self.push(" ");
+                self.print_object_body(body);
+            }
+            self.newline();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::roundtrip;
+
+    #[test]
+    fn headers() {
+        let source = r#"/// The app
+@ModuleInfo {
+  minPklVersion = "0.25.0"
+}
+open module com.example.App
+
+extends "base.pkl"
+
+import "pkl:json"
+import* "*.pkl" as all
+
+x = 1
+"#;
+        assert_eq!(roundtrip(source), source);
+        assert_eq!(roundtrip("amends \"a.pkl\"\nx = 1"), "amends \"a.pkl\"\n\nx = 1\n");
+    }
+
+    #[test]
+    fn declarations() {
+        let source = r#"name = "a"
+version = 1
+
+/// A person
+///
+/// With details.
+open class Person<out T> extends Base {
+  name: String
+  age: Int(this >= 0)
+
+  @Deprecated
+  hidden nickname: String?
+
+  function greet(other: Person): String = "hi \(other.name)"
+
+  abstract function pending()
+}
+
+class Empty
+
+typealias Labels<V> = Mapping<String, V>
+
+local function double(x) = x * 2
+"#;
+        assert_eq!(roundtrip(source), source);
+    }
+}
@@ -0,0 +1,326 @@
+use pkl_ast::*;
+
+use crate::{escape, Codegen};
+
+/// Precedence of expressions that extend as far to the right as possible: `if`, `let`, and lambdas.
+const TRAILING: u8 = 0;
+const PREFIX: u8 = 11;
+const POSTFIX: u8 = 12;
+const PRIMARY: u8 = 13;
+
+/// How tightly an expression binds, mirroring the binding powers of the parser.
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::If(_) | Expr::Let(_) | Expr::Lambda(_) => TRAILING,
+        Expr::Binary(binary) => binary_precedence(binary.op),
+        Expr::Is(_) | Expr::As(_) => 6,
+        Expr::Unary(_) => PREFIX,
+        // hand-built trees may contain negative literals, which print like negations
+        Expr::Int(_, value) if *value < 0 => PREFIX,
+        Expr::Float(_, value) if value.is_sign_negative() && !value.is_nan() => PREFIX,
+        Expr::Member(_) | Expr::Call(_) | Expr::Subscript(_) | Expr::NonNull(_) | Expr::Amend(_) => POSTFIX,
+        _ => PRIMARY,
+    }
+}
+
+fn binary_precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::NullCoalesce => 1,
+        BinaryOp::Pipe => 2,
+        BinaryOp::Or => 3,
+        BinaryOp::And => 4,
+        BinaryOp::Eq | BinaryOp::NotEq => 5,
+        BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq => 7,
+        BinaryOp::Add | BinaryOp::Sub => 8,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::IntDiv | BinaryOp::Rem => 9,
+        BinaryOp::Pow => 10,
+    }
+}
+
+impl Codegen {
+    /// Prints an expression, parenthesizing it if it binds less tightly than `min_precedence`.
+    pub(crate) fn print_expr(&mut self, expr: &Expr, min_precedence: u8) {
+        let parenthesize = precedence(expr) < min_precedence;
+        if parenthesize {
+            self.push("(");
+        }
+
+        match expr {
+            Expr::Null(_) => self.push("null"),
+            Expr::Bool(_, value) => self.push(if *value { "true" } else { "false" }),
+            Expr::Int(_, value) => self.push(&value.to_string()),
+            Expr::Float(_, value) => self.push(&format_float(*value)),
+            Expr::String(literal) => self.print_string(literal),
+            Expr::This(_) => self.push("this"),
+            Expr::Outer(_) => self.push("outer"),
+            Expr::Module(_) => self.push("module"),
+            Expr::Ident(ident) => self.print_ident(ident),
+            Expr::Member(member) => {
+                self.print_expr(&member.receiver, POSTFIX);
+                self.push(if member.null_safe { "?." } else { "." });
+                self.print_ident(&member.name);
+            }
+            Expr::Call(call) => {
+                if let Some(receiver) = &call.receiver {
+                    self.print_expr(receiver, POSTFIX);
+                    self.push(if call.null_safe { "?." } else { "." });
+                }
+                self.print_ident(&call.name);
+                self.print_args(&call.args);
+            }
+            Expr::Super(sup) => {
+                self.push("super.");
+                self.print_ident(&sup.name);
+                if let Some(args) = &sup.args {
+                    self.print_args(args);
+                }
+            }
+            Expr::Subscript(subscript) => {
+                match &subscript.receiver {
+                    Some(receiver) => self.print_expr(receiver, POSTFIX),
+                    None => self.push("super"),
+                }
+                self.push("[");
+                self.print_expr(&subscript.index, TRAILING);
+                self.push("]");
+            }
+            Expr::Unary(unary) => {
+                self.push(match unary.op {
+                    UnaryOp::Neg => "-",
+                    UnaryOp::Not => "!",
+                });
+                // `!!` would be a non-null assertion
+                let not = |op| op == UnaryOp::Not;
+                if matches!(&unary.operand, Expr::Unary(operand) if not(unary.op) && not(operand.op)) {
+                    self.push(" ");
+                }
+                self.print_expr(&unary.operand, PREFIX);
+            }
+            Expr::NonNull(non_null) => {
+                self.print_expr(&non_null.operand, POSTFIX);
+                self.push("!!");
+            }
+            Expr::Binary(binary) => {
+                let precedence = binary_precedence(binary.op);
+                let right_associative = matches!(binary.op, BinaryOp::Pow | BinaryOp::NullCoalesce);
+                let (left, right) =
+                    if right_associative { (precedence + 1, precedence) } else { (precedence, precedence + 1) };
+
+                self.print_expr(&binary.left, left);
+                self.push(" ");
+                self.push(binary.op.as_str());
+                self.push(" ");
+                self.print_expr(&binary.right, right);
+            }
+            Expr::Is(test) | Expr::As(test) => {
+                self.print_expr(&test.value, 6);
+                self.push(if matches!(expr, Expr::Is(_)) { " is " } else { " as " });
+                self.print_type(&test.ty);
+            }
+            Expr::If(if_expr) => {
+                self.push("if (");
+                self.print_expr(&if_expr.condition, TRAILING);
+                self.push(") ");
+                self.print_expr(&if_expr.then, TRAILING);
+                self.push(" else ");
+                self.print_expr(&if_expr.otherwise, TRAILING);
+            }
+            Expr::Let(let_expr) => {
+                self.push("let (");
+                self.print_parameter(&let_expr.param);
+                self.push(" = ");
+                self.print_expr(&let_expr.value, TRAILING);
+                self.push(") ");
+                self.print_expr(&let_expr.body, TRAILING);
+            }
+            Expr::Lambda(lambda) => {
+                self.push("(");
+                self.print_list(&lambda.params, Self::print_parameter);
+                self.push(") -> ");
+                self.print_expr(&lambda.body, TRAILING);
+            }
+            Expr::New(new) => {
+                self.push("new ");
+                if let Some(ty) = &new.ty {
+                    self.print_type(ty);
+                    self.push(" ");
+                }
+                self.print_object_body(&new.body);
+            }
+            Expr::Amend(amend) => {
+                // only these may be amended without parentheses
+                if matches!(amend.parent, Expr::Parenthesized(_) | Expr::New(_) | Expr::Amend(_)) {
+                    self.print_expr(&amend.parent, TRAILING);
+                } else {
+                    self.push("(");
+                    self.print_expr(&amend.parent, TRAILING);
+                    self.push(")");
+                }
+                self.push(" ");
+                self.print_object_body(&amend.body);
+            }
+            Expr::Throw(keyword) | Expr::Trace(keyword) => {
+                self.push(if matches!(expr, Expr::Throw(_)) { "throw(" } else { "trace(" });
+                self.print_expr(&keyword.value, TRAILING);
+                self.push(")");
+            }
+            Expr::Import(import) => {
+                self.push(if import.glob { "import*(" } else { "import(" });
+                self.print_string_constant(&import.uri);
+                self.push(")");
+            }
+            Expr::Read(read) => {
+                self.push(match read.kind {
+                    ReadKind::Read => "read(",
+                    ReadKind::ReadOrNull => "read?(",
+                    ReadKind::ReadGlob => "read*(",
+                });
+                self.print_expr(&read.uri, TRAILING);
+                self.push(")");
+            }
+            Expr::Parenthesized(parenthesized) => {
+                self.push("(");
+                self.print_expr(&parenthesized.expr, TRAILING);
+                self.push(")");
+            }
+            Expr::Error(_) => {}
+        }
+
+        if parenthesize {
+            self.push(")");
+        }
+    }
+
+    fn print_args(&mut self, args: &[Expr]) {
+        self.push("(");
+        self.print_list(args, |codegen, arg| codegen.print_expr(arg, TRAILING));
+        self.push(")");
+    }
+
+    /// Prints a parameter: its name, or `_` if it has none, and its type.
+    pub(crate) fn print_parameter(&mut self, param: &Parameter) {
+        match &param.name {
+            // a parameter named `_` has to be quoted to tell it from an ignored one
+            Some(name) if name.name == "_" => self.push("`_`"),
+            Some(name) => self.print_ident(name),
+            None => self.push("_"),
+        }
+        if let Some(ty) = &param.ty {
+            self.push(": ");
+            self.print_type(ty);
+        }
+    }
+
+    fn print_string(&mut self, literal: &StringLiteral) {
+        if !literal.multiline {
+            self.push("\"");
+            for part in &literal.parts {
+                match part {
+                    StringPart::Text(_, text) => self.push(&escape(text, false)),
+                    StringPart::Interpolation(expr) => self.print_interpolation(expr),
+                }
+            }
+            self.push("\"");
+            return;
+        }
+
+        // the content is indented like the closing delimiter, which is where the lines start anyway
+        self.push("\"\"\"");
+        self.newline();
+        for part in &literal.parts {
+            match part {
+                StringPart::Text(_, text) => {
+                    for (index, line) in text.split('\n').enumerate() {
+                        if index > 0 {
+                            self.newline();
+                        }
+                        self.push(&escape(line, true));
+                    }
+                }
+                StringPart::Interpolation(expr) => self.print_interpolation(expr),
+            }
+        }
+        self.newline();
+        self.push("\"\"\"");
+    }
+
+    fn print_interpolation(&mut self, expr: &Expr) {
+        self.push("\\(");
+        self.print_expr(expr, TRAILING);
+        self.push(")");
+    }
+}
+
+/// Formats a float so that it reads back as a float rather than an int, e.g. `1.0` rather than `1`.
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        format!("{value:?}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+    use pkl_ast::*;
+
+    use crate::{Codegen, CodegenOptions};
+
+    fn print(source: &str) -> String {
+        let alloc = Allocator::default();
+        let result = pkl_parser::parse_expr(&alloc, source);
+        assert!(result.diagnostics.is_empty(), "{source}: {:?}", result.diagnostics);
+
+        Codegen::new(CodegenOptions::default()).build_expr(&result.node)
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(print("1+2*3"), "1 + 2 * 3");
+        assert_eq!(print("(1+2)*3"), "(1 + 2) * 3");
+        assert_eq!(print("a ?? b ?? c"), "a ?? b ?? c");
+        assert_eq!(print("-x.y!!"), "-x.y!!");
+        assert_eq!(print("! !x"), "! !x");
+        assert_eq!(print("x is String|Int && !y"), "x is String | Int && !y");
+    }
+
+    #[test]
+    fn hand_built_trees_get_parentheses() {
+        let alloc = Allocator::default();
+        let ast = AstBuilder::new(&alloc);
+        let ident = |name| Expr::Ident(Ident { span: Span::default(), name });
+        let binary = |op, left, right| Expr::Binary(ast.boxed(BinaryExpr { span: Span::default(), op, left, right }));
+
+        // (a - b) - c and a - (b - c)
+        let left = binary(BinaryOp::Sub, binary(BinaryOp::Sub, ident("a"), ident("b")), ident("c"));
+        let right = binary(BinaryOp::Sub, ident("a"), binary(BinaryOp::Sub, ident("b"), ident("c")));
+        let codegen = || Codegen::new(CodegenOptions::default());
+
+        assert_eq!(codegen().build_expr(&left), "a - b - c");
+        assert_eq!(codegen().build_expr(&right), "a - (b - c)");
+        assert_eq!(codegen().build_expr(&Expr::Float(Span::default(), 2.0)), "2.0");
+    }
+
+    #[test]
+    fn keyword_expressions() {
+        assert_eq!(
+            print("let(x=1)if(x>0)(y)->y else throw(\"no\")"),
+            "let (x = 1) if (x > 0) (y) -> y else throw(\"no\")"
+        );
+        let source = "import*(\"*.pkl\").length + read?(\"env:HOME\")";
+        assert_eq!(print(source), source);
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(print(r#""a\tb \(x + 1) \"q\"""#), r#""a\tb \(x + 1) \"q\"""#);
+        assert_eq!(print("#\"raw \\ \"#"), r#""raw \\ ""#);
+        assert_eq!(
+            print("new { s = \"\"\"\n    one\n      \\(two)\n\n    \"\"\" }"),
+            "new {\n  s = \"\"\"\n  one\n    \\(two)\n\n  \"\"\"\n}"
+        );
+    }
+}
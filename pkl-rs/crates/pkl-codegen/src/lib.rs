@@ -0,0 +1,293 @@
+//! Turns a [`pkl_ast`] tree back into Pkl source text.
+//!
+//! The output parses back into an equivalent tree, but doesn't try to reproduce the layout of the source a tree was
+//! parsed from: comments other than doc comments are gone from the tree, object members are laid out one per line,
+//! and parentheses are added wherever operator precedence requires them (so that trees built by hand print
+//! correctly), on top of the ones recorded as [`Expr::Parenthesized`] nodes.
+//!
+//! Error nodes, which only come from source that failed to parse, print as nothing.
+
+#![forbid(unsafe_code)]
+
+mod decl;
+mod expr;
+mod object;
+mod types;
+
+use pkl_ast::{Expr, Ident, Module, StringConstant, Type};
+use pkl_lexer::identifier::is_identifier;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// The text of one level of indentation
+    pub indent: String,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions { indent: "  ".to_string() }
+    }
+}
+
+/// Prints syntax trees as Pkl source text.
+///
+/// ```
+/// use pkl_codegen::{Codegen, CodegenOptions};
+///
+/// let alloc = oxc_allocator::Allocator::default();
+/// let module = pkl_parser::parse_module(&alloc, "version   =  \"1.0\"").node;
+///
+/// assert_eq!(Codegen::new(CodegenOptions::default()).build(&module), "version = \"1.0\"\n");
+/// ```
+pub struct Codegen {
+    options: CodegenOptions,
+    out: String,
+    level: usize,
+    /// Whether a line was started but its indentation not written yet, which is left out for blank lines
+    pending_indent: bool,
+}
+
+impl Codegen {
+    pub fn new(options: CodegenOptions) -> Self {
+        Codegen { options, out: String::new(), level: 0, pending_indent: false }
+    }
+
+    /// Prints a whole module, ending with a line break.
+    pub fn build(mut self, module: &Module) -> String {
+        self.print_module(module);
+        self.out
+    }
+
+    /// Prints a single expression.
+    pub fn build_expr(mut self, expr: &Expr) -> String {
+        self.print_expr(expr, 0);
+        self.out
+    }
+
+    /// Prints a single type.
+    pub fn build_type(mut self, ty: &Type) -> String {
+        self.print_type(ty);
+        self.out
+    }
+
+    fn push(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if self.pending_indent {
+            self.pending_indent = false;
+            for _ in 0..self.level {
+                self.out.push_str(&self.options.indent);
+            }
+        }
+        self.out.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.pending_indent = true;
+    }
+
+    fn indent(&mut self) {
+        self.level += 1;
+    }
+
+    fn dedent(&mut self) {
+        self.level -= 1;
+    }
+
+    /// Prints a name, quoting it in backticks if it isn't a plain identifier.
+    fn print_ident(&mut self, ident: &Ident) {
+        self.print_name(ident.name);
+    }
+
+    fn print_name(&mut self, name: &str) {
+        if is_identifier(name) {
+            self.push(name);
+        } else {
+            self.push("`");
+            self.push(name);
+            self.push("`");
+        }
+    }
+
+    fn print_string_constant(&mut self, constant: &StringConstant) {
+        self.push("\"");
+        self.push(&escape(constant.value, false));
+        self.push("\"");
+    }
+
+    /// Prints items separated by `, `.
+    fn print_list<T>(&mut self, items: &[T], mut print: impl FnMut(&mut Self, &T)) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.push(", ");
+            }
+            print(self, item);
+        }
+    }
+}
+
+/// Escapes the text of a string so that it reads back as the same text.
+///
+/// In a multiline string, line breaks stay as they are and only `"""` has to be broken up, rather than every `"`.
+fn escape(text: &str, multiline: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut quotes = 0;
+
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' if !multiline => out.push_str("\\\""),
+            '"' if quotes == 2 => out.push_str("\\\""),
+            '\n' if !multiline => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' if !multiline => out.push_str("\\t"),
+            c => out.push(c),
+        }
+        quotes = if c == '"' && quotes < 2 { quotes + 1 } else { 0 };
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+
+    use super::*;
+
+    /// Prints a module, checking that the output prints the same way once parsed again.
+    pub(crate) fn roundtrip(source: &str) -> String {
+        let alloc = Allocator::default();
+        let result = pkl_parser::parse_module(&alloc, source);
+        assert!(result.diagnostics.is_empty(), "{source}: {:?}", result.diagnostics);
+        let printed = Codegen::new(CodegenOptions::default()).build(&result.node);
+
+        {
+            let reparsed = pkl_parser::parse_module(&alloc, &printed);
+            assert!(reparsed.diagnostics.is_empty(), "{printed}: {:?}", reparsed.diagnostics);
+            assert_eq!(Codegen::new(CodegenOptions::default()).build(&reparsed.node), printed);
+        }
+
+        printed
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(escape("a\"b\\c\n\t", false), "a\\\"b\\\\c\\n\\t");
+        assert_eq!(escape("\"\"\" \"\"\"\"", true), "\"\"\\\" \"\"\\\"\"");
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        assert_eq!(roundtrip("`my-name` = 1\n`class` = 2\nplain = 3\n"), "`my-name` = 1\n`class` = 2\nplain = 3\n");
+    }
+
+    #[test]
+    fn configurable_indentation() {
+        let alloc = Allocator::default();
+        let module = pkl_parser::parse_module(&alloc, "a { b { c = 1 } }").node;
+        let options = CodegenOptions { indent: "\t".to_string() };
+
+        assert_eq!(Codegen::new(options).build(&module), "a {\n\tb {\n\t\tc = 1\n\t}\n}\n");
+    }
+
+    /// A seeded generator of pseudo-random numbers (xorshift), so that a failing property test can be replayed.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        fn pick<'t>(&mut self, items: &[&'t str]) -> &'t str {
+            items[self.below(items.len())]
+        }
+    }
+
+    /// The source of a small random module, of properties whose values nest up to a few levels.
+    fn module(rng: &mut Rng) -> String {
+        let mut source = String::new();
+        for _ in 0..rng.below(4) + 1 {
+            let name = rng.pick(&["a", "b", "local c", "hidden d", "`e f`"]);
+            match rng.below(3) {
+                0 => source.push_str(&format!("{name} {{{}}}\n", body(rng, 3))),
+                1 => {
+                    let ty = rng.pick(&["Int", "String?", "Listing<Int>"]);
+                    source.push_str(&format!("{name}: {ty} = {}\n", expr(rng, 3)));
+                }
+                _ => source.push_str(&format!("{name} = {}\n", expr(rng, 3))),
+            }
+        }
+        source
+    }
+
+    fn expr(rng: &mut Rng, depth: usize) -> String {
+        const ATOMS: &[&str] = &["1", "42", "1.5", "x", "this", "null", "true", "\"pigeon\"", "\"a\\n\\(x)\""];
+        if depth == 0 {
+            return rng.pick(ATOMS).to_string();
+        }
+        let d = depth - 1;
+        match rng.below(11) {
+            0 => rng.pick(ATOMS).to_string(),
+            1 => {
+                let (left, right) = (expr(rng, d), expr(rng, d));
+                let op = rng.pick(&["+", "-", "*", "/", "~/", "**", "==", "<", "&&", "||", "??", "|>"]);
+                format!("{left} {op} {right}")
+            }
+            2 => {
+                let (op, operand) = (rng.pick(&["-", "!"]), expr(rng, d));
+                // `!!` and `--` would be a single token
+                let space = if operand.starts_with(['-', '!']) { " " } else { "" };
+                format!("{op}{space}{operand}")
+            }
+            3 => format!("({})", expr(rng, d)),
+            4 => format!("{}.{}", expr(rng, d), rng.pick(&["length", "foo", "toString()", "map((n) -> n)"])),
+            5 => format!("{}[{}]", expr(rng, d), expr(rng, d)),
+            6 => format!("if ({}) {} else {}", expr(rng, d), expr(rng, d), expr(rng, d)),
+            7 => format!("let (y = {}) {}", expr(rng, d), expr(rng, d)),
+            8 => format!("(n) -> {}", expr(rng, d)),
+            9 => format!("List({}, {})", expr(rng, d), expr(rng, d)),
+            _ => format!("new {{{}}}", body(rng, depth - 1)),
+        }
+    }
+
+    fn body(rng: &mut Rng, depth: usize) -> String {
+        let mut members = Vec::new();
+        for _ in 0..rng.below(4) {
+            members.push(match rng.below(4) {
+                0 => format!("p = {}", expr(rng, depth)),
+                1 => format!("[{}] = {}", expr(rng, depth), expr(rng, depth)),
+                2 => format!("q {{{}}}", body(rng, depth.saturating_sub(1))),
+                _ => expr(rng, depth),
+            });
+        }
+        format!(" {} ", members.join("; "))
+    }
+
+    fn tokens(source: &str) -> Vec<(pkl_lexer::token::TokenKind, String)> {
+        use pkl_lexer::token::TokenKind;
+        let alloc = Allocator::default();
+        let tokens = pkl_lexer::Lexer::tokenize(&alloc, source).into_iter();
+        let separators = [TokenKind::Comma, TokenKind::Semicolon, TokenKind::LParen, TokenKind::RParen];
+        let tokens = tokens.filter(|token| !separators.contains(&token.kind));
+        tokens.map(|token| (token.kind, source[token.span.start..token.span.end].to_string())).collect()
+    }
+
+    /// Printing a parsed random module gives source that parses back into a tree that prints the same, and that has
+    /// the same tokens as the module, apart from the separators of members and the parentheses that are added where
+    /// precedence needs them.
+    #[test]
+    fn random_modules_roundtrip() {
+        let mut rng = Rng(0x0dd_ba11);
+        for _ in 0..300 {
+            let source = module(&mut rng);
+            let printed = roundtrip(&source);
+            assert_eq!(tokens(&printed), tokens(&source), "{source}");
+        }
+    }
+}
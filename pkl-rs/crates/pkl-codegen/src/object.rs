@@ -0,0 +1,153 @@
+use pkl_ast::*;
+
+use crate::Codegen;
+
+impl Codegen {
+    /// Prints `{ members }` with one member per line, or `{}` if it is empty.
+    pub(crate) fn print_object_body(&mut self, body: &ObjectBody) {
+        self.push("{");
+        if !body.params.is_empty() {
+            self.push(" ");
+            self.print_list(&body.params, Self::print_parameter);
+            self.push(" ->");
+        }
+        if body.members.is_empty() {
+            self.push(if body.params.is_empty() { "}" } else { " }" });
+            return;
+        }
+
+        self.indent();
+        for member in &body.members {
+            self.newline();
+            self.print_object_member(member);
+        }
+        self.dedent();
+        self.newline();
+        self.push("}");
+    }
+
+    fn print_object_member(&mut self, member: &ObjectMember) {
+        match member {
+            ObjectMember::Property(property) => {
+                self.print_modifiers(&property.modifiers);
+                self.print_ident(&property.name);
+                if let Some(ty) = &property.ty {
+                    self.push(": ");
+                    self.print_type(ty);
+                }
+                self.print_member_value(&property.value, &property.bodies);
+            }
+            ObjectMember::Method(method) => {
+                self.print_modifiers(&method.modifiers);
+                self.push("function ");
+                self.print_ident(&method.name);
+                self.push("(");
+                self.print_list(&method.params, Self::print_parameter);
+                self.push(")");
+                if let Some(ty) = &method.return_type {
+                    self.push(": ");
+                    self.print_type(ty);
+                }
+                self.push(" = ");
+                self.print_expr(&method.body, 0);
+            }
+            ObjectMember::Entry(entry) => {
+                self.push("[");
+                self.print_expr(&entry.key, 0);
+                self.push("]");
+                self.print_member_value(&entry.value, &entry.bodies);
+            }
+            ObjectMember::Element(element) => self.print_expr(&element.value, 0),
+            ObjectMember::MemberPredicate(predicate) => {
+                self.push("[[");
+                self.print_expr(&predicate.predicate, 0);
+                self.push("]]");
+                self.print_member_value(&predicate.value, &predicate.bodies);
+            }
+            ObjectMember::Spread(spread) => {
+                self.push(if spread.nullable { "...?" } else { "..." });
+                self.print_expr(&spread.value, 0);
+            }
+            ObjectMember::For(generator) => {
+                self.push("for (");
+                if let Some(key) = &generator.key {
+                    self.print_parameter(key);
+                    self.push(", ");
+                }
+                self.print_parameter(&generator.value);
+                self.push(" in ");
+                self.print_expr(&generator.iterable, 0);
+                self.push(") ");
+                self.print_object_body(&generator.body);
+            }
+            ObjectMember::When(generator) => {
+                self.push("when (");
+                self.print_expr(&generator.condition, 0);
+                self.push(") ");
+                self.print_object_body(&generator.body);
+                if let Some(body) = &generator.else_body {
+                    self.push(" else ");
+                    self.print_object_body(body);
+                }
+            }
+        }
+    }
+
+    /// Prints the ` = value` or ` { ... } { ... }` defining a property, entry, or member predicate.
+    pub(crate) fn print_member_value(&mut self, value: &Option<Expr>, bodies: &[ObjectBody]) {
+        if let Some(value) = value {
+            self.push(" = ");
+            self.print_expr(value, 0);
+        }
+        for body in bodies {
+            self.push(" ");
+            self.print_object_body(body);
+        }
+    }
+
+    /// Prints modifiers, each followed by a space.
+    pub(crate) fn print_modifiers(&mut self, modifiers: &Modifiers) {
+        for modifier in modifiers.0.iter() {
+            self.push(modifier.kind.as_str());
+            self.push(" ");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::roundtrip;
+
+    #[test]
+    fn members() {
+        let source = r#"config {
+  name = "app"
+  ["key"] {
+    enabled = true
+  }
+  42
+  [[this > 1]] = 2
+  local function double(n) = n * 2
+  items = new Listing<Int> {}
+  map { k, v -> }
+}
+"#;
+        assert_eq!(roundtrip(source), source);
+    }
+
+    #[test]
+    fn generators() {
+        let source = r#"a {
+  for (k, _ in m) {
+    [k] = 1
+  }
+  when (x) {
+    y = 1
+  } else {
+    y = 2
+  }
+}
+"#;
+        assert_eq!(roundtrip(source), source);
+    }
+}
@@ -0,0 +1,91 @@
+use pkl_ast::*;
+
+use crate::Codegen;
+
+impl Codegen {
+    pub(crate) fn print_type(&mut self, ty: &Type) {
+        match ty {
+            Type::Unknown(_) => self.push("unknown"),
+            Type::Nothing(_) => self.push("nothing"),
+            Type::Module(_) => self.push("module"),
+            Type::StringLiteral(literal) => self.print_string_constant(literal),
+            Type::Named(named) => {
+                self.print_qualified_name(&named.name);
+                if !named.args.is_empty() {
+                    self.push("<");
+                    self.print_list(&named.args, Self::print_type);
+                    self.push(">");
+                }
+            }
+            Type::Nullable(nullable) => {
+                self.print_operand_type(&nullable.inner);
+                self.push("?");
+            }
+            Type::Union(union) => {
+                for (index, member) in union.members.iter().enumerate() {
+                    if index > 0 {
+                        self.push(" | ");
+                    }
+                    if union.default == Some(index) {
+                        self.push("*");
+                    }
+                    self.print_operand_type(member);
+                }
+            }
+            Type::Function(function) => {
+                self.push("(");
+                self.print_list(&function.params, Self::print_type);
+                self.push(") -> ");
+                self.print_type(&function.ret);
+            }
+            Type::Constrained(constrained) => {
+                self.print_operand_type(&constrained.base);
+                self.push("(");
+                self.print_list(&constrained.constraints, |codegen, constraint| codegen.print_expr(constraint, 0));
+                self.push(")");
+            }
+            Type::Parenthesized(parenthesized) => {
+                self.push("(");
+                self.print_type(&parenthesized.inner);
+                self.push(")");
+            }
+        }
+    }
+
+    /// Prints a type that is part of a union, nullable, or constrained type, parenthesizing it if it is itself a
+    /// union or function type.
+    fn print_operand_type(&mut self, ty: &Type) {
+        if matches!(ty, Type::Union(_) | Type::Function(_)) {
+            self.push("(");
+            self.print_type(ty);
+            self.push(")");
+        } else {
+            self.print_type(ty);
+        }
+    }
+
+    pub(crate) fn print_qualified_name(&mut self, name: &QualifiedName) {
+        for (index, part) in name.parts.iter().enumerate() {
+            if index > 0 {
+                self.push(".");
+            }
+            self.print_ident(part);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::roundtrip;
+
+    #[test]
+    fn types() {
+        let source = r#"a: Mapping<String, Listing<Int?>>
+b: "x" | *"y" | ((Int) -> String)
+c: String(length > 3, !isEmpty)?
+d: ((Int) -> Int)?
+e: unknown | nothing | module
+"#;
+        assert_eq!(roundtrip(source), source);
+    }
+}
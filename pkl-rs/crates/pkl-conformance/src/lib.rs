@@ -0,0 +1,305 @@
+//! Runs the language snippet tests of Pkl against this implementation, to measure how much of the language it
+//! supports, and to catch regressions.
+//!
+//! A corpus is laid out like `LanguageSnippetTests` of the Pkl repository: every module under `input/` is a case,
+//! and what evaluating it results in is in the file at the same path under `output/`, with the extension `.pcf` for
+//! the rendered output, or `.err` for an error. Modules without one, like those of `input-helper/`, are only there to
+//! be imported. The rendered output has to match, apart from whitespace at the end; an error has to be an error, as
+//! the messages of this implementation aren't those of Pkl.
+//!
+//! Cases that fail because of what isn't supported yet are listed in an [`Allowlist`], and don't count as
+//! regressions. Once a case on the list passes, it should be taken off, so it counts as one too.
+//!
+//! `cargo test -p pkl-conformance` runs the sample corpus in `snippets/`, and the corpus of Pkl too if the
+//! `PKL_SNIPPET_TESTS` environment variable is its directory. The `pkl-conformance` binary runs any corpus and lists
+//! what fails.
+
+#![forbid(unsafe_code)]
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use pkl_diagnostics::Code;
+use pkl_eval::{Deprecations, Error, EvaluatorOptions};
+
+/// The allowlist of what the snippet tests of Pkl use that isn't supported yet.
+pub const KNOWN_UNSUPPORTED: &str = include_str!("../unsupported.txt");
+
+/// How long a case can take before it fails, so that one that loops doesn't keep the others from running.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The cases of a corpus, sorted by their names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Corpus {
+    pub cases: Vec<Case>,
+}
+
+/// A module under `input/`, with what evaluating it is expected to result in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// The path of the module under `input/`, without its extension, like `basic/strings`
+    pub name: String,
+    pub input: PathBuf,
+    pub expected: Expected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    /// The module renders as this text
+    Output(String),
+    /// Evaluating the module fails, with an error like this one of Pkl
+    Error(String),
+}
+
+impl Corpus {
+    /// The cases of the corpus in the directory `root`, which has `input/` and `output/` directories.
+    pub fn load(root: &Path) -> Result<Corpus, String> {
+        let mut modules = Vec::new();
+        find_modules(&root.join("input"), &mut modules)?;
+        let mut cases = Vec::new();
+        for input in modules {
+            let relative = input.strip_prefix(root.join("input")).unwrap_or(&input).with_extension("");
+            let name = relative.to_string_lossy().replace('\\', "/");
+            let output = root.join("output").join(&relative);
+            let read = |path: &Path| {
+                std::fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {err}", path.display()))
+            };
+            let expected = if output.with_extension("pcf").is_file() {
+                Expected::Output(read(&output.with_extension("pcf"))?)
+            } else if output.with_extension("err").is_file() {
+                Expected::Error(read(&output.with_extension("err"))?)
+            } else {
+                continue;
+            };
+            cases.push(Case { name, input, expected });
+        }
+        cases.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Corpus { cases })
+    }
+
+    /// Runs every case, checking the failures against `allowlist`.
+    pub fn run(&self, allowlist: &Allowlist) -> Report {
+        let results = self.cases.iter().map(|case| {
+            let outcome = match case.run() {
+                Ok(()) if allowlist.lists(case) => Outcome::Fixed,
+                Ok(()) => Outcome::Passed,
+                Err(failure) if allowlist.lists(case) || allowlist.allows(&failure) => {
+                    Outcome::Unsupported(failure.message)
+                }
+                Err(failure) => Outcome::Failed(failure.message),
+            };
+            (case.name.clone(), outcome)
+        });
+        Report { results: results.collect() }
+    }
+}
+
+fn find_modules(dir: &Path, modules: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|err| format!("couldn't read {}: {err}", dir.display()))?;
+    for entry in entries {
+        let path = entry.map_err(|err| format!("couldn't read {}: {err}", dir.display()))?.path();
+        if path.is_dir() {
+            find_modules(&path, modules)?;
+        } else if path.extension().is_some_and(|extension| extension == "pkl") {
+            modules.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Why a case failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Failure {
+    message: String,
+    /// Whether it failed with an error about what isn't supported yet
+    unsupported: bool,
+}
+
+impl Failure {
+    fn new(message: impl Into<String>) -> Self {
+        Failure { message: message.into(), unsupported: false }
+    }
+}
+
+impl Case {
+    fn run(&self) -> Result<(), Failure> {
+        let Ok(rendered) = panic::catch_unwind(AssertUnwindSafe(|| self.render())) else {
+            return Err(Failure::new("the evaluator panicked"));
+        };
+        match (&self.expected, rendered) {
+            (Expected::Output(expected), Ok(output)) => compare(expected, &output),
+            (Expected::Output(_), Err(failure)) => Err(failure),
+            (Expected::Error(_), Ok(_)) => Err(Failure::new("expected an error, but the module evaluated")),
+            (Expected::Error(_), Err(_)) => Ok(()),
+        }
+    }
+
+    /// The output of the module, rendered as `pkl eval` renders it.
+    fn render(&self) -> Result<String, Failure> {
+        let source = std::fs::read_to_string(&self.input)
+            .map_err(|err| Failure::new(format!("couldn't read {}: {err}", self.input.display())))?;
+        let path = self.input.canonicalize().unwrap_or_else(|_| self.input.clone());
+        let options = EvaluatorOptions {
+            timeout: Some(TIMEOUT),
+            deprecations: Deprecations::Ignore,
+            threads: 1,
+            ..EvaluatorOptions::default()
+        };
+        let output = pkl_eval::evaluate_output_with(&source, &format!("file://{}", path.display()), options)
+            .map_err(|error| {
+                let mut failure = Failure::new(error.to_string());
+                failure.unsupported = matches!(&error, Error::Eval(error) if error.code == Some(Code::Unsupported));
+                failure
+            })?;
+        pkl_render::render_output(&output, None).map_err(|error| Failure::new(error.to_string()))
+    }
+}
+
+/// Fails unless the rendered output is the expected one, apart from whitespace at the end, describing the first line
+/// that's different.
+fn compare(expected: &str, output: &str) -> Result<(), Failure> {
+    let (expected, output) = (expected.trim_end(), output.trim_end());
+    if expected == output {
+        return Ok(());
+    }
+    let mut lines = expected.lines().zip(output.lines()).enumerate();
+    let message = match lines.find(|(_, (expected, found))| expected != found) {
+        Some((i, (expected, found))) => format!("line {} is `{found}` rather than `{expected}`", i + 1),
+        None if output.lines().count() < expected.lines().count() => "the output is missing lines".to_string(),
+        None => "the output has more lines".to_string(),
+    };
+    Err(Failure::new(message))
+}
+
+/// The cases that are known to fail, each on a line of its own:
+///
+/// * the name of a case, like `objects/memberPredicates`, or a directory of them, like `objects/`;
+/// * `feature` and the start of the message of an error about what isn't supported yet, like
+///   `feature object body parameters` for "object body parameters aren't supported yet", which allows any case that
+///   fails with that error.
+///
+/// What follows a `#` is a comment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Allowlist {
+    cases: Vec<String>,
+    features: Vec<String>,
+}
+
+impl Allowlist {
+    pub fn parse(text: &str) -> Allowlist {
+        let mut allowlist = Allowlist::default();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            match line.strip_prefix("feature ") {
+                Some(feature) => allowlist.features.push(feature.trim().to_string()),
+                None if !line.is_empty() => allowlist.cases.push(line.to_string()),
+                None => {}
+            }
+        }
+        allowlist
+    }
+
+    /// Whether the case is listed, by its name or its directory.
+    fn lists(&self, case: &Case) -> bool {
+        self.cases.iter().any(|entry| match entry.strip_suffix('/') {
+            Some(dir) => case.name.starts_with(&format!("{dir}/")),
+            None => case.name == *entry,
+        })
+    }
+
+    /// Whether the failure is about a listed feature.
+    fn allows(&self, failure: &Failure) -> bool {
+        failure.unsupported && self.features.iter().any(|feature| failure.message.starts_with(feature.as_str()))
+    }
+}
+
+/// What happened with a case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// The case failed, and isn't allowed to, with why
+    Failed(String),
+    /// The case failed, as the allowlist expects, with why
+    Unsupported(String),
+    /// The case passed, but the allowlist expects it to fail
+    Fixed,
+}
+
+/// The outcome of every case of a corpus, by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub results: Vec<(String, Outcome)>,
+}
+
+impl Report {
+    /// The cases that failed without being allowed to, and those that passed despite being on the allowlist, which
+    /// should be taken off it.
+    pub fn regressions(&self) -> impl Iterator<Item = &(String, Outcome)> {
+        self.results.iter().filter(|(_, outcome)| matches!(outcome, Outcome::Failed(_) | Outcome::Fixed))
+    }
+
+    fn count(&self, matches: impl Fn(&Outcome) -> bool) -> usize {
+        self.results.iter().filter(|(_, outcome)| matches(outcome)).count()
+    }
+}
+
+impl fmt::Display for Report {
+    /// A summary like `40 passed, 2 failed, 7 unsupported, 1 fixed (80.0% passing)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let passed = self.count(|outcome| matches!(outcome, Outcome::Passed | Outcome::Fixed));
+        let failed = self.count(|outcome| matches!(outcome, Outcome::Failed(_)));
+        let unsupported = self.count(|outcome| matches!(outcome, Outcome::Unsupported(_)));
+        let fixed = self.count(|outcome| *outcome == Outcome::Fixed);
+        let share = if self.results.is_empty() { 0.0 } else { passed as f64 * 100.0 / self.results.len() as f64 };
+        write!(f, "{passed} passed, {failed} failed, {unsupported} unsupported, {fixed} fixed ({share:.1}% passing)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snippets() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("snippets")
+    }
+
+    #[test]
+    fn sample_corpus() {
+        let corpus = Corpus::load(&snippets()).unwrap();
+        let names: Vec<&str> = corpus.cases.iter().map(|case| case.name.as_str()).collect();
+        let expected = ["basic/arithmetic", "basic/strings", "errors/divisionByZero", "modules/imports"];
+        let objects = ["objects/amending", "objects/functionAmending", "objects/memberPredicates"];
+        assert_eq!(names, [&expected[..], &objects].concat());
+
+        let report = corpus.run(&Allowlist::parse(KNOWN_UNSUPPORTED));
+        assert_eq!(report.regressions().collect::<Vec<_>>(), Vec::<&(String, Outcome)>::new());
+        let message = "object body parameters aren't supported yet".to_string();
+        assert_eq!(report.results[5], ("objects/functionAmending".to_string(), Outcome::Unsupported(message)));
+        assert_eq!(report.to_string(), "6 passed, 0 failed, 1 unsupported, 0 fixed (85.7% passing)");
+
+        // without the allowlist, what isn't supported is a regression, and once it's listed, passing is too
+        let report = corpus.run(&Allowlist::parse("objects/amending\nbasic/"));
+        let regressions: Vec<&str> = report.regressions().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(regressions, ["basic/arithmetic", "basic/strings", "objects/amending", "objects/functionAmending"]);
+    }
+
+    #[test]
+    fn mismatched_output() {
+        let failure = compare("a = 1\nb = 2\n", "a = 1\nb = 3").unwrap_err();
+        assert_eq!(failure.message, "line 2 is `b = 3` rather than `b = 2`");
+        assert_eq!(compare("a = 1\nb = 2", "a = 1").unwrap_err().message, "the output is missing lines");
+        assert!(compare("a = 1\n\n", "a = 1\n").is_ok());
+    }
+
+    /// Runs the snippet tests of Pkl, from `pkl-core/src/test/files/LanguageSnippetTests` of its repository.
+    #[test]
+    fn official_corpus() {
+        let Some(dir) = std::env::var_os("PKL_SNIPPET_TESTS") else { return };
+        let report = Corpus::load(Path::new(&dir)).unwrap().run(&Allowlist::parse(KNOWN_UNSUPPORTED));
+        let regressions = report.regressions().map(|(name, outcome)| format!("{name}: {outcome:?}"));
+        let regressions: Vec<String> = regressions.collect();
+        assert!(regressions.is_empty(), "{report}\n{}", regressions.join("\n"));
+    }
+}
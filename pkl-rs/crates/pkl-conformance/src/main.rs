@@ -0,0 +1,45 @@
+//! `pkl-conformance CORPUS [ALLOWLIST]`, which runs the snippet tests in the directory `CORPUS`, lists the cases that
+//! regressed, and sums up the outcomes. Without `ALLOWLIST`, the one of this crate is used. The exit code is 1 if any
+//! case regressed.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use pkl_conformance::{Allowlist, Corpus, Outcome, KNOWN_UNSUPPORTED};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (corpus, allowlist) = match args.as_slice() {
+        [corpus] => (corpus, KNOWN_UNSUPPORTED.to_string()),
+        [corpus, allowlist] => match std::fs::read_to_string(allowlist) {
+            Ok(text) => (corpus, text),
+            Err(err) => return fail(&format!("couldn't read {allowlist}: {err}")),
+        },
+        _ => return fail("usage: pkl-conformance CORPUS [ALLOWLIST]"),
+    };
+    let corpus = match Corpus::load(Path::new(corpus)) {
+        Ok(corpus) => corpus,
+        Err(message) => return fail(&message),
+    };
+
+    let report = corpus.run(&Allowlist::parse(&allowlist));
+    let mut regressed = false;
+    for (name, outcome) in report.regressions() {
+        regressed = true;
+        match outcome {
+            Outcome::Failed(message) => println!("FAILED {name}: {message}"),
+            _ => println!("FIXED  {name}: it passes, so it can be taken off the allowlist"),
+        }
+    }
+    println!("{report}");
+    if regressed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn fail(message: &str) -> ExitCode {
+    eprintln!("pkl-conformance: {message}");
+    ExitCode::from(2)
+}
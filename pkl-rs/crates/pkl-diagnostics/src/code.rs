@@ -0,0 +1,161 @@
+//! The stable codes of the kinds of problems found in Pkl source.
+
+use std::fmt;
+
+/// A kind of problem, shown as `E` and its number, like `E0002`.
+///
+/// The numbers of the codes never change and aren't reused, so that they can be looked up: `E00xx` are found by the
+/// lexer, `E01xx` by the parser, and `E02xx` while evaluating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Code {
+    UnexpectedCharacter = 1,
+    UnterminatedString = 2,
+    UnterminatedInterpolation = 3,
+    UnterminatedBlockComment = 4,
+    UnterminatedQuotedIdentifier = 5,
+    InvalidEscape = 6,
+    InvalidMultilineString = 7,
+    MissingDigits = 8,
+    IntegerTooLarge = 9,
+
+    UnexpectedToken = 100,
+    InvalidModifier = 101,
+    InvalidDefaultMember = 102,
+    InterpolatedConstant = 103,
+    NestedTooDeeply = 104,
+
+    TypeMismatch = 200,
+    ConstraintViolation = 201,
+    UnknownProperty = 202,
+    UnknownMethod = 203,
+    UnknownClass = 204,
+    CircularReference = 205,
+    DuplicateDefinition = 206,
+    DivisionByZero = 207,
+    IntegerOverflow = 208,
+    UndefinedOperator = 209,
+    MissingValue = 210,
+    Unsupported = 211,
+    AccessDenied = 212,
+    Timeout = 213,
+    Thrown = 214,
+    NullValue = 215,
+    InvalidImport = 216,
+    RecursionLimit = 217,
+    Deprecated = 218,
+    DeprecatedWarning = 219,
+}
+
+impl Code {
+    /// Every code, in the order of their numbers
+    pub const ALL: [Code; 34] = [
+        Code::UnexpectedCharacter,
+        Code::UnterminatedString,
+        Code::UnterminatedInterpolation,
+        Code::UnterminatedBlockComment,
+        Code::UnterminatedQuotedIdentifier,
+        Code::InvalidEscape,
+        Code::InvalidMultilineString,
+        Code::MissingDigits,
+        Code::IntegerTooLarge,
+        Code::UnexpectedToken,
+        Code::InvalidModifier,
+        Code::InvalidDefaultMember,
+        Code::InterpolatedConstant,
+        Code::NestedTooDeeply,
+        Code::TypeMismatch,
+        Code::ConstraintViolation,
+        Code::UnknownProperty,
+        Code::UnknownMethod,
+        Code::UnknownClass,
+        Code::CircularReference,
+        Code::DuplicateDefinition,
+        Code::DivisionByZero,
+        Code::IntegerOverflow,
+        Code::UndefinedOperator,
+        Code::MissingValue,
+        Code::Unsupported,
+        Code::AccessDenied,
+        Code::Timeout,
+        Code::Thrown,
+        Code::NullValue,
+        Code::InvalidImport,
+        Code::RecursionLimit,
+        Code::Deprecated,
+        Code::DeprecatedWarning,
+    ];
+
+    pub fn number(self) -> u16 {
+        self as u16
+    }
+
+    /// The code with a number, as in `E0002`.
+    pub fn parse(code: &str) -> Option<Code> {
+        let number: u16 = code.strip_prefix('E').filter(|number| number.len() == 4)?.parse().ok()?;
+        Code::ALL.into_iter().find(|code| code.number() == number)
+    }
+
+    /// What kind of problem it is, in a few words.
+    pub fn title(self) -> &'static str {
+        match self {
+            Code::UnexpectedCharacter => "unexpected character",
+            Code::UnterminatedString => "unterminated string literal",
+            Code::UnterminatedInterpolation => "unterminated string interpolation",
+            Code::UnterminatedBlockComment => "unterminated block comment",
+            Code::UnterminatedQuotedIdentifier => "unterminated quoted identifier",
+            Code::InvalidEscape => "invalid escape sequence",
+            Code::InvalidMultilineString => "invalid multiline string",
+            Code::MissingDigits => "missing digits in a numeric literal",
+            Code::IntegerTooLarge => "integer literal is too large",
+            Code::UnexpectedToken => "unexpected token",
+            Code::InvalidModifier => "invalid modifier",
+            Code::InvalidDefaultMember => "invalid default member of a union type",
+            Code::InterpolatedConstant => "interpolation in a string constant",
+            Code::NestedTooDeeply => "source nested too deeply",
+            Code::TypeMismatch => "value of the wrong type",
+            Code::ConstraintViolation => "value that doesn't satisfy a type constraint",
+            Code::UnknownProperty => "unknown property",
+            Code::UnknownMethod => "unknown method",
+            Code::UnknownClass => "unknown class",
+            Code::CircularReference => "circular reference",
+            Code::DuplicateDefinition => "duplicate definition",
+            Code::DivisionByZero => "division by zero",
+            Code::IntegerOverflow => "integer overflow",
+            Code::UndefinedOperator => "operator isn't defined for its operands",
+            Code::MissingValue => "member without a value",
+            Code::Unsupported => "unsupported feature",
+            Code::AccessDenied => "module or resource that may not be read",
+            Code::Timeout => "evaluation timed out",
+            Code::Thrown => "error thrown by `throw`",
+            Code::NullValue => "unexpected `null`",
+            Code::InvalidImport => "imported module with errors",
+            Code::RecursionLimit => "method calls or members nested too deeply",
+            Code::Deprecated => "use of a deprecated member",
+            Code::DeprecatedWarning => "use of a deprecated member, warned about",
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.number())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn codes_are_distinct_and_parse() {
+        let mut numbers: Vec<u16> = Code::ALL.iter().map(|code| code.number()).collect();
+        numbers.sort();
+        numbers.dedup();
+        assert_eq!(numbers.len(), Code::ALL.len());
+
+        assert_eq!(Code::UnterminatedString.to_string(), "E0002");
+        assert_eq!(Code::parse("E0213"), Some(Code::Timeout));
+        assert_eq!(Code::parse("E213"), None);
+        assert_eq!(Code::parse("E9999"), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Reports of problems in Pkl source, rendered for terminals with the lines they point at.
+//!
+//! The lexer, parser, and evaluator each describe what they find in their own terms; a [`Report`] is what they have
+//! in common: a message with a stable [`Code`], the place it's about, other places it involves, and notes. The
+//! [`Renderer`] prints a report the way compilers do, with an excerpt of the source under a heading like
+//! `error[E0002]: unterminated string literal`, or as a line of JSON for tools that read them.
+
+#![forbid(unsafe_code)]
+
+pub mod code;
+pub mod render;
+
+use std::ops::Range;
+
+pub use code::Code;
+pub use render::{Format, Renderer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    /// Something worth knowing that isn't a problem, like the value of a traced expression
+    Info,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A place in the source, as a range of bytes, with what it has to do with the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Range<usize>,
+    /// What's at the place; empty if the report's message says it all
+    pub message: String,
+}
+
+/// A problem found in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub severity: Severity,
+    pub code: Option<Code>,
+    pub message: String,
+    /// Where the problem is, unless it isn't about any place in particular
+    pub primary: Option<Label>,
+    /// Other places the problem involves
+    pub labels: Vec<Label>,
+    /// Explanations or hints that don't point at any place
+    pub notes: Vec<String>,
+}
+
+impl Report {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Report { severity, code: None, message: message.into(), primary: None, labels: Vec::new(), notes: Vec::new() }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Report::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Report::new(Severity::Warning, message)
+    }
+
+    pub fn with_code(mut self, code: Option<Code>) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Sets where the problem is.
+    pub fn with_primary(mut self, span: Range<usize>, message: impl Into<String>) -> Self {
+        self.primary = Some(Label { span, message: message.into() });
+        self
+    }
+
+    /// Adds a label pointing at another place the problem involves.
+    pub fn with_label(mut self, span: Range<usize>, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into() });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+}
@@ -0,0 +1,303 @@
+//! Rendering reports as text, with the lines of source they point at, or as JSON for tools to read.
+
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+use crate::{Label, Report, Severity};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Renders reports like this, underlining the place a report is about with `^` and the other places it involves
+/// with `-`:
+///
+/// ```text
+/// error[E0201]: `80` doesn't satisfy the constraints of type `Int(isBetween(0, 10))`
+///  --> config.pkl:2:8
+///   |
+/// 1 | port: Int(isBetween(0, 10))
+///   |           ---------------- this constraint is `false`
+/// 2 | port = 80
+///   |        ^^
+/// ```
+///
+/// With [`Format::Json`], each report is a line of JSON instead; see [`Renderer::render_json`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Renderer {
+    /// Whether to color the output with ANSI escapes
+    pub color: bool,
+    /// Whether to render reports as text or JSON
+    pub format: Format,
+}
+
+/// How a [`Renderer`] renders reports.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// For people, with excerpts of the source
+    #[default]
+    Text,
+    /// For tools, a line of JSON for each report
+    Json,
+}
+
+impl Renderer {
+    /// A renderer for standard error, which colors the output if it's a terminal and `NO_COLOR` isn't set.
+    pub fn stderr() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Renderer { color: std::io::stderr().is_terminal() && !no_color, format: Format::Text }
+    }
+
+    /// Renders a report about `source`, a file called `name`, ending in a line break.
+    pub fn render(&self, report: &Report, name: &str, source: &str) -> String {
+        if self.format == Format::Json {
+            return self.render_json(report, name, source) + "\n";
+        }
+        let lines = Lines::new(source);
+        let mut out = String::new();
+
+        let severity = match report.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Info => BLUE,
+        };
+        let heading = match report.code {
+            Some(code) => format!("{}[{code}]", report.severity.as_str()),
+            None => report.severity.as_str().to_string(),
+        };
+        let message = self.paint(BOLD, &format!(": {}", report.message));
+        let _ = writeln!(out, "{}{message}", self.paint(severity, &heading));
+
+        let mut labels: Vec<(&Label, bool)> = report.primary.iter().map(|label| (label, true)).collect();
+        labels.extend(report.labels.iter().map(|label| (label, false)));
+        let last_line = labels.iter().map(|(label, _)| lines.position(label.span.start).0).max();
+        let width = last_line.map_or(0, |line| (line + 1).to_string().len());
+        let gutter = self.paint(BLUE, &format!("{:width$} |", ""));
+
+        if let Some((first, _)) = labels.first() {
+            let (line, col) = lines.position(first.span.start);
+            let arrow = self.paint(BLUE, &format!("{:width$}-->", ""));
+            let _ = writeln!(out, "{arrow} {name}:{}:{}", line + 1, col + 1);
+            let _ = writeln!(out, "{gutter}");
+
+            labels.sort_by_key(|(label, _)| (label.span.start, label.span.end));
+            let mut previous = None;
+            for (label, primary) in &labels {
+                let (line, col) = lines.position(label.span.start);
+                let text = lines.text(line);
+                if previous != Some(line) {
+                    if previous.is_some_and(|previous| line > previous + 1) {
+                        let _ = writeln!(out, "{}", self.paint(BLUE, "..."));
+                    }
+                    let number = self.paint(BLUE, &format!("{:>width$} |", line + 1));
+                    let _ = writeln!(out, "{number} {text}");
+                    previous = Some(line);
+                }
+
+                // tabs are kept, so that the marks line up with the text above them however wide tabs are shown
+                let indent: String = text[..col].chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+                let end = (label.span.end.saturating_sub(label.span.start)).min(text.len() - col) + col;
+                let length = text[col..end].chars().count().max(1);
+                let (mark, style) = if *primary { ('^', severity) } else { ('-', BLUE) };
+                let marks = mark.to_string().repeat(length);
+                let marks = match label.message.as_str() {
+                    "" => self.paint(style, &marks),
+                    message => self.paint(style, &format!("{marks} {message}")),
+                };
+                let _ = writeln!(out, "{gutter} {indent}{marks}");
+            }
+        }
+
+        for note in &report.notes {
+            let _ = writeln!(out, "{:width$} {} {note}", "", self.paint(BOLD, "= note:"));
+        }
+        out
+    }
+
+    /// Renders a report about `source`, a file called `name`, as one line of JSON, an object like this:
+    ///
+    /// ```json
+    /// {"severity":"error","code":"E0201","message":"`80` doesn't satisfy the constraints of type `Int(isBetween(0,
+    /// 10))`","file":"config.pkl","span":{"start":35,"end":37,"line":2,"column":8,"endLine":2,"endColumn":10},
+    /// "label":"","related":[{"span":{"start":10,"end":26,"line":1,"column":11,"endLine":1,"endColumn":27},
+    /// "message":"this constraint is `false`"}],"notes":[]}
+    /// ```
+    ///
+    /// (broken over lines here, to fit)
+    ///
+    /// `code` is `null` for a report without one, `file` for one that isn't about a file, and `span` and `label` for
+    /// one that isn't about any place in particular. Spans are ranges of bytes, with the 1-based lines and columns,
+    /// in bytes, of where they start and end.
+    pub fn render_json(&self, report: &Report, name: &str, source: &str) -> String {
+        let lines = Lines::new(source);
+        let span = |label: &Label| {
+            let (line, column) = lines.position(label.span.start);
+            let (end_line, end_column) = lines.position(label.span.end);
+            format!(
+                "{{\"start\":{},\"end\":{},\"line\":{},\"column\":{},\"endLine\":{},\"endColumn\":{}}}",
+                label.span.start,
+                label.span.end,
+                line + 1,
+                column + 1,
+                end_line + 1,
+                end_column + 1
+            )
+        };
+        let code = report.code.map_or("null".to_string(), |code| string(&code.to_string()));
+        let file = if name.is_empty() { "null".to_string() } else { string(name) };
+        let (primary, label) = match &report.primary {
+            Some(primary) => (span(primary), string(&primary.message)),
+            None => ("null".to_string(), "null".to_string()),
+        };
+        let related = report.labels.iter().map(|label| {
+            format!("{{\"span\":{},\"message\":{}}}", span(label), string(&label.message))
+        });
+        let notes = report.notes.iter().map(|note| string(note));
+        format!(
+            "{{\"severity\":\"{}\",\"code\":{code},\"message\":{},\"file\":{file},\"span\":{primary},\"label\":{label},\
+             \"related\":[{}],\"notes\":[{}]}}",
+            report.severity.as_str(),
+            string(&report.message),
+            related.collect::<Vec<_>>().join(","),
+            notes.collect::<Vec<_>>().join(",")
+        )
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// A JSON string with the text of `value`.
+fn string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Where the lines of a source start, and `\n`, `\r\n`, and `\r` end them, the same as for the lexer.
+struct Lines<'s> {
+    source: &'s str,
+    starts: Vec<usize>,
+}
+
+impl<'s> Lines<'s> {
+    fn new(source: &'s str) -> Self {
+        let mut starts = vec![0];
+        let bytes = source.as_bytes();
+        for (offset, &byte) in bytes.iter().enumerate() {
+            let ends_line = byte == b'\n' || (byte == b'\r' && bytes.get(offset + 1) != Some(&b'\n'));
+            if ends_line {
+                starts.push(offset + 1);
+            }
+        }
+        Lines { source, starts }
+    }
+
+    /// The 0-based line and byte column of an offset, which is clamped to the source.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        (line, (offset - self.starts[line]).min(self.text(line).len()))
+    }
+
+    /// The text of a line, without its terminator.
+    fn text(&self, line: usize) -> &'s str {
+        let end = self.starts.get(line + 1).copied().unwrap_or(self.source.len());
+        self.source[self.starts[line]..end].trim_end_matches(['\n', '\r'])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Code;
+
+    #[test]
+    fn renders_code_frames() {
+        let source = "port: Int(isBetween(0, 10))\n\nname = \"x\"\nport = 80\n";
+        let report = Report::error("`80` doesn't satisfy the constraints of type `Int(isBetween(0, 10))`")
+            .with_code(Some(Code::ConstraintViolation))
+            .with_primary(source.find("80").unwrap()..source.find("80").unwrap() + 2, "")
+            .with_label(10..26, "this constraint is `false`")
+            .with_note("the value is checked when it's read");
+        let expected = "\
+error[E0201]: `80` doesn't satisfy the constraints of type `Int(isBetween(0, 10))`
+ --> config.pkl:4:8
+  |
+1 | port: Int(isBetween(0, 10))
+  |           ---------------- this constraint is `false`
+...
+4 | port = 80
+  |        ^^
+  = note: the value is checked when it's read
+";
+        assert_eq!(Renderer::default().render(&report, "config.pkl", source), expected);
+    }
+
+    #[test]
+    fn renders_edge_cases() {
+        // a span at the end of the input, and one over several lines
+        let source = "a = \"x\r\nb = 1";
+        let report = Report::error("unterminated string literal").with_primary(source.len()..source.len(), "here");
+        let expected = "error: unterminated string literal\n --> a.pkl:2:6\n  |\n2 | b = 1\n  |      ^ here\n";
+        assert_eq!(Renderer::default().render(&report, "a.pkl", source), expected);
+
+        let report = Report::warning("spans lines").with_primary(4..source.len(), "");
+        let rendered = Renderer::default().render(&report, "a.pkl", source);
+        assert!(rendered.ends_with("1 | a = \"x\n  |     ^^\n"), "{rendered}");
+
+        let report = Report::error("no place").with_code(Some(Code::Timeout)).with_note("it took too long");
+        let rendered = Renderer { color: true, format: Format::Text }.render(&report, "a.pkl", source);
+        let heading = format!("{RED}error[E0213]{RESET}{BOLD}: no place{RESET}\n");
+        assert_eq!(rendered, format!("{heading} {BOLD}= note:{RESET} it took too long\n"));
+    }
+
+    #[test]
+    fn renders_json() {
+        let source = "port: Int(isBetween(0, 10))\nport = 80\n";
+        let report = Report::error("`80` doesn't satisfy the constraints of type `Int(isBetween(0, 10))`")
+            .with_code(Some(Code::ConstraintViolation))
+            .with_primary(35..37, "")
+            .with_label(10..26, "this constraint is `false`");
+        let expected = concat!(
+            r#"{"severity":"error","code":"E0201","message":"`80` doesn't satisfy the constraints of type "#,
+            r#"`Int(isBetween(0, 10))`","file":"config.pkl","span":{"start":35,"end":37,"line":2,"column":8,"#,
+            r#""endLine":2,"endColumn":10},"label":"","related":[{"span":{"start":10,"end":26,"line":1,"column":11,"#,
+            r#""endLine":1,"endColumn":27},"message":"this constraint is `false`"}],"notes":[]}"#,
+            "\n"
+        );
+        let renderer = Renderer { color: true, format: Format::Json };
+        assert_eq!(renderer.render(&report, "config.pkl", source), expected);
+
+        let report = Report::warning("a \"quoted\"\tmessage\u{1}").with_note("a note");
+        let expected = concat!(
+            r#"{"severity":"warning","code":null,"message":"a \"quoted\"\tmessage\u0001","file":null,"span":null,"#,
+            r#""label":null,"related":[],"notes":["a note"]}"#
+        );
+        assert_eq!(renderer.render_json(&report, "", ""), expected);
+    }
+}
@@ -0,0 +1,167 @@
+//! Renders documentation as standalone HTML pages, with an anchor for each class and member, like
+//! `birds.html#Bird.name`.
+
+use crate::{summary, ClassDoc, ModuleDoc};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }\n\
+                     code { background: #f4f4f4; padding: 0 0.2em; }\n\
+                     .declaration { font-family: monospace; font-size: 1.1em; }\n\
+                     .annotation { color: #777; }";
+
+/// The page of a module.
+pub fn module_page(module: &ModuleDoc) -> String {
+    let mut body = format!("<h1><code>{}</code></h1>\n", escape(&module.name));
+    body.push_str(&doc(&module.doc));
+    let mut header = module.annotations.clone();
+    if !module.modifiers.is_empty() {
+        header.push(format!("{} module", module.modifiers.join(" ")));
+    }
+    header.extend(module.extends.clone());
+    if !header.is_empty() {
+        let header: Vec<String> = header.iter().map(|part| format!("<code>{}</code>", escape(part))).collect();
+        body.push_str(&format!("<p>{}</p>\n", header.join(" ")));
+    }
+
+    if !module.properties.is_empty() {
+        body.push_str("<h2>Properties</h2>\n");
+        for property in &module.properties {
+            body.push_str(&member(&property.name, &property.declaration(), &property.annotations, &property.doc));
+        }
+    }
+    if !module.methods.is_empty() {
+        body.push_str("<h2>Methods</h2>\n");
+        for method in &module.methods {
+            let id = format!("{}()", method.name);
+            body.push_str(&member(&id, &method.declaration(), &method.annotations, &method.doc));
+        }
+    }
+    for class in &module.classes {
+        class_section(&mut body, class);
+    }
+    if !module.aliases.is_empty() {
+        body.push_str("<h2>Typealiases</h2>\n");
+        for alias in &module.aliases {
+            let id = alias.name.split('<').next().unwrap_or_default();
+            body.push_str(&member(id, &alias.declaration(), &alias.annotations, &alias.doc));
+        }
+    }
+    page(&module.name, &body)
+}
+
+/// The index of the modules, with the first sentence of the doc comment of each, linking to their pages at
+/// `<name>.html`.
+pub fn index(modules: &[ModuleDoc]) -> String {
+    let mut body = String::from("<h1>Modules</h1>\n<ul>\n");
+    for module in modules {
+        let name = escape(&module.name);
+        body.push_str(&format!("<li><a href=\"{name}.html\"><code>{name}</code></a>"));
+        if let Some(doc) = &module.doc {
+            body.push_str(&format!(": {}", inline(summary(doc))));
+        }
+        body.push_str("</li>\n");
+    }
+    body.push_str("</ul>\n");
+    page("Modules", &body)
+}
+
+fn class_section(body: &mut String, class: &ClassDoc) {
+    let id = class.name.split('<').next().unwrap_or_default();
+    body.push_str(&format!("<h2 id=\"{}\"><code>{}</code></h2>\n", escape(id), escape(&class.declaration())));
+    for annotation in &class.annotations {
+        body.push_str(&format!("<p class=\"annotation\"><code>{}</code></p>\n", escape(annotation)));
+    }
+    body.push_str(&doc(&class.doc));
+    if !class.superclasses.is_empty() {
+        let chain = class.superclasses.iter().map(|class| format!("<code>{}</code>", escape(class)));
+        let chain: Vec<String> = chain.collect();
+        body.push_str(&format!("<p>Extends {}</p>\n", chain.join(" → ")));
+    }
+    if !class.subclasses.is_empty() {
+        let link = |class: &String| format!("<a href=\"#{0}\"><code>{0}</code></a>", escape(class));
+        let subclasses = class.subclasses.iter().map(link);
+        let subclasses: Vec<String> = subclasses.collect();
+        body.push_str(&format!("<p>Known subclasses: {}</p>\n", subclasses.join(", ")));
+    }
+    for property in &class.properties {
+        let id = format!("{id}.{}", property.name);
+        body.push_str(&member(&id, &property.declaration(), &property.annotations, &property.doc));
+    }
+    for method in &class.methods {
+        let id = format!("{id}.{}()", method.name);
+        body.push_str(&member(&id, &method.declaration(), &method.annotations, &method.doc));
+    }
+}
+
+fn member(id: &str, declaration: &str, annotations: &[String], text: &Option<String>) -> String {
+    let mut html = format!("<h3 id=\"{}\" class=\"declaration\">{}</h3>\n", escape(id), escape(declaration));
+    for annotation in annotations {
+        html.push_str(&format!("<p class=\"annotation\"><code>{}</code></p>\n", escape(annotation)));
+    }
+    html.push_str(&doc(text));
+    html
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{STYLE}\n</style>\n\
+         </head>\n<body>\n{body}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// A doc comment as paragraphs, one for each run of lines without a blank one.
+fn doc(text: &Option<String>) -> String {
+    let Some(text) = text else { return String::new() };
+    text.split("\n\n").map(|paragraph| format!("<p>{}</p>\n", inline(paragraph.trim()))).collect()
+}
+
+/// Text with its `code` spans.
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    for (index, part) in text.split('`').enumerate() {
+        // the odd parts are between backticks, unless the last backtick isn't closed
+        if index % 2 == 1 && text.matches('`').count() > index {
+            html.push_str(&format!("<code>{}</code>", escape(part)));
+        } else if index % 2 == 1 {
+            html.push_str(&format!("`{}", escape(part)));
+        } else {
+            html.push_str(&escape(part));
+        }
+    }
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::document;
+
+    #[test]
+    fn pages() {
+        let source = "/// Birds, in a `Listing`.\n///\n/// Or <none>.\nbirds: Listing<Bird>\n\
+                      class Bird { name: String }";
+        let module = document(source, "birds.pkl").unwrap();
+        let page = module_page(&module);
+        assert!(page.starts_with("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>birds</title>"));
+        let birds = "<h3 id=\"birds\" class=\"declaration\">birds: Listing&lt;Bird&gt;</h3>\n\
+                     <p>Birds, in a <code>Listing</code>.</p>\n<p>Or &lt;none&gt;.</p>\n";
+        assert!(page.contains(birds), "{page}");
+        assert!(page.contains("<h3 id=\"Bird.name\" class=\"declaration\">name: String</h3>\n"), "{page}");
+        assert!(index(&[module]).contains("<li><a href=\"birds.html\"><code>birds</code></a></li>\n"));
+        assert_eq!(inline("a `b` `c"), "a <code>b</code> `c");
+    }
+}
@@ -0,0 +1,386 @@
+//! Generates documentation for Pkl modules, like `pkldoc`: a page for each module with its `///` doc comments, and
+//! the types, modifiers, and annotations of its properties, methods, classes, and typealiases, and an index of the
+//! modules.
+//!
+//! A module is first read into a [`ModuleDoc`], which [`markdown`] and [`html`] render. Doc comments are written in
+//! Markdown, so Markdown pages keep them as they are, and HTML pages show them as paragraphs of text with their
+//! `code` spans. Types are printed as they're written. Local members are private to their module, so they're left
+//! out.
+//!
+//! ```
+//! let source = "/// Birds.\nmodule birds\n\n/// A bird.\nclass Bird {\n  /// Its name.\n  name: String\n}";
+//! let module = pkl_doc::document(source, "birds.pkl").unwrap();
+//! let page = pkl_doc::markdown::module_page(&module);
+//!
+//! assert!(page.starts_with("# `birds`\n\nBirds.\n"));
+//! assert!(page.contains("### `name: String`\n\nIts name.\n"));
+//! ```
+
+#![forbid(unsafe_code)]
+
+pub mod html;
+pub mod markdown;
+
+use std::collections::HashMap;
+
+use oxc_allocator::Allocator;
+use pkl_ast::{
+    Annotation, ClassDecl, ClassMember, ClassMethod, ClassProperty, DocComment, ExtendsKind, Modifiers, Module,
+    ModuleMember, ModifierKind, Type, TypeAlias, TypeParameter, Variance,
+};
+use pkl_codegen::Codegen;
+use pkl_lexer::diagnostic::Diagnostic;
+
+/// The documentation of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleDoc {
+    /// The name of its `module` clause, or of its file without the extension
+    pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<String>,
+    pub modifiers: Vec<&'static str>,
+    /// Its `amends` or `extends` clause, like `amends "pkl:Project"`
+    pub extends: Option<String>,
+    pub properties: Vec<PropertyDoc>,
+    pub methods: Vec<MethodDoc>,
+    pub classes: Vec<ClassDoc>,
+    pub aliases: Vec<AliasDoc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassDoc {
+    /// The name with its type parameters, like `Box<out T>`
+    pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<String>,
+    pub modifiers: Vec<&'static str>,
+    /// The classes it extends, from its parent up, as far as they're declared in the module, and the first one
+    /// that isn't
+    pub superclasses: Vec<String>,
+    /// The names of the classes of the module that extend it directly
+    pub subclasses: Vec<String>,
+    pub properties: Vec<PropertyDoc>,
+    pub methods: Vec<MethodDoc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyDoc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<String>,
+    pub modifiers: Vec<&'static str>,
+    /// The declared type, if it has one
+    pub ty: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodDoc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<String>,
+    pub modifiers: Vec<&'static str>,
+    /// Its type parameters, parameters, and return type, like `<T>(value: T): List<T>`
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasDoc {
+    /// The name with its type parameters
+    pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<String>,
+    pub ty: String,
+}
+
+impl ClassDoc {
+    /// How the class is declared, like `open class Bird`.
+    pub fn declaration(&self) -> String {
+        format!("{}class {}", prefix(&self.modifiers), self.name)
+    }
+}
+
+impl PropertyDoc {
+    /// How the property is declared, like `hidden name: String`.
+    pub fn declaration(&self) -> String {
+        match &self.ty {
+            Some(ty) => format!("{}{}: {ty}", prefix(&self.modifiers), self.name),
+            None => format!("{}{}", prefix(&self.modifiers), self.name),
+        }
+    }
+}
+
+impl MethodDoc {
+    /// How the method is declared, like `function greet(name: String): String`.
+    pub fn declaration(&self) -> String {
+        format!("{}function {}{}", prefix(&self.modifiers), self.name, self.signature)
+    }
+}
+
+impl AliasDoc {
+    /// How the typealias is declared, like `typealias Birds = Listing<Bird>`.
+    pub fn declaration(&self) -> String {
+        format!("typealias {} = {}", self.name, self.ty)
+    }
+}
+
+/// The modifiers of a declaration, each followed by a space.
+fn prefix(modifiers: &[&str]) -> String {
+    modifiers.iter().map(|modifier| format!("{modifier} ")).collect()
+}
+
+/// Parses a module and reads its documentation, or returns its syntax errors. `file` is the name of its file, which
+/// names it if it has no `module` clause.
+pub fn document(source: &str, file: &str) -> Result<ModuleDoc, Vec<Diagnostic>> {
+    let alloc = Allocator::default();
+    let result = pkl_parser::parse_module(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        return Err(result.diagnostics);
+    }
+    Ok(document_module(&result.node, source, file))
+}
+
+/// Reads the documentation of a parsed module, whose source is `source`.
+pub fn document_module(module: &Module, source: &str, file: &str) -> ModuleDoc {
+    let header = &module.header;
+    let name = match &header.name {
+        Some(name) => name.parts.iter().map(|part| part.name).collect::<Vec<_>>().join("."),
+        None => {
+            let file = file.rsplit(['/', '\\']).next().unwrap_or(file);
+            file.strip_suffix(".pkl").unwrap_or(file).to_string()
+        }
+    };
+    let extends = header.extends.as_ref().map(|extends| {
+        let keyword = if extends.kind == ExtendsKind::Amends { "amends" } else { "extends" };
+        format!("{keyword} \"{}\"", extends.uri.value)
+    });
+
+    let classes: Vec<&ClassDecl> = module
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ModuleMember::Class(class) if !class.modifiers.has(ModifierKind::Local) => Some(class),
+            _ => None,
+        })
+        .collect();
+    let parents: HashMap<&str, String> = classes
+        .iter()
+        .filter_map(|class| Some((class.name.name, type_text(class.extends.as_ref()?))))
+        .collect();
+
+    let mut doc = ModuleDoc {
+        name,
+        doc: text(header.doc.as_ref()),
+        annotations: annotations(&header.annotations, source),
+        modifiers: modifiers(&header.modifiers),
+        extends,
+        properties: Vec::new(),
+        methods: Vec::new(),
+        classes: classes.iter().map(|class| class_doc(class, &classes, &parents, source)).collect(),
+        aliases: Vec::new(),
+    };
+    for member in &module.members {
+        match member {
+            ModuleMember::Property(property) if !property.modifiers.has(ModifierKind::Local) => {
+                doc.properties.push(property_doc(property, source));
+            }
+            ModuleMember::Method(method) if !method.modifiers.has(ModifierKind::Local) => {
+                doc.methods.push(method_doc(method, source));
+            }
+            ModuleMember::TypeAlias(alias) if !alias.modifiers.has(ModifierKind::Local) => {
+                doc.aliases.push(alias_doc(alias, source));
+            }
+            _ => {}
+        }
+    }
+    doc
+}
+
+fn class_doc(class: &ClassDecl, classes: &[&ClassDecl], parents: &HashMap<&str, String>, source: &str) -> ClassDoc {
+    // the chain ends at a class declared elsewhere, or at a cycle, which evaluation reports
+    let mut superclasses: Vec<String> = Vec::new();
+    let mut current = class.name.name;
+    while let Some(parent) = parents.get(current) {
+        if superclasses.contains(parent) || parent == class.name.name {
+            break;
+        }
+        superclasses.push(parent.clone());
+        current = parent.split('<').next().unwrap_or(parent);
+    }
+    let subclasses = classes
+        .iter()
+        .filter(|other| {
+            let parent = parents.get(other.name.name);
+            parent.is_some_and(|parent| parent.split('<').next() == Some(class.name.name))
+        })
+        .map(|other| other.name.name.to_string())
+        .collect();
+
+    let mut doc = ClassDoc {
+        name: format!("{}{}", class.name.name, type_params(&class.type_params)),
+        doc: text(class.doc.as_ref()),
+        annotations: annotations(&class.annotations, source),
+        modifiers: modifiers(&class.modifiers),
+        superclasses,
+        subclasses,
+        properties: Vec::new(),
+        methods: Vec::new(),
+    };
+    for member in &class.members {
+        match member {
+            ClassMember::Property(property) if !property.modifiers.has(ModifierKind::Local) => {
+                doc.properties.push(property_doc(property, source));
+            }
+            ClassMember::Method(method) if !method.modifiers.has(ModifierKind::Local) => {
+                doc.methods.push(method_doc(method, source));
+            }
+            _ => {}
+        }
+    }
+    doc
+}
+
+fn property_doc(property: &ClassProperty, source: &str) -> PropertyDoc {
+    PropertyDoc {
+        name: property.name.name.to_string(),
+        doc: text(property.doc.as_ref()),
+        annotations: annotations(&property.annotations, source),
+        modifiers: modifiers(&property.modifiers),
+        ty: property.ty.as_ref().map(type_text),
+    }
+}
+
+fn method_doc(method: &ClassMethod, source: &str) -> MethodDoc {
+    let params: Vec<String> = method
+        .params
+        .iter()
+        .map(|param| {
+            let name = param.name.as_ref().map_or("_", |name| name.name);
+            match &param.ty {
+                Some(ty) => format!("{name}: {}", type_text(ty)),
+                None => name.to_string(),
+            }
+        })
+        .collect();
+    let mut signature = format!("{}({})", type_params(&method.type_params), params.join(", "));
+    if let Some(ty) = &method.return_type {
+        signature.push_str(&format!(": {}", type_text(ty)));
+    }
+    MethodDoc {
+        name: method.name.name.to_string(),
+        doc: text(method.doc.as_ref()),
+        annotations: annotations(&method.annotations, source),
+        modifiers: modifiers(&method.modifiers),
+        signature,
+    }
+}
+
+fn alias_doc(alias: &TypeAlias, source: &str) -> AliasDoc {
+    AliasDoc {
+        name: format!("{}{}", alias.name.name, type_params(&alias.type_params)),
+        doc: text(alias.doc.as_ref()),
+        annotations: annotations(&alias.annotations, source),
+        ty: type_text(&alias.ty),
+    }
+}
+
+/// The text of a doc comment, without the space after each `///`.
+fn text(doc: Option<&DocComment>) -> Option<String> {
+    let lines: Vec<&str> = doc?.lines.iter().map(|line| line.strip_prefix(' ').unwrap_or(line)).collect();
+    Some(lines.join("\n").trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// The annotations as they're written, like `@Deprecated { since = "1.2" }`.
+fn annotations(annotations: &[Annotation], source: &str) -> Vec<String> {
+    annotations.iter().map(|annotation| source[annotation.span.start..annotation.span.end].to_string()).collect()
+}
+
+fn modifiers(modifiers: &Modifiers) -> Vec<&'static str> {
+    modifiers.0.iter().map(|modifier| modifier.kind.as_str()).collect()
+}
+
+fn type_text(ty: &Type) -> String {
+    Codegen::new(Default::default()).build_type(ty)
+}
+
+fn type_params(params: &[TypeParameter]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| match param.variance {
+            Some(Variance::In) => format!("in {}", param.name.name),
+            Some(Variance::Out) => format!("out {}", param.name.name),
+            None => param.name.name.to_string(),
+        })
+        .collect();
+    format!("<{}>", params.join(", "))
+}
+
+/// The first sentence of a doc comment, for summaries like those of the index.
+pub fn summary(doc: &str) -> &str {
+    let paragraph = doc.split("\n\n").next().unwrap_or_default();
+    match paragraph.find(". ") {
+        Some(end) => &paragraph[..=end],
+        None => paragraph,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn members() {
+        let source = r#"
+            /// Birds and where they live.
+            @ModuleInfo { minPklVersion = "0.25.0" }
+            open module zoo.birds
+
+            amends "pkl:Project"
+
+            /// The birds.
+            fixed birds: Listing<Bird>
+            local secret = 1
+
+            /// Names a bird.
+            function name<T>(bird: Bird, _): String = bird.name
+
+            abstract class Animal
+            @Deprecated
+            open class Bird extends Animal
+            class Pigeon extends Bird {
+              hidden wingspan: Float?
+              local id = 1
+            }
+            typealias Birds<out T> = Listing<T>(!isEmpty)
+        "#;
+        let module = document(source, "zoo/birds.pkl").unwrap();
+        assert_eq!(module.name, "zoo.birds");
+        assert_eq!(module.doc.as_deref(), Some("Birds and where they live."));
+        assert_eq!(module.annotations, ["@ModuleInfo { minPklVersion = \"0.25.0\" }"]);
+        assert_eq!(module.modifiers, ["open"]);
+        assert_eq!(module.extends.as_deref(), Some("amends \"pkl:Project\""));
+        assert_eq!(module.properties.len(), 1);
+        assert_eq!(module.properties[0].declaration(), "fixed birds: Listing<Bird>");
+        assert_eq!(module.properties[0].modifiers, ["fixed"]);
+        assert_eq!(module.methods[0].declaration(), "function name<T>(bird: Bird, _): String");
+
+        let [animal, bird, pigeon] = &module.classes[..] else { panic!() };
+        assert_eq!(animal.subclasses, ["Bird"]);
+        assert_eq!(bird.annotations, ["@Deprecated"]);
+        assert_eq!(pigeon.superclasses, ["Bird", "Animal"]);
+        assert_eq!(pigeon.properties.len(), 1);
+        assert_eq!(pigeon.properties[0].declaration(), "hidden wingspan: Float?");
+        assert_eq!(module.aliases[0].name, "Birds<out T>");
+        assert_eq!(module.aliases[0].ty, "Listing<T>(!isEmpty)");
+    }
+
+    #[test]
+    fn names_and_summaries() {
+        assert_eq!(document("x = 1", "configs/birds.pkl").unwrap().name, "birds");
+        assert!(document("x = ", "birds.pkl").is_err());
+        assert_eq!(summary("Birds. And where they live.\n\nMore."), "Birds.");
+        assert_eq!(summary("Birds and where they live"), "Birds and where they live");
+    }
+}
@@ -0,0 +1,110 @@
+//! Renders documentation as Markdown, with a heading for the module, each of its classes, and each member.
+
+use crate::{summary, AliasDoc, ClassDoc, MethodDoc, ModuleDoc, PropertyDoc};
+
+/// The page of a module.
+pub fn module_page(module: &ModuleDoc) -> String {
+    let mut page = format!("# `{}`\n\n", module.name);
+    if let Some(doc) = &module.doc {
+        page.push_str(&format!("{doc}\n\n"));
+    }
+    let mut header = module.annotations.clone();
+    if !module.modifiers.is_empty() {
+        header.push(format!("{} module", module.modifiers.join(" ")));
+    }
+    header.extend(module.extends.clone());
+    if !header.is_empty() {
+        let header: Vec<String> = header.iter().map(|part| format!("`{part}`")).collect();
+        page.push_str(&format!("{}\n\n", header.join(" ")));
+    }
+
+    section(&mut page, "Properties", module.properties.iter().map(property));
+    section(&mut page, "Methods", module.methods.iter().map(method));
+    for class in &module.classes {
+        class_section(&mut page, class);
+    }
+    section(&mut page, "Typealiases", module.aliases.iter().map(alias));
+    format!("{}\n", page.trim_end())
+}
+
+/// The index of the modules, with the first sentence of the doc comment of each, linking to their pages at
+/// `<name>.md`.
+pub fn index(modules: &[ModuleDoc]) -> String {
+    let mut page = String::from("# Modules\n\n");
+    for module in modules {
+        page.push_str(&format!("- [`{0}`]({0}.md)", module.name));
+        if let Some(doc) = &module.doc {
+            page.push_str(&format!(": {}", summary(doc).replace('\n', " ")));
+        }
+        page.push('\n');
+    }
+    page
+}
+
+fn class_section(page: &mut String, class: &ClassDoc) {
+    page.push_str(&format!("## `{}`\n\n", class.declaration()));
+    for annotation in &class.annotations {
+        page.push_str(&format!("`{annotation}`\n\n"));
+    }
+    if let Some(doc) = &class.doc {
+        page.push_str(&format!("{doc}\n\n"));
+    }
+    if !class.superclasses.is_empty() {
+        let chain: Vec<String> = class.superclasses.iter().map(|class| format!("`{class}`")).collect();
+        page.push_str(&format!("Extends {}\n\n", chain.join(" → ")));
+    }
+    if !class.subclasses.is_empty() {
+        let subclasses: Vec<String> = class.subclasses.iter().map(|class| format!("`{class}`")).collect();
+        page.push_str(&format!("Known subclasses: {}\n\n", subclasses.join(", ")));
+    }
+    page.extend(class.properties.iter().map(property));
+    page.extend(class.methods.iter().map(method));
+}
+
+fn section(page: &mut String, title: &str, members: impl Iterator<Item = String>) {
+    let members: String = members.collect();
+    if !members.is_empty() {
+        page.push_str(&format!("## {title}\n\n{members}"));
+    }
+}
+
+fn property(property: &PropertyDoc) -> String {
+    member(&property.declaration(), &property.annotations, &property.doc)
+}
+
+fn method(method: &MethodDoc) -> String {
+    member(&method.declaration(), &method.annotations, &method.doc)
+}
+
+fn alias(alias: &AliasDoc) -> String {
+    member(&alias.declaration(), &alias.annotations, &alias.doc)
+}
+
+fn member(declaration: &str, annotations: &[String], doc: &Option<String>) -> String {
+    let mut text = format!("### `{declaration}`\n\n");
+    for annotation in annotations {
+        text.push_str(&format!("`{annotation}`\n\n"));
+    }
+    if let Some(doc) = doc {
+        text.push_str(&format!("{doc}\n\n"));
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::document;
+
+    #[test]
+    fn pages() {
+        let source = "/// Birds. Many of them.\nmodule birds\n/// Some birds.\nhidden birds: Listing<Bird>\n\
+                      @Deprecated\nclass Bird { function fly(): Boolean = true }\nclass Pigeon extends Bird";
+        let module = document(source, "birds.pkl").unwrap();
+        let page = module_page(&module);
+        assert!(page.contains("## Properties\n\n### `hidden birds: Listing<Bird>`\n\nSome birds.\n\n"), "{page}");
+        assert!(page.contains("## `class Bird`\n\n`@Deprecated`\n\nKnown subclasses: `Pigeon`\n\n"), "{page}");
+        assert!(page.contains("### `function fly(): Boolean`\n\n## `class Pigeon`\n\nExtends `Bird`\n"), "{page}");
+        assert_eq!(index(&[module]), "# Modules\n\n- [`birds`](birds.md): Birds.\n");
+    }
+}
@@ -0,0 +1,67 @@
+/* The C API of pkl-eval, built with its `ffi` feature; see `src/ffi.rs` for who owns what. */
+
+#ifndef PKL_H
+#define PKL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct PklEvaluator PklEvaluator;
+typedef struct PklValue PklValue;
+
+typedef enum PklKind {
+  PKL_NULL = 0,
+  PKL_BOOLEAN,
+  PKL_INT,
+  PKL_FLOAT,
+  PKL_STRING,
+  PKL_DURATION,
+  PKL_DATA_SIZE,
+  PKL_LIST,
+  PKL_SET,
+  PKL_MAP,
+  PKL_OBJECT,
+  PKL_FUNCTION,
+  PKL_CLASS,
+  PKL_REGEX,
+} PklKind;
+
+PklEvaluator *pkl_evaluator_new(void);
+void pkl_evaluator_set_property(PklEvaluator *evaluator, const char *name, const char *value);
+void pkl_evaluator_set_timeout(PklEvaluator *evaluator, uint64_t seconds);
+
+/* Return NULL on failure, setting `*error` (unless `error` is NULL) to a message to free with `pkl_free_string`. */
+PklValue *pkl_eval_file(const PklEvaluator *evaluator, const char *path, char **error);
+PklValue *pkl_eval_text(const PklEvaluator *evaluator, const char *source, char **error);
+
+PklKind pkl_value_kind(const PklValue *value);
+bool pkl_value_boolean(const PklValue *value);
+int64_t pkl_value_int(const PklValue *value);
+double pkl_value_float(const PklValue *value);
+/* Returns NULL for a string with a null character in it, setting `*error` like evaluating does. */
+char *pkl_value_string(const PklValue *value, char **error);
+
+/* The values returned by these are borrowed from `value`, and live as long as it does. */
+size_t pkl_value_element_count(const PklValue *value);
+const PklValue *pkl_value_element(const PklValue *value, size_t index);
+size_t pkl_value_property_count(const PklValue *value);
+char *pkl_value_property_name(const PklValue *value, size_t index, char **error);
+const PklValue *pkl_value_property(const PklValue *value, const char *name);
+size_t pkl_value_entry_count(const PklValue *value);
+const PklValue *pkl_value_entry_key(const PklValue *value, size_t index);
+const PklValue *pkl_value_entry_value(const PklValue *value, size_t index);
+
+void pkl_free_evaluator(PklEvaluator *evaluator);
+void pkl_free_value(PklValue *value);
+void pkl_free_string(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,40 @@
+//! The methods of `pkl:base` that can be called without a receiver, like `List(...)`.
+
+use std::rc::Rc;
+
+use pkl_ast::Span;
+use pkl_stdlib::regex::Regex;
+
+use crate::equality::{distinct, Entries};
+use crate::error::{EvalError, Result};
+use crate::runtime::Val;
+
+/// Calls the base method `name`, or returns `None` if there is no such method.
+pub(crate) fn call<'a>(span: Span, name: &str, args: Vec<Val<'a>>) -> Option<Result<Val<'a>>> {
+    let value = match name {
+        "List" => Ok(Val::List(args.into())),
+        "Set" => Ok(Val::Set(distinct(args).into())),
+        "Map" => {
+            if !args.len().is_multiple_of(2) {
+                let message = "`Map` takes an even number of arguments, alternating keys and values";
+                return Some(Err(EvalError::new(span, message)));
+            }
+            let mut entries = Entries::with_capacity(args.len() / 2);
+            let mut args = args.into_iter();
+            while let (Some(key), Some(value)) = (args.next(), args.next()) {
+                entries.insert(key, value);
+            }
+            Ok(entries.into_val())
+        }
+        "Regex" => match <[Val; 1]>::try_from(args) {
+            Ok([Val::String(pattern)]) => match Regex::new(&pattern) {
+                Ok(regex) => Ok(Val::Regex(Rc::new(regex))),
+                Err(error) => Err(EvalError::new(span, error.message)),
+            },
+            _ => Err(EvalError::new(span, "`Regex` takes a single `String` argument")),
+        },
+        _ => return None,
+    };
+
+    Some(value)
+}
@@ -0,0 +1,377 @@
+//! Classes and the default values of types.
+//!
+//! A class is evaluated like an object: `new Foo { ... }` amends a prototype whose layers hold the properties and
+//! methods of `Foo` and its superclasses, root class first. A class of an imported module, like `birds.Bird`, can be
+//! instantiated, extended, and given as a property type the same way, and its members are evaluated in its module.
+//! Property declarations without a value take the default of their type, such as `null` for `String?` or an empty
+//! listing for `Listing<Int>`. The elements of a `Listing<Bird>` and the values of a `Mapping<String, Bird>` amend a
+//! `Bird` by default, like `Bird` properties do.
+
+use std::rc::Rc;
+
+use pkl_ast::{ClassDecl, ClassMember, ClassMethod, ClassProperty, ModifierKind, NamedType, Span, Type};
+use pkl_diagnostics::Code;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::{duplicate, Evaluator, DEFAULT};
+use crate::runtime::{Def, Env, Frame, Key, Member, Method, Obj, Val};
+use crate::value::ObjectKind;
+
+impl<'a> Evaluator<'a> {
+    /// The class that a type like `Bird`, or `birds.Bird` where `birds` is an imported module, names, along with the
+    /// module declaring it, if it names one.
+    pub(crate) fn named_class(
+        &self,
+        named: &NamedType<'a>,
+        env: &Rc<Env<'a>>,
+    ) -> Result<Option<(&'a ClassDecl<'a>, Rc<Obj<'a>>)>> {
+        let (module, name) = match named.name.parts.as_slice() {
+            [name] => (module_of(env).clone(), name.name),
+            [module, name] => match self.resolve(module, env)? {
+                Some(Val::Object(module)) => (module, name.name),
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let class = find_class(&module, name).map(|(_, class)| class);
+        Ok(class.map(|class| (class, module)))
+    }
+
+    /// Creates an object holding the members of the class `name` of the module of `env` for instances to amend.
+    pub(crate) fn class_prototype(&self, span: Span, name: &str, env: &Rc<Env<'a>>) -> Result<Rc<Obj<'a>>> {
+        let module = module_of(env);
+        let Some((_, class)) = find_class(module, name) else {
+            return Err(EvalError::new(span, format!("can't find class `{name}`")).with_code(Code::UnknownClass));
+        };
+        self.prototype(span, class, module)
+    }
+
+    /// Creates an object holding the members of a class declared by `module` for instances to amend.
+    pub(crate) fn prototype(&self, span: Span, class: &'a ClassDecl<'a>, module: &Rc<Obj<'a>>) -> Result<Rc<Obj<'a>>> {
+        // the class and its superclasses, along with the modules declaring them
+        let mut chain: Vec<(&'a ClassDecl<'a>, Rc<Obj<'a>>)> = Vec::new();
+        let mut next = Some((span, class, module.clone()));
+        while let Some((span, class, module)) = next {
+            let name = class.name.name;
+            if chain.iter().any(|(subclass, _)| std::ptr::eq(*subclass, class)) {
+                return Err(EvalError::new(span, format!("class `{name}` extends itself")));
+            }
+            if chain.is_empty() && class.modifiers.has(ModifierKind::Abstract) {
+                return Err(EvalError::new(span, format!("can't instantiate abstract class `{name}`")));
+            }
+            if !chain.is_empty() && !is_open(class) {
+                let message = format!("class `{name}` can't be extended because it isn't `open` or `abstract`");
+                return Err(EvalError::new(span, message));
+            }
+            next = match &class.extends {
+                None => None,
+                // the superclass is named in the scope of the module declaring the class, like `birds.Bird`
+                Some(Type::Named(named)) => match self.named_class(named, &module_scope(&module, class))? {
+                    Some((superclass, module)) => Some((named.span, superclass, module)),
+                    None => return Err(unknown_class(named)),
+                },
+                Some(ty) => return Err(EvalError::new(ty.span(), "a class can only extend a class")),
+            };
+            chain.push((class, module));
+        }
+
+        let mut prototype = None;
+        for (class, module) in chain.into_iter().rev() {
+            let env = module_scope(&module, class);
+            let mut object = Obj::new(ObjectKind::Typed(class.name.name.to_string()), prototype.take(), Some(env));
+            object.class = Some(class);
+            for member in class.members.iter() {
+                match member {
+                    ClassMember::Property(property) => declare_property(&mut object, property)?,
+                    ClassMember::Method(method) => declare_method(&mut object, method),
+                }
+            }
+            prototype = Some(Rc::new(object));
+        }
+
+        Ok(prototype.expect("the chain includes the class itself"))
+    }
+
+    /// The value of a property declared with the type `ty` that isn't given a value, if the type has one.
+    pub(crate) fn type_default(&self, ty: &'a Type<'a>, env: &Rc<Env<'a>>) -> Result<Option<Val<'a>>> {
+        let empty = |kind| Some(Val::Object(Rc::new(Obj::new(kind, None, None))));
+
+        Ok(match ty {
+            Type::Nullable(_) => Some(Val::Null),
+            Type::Parenthesized(ty) => return self.type_default(&ty.inner, env),
+            Type::Constrained(ty) => return self.type_default(&ty.base, env),
+            Type::Union(union) => match union.default {
+                Some(index) => return self.type_default(&union.members[index], env),
+                None => None,
+            },
+            Type::Named(named) => match named.name.parts.as_slice() {
+                [name] if name.name == "Dynamic" => empty(ObjectKind::Dynamic),
+                [name] if name.name == "Listing" => {
+                    Some(self.typed_collection(ObjectKind::Listing, named.args.first(), env)?)
+                }
+                [name] if name.name == "Mapping" => {
+                    Some(self.typed_collection(ObjectKind::Mapping, named.args.get(1), env)?)
+                }
+                _ => match self.named_class(named, env)? {
+                    Some((class, module)) if !class.modifiers.has(ModifierKind::Abstract) => {
+                        Some(Val::Object(self.prototype(named.span, class, &module)?))
+                    }
+                    _ => None,
+                },
+            },
+            _ => None,
+        })
+    }
+
+    /// The value a `new { ... }` without a type amends when it's the value of a property declared with the type `ty`:
+    /// the default of the type, even if it's nullable, or a `Dynamic` if it doesn't have a default object.
+    pub(crate) fn object_default(&self, ty: &'a Type<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        let ty = match ty {
+            Type::Nullable(nullable) => &nullable.inner,
+            ty => ty,
+        };
+        Ok(match self.type_default(ty, env)? {
+            Some(value @ Val::Object(_)) => value,
+            _ => Val::Object(Rc::new(Obj::new(ObjectKind::Dynamic, None, None))),
+        })
+    }
+
+    /// An empty listing or mapping whose elements or entries amend the default of their type, `element`, if it has a
+    /// default object.
+    fn typed_collection(
+        &self,
+        kind: ObjectKind,
+        element: Option<&'a Type<'a>>,
+        env: &Rc<Env<'a>>,
+    ) -> Result<Val<'a>> {
+        let mut collection = Obj::new(kind, None, None);
+        if let Some(element) = element {
+            if let Some(default @ Val::Object(_)) = self.type_default(element, env)? {
+                let member = Member { span: element.span(), def: Def::Value(default) };
+                collection.members.push((Key::Property(DEFAULT.into()), member));
+            }
+        }
+        Ok(Val::Object(Rc::new(collection)))
+    }
+}
+
+/// Adds a property declared by a module or class to its layer.
+pub(crate) fn declare_property<'a>(layer: &mut Obj<'a>, property: &'a ClassProperty<'a>) -> Result<()> {
+    let name: Rc<str> = property.name.name.into();
+    let key = if property.modifiers.has(ModifierKind::Local) { Key::Local(name) } else { Key::Property(name) };
+    if layer.own_member(&key).is_some() {
+        return Err(duplicate(property.name.span, property.name.name));
+    }
+
+    // a property overriding a `fixed` or `const` one has to keep the modifier
+    if let Some(parent) = &layer.parent {
+        for kind in [ModifierKind::Fixed, ModifierKind::Const] {
+            if parent.modifier(&key, kind).is_some() && !property.modifiers.has(kind) {
+                let kind = kind.as_str();
+                let name = property.name.name;
+                let message = format!("property `{name}` overrides a `{kind}` property, so it has to be `{kind}` too");
+                return Err(EvalError::new(property.name.span, message));
+            }
+        }
+    }
+
+    let def = Def::Expr {
+        value: property.value.as_ref(),
+        bodies: &property.bodies,
+        ty: property.ty.as_ref(),
+        modifiers: &property.modifiers.0,
+        deprecated: property.annotations.iter().find(|annotation| annotation.is_deprecated()),
+        bindings: None,
+    };
+    layer.members.push((key, Member { span: property.name.span, def }));
+    Ok(())
+}
+
+/// Adds a method declared by a module or class to its layer.
+pub(crate) fn declare_method<'a>(layer: &mut Obj<'a>, method: &'a ClassMethod<'a>) {
+    layer.methods.push(Method {
+        span: method.span,
+        name: method.name.name,
+        local: method.modifiers.has(ModifierKind::Local),
+        type_params: &method.type_params,
+        params: &method.params,
+        body: method.body.as_ref(),
+        deprecated: method.annotations.iter().find(|annotation| annotation.is_deprecated()),
+    });
+}
+
+/// The scope that the members of a class declared by `module` are evaluated in.
+fn module_scope<'a>(module: &Rc<Obj<'a>>, class: &ClassDecl<'a>) -> Rc<Env<'a>> {
+    let layer = module.layers().find(|layer| layer.classes.iter().any(|declared| std::ptr::eq(*declared, class)));
+    Env::new(Frame::Object { this: module.clone(), layer: layer.unwrap_or(module).clone() }, None)
+}
+
+pub(crate) fn unknown_class(named: &NamedType) -> EvalError {
+    let name = named.name.parts.iter().map(|part| part.name).collect::<Vec<_>>().join(".");
+    EvalError::new(named.span, format!("can't find class `{name}`")).with_code(Code::UnknownClass)
+}
+
+/// The module that a scope is nested in.
+pub(crate) fn module_of<'e, 'a>(env: &'e Rc<Env<'a>>) -> &'e Rc<Obj<'a>> {
+    env.receivers().last().expect("evaluation happens inside a module")
+}
+
+/// The class `name` declared by a module, along with the layer of the module declaring it.
+pub(crate) fn find_class<'m, 'a>(module: &'m Rc<Obj<'a>>, name: &str) -> Option<(&'m Rc<Obj<'a>>, &'a ClassDecl<'a>)> {
+    module.layers().find_map(|layer| {
+        let class = layer.classes.iter().find(|class| class.name.name == name)?;
+        Some((layer, *class))
+    })
+}
+
+fn is_open(class: &ClassDecl) -> bool {
+    class.modifiers.has(ModifierKind::Open) || class.modifiers.has(ModifierKind::Abstract)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::value::{ObjectKind, Value};
+    use crate::{evaluate, Error};
+
+    fn property(source: &str, name: &str) -> Value {
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{source}: {error:?}"));
+        module.as_object().unwrap().property(name).cloned().unwrap_or_else(|| panic!("{name} is missing"))
+    }
+
+    fn error(source: &str) -> String {
+        match evaluate(source) {
+            Err(Error::Eval(error)) => error.message,
+            result => panic!("{source}: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn classes() {
+        let source = "class Bird {\n  name: String\n  age = 1\n  function greet() = \"hi \\(name)\"\n}\n\
+                      bird = new Bird { name = \"Pigeon\" }\n\
+                      greeting = bird.greet()";
+        let Value::Object(bird) = property(source, "bird") else { panic!() };
+        assert_eq!(bird.kind, ObjectKind::Typed("Bird".into()));
+        assert_eq!(bird.properties.keys().collect::<Vec<_>>(), ["name", "age"]);
+        assert_eq!(property(source, "greeting"), Value::String("hi Pigeon".into()));
+
+        assert_eq!(error("class Bird { name: String }\nbird = new Bird {}"), "property `name` has no value");
+        assert_eq!(error("class Bird {}\nbird = new Bird { name = 1 }"), "class `Bird` has no property `name`");
+    }
+
+    #[test]
+    fn inheritance() {
+        let source = "open class Animal {\n  legs = 4\n  sound = \"...\"\n\
+                      function describe() = \"\\(legs) legs\"\n}\n\
+                      class Bird extends Animal {\n  legs = 2\n  fly = true\n}\n\
+                      bird = new Bird {}\n\
+                      description = bird.describe()";
+        let Value::Object(bird) = property(source, "bird") else { panic!() };
+        assert_eq!(bird.properties.keys().collect::<Vec<_>>(), ["legs", "sound", "fly"]);
+        assert_eq!(bird.property("legs"), Some(&Value::Int(2)));
+        assert_eq!(property(source, "description"), Value::String("2 legs".into()));
+
+        let source = "open class A {\n  function f() = 1\n}\nclass B extends A {\n  function f() = super.f() + 1\n}\n\
+                      x = new B {}.f()";
+        assert_eq!(property(source, "x"), Value::Int(2));
+
+        assert!(error("class A {}\nclass B extends A {}\nb = new B {}").contains("isn't `open`"));
+        assert_eq!(error("abstract class A {}\na = new A {}"), "can't instantiate abstract class `A`");
+        let source = "open class A extends B {}\nopen class B extends A {}\na = new A {}";
+        assert_eq!(error(source), "class `A` extends itself");
+    }
+
+    #[test]
+    fn modifiers() {
+        let source = "open class Bird {\n  name: String\n  hidden secret = \"shh\"\n  fixed legs = 2\n\
+                      local id = 7\n  tag = \"\\(secret)-\\(id)\"\n}\n\
+                      hidden internal = 1\n\
+                      bird = new Bird { name = \"Pigeon\" }\n\
+                      exposed = bird.secret + internal.toString()";
+        let module = evaluate(source).unwrap();
+        let module = module.as_object().unwrap();
+        assert_eq!(module.property("internal"), None);
+        assert_eq!(module.property("exposed"), Some(&Value::String("shh1".into())));
+        let Some(Value::Object(bird)) = module.property("bird") else { panic!() };
+        assert_eq!(bird.properties.keys().collect::<Vec<_>>(), ["name", "legs", "tag"]);
+        assert_eq!(bird.property("tag"), Some(&Value::String("shh-7".into())));
+
+        let class = "open class Bird {\n  fixed legs = 2\n  const kind = \"bird\"\n}\n";
+        assert_eq!(error(&format!("{class}b = new Bird {{ legs = 1 }}")), "can't assign to `fixed` property `legs`");
+        let message = "can't assign to `const` property `kind`";
+        assert_eq!(error(&format!("{class}b = new Bird {{ kind = \"x\" }}")), message);
+        assert_eq!(
+            error(&format!("{class}class Kiwi extends Bird {{\n  legs = 1\n}}\nk = new Kiwi {{}}")),
+            "property `legs` overrides a `fixed` property, so it has to be `fixed` too"
+        );
+        let source = format!("{class}class Kiwi extends Bird {{\n  fixed legs = 1\n}}\nk = new Kiwi {{}}");
+        let Value::Object(kiwi) = property(&source, "k") else { panic!() };
+        assert_eq!(kiwi.property("legs"), Some(&Value::Int(1)));
+    }
+
+    #[test]
+    fn dynamic_and_typed_objects() {
+        let source = "class Bird {\n  name: String\n  legs: Int = 2\n}\n\
+                      pigeon = new Dynamic { name = \"pigeon\" }.toTyped(Bird)\n\
+                      dynamic = pigeon.toDynamic()\n\
+                      className = Bird.simpleName";
+        let Value::Object(pigeon) = property(source, "pigeon") else { panic!() };
+        assert_eq!(pigeon.kind, ObjectKind::Typed("Bird".into()));
+        assert_eq!(pigeon.property("legs"), Some(&Value::Int(2)));
+        let Value::Object(dynamic) = property(source, "dynamic") else { panic!() };
+        assert_eq!(dynamic.kind, ObjectKind::Dynamic);
+        assert_eq!(dynamic.properties.keys().collect::<Vec<_>>(), ["name", "legs"]);
+        assert_eq!(property(source, "className"), Value::String("Bird".into()));
+
+        let source = "class Bird { name: String }\nbird = new Dynamic { name = 1; age = 2 }.toTyped(Bird)";
+        assert_eq!(error(source), "class `Bird` has no property `age`");
+        let source = "class Bird { name: String }\nbird = new Dynamic { name = 1 }.toTyped(Bird).name";
+        assert_eq!(error(source), "expected a value of type `String`, but got `Int`");
+        let source = "class Bird { name: String }\nname = new Bird { name = \"a\" }.age";
+        assert_eq!(error(source), "can't find property `age` on a value of type `Bird`");
+    }
+
+    #[test]
+    fn imported_classes() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-classes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("birds.pkl"), "open class Bird {\n  name: String\n  legs = 2\n}").unwrap();
+        let uri = format!("file://{}/main.pkl", dir.display());
+        let evaluate = |source: &str| crate::evaluate_at(&format!("import \"birds.pkl\"\n{source}"), &uri);
+        let source = "class Parrot extends birds.Bird { talks = true }\n\
+                      parrot = new Parrot { name = \"polly\" }\n\
+                      bird = new birds.Bird { name = \"x\" }\n\
+                      b: birds.Bird = new { name = \"y\" }\n\
+                      tests = List(parrot is birds.Bird, b is birds.Bird)";
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{error:?}"));
+        let module = module.as_object().unwrap();
+        let Some(Value::Object(parrot)) = module.property("parrot") else { panic!() };
+        assert_eq!(parrot.kind, ObjectKind::Typed("Parrot".into()));
+        assert_eq!(parrot.properties.keys().collect::<Vec<_>>(), ["name", "legs", "talks"]);
+        let Some(Value::Object(b)) = module.property("b") else { panic!() };
+        assert_eq!((&b.kind, b.property("legs")), (&ObjectKind::Typed("Bird".into()), Some(&Value::Int(2))));
+        assert_eq!(module.property("tests"), Some(&Value::List(vec![Value::Boolean(true); 2])));
+
+        let error = |source: &str| evaluate(source).unwrap_err().to_string();
+        assert_eq!(error("owl = new birds.Owl {}"), "can't find class `birds.Owl`");
+        assert_eq!(error("class Owl extends birds.Owl {}\nowl = new Owl {}"), "can't find class `birds.Owl`");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn type_defaults() {
+        let source = "class Config {\n  host: String?\n  ports: Listing<Int>\n  server: Server\n}\n\
+                      class Server { port = 80 }\n\
+                      config: Config\n\
+                      amended: Config = (config) { server { port = 443 } }";
+        let Value::Object(config) = property(source, "config") else { panic!() };
+        assert_eq!(config.property("host"), Some(&Value::Null));
+        let Some(Value::Object(ports)) = config.property("ports") else { panic!() };
+        assert_eq!(ports.kind, ObjectKind::Listing);
+
+        let Value::Object(amended) = property(source, "amended") else { panic!() };
+        let Some(Value::Object(server)) = amended.property("server") else { panic!() };
+        assert_eq!(server.kind, ObjectKind::Typed("Server".into()));
+        assert_eq!(server.property("port"), Some(&Value::Int(443)));
+    }
+}
@@ -0,0 +1,657 @@
+//! The built-in members of collections (`List`, `Set`, and `Map`), of `Listing`, `Mapping`, and `Dynamic` objects,
+//! and of functions.
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use pkl_ast::Span;
+use pkl_diagnostics::Code;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::equality::{distinct, values_equal, Entries};
+use crate::methods::{string_val, Call};
+use crate::runtime::{Def, Key, Lambda, Member, Obj, Val};
+use crate::units;
+use crate::value::ObjectKind;
+
+/// Whether the elements of a sequence are those of a `List` or a `Set`, which decides what methods return.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sequence {
+    List,
+    Set,
+}
+
+impl Sequence {
+    fn collect<'a>(self, elements: Vec<Val<'a>>) -> Val<'a> {
+        match self {
+            Sequence::List => Val::List(elements.into()),
+            Sequence::Set => Val::Set(distinct(elements).into()),
+        }
+    }
+}
+
+impl<'a> Evaluator<'a> {
+    pub(crate) fn collection_property(&self, span: Span, receiver: &Val<'a>, name: &str) -> Result<Option<Val<'a>>> {
+        let value = match receiver {
+            Val::List(elements) | Val::Set(elements) => {
+                return sequence_property(span, receiver.type_name(), sequence(receiver), elements, name);
+            }
+            Val::Map(entries) => match name {
+                "length" => Val::Int(entries.len() as i64),
+                "isEmpty" => Val::Boolean(entries.is_empty()),
+                "keys" => Val::Set(entries.iter().map(|(key, _)| key.clone()).collect()),
+                "values" => Val::List(entries.iter().map(|(_, value)| value.clone()).collect()),
+                _ => return Ok(None),
+            },
+            Val::Object(object) => match (&object.kind, name) {
+                (ObjectKind::Listing, "length") => Val::Int(object.element_count as i64),
+                (ObjectKind::Listing, "isEmpty") => Val::Boolean(object.element_count == 0),
+                (ObjectKind::Listing, "first" | "last" | "firstOrNull" | "lastOrNull" | "max" | "min") => {
+                    return sequence_property(span, "Listing", Sequence::List, &self.elements(object)?, name);
+                }
+                (ObjectKind::Listing, "isDistinct") => {
+                    let elements = self.elements(object)?;
+                    Val::Boolean(distinct(elements.clone()).len() == elements.len())
+                }
+                (ObjectKind::Mapping, "length") => Val::Int(self.entries(object)?.len() as i64),
+                (ObjectKind::Mapping, "isEmpty") => Val::Boolean(self.entries(object)?.is_empty()),
+                (ObjectKind::Mapping, "keys") => {
+                    Val::Set(self.entries(object)?.into_iter().map(|(key, _)| key).collect())
+                }
+                (ObjectKind::Mapping, "values") => {
+                    Val::List(self.entries(object)?.into_iter().map(|(_, value)| value).collect())
+                }
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    pub(crate) fn collection_method(
+        &self,
+        call: &Call,
+        receiver: &Val<'a>,
+        args: Vec<Val<'a>>,
+    ) -> Result<Option<Val<'a>>> {
+        match receiver {
+            Val::List(elements) | Val::Set(elements) => self.sequence_method(call, sequence(receiver), elements, args),
+            Val::Map(entries) => self.map_method(call, entries, args),
+            Val::Object(object) => self.object_method(call, object, args),
+            Val::Function(function) if call.name == "apply" => self.apply(call.span, function, args).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn sequence_method(
+        &self,
+        call: &Call,
+        kind: Sequence,
+        elements: &[Val<'a>],
+        args: Vec<Val<'a>>,
+    ) -> Result<Option<Val<'a>>> {
+        let is_list = kind == Sequence::List;
+        let value = match call.name {
+            "contains" => {
+                let [element] = call.args(args)?;
+                Val::Boolean(elements.iter().any(|e| values_equal(e, &element)))
+            }
+            "indexOf" if is_list => {
+                let [element] = call.args(args)?;
+                let index = elements.iter().position(|e| values_equal(e, &element));
+                Val::Int(index.map_or(-1, |index| index as i64))
+            }
+            "getOrNull" if is_list => {
+                let [index] = call.args(args)?;
+                let index = usize::try_from(call.int(&index)?).ok();
+                index.and_then(|index| elements.get(index)).cloned().unwrap_or(Val::Null)
+            }
+            "add" => {
+                let [element] = call.args(args)?;
+                kind.collect(elements.iter().cloned().chain([element]).collect())
+            }
+            "map" => {
+                let [function] = call.args(args)?;
+                let function = call.function(&function)?;
+                let mut mapped = Vec::with_capacity(elements.len());
+                for element in elements {
+                    mapped.push(self.apply(call.span, function, vec![element.clone()])?);
+                }
+                kind.collect(mapped)
+            }
+            "mapIndexed" if is_list => {
+                let [function] = call.args(args)?;
+                let function = call.function(&function)?;
+                let mut mapped = Vec::with_capacity(elements.len());
+                for (index, element) in elements.iter().enumerate() {
+                    mapped.push(self.apply(call.span, function, vec![Val::Int(index as i64), element.clone()])?);
+                }
+                kind.collect(mapped)
+            }
+            "flatMap" => {
+                let [function] = call.args(args)?;
+                let function = call.function(&function)?;
+                let mut mapped = Vec::new();
+                for element in elements {
+                    match self.apply(call.span, function, vec![element.clone()])? {
+                        Val::List(values) | Val::Set(values) => mapped.extend(values.iter().cloned()),
+                        value => return Err(returned(call, "a `List` or `Set`", &value)),
+                    }
+                }
+                kind.collect(mapped)
+            }
+            "filter" => {
+                let [predicate] = call.args(args)?;
+                let predicate = call.function(&predicate)?;
+                let mut kept = Vec::new();
+                for element in elements {
+                    if self.test(call, predicate, vec![element.clone()])? {
+                        kept.push(element.clone());
+                    }
+                }
+                kind.collect(kept)
+            }
+            "count" | "any" | "every" | "find" | "findOrNull" => {
+                let [predicate] = call.args(args)?;
+                let predicate = call.function(&predicate)?;
+                let mut matches = Vec::new();
+                for element in elements {
+                    let matched = self.test(call, predicate, vec![element.clone()])?;
+                    match call.name {
+                        "any" | "find" | "findOrNull" if matched => {
+                            matches.push(element.clone());
+                            break;
+                        }
+                        "every" if !matched => return Ok(Some(Val::Boolean(false))),
+                        _ if matched => matches.push(element.clone()),
+                        _ => {}
+                    }
+                }
+                match call.name {
+                    "count" => Val::Int(matches.len() as i64),
+                    "any" => Val::Boolean(!matches.is_empty()),
+                    "every" => Val::Boolean(true),
+                    "find" => matches.pop().ok_or_else(|| EvalError::new(call.span, "no element matches"))?,
+                    _ => matches.pop().unwrap_or(Val::Null),
+                }
+            }
+            "fold" => {
+                let [initial, function] = call.args(args)?;
+                let function = call.function(&function)?;
+                let mut result = initial;
+                for element in elements {
+                    result = self.apply(call.span, function, vec![result, element.clone()])?;
+                }
+                result
+            }
+            "take" | "drop" if is_list => {
+                let [n] = call.args(args)?;
+                let n = usize::try_from(call.int(&n)?)
+                    .map_err(|_| EvalError::new(call.span, "expected a non-negative count"))?
+                    .min(elements.len());
+                let kept = if call.name == "take" { &elements[..n] } else { &elements[n..] };
+                Val::List(kept.into())
+            }
+            "reverse" if is_list => {
+                let [] = call.args(args)?;
+                Val::List(elements.iter().rev().cloned().collect())
+            }
+            "sort" if is_list => {
+                let [] = call.args(args)?;
+                let keyed = elements.iter().map(|element| (element.clone(), element.clone())).collect();
+                Val::List(sort_by_key(call.span, keyed)?.into())
+            }
+            "sortBy" if is_list => {
+                let [selector] = call.args(args)?;
+                let selector = call.function(&selector)?;
+                let mut keyed = Vec::with_capacity(elements.len());
+                for element in elements {
+                    keyed.push((self.apply(call.span, selector, vec![element.clone()])?, element.clone()));
+                }
+                Val::List(sort_by_key(call.span, keyed)?.into())
+            }
+            "sortWith" if is_list => {
+                let [comparator] = call.args(args)?;
+                let comparator = call.function(&comparator)?;
+                let sorted = try_sort(elements.to_vec(), &mut |left, right| {
+                    match self.apply(call.span, comparator, vec![left.clone(), right.clone()])? {
+                        Val::Int(n) => Ok(n.cmp(&0)),
+                        value => Err(returned(call, "an `Int`", &value)),
+                    }
+                })?;
+                Val::List(sorted.into())
+            }
+            "zip" if is_list => {
+                let [other] = call.args(args)?;
+                let pairs = elements.iter().zip(call.list(&other)?).map(|(l, r)| pair(call.span, l.clone(), r.clone()));
+                Val::List(pairs.collect())
+            }
+            "groupBy" => {
+                let [selector] = call.args(args)?;
+                let selector = call.function(&selector)?;
+                let mut groups: Vec<(Val<'a>, Vec<Val<'a>>)> = Vec::new();
+                for element in elements {
+                    let key = self.apply(call.span, selector, vec![element.clone()])?;
+                    match groups.iter_mut().find(|(k, _)| values_equal(k, &key)) {
+                        Some((_, group)) => group.push(element.clone()),
+                        None => groups.push((key, vec![element.clone()])),
+                    }
+                }
+                Val::Map(groups.into_iter().map(|(key, group)| (key, kind.collect(group))).collect())
+            }
+            "join" => {
+                let [separator] = call.args(args)?;
+                string_val(self.join(call, elements, call.string(&separator)?)?)
+            }
+            "toList" => {
+                let [] = call.args(args)?;
+                Val::List(elements.into())
+            }
+            "toSet" => {
+                let [] = call.args(args)?;
+                Val::Set(distinct(elements.to_vec()).into())
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    fn map_method(&self, call: &Call, entries: &[(Val<'a>, Val<'a>)], args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+        let value = match call.name {
+            "containsKey" => {
+                let [key] = call.args(args)?;
+                Val::Boolean(entries.iter().any(|(k, _)| values_equal(k, &key)))
+            }
+            "containsValue" => {
+                let [value] = call.args(args)?;
+                Val::Boolean(entries.iter().any(|(_, v)| values_equal(v, &value)))
+            }
+            "getOrNull" => {
+                let [key] = call.args(args)?;
+                entries.iter().find(|(k, _)| values_equal(k, &key)).map_or(Val::Null, |(_, value)| value.clone())
+            }
+            "put" => {
+                let [key, value] = call.args(args)?;
+                let mut entries = Entries::of(entries);
+                entries.insert(key, value);
+                entries.into_val()
+            }
+            "remove" => {
+                let [key] = call.args(args)?;
+                Val::Map(entries.iter().filter(|(k, _)| !values_equal(k, &key)).cloned().collect())
+            }
+            "filter" | "any" | "every" => {
+                let [predicate] = call.args(args)?;
+                let predicate = call.function(&predicate)?;
+                let mut kept = Vec::new();
+                for (key, value) in entries {
+                    let matched = self.test(call, predicate, vec![key.clone(), value.clone()])?;
+                    match call.name {
+                        "any" if matched => return Ok(Some(Val::Boolean(true))),
+                        "every" if !matched => return Ok(Some(Val::Boolean(false))),
+                        "filter" if matched => kept.push((key.clone(), value.clone())),
+                        _ => {}
+                    }
+                }
+                match call.name {
+                    "filter" => Val::Map(kept.into()),
+                    name => Val::Boolean(name == "every"),
+                }
+            }
+            "mapKeys" | "mapValues" => {
+                let [function] = call.args(args)?;
+                let function = call.function(&function)?;
+                let mut mapped = Entries::with_capacity(entries.len());
+                for (key, value) in entries {
+                    let result = self.apply(call.span, function, vec![key.clone(), value.clone()])?;
+                    match call.name {
+                        "mapKeys" => mapped.insert(result, value.clone()),
+                        _ => mapped.insert(key.clone(), result),
+                    }
+                }
+                mapped.into_val()
+            }
+            "fold" => {
+                let [initial, function] = call.args(args)?;
+                let function = call.function(&function)?;
+                let mut result = initial;
+                for (key, value) in entries {
+                    result = self.apply(call.span, function, vec![result, key.clone(), value.clone()])?;
+                }
+                result
+            }
+            "toMap" => {
+                let [] = call.args(args)?;
+                Val::Map(entries.into())
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    fn object_method(&self, call: &Call, object: &Rc<Obj<'a>>, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+        let value = match (&object.kind, call.name) {
+            (ObjectKind::Listing | ObjectKind::Dynamic, "toList") => {
+                let [] = call.args(args)?;
+                Val::List(self.elements(object)?.into())
+            }
+            (ObjectKind::Listing, "toSet") => {
+                let [] = call.args(args)?;
+                Val::Set(distinct(self.elements(object)?).into())
+            }
+            (ObjectKind::Listing, "contains" | "any" | "every" | "fold") => {
+                return self.sequence_method(call, Sequence::List, &self.elements(object)?, args);
+            }
+            (ObjectKind::Listing, "join") => {
+                let [separator] = call.args(args)?;
+                string_val(self.join(call, &self.elements(object)?, call.string(&separator)?)?)
+            }
+            (ObjectKind::Mapping, "toMap") => {
+                let [] = call.args(args)?;
+                Val::Map(self.entries(object)?.into())
+            }
+            (ObjectKind::Dynamic, "toMap") => {
+                let [] = call.args(args)?;
+                let properties = self.properties(object)?.into_iter().map(|(name, value)| (Val::String(name), value));
+                let mut entries = Entries::default();
+                for (key, value) in properties.chain(self.entries(object)?) {
+                    entries.insert(key, value);
+                }
+                entries.into_val()
+            }
+            (ObjectKind::Typed(_), "toDynamic") => {
+                let [] = call.args(args)?;
+                let mut dynamic = Obj::new(ObjectKind::Dynamic, None, None);
+                for (name, value) in self.properties(object)? {
+                    dynamic.members.push((Key::Property(name), Member { span: call.span, def: Def::Value(value) }));
+                }
+                Val::Object(Rc::new(dynamic))
+            }
+            (ObjectKind::Dynamic, "toTyped") => {
+                let [class] = call.args(args)?;
+                let class = call.class(&class)?;
+                let name = class.name;
+                let Some((declaration, module)) = &class.declared else {
+                    return Err(EvalError::new(call.span, format!("can't convert a `Dynamic` to `{name}`")));
+                };
+                let prototype = self.prototype(call.span, declaration, module)?;
+                let mut typed = Obj::new(prototype.kind.clone(), Some(prototype.clone()), None);
+                for (property, value) in self.properties(object)? {
+                    let key = Key::Property(property.clone());
+                    if !self.has_member(&prototype, &key) {
+                        let message = format!("class `{name}` has no property `{property}`");
+                        return Err(EvalError::new(call.span, message).with_code(Code::UnknownProperty));
+                    }
+                    typed.members.push((key, Member { span: call.span, def: Def::Value(value) }));
+                }
+                Val::Object(Rc::new(typed))
+            }
+            (ObjectKind::Mapping, "containsKey") => {
+                let [key] = call.args(args)?;
+                Val::Boolean(self.has_member(object, &Key::Entry(key)))
+            }
+            (ObjectKind::Mapping, "getOrNull") => {
+                let [key] = call.args(args)?;
+                self.member(object, &Key::Entry(key))?.unwrap_or(Val::Null)
+            }
+            (_, "hasProperty") => {
+                let [name] = call.args(args)?;
+                Val::Boolean(self.has_member(object, &Key::Property(call.string(&name)?.into())))
+            }
+            (_, "getProperty" | "getPropertyOrNull") => {
+                let [name] = call.args(args)?;
+                let name = call.string(&name)?;
+                match self.member(object, &Key::Property(name.into()))? {
+                    Some(value) => value,
+                    None if call.name == "getPropertyOrNull" => Val::Null,
+                    None => {
+                        let error = EvalError::new(call.span, format!("can't find property `{name}`"));
+                        return Err(error.with_code(Code::UnknownProperty));
+                    }
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Applies a predicate, which must return a `Boolean`.
+    fn test(&self, call: &Call, predicate: &Lambda<'a>, args: Vec<Val<'a>>) -> Result<bool> {
+        match self.apply(call.span, predicate, args)? {
+            Val::Boolean(b) => Ok(b),
+            value => Err(returned(call, "a `Boolean`", &value)),
+        }
+    }
+
+    fn join(&self, call: &Call, elements: &[Val<'a>], separator: &str) -> Result<String> {
+        let mut parts = Vec::with_capacity(elements.len());
+        for element in elements {
+            parts.push(self.stringify(call.span, element)?);
+        }
+        Ok(parts.join(separator))
+    }
+}
+
+/// The properties of lists and sets, some of which listings have too: `class` is the receiver's.
+fn sequence_property<'a>(
+    span: Span,
+    class: &str,
+    kind: Sequence,
+    elements: &[Val<'a>],
+    name: &str,
+) -> Result<Option<Val<'a>>> {
+    let is_list = kind == Sequence::List;
+    let empty = || EvalError::new(span, format!("can't read `{name}` of an empty `{class}`"));
+    let value = match name {
+        "length" => Val::Int(elements.len() as i64),
+        "isEmpty" => Val::Boolean(elements.is_empty()),
+        "first" => elements.first().cloned().ok_or_else(empty)?,
+        "last" => elements.last().cloned().ok_or_else(empty)?,
+        "firstOrNull" => elements.first().cloned().unwrap_or(Val::Null),
+        "lastOrNull" => elements.last().cloned().unwrap_or(Val::Null),
+        "max" | "min" | "maxOrNull" | "minOrNull" => {
+            let greatest = if name.starts_with("max") { Ordering::Greater } else { Ordering::Less };
+            let mut extreme: Option<&Val<'a>> = None;
+            for element in elements {
+                let replaces = match extreme {
+                    Some(extreme) => compare(span, element, extreme)? == greatest,
+                    None => true,
+                };
+                if replaces {
+                    extreme = Some(element);
+                }
+            }
+            match extreme {
+                Some(extreme) => extreme.clone(),
+                None if name.ends_with("OrNull") => Val::Null,
+                None => return Err(empty()),
+            }
+        }
+        "rest" if !elements.is_empty() => kind.collect(elements[1..].to_vec()),
+        "rest" => return Err(empty()),
+        "lastIndex" if is_list => Val::Int(elements.len() as i64 - 1),
+        "isDistinct" if is_list => Val::Boolean(distinct(elements.to_vec()).len() == elements.len()),
+        "distinct" if is_list => Val::List(distinct(elements.to_vec()).into()),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn sequence(value: &Val) -> Sequence {
+    match value {
+        Val::Set(_) => Sequence::Set,
+        _ => Sequence::List,
+    }
+}
+
+/// Sorts values by their keys, keeping the order of values with equal keys.
+fn sort_by_key<'a>(span: Span, keyed: Vec<(Val<'a>, Val<'a>)>) -> Result<Vec<Val<'a>>> {
+    let sorted = try_sort(keyed, &mut |(l, _), (r, _)| compare(span, l, r))?;
+    Ok(sorted.into_iter().map(|(_, value)| value).collect())
+}
+
+/// Sorts values with a comparison that can fail, keeping the order of values that compare equal. It's a merge sort of
+/// its own because `sort_by` can panic when the comparison isn't a total order, which a Pkl function needn't be.
+fn try_sort<T>(mut values: Vec<T>, compare: &mut impl FnMut(&T, &T) -> Result<Ordering>) -> Result<Vec<T>> {
+    if values.len() < 2 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let (left, right) = (try_sort(values, compare)?, try_sort(right, compare)?);
+    let mut sorted = Vec::with_capacity(left.len() + right.len());
+    let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        let next = if compare(r, l)? == Ordering::Less { right.next() } else { left.next() };
+        sorted.extend(next);
+    }
+    sorted.extend(left.chain(right));
+    Ok(sorted)
+}
+
+/// A `Pair` of `pkl:base`, like the elements of `zip`'s list, with its values as `first` and `second`.
+fn pair<'a>(span: Span, first: Val<'a>, second: Val<'a>) -> Val<'a> {
+    let mut pair = Obj::new(ObjectKind::Typed("Pair".to_string()), None, None);
+    for (name, value) in [("first", first), ("second", second)] {
+        pair.members.push((Key::Property(name.into()), Member { span, def: Def::Value(value) }));
+    }
+    Val::Object(Rc::new(pair))
+}
+
+/// Orders numbers, strings, durations, and data sizes, the values that `sort()` is defined for.
+fn compare(span: Span, left: &Val, right: &Val) -> Result<Ordering> {
+    match (left, right) {
+        (Val::Int(l), Val::Int(r)) => Ok(l.cmp(r)),
+        (Val::Int(_) | Val::Float(_), Val::Int(_) | Val::Float(_)) => {
+            let (l, r) = (as_f64(left), as_f64(right));
+            Ok(l.partial_cmp(&r).unwrap_or(Ordering::Equal))
+        }
+        (Val::String(l), Val::String(r)) => Ok(l.cmp(r)),
+        _ => units::compare_quantities(left, right).ok_or_else(|| {
+            let message = format!("can't compare `{}` with `{}`", left.type_name(), right.type_name());
+            EvalError::new(span, message)
+        }),
+    }
+}
+
+fn as_f64(value: &Val) -> f64 {
+    match value {
+        Val::Int(n) => *n as f64,
+        Val::Float(x) => *x,
+        _ => unreachable!("only called for numbers"),
+    }
+}
+
+pub(crate) fn returned(call: &Call, expected: &str, value: &Val) -> EvalError {
+    let message =
+        format!("the function passed to `{}` must return {expected}, but returned `{}`", call.name, value.type_name());
+    EvalError::new(call.span, message)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate_expr;
+    use crate::value::Value;
+
+    fn eval(source: &str) -> Value {
+        evaluate_expr(source).unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    fn ints(values: &[i64]) -> Vec<Value> {
+        values.iter().copied().map(Value::Int).collect()
+    }
+
+    #[test]
+    fn lists_and_sets() {
+        assert_eq!(eval("List(1, 2, 3).map((n) -> n * 2)"), Value::List(ints(&[2, 4, 6])));
+        assert_eq!(eval("List(1, 2, 3, 4).filter((n) -> n.isEven)"), Value::List(ints(&[2, 4])));
+        assert_eq!(eval("List(1, 2, 3).fold(0, (sum, n) -> sum + n)"), Value::Int(6));
+        assert_eq!(eval("Set(1, 2, 3).map((n) -> n % 2)"), Value::Set(ints(&[1, 0])));
+        assert_eq!(eval("List(3, 1, 2).sort().first"), Value::Int(1));
+        assert_eq!(eval("List(2.min, 90.s, 1.h).sort()"), eval("List(90.s, 2.min, 1.h)"));
+        assert_eq!(eval("List(\"bb\", \"a\").sortBy((s) -> s.length)"), eval("List(\"a\", \"bb\")"));
+        assert_eq!(eval("List(1, 2).join(\", \")"), Value::String("1, 2".into()));
+        assert_eq!(eval("List(1, 2, 3).any((n) -> n > 2) && !List().any((n) -> true)"), Value::Boolean(true));
+        assert_eq!(eval("List(1, 1, 2).isDistinct"), Value::Boolean(false));
+        assert_eq!(eval("List(1, 2).findOrNull((n) -> n > 5)"), Value::Null);
+
+        let error = evaluate_expr("List(1).filter((n) -> n)").unwrap_err().to_string();
+        assert_eq!(error, "the function passed to `filter` must return a `Boolean`, but returned `Int`");
+        let error = evaluate_expr("List().first").unwrap_err().to_string();
+        assert_eq!(error, "can't read `first` of an empty `List`");
+    }
+
+    #[test]
+    fn max_and_min() {
+        assert_eq!(eval("List(3, 1.5, 2).max"), Value::Int(3));
+        assert_eq!(eval("Set(\"b\", \"a\").min"), Value::String("a".into()));
+        assert_eq!(eval("List(2.min, 90.s).min"), eval("90.s"));
+        assert_eq!(eval("List().maxOrNull"), Value::Null);
+        assert_eq!(evaluate_expr("List().max").unwrap_err().to_string(), "can't read `max` of an empty `List`");
+        assert_eq!(evaluate_expr("List(1, \"a\").min").unwrap_err().to_string(), "can't compare `String` with `Int`");
+    }
+
+    #[test]
+    fn sort_with() {
+        assert_eq!(eval("List(1, 3, 2).sortWith((a, b) -> b - a)"), Value::List(ints(&[3, 2, 1])));
+        // equal elements keep their order
+        let source = "List(\"bb\", \"a\", \"cc\").sortWith((a, b) -> a.length - b.length)";
+        assert_eq!(eval(source), eval("List(\"a\", \"bb\", \"cc\")"));
+        // a comparison that isn't a total order doesn't panic
+        assert_eq!(eval("List(1, 2, 3, 4, 5).sortWith((a, b) -> -1).length"), Value::Int(5));
+        let error = evaluate_expr("List(1, 2).sortWith((a, b) -> true)").unwrap_err().to_string();
+        assert_eq!(error, "the function passed to `sortWith` must return an `Int`, but returned `Boolean`");
+    }
+
+    #[test]
+    fn zip() {
+        assert_eq!(eval("List(1, 2, 3).zip(List(\"a\", \"b\")).length"), Value::Int(2));
+        assert_eq!(eval("List(1, 2).zip(List(\"a\", \"b\"))[1].second"), Value::String("b".into()));
+        assert_eq!(eval("List(1).zip(List(2)).first is Pair"), Value::Boolean(true));
+        let error = evaluate_expr("List(1).zip(Set(1))").unwrap_err().to_string();
+        assert_eq!(error, "method `zip` expects an argument of type `List`, but got `Set`");
+    }
+
+    #[test]
+    fn group_by() {
+        let groups = eval("List(1, 2, 3, 4, 5).groupBy((n) -> n % 2)");
+        assert_eq!(groups, eval("Map(1, List(1, 3, 5), 0, List(2, 4))"));
+        let groups = eval("Set(\"a\", \"bb\", \"cc\").groupBy((s) -> s.length)");
+        assert_eq!(groups, eval("Map(1, Set(\"a\"), 2, Set(\"bb\", \"cc\"))"));
+    }
+
+    #[test]
+    fn listing_elements() {
+        assert_eq!(eval("new Listing { 1; 2; 3 }.first"), Value::Int(1));
+        assert_eq!(eval("new Listing { 1; 2; 3 }.last"), Value::Int(3));
+        assert_eq!(eval("new Listing {}.firstOrNull"), Value::Null);
+        assert_eq!(eval("new Listing { 3; 1 }.max"), Value::Int(3));
+        assert_eq!(eval("new Listing { 1; 2 }.any((n) -> n > 1)"), Value::Boolean(true));
+        assert_eq!(eval("new Listing { 1; 2 }.every((n) -> n > 1)"), Value::Boolean(false));
+        let error = evaluate_expr("new Listing {}.last").unwrap_err().to_string();
+        assert_eq!(error, "can't read `last` of an empty `Listing`");
+    }
+
+    #[test]
+    fn maps() {
+        assert_eq!(eval("Map(\"a\", 1, \"b\", 2).keys"), eval("Set(\"a\", \"b\")"));
+        assert_eq!(eval("Map(\"a\", 1).put(\"b\", 2).length"), Value::Int(2));
+        assert_eq!(eval("Map(\"a\", 1, \"b\", 2).mapValues((k, v) -> v * 10).values"), Value::List(ints(&[10, 20])));
+        assert_eq!(eval("Map(\"a\", 1, \"b\", 2).fold(0, (sum, k, v) -> sum + v)"), Value::Int(3));
+        assert_eq!(eval("Map(\"a\", 1).getOrNull(\"b\")"), Value::Null);
+    }
+
+    #[test]
+    fn objects() {
+        assert_eq!(eval("new Listing { 1; 2 }.length"), Value::Int(2));
+        assert_eq!(eval("new Listing { 1; 2 }.toList()"), Value::List(ints(&[1, 2])));
+        assert_eq!(eval("new Mapping { [\"a\"] = 1 }.toMap()"), eval("Map(\"a\", 1)"));
+        assert_eq!(eval("new Dynamic { a = 1; [\"b\"] = 2 }.toMap()"), eval("Map(\"a\", 1, \"b\", 2)"));
+        assert_eq!(eval("new Dynamic { a = 1 }.getPropertyOrNull(\"b\")"), Value::Null);
+        assert_eq!(eval("((n) -> n + 1).apply(1)"), Value::Int(2));
+    }
+}
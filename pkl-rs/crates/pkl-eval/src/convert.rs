@@ -0,0 +1,259 @@
+//! Converting values before they're rendered, as the `converters` of a renderer do.
+//!
+//! A converter applies to the values of a class, like `Duration`, or to the values at the paths matching a pattern,
+//! like `servers[*].port`, where `*` stands for any property and `[*]` for any element or entry. Path converters take
+//! precedence over class converters. A converted value isn't converted again, but its members are.
+
+use std::rc::Rc;
+
+use pkl_ast::Span;
+use pkl_stdlib::regex::Regex;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::methods::string_val;
+use crate::runtime::{Def, Key, Member, Obj, Val};
+use crate::value::{Object, Value};
+
+/// A step on the path from a rendered value to one inside it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Property(String),
+    Entry(Value),
+    Element(usize),
+}
+
+/// The values a converter applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The values of the class with this name
+    Class(String),
+    /// The values at the paths matching a pattern
+    Path(Vec<Pattern>),
+}
+
+/// A step of a path pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    Property(String),
+    /// `*`
+    AnyProperty,
+    /// `[key]`, with the key or index as it's written in Pkl, like `["host"]` or `[0]`
+    Key(String),
+    /// `[*]`
+    AnyKey,
+}
+
+impl Target {
+    /// Parses a path pattern, like `servers[*].port`.
+    pub fn path(pattern: &str) -> Target {
+        let mut patterns = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            if let Some(key) = rest.strip_prefix('[') {
+                let end = key.find(']').unwrap_or(key.len());
+                patterns.push(match &key[..end] {
+                    "*" => Pattern::AnyKey,
+                    key => Pattern::Key(key.to_string()),
+                });
+                rest = key.get(end + 1..).unwrap_or("");
+            } else {
+                let name = rest.strip_prefix('.').unwrap_or(rest);
+                let end = name.find(['.', '[']).unwrap_or(name.len());
+                patterns.push(match &name[..end] {
+                    "*" => Pattern::AnyProperty,
+                    name => Pattern::Property(name.to_string()),
+                });
+                rest = &name[end..];
+            }
+        }
+        Target::Path(patterns)
+    }
+
+    /// Whether this target applies to `value`, which is at `path`.
+    pub fn matches(&self, value: &Value, path: &[Segment]) -> bool {
+        match self {
+            Target::Class(name) => value.type_name() == name,
+            Target::Path(patterns) => {
+                patterns.len() == path.len()
+                    && patterns.iter().zip(path).all(|(pattern, segment)| match (pattern, segment) {
+                        (Pattern::AnyProperty, Segment::Property(_)) => true,
+                        (Pattern::Property(name), Segment::Property(property)) => name == property,
+                        (Pattern::AnyKey, Segment::Entry(_) | Segment::Element(_)) => true,
+                        (Pattern::Key(key), Segment::Element(index)) => *key == index.to_string(),
+                        (Pattern::Key(key), Segment::Entry(Value::String(s))) => *key == pkl_stdlib::string::quote(s),
+                        (Pattern::Key(key), Segment::Entry(Value::Int(n))) => *key == n.to_string(),
+                        _ => false,
+                    })
+            }
+        }
+    }
+}
+
+/// Converts a value and the values inside it: each value that one of `targets` applies to is replaced by what
+/// `converter` returns for it, which is given the index of the target.
+pub fn convert<E>(
+    value: Value,
+    targets: &[Target],
+    converter: &mut dyn FnMut(usize, Value) -> std::result::Result<Value, E>,
+) -> std::result::Result<Value, E> {
+    convert_at(value, targets, converter, &mut Vec::new())
+}
+
+fn convert_at<E>(
+    value: Value,
+    targets: &[Target],
+    converter: &mut dyn FnMut(usize, Value) -> std::result::Result<Value, E>,
+    path: &mut Vec<Segment>,
+) -> std::result::Result<Value, E> {
+    let is_path = |target: &&Target| matches!(target, Target::Path(_));
+    let paths = targets.iter().enumerate().filter(|(_, target)| is_path(target));
+    let classes = targets.iter().enumerate().filter(|(_, target)| !is_path(target));
+    let value = match paths.chain(classes).find(|(_, target)| target.matches(&value, path)) {
+        Some((index, _)) => converter(index, value)?,
+        None => value,
+    };
+
+    let mut inside = |segment: Segment, value: Value| {
+        path.push(segment);
+        let converted = convert_at(value, targets, converter, path);
+        path.pop();
+        converted
+    };
+    let mut elements = |elements: Vec<Value>| {
+        let elements = elements.into_iter().enumerate();
+        elements.map(|(index, value)| inside(Segment::Element(index), value)).collect::<std::result::Result<_, _>>()
+    };
+    Ok(match value {
+        Value::List(values) => Value::List(elements(values)?),
+        Value::Set(values) => Value::Set(elements(values)?),
+        Value::Map(entries) => {
+            let mut converted = Vec::with_capacity(entries.len());
+            for (key, value) in entries {
+                let value = inside(Segment::Entry(key.clone()), value)?;
+                converted.push((key, value));
+            }
+            Value::Map(converted)
+        }
+        Value::Object(object) => {
+            let mut converted = Object::new(object.kind);
+            for (name, value) in object.properties {
+                let value = inside(Segment::Property(name.clone()), value)?;
+                converted.properties.insert(name, value);
+            }
+            for (key, value) in object.entries {
+                let value = inside(Segment::Entry(key.clone()), value)?;
+                converted.entries.push((key, value));
+            }
+            for (index, value) in object.elements.into_iter().enumerate() {
+                converted.elements.push(inside(Segment::Element(index), value)?);
+            }
+            Value::Object(converted)
+        }
+        value => value,
+    })
+}
+
+impl<'a> Evaluator<'a> {
+    /// Converts a value with the `converters` of a renderer, a mapping from classes and path patterns to functions.
+    pub(crate) fn convert(&self, span: Span, value: Value, converters: &Rc<Obj<'a>>) -> Result<Value> {
+        let mut targets = Vec::new();
+        let mut functions = Vec::new();
+        for (key, function) in self.entries(converters)? {
+            targets.push(match key {
+                Val::Class(class) => Target::Class(class.name.to_string()),
+                Val::String(pattern) => Target::path(&pattern),
+                key => {
+                    let actual = key.type_name();
+                    let message = format!("expected classes or paths as the keys of `converters`, but got `{actual}`");
+                    return Err(EvalError::new(span, message));
+                }
+            });
+            let Val::Function(function) = function else {
+                let message = format!("expected a converter to be a function, but got `{}`", function.type_name());
+                return Err(EvalError::new(span, message));
+            };
+            functions.push(function);
+        }
+
+        convert(value, &targets, &mut |index, value| {
+            let value = self.apply(span, &functions[index], vec![val(span, &value)?])?;
+            self.export(&value)
+        })
+    }
+}
+
+/// The value an evaluated one was exported from, for converters to take as their argument.
+fn val<'a>(span: Span, value: &Value) -> Result<Val<'a>> {
+    let all = |values: &[Value]| values.iter().map(|value| val(span, value)).collect::<Result<Rc<[_]>>>();
+    Ok(match value {
+        Value::Null => Val::Null,
+        Value::Boolean(b) => Val::Boolean(*b),
+        Value::Int(n) => Val::Int(*n),
+        Value::Float(x) => Val::Float(*x),
+        Value::String(s) => string_val(s.as_str()),
+        Value::Duration(duration) => Val::Duration(*duration),
+        Value::DataSize(size) => Val::DataSize(*size),
+        Value::List(elements) => Val::List(all(elements)?),
+        Value::Set(elements) => Val::Set(all(elements)?),
+        Value::Map(entries) => Val::Map(
+            entries.iter().map(|(key, value)| Ok((val(span, key)?, val(span, value)?))).collect::<Result<_>>()?,
+        ),
+        Value::Object(object) => {
+            let member = |value| Member { span, def: Def::Value(value) };
+            let mut converted = Obj::new(object.kind.clone(), None, None);
+            for (name, value) in &object.properties {
+                converted.members.push((Key::Property(name.as_str().into()), member(val(span, value)?)));
+            }
+            for (key, value) in &object.entries {
+                converted.members.push((Key::Entry(val(span, key)?), member(val(span, value)?)));
+            }
+            for (index, value) in object.elements.iter().enumerate() {
+                converted.members.push((Key::Element(index), member(val(span, value)?)));
+            }
+            converted.element_count = object.elements.len();
+            Val::Object(Rc::new(converted))
+        }
+        Value::Regex { pattern } => match Regex::new(pattern) {
+            Ok(regex) => Val::Regex(Rc::new(regex)),
+            Err(error) => return Err(EvalError::new(span, error.message)),
+        },
+        value => {
+            let message = format!("a converter can't take a value of type `{}`", value.type_name());
+            return Err(EvalError::new(span, message));
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{convert, Segment, Target};
+    use crate::value::Value;
+
+    #[test]
+    fn targets() {
+        let path = [Segment::Property("servers".into()), Segment::Element(0), Segment::Property("port".into())];
+        let value = Value::Int(80);
+        assert!(Target::path("servers[*].port").matches(&value, &path));
+        assert!(Target::path("servers[0].port").matches(&value, &path));
+        assert!(Target::path("*[*].*").matches(&value, &path));
+        assert!(!Target::path("servers.port").matches(&value, &path));
+        assert!(!Target::path("port").matches(&value, &path));
+        assert!(Target::Class("Int".into()).matches(&value, &path));
+
+        let entry = [Segment::Entry(Value::String("a".into()))];
+        assert!(Target::path("[\"a\"]").matches(&value, &entry));
+    }
+
+    #[test]
+    fn converting() {
+        let value = Value::List(vec![Value::Int(1), Value::String("a".into()), Value::Int(2)]);
+        let targets = [Target::Class("Int".into()), Target::path("[1]")];
+        let converted = convert::<()>(value, &targets, &mut |index, value| match (index, value) {
+            (0, Value::Int(n)) => Ok(Value::Int(n * 10)),
+            (_, value) => Ok(Value::List(vec![value])),
+        });
+        let expected = [Value::Int(10), Value::List(vec![Value::String("a".into())]), Value::Int(20)];
+        assert_eq!(converted, Ok(Value::List(expected.to_vec())));
+    }
+}
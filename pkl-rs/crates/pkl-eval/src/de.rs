@@ -0,0 +1,365 @@
+//! Deserializing evaluated values into Rust types with serde, so that applications can read their configuration
+//! straight from Pkl.
+//!
+//! Objects with properties or entries deserialize like maps, so into structs and maps, and listings, lists, sets, and
+//! objects with only elements like sequences. An enum variant is a string, or an object with a single property named
+//! after the variant. `Duration`s and `DataSize`s deserialize like strings, such as `"5.min"`.
+//!
+//! ```
+//! #[derive(serde::Deserialize)]
+//! struct Config {
+//!     host: String,
+//!     port: u16,
+//!     tags: Vec<String>,
+//! }
+//!
+//! let config: Config = pkl_eval::from_str("host = \"localhost\"\nport = 80\ntags { \"web\" }").unwrap();
+//! assert_eq!((config.host.as_str(), config.port), ("localhost", 80));
+//! assert_eq!(config.tags, ["web"]);
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::value::{Object, ObjectKind, Value};
+
+/// Deserializes a value into `T`.
+pub fn from_value<'de, T: de::Deserialize<'de>>(value: &'de Value) -> Result<T, DeError> {
+    T::deserialize(Deserializer::new(value))
+}
+
+/// A value can't be deserialized into the type it's deserialized into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeError {
+    pub message: String,
+    /// The properties, keys, and indices leading to the value from the deserialized one, like `servers[0].port`
+    pub path: String,
+}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        DeError { message: message.to_string(), path: String::new() }
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} (at `{}`)", self.message, self.path)
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+/// Deserializes a [`Value`].
+#[derive(Debug, Clone, Copy)]
+pub struct Deserializer<'de> {
+    value: &'de Value,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(value: &'de Value) -> Self {
+        Deserializer { value }
+    }
+}
+
+/// Where a value is inside the one it's a member of.
+#[derive(Clone, Copy)]
+enum Segment<'de> {
+    Property(&'de str),
+    Key(&'de Value),
+    Index(usize),
+}
+
+/// Deserializes a member, adding the member to the path of the errors that don't have one yet.
+fn member<'de, S: DeserializeSeed<'de>>(seed: S, segment: Segment, value: &'de Value) -> Result<S::Value, DeError> {
+    seed.deserialize(Deserializer::new(value)).map_err(|mut error| {
+        let segment = match segment {
+            Segment::Property(name) => name.to_string(),
+            Segment::Key(Value::String(key)) => format!("[{}]", pkl_stdlib::string::quote(key)),
+            Segment::Key(Value::Int(key)) => format!("[{key}]"),
+            Segment::Key(key) => format!("[{}]", key.type_name()),
+            Segment::Index(index) => format!("[{index}]"),
+        };
+        error.path = match (error.path.as_str(), segment.starts_with('[')) {
+            ("", _) => segment,
+            (path, _) if path.starts_with('[') => format!("{segment}{path}"),
+            (path, _) => format!("{segment}.{path}"),
+        };
+        error
+    })
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(b) => visitor.visit_bool(*b),
+            Value::Int(n) => visitor.visit_i64(*n),
+            Value::Float(x) => visitor.visit_f64(*x),
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Duration(duration) => visitor.visit_string(duration.to_string()),
+            Value::DataSize(size) => visitor.visit_string(size.to_string()),
+            Value::List(elements) | Value::Set(elements) => visitor.visit_seq(Elements::new(elements)),
+            Value::Map(entries) => {
+                let entries = entries.iter().map(|(key, value)| (Key::Value(key), value)).collect();
+                visitor.visit_map(Entries::new(entries))
+            }
+            Value::Object(object) => match object_entries(object) {
+                Some(entries) => visitor.visit_map(Entries::new(entries)),
+                None => visitor.visit_seq(Elements::new(&object.elements)),
+            },
+            value => Err(de::Error::custom(format!("can't deserialize a value of type `{}`", value.type_name()))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &str, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self.value {
+            Value::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            Value::Object(object) if object.properties.len() == 1 && object.entries.is_empty() => {
+                let (variant, value) = object.properties.first().expect("checked");
+                visitor.visit_enum(Variant { name: variant, value })
+            }
+            value => {
+                let actual = value.type_name();
+                let message = format!("expected a `String` or an object with one property, but got `{actual}`");
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The properties and entries of an object, unless it's read as a sequence: a listing, or an object with only
+/// elements.
+fn object_entries(object: &Object) -> Option<Vec<(Key<'_>, &Value)>> {
+    let has_members = !object.properties.is_empty() || !object.entries.is_empty();
+    if object.kind == ObjectKind::Listing || (!has_members && !object.elements.is_empty()) {
+        return None;
+    }
+    let properties = object.properties.iter().map(|(name, value)| (Key::Property(name), value));
+    let entries = object.entries.iter().map(|(key, value)| (Key::Value(key), value));
+    Some(properties.chain(entries).collect())
+}
+
+struct Elements<'de> {
+    elements: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+}
+
+impl<'de> Elements<'de> {
+    fn new(elements: &'de [Value]) -> Self {
+        Elements { elements: elements.iter().enumerate() }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'de> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, DeError> {
+        match self.elements.next() {
+            Some((index, value)) => member(seed, Segment::Index(index), value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Key<'de> {
+    Property(&'de str),
+    Value(&'de Value),
+}
+
+struct Entries<'de> {
+    entries: std::vec::IntoIter<(Key<'de>, &'de Value)>,
+    /// The entry whose key was read last, whose value is read next
+    current: Option<(Key<'de>, &'de Value)>,
+}
+
+impl<'de> Entries<'de> {
+    fn new(entries: Vec<(Key<'de>, &'de Value)>) -> Self {
+        Entries { entries: entries.into_iter(), current: None }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Entries<'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, DeError> {
+        self.current = self.entries.next();
+        match self.current {
+            Some((Key::Property(name), _)) => seed.deserialize(name.into_deserializer()).map(Some),
+            Some((Key::Value(key), _)) => seed.deserialize(Deserializer::new(key)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let (key, value) = self.current.take().expect("serde reads a key before its value");
+        let segment = match key {
+            Key::Property(name) => Segment::Property(name),
+            Key::Value(key) => Segment::Key(key),
+        };
+        member(seed, segment, value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// An enum variant written as an object with a single property.
+struct Variant<'de> {
+    name: &'de str,
+    value: &'de Value,
+}
+
+impl<'de> de::EnumAccess<'de> for Variant<'de> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<(T::Value, Self), DeError> {
+        let name = de::value::BorrowedStrDeserializer::<DeError>::new(self.name);
+        Ok((seed.deserialize(name)?, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant<'de> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        de::Deserialize::deserialize(Deserializer::new(self.value))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        member(seed, Segment::Property(self.name), self.value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, DeError> {
+        member(VisitorSeed(visitor), Segment::Property(self.name), self.value)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        member(VisitorSeed(visitor), Segment::Property(self.name), self.value)
+    }
+}
+
+/// Deserializes a value with a visitor, like `deserialize_any` does.
+struct VisitorSeed<V>(V);
+
+impl<'de, V: Visitor<'de>> DeserializeSeed<'de> for VisitorSeed<V> {
+    type Value = V::Value;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        deserializer.deserialize_any(self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::from_str;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f64,
+        debug: Option<bool>,
+        servers: Vec<Server>,
+        labels: HashMap<String, i32>,
+        timeout: String,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        host: String,
+        #[serde(default)]
+        weight: u8,
+    }
+
+    #[test]
+    fn structs_and_collections() {
+        let source = "name = \"web\"\nport = 8080\nratio = 1\ndebug = null\n\
+                      servers { new { host = \"a\" }; new { host = \"b\"; weight = 2 } }\n\
+                      labels = new Mapping { [\"tier\"] = 1 }\ntimeout = 5.min";
+        let config: Config = from_str(source).unwrap();
+        assert_eq!(config.name, "web");
+        assert_eq!((config.port, config.ratio, config.debug), (8080, 1.0, None));
+        assert_eq!(config.servers, [Server { host: "a".into(), weight: 0 }, Server { host: "b".into(), weight: 2 }]);
+        assert_eq!(config.labels, HashMap::from([("tier".to_string(), 1)]));
+        assert_eq!(config.timeout, "5.min");
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Shape {
+        Point,
+        Circle { radius: f64 },
+        Square(f64),
+    }
+
+    #[test]
+    fn enums() {
+        let shapes: Vec<Shape> = from_str::<HashMap<String, Vec<Shape>>>(
+            "shapes { \"point\"; new { circle { radius = 2 } }; new { square = 3 } }",
+        )
+        .unwrap()
+        .remove("shapes")
+        .unwrap();
+        assert_eq!(shapes, [Shape::Point, Shape::Circle { radius: 2.0 }, Shape::Square(3.0)]);
+    }
+
+    #[test]
+    fn errors() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Ports {
+            servers: Vec<Server>,
+        }
+        let error = from_str::<Ports>("servers { new { host = \"a\"; weight = 300 } }").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: integer `300`, expected u8 (at `servers[0].weight`)"
+        );
+        let error = from_str::<Ports>("servers { new { weight = 1 } }").unwrap_err();
+        assert_eq!(error.to_string(), "missing field `host` (at `servers[0]`)");
+    }
+}
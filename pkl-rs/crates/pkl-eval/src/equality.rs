@@ -0,0 +1,269 @@
+//! Equality, hashing, and ordering of values.
+//!
+//! `==` compares values by what they are: numbers by value, so `1 == 1.0`, durations and data sizes by the quantity
+//! they measure, so `1.min == 60.s`, lists by their elements in order, sets and maps by their elements and entries in
+//! any order, and objects of the same class by their properties, entries, and elements, which comparing evaluates.
+//!
+//! The keys of maps and the elements of sets are compared without evaluating anything, so there an object is only
+//! equal to itself. [`hash`] agrees with that equality, so that [`Entries`] and [`distinct`] find equal keys without
+//! comparing every pair.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::runtime::{Obj, Val};
+use crate::units;
+
+/// Whether two values are equal in the sense of `==`, which considers `1` and `1.0` equal.
+///
+/// Objects are only equal to themselves; [`Evaluator::equal`] compares their members.
+pub(crate) fn values_equal<'a>(left: &Val<'a>, right: &Val<'a>) -> bool {
+    match (left, right) {
+        (Val::Null, Val::Null) => true,
+        (Val::Boolean(l), Val::Boolean(r)) => l == r,
+        (Val::Int(l), Val::Int(r)) => l == r,
+        (Val::Int(_) | Val::Float(_), Val::Int(_) | Val::Float(_)) => as_f64(left) == as_f64(right),
+        (Val::String(l), Val::String(r)) => l == r,
+        (Val::List(l), Val::List(r)) => l.len() == r.len() && l.iter().zip(r.iter()).all(|(l, r)| values_equal(l, r)),
+        // the elements of a set are distinct, so it's enough that each of one is in the other
+        (Val::Set(l), Val::Set(r)) => l.len() == r.len() && l.iter().all(|l| r.iter().any(|r| values_equal(l, r))),
+        (Val::Map(l), Val::Map(r)) => {
+            let contains = |(key, value): &(Val<'a>, Val<'a>)| {
+                r.iter().any(|(k, v)| values_equal(key, k) && values_equal(value, v))
+            };
+            l.len() == r.len() && l.iter().all(contains)
+        }
+        (Val::Object(l), Val::Object(r)) => Rc::ptr_eq(l, r),
+        (Val::Function(l), Val::Function(r)) => Rc::ptr_eq(l, r),
+        (Val::Regex(l), Val::Regex(r)) => l.pattern() == r.pattern(),
+        (Val::RegexMatch(l), Val::RegexMatch(r)) => l == r,
+        (Val::Class(l), Val::Class(r)) => match (&l.declared, &r.declared) {
+            (Some((l, _)), Some((r, _))) => std::ptr::eq(*l, *r),
+            (None, None) => l.name == r.name,
+            _ => false,
+        },
+        (Val::Duration(_) | Val::DataSize(_), _) => units::quantities_equal(left, right),
+        _ => false,
+    }
+}
+
+fn as_f64(value: &Val) -> Option<f64> {
+    match value {
+        Val::Int(value) => Some(*value as f64),
+        Val::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// A hash of a value, which is the same for values that [`values_equal`] considers equal.
+pub(crate) fn hash(value: &Val) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_into(value, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_into(value: &Val, state: &mut DefaultHasher) {
+    // numbers of either type hash by value, with `0.0` and `-0.0` the same
+    let number = |n: f64| if n == 0.0 { 0 } else { n.to_bits() };
+    // the elements and entries of sets and maps are combined in a way that doesn't depend on their order
+    let unordered = |hashes: &mut dyn Iterator<Item = u64>| hashes.fold(0u64, u64::wrapping_add);
+    match value {
+        Val::Null => 0u8.hash(state),
+        Val::Boolean(b) => (1u8, b).hash(state),
+        Val::Int(n) => (2u8, number(*n as f64)).hash(state),
+        Val::Float(n) => (2u8, number(*n)).hash(state),
+        Val::String(s) => (3u8, s).hash(state),
+        Val::Duration(_) | Val::DataSize(_) => {
+            (4u8, value.type_name(), units::smallest_units(value).map(number)).hash(state);
+        }
+        Val::List(elements) => {
+            (5u8, elements.len()).hash(state);
+            elements.iter().for_each(|element| hash_into(element, state));
+        }
+        Val::Set(elements) => (6u8, unordered(&mut elements.iter().map(hash))).hash(state),
+        Val::Map(entries) => {
+            let mut entries = entries.iter().map(|(key, value)| {
+                let mut hasher = DefaultHasher::new();
+                (hash(key), hash(value)).hash(&mut hasher);
+                hasher.finish()
+            });
+            (7u8, unordered(&mut entries)).hash(state);
+        }
+        Val::Object(object) => (8u8, Rc::as_ptr(object) as usize).hash(state),
+        Val::Function(function) => (9u8, Rc::as_ptr(function) as usize).hash(state),
+        Val::Class(class) => (10u8, &class.name).hash(state),
+        Val::Regex(regex) => (11u8, regex.pattern()).hash(state),
+        Val::RegexMatch(_) => 12u8.hash(state),
+    }
+}
+
+/// The indices of values by their hashes.
+type Index = HashMap<u64, Vec<usize>>;
+
+/// The entries of a map as it's built, where adding one with the key of another replaces its value.
+#[derive(Default)]
+pub(crate) struct Entries<'a> {
+    entries: Vec<(Val<'a>, Val<'a>)>,
+    index: Index,
+}
+
+impl<'a> Entries<'a> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Entries { entries: Vec::with_capacity(capacity), index: Index::with_capacity(capacity) }
+    }
+
+    /// The entries of a map, whose keys are already distinct.
+    pub(crate) fn of(entries: &[(Val<'a>, Val<'a>)]) -> Self {
+        let mut index = Index::with_capacity(entries.len());
+        for (i, (key, _)) in entries.iter().enumerate() {
+            index.entry(hash(key)).or_default().push(i);
+        }
+        Entries { entries: entries.to_vec(), index }
+    }
+
+    /// Adds an entry, replacing the value of an entry with an equal key.
+    pub(crate) fn insert(&mut self, key: Val<'a>, value: Val<'a>) {
+        let indices = self.index.entry(hash(&key)).or_default();
+        match indices.iter().find(|&&i| values_equal(&self.entries[i].0, &key)) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                indices.push(self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub(crate) fn into_val(self) -> Val<'a> {
+        Val::Map(self.entries.into())
+    }
+}
+
+/// The values without the ones equal to an earlier one.
+pub(crate) fn distinct<'a>(values: Vec<Val<'a>>) -> Vec<Val<'a>> {
+    let mut index = Index::with_capacity(values.len());
+    let mut kept: Vec<Val> = Vec::with_capacity(values.len());
+    for value in values {
+        let indices = index.entry(hash(&value)).or_default();
+        if !indices.iter().any(|&i| values_equal(&kept[i], &value)) {
+            indices.push(kept.len());
+            kept.push(value);
+        }
+    }
+    kept
+}
+
+impl<'a> Evaluator<'a> {
+    /// Whether two values are equal in the sense of `==`, evaluating the members of the objects it compares.
+    pub(crate) fn equal(&self, left: &Val<'a>, right: &Val<'a>) -> Result<bool> {
+        match (left, right) {
+            (Val::Object(l), Val::Object(r)) => self.objects_equal(l, r),
+            (Val::List(l), Val::List(r)) => self.all_equal(l, r),
+            (Val::Set(l), Val::Set(r)) => {
+                if l.len() != r.len() {
+                    return Ok(false);
+                }
+                for element in l.iter() {
+                    if !self.any_equal(element, r)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            (Val::Map(l), Val::Map(r)) => self.entries_equal(l, r),
+            _ => Ok(values_equal(left, right)),
+        }
+    }
+
+    /// Whether two objects are of the same class, with equal properties, entries, and elements.
+    fn objects_equal(&self, left: &Rc<Obj<'a>>, right: &Rc<Obj<'a>>) -> Result<bool> {
+        if Rc::ptr_eq(left, right) {
+            return Ok(true);
+        }
+        if left.kind != right.kind {
+            return Ok(false);
+        }
+        let (properties, others) = (self.properties(left)?, self.properties(right)?);
+        if properties.len() != others.len() {
+            return Ok(false);
+        }
+        for (name, value) in &properties {
+            match others.iter().find(|(other, _)| other == name) {
+                Some((_, other)) if self.equal(value, other)? => {}
+                _ => return Ok(false),
+            }
+        }
+        Ok(self.entries_equal(&self.entries(left)?, &self.entries(right)?)?
+            && self.all_equal(&self.elements(left)?, &self.elements(right)?)?)
+    }
+
+    fn all_equal(&self, left: &[Val<'a>], right: &[Val<'a>]) -> Result<bool> {
+        if left.len() != right.len() {
+            return Ok(false);
+        }
+        for (l, r) in left.iter().zip(right) {
+            if !self.equal(l, r)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn any_equal(&self, value: &Val<'a>, values: &[Val<'a>]) -> Result<bool> {
+        for other in values {
+            if self.equal(value, other)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Whether two sets of entries have the same keys, in any order, with equal values.
+    fn entries_equal(&self, left: &[(Val<'a>, Val<'a>)], right: &[(Val<'a>, Val<'a>)]) -> Result<bool> {
+        if left.len() != right.len() {
+            return Ok(false);
+        }
+        for (key, value) in left {
+            match right.iter().find(|(other, _)| values_equal(key, other)) {
+                Some((_, other)) if self.equal(value, other)? => {}
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate_expr;
+    use crate::value::Value;
+
+    fn eval(source: &str) -> Value {
+        evaluate_expr(source).unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    #[test]
+    fn structural_equality() {
+        assert_eq!(eval("new Dynamic { a = 1; b { 2 } } == new Dynamic { b { 2 }; a = 1.0 }"), Value::Boolean(true));
+        assert_eq!(eval("new Dynamic { a = 1 } == new Dynamic { a = 2 }"), Value::Boolean(false));
+        assert_eq!(eval("new Listing { 1 } != new Dynamic { 1 }"), Value::Boolean(true));
+        let mappings = "new Mapping { [\"a\"] = 1; [\"b\"] = 2 } == new Mapping { [\"b\"] = 2; [\"a\"] = 1 }";
+        assert_eq!(eval(mappings), Value::Boolean(true));
+        assert_eq!(eval("List(new Dynamic { x = 1 }) == List(new Dynamic { x = 1 })"), Value::Boolean(true));
+        assert_eq!(eval("Set(1, 2) == Set(2, 1) && Map(1, 2, 3, 4) == Map(3, 4, 1, 2)"), Value::Boolean(true));
+        assert_eq!(eval("List(1, 2) == List(2, 1)"), Value::Boolean(false));
+        assert_eq!(eval("1.min == 60.s && 1.kb != 1.kib && 1.s != 1.b"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn hashed_keys() {
+        assert_eq!(eval("Set(1, 1.0, 60.s, 1.min, 0.0, -0.0).length"), Value::Int(3));
+        assert_eq!(eval("Map(Set(1, 2), \"a\", Set(2, 1), \"b\").length"), Value::Int(1));
+        assert_eq!(eval("Map(List(1), \"a\", List(1.0), \"b\")[List(1)]"), Value::String("b".into()));
+        let set = (0..2000).map(|i| format!("{}", i % 1000)).collect::<Vec<_>>().join(", ");
+        assert_eq!(eval(&format!("Set({set}).length")), Value::Int(1000));
+    }
+}
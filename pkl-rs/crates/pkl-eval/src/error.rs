@@ -0,0 +1,153 @@
+use std::fmt;
+
+use pkl_diagnostics::{Code, Report};
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_lexer::line_index::LineCol;
+use pkl_lexer::token::Span;
+
+use crate::de::DeError;
+
+/// A failure while evaluating a well-formed program, such as a type mismatch or a reference to a missing property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+    /// The expression that failed to evaluate
+    pub span: Span,
+    pub message: String,
+    /// Other places the error involves, with what they are, like the type annotation that a value doesn't match
+    pub labels: Vec<(Span, String)>,
+    pub code: Option<Code>,
+    /// The URI of the module the spans are in, if the error happened while evaluating a module
+    pub uri: Option<String>,
+    /// What was being evaluated when the error happened, innermost first
+    pub frames: Vec<StackFrame>,
+}
+
+/// Something that was being evaluated when an error happened, like a property or the body of a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    /// What it is, like ``property `port` ``
+    pub description: String,
+    /// The URI of the module it's in
+    pub uri: String,
+    pub span: Span,
+    /// Where the span starts, if the evaluator has the source of the module
+    pub position: Option<LineCol>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "in {} at {}:{position}", self.description, self.uri),
+            None => write!(f, "in {} in {}", self.description, self.uri),
+        }
+    }
+}
+
+impl EvalError {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        EvalError { span, message: message.into(), labels: Vec::new(), code: None, uri: None, frames: Vec::new() }
+    }
+
+    pub fn with_code(mut self, code: Code) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Adds a label pointing at another place the error involves.
+    pub fn with_label(mut self, span: Span, label: impl Into<String>) -> Self {
+        self.labels.push((span, label.into()));
+        self
+    }
+
+    /// Adds the frame of something that was being evaluated when the error happened, outside of the frames added
+    /// before. Unless it's known already, the error is in the same module as the frame.
+    pub(crate) fn in_frame(self, description: String, uri: String, span: Span) -> Self {
+        let mut error = self.in_module(&uri);
+        error.frames.push(StackFrame { description, uri, span, position: None });
+        error
+    }
+
+    /// Tells the module the error is in, unless it's known already.
+    pub(crate) fn in_module(mut self, uri: &str) -> Self {
+        self.uri.get_or_insert_with(|| uri.to_string());
+        self
+    }
+
+    /// The error as a report, for rendering with the source it's in, with a note for each frame. A frame that repeats,
+    /// like that of a recursive method, gets a single note.
+    pub fn report(&self) -> Report {
+        let report = Report::error(&self.message).with_code(self.code).with_primary(self.span.start..self.span.end, "");
+        let mut report =
+            self.labels.iter().fold(report, |report, (span, label)| report.with_label(span.start..span.end, label));
+        let mut frames = self.frames.iter().peekable();
+        while let Some(frame) = frames.next() {
+            let mut times = 1;
+            while frames.next_if_eq(&frame).is_some() {
+                times += 1;
+            }
+            report = match times {
+                1 => report.with_note(frame.to_string()),
+                times => report.with_note(format!("{frame} ({times} times)")),
+            };
+        }
+        report
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Why a module couldn't be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The source has syntax errors, so evaluation wasn't attempted.
+    Syntax(Vec<Diagnostic>),
+    Eval(EvalError),
+    /// The module was evaluated, but its value doesn't fit the type it was deserialized into.
+    Deserialize(DeError),
+}
+
+impl Error {
+    /// The reports of the problems, for rendering with the source of the module.
+    pub fn reports(&self) -> Vec<Report> {
+        match self {
+            Error::Syntax(diagnostics) => diagnostics.iter().map(Diagnostic::report).collect(),
+            Error::Eval(error) => vec![error.report()],
+            Error::Deserialize(error) => vec![Report::error(error.to_string())],
+        }
+    }
+}
+
+impl From<EvalError> for Error {
+    fn from(error: EvalError) -> Self {
+        Error::Eval(error)
+    }
+}
+
+impl From<DeError> for Error {
+    fn from(error: DeError) -> Self {
+        Error::Deserialize(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax(diagnostics) => match diagnostics.as_slice() {
+                [diagnostic] => write!(f, "{}", diagnostic.message),
+                diagnostics => write!(f, "{} syntax errors", diagnostics.len()),
+            },
+            Error::Eval(error) => write!(f, "{error}"),
+            Error::Deserialize(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub(crate) type Result<T> = std::result::Result<T, EvalError>;
@@ -0,0 +1,820 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::time::Instant;
+
+use indexmap::IndexSet;
+use oxc_allocator::Allocator;
+use pkl_ast::uri::import_name;
+use pkl_ast::{Expr, ExtendsKind, Modifier, ModifierKind, Module, ModuleMember, Span};
+use pkl_diagnostics::Code;
+use pkl_lexer::line_index::LineIndex;
+
+use crate::class::{declare_method, declare_property, module_of};
+use crate::error::{EvalError, Result};
+use crate::equality::values_equal;
+use crate::log::Log;
+use crate::options::EvaluatorOptions;
+use crate::packages::Package;
+use crate::prefetch::prefetch;
+use crate::regex;
+use crate::resources::ResourceReader;
+use crate::runtime::{CacheKey, Def, Env, Frame, Key, Member, Method, Obj, Slot, Val};
+use crate::types::declared_type;
+use crate::value::{ModuleOutput, Object, ObjectKind, Value};
+
+/// A member being evaluated: its receiver, the layer defining it, its key, and where it's defined.
+type Evaluating<'a> = (Rc<Obj<'a>>, Rc<Obj<'a>>, Key<'a>, Span);
+
+/// Evaluates the syntax tree of a module, or expressions in the context of one.
+pub struct Evaluator<'a> {
+    /// Where the sources of imported modules are kept and parsed into
+    pub(crate) alloc: &'a Allocator,
+    /// The modules evaluated so far, by their URI: the evaluated module and the ones it imports
+    pub(crate) modules: RefCell<Vec<(String, Rc<Obj<'a>>)>>,
+    /// The URIs of the modules whose imports are being resolved, innermost last
+    pub(crate) loading: RefCell<Vec<String>>,
+    /// The members being evaluated, innermost last
+    evaluating: RefCell<Vec<Evaluating<'a>>>,
+    /// The packages fetched so far, by their URI
+    pub(crate) packages: RefCell<Vec<(String, Rc<Package>)>>,
+    pub(crate) options: EvaluatorOptions,
+    /// The readers added by the embedder, tried before the built-in ones
+    pub(crate) readers: Vec<Box<dyn ResourceReader>>,
+    /// When the evaluation times out, if it can
+    pub(crate) deadline: Cell<Option<Instant>>,
+    /// How many expressions have been evaluated, for checking the timeout now and then
+    pub(crate) ticks: Cell<u32>,
+    /// How deeply the calls and members being evaluated are nested
+    pub(crate) depth: Cell<usize>,
+    /// The sources of the modules, by their URI, for showing the source of traced expressions
+    pub(crate) sources: RefCell<Vec<(String, &'a str)>>,
+    /// The sources of the modules that have been read ahead of importing them, by their resolved URI
+    pub(crate) prefetched: RefCell<HashMap<String, String>>,
+    /// Where the messages of `trace` and warnings go, which is nowhere unless one is set
+    pub(crate) logger: Box<dyn Fn(&Log)>,
+    /// The deprecated members that have been warned about, by their annotation and the module and offset of the use
+    pub(crate) warned: RefCell<HashSet<(usize, String, usize)>>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(alloc: &'a Allocator) -> Self {
+        Evaluator::with_options(alloc, EvaluatorOptions::default())
+    }
+
+    pub fn with_options(alloc: &'a Allocator, options: EvaluatorOptions) -> Self {
+        Evaluator {
+            alloc,
+            modules: RefCell::default(),
+            loading: RefCell::default(),
+            evaluating: RefCell::default(),
+            packages: RefCell::default(),
+            options,
+            readers: Vec::new(),
+            deadline: Cell::new(None),
+            ticks: Cell::new(0),
+            depth: Cell::new(0),
+            sources: RefCell::default(),
+            prefetched: RefCell::default(),
+            logger: Box::new(|_| {}),
+            warned: RefCell::default(),
+        }
+    }
+
+    /// Tells the evaluator the source of a module that it's given the syntax tree of, so that the messages of `trace`
+    /// can show the source of the traced expressions. Imported modules are read by the evaluator, which knows their
+    /// sources already.
+    pub fn add_source(&mut self, uri: &str, source: &'a str) {
+        self.sources.get_mut().push((uri.to_string(), source));
+    }
+
+    /// Sends the messages of `trace(...)` and the warnings about deprecated members to `logger`. Without one, they're
+    /// dropped, since an application evaluating its configuration may not want its standard error written to: the
+    /// `pkl-lang` command line writes them there, like `pkl: TRACE: 1 + 1 = 2 (repl:text, line 1)`.
+    pub fn set_logger(&mut self, logger: impl Fn(&Log) + 'static) {
+        self.logger = Box::new(logger);
+    }
+
+    /// Evaluates a module into an object holding its properties. Its relative imports are resolved against `uri`,
+    /// like `file:///birds/index.pkl`, or the working directory if it isn't a `file:` URI.
+    pub fn evaluate_module(&mut self, module: &'a Module<'a>, uri: &str) -> Result<Value> {
+        self.start_clock();
+        self.prefetched.get_mut().extend(prefetch(&self.options, &module.header, uri));
+        let value = self.module_object(module, uri).and_then(|module| {
+            self.modules.borrow_mut().push((uri.to_string(), module.clone()));
+            self.export(&Val::Object(module))
+        });
+        value.map_err(|error| self.locate(error))
+    }
+
+    /// Evaluates a module's `output`: its `output.value`, `output.text`, `output.renderer`, and `output.files`, where
+    /// the value is the module itself if the module doesn't set one.
+    pub fn evaluate_output(&mut self, module: &'a Module<'a>, uri: &str) -> Result<ModuleOutput> {
+        self.start_clock();
+        self.prefetched.get_mut().extend(prefetch(&self.options, &module.header, uri));
+        self.module_output(module, uri).map_err(|error| self.locate(error))
+    }
+
+    fn module_output(&self, module: &'a Module<'a>, uri: &str) -> Result<ModuleOutput> {
+        let module = self.module_object(module, uri)?;
+        self.modules.borrow_mut().push((uri.to_string(), module.clone()));
+
+        let key = Key::Property(OUTPUT.into());
+        let span = module.own_member(&key).map_or(Span::new(0, 0), |member| member.span);
+        let output = match self.member(&module, &key)? {
+            None | Some(Val::Null) => None,
+            Some(Val::Object(output)) => Some(output),
+            Some(value) => {
+                let message = format!("expected `output` to be an object, but got `{}`", value.type_name());
+                return Err(EvalError::new(span, message));
+            }
+        };
+        let mut result = self.output(output.as_ref(), span, OUTPUT, Val::Object(module))?;
+
+        let files = match &output {
+            Some(output) => self.member(output, &Key::Property("files".into()))?,
+            None => None,
+        };
+        let files = match files {
+            None | Some(Val::Null) => Vec::new(),
+            Some(Val::Object(files)) => self.entries(&files)?,
+            Some(Val::Map(files)) => files.to_vec(),
+            Some(value) => {
+                let message = format!("expected `output.files` to be a mapping, but got `{}`", value.type_name());
+                return Err(EvalError::new(span, message));
+            }
+        };
+        for (path, file) in files {
+            let Val::String(path) = path else {
+                let actual = path.type_name();
+                let message = format!("expected the paths of `output.files` to be `String`s, but got `{actual}`");
+                return Err(EvalError::new(span, message));
+            };
+            let name = format!("output.files[{}]", pkl_stdlib::string::quote(&path));
+            let Val::Object(file) = file else {
+                let message = format!("expected `{name}` to be an object, but got `{}`", file.type_name());
+                return Err(EvalError::new(span, message));
+            };
+            result.files.push((path.to_string(), self.output(Some(&file), span, &name, Val::Null)?));
+        }
+        Ok(result)
+    }
+
+    /// Evaluates the `value`, `text`, and `renderer` of the `output`, or of one of its files, called `name`, where the
+    /// value is `default` if it isn't set.
+    fn output(&self, output: Option<&Rc<Obj<'a>>>, span: Span, name: &str, default: Val<'a>) -> Result<ModuleOutput> {
+        let property = |property: &str| match output {
+            Some(output) => self.member(output, &Key::Property(property.into())),
+            None => Ok(None),
+        };
+        let expected = |property: &str, expected: &str, value: Val<'a>| {
+            let message = format!("expected `{name}.{property}` to be {expected}, but got `{}`", value.type_name());
+            EvalError::new(span, message)
+        };
+
+        let mut value = self.export(&property("value")?.unwrap_or(default))?;
+        let text = match property("text")? {
+            None | Some(Val::Null) => None,
+            Some(Val::String(text)) => Some(text.to_string()),
+            Some(value) => return Err(expected("text", "a `String`", value)),
+        };
+        let renderer = match property("renderer")? {
+            None | Some(Val::Null) => None,
+            Some(Val::Object(renderer)) => {
+                match self.member(&renderer, &Key::Property("converters".into()))? {
+                    None | Some(Val::Null) => {}
+                    Some(Val::Object(converters)) => value = self.convert(span, value, &converters)?,
+                    Some(value) => return Err(expected("renderer.converters", "a mapping", value)),
+                }
+                Some(self.export_object(&renderer)?)
+            }
+            Some(value) => return Err(expected("renderer", "an object", value)),
+        };
+        Ok(ModuleOutput { value, text, renderer, files: Vec::new() })
+    }
+
+    /// Evaluates an expression in the context of a module, as if it were the value of a property of the module, like
+    /// `output.text`.
+    pub fn evaluate_expr_in(&mut self, module: &'a Module<'a>, uri: &str, expr: &'a Expr<'a>) -> Result<Value> {
+        self.start_clock();
+        let value = self.module_object(module, uri).and_then(|module| {
+            self.modules.borrow_mut().push((uri.to_string(), module.clone()));
+            let env = Env::new(Frame::Object { this: module.clone(), layer: module }, None);
+            self.export(&self.eval_expr(expr, &env)?)
+        });
+        value.map_err(|error| self.locate(error))
+    }
+
+    /// Evaluates an expression in the context of an empty module.
+    pub fn evaluate_expr(&mut self, expr: &'a Expr<'a>) -> Result<Value> {
+        self.start_clock();
+        let module = Rc::new(Obj::new(ObjectKind::Typed("ModuleClass".to_string()), None, None));
+        let env = Env::new(Frame::Object { this: module.clone(), layer: module }, None);
+        let value = self.eval_expr(expr, &env).and_then(|value| self.export(&value));
+        value.map_err(|error| self.locate(error))
+    }
+
+    pub(crate) fn module_object(&self, module: &'a Module<'a>, uri: &str) -> Result<Rc<Obj<'a>>> {
+        let name = match &module.header.name {
+            Some(name) => name.parts.iter().map(|part| part.name).collect::<Vec<_>>().join("."),
+            None => "ModuleClass".to_string(),
+        };
+        // a module that amends another is an instance of the same module class, one that extends it a subclass
+        let mut object = match &module.header.extends {
+            Some(extends) => {
+                let parent = self.import(extends.uri.span, extends.uri.value, uri)?;
+                let kind = match extends.kind {
+                    ExtendsKind::Amends => parent.kind.clone(),
+                    ExtendsKind::Extends => ObjectKind::Typed(name),
+                };
+                Obj::new(kind, Some(parent), None)
+            }
+            None => Obj::new(ObjectKind::Typed(name), None, None),
+        };
+        let amends = matches!(&module.header.extends, Some(extends) if extends.kind == ExtendsKind::Amends);
+
+        for import in module.header.imports.iter() {
+            let name = match &import.alias {
+                Some(alias) => alias.name,
+                None if import.glob => {
+                    let message = "an `import*` clause needs a name, like `import* \"birds/*.pkl\" as birds`";
+                    return Err(EvalError::new(import.span, message));
+                }
+                None => import_name(import.uri.value),
+            };
+            let key = Key::Local(name.into());
+            if object.own_member(&key).is_some() {
+                return Err(duplicate(import.span, name));
+            }
+            let imported = if import.glob {
+                self.import_glob(import.uri.span, import.uri.value, uri)?
+            } else {
+                self.import(import.uri.span, import.uri.value, uri)?
+            };
+            object.members.push((key, Member { span: import.span, def: Def::Value(Val::Object(imported)) }));
+        }
+
+        for member in module.members.iter() {
+            match member {
+                ModuleMember::Property(property) => {
+                    let name = property.name.name;
+                    let local = property.modifiers.has(ModifierKind::Local);
+                    let inherited = |parent: &Rc<Obj<'a>>| self.has_member(parent, &Key::Property(name.into()));
+                    if amends && !local && !object.parent.as_ref().is_some_and(inherited) {
+                        let class = object.kind.class_name();
+                        let message = format!("module `{class}` has no property `{name}`");
+                        return Err(EvalError::new(property.name.span, message).with_code(Code::UnknownProperty));
+                    }
+                    declare_property(&mut object, property)?
+                }
+                ModuleMember::Method(method) => declare_method(&mut object, method),
+                ModuleMember::Class(class) => {
+                    if object.classes.iter().any(|other| other.name.name == class.name.name) {
+                        return Err(duplicate(class.name.span, class.name.name));
+                    }
+                    object.classes.push(class);
+                }
+                ModuleMember::TypeAlias(alias) => {
+                    if object.aliases.iter().any(|other| other.name.name == alias.name.name) {
+                        return Err(duplicate(alias.name.span, alias.name.name));
+                    }
+                    object.aliases.push(alias);
+                }
+            }
+        }
+
+        // a module's `output` isn't rendered as part of the module, like the `output` of `pkl:base#Module`
+        for (key, member) in object.members.iter_mut() {
+            if let (Key::Property(name), Def::Expr { modifiers, .. }) = (key, &mut member.def) {
+                if **name == *OUTPUT {
+                    let mut hidden = modifiers.to_vec();
+                    hidden.push(Modifier { span: member.span, kind: ModifierKind::Hidden });
+                    *modifiers = self.alloc.alloc_slice_copy(&hidden);
+                }
+            }
+        }
+
+        Ok(Rc::new(object))
+    }
+
+    /// Reads a property, entry, or element of an object, or returns `None` if the object has no such member.
+    pub(crate) fn member(&self, receiver: &Rc<Obj<'a>>, key: &Key<'a>) -> Result<Option<Val<'a>>> {
+        for layer in receiver.layers() {
+            if let Some(member) = layer.own_member(key) {
+                return self.force(receiver, layer, key, member).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether an object has a member, without evaluating it.
+    pub(crate) fn has_member(&self, receiver: &Rc<Obj<'a>>, key: &Key<'a>) -> bool {
+        receiver.layers().any(|layer| layer.own_member(key).is_some())
+    }
+
+    /// Reads the local property `name` defined by `layer`, for the receiver `this`.
+    pub(crate) fn local(&self, this: &Rc<Obj<'a>>, layer: &Rc<Obj<'a>>, name: &str) -> Result<Option<Val<'a>>> {
+        let key = Key::Local(name.into());
+        match layer.own_member(&key) {
+            Some(member) => self.force(this, layer, &key, member).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Evaluates a member defined by `layer` for `receiver`, unless it was evaluated for it before.
+    fn force(
+        &self,
+        receiver: &Rc<Obj<'a>>,
+        layer: &Rc<Obj<'a>>,
+        key: &Key<'a>,
+        member: &Member<'a>,
+    ) -> Result<Val<'a>> {
+        let layer_id = if matches!(key, Key::Local(_)) { Rc::as_ptr(layer) as usize } else { 0 };
+        let position = |cache: &[(CacheKey<'a>, Slot<'a>)]| {
+            cache.iter().position(|((k, id), _)| *id == layer_id && k.same(key))
+        };
+
+        let cached = {
+            let cache = receiver.cache.borrow();
+            position(&cache).map(|index| cache[index].1.clone())
+        };
+        match cached {
+            Some(Slot::Done(value)) => return Ok(value),
+            Some(Slot::Evaluating) => return Err(self.circular(receiver, layer, key)),
+            None => {}
+        }
+
+        receiver.cache.borrow_mut().push(((key.clone(), layer_id), Slot::Evaluating));
+        self.evaluating.borrow_mut().push((receiver.clone(), layer.clone(), key.clone(), member.span));
+        let result = self.nested(member.span, || self.eval_member(receiver, layer, key, member));
+        self.evaluating.borrow_mut().pop();
+
+        let forget = || {
+            let mut cache = receiver.cache.borrow_mut();
+            let index = position(&cache).expect("slot was reserved");
+            cache.remove(index);
+        };
+        let value = match result {
+            Ok(value) => value,
+            Err(error) => {
+                forget();
+                return Err(self.in_member(error, receiver, layer, key, member));
+            }
+        };
+        {
+            let mut cache = receiver.cache.borrow_mut();
+            let index = position(&cache).expect("slot was reserved");
+            cache[index].1 = Slot::Done(value.clone());
+        }
+
+        // the type is checked once the value is memoized, so that checking the elements of a listing can read
+        // elements that refer to the listing itself
+        if let Err(error) = self.check_member_type(receiver, layer, key, member, &value) {
+            forget();
+            return Err(self.in_member(error, receiver, layer, key, member));
+        }
+        Ok(value)
+    }
+
+    /// The error for a member that's read while it's being evaluated for the same receiver, which points at each
+    /// member of the cycle that leads back to it.
+    fn circular(&self, receiver: &Rc<Obj<'a>>, layer: &Rc<Obj<'a>>, key: &Key<'a>) -> EvalError {
+        let evaluating = self.evaluating.borrow();
+        let start = evaluating
+            .iter()
+            .rposition(|(r, l, k, _)| {
+                Rc::ptr_eq(r, receiver) && k.same(key) && (!matches!(key, Key::Local(_)) || Rc::ptr_eq(l, layer))
+            })
+            .expect("member is being evaluated");
+        let cycle = &evaluating[start..];
+        let uri = self.layer_uri(receiver, layer);
+        let names: Vec<String> = cycle.iter().map(|(_, _, key, _)| self.member_name(key)).collect();
+
+        let chain: Vec<&str> = names.iter().chain(&names[..1]).map(String::as_str).collect();
+        let message = format!("circular reference: {}", chain.join(" -> "));
+        let mut error = EvalError::new(cycle[0].3, message).with_code(Code::CircularReference);
+        for (index, (receiver, layer, _, span)) in cycle.iter().enumerate().skip(1) {
+            // labels can only point into the module of the error
+            if self.layer_uri(receiver, layer) == uri {
+                let next = names.get(index + 1).unwrap_or(&names[0]);
+                error = error.with_label(*span, format!("{} refers to {next}", names[index]));
+            }
+        }
+        error.in_module(&uri)
+    }
+
+    /// How a member is named in the chain of a cycle, like `` `port` `` or `` `[0]` ``.
+    fn member_name(&self, key: &Key<'a>) -> String {
+        match key {
+            Key::Property(name) | Key::Local(name) => format!("`{name}`"),
+            Key::Entry(key) => format!("`[{}]`", self.describe(key).trim_matches('`')),
+            Key::Element(index) => format!("`[{index}]`"),
+        }
+    }
+
+    /// Adds the frame of a member to an error that happened while evaluating it.
+    fn in_member(
+        &self,
+        error: EvalError,
+        receiver: &Rc<Obj<'a>>,
+        layer: &Rc<Obj<'a>>,
+        key: &Key<'a>,
+        member: &Member<'a>,
+    ) -> EvalError {
+        let description = match key {
+            Key::Property(name) => format!("property `{name}`"),
+            Key::Local(name) => format!("local property `{name}`"),
+            Key::Entry(key) => format!("entry {}", self.describe(key)),
+            Key::Element(index) => format!("element {index}"),
+        };
+        error.in_frame(description, self.layer_uri(receiver, layer), member.span)
+    }
+
+    /// The URI of the module that the body of `layer` is written in.
+    pub(crate) fn layer_uri(&self, receiver: &Rc<Obj<'a>>, layer: &Rc<Obj<'a>>) -> String {
+        match &layer.env {
+            Some(env) => self.module_uri(module_of(env)),
+            // the layer of a module that another one amends or extends is that module
+            None if self.modules.borrow().iter().any(|(_, module)| Rc::ptr_eq(module, layer)) => self.module_uri(layer),
+            None => self.module_uri(receiver),
+        }
+    }
+
+    /// Fills in where the frames of an error are, from the sources of the modules.
+    pub(crate) fn locate(&self, mut error: EvalError) -> EvalError {
+        let sources = self.sources.borrow();
+        let mut lines: Vec<(&str, LineIndex)> = Vec::new();
+        for frame in &mut error.frames {
+            let index = match lines.iter().position(|(uri, _)| *uri == frame.uri) {
+                Some(index) => index,
+                None => {
+                    let Some((uri, source)) = sources.iter().find(|(uri, _)| *uri == frame.uri) else { continue };
+                    lines.push((uri, LineIndex::new(source)));
+                    lines.len() - 1
+                }
+            };
+            frame.position = Some(lines[index].1.line_col(frame.span.start));
+        }
+        error
+    }
+
+    fn eval_member(
+        &self,
+        receiver: &Rc<Obj<'a>>,
+        layer: &Rc<Obj<'a>>,
+        key: &Key<'a>,
+        member: &Member<'a>,
+    ) -> Result<Val<'a>> {
+        let (value, bodies, ty, bindings) = match &member.def {
+            Def::Expr { value, bodies, ty, bindings, .. } => (value, bodies, ty, bindings),
+            Def::Value(value) => return Ok(value.clone()),
+        };
+
+        let mut env = Env::new(Frame::Object { this: receiver.clone(), layer: layer.clone() }, layer.env.clone());
+        if let Some(bindings) = bindings {
+            env = Env::new(Frame::Bindings(bindings.to_vec()), Some(env));
+        }
+        let mut result = match value {
+            // `new { ... }` without a type amends the default of the property's type, or the listing or mapping's
+            Some(Expr::New(new)) if new.ty.is_none() => {
+                let parent = match declared_type(receiver, layer, key, member) {
+                    Some((ty, env)) => self.object_default(ty, &env)?,
+                    None => match self.default_member(member.span, receiver, key)? {
+                        Some(default) => default,
+                        None => Val::Object(Rc::new(Obj::new(ObjectKind::Dynamic, None, None))),
+                    },
+                };
+                Some(self.amend(parent, &new.body, &env)?)
+            }
+            Some(value) => Some(self.eval_expr(value, &env)?),
+            None => self.inherited(receiver, layer, key)?,
+        };
+        if result.is_none() {
+            result = match ty {
+                Some(ty) => self.type_default(ty, &env)?,
+                None => self.default_member(member.span, receiver, key)?,
+            };
+        }
+        for body in bodies.iter() {
+            let parent = match result.take() {
+                // a body amends the default of the type of a property that's `null`, like `package` of `pkl:Project`
+                Some(Val::Null) => match declared_type(receiver, layer, key, member) {
+                    Some((ty, env)) => self.object_default(ty, &env)?,
+                    None => Val::Null,
+                },
+                Some(parent) => parent,
+                None => Val::Object(Rc::new(Obj::new(ObjectKind::Dynamic, None, None))),
+            };
+            result = Some(self.amend(parent, body, &env)?);
+        }
+
+        let error = match key {
+            Key::Property(name) | Key::Local(name) => format!("property `{name}` has no value"),
+            _ => "member has no value".to_string(),
+        };
+        result.ok_or_else(|| EvalError::new(member.span, error).with_code(Code::MissingValue))
+    }
+
+    /// The `default` of a listing or mapping, which entries and elements that don't amend an inherited value amend.
+    /// A `default` function like `(key) -> new Dynamic { name = key }` is applied to the key or index of the member
+    /// at `span` first.
+    fn default_member(&self, span: Span, receiver: &Rc<Obj<'a>>, key: &Key<'a>) -> Result<Option<Val<'a>>> {
+        let argument = match key {
+            Key::Entry(key) => key.clone(),
+            Key::Element(index) => Val::Int(*index as i64),
+            Key::Property(_) | Key::Local(_) => return Ok(None),
+        };
+        if !matches!(receiver.kind, ObjectKind::Listing | ObjectKind::Mapping) {
+            return Ok(None);
+        }
+        match self.member(receiver, &Key::Property(DEFAULT.into()))? {
+            Some(Val::Function(default)) => self.apply(span, &default, vec![argument]).map(Some),
+            default => Ok(default),
+        }
+    }
+
+    /// Evaluates the member that a member of `layer` overrides, with the same receiver.
+    pub(crate) fn inherited(
+        &self,
+        receiver: &Rc<Obj<'a>>,
+        layer: &Rc<Obj<'a>>,
+        key: &Key<'a>,
+    ) -> Result<Option<Val<'a>>> {
+        let Some(parent) = &layer.parent else { return Ok(None) };
+        for layer in parent.layers() {
+            if let Some(member) = layer.own_member(key) {
+                return self.eval_member(receiver, layer, key, member).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The (non-local) method `name` of an object, along with the layer defining it.
+    pub(crate) fn method<'o>(
+        &self,
+        receiver: &'o Rc<Obj<'a>>,
+        name: &str,
+    ) -> Option<(&'o Rc<Obj<'a>>, &'o Method<'a>)> {
+        receiver.layers().find_map(|layer| {
+            let method = layer.methods.iter().find(|method| method.name == name && !method.local)?;
+            Some((layer, method))
+        })
+    }
+
+    /// Evaluates a value completely, turning it into its public representation.
+    pub(crate) fn export(&self, value: &Val<'a>) -> Result<Value> {
+        let export_all = |values: &[Val<'a>]| values.iter().map(|value| self.export(value)).collect::<Result<_>>();
+
+        Ok(match value {
+            Val::Null => Value::Null,
+            Val::Boolean(value) => Value::Boolean(*value),
+            Val::Int(value) => Value::Int(*value),
+            Val::Float(value) => Value::Float(*value),
+            Val::String(value) => Value::String(value.to_string()),
+            Val::Duration(value) => Value::Duration(*value),
+            Val::DataSize(value) => Value::DataSize(*value),
+            Val::List(elements) => Value::List(export_all(elements)?),
+            Val::Set(elements) => Value::Set(export_all(elements)?),
+            Val::Map(entries) => Value::Map(
+                entries.iter().map(|(key, value)| Ok((self.export(key)?, self.export(value)?))).collect::<Result<_>>()?,
+            ),
+            Val::Object(object) => Value::Object(self.export_object(object)?),
+            Val::Function(function) => Value::Function { arity: function.params.len() },
+            Val::Class(class) => Value::Class { name: class.name.to_string() },
+            Val::Regex(regex) => Value::Regex { pattern: regex.pattern().to_string() },
+            Val::RegexMatch(found) => regex::export_match(found),
+        })
+    }
+
+    fn export_object(&self, object: &Rc<Obj<'a>>) -> Result<Object> {
+        let mut result = Object::new(object.kind.clone());
+        for (name, value) in self.properties(object)? {
+            if object.modifier(&Key::Property(name.clone()), ModifierKind::Hidden).is_some() {
+                continue;
+            }
+            result.properties.insert(name.to_string(), self.export(&value)?);
+        }
+        for (key, value) in self.entries(object)? {
+            result.entries.push((self.export(&key)?, self.export(&value)?));
+        }
+        for value in self.elements(object)? {
+            result.elements.push(self.export(&value)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Evaluates the (non-local) properties of an object.
+    pub(crate) fn properties(&self, object: &Rc<Obj<'a>>) -> Result<Vec<(Rc<str>, Val<'a>)>> {
+        let mut names: IndexSet<Rc<str>> = IndexSet::new();
+        for layer in root_first(object) {
+            for (key, _) in &layer.members {
+                if let Key::Property(name) = key {
+                    if !is_default(&object.kind, name) {
+                        names.insert(name.clone());
+                    }
+                }
+            }
+        }
+
+        let mut properties = Vec::with_capacity(names.len());
+        for name in names {
+            if let Some(value) = self.member(object, &Key::Property(name.clone()))? {
+                properties.push((name, value));
+            }
+        }
+        Ok(properties)
+    }
+
+    /// Evaluates the entries of an object.
+    pub(crate) fn entries(&self, object: &Rc<Obj<'a>>) -> Result<Vec<(Val<'a>, Val<'a>)>> {
+        let mut keys: Vec<&Val<'a>> = Vec::new();
+        for layer in root_first(object) {
+            for (key, _) in &layer.members {
+                if let Key::Entry(key) = key {
+                    if !keys.iter().any(|k| values_equal(k, key)) {
+                        keys.push(key);
+                    }
+                }
+            }
+        }
+
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(value) = self.member(object, &Key::Entry(key.clone()))? {
+                entries.push((key.clone(), value));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Evaluates the elements of an object.
+    pub(crate) fn elements(&self, object: &Rc<Obj<'a>>) -> Result<Vec<Val<'a>>> {
+        let mut elements = Vec::with_capacity(object.element_count);
+        for index in 0..object.element_count {
+            if let Some(value) = self.member(object, &Key::Element(index))? {
+                elements.push(value);
+            }
+        }
+        Ok(elements)
+    }
+}
+
+/// The layers of an object, starting from the root of the amend chain, which is the order members are listed in.
+fn root_first<'o, 'a>(object: &'o Rc<Obj<'a>>) -> impl Iterator<Item = &'o Rc<Obj<'a>>> {
+    let mut layers: Vec<_> = object.layers().collect();
+    layers.reverse();
+    layers.into_iter()
+}
+
+/// The property of modules saying what evaluating them produces.
+pub(crate) const OUTPUT: &str = "output";
+
+/// The property of listings and mappings holding the value their members amend by default.
+pub(crate) const DEFAULT: &str = "default";
+
+/// Whether `name` is the `default` of a listing or mapping, which isn't rendered like other properties.
+pub(crate) fn is_default(kind: &ObjectKind, name: &str) -> bool {
+    matches!(kind, ObjectKind::Listing | ObjectKind::Mapping) && name == DEFAULT
+}
+
+/// The error for assigning to a property whose declaration in `layer` or the layers it amends is `fixed` or `const`.
+pub(crate) fn check_assignable<'a>(layer: &Obj<'a>, key: &Key<'a>, span: Span) -> Result<()> {
+    for kind in [ModifierKind::Fixed, ModifierKind::Const] {
+        if let (Some(_), Key::Property(name)) = (layer.modifier(key, kind), key) {
+            let message = format!("can't assign to `{}` property `{name}`", kind.as_str());
+            return Err(EvalError::new(span, message));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn duplicate(span: Span, name: &str) -> EvalError {
+    EvalError::new(span, format!("duplicate definition of `{name}`")).with_code(Code::DuplicateDefinition)
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+    use pkl_diagnostics::Code;
+    use pkl_lexer::token::Span;
+
+    use super::Evaluator;
+    use crate::value::Value;
+    use crate::{evaluate, Deprecations, Error, EvaluatorOptions};
+
+    fn property(source: &str, name: &str) -> Value {
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{source}: {error:?}"));
+        module.as_object().unwrap().property(name).cloned().unwrap_or_else(|| panic!("{name} is missing"))
+    }
+
+    #[test]
+    fn module_properties_and_methods() {
+        let source = "function double(n) = n * 2\nport = double(base)\nnext = module.port + 1\nlocal base = 4000";
+        let module = evaluate(source).unwrap();
+        let module = module.as_object().unwrap();
+        assert_eq!(module.properties.keys().collect::<Vec<_>>(), ["port", "next"]);
+        assert_eq!(module.property("next"), Some(&Value::Int(8001)));
+    }
+
+    #[test]
+    fn late_binding() {
+        let source = "base { a = 1; b = a + 1 }\nderived = (base) { a = 10 }\nc = derived.b";
+        assert_eq!(property(source, "c"), Value::Int(11));
+
+        let source = "base { a = 1; inner { b = a * 2 } }\nderived = (base) { a = 5 }\nc = derived.inner.b";
+        assert_eq!(property(source, "c"), Value::Int(10));
+    }
+
+    #[test]
+    fn laziness() {
+        assert_eq!(property("local unused = 1 + \"a\"\nused = 1", "used"), Value::Int(1));
+    }
+
+    #[test]
+    fn circular_references() {
+        let Err(Error::Eval(error)) = evaluate("a = b\nb = c + 1\nc = a") else { panic!() };
+        assert_eq!(error.message, "circular reference: `a` -> `b` -> `c` -> `a`");
+        assert_eq!(error.span, Span::new(0, 1));
+        let labels = [(Span::new(6, 7), "`b` refers to `c`".into()), (Span::new(16, 17), "`c` refers to `a`".into())];
+        assert_eq!(error.labels, labels);
+
+        // the cycle is only the part of the chain that leads back to the member
+        let Err(Error::Eval(error)) = evaluate("x = y\ny = z\nz = z + 1") else { panic!() };
+        assert_eq!(error.message, "circular reference: `z` -> `z`");
+    }
+
+    #[test]
+    fn repeated_frames() {
+        let source = "function f(n) = if (n == 0) 1 ~/ 0 else f(n - 1)\nx = f(3)";
+        let Err(Error::Eval(error)) = evaluate(source) else { panic!() };
+        let notes = ["in method `f` at repl:text:1:17 (4 times)", "in property `x` at repl:text:2:1"];
+        assert_eq!(error.report().notes, notes);
+    }
+
+    #[test]
+    fn expressions_in_modules() {
+        let alloc = Allocator::default();
+        let module = alloc.alloc(pkl_parser::parse_module(&alloc, "local base = 40\nport = base + 2").node);
+        let expr = alloc.alloc(pkl_parser::parse_expr(&alloc, "port + base").node);
+        let value = Evaluator::new(&alloc).evaluate_expr_in(module, "repl:text", expr).unwrap();
+        assert_eq!(value, Value::Int(82));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let Err(Error::Syntax(diagnostics)) = evaluate("a = (") else { panic!() };
+        assert!(!diagnostics.is_empty());
+    }
+
+    #[test]
+    fn trace() {
+        let alloc = Allocator::default();
+        let source = "a = 1\nb = trace(a + 1) * 10\nc = trace(\"x\".length)";
+        let module = alloc.alloc(pkl_parser::parse_module(&alloc, source).node);
+        let messages = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut evaluator = Evaluator::new(&alloc);
+        evaluator.add_source("repl:text", source);
+        let logged = messages.clone();
+        evaluator.set_logger(move |log| logged.borrow_mut().push(log.to_string()));
+
+        let module = evaluator.evaluate_module(module, "repl:text").unwrap();
+        assert_eq!(module.as_object().unwrap().property("b"), Some(&Value::Int(20)));
+        let expected = [
+            "pkl: TRACE: a + 1 = 2 (repl:text, line 2)",
+            r#"pkl: TRACE: "x".length = 1 (repl:text, line 3)"#,
+        ];
+        assert_eq!(*messages.borrow(), expected);
+    }
+
+    #[test]
+    fn deprecated_members() {
+        let alloc = Allocator::default();
+        let source = "@Deprecated { message = \"use `make`\"; replaceWith = \"make()\" }\nfunction create() = 1\n\
+            @Deprecated\nold = 2\nfunction make() = 1\na = List(1, 2).map((n) -> create() + old)\nb = module.old\n";
+        let module = alloc.alloc(pkl_parser::parse_module(&alloc, source).node);
+        let messages = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut evaluator = Evaluator::new(&alloc);
+        evaluator.add_source("repl:text", source);
+        let logged = messages.clone();
+        evaluator.set_logger(move |log| {
+            assert_eq!(log.code, Some(Code::DeprecatedWarning));
+            logged.borrow_mut().push(log.to_string());
+        });
+
+        assert!(evaluator.evaluate_module(module, "repl:text").is_ok());
+        // once for each place that uses them
+        let expected = [
+            "pkl: WARN: `create` is deprecated: use `make` (replace it with `make()`) (repl:text, line 6)",
+            "pkl: WARN: `old` is deprecated (repl:text, line 6)",
+            "pkl: WARN: `old` is deprecated (repl:text, line 7)",
+        ];
+        assert_eq!(*messages.borrow(), expected);
+
+        let options = EvaluatorOptions { deprecations: Deprecations::Error, ..EvaluatorOptions::default() };
+        let Err(Error::Eval(error)) = crate::evaluate_with(source, "repl:text", options) else { panic!() };
+        assert_eq!(error.code, Some(Code::Deprecated));
+        let options = EvaluatorOptions { deprecations: Deprecations::Ignore, ..EvaluatorOptions::default() };
+        assert!(crate::evaluate_with(source, "repl:text", options).is_ok());
+    }
+}
@@ -0,0 +1,792 @@
+use std::rc::Rc;
+
+use pkl_ast::{
+    Annotation, BinaryExpr, BinaryOp, CallExpr, Expr, Ident, Parameter, Span, StringLiteral, StringPart, SuperExpr,
+    Type, UnaryOp,
+};
+
+use pkl_diagnostics::Code;
+
+use crate::builtins;
+use crate::class::{find_class, module_of, unknown_class};
+use crate::equality::values_equal;
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::log::{Log, LogLevel};
+use crate::modules;
+use crate::object::unsupported;
+use crate::runtime::{Class, Env, Frame, Key, Lambda, Method, Obj, Val};
+use crate::options::Deprecations;
+use crate::units;
+use crate::value::ObjectKind;
+
+/// Every scope is nested in the scope of a module, so there is always a receiver.
+const IN_MODULE: &str = "evaluation happens inside a module";
+
+/// The classes of `pkl:base` that can be used as values, like `Duration` as a key of a renderer's `converters`.
+const BASE_CLASSES: &[&str] = &[
+    "Any", "Null", "Boolean", "Number", "Int", "Float", "String", "Duration", "DataSize", "Regex", "RegexMatch",
+    "Function", "Class", "Object", "Typed", "Dynamic", "Listing", "Mapping", "Collection", "List", "Set", "Map",
+];
+
+impl<'a> Evaluator<'a> {
+    pub(crate) fn eval_expr(&self, expr: &'a Expr<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        self.check_timeout(expr.span())?;
+        match expr {
+            Expr::Null(_) => Ok(Val::Null),
+            Expr::Bool(_, value) => Ok(Val::Boolean(*value)),
+            Expr::Int(_, value) => Ok(Val::Int(*value)),
+            Expr::Float(_, value) => Ok(Val::Float(*value)),
+            Expr::String(literal) => self.eval_string(literal, env),
+            Expr::This(_) => Ok(env
+                .scopes()
+                .find_map(|scope| match &scope.frame {
+                    Frame::Object { this, .. } => Some(Val::Object(this.clone())),
+                    Frame::Constraint(value) => Some(value.clone()),
+                    Frame::Bindings(_) => None,
+                })
+                .expect(IN_MODULE)),
+            Expr::Outer(span) => match env.receivers().nth(1) {
+                Some(object) => Ok(Val::Object(object.clone())),
+                None => Err(EvalError::new(*span, "`outer` is only defined inside an object body")),
+            },
+            Expr::Module(_) => Ok(Val::Object(env.receivers().last().expect(IN_MODULE).clone())),
+            Expr::Ident(ident) => self.lookup(ident, env),
+            Expr::Member(member) => match self.eval_expr(&member.receiver, env)? {
+                Val::Null if member.null_safe => Ok(Val::Null),
+                receiver => {
+                    if let Val::Object(object) = &receiver {
+                        let deprecated = object.deprecation(&Key::Property(member.name.name.into()));
+                        self.check_deprecated(member.name.span, env, member.name.name, deprecated)?;
+                    }
+                    self.property(&receiver, &member.name)
+                }
+            },
+            Expr::Call(call) => self.eval_call(call, env),
+            Expr::Subscript(subscript) => {
+                let receiver = match &subscript.receiver {
+                    Some(receiver) => Some(self.eval_expr(receiver, env)?),
+                    None => None,
+                };
+                let index = self.eval_expr(&subscript.index, env)?;
+                match receiver {
+                    Some(receiver) => self.subscript(subscript.span, &receiver, index),
+                    None => self.super_subscript(subscript.span, env, index),
+                }
+            }
+            Expr::Unary(unary) => {
+                let operand = self.eval_expr(&unary.operand, env)?;
+                match (unary.op, operand) {
+                    (UnaryOp::Neg, Val::Int(value)) => {
+                        value.checked_neg().map(Val::Int).ok_or_else(|| overflow(unary.span))
+                    }
+                    (UnaryOp::Neg, Val::Float(value)) => Ok(Val::Float(-value)),
+                    (UnaryOp::Not, Val::Boolean(value)) => Ok(Val::Boolean(!value)),
+                    (UnaryOp::Neg, value @ (Val::Duration(_) | Val::DataSize(_))) => {
+                        units::negate(unary.span, &value)
+                    }
+                    (op, operand) => {
+                        let op = if op == UnaryOp::Neg { "-" } else { "!" };
+                        let message = format!("operator `{op}` isn't defined for `{}`", operand.type_name());
+                        Err(EvalError::new(unary.span, message))
+                    }
+                }
+            }
+            Expr::Binary(binary) => self.eval_binary(binary, env),
+            Expr::New(new) => {
+                let kind = match &new.ty {
+                    None => ObjectKind::Dynamic,
+                    Some(Type::Named(named)) if named.name.parts.len() == 1 => match named.name.parts[0].name {
+                        "Dynamic" => ObjectKind::Dynamic,
+                        "Listing" | "Mapping" => {
+                            let parent = self.type_default(new.ty.as_ref().expect("matched"), env)?;
+                            return self.amend(parent.expect("listings and mappings have defaults"), &new.body, env);
+                        }
+                        name => {
+                            // the module's own classes shadow those of `pkl:base`
+                            let prototype = match modules::base_class(name) {
+                                Some(prototype) if find_class(module_of(env), name).is_none() => Rc::new(prototype),
+                                _ => self.class_prototype(named.span, name, env)?,
+                            };
+                            return self.amend(Val::Object(prototype), &new.body, env);
+                        }
+                    },
+                    Some(Type::Named(named)) if named.name.parts.len() == 2 => {
+                        let [module, class] = [&named.name.parts[0], &named.name.parts[1]];
+                        let prototype = match self.lookup(module, env)? {
+                            // the classes of imported modules, or of standard library modules like `json.Parser`
+                            Val::Object(module) => match find_class(&module, class.name) {
+                                Some((_, class)) => self.prototype(named.span, class, &module)?,
+                                None => match modules::module_class(&module, class.name) {
+                                    Some(prototype) => Rc::new(prototype),
+                                    None => return Err(unknown_class(named)),
+                                },
+                            },
+                            _ => return Err(unknown_class(named)),
+                        };
+                        return self.amend(Val::Object(prototype), &new.body, env);
+                    }
+                    Some(ty) => return Err(unsupported(ty.span(), "types other than classes")),
+                };
+                self.amend(Val::Object(Rc::new(Obj::new(kind, None, None))), &new.body, env)
+            }
+            Expr::Amend(amend) => {
+                let parent = self.eval_expr(&amend.parent, env)?;
+                self.amend(parent, &amend.body, env)
+            }
+            Expr::Parenthesized(parenthesized) => self.eval_expr(&parenthesized.expr, env),
+            Expr::Super(expr) => self.eval_super(expr, env),
+            Expr::NonNull(expr) => match self.eval_expr(&expr.operand, env)? {
+                Val::Null => {
+                    let error = EvalError::new(expr.operand.span(), "expected a non-null value, but got `null`");
+                    Err(error.with_code(Code::NullValue))
+                }
+                value => Ok(value),
+            },
+            Expr::Is(test) => {
+                let value = self.eval_expr(&test.value, env)?;
+                Ok(Val::Boolean(self.is_type(&value, &test.ty, env)?))
+            }
+            Expr::As(test) => {
+                let value = self.eval_expr(&test.value, env)?;
+                self.cast(test.value.span(), value, &test.ty, env)
+            }
+            Expr::If(expr) => match self.eval_expr(&expr.condition, env)? {
+                Val::Boolean(true) => self.eval_expr(&expr.then, env),
+                Val::Boolean(false) => self.eval_expr(&expr.otherwise, env),
+                condition => {
+                    let message = format!("expected a `Boolean` condition, but got `{}`", condition.type_name());
+                    Err(EvalError::new(expr.condition.span(), message))
+                }
+            },
+            Expr::Let(expr) => {
+                let value = self.eval_expr(&expr.value, env)?;
+                if let Some(ty) = &expr.param.ty {
+                    self.check_type(expr.value.span(), &value, ty, env)?;
+                }
+                let bindings = expr.param.name.iter().map(|name| (name.name, value.clone())).collect();
+                self.eval_expr(&expr.body, &Env::new(Frame::Bindings(bindings), Some(env.clone())))
+            }
+            Expr::Lambda(lambda) => {
+                Ok(Val::Function(Rc::new(Lambda { params: &lambda.params, body: &lambda.body, env: env.clone() })))
+            }
+            Expr::Throw(expr) => match self.eval_expr(&expr.value, env)? {
+                Val::String(message) => Err(EvalError::new(expr.span, message.to_string()).with_code(Code::Thrown)),
+                value => {
+                    let message = format!("expected a `String` message to throw, but got `{}`", value.type_name());
+                    Err(EvalError::new(expr.value.span(), message))
+                }
+            },
+            Expr::Trace(expr) => {
+                let value = self.eval_expr(&expr.value, env)?;
+                self.trace(&expr.value, &value, env);
+                Ok(value)
+            }
+            Expr::Import(expr) => {
+                let base = self.module_uri(module_of(env));
+                Ok(Val::Object(if expr.glob {
+                    self.import_glob(expr.uri.span, expr.uri.value, &base)?
+                } else {
+                    self.import(expr.uri.span, expr.uri.value, &base)?
+                }))
+            }
+            Expr::Read(expr) => {
+                let uri = self.eval_expr(&expr.uri, env)?;
+                let Val::String(uri) = uri else {
+                    let message = format!("expected a `String` URI to read, but got `{}`", uri.type_name());
+                    return Err(EvalError::new(expr.uri.span(), message));
+                };
+                self.read(expr.span, expr.kind, &uri, &self.module_uri(module_of(env)))
+            }
+            Expr::Error(span) => Err(EvalError::new(*span, "can't evaluate an expression with syntax errors")),
+        }
+    }
+
+    /// Resolves an unqualified name: parameters first, then for each enclosing object body from the innermost out,
+    /// its local properties and the properties of its receiver. In a type constraint, the properties of the checked
+    /// value come first, and the classes of the module last.
+    fn lookup(&self, ident: &Ident<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        let missing = || EvalError::new(ident.span, format!("can't find property `{}`", ident.name));
+        self.resolve(ident, env)?.ok_or_else(|| missing().with_code(Code::UnknownProperty))
+    }
+
+    /// Logs the source and value of a traced expression, and where it is.
+    fn trace(&self, expr: &Expr<'a>, value: &Val<'a>, env: &Rc<Env<'a>>) {
+        let uri = self.module_uri(module_of(env));
+        let span = expr.span();
+        let source = self.source(&uri);
+        let text = source.and_then(|source| source.get(span.start..span.end)).unwrap_or("<expression>");
+
+        let mut shown = String::new();
+        if self.write_value(span, &mut shown, value).is_err() {
+            // objects and functions aren't written out
+            shown = match value {
+                Val::Object(object) => format!("new {} {{ ... }}", object.kind.class_name()),
+                value => format!("<{}>", value.type_name()),
+            };
+        }
+        let message = format!("{text} = {shown}");
+        (self.logger)(&Log { level: LogLevel::Trace, message: &message, code: None, uri: &uri, span, source });
+    }
+
+    /// The source of the module at `uri`, if it's known.
+    fn source(&self, uri: &str) -> Option<&'a str> {
+        let sources = self.sources.borrow();
+        sources.iter().rev().find(|(module, _)| *module == uri).map(|(_, source)| *source)
+    }
+
+    /// Warns about or fails on the use at `span` of the member `name`, if it's `deprecated`, as the options say.
+    fn check_deprecated(
+        &self,
+        span: Span,
+        env: &Rc<Env<'a>>,
+        name: &str,
+        deprecated: Option<&'a Annotation<'a>>,
+    ) -> Result<()> {
+        let Some(annotation) = deprecated else { return Ok(()) };
+        let message = || pkl_ast::Deprecation::read(annotation).describe(name);
+        match self.options.deprecations {
+            Deprecations::Ignore => Ok(()),
+            Deprecations::Error => Err(EvalError::new(span, message()).with_code(Code::Deprecated)),
+            Deprecations::Warn => {
+                let uri = self.module_uri(module_of(env));
+                let use_site = (annotation as *const Annotation as usize, uri.clone(), span.start);
+                if self.warned.borrow_mut().insert(use_site) {
+                    (self.logger)(&Log {
+                        level: LogLevel::Warn,
+                        message: &message(),
+                        code: Some(Code::DeprecatedWarning),
+                        uri: &uri,
+                        span,
+                        source: self.source(&uri),
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Resolves an unqualified name like [`Evaluator::lookup`], or returns `None` if nothing has the name.
+    pub(crate) fn resolve(&self, ident: &Ident<'a>, env: &Rc<Env<'a>>) -> Result<Option<Val<'a>>> {
+        for scope in env.scopes() {
+            match &scope.frame {
+                Frame::Bindings(bindings) => {
+                    if let Some((_, value)) = bindings.iter().rev().find(|(name, _)| *name == ident.name) {
+                        return Ok(Some(value.clone()));
+                    }
+                }
+                Frame::Object { this, layer } => {
+                    if let Some(value) = self.local(this, layer, ident.name)? {
+                        return Ok(Some(value));
+                    }
+                    let key = Key::Property(ident.name.into());
+                    if self.has_member(this, &key) {
+                        self.check_deprecated(ident.span, env, ident.name, this.deprecation(&key))?;
+                        return self.member(this, &key);
+                    }
+                }
+                Frame::Constraint(value) => {
+                    if let Val::Object(object) = value {
+                        if let Some(value) = self.member(object, &Key::Property(ident.name.into()))? {
+                            return Ok(Some(value));
+                        }
+                    }
+                    if let Some(value) = self.base_property(ident.span, value, ident.name)? {
+                        return Ok(Some(value));
+                    }
+                }
+            }
+        }
+
+        let module = module_of(env);
+        let declared = find_class(module, ident.name).map(|(_, decl)| (decl, module.clone()));
+        if declared.is_none() && !BASE_CLASSES.contains(&ident.name) {
+            return Ok(None);
+        }
+        Ok(Some(Val::Class(Rc::new(Class { name: ident.name, declared }))))
+    }
+
+    fn eval_string(&self, literal: &'a StringLiteral<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        // the text parts are borrowed from the arena, and the interpolated values are written straight after them
+        let texts = literal.parts.iter().map(|part| match part {
+            StringPart::Text(_, part) => part.len(),
+            StringPart::Interpolation(_) => 0,
+        });
+        let mut text = String::with_capacity(texts.sum());
+        for part in literal.parts.iter() {
+            match part {
+                StringPart::Text(_, part) => text.push_str(part),
+                StringPart::Interpolation(expr) => {
+                    let value = self.eval_expr(expr, env)?;
+                    self.write_string(expr.span(), &mut text, &value)?;
+                }
+            }
+        }
+
+        Ok(Val::String(text.into()))
+    }
+
+    fn eval_call(&self, call: &'a CallExpr<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        let receiver = match &call.receiver {
+            Some(receiver) => Some(self.eval_expr(receiver, env)?),
+            None => None,
+        };
+        // `?.` skips evaluating the arguments too
+        if call.null_safe && matches!(receiver, Some(Val::Null)) {
+            return Ok(Val::Null);
+        }
+        let mut args = Vec::with_capacity(call.args.len());
+        for arg in call.args.iter() {
+            args.push(self.eval_expr(arg, env)?);
+        }
+
+        let name = call.name.name;
+        let call_method = |this: &Rc<Obj<'a>>, layer: &Rc<Obj<'a>>, method: &Method<'a>, args| {
+            self.check_deprecated(call.name.span, env, name, method.deprecated)?;
+            self.call_method(call.span, this, layer, method, args)
+        };
+        match receiver {
+            Some(receiver) => {
+                if let Val::Object(object) = &receiver {
+                    if let Some((layer, method)) = self.method(object, name) {
+                        return call_method(object, layer, method, args);
+                    }
+                    // a property holding a function, like `birds.describe(bird)` for `describe = (bird) -> ...`
+                    if let Some(Val::Function(function)) = self.member(object, &Key::Property(name.into()))? {
+                        return self.apply(call.span, &function, args);
+                    }
+                }
+                if let Some(value) = self.base_method(call.span, &receiver, name, args)? {
+                    return Ok(value);
+                }
+                let message = format!("can't find method `{name}` on a value of type `{}`", receiver.type_name());
+                return Err(EvalError::new(call.name.span, message));
+            }
+            None => {
+                for scope in env.scopes() {
+                    if let Frame::Constraint(value) = &scope.frame {
+                        if let Val::Object(object) = value {
+                            if let Some((layer, method)) = self.method(object, name) {
+                                return call_method(object, layer, method, args);
+                            }
+                        }
+                        if let Some(value) = self.base_method(call.span, value, name, args.clone())? {
+                            return Ok(value);
+                        }
+                    }
+                    let Frame::Object { this, layer } = &scope.frame else { continue };
+                    if let Some(method) = layer.methods.iter().find(|method| method.name == name && method.local) {
+                        return call_method(this, layer, method, args);
+                    }
+                    if let Some((layer, method)) = self.method(this, name) {
+                        return call_method(this, layer, method, args);
+                    }
+                }
+                match self.resolve(&call.name, env)? {
+                    Some(Val::Function(function)) => return self.apply(call.span, &function, args),
+                    // `List(...)` calls the base method rather than the class
+                    None => {}
+                    Some(Val::Class(class)) if class.declared.is_none() => {}
+                    Some(value) => {
+                        let class = value.type_name();
+                        let message = format!("can't call `{name}`, which is a value of type `{class}`");
+                        return Err(EvalError::new(call.name.span, message));
+                    }
+                }
+                if let Some(result) = builtins::call(call.span, name, args) {
+                    return result;
+                }
+            }
+        }
+
+        Err(EvalError::new(call.name.span, format!("can't find method `{name}`")).with_code(Code::UnknownMethod))
+    }
+
+    /// Calls a method defined by `layer` of `this`, whose body sees the parameters and the scope of the method.
+    fn call_method(
+        &self,
+        span: Span,
+        this: &Rc<Obj<'a>>,
+        layer: &Rc<Obj<'a>>,
+        method: &Method<'a>,
+        args: Vec<Val<'a>>,
+    ) -> Result<Val<'a>> {
+        let Some(body) = method.body else {
+            return Err(EvalError::new(span, format!("method `{}` has no body", method.name)));
+        };
+        if args.len() != method.params.len() {
+            let (name, expected) = (method.name, method.params.len());
+            let message = format!("method `{name}` takes {expected} arguments, but {} were given", args.len());
+            return Err(EvalError::new(span, message));
+        }
+
+        let scope = Env::new(Frame::Object { this: this.clone(), layer: layer.clone() }, layer.env.clone());
+        let bindings = self.bind(span, method.params, args, &scope)?;
+        let result = self.nested(span, || self.eval_expr(body, &Env::new(Frame::Bindings(bindings), Some(scope))));
+        let description = || format!("method `{}`", method.name);
+        result.map_err(|error| error.in_frame(description(), self.layer_uri(this, layer), body.span()))
+    }
+
+    /// Evaluates `super.name` or `super.name(args)`, which refer to the members that the layer of the innermost object
+    /// body amends, still with `this` as their receiver.
+    fn eval_super(&self, expr: &'a SuperExpr<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        let (this, layer) = innermost_layer(env);
+        let name = expr.name.name;
+
+        let Some(args) = &expr.args else {
+            return match self.inherited(this, layer, &Key::Property(name.into()))? {
+                Some(value) => Ok(value),
+                None => {
+                    let error = EvalError::new(expr.name.span, format!("can't find property `{name}` in `super`"));
+                    Err(error.with_code(Code::UnknownProperty))
+                }
+            };
+        };
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args.iter() {
+            values.push(self.eval_expr(arg, env)?);
+        }
+        match layer.parent.as_ref().and_then(|parent| self.method(parent, name)) {
+            Some((layer, method)) => self.call_method(expr.span, this, layer, method, values),
+            None => {
+                let error = EvalError::new(expr.name.span, format!("can't find method `{name}` in `super`"));
+                Err(error.with_code(Code::UnknownMethod))
+            }
+        }
+    }
+
+    /// Calls a function value with the given arguments.
+    pub(crate) fn apply(&self, span: Span, function: &Lambda<'a>, args: Vec<Val<'a>>) -> Result<Val<'a>> {
+        if args.len() != function.params.len() {
+            let expected = function.params.len();
+            let message = format!("the function takes {expected} arguments, but {} were given", args.len());
+            return Err(EvalError::new(span, message));
+        }
+
+        let bindings = self.bind(span, function.params, args, &function.env)?;
+        let env = Env::new(Frame::Bindings(bindings), Some(function.env.clone()));
+        self.nested(span, || self.eval_expr(function.body, &env))
+    }
+
+    /// Binds the parameters of a method or function to the arguments of a call at `span`, checking the arguments
+    /// against the types of the parameters, which are written in the scope `env`.
+    fn bind(
+        &self,
+        span: Span,
+        params: &'a [Parameter<'a>],
+        args: Vec<Val<'a>>,
+        env: &Rc<Env<'a>>,
+    ) -> Result<Vec<(&'a str, Val<'a>)>> {
+        let mut bindings = Vec::with_capacity(args.len());
+        for (param, arg) in params.iter().zip(args) {
+            if let Some(ty) = &param.ty {
+                self.check_type(span, &arg, ty, env)?;
+            }
+            if let Some(name) = &param.name {
+                bindings.push((name.name, arg));
+            }
+        }
+        Ok(bindings)
+    }
+
+    /// Evaluates a binary operation. A chain of them like `1 + 1 + 1` nests to the left, and is evaluated from the
+    /// innermost operation out rather than by recursion, so that a long one doesn't overflow the stack.
+    fn eval_binary(&self, binary: &'a BinaryExpr<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        let mut chain = vec![binary];
+        let mut innermost = binary;
+        while let Expr::Binary(left) = &innermost.left {
+            innermost = left;
+            chain.push(innermost);
+        }
+        let mut left = self.eval_expr(&innermost.left, env)?;
+        for binary in chain.into_iter().rev() {
+            left = self.apply_binary(binary, left, env)?;
+        }
+        Ok(left)
+    }
+
+    /// Applies a binary operation to its evaluated left operand.
+    fn apply_binary(&self, binary: &'a BinaryExpr<'a>, left: Val<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        match binary.op {
+            BinaryOp::And | BinaryOp::Or => {
+                let Val::Boolean(left) = left else { return Err(mismatch(binary, &left, None)) };
+                if left == (binary.op == BinaryOp::Or) {
+                    return Ok(Val::Boolean(left));
+                }
+                return match self.eval_expr(&binary.right, env)? {
+                    Val::Boolean(right) => Ok(Val::Boolean(right)),
+                    right => Err(mismatch(binary, &Val::Boolean(left), Some(&right))),
+                };
+            }
+            BinaryOp::NullCoalesce => {
+                return match left {
+                    Val::Null => self.eval_expr(&binary.right, env),
+                    left => Ok(left),
+                };
+            }
+            BinaryOp::Pipe => {
+                return match self.eval_expr(&binary.right, env)? {
+                    Val::Function(function) => self.apply(binary.span, &function, vec![left]),
+                    right => Err(mismatch(binary, &left, Some(&right))),
+                };
+            }
+            _ => {}
+        }
+
+        let right = self.eval_expr(&binary.right, env)?;
+        match binary.op {
+            BinaryOp::Eq => Ok(Val::Boolean(self.equal(&left, &right)?)),
+            BinaryOp::NotEq => Ok(Val::Boolean(!self.equal(&left, &right)?)),
+            _ => binary_op(binary, left, right),
+        }
+    }
+
+    /// Reads the property `name` of a value.
+    fn property(&self, receiver: &Val<'a>, name: &Ident) -> Result<Val<'a>> {
+        if let Val::Object(object) = receiver {
+            if let Some(value) = self.member(object, &Key::Property(name.name.into()))? {
+                return Ok(value);
+            }
+        }
+        if let Some(value) = self.base_property(name.span, receiver, name.name)? {
+            return Ok(value);
+        }
+
+        let message = format!("can't find property `{}` on a value of type `{}`", name.name, receiver.type_name());
+        Err(EvalError::new(name.span, message))
+    }
+
+    fn subscript(&self, span: Span, receiver: &Val<'a>, index: Val<'a>) -> Result<Val<'a>> {
+        let element = |elements: &[Val<'a>]| match index {
+            Val::Int(i) => usize::try_from(i).ok().and_then(|i| elements.get(i)).cloned(),
+            _ => None,
+        };
+
+        let found = match receiver {
+            Val::List(elements) => element(elements),
+            Val::Map(entries) => {
+                entries.iter().find(|(key, _)| values_equal(key, &index)).map(|(_, value)| value.clone())
+            }
+            Val::Object(object) => {
+                let mut found = None;
+                for key in subscript_keys(&object.kind, index) {
+                    found = self.member(object, &key)?;
+                    if found.is_some() {
+                        break;
+                    }
+                }
+                found
+            }
+            _ => {
+                let message = format!("can't subscript a value of type `{}`", receiver.type_name());
+                return Err(EvalError::new(span, message));
+            }
+        };
+
+        found.ok_or_else(|| EvalError::new(span, "there is no element or entry for this key"))
+    }
+
+    /// Evaluates `super[index]`, the entry or element that the layer of the innermost object body amends.
+    fn super_subscript(&self, span: Span, env: &Rc<Env<'a>>, index: Val<'a>) -> Result<Val<'a>> {
+        let (this, layer) = innermost_layer(env);
+        for key in subscript_keys(&this.kind, index) {
+            if let Some(value) = self.inherited(this, layer, &key)? {
+                return Ok(value);
+            }
+        }
+        Err(EvalError::new(span, "there is no element or entry for this key in `super`"))
+    }
+}
+
+/// The members of an object of the given kind that `object[index]` may refer to, in the order to try them.
+fn subscript_keys<'a>(kind: &ObjectKind, index: Val<'a>) -> Vec<Key<'a>> {
+    let element = match index {
+        Val::Int(i) => usize::try_from(i).ok().map(Key::Element),
+        _ => None,
+    };
+    match kind {
+        ObjectKind::Listing => element.into_iter().collect(),
+        ObjectKind::Dynamic => std::iter::once(Key::Entry(index)).chain(element).collect(),
+        _ => vec![Key::Entry(index)],
+    }
+}
+
+/// The receiver and layer of the innermost object body of a scope.
+fn innermost_layer<'e, 'a>(env: &'e Rc<Env<'a>>) -> (&'e Rc<Obj<'a>>, &'e Rc<Obj<'a>>) {
+    env.scopes()
+        .find_map(|scope| match &scope.frame {
+            Frame::Object { this, layer } => Some((this, layer)),
+            Frame::Bindings(_) | Frame::Constraint(_) => None,
+        })
+        .expect(IN_MODULE)
+}
+
+pub(crate) fn binary_op<'a>(binary: &BinaryExpr, left: Val<'a>, right: Val<'a>) -> Result<Val<'a>> {
+    use Val::{Float, Int};
+
+    let span = binary.span;
+    let value = match (binary.op, &left, &right) {
+        (BinaryOp::Eq, _, _) => Val::Boolean(values_equal(&left, &right)),
+        (BinaryOp::NotEq, _, _) => Val::Boolean(!values_equal(&left, &right)),
+        (_, Val::Duration(_) | Val::DataSize(_), _) | (_, _, Val::Duration(_) | Val::DataSize(_)) => {
+            units::quantity_op(binary, &left, &right)?.ok_or_else(|| mismatch(binary, &left, Some(&right)))?
+        }
+
+        (BinaryOp::Add, Int(l), Int(r)) => Int(l.checked_add(*r).ok_or_else(|| overflow(span))?),
+        (BinaryOp::Sub, Int(l), Int(r)) => Int(l.checked_sub(*r).ok_or_else(|| overflow(span))?),
+        (BinaryOp::Mul, Int(l), Int(r)) => Int(l.checked_mul(*r).ok_or_else(|| overflow(span))?),
+        (BinaryOp::IntDiv, Int(_), Int(0)) | (BinaryOp::Rem, Int(_), Int(0)) => {
+            return Err(EvalError::new(span, "division by zero").with_code(Code::DivisionByZero));
+        }
+        (BinaryOp::IntDiv, Int(l), Int(r)) => Int(l.checked_div(*r).ok_or_else(|| overflow(span))?),
+        (BinaryOp::Rem, Int(l), Int(r)) => Int(l.checked_rem(*r).ok_or_else(|| overflow(span))?),
+        (BinaryOp::Pow, Int(l), Int(r)) if *r >= 0 => {
+            let exponent = u32::try_from(*r).map_err(|_| overflow(span))?;
+            Int(l.checked_pow(exponent).ok_or_else(|| overflow(span))?)
+        }
+
+        (BinaryOp::Add, Val::String(l), Val::String(r)) => Val::String(format!("{l}{r}").into()),
+        (BinaryOp::Add, Val::List(l), Val::List(r)) => Val::List(l.iter().chain(r.iter()).cloned().collect()),
+
+        (op, _, _) => match (as_f64(&left), as_f64(&right)) {
+            (Some(l), Some(r)) => match op {
+                BinaryOp::Add => Float(l + r),
+                BinaryOp::Sub => Float(l - r),
+                BinaryOp::Mul => Float(l * r),
+                BinaryOp::Div => Float(l / r),
+                BinaryOp::IntDiv => Int((l / r).trunc() as i64),
+                BinaryOp::Rem => Float(l % r),
+                BinaryOp::Pow => Float(l.powf(r)),
+                BinaryOp::Lt => Val::Boolean(l < r),
+                BinaryOp::LtEq => Val::Boolean(l <= r),
+                BinaryOp::Gt => Val::Boolean(l > r),
+                BinaryOp::GtEq => Val::Boolean(l >= r),
+                _ => return Err(mismatch(binary, &left, Some(&right))),
+            },
+            _ => match (op, &left, &right) {
+                (BinaryOp::Lt, Val::String(l), Val::String(r)) => Val::Boolean(l < r),
+                (BinaryOp::LtEq, Val::String(l), Val::String(r)) => Val::Boolean(l <= r),
+                (BinaryOp::Gt, Val::String(l), Val::String(r)) => Val::Boolean(l > r),
+                (BinaryOp::GtEq, Val::String(l), Val::String(r)) => Val::Boolean(l >= r),
+                _ => return Err(mismatch(binary, &left, Some(&right))),
+            },
+        },
+    };
+
+    Ok(value)
+}
+
+fn as_f64(value: &Val) -> Option<f64> {
+    match value {
+        Val::Int(value) => Some(*value as f64),
+        Val::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+pub(crate) fn overflow(span: Span) -> EvalError {
+    EvalError::new(span, "integer overflow").with_code(Code::IntegerOverflow)
+}
+
+fn mismatch(binary: &BinaryExpr, left: &Val, right: Option<&Val>) -> EvalError {
+    let op = binary.op.as_str();
+    let message = match right {
+        Some(right) => format!("operator `{op}` isn't defined for `{}` and `{}`", left.type_name(), right.type_name()),
+        None => format!("operator `{op}` isn't defined for `{}`", left.type_name()),
+    };
+    EvalError::new(binary.span, message).with_code(Code::UndefinedOperator)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate_expr;
+    use crate::value::Value;
+
+    fn eval(source: &str) -> Value {
+        evaluate_expr(source).unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    fn error(source: &str) -> String {
+        evaluate_expr(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("1 + 2 * 3 - 4"), Value::Int(3));
+        assert_eq!(eval("7 ~/ 2 + 7 % 2"), Value::Int(4));
+        assert_eq!(eval("2 ** 10"), Value::Int(1024));
+        assert_eq!(eval("1 / 2"), Value::Float(0.5));
+        assert_eq!(eval("1 + 0.5"), Value::Float(1.5));
+        assert_eq!(eval("-(3)"), Value::Int(-3));
+        assert_eq!(error("9223372036854775807 + 1"), "integer overflow");
+        assert_eq!(error("1 ~/ 0"), "division by zero");
+        assert_eq!(error("1 + \"a\""), "operator `+` isn't defined for `Int` and `String`");
+        // a long chain is evaluated without recursing into it
+        assert_eq!(eval(&format!("1{}", " + 1".repeat(1000))), Value::Int(1001));
+        assert_eq!(eval(&format!("false{} || true", " && 1 / 0 > 1".repeat(1000))), Value::Boolean(true));
+    }
+
+    #[test]
+    fn comparisons_and_logic() {
+        assert_eq!(eval("1 < 2 && 2.5 >= 2"), Value::Boolean(true));
+        assert_eq!(eval("1 == 1.0"), Value::Boolean(true));
+        assert_eq!(eval("\"a\" != \"b\" || 1 / 0 > 1"), Value::Boolean(true));
+        assert_eq!(eval("!true"), Value::Boolean(false));
+        assert_eq!(error("1 && true"), "operator `&&` isn't defined for `Int`");
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(eval("\"a\" + \"b\""), Value::String("ab".into()));
+        assert_eq!(eval("\"\\(1) + \\(1.5) = \\(true)\""), Value::String("1 + 1.5 = true".into()));
+    }
+
+    #[test]
+    fn collections() {
+        assert_eq!(eval("List(1, 2) + List(3)"), Value::List(vec![Value::Int(1), Value::Int(2), Value::Int(3)]));
+        assert_eq!(eval("List(1, 2)[1]"), Value::Int(2));
+        assert_eq!(eval("Map(\"a\", 1)[\"a\"]"), Value::Int(1));
+        assert_eq!(eval("Set(1, 1, 2)"), Value::Set(vec![Value::Int(1), Value::Int(2)]));
+        assert_eq!(eval("new Listing { 1; 2 }[0]"), Value::Int(1));
+    }
+
+    #[test]
+    fn null_safety() {
+        assert_eq!(eval("null ?? 1"), Value::Int(1));
+        assert_eq!(eval("2 ?? 1 / 0"), Value::Int(2));
+        assert_eq!(eval("null?.length"), Value::Null);
+        assert_eq!(eval("\"abc\"?.length"), Value::Int(3));
+        assert_eq!(eval("null?.repeat(1 / 0) ?? \"none\""), Value::String("none".into()));
+        assert_eq!(eval("\"a\"!!"), Value::String("a".into()));
+        assert_eq!(error("null!!"), "expected a non-null value, but got `null`");
+        let error = evaluate_expr("List(1, null)[1]!!").unwrap_err();
+        let crate::Error::Eval(error) = error else { panic!() };
+        assert_eq!(error.span, pkl_ast::Span::new(0, 16));
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(eval("((x) -> x + 1).apply(1)"), Value::Int(2));
+        assert_eq!(eval("let (x = 2) let (y = x * 3) x + y"), Value::Int(8));
+        assert_eq!(eval("let (add = (a) -> (b) -> a + b) add.apply(1).apply(2)"), Value::Int(3));
+        assert_eq!(eval("let (double = (n: Int) -> n * 2) double(4)"), Value::Int(8));
+        assert_eq!(eval("3 |> (n) -> n * n"), Value::Int(9));
+        assert_eq!(eval("new Dynamic { f = (n) -> n + k; k = 10 }.f(1)"), Value::Int(11));
+
+        assert_eq!(error("let (x: String = 1) x"), "expected a value of type `String`, but got `Int`");
+        assert_eq!(error("((n: Int) -> n).apply(\"a\")"), "expected a value of type `Int`, but got `String`");
+        assert_eq!(error("let (x = 1) x(2)"), "can't call `x`, which is a value of type `Int`");
+    }
+
+    #[test]
+    fn conditions_and_throw() {
+        assert_eq!(eval("if (1 < 2) \"yes\" else 1 / 0"), Value::String("yes".into()));
+        assert_eq!(eval("if (false) 1 else if (true) 2 else 3"), Value::Int(2));
+        assert_eq!(error("if (1) 2 else 3"), "expected a `Boolean` condition, but got `Int`");
+
+        let error = evaluate_expr("1 + throw(\"no \\(1 + 1)\")").unwrap_err();
+        let crate::Error::Eval(error) = error else { panic!() };
+        assert_eq!(error.message, "no 2");
+        assert_eq!(error.span, pkl_ast::Span::new(4, 24));
+    }
+}
@@ -0,0 +1,585 @@
+//! A C API for embedding the evaluator in applications written in other languages, enabled by the `ffi` feature and
+//! declared in `include/pkl.h`. Build it as a library with
+//! `cargo rustc -p pkl-eval --features ffi --release --crate-type cdylib` (or `staticlib`).
+//!
+//! An evaluator holds the options modules are evaluated with, and evaluating a module returns its value, which the
+//! `pkl_value_*` functions read. Values returned by evaluating are owned by the caller and freed with
+//! `pkl_free_value`; the values inside them are borrowed, and live as long as the value they're in. Strings returned
+//! by the API, including error messages, are owned by the caller and freed with `pkl_free_string`.
+//!
+//! Every function accepts null pointers, doing nothing or returning a null pointer, zero, or `false` for them. A panic
+//! never unwinds into the caller: evaluating a module fails with an error saying the evaluator panicked, and the other
+//! functions do what they do for a null pointer.
+
+#![allow(unsafe_code)]
+
+use std::any::Any;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::value::Value;
+use crate::EvaluatorOptions;
+
+/// The options modules are evaluated with.
+pub struct PklEvaluator {
+    options: EvaluatorOptions,
+}
+
+/// An evaluated value.
+#[repr(transparent)]
+pub struct PklValue(Value);
+
+/// The kinds of values, as returned by `pkl_value_kind`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PklKind {
+    Null = 0,
+    Boolean,
+    Int,
+    Float,
+    String,
+    Duration,
+    DataSize,
+    List,
+    Set,
+    Map,
+    Object,
+    Function,
+    Class,
+    Regex,
+}
+
+/// Creates an evaluator with the default options.
+#[no_mangle]
+pub extern "C" fn pkl_evaluator_new() -> *mut PklEvaluator {
+    guard(ptr::null_mut(), || Box::into_raw(Box::new(PklEvaluator { options: EvaluatorOptions::default() })))
+}
+
+/// Sets an external property, read by `read("prop:name")`.
+///
+/// # Safety
+///
+/// `evaluator` has to come from `pkl_evaluator_new`, and `name` and `value` have to be null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_evaluator_set_property(
+    evaluator: *mut PklEvaluator,
+    name: *const c_char,
+    value: *const c_char,
+) {
+    guard((), || {
+        // SAFETY: the caller passes a valid evaluator and strings, or null pointers
+        let Some(evaluator) = (unsafe { evaluator.as_mut() }) else { return };
+        // SAFETY: as above
+        let (Some(name), Some(value)) = (unsafe { string(name) }, unsafe { string(value) }) else { return };
+        evaluator.options.external_properties.insert(name, value);
+    })
+}
+
+/// Sets how many seconds an evaluation can take before it fails; 0 means there's no limit.
+///
+/// # Safety
+///
+/// `evaluator` has to come from `pkl_evaluator_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_evaluator_set_timeout(evaluator: *mut PklEvaluator, seconds: u64) {
+    guard((), || {
+        // SAFETY: the caller passes a valid evaluator, or a null pointer
+        if let Some(evaluator) = unsafe { evaluator.as_mut() } {
+            evaluator.options.timeout = (seconds > 0).then(|| std::time::Duration::from_secs(seconds));
+        }
+    })
+}
+
+/// Evaluates the module in the file at `path`. On failure, it returns a null pointer and sets `*error`, unless
+/// `error` is null, to the message.
+///
+/// # Safety
+///
+/// `evaluator` has to come from `pkl_evaluator_new`, `path` has to be a null-terminated string, and `error` a valid
+/// place to write a pointer to.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_eval_file(
+    evaluator: *const PklEvaluator,
+    path: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut PklValue {
+    // SAFETY: the caller passes a valid evaluator and string, or null pointers
+    let (Some(evaluator), Some(path)) = (unsafe { evaluator.as_ref() }, unsafe { string(path) }) else {
+        return ptr::null_mut();
+    };
+    let result = catch(|| {
+        let unreadable = |err: std::io::Error| format!("couldn't read {path}: {err}");
+        let source = std::fs::read_to_string(&path).map_err(unreadable)?;
+        let canonical = std::path::Path::new(&path).canonicalize().map_err(unreadable)?;
+        let uri = format!("file://{}", canonical.display());
+        crate::evaluate_with(&source, &uri, evaluator.options.clone()).map_err(|error| error.to_string())
+    });
+    // SAFETY: the caller passes a valid place for the error, or a null pointer
+    unsafe { value_or_error(result, error) }
+}
+
+/// Evaluates the source of a module, whose relative imports are resolved against the working directory. On failure,
+/// it returns a null pointer and sets `*error`, unless `error` is null, to the message.
+///
+/// # Safety
+///
+/// `evaluator` has to come from `pkl_evaluator_new`, `source` has to be a null-terminated string, and `error` a
+/// valid place to write a pointer to.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_eval_text(
+    evaluator: *const PklEvaluator,
+    source: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut PklValue {
+    // SAFETY: the caller passes a valid evaluator and string, or null pointers
+    let (Some(evaluator), Some(source)) = (unsafe { evaluator.as_ref() }, unsafe { string(source) }) else {
+        return ptr::null_mut();
+    };
+    let options = evaluator.options.clone();
+    let result = catch(|| crate::evaluate_with(&source, "repl:text", options).map_err(|error| error.to_string()));
+    // SAFETY: the caller passes a valid place for the error, or a null pointer
+    unsafe { value_or_error(result, error) }
+}
+
+/// The kind of a value.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_kind(value: *const PklValue) -> PklKind {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(PklKind::Null, || match unsafe { value.as_ref() }.map(|value| &value.0) {
+        None | Some(Value::Null) => PklKind::Null,
+        Some(Value::Boolean(_)) => PklKind::Boolean,
+        Some(Value::Int(_)) => PklKind::Int,
+        Some(Value::Float(_)) => PklKind::Float,
+        Some(Value::String(_)) => PklKind::String,
+        Some(Value::Duration(_)) => PklKind::Duration,
+        Some(Value::DataSize(_)) => PklKind::DataSize,
+        Some(Value::List(_)) => PklKind::List,
+        Some(Value::Set(_)) => PklKind::Set,
+        Some(Value::Map(_)) => PklKind::Map,
+        Some(Value::Object(_)) => PklKind::Object,
+        Some(Value::Function { .. }) => PklKind::Function,
+        Some(Value::Class { .. }) => PklKind::Class,
+        Some(Value::Regex { .. }) => PklKind::Regex,
+    })
+}
+
+/// Whether a value is `true`.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_boolean(value: *const PklValue) -> bool {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(false, || matches!(unsafe { value.as_ref() }, Some(PklValue(Value::Boolean(true)))))
+}
+
+/// The value of an `Int`, or 0 for other values.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_int(value: *const PklValue) -> i64 {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(0, || match unsafe { value.as_ref() } {
+        Some(PklValue(Value::Int(n))) => *n,
+        _ => 0,
+    })
+}
+
+/// The value of a `Float` or `Int`, or the magnitude of a `Duration` or `DataSize`, or 0 for other values.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_float(value: *const PklValue) -> f64 {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(0.0, || match unsafe { value.as_ref() } {
+        Some(PklValue(Value::Float(x))) => *x,
+        Some(PklValue(Value::Int(n))) => *n as f64,
+        Some(PklValue(Value::Duration(duration))) => duration.value.as_f64(),
+        Some(PklValue(Value::DataSize(size))) => size.value.as_f64(),
+        _ => 0.0,
+    })
+}
+
+/// The text of a value: a `String` itself, a `Duration` or `DataSize` as it's written in Pkl, like `5.min`, the
+/// pattern of a `Regex`, and the class name of an object or class. It's a null pointer for other values. A text with
+/// a null character in it can't be a C string, so for one it returns a null pointer and sets `*error`, unless `error`
+/// is null, to the message.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive, and `error` a valid place to write a pointer
+/// to.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_string(value: *const PklValue, error: *mut *mut c_char) -> *mut c_char {
+    let result = catch(|| {
+        // SAFETY: the caller passes a live value, or a null pointer
+        Ok(match unsafe { value.as_ref() } {
+            Some(PklValue(Value::String(s))) => Some(s.clone()),
+            Some(PklValue(Value::Duration(duration))) => Some(duration.to_string()),
+            Some(PklValue(Value::DataSize(size))) => Some(size.to_string()),
+            Some(PklValue(Value::Regex { pattern })) => Some(pattern.clone()),
+            Some(PklValue(Value::Class { name })) => Some(name.clone()),
+            Some(PklValue(Value::Object(object))) => Some(object.kind.class_name().to_string()),
+            _ => None,
+        })
+    });
+    // SAFETY: the caller passes a valid place for the error, or a null pointer
+    unsafe { string_or_error(result, error) }
+}
+
+/// The number of elements of a `List`, `Set`, or object, or 0 for other values.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_element_count(value: *const PklValue) -> usize {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(0, || unsafe { elements(value) }.len())
+}
+
+/// The element at `index` of a `List`, `Set`, or object, or a null pointer if there isn't one.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_element(value: *const PklValue, index: usize) -> *const PklValue {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(ptr::null(), || borrow(unsafe { elements(value) }.get(index)))
+}
+
+/// The number of properties of an object, or 0 for other values.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_property_count(value: *const PklValue) -> usize {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(0, || match unsafe { value.as_ref() } {
+        Some(PklValue(Value::Object(object))) => object.properties.len(),
+        _ => 0,
+    })
+}
+
+/// The name of the property at `index` of an object, in definition order, or a null pointer if there isn't one. For
+/// a name with a null character in it, it returns a null pointer and sets `*error`, unless `error` is null, to the
+/// message.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive, and `error` a valid place to write a pointer
+/// to.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_property_name(
+    value: *const PklValue,
+    index: usize,
+    error: *mut *mut c_char,
+) -> *mut c_char {
+    let result = catch(|| {
+        // SAFETY: the caller passes a live value, or a null pointer
+        Ok(match unsafe { value.as_ref() } {
+            Some(PklValue(Value::Object(object))) => object.properties.get_index(index).map(|(name, _)| name.clone()),
+            _ => None,
+        })
+    });
+    // SAFETY: the caller passes a valid place for the error, or a null pointer
+    unsafe { string_or_error(result, error) }
+}
+
+/// The value of the property of an object called `name`, or a null pointer if it doesn't have one.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive, and `name` a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_property(value: *const PklValue, name: *const c_char) -> *const PklValue {
+    // SAFETY: the caller passes a live value and a string, or null pointers
+    guard(ptr::null(), || match (unsafe { value.as_ref() }, unsafe { string(name) }) {
+        (Some(PklValue(Value::Object(object))), Some(name)) => borrow(object.property(&name)),
+        _ => ptr::null(),
+    })
+}
+
+/// The number of entries of a `Map` or object, or 0 for other values.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_entry_count(value: *const PklValue) -> usize {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(0, || unsafe { entries(value) }.len())
+}
+
+/// The key of the entry at `index` of a `Map` or object, or a null pointer if there isn't one.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_entry_key(value: *const PklValue, index: usize) -> *const PklValue {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(ptr::null(), || borrow(unsafe { entries(value) }.get(index).map(|(key, _)| key)))
+}
+
+/// The value of the entry at `index` of a `Map` or object, or a null pointer if there isn't one.
+///
+/// # Safety
+///
+/// `value` has to be a value returned by this API that's still alive.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_value_entry_value(value: *const PklValue, index: usize) -> *const PklValue {
+    // SAFETY: the caller passes a live value, or a null pointer
+    guard(ptr::null(), || borrow(unsafe { entries(value) }.get(index).map(|(_, value)| value)))
+}
+
+/// Frees an evaluator.
+///
+/// # Safety
+///
+/// `evaluator` has to come from `pkl_evaluator_new`, and can't be used after.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_free_evaluator(evaluator: *mut PklEvaluator) {
+    if !evaluator.is_null() {
+        // SAFETY: the evaluator was allocated by `pkl_evaluator_new`, and isn't used again
+        guard((), || drop(unsafe { Box::from_raw(evaluator) }));
+    }
+}
+
+/// Frees a value returned by `pkl_eval_file` or `pkl_eval_text`, and the values inside it.
+///
+/// # Safety
+///
+/// `value` has to come from `pkl_eval_file` or `pkl_eval_text`, and neither it nor the values inside it can be used
+/// after.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_free_value(value: *mut PklValue) {
+    if !value.is_null() {
+        // SAFETY: the value was allocated by `value_or_error`, and isn't used again
+        guard((), || drop(unsafe { Box::from_raw(value) }));
+    }
+}
+
+/// Frees a string returned by this API.
+///
+/// # Safety
+///
+/// `string` has to be a string returned by this API, and can't be used after.
+#[no_mangle]
+pub unsafe extern "C" fn pkl_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: the string was allocated by `c_string` or `set_error`, and isn't used again
+        guard((), || drop(unsafe { CString::from_raw(string) }));
+    }
+}
+
+/// A caller's string, or nothing for a null pointer or a string that isn't valid UTF-8.
+///
+/// # Safety
+///
+/// `string` has to be null or a null-terminated string.
+unsafe fn string(string: *const c_char) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    // SAFETY: the caller passes a null-terminated string
+    unsafe { CStr::from_ptr(string) }.to_str().ok().map(String::from)
+}
+
+/// A string for the caller to free, or else the error of it having a null character in it.
+fn c_string(string: String) -> Result<*mut c_char, String> {
+    CString::new(string).map(CString::into_raw).map_err(|error| {
+        format!("the string has a null character at byte {}, so it can't be a C string", error.nul_position())
+    })
+}
+
+/// Runs `f`, or returns `fallback` if it panics, so that a panic never unwinds into the caller.
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// The result of `f`, or else an error saying it panicked, so that a panic never unwinds into the caller.
+fn catch<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(panicked(&*payload)))
+}
+
+/// The message of a caught panic.
+fn panicked(payload: &(dyn Any + Send)) -> String {
+    let message = payload.downcast_ref::<&str>().copied();
+    let message = message.or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+    match message {
+        Some(message) => format!("the evaluator panicked: {message}"),
+        None => "the evaluator panicked".to_string(),
+    }
+}
+
+fn borrow(value: Option<&Value>) -> *const PklValue {
+    // `PklValue` is a transparent wrapper of `Value`
+    value.map_or(ptr::null(), |value| ptr::from_ref(value).cast())
+}
+
+/// Hands an evaluated value over to the caller, or else the error message.
+///
+/// # Safety
+///
+/// `error` has to be null or a valid place to write a pointer to.
+unsafe fn value_or_error(result: Result<Value, String>, error: *mut *mut c_char) -> *mut PklValue {
+    match result {
+        Ok(value) => Box::into_raw(Box::new(PklValue(value))),
+        Err(message) => {
+            // SAFETY: the caller passes a valid place for the error, or a null pointer
+            unsafe { set_error(error, message) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Hands a string over to the caller, or a null pointer if there's none, or else sets the error.
+///
+/// # Safety
+///
+/// `error` has to be null or a valid place to write a pointer to.
+unsafe fn string_or_error(result: Result<Option<String>, String>, error: *mut *mut c_char) -> *mut c_char {
+    match result.and_then(|string| string.map_or(Ok(ptr::null_mut()), c_string)) {
+        Ok(string) => string,
+        Err(message) => {
+            // SAFETY: the caller passes a valid place for the error, or a null pointer
+            unsafe { set_error(error, message) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Sets `*error`, unless `error` is null, to an error message, with any null characters in it written as `\0`.
+///
+/// # Safety
+///
+/// `error` has to be null or a valid place to write a pointer to.
+unsafe fn set_error(error: *mut *mut c_char, message: String) {
+    if !error.is_null() {
+        let message = CString::new(message.replace('\0', "\\0")).expect("null characters are escaped");
+        // SAFETY: the caller passes a valid place for the error
+        unsafe { *error = message.into_raw() };
+    }
+}
+
+/// The elements of a `List`, `Set`, or object.
+///
+/// # Safety
+///
+/// `value` has to be null or a live value.
+unsafe fn elements<'v>(value: *const PklValue) -> &'v [Value] {
+    // SAFETY: the caller passes a live value, or a null pointer
+    match unsafe { value.as_ref() } {
+        Some(PklValue(Value::List(elements) | Value::Set(elements))) => elements,
+        Some(PklValue(Value::Object(object))) => &object.elements,
+        _ => &[],
+    }
+}
+
+/// The entries of a `Map` or object.
+///
+/// # Safety
+///
+/// `value` has to be null or a live value.
+unsafe fn entries<'v>(value: *const PklValue) -> &'v [(Value, Value)] {
+    // SAFETY: the caller passes a live value, or a null pointer
+    match unsafe { value.as_ref() } {
+        Some(PklValue(Value::Map(entries))) => entries,
+        Some(PklValue(Value::Object(object))) => &object.entries,
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    use super::*;
+
+    /// Takes a string returned by the API.
+    fn take(string: *mut c_char) -> String {
+        assert!(!string.is_null());
+        let text = unsafe { CStr::from_ptr(string) }.to_str().unwrap().to_string();
+        unsafe { pkl_free_string(string) };
+        text
+    }
+
+    #[test]
+    fn reading_values() {
+        let evaluator = pkl_evaluator_new();
+        let (name, bird) = (CString::new("bird").unwrap(), CString::new("pigeon").unwrap());
+        let source = "bird = read(\"prop:bird\")\nages { [\"pigeon\"] = 2 }\ntimeout = 5.min\nlist = List(1.5)";
+        let source = CString::new(source).unwrap();
+        unsafe {
+            pkl_evaluator_set_property(evaluator, name.as_ptr(), bird.as_ptr());
+            let module = pkl_eval_text(evaluator, source.as_ptr(), ptr::null_mut());
+            assert_eq!(pkl_value_kind(module), PklKind::Object);
+            assert_eq!(pkl_value_property_count(module), 4);
+            assert_eq!(take(pkl_value_property_name(module, 0, ptr::null_mut())), "bird");
+            assert_eq!(take(pkl_value_string(pkl_value_property(module, name.as_ptr()), ptr::null_mut())), "pigeon");
+
+            let ages = pkl_value_property(module, c"ages".as_ptr());
+            assert_eq!(pkl_value_entry_count(ages), 1);
+            assert_eq!(take(pkl_value_string(pkl_value_entry_key(ages, 0), ptr::null_mut())), "pigeon");
+            assert_eq!(pkl_value_int(pkl_value_entry_value(ages, 0)), 2);
+            assert!(pkl_value_entry_value(ages, 1).is_null());
+
+            let timeout = pkl_value_property(module, c"timeout".as_ptr());
+            assert_eq!((pkl_value_kind(timeout), pkl_value_float(timeout)), (PklKind::Duration, 5.0));
+            assert_eq!(take(pkl_value_string(timeout, ptr::null_mut())), "5.min");
+            let list = pkl_value_property(module, c"list".as_ptr());
+            assert_eq!(pkl_value_element_count(list), 1);
+            assert_eq!(pkl_value_float(pkl_value_element(list, 0)), 1.5);
+
+            pkl_free_value(module);
+            pkl_free_evaluator(evaluator);
+        }
+    }
+
+    #[test]
+    fn errors_and_null_pointers() {
+        let evaluator = pkl_evaluator_new();
+        let mut error = ptr::null_mut();
+        unsafe {
+            let value = pkl_eval_text(evaluator, c"a = 1 +".as_ptr(), &mut error);
+            assert!(value.is_null());
+            assert_eq!(take(error), "expected expression, found end of input");
+            let value = pkl_eval_file(evaluator, c"/nonexistent.pkl".as_ptr(), &mut error);
+            assert!(value.is_null());
+            assert!(take(error).starts_with("couldn't read /nonexistent.pkl: "));
+
+            assert_eq!(pkl_value_kind(ptr::null()), PklKind::Null);
+            assert!(pkl_value_string(ptr::null(), &mut error).is_null());
+            assert!(pkl_eval_text(ptr::null(), c"a = 1".as_ptr(), ptr::null_mut()).is_null());
+            pkl_free_value(ptr::null_mut());
+
+            let module = pkl_eval_text(evaluator, c"bird = \"pi\\u{0}geon\"".as_ptr(), ptr::null_mut());
+            let mut error = ptr::null_mut();
+            assert!(pkl_value_string(pkl_value_property(module, c"bird".as_ptr()), &mut error).is_null());
+            assert_eq!(take(error), "the string has a null character at byte 2, so it can't be a C string");
+            pkl_free_value(module);
+            pkl_free_evaluator(evaluator);
+        }
+    }
+
+    #[test]
+    fn panics_are_errors() {
+        let result: Result<(), String> = catch(|| panic!("a {} bird", "lost"));
+        assert_eq!(result, Err("the evaluator panicked: a lost bird".to_string()));
+        assert_eq!(guard(7, || panic!("a lost bird")), 7);
+    }
+}
@@ -0,0 +1,277 @@
+//! What evaluating a module can read, found from the syntax trees of the module and the modules it imports, without
+//! evaluating anything: for tools that keep the output of an evaluation for as long as none of what it read changes.
+//!
+//! Every `amends`, `extends`, `import`, and `import*` of a module counts, and every `import(...)`, `import*(...)`,
+//! `read(...)`, `read?(...)`, and `read*(...)` expression in it, whether or not evaluating the module gets to it.
+//! Glob patterns are expanded to the files and variables that match them now.
+
+use std::collections::{BTreeSet, HashSet};
+
+use oxc_allocator::Allocator;
+use pkl_ast::visit::{walk, Visit};
+use pkl_ast::{Expr, Import, ModuleExtends, ReadKind, Span};
+use pkl_stdlib::glob::Glob;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::modules::{file_path, resolve};
+use crate::options::{Access, EvaluatorOptions};
+use crate::resources;
+
+/// The inputs of an evaluation, by their resolved URIs or names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inputs {
+    /// The `file:` URIs of the modules and files that can be imported or read, including those that don't exist,
+    /// as creating them changes what's read
+    pub files: BTreeSet<String>,
+    /// The `package:` URIs of the modules of packages, which can't change
+    pub packages: BTreeSet<String>,
+    /// The names of the environment variables that can be read
+    pub environment_variables: BTreeSet<String>,
+    /// The names of the external properties that can be read
+    pub external_properties: BTreeSet<String>,
+}
+
+/// The inputs of evaluating the module with `source` at `uri` with `options`, and of the modules it imports in turn.
+///
+/// Fails with an error at the import or read whose target can't be known, or can change, without evaluating the
+/// module: a `read` of a URI that isn't a constant string, an import or read of an `https:` URL or of a scheme
+/// without a built-in reader, or one that can't be resolved.
+///
+/// ```
+/// use pkl_eval::EvaluatorOptions;
+///
+/// let source = "home = read(\"env:HOME\")\nbirds = import*(\"birds/*.pkl\")\n";
+/// let inputs = pkl_eval::inputs(source, "file:///nowhere/index.pkl", &EvaluatorOptions::default()).unwrap();
+/// assert!(inputs.environment_variables.contains("HOME"));
+///
+/// let error = pkl_eval::inputs("a = read(\"env:\\(b)\")\nb = 1\n", "repl:text", &EvaluatorOptions::default());
+/// assert!(error.is_err());
+/// ```
+pub fn inputs(source: &str, uri: &str, options: &EvaluatorOptions) -> Result<Inputs> {
+    let alloc = Allocator::default();
+    let evaluator = Evaluator::with_options(&alloc, options.clone());
+    let seen = HashSet::from([uri.to_string()]);
+    let mut found = Found { evaluator: &evaluator, inputs: Inputs::default(), pending: Vec::new(), seen };
+    found.module(uri, source)?;
+    while let Some(module) = found.pending.pop() {
+        let source = if module.starts_with("package:") {
+            evaluator.package_source(Span::new(0, 0), &module, &module)?
+        } else {
+            let path = module.strip_prefix("file://").expect("only files and packages are walked");
+            match std::fs::read_to_string(path) {
+                Ok(source) => source,
+                // evaluating an import of it fails the same way until it changes
+                Err(_) => continue,
+            }
+        };
+        found.module(&module, &source).map_err(|error| error.in_module(&module))?;
+    }
+    Ok(found.inputs)
+}
+
+/// The inputs found so far, and the modules still to look through.
+struct Found<'e, 'a> {
+    /// For resolving packages and glob patterns the way evaluating does
+    evaluator: &'e Evaluator<'a>,
+    inputs: Inputs,
+    /// The modules whose own imports and reads are still to be found
+    pending: Vec<String>,
+    seen: HashSet<String>,
+}
+
+impl Found<'_, '_> {
+    /// Adds the inputs of the module with `source` at `uri`, queueing the modules it imports.
+    fn module(&mut self, uri: &str, source: &str) -> Result<()> {
+        let alloc = Allocator::default();
+        let module = pkl_parser::parse_module(&alloc, source).node;
+        let mut targets = Targets::default();
+        targets.visit_module(&module);
+        for target in targets.0 {
+            match target {
+                Target::Import { span, uri: import, glob: false } => self.import(span, &import, uri)?,
+                Target::Import { span, uri: pattern, glob: true } => {
+                    for (_, file) in self.evaluator.glob_files(span, Access::Import, &pattern, uri)? {
+                        self.queue(file);
+                    }
+                }
+                Target::Read { span, kind, uri: resource } => {
+                    let Some(resource) = resource else {
+                        let message = "the URI of this `read` is only known when the module is evaluated";
+                        return Err(EvalError::new(span, message));
+                    };
+                    self.read(span, kind, &resource, uri)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds an import of `import` in the module at `base`, resolving it the way the evaluator does.
+    fn import(&mut self, span: Span, import: &str, base: &str) -> Result<()> {
+        if import.starts_with("pkl:") {
+            return Ok(());
+        }
+        let resolved = if let Some(dependency) = import.strip_prefix('@').filter(|_| !base.starts_with("package:")) {
+            let (name, path) = dependency.split_once('/').unwrap_or((dependency, ""));
+            let Some(target) = self.evaluator.options.dependencies.get(name) else {
+                return Err(EvalError::new(span, format!("there's no dependency named `{name}`")));
+            };
+            if target.starts_with("package:") {
+                self.evaluator.resolve_package(span, &format!("{target}#/{path}"), "repl:text")?
+            } else {
+                self.file(span, &format!("{}/{path}", target.trim_end_matches('/')), "repl:text")?
+            }
+        } else if import.starts_with("package:") || base.starts_with("package:") {
+            self.evaluator.resolve_package(span, import, base)?
+        } else {
+            self.file(span, import, base)?
+        };
+        self.queue(resolved);
+        Ok(())
+    }
+
+    /// The resolved `file:` URI of a module, which may not exist.
+    fn file(&self, span: Span, import: &str, base: &str) -> Result<String> {
+        if import.starts_with("https:") || base.starts_with("https:") {
+            return Err(EvalError::new(span, "imported URLs can change without any file changing"));
+        }
+        match resolve(span, import, base) {
+            Ok(resolved) => Ok(resolved),
+            Err(error) => match file_path(import, base) {
+                Some(path) if !path.exists() => Ok(format!("file://{}", path.display())),
+                _ => Err(error),
+            },
+        }
+    }
+
+    fn queue(&mut self, module: String) {
+        if module.starts_with("package:") {
+            self.inputs.packages.insert(module.clone());
+        } else {
+            self.inputs.files.insert(module.clone());
+        }
+        if self.seen.insert(module.clone()) {
+            self.pending.push(module);
+        }
+    }
+
+    /// Adds a read of `resource` in the module at `base`, resolving it the way the evaluator does.
+    fn read(&mut self, span: Span, kind: ReadKind, resource: &str, base: &str) -> Result<()> {
+        let options = &self.evaluator.options;
+        if kind == ReadKind::ReadGlob {
+            match resource.split_once(':').map(|(scheme, _)| scheme) {
+                Some(scheme @ ("env" | "prop")) => {
+                    let glob = Glob::new(&resource[scheme.len() + 1..]);
+                    let glob = glob.map_err(|error| EvalError::new(span, error.message))?;
+                    let names: Vec<String> = match (scheme, &options.environment_variables) {
+                        ("env", Some(variables)) => variables.keys().cloned().collect(),
+                        ("env", None) => std::env::vars().map(|(name, _)| name).collect(),
+                        _ => options.external_properties.keys().cloned().collect(),
+                    };
+                    let names = names.into_iter().filter(|name| glob.is_match(name));
+                    match scheme {
+                        "env" => self.inputs.environment_variables.extend(names),
+                        _ => self.inputs.external_properties.extend(names),
+                    }
+                }
+                _ => {
+                    let files = self.evaluator.glob_files(span, Access::Read, resource, base)?;
+                    self.inputs.files.extend(files.into_iter().map(|(_, file)| file));
+                }
+            }
+            return Ok(());
+        }
+
+        let resolved = resources::resolve(span, resource, base)?;
+        let (scheme, rest) = resolved.split_once(':').expect("resolved URIs have a scheme");
+        match scheme {
+            "env" => _ = self.inputs.environment_variables.insert(rest.to_string()),
+            "prop" => _ = self.inputs.external_properties.insert(rest.to_string()),
+            "file" => _ = self.inputs.files.insert(resolved.clone()),
+            _ => {
+                let message = format!("`{scheme}:` resources can change without any file changing");
+                return Err(EvalError::new(span, message));
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Target {
+    Import { span: Span, uri: String, glob: bool },
+    /// A read, with its URI if it's a constant string
+    Read { span: Span, kind: ReadKind, uri: Option<String> },
+}
+
+/// The imports and reads of a module, in source order.
+#[derive(Default)]
+struct Targets(Vec<Target>);
+
+impl<'a> Visit<'a> for Targets {
+    fn visit_module_extends(&mut self, extends: &ModuleExtends<'a>) {
+        self.0.push(Target::Import { span: extends.uri.span, uri: extends.uri.value.to_string(), glob: false });
+    }
+
+    fn visit_import(&mut self, import: &Import<'a>) {
+        self.0.push(Target::Import { span: import.uri.span, uri: import.uri.value.to_string(), glob: import.glob });
+        walk::walk_import(self, import);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Import(import) => {
+                let uri = import.uri.value.to_string();
+                self.0.push(Target::Import { span: import.uri.span, uri, glob: import.glob });
+            }
+            Expr::Read(read) => {
+                let uri = match &read.uri {
+                    Expr::String(literal) => literal.as_constant().map(str::to_string),
+                    _ => None,
+                };
+                self.0.push(Target::Read { span: read.uri.span(), kind: read.kind, uri });
+            }
+            _ => {}
+        }
+        walk::walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::EvaluatorOptions;
+
+    #[test]
+    fn inputs_of_a_module() {
+        let dir = std::env::temp_dir().join(format!("pkl-inputs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("birds")).unwrap();
+        fs::write(dir.join("base.pkl"), "x = 1\n").unwrap();
+        fs::write(dir.join("bird.pkl"), "amends \"base.pkl\"\nname = read?(\"name.txt\")\n").unwrap();
+        fs::write(dir.join("birds/pigeon.pkl"), "tag = read(\"prop:tag\")\n").unwrap();
+        let uri = format!("file://{}", dir.join("main.pkl").display());
+        let source = "b = import(\"bird.pkl\")\nall = import*(\"birds/*.pkl\")\nhome = read*(\"env:HOM*\")\n\
+            missing = if (false) import(\"nope.pkl\") else null\n";
+        let options = EvaluatorOptions { threads: 1, ..EvaluatorOptions::default() };
+
+        let inputs = super::inputs(source, &uri, &options).unwrap();
+        let file = |path: &str| format!("file://{}", dir.canonicalize().unwrap().join(path).display());
+        let files: Vec<String> = inputs.files.into_iter().collect();
+        let mut expected = ["base.pkl", "bird.pkl", "birds/pigeon.pkl", "name.txt"].map(file).to_vec();
+        expected.push(format!("file://{}", dir.join("nope.pkl").display()));
+        expected.sort();
+        assert_eq!(files, expected);
+        assert!(inputs.environment_variables.iter().all(|name| name.starts_with("HOM")));
+        assert_eq!(inputs.external_properties.into_iter().collect::<Vec<_>>(), ["tag"]);
+
+        // what's read from a URL, or at a URI computed while evaluating, isn't known
+        fs::write(dir.join("bird.pkl"), "name = read(\"https://example.com/name.txt\")\n").unwrap();
+        let error = super::inputs(source, &uri, &options).unwrap_err();
+        assert_eq!(error.message, "`https:` resources can change without any file changing");
+        assert_eq!(error.uri, Some(file("bird.pkl")));
+        let error = super::inputs("a = read(\"env:\\(b)\")\nb = \"HOME\"\n", &uri, &options).unwrap_err();
+        assert_eq!(error.span, pkl_ast::Span::new(9, 19));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
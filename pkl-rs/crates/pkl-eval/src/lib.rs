@@ -0,0 +1,126 @@
+//! Evaluates parsed Pkl modules into [`Value`]s.
+//!
+//! The evaluator walks the syntax tree directly. Members of modules and objects are evaluated lazily, when they are
+//! first read, and late-bound: a member refers to the other members of the object it is read from, so amending an
+//! object also changes the members derived from the ones it overrides. The `runtime` module describes how this works.
+//!
+//! Object bodies with parameters, like `(function) { x -> ... }` to amend a function, aren't supported yet: evaluating
+//! one fails with an error of code [`Code::Unsupported`](pkl_diagnostics::Code::Unsupported).
+//!
+//! ```
+//! use pkl_eval::value::Value;
+//!
+//! let module = pkl_eval::evaluate("port = 8000 + 80\nhost = \"localhost\"").unwrap();
+//! let object = module.as_object().unwrap();
+//!
+//! assert_eq!(object.property("port"), Some(&Value::Int(8080)));
+//! ```
+
+// only the C API of the `ffi` feature needs unsafe code
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
+
+mod builtins;
+mod class;
+mod collections;
+pub mod convert;
+pub mod de;
+mod equality;
+mod error;
+mod evaluator;
+mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod inputs;
+mod log;
+pub mod members;
+mod methods;
+mod modules;
+mod object;
+mod options;
+mod packages;
+mod parsers;
+mod prefetch;
+pub mod project;
+mod regex;
+mod resources;
+mod runtime;
+mod types;
+mod units;
+pub mod value;
+
+use oxc_allocator::Allocator;
+
+pub use de::{from_value, DeError};
+pub use error::{Error, EvalError, StackFrame};
+pub use evaluator::Evaluator;
+pub use inputs::{inputs, Inputs};
+pub use log::{Log, LogLevel};
+pub use options::{Deprecations, EvaluatorOptions, DEFAULT_MAX_DEPTH};
+pub use prefetch::{dependencies, import_graph, ImportGraph};
+pub use resources::{Resource, ResourceReader};
+use value::{ModuleOutput, Value};
+
+/// Parses and evaluates a module, whose relative imports are resolved against the working directory.
+pub fn evaluate(source: &str) -> Result<Value, Error> {
+    evaluate_at(source, "repl:text")
+}
+
+/// Parses and evaluates the source of the module at `uri`, like `file:///birds/index.pkl`, which its relative
+/// imports are resolved against.
+pub fn evaluate_at(source: &str, uri: &str) -> Result<Value, Error> {
+    evaluate_with(source, uri, EvaluatorOptions::default())
+}
+
+/// Parses and evaluates the source of the module at `uri` with `options`, which say what it can import and read.
+pub fn evaluate_with(source: &str, uri: &str, options: EvaluatorOptions) -> Result<Value, Error> {
+    let alloc = Allocator::default();
+    let source = alloc.alloc_str(source);
+    let result = pkl_parser::parse_module(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        return Err(Error::Syntax(result.diagnostics));
+    }
+
+    let mut evaluator = Evaluator::with_options(&alloc, options);
+    evaluator.add_source(uri, source);
+    Ok(evaluator.evaluate_module(alloc.alloc(result.node), uri)?)
+}
+
+/// Parses and evaluates the `output` of the module at `uri` with `options`: what `pkl eval` renders, and how.
+pub fn evaluate_output_with(source: &str, uri: &str, options: EvaluatorOptions) -> Result<ModuleOutput, Error> {
+    let alloc = Allocator::default();
+    let source = alloc.alloc_str(source);
+    let result = pkl_parser::parse_module(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        return Err(Error::Syntax(result.diagnostics));
+    }
+
+    let mut evaluator = Evaluator::with_options(&alloc, options);
+    evaluator.add_source(uri, source);
+    Ok(evaluator.evaluate_output(alloc.alloc(result.node), uri)?)
+}
+
+/// Parses and evaluates a module, and deserializes its value into `T`; see [`de`].
+pub fn from_str<T: serde::de::DeserializeOwned>(source: &str) -> Result<T, Error> {
+    from_str_with(source, "repl:text", EvaluatorOptions::default())
+}
+
+/// Parses and evaluates the source of the module at `uri` with `options`, and deserializes its value into `T`.
+pub fn from_str_with<T: serde::de::DeserializeOwned>(
+    source: &str,
+    uri: &str,
+    options: EvaluatorOptions,
+) -> Result<T, Error> {
+    Ok(from_value(&evaluate_with(source, uri, options)?)?)
+}
+
+/// Parses and evaluates a single expression, as if it were the value of a property of an empty module.
+pub fn evaluate_expr(source: &str) -> Result<Value, Error> {
+    let alloc = Allocator::default();
+    let result = pkl_parser::parse_expr(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        return Err(Error::Syntax(result.diagnostics));
+    }
+
+    Ok(Evaluator::new(&alloc).evaluate_expr(alloc.alloc(result.node))?)
+}
@@ -0,0 +1,63 @@
+//! What an evaluator logs while evaluating: the messages of `trace(...)`, and warnings, each about a place in a
+//! module. See [`Evaluator::set_logger`](crate::Evaluator::set_logger).
+
+use std::fmt;
+
+use pkl_ast::Span;
+use pkl_diagnostics::{Code, Report, Severity};
+use pkl_lexer::line_index::LineIndex;
+
+/// What kind of message is logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    /// The source and value of an expression passed to `trace`
+    Trace,
+    /// Something that doesn't stop the evaluation but is likely a mistake, like using a deprecated member
+    Warn,
+}
+
+/// A message logged while evaluating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Log<'l> {
+    pub level: LogLevel,
+    /// What's logged, like ``"`old` is deprecated"`` or `1 + 1 = 2`
+    pub message: &'l str,
+    /// The code of a warning
+    pub code: Option<Code>,
+    /// The URI of the module the message is about
+    pub uri: &'l str,
+    /// Where in the module the message is about
+    pub span: Span,
+    /// The source of the module, if the evaluator knows it
+    pub source: Option<&'l str>,
+}
+
+impl Log<'_> {
+    /// The message as a report, pointing at where it's about if the source of the module is known.
+    pub fn report(&self) -> Report {
+        let severity = match self.level {
+            LogLevel::Trace => Severity::Info,
+            LogLevel::Warn => Severity::Warning,
+        };
+        let report = Report::new(severity, self.message).with_code(self.code);
+        match self.source {
+            Some(_) => report.with_primary(self.span.start..self.span.end, ""),
+            None => report,
+        }
+    }
+}
+
+/// Writes the message the way `pkl` logs it, like ``pkl: WARN: `old` is deprecated (repl:text, line 7)``.
+impl fmt::Display for Log<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Warn => "WARN",
+        };
+        write!(f, "pkl: {level}: {} ({}", self.message, self.uri)?;
+        if let Some(source) = self.source {
+            write!(f, ", line {}", LineIndex::new(source).line_col(self.span.start).line + 1)?;
+        }
+        f.write_str(")")
+    }
+}
@@ -0,0 +1,150 @@
+//! The names of the built-in members of values, for tools that suggest them, like the completion of a language server.
+//!
+//! These are the members that `methods`, `collections`, and `modules` implement, so the lists have to be kept in sync
+//! with them. Every value also has a `toString()` method, which is listed for every class.
+
+/// The built-in properties and methods of a class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Members {
+    pub properties: &'static [&'static str],
+    pub methods: &'static [&'static str],
+}
+
+/// The modules of the standard library that can be imported.
+pub const STANDARD_MODULES: [&str; 6] = ["pkl:json", "pkl:math", "pkl:platform", "pkl:toml", "pkl:xml", "pkl:yaml"];
+
+/// The properties of `Int` and `Float` that make durations and data sizes, like the `s` of `5.s`, which
+/// [`class_members`] leaves out since they would crowd out the others.
+pub const UNITS: [&str; 18] = [
+    "ns", "us", "ms", "s", "min", "h", "d", "b", "kb", "kib", "mb", "mib", "gb", "gib", "tb", "tib", "pb", "pib",
+];
+
+/// The built-in members of the class named `class`, as in [`Value::type_name`](crate::value::Value::type_name).
+///
+/// A name that isn't a class of `pkl:base` or a standard library module is taken to be a class declared in Pkl,
+/// whose instances have the members every typed object has.
+pub fn class_members(class: &str) -> Members {
+    let (properties, methods): (&[&str], &[&str]) = match class {
+        "Null" => (&[], &["toString"]),
+        "Boolean" => (&[], &["xor", "implies", "toString"]),
+        "String" => (
+            &["length", "lastIndex", "isEmpty", "isBlank", "chars", "lines"],
+            &[
+                "contains", "startsWith", "endsWith", "matches", "indexOf", "lastIndexOf", "getOrNull", "substring",
+                "take", "drop", "takeLast", "dropLast", "repeat", "reverse", "split", "replaceAll", "replaceFirst",
+                "replaceLast", "replaceAllMapped", "replaceFirstMapped", "replaceLastMapped", "padStart", "padEnd",
+                "toUpperCase", "toLowerCase", "trim", "trimStart", "trimEnd", "capitalize", "decapitalize", "toInt",
+                "toIntOrNull", "toFloat", "toFloatOrNull", "toBoolean", "toBooleanOrNull", "toString",
+            ],
+        ),
+        "Int" => (
+            &[
+                "sign", "abs", "ceil", "floor", "isEven", "isOdd", "isPositive", "isNonZero", "isFinite", "isInfinite",
+                "isNaN",
+            ],
+            &["toInt", "toFloat", "round", "truncate", "toRadixString", "toFixed", "isBetween", "toString"],
+        ),
+        "Float" => (
+            &["sign", "abs", "ceil", "floor", "isPositive", "isNonZero", "isFinite", "isInfinite", "isNaN"],
+            &["toInt", "toFloat", "round", "truncate", "toFixed", "isBetween", "toString"],
+        ),
+        "Duration" => (&["value", "unit", "isPositive"], &["toUnit", "isBetween", "toString"]),
+        "DataSize" => (
+            &["value", "unit", "isPositive", "isBinaryUnit", "isDecimalUnit"],
+            &["toUnit", "toBinaryUnit", "toDecimalUnit", "isBetween", "toString"],
+        ),
+        "List" => (
+            &[
+                "length", "isEmpty", "first", "last", "firstOrNull", "lastOrNull", "max", "min", "maxOrNull",
+                "minOrNull", "rest", "lastIndex", "isDistinct", "distinct",
+            ],
+            &[
+                "contains", "indexOf", "getOrNull", "add", "map", "mapIndexed", "flatMap", "filter", "count", "any",
+                "every", "find", "findOrNull", "fold", "take", "drop", "reverse", "sort", "sortBy", "sortWith", "zip",
+                "groupBy", "join", "toList", "toSet", "toString",
+            ],
+        ),
+        "Set" => (
+            &[
+                "length", "isEmpty", "first", "last", "firstOrNull", "lastOrNull", "max", "min", "maxOrNull",
+                "minOrNull", "rest",
+            ],
+            &[
+                "contains", "add", "map", "flatMap", "filter", "count", "any", "every", "find", "findOrNull", "fold",
+                "groupBy", "join", "toList", "toSet", "toString",
+            ],
+        ),
+        "Map" => (
+            &["length", "isEmpty", "keys", "values"],
+            &[
+                "containsKey", "containsValue", "getOrNull", "put", "remove", "filter", "any", "every", "mapKeys",
+                "mapValues", "fold", "toMap", "toString",
+            ],
+        ),
+        "Listing" => (
+            &["length", "isEmpty", "first", "last", "firstOrNull", "lastOrNull", "max", "min", "isDistinct"],
+            &[
+                "contains", "any", "every", "fold", "toList", "toSet", "join", "hasProperty", "getProperty",
+                "getPropertyOrNull", "toString",
+            ],
+        ),
+        "Mapping" => (
+            &["length", "isEmpty", "keys", "values"],
+            &["toMap", "containsKey", "getOrNull", "hasProperty", "getProperty", "getPropertyOrNull", "toString"],
+        ),
+        "Dynamic" => {
+            (&[], &["toList", "toMap", "toTyped", "hasProperty", "getProperty", "getPropertyOrNull", "toString"])
+        }
+        "Function" => (&[], &["apply", "toString"]),
+        "Class" => (&["simpleName"], &["toString"]),
+        "Regex" => (&["pattern", "groupCount"], &["matchEntire", "findMatchesIn", "toString"]),
+        "RegexMatch" => (&["value", "start", "end", "groups"], &["toString"]),
+        "pkl.math" => (
+            &[
+                "minInt", "minInt8", "minInt16", "minInt32", "maxInt", "maxInt8", "maxInt16", "maxInt32", "maxUInt",
+                "maxUInt8", "maxUInt16", "maxUInt32", "minFiniteFloat", "maxFiniteFloat", "minPositiveFloat",
+                "infinity", "nan", "e", "pi",
+            ],
+            &[
+                "exp", "sqrt", "cbrt", "log", "log2", "log10", "sin", "cos", "tan", "asin", "acos", "atan", "atan2",
+                "gcd", "lcm", "isPowerOfTwo", "min", "max",
+            ],
+        ),
+        "pkl.platform" => (&["current"], &[]),
+        "pkl.xml" => (&[], &["Element", "Comment", "CData", "Inline"]),
+        "pkl.json" | "pkl.yaml" | "pkl.toml" => (&[], &[]),
+        _ => (&[], &["toDynamic", "hasProperty", "getProperty", "getPropertyOrNull", "toString"]),
+    };
+    Members { properties, methods }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{class_members, UNITS};
+    use crate::evaluate_expr;
+
+    #[test]
+    fn listed_properties_exist() {
+        let receivers = [
+            ("String", "\"abc\""),
+            ("Int", "3"),
+            ("Float", "1.5"),
+            ("Duration", "3.min"),
+            ("DataSize", "2.mb"),
+            ("List", "List(1, 2)"),
+            ("Set", "Set(1, 2)"),
+            ("Map", "Map(1, 2)"),
+            ("Listing", "new Listing { 1 }"),
+            ("Mapping", "new Mapping { [1] = 2 }"),
+            ("Regex", "Regex(\"a\")"),
+            ("pkl.math", "import(\"pkl:math\")"),
+        ];
+        for (class, receiver) in receivers {
+            let units = if matches!(class, "Int" | "Float") { &UNITS[..] } else { &[] };
+            for property in class_members(class).properties.iter().chain(units) {
+                let source = format!("({receiver}).{property}");
+                assert!(evaluate_expr(&source).is_ok(), "{source}");
+            }
+        }
+    }
+}
@@ -0,0 +1,509 @@
+//! The built-in properties and methods of `pkl:base` classes, like `"abc".length` and `1.5.toFixed(2)`.
+//!
+//! Members of objects are only looked up here if the object doesn't define a member of the same name. Most members
+//! are implemented by [`pkl_stdlib`]; the ones taking functions, like `List.map`, are in `collections`.
+
+use std::fmt::Write;
+use std::rc::Rc;
+
+use pkl_ast::Span;
+use pkl_stdlib::regex::Regex;
+use pkl_stdlib::{number, string};
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::expr::overflow;
+use crate::runtime::{Class, Lambda, Obj, Val};
+use crate::{modules, parsers, regex, units};
+use crate::value::Number;
+
+impl<'a> Evaluator<'a> {
+    /// Reads the built-in property `name` of a value, or returns `None` if its class has no such property.
+    pub(crate) fn base_property(&self, span: Span, receiver: &Val<'a>, name: &str) -> Result<Option<Val<'a>>> {
+        let value = match receiver {
+            Val::String(s) => match name {
+                "length" => Val::Int(string::length(s)),
+                "lastIndex" => Val::Int(string::length(s) - 1),
+                "isEmpty" => Val::Boolean(s.is_empty()),
+                "isBlank" => Val::Boolean(string::is_blank(s)),
+                "chars" => Val::List(s.chars().map(|c| string_val(c.to_string())).collect()),
+                "lines" => Val::List(s.lines().map(string_val).collect()),
+                _ => return Ok(None),
+            },
+            Val::Int(n) => match name {
+                "sign" => Val::Int(n.signum()),
+                "abs" => Val::Int(n.checked_abs().ok_or_else(|| overflow(span))?),
+                "ceil" | "floor" => Val::Int(*n),
+                "isEven" => Val::Boolean(n % 2 == 0),
+                "isOdd" => Val::Boolean(n % 2 != 0),
+                "isPositive" => Val::Boolean(*n >= 0),
+                "isNonZero" => Val::Boolean(*n != 0),
+                "isFinite" => Val::Boolean(true),
+                "isInfinite" | "isNaN" => Val::Boolean(false),
+                _ => return Ok(units::quantity(Number::Int(*n), name)),
+            },
+            Val::Float(x) => match name {
+                "sign" => Val::Float(number::float_sign(*x)),
+                "abs" => Val::Float(x.abs()),
+                "ceil" => Val::Float(x.ceil()),
+                "floor" => Val::Float(x.floor()),
+                "isPositive" => Val::Boolean(*x >= 0.0),
+                "isNonZero" => Val::Boolean(*x != 0.0),
+                "isFinite" => Val::Boolean(x.is_finite()),
+                "isInfinite" => Val::Boolean(x.is_infinite()),
+                "isNaN" => Val::Boolean(x.is_nan()),
+                _ => return Ok(units::quantity(Number::Float(*x), name)),
+            },
+            Val::List(_) | Val::Set(_) | Val::Map(_) | Val::Object(_) => {
+                return self.collection_property(span, receiver, name);
+            }
+            Val::Duration(_) | Val::DataSize(_) => return Ok(units::quantity_property(receiver, name)),
+            Val::Regex(_) | Val::RegexMatch(_) => return Ok(regex::regex_property(receiver, name)),
+            Val::Class(class) => match name {
+                "simpleName" => string_val(class.name),
+                _ => return Ok(None),
+            },
+            Val::Null | Val::Boolean(_) | Val::Function(_) => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Calls the built-in method `name` of a value, or returns `None` if its class has no such method.
+    pub(crate) fn base_method(
+        &self,
+        span: Span,
+        receiver: &Val<'a>,
+        name: &str,
+        args: Vec<Val<'a>>,
+    ) -> Result<Option<Val<'a>>> {
+        let call = Call { span, name };
+        if name == "toString" {
+            let [] = call.args(args)?;
+            return Ok(Some(string_val(self.stringify(span, receiver)?)));
+        }
+
+        match receiver {
+            Val::String(s) if matches!(args.first(), Some(Val::Regex(_))) || name.ends_with("Mapped") => {
+                self.regex_string_method(&call, s, args)
+            }
+            Val::String(s) => string_method(&call, s, args),
+            Val::Int(n) => int_method(&call, *n, args),
+            Val::Float(x) => float_method(&call, *x, args),
+            Val::Boolean(b) => boolean_method(&call, *b, args),
+            Val::Duration(_) | Val::DataSize(_) => units::quantity_method(&call, receiver, args),
+            Val::Regex(_) => self.regex_method(&call, receiver, args),
+            Val::RegexMatch(_) | Val::Class(_) => Ok(None),
+            Val::Object(object) if modules::is_math(object) => modules::math_method(&call, args),
+            Val::Object(object) if modules::is_xml(object) => modules::xml_method(&call, args),
+            Val::Object(object) if parsers::is_parser(object) => self.parser_method(&call, object, args),
+            Val::List(_) | Val::Set(_) | Val::Map(_) | Val::Object(_) | Val::Function(_) => {
+                self.collection_method(&call, receiver, args)
+            }
+            Val::Null => Ok(None),
+        }
+    }
+
+    /// Converts a value to a string, as `toString()` does.
+    pub(crate) fn stringify(&self, span: Span, value: &Val<'a>) -> Result<String> {
+        let mut text = String::new();
+        self.write_string(span, &mut text, value)?;
+        Ok(text)
+    }
+
+    /// Appends a value converted to a string to `text`, as string interpolation does: strings as they are, and other
+    /// values the way they read as Pkl source.
+    pub(crate) fn write_string(&self, span: Span, text: &mut String, value: &Val<'a>) -> Result<()> {
+        match value {
+            Val::String(s) => text.push_str(s),
+            value => self.write_value(span, text, value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a value the way it reads as Pkl source, where strings are quoted.
+    pub(crate) fn write_value(&self, span: Span, text: &mut String, value: &Val<'a>) -> Result<()> {
+        let write_all = |text: &mut String, class: &str, values: &mut dyn Iterator<Item = &Val<'a>>| {
+            text.push_str(class);
+            text.push('(');
+            for (index, value) in values.enumerate() {
+                if index > 0 {
+                    text.push_str(", ");
+                }
+                self.write_value(span, text, value)?;
+            }
+            text.push(')');
+            Ok(())
+        };
+
+        match value {
+            Val::Null => text.push_str("null"),
+            Val::Boolean(b) => text.push_str(if *b { "true" } else { "false" }),
+            Val::Int(n) => _ = write!(text, "{n}"),
+            Val::Float(x) => text.push_str(&number::float_to_string(*x)),
+            Val::String(s) => text.push_str(&string::quote(s)),
+            Val::Duration(duration) => _ = write!(text, "{duration}"),
+            Val::DataSize(size) => _ = write!(text, "{size}"),
+            Val::Regex(regex) => _ = write!(text, "Regex({})", string::quote(regex.pattern())),
+            Val::RegexMatch(found) => text.push_str(&found.value),
+            Val::Class(class) => _ = write!(text, "class {}", class.name),
+            Val::List(elements) => write_all(text, "List", &mut elements.iter())?,
+            Val::Set(elements) => write_all(text, "Set", &mut elements.iter())?,
+            Val::Map(entries) => write_all(text, "Map", &mut entries.iter().flat_map(|(k, v)| [k, v]))?,
+            Val::Object(object) => self.write_object(span, text, object)?,
+            Val::Function(function) => _ = write!(text, "new Function{} {{}}", function.params.len()),
+        }
+        Ok(())
+    }
+
+    /// Writes an object as an object literal of its class, like `new Dynamic { name = "pigeon"; 1; ["a"] = 2 }`,
+    /// with its properties, then its entries, then its elements.
+    fn write_object(&self, span: Span, text: &mut String, object: &Rc<Obj<'a>>) -> Result<()> {
+        _ = write!(text, "new {} {{", object.kind.class_name());
+        let mut first = true;
+        let mut separate = |text: &mut String| text.push_str(if std::mem::take(&mut first) { " " } else { "; " });
+        for (name, value) in self.properties(object)? {
+            separate(text);
+            _ = write!(text, "{name} = ");
+            self.write_value(span, text, &value)?;
+        }
+        for (key, value) in self.entries(object)? {
+            separate(text);
+            text.push('[');
+            self.write_value(span, text, &key)?;
+            text.push_str("] = ");
+            self.write_value(span, text, &value)?;
+        }
+        for element in self.elements(object)? {
+            separate(text);
+            self.write_value(span, text, &element)?;
+        }
+        text.push_str(if first { "}" } else { " }" });
+        Ok(())
+    }
+}
+
+fn string_method<'a>(call: &Call, s: &str, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let value = match call.name {
+        "contains" | "startsWith" | "endsWith" => {
+            let [pattern] = call.args(args)?;
+            let pattern = call.string(&pattern)?;
+            Val::Boolean(match call.name {
+                "contains" => s.contains(pattern),
+                "startsWith" => s.starts_with(pattern),
+                _ => s.ends_with(pattern),
+            })
+        }
+        "indexOf" | "lastIndexOf" => {
+            let [pattern] = call.args(args)?;
+            let pattern = call.string(&pattern)?;
+            let index = match call.name {
+                "indexOf" => string::index_of(s, pattern),
+                _ => string::last_index_of(s, pattern),
+            };
+            Val::Int(index.unwrap_or(-1))
+        }
+        "getOrNull" => {
+            let [index] = call.args(args)?;
+            string::char_at(s, call.int(&index)?).map_or(Val::Null, string_val)
+        }
+        "substring" => {
+            let [start, end] = call.args(args)?;
+            string_val(string::substring(s, call.int(&start)?, call.int(&end)?).map_err(|e| call.error(e))?)
+        }
+        "take" | "drop" | "takeLast" | "dropLast" | "repeat" => {
+            let [n] = call.args(args)?;
+            let n = call.int(&n)?;
+            let result = match call.name {
+                "take" => string::take(s, n),
+                "drop" => string::drop(s, n),
+                "takeLast" => string::take_last(s, n),
+                "dropLast" => string::drop_last(s, n),
+                _ => string::repeat(s, n),
+            };
+            string_val(result.map_err(|e| call.error(e))?)
+        }
+        "split" => {
+            let [separator] = call.args(args)?;
+            Val::List(string::split(s, call.string(&separator)?).into_iter().map(string_val).collect())
+        }
+        "replaceAll" | "replaceFirst" | "replaceLast" => {
+            let [pattern, replacement] = call.args(args)?;
+            let (pattern, replacement) = (call.string(&pattern)?, call.string(&replacement)?);
+            string_val(match call.name {
+                "replaceAll" => s.replace(pattern, replacement),
+                "replaceFirst" => string::replace_first(s, pattern, replacement),
+                _ => string::replace_last(s, pattern, replacement),
+            })
+        }
+        "padStart" | "padEnd" => {
+            let [width, pad] = call.args(args)?;
+            let (width, pad) = (call.int(&width)?, call.string(&pad)?);
+            let result = match call.name {
+                "padStart" => string::pad_start(s, width, pad),
+                _ => string::pad_end(s, width, pad),
+            };
+            string_val(result.map_err(|e| call.error(e))?)
+        }
+        "toUpperCase" | "toLowerCase" | "trim" | "trimStart" | "trimEnd" | "capitalize" | "decapitalize"
+        | "reverse" => {
+            let [] = call.args(args)?;
+            string_val(match call.name {
+                "toUpperCase" => s.to_uppercase(),
+                "toLowerCase" => s.to_lowercase(),
+                "trim" => s.trim().to_string(),
+                "trimStart" => s.trim_start().to_string(),
+                "trimEnd" => s.trim_end().to_string(),
+                "capitalize" => string::capitalize(s),
+                "decapitalize" => string::decapitalize(s),
+                _ => string::reverse(s),
+            })
+        }
+        "toInt" | "toIntOrNull" => {
+            let [] = call.args(args)?;
+            parsed(call, "Int", s, string::to_int(s).map(Val::Int))?
+        }
+        "toFloat" | "toFloatOrNull" => {
+            let [] = call.args(args)?;
+            parsed(call, "Float", s, string::to_float(s).map(Val::Float))?
+        }
+        "toBoolean" | "toBooleanOrNull" => {
+            let [] = call.args(args)?;
+            parsed(call, "Boolean", s, string::to_boolean(s).map(Val::Boolean))?
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+/// The result of `toInt()` and friends, whose `OrNull` variants return `null` for strings they can't parse.
+fn parsed<'a>(call: &Call, class: &str, s: &str, value: Option<Val<'a>>) -> Result<Val<'a>> {
+    match value {
+        Some(value) => Ok(value),
+        None if call.name.ends_with("OrNull") => Ok(Val::Null),
+        None => Err(EvalError::new(call.span, format!("can't convert {} to `{class}`", string::quote(s)))),
+    }
+}
+
+fn int_method<'a>(call: &Call, n: i64, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let value = match call.name {
+        "toInt" | "round" | "truncate" => {
+            let [] = call.args(args)?;
+            Val::Int(n)
+        }
+        "toFloat" => {
+            let [] = call.args(args)?;
+            Val::Float(n as f64)
+        }
+        "toRadixString" => {
+            let [radix] = call.args(args)?;
+            string_val(number::to_radix_string(n, call.int(&radix)?).map_err(|e| call.error(e))?)
+        }
+        _ => return number_method(call, n as f64, args),
+    };
+
+    Ok(Some(value))
+}
+
+fn float_method<'a>(call: &Call, x: f64, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let value = match call.name {
+        "toInt" => {
+            let [] = call.args(args)?;
+            Val::Int(number::to_int(x).map_err(|e| call.error(e))?)
+        }
+        "toFloat" => {
+            let [] = call.args(args)?;
+            Val::Float(x)
+        }
+        "round" => {
+            let [] = call.args(args)?;
+            Val::Float(number::round(x))
+        }
+        "truncate" => {
+            let [] = call.args(args)?;
+            Val::Float(x.trunc())
+        }
+        _ => return number_method(call, x, args),
+    };
+
+    Ok(Some(value))
+}
+
+/// Methods shared by `Int` and `Float`.
+fn number_method<'a>(call: &Call, x: f64, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let value = match call.name {
+        "toFixed" => {
+            let [digits] = call.args(args)?;
+            string_val(number::to_fixed(x, call.int(&digits)?).map_err(|e| call.error(e))?)
+        }
+        "isBetween" => {
+            let [start, end] = call.args(args)?;
+            Val::Boolean(call.number(&start)? <= x && x <= call.number(&end)?)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+fn boolean_method<'a>(call: &Call, b: bool, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let value = match call.name {
+        "xor" => {
+            let [other] = call.args(args)?;
+            Val::Boolean(b ^ call.boolean(&other)?)
+        }
+        "implies" => {
+            let [other] = call.args(args)?;
+            Val::Boolean(!b || call.boolean(&other)?)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+pub(crate) fn string_val<'a>(s: impl Into<Rc<str>>) -> Val<'a> {
+    Val::String(s.into())
+}
+
+/// A call of a built-in method, for checking its arguments.
+pub(crate) struct Call<'c> {
+    pub(crate) span: Span,
+    pub(crate) name: &'c str,
+}
+
+impl Call<'_> {
+    /// The arguments, if there are exactly `N` of them.
+    pub(crate) fn args<'a, const N: usize>(&self, args: Vec<Val<'a>>) -> Result<[Val<'a>; N]> {
+        let given = args.len();
+        args.try_into().map_err(|_| {
+            let message = format!("method `{}` takes {N} arguments, but {given} were given", self.name);
+            EvalError::new(self.span, message)
+        })
+    }
+
+    pub(crate) fn string<'v>(&self, value: &'v Val) -> Result<&'v str> {
+        match value {
+            Val::String(s) => Ok(s),
+            value => Err(self.mismatch("String", value)),
+        }
+    }
+
+    pub(crate) fn int(&self, value: &Val) -> Result<i64> {
+        match value {
+            Val::Int(n) => Ok(*n),
+            value => Err(self.mismatch("Int", value)),
+        }
+    }
+
+    pub(crate) fn number(&self, value: &Val) -> Result<f64> {
+        match value {
+            Val::Int(n) => Ok(*n as f64),
+            Val::Float(x) => Ok(*x),
+            value => Err(self.mismatch("Number", value)),
+        }
+    }
+
+    pub(crate) fn boolean(&self, value: &Val) -> Result<bool> {
+        match value {
+            Val::Boolean(b) => Ok(*b),
+            value => Err(self.mismatch("Boolean", value)),
+        }
+    }
+
+    pub(crate) fn list<'v, 'a>(&self, value: &'v Val<'a>) -> Result<&'v [Val<'a>]> {
+        match value {
+            Val::List(elements) => Ok(elements),
+            value => Err(self.mismatch("List", value)),
+        }
+    }
+
+    pub(crate) fn regex<'v>(&self, value: &'v Val) -> Result<&'v Rc<Regex>> {
+        match value {
+            Val::Regex(regex) => Ok(regex),
+            value => Err(self.mismatch("Regex", value)),
+        }
+    }
+
+    pub(crate) fn function<'v, 'a>(&self, value: &'v Val<'a>) -> Result<&'v Lambda<'a>> {
+        match value {
+            Val::Function(function) => Ok(function),
+            value => Err(self.mismatch("Function", value)),
+        }
+    }
+
+    pub(crate) fn class<'v, 'a>(&self, value: &'v Val<'a>) -> Result<&'v Rc<Class<'a>>> {
+        match value {
+            Val::Class(class) => Ok(class),
+            value => Err(self.mismatch("Class", value)),
+        }
+    }
+
+    fn mismatch(&self, expected: &str, value: &Val) -> EvalError {
+        let message =
+            format!("method `{}` expects an argument of type `{expected}`, but got `{}`", self.name, value.type_name());
+        EvalError::new(self.span, message)
+    }
+
+    pub(crate) fn error(&self, error: pkl_stdlib::Error) -> EvalError {
+        EvalError::new(self.span, error.message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate_expr;
+    use crate::value::Value;
+
+    fn eval(source: &str) -> Value {
+        evaluate_expr(source).unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    fn string(source: &str) -> String {
+        match eval(source) {
+            Value::String(s) => s,
+            value => panic!("{source}: {value:?}"),
+        }
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(eval("\"héllo\".length"), Value::Int(5));
+        assert_eq!(eval("\"  \".isBlank"), Value::Boolean(true));
+        assert_eq!(string("\"a-b-c\".replaceAll(\"-\", \"+\").toUpperCase()"), "A+B+C");
+        assert_eq!(eval("\"a,b\".split(\",\")"), eval("List(\"a\", \"b\")"));
+        assert_eq!(eval("\"42\".toInt() + 1"), Value::Int(43));
+        assert_eq!(eval("\"x\".toIntOrNull()"), Value::Null);
+        assert_eq!(string("\"7\".padStart(3, \"0\")"), "007");
+
+        let error = evaluate_expr("\"x\".toInt()").unwrap_err().to_string();
+        assert_eq!(error, "can't convert \"x\" to `Int`");
+        let error = evaluate_expr("\"abc\".take(\"1\")").unwrap_err().to_string();
+        assert_eq!(error, "method `take` expects an argument of type `Int`, but got `String`");
+        let error = evaluate_expr("\"abc\".contains()").unwrap_err().to_string();
+        assert_eq!(error, "method `contains` takes 1 arguments, but 0 were given");
+    }
+
+    #[test]
+    fn numbers_and_booleans() {
+        assert_eq!(eval("(-3).abs"), Value::Int(3));
+        assert_eq!(eval("2.5.round()"), Value::Float(2.0));
+        assert_eq!(eval("2.7.toInt()"), Value::Int(2));
+        assert_eq!(string("3.14159.toFixed(2)"), "3.14");
+        assert_eq!(string("255.toRadixString(16)"), "ff");
+        assert_eq!(eval("5.isBetween(1, 10)"), Value::Boolean(true));
+        assert_eq!(eval("true.xor(true)"), Value::Boolean(false));
+    }
+
+    #[test]
+    fn to_string() {
+        assert_eq!(string("1.0.toString()"), "1.0");
+        assert_eq!(string("List(1, \"a\", null).toString()"), "List(1, \"a\", null)");
+        assert_eq!(string("\"\\(Map(\"k\", 1.5))\""), "Map(\"k\", 1.5)");
+        assert_eq!(string("\"\\(null) \\(5.min) \\(1.5.gib) \\(\"a\")\""), "null 5.min 1.5.gib a");
+        let object = "\"\\(new Dynamic { name = \"x\"; [\"k\"] = 1; new Listing { 2 } })\"";
+        assert_eq!(string(object), "new Dynamic { name = \"x\"; [\"k\"] = 1; new Listing { 2 } }");
+        assert_eq!(string("new Mapping {}.toString() + ((a, b) -> a).toString()"), "new Mapping {}new Function2 {}");
+    }
+}
@@ -0,0 +1,665 @@
+//! Resolving imports: modules read from files, like `import "./birds.pkl"`, downloaded from `https:` URLs, or
+//! from packages, which the `packages` module fetches, and the modules of the standard library besides `pkl:base`,
+//! like `import "pkl:math"`. A module of a project can import the modules of the project's dependencies by name, like
+//! `import "@birds/pigeon.pkl"`, as the `project` module resolves them. `import*` imports every file matching a glob
+//! pattern, like `import* "birds/*.pkl"`, into a `Mapping` by path.
+//!
+//! The files and URLs that a module imports are read ahead of evaluating it, several at once, by the `prefetch`
+//! module.
+//!
+//! Relative imports are resolved against the directory of the importing module, whether it's a file or a URL. A
+//! module is evaluated once, however many modules import it: every import of it resolves to the same object, cached
+//! by the canonical `file:` URI of the file, its URL, or the URI of a standard library module. So a URL is
+//! downloaded at most once by an evaluator, but it isn't kept anywhere once the evaluator is dropped: unlike the
+//! packages in the cache directory, every new evaluator downloads it again.
+//!
+//! Only modules whose resolved URI matches one of the `allowed_modules` patterns of the [`EvaluatorOptions`] can be
+//! imported.
+//!
+//! Standard library modules are built by the evaluator rather than evaluated from source: their properties are values
+//! computed up front, and their methods are implemented here.
+
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use pkl_ast::uri::has_scheme;
+use pkl_ast::Span;
+use pkl_diagnostics::Code;
+use pkl_lexer::line_index::LineIndex;
+use pkl_stdlib::glob::{self, Glob};
+use pkl_stdlib::{math, platform};
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::methods::{string_val, Call};
+use crate::object::unsupported;
+use crate::options::Access;
+use crate::packages;
+use crate::runtime::{Def, Key, Member, Obj, Val};
+use crate::value::ObjectKind;
+#[cfg(doc)]
+use crate::EvaluatorOptions;
+
+const MATH: &str = "pkl.math";
+const PLATFORM: &str = "pkl.platform";
+const XML: &str = "pkl.xml";
+
+impl<'a> Evaluator<'a> {
+    /// Resolves an imported module by its URI, like `pkl:math` or `../birds.pkl`, written in the module at `base`.
+    pub(crate) fn import(&self, span: Span, uri: &str, base: &str) -> Result<Rc<Obj<'a>>> {
+        let key = if uri.starts_with("pkl:") {
+            uri.to_string()
+        } else if let Some(dependency) = uri.strip_prefix('@').filter(|_| !base.starts_with("package:")) {
+            self.resolve_dependency(span, dependency)?
+        } else if uri.starts_with("package:") || base.starts_with("package:") {
+            self.resolve_package(span, uri, base)?
+        } else {
+            resolve(span, uri, base)?
+        };
+        self.check_access(span, Access::Import, &key)?;
+        if let Some((_, module)) = self.modules.borrow().iter().find(|(imported, _)| *imported == key) {
+            return Ok(module.clone());
+        }
+
+        let module = match key.as_str() {
+            "pkl:math" => Rc::new(math_module()),
+            "pkl:platform" => Rc::new(platform_module()),
+            "pkl:xml" => Rc::new(native_object(ObjectKind::Typed(XML.to_string()), Vec::new())),
+            "pkl:json" | "pkl:yaml" | "pkl:toml" => {
+                Rc::new(native_object(ObjectKind::Typed(uri.replace(':', ".")), Vec::new()))
+            }
+            "pkl:Project" => self.load(span, uri, &key)?,
+            _ if uri.starts_with("pkl:") => return Err(EvalError::new(span, format!("can't find module `{uri}`"))),
+            _ => {
+                let in_import = |error: EvalError| error.in_frame(format!("import of `{uri}`"), base.to_string(), span);
+                self.load(span, uri, &key).map_err(in_import)?
+            }
+        };
+        self.modules.borrow_mut().push((key, module.clone()));
+        Ok(module)
+    }
+
+    /// Evaluates an `import*` of the files matching a glob pattern, written in the module at `base`, into a `Mapping`
+    /// of the modules by their paths as the pattern writes them.
+    pub(crate) fn import_glob(&self, span: Span, pattern: &str, base: &str) -> Result<Rc<Obj<'a>>> {
+        let mut entries = Vec::new();
+        for (path, uri) in self.glob_files(span, Access::Import, pattern, base)? {
+            entries.push((path, Val::Object(self.import(span, &uri, base)?)));
+        }
+        Ok(Rc::new(mapping(span, entries)))
+    }
+
+    /// The files matching a glob pattern like `configs/*.pkl`, relative to the module at `base`, that can be
+    /// imported or read: each by its path as the pattern writes it, and its `file:` URI, sorted by path.
+    ///
+    /// Only the directory before the first segment with a wildcard is searched, without following links to other
+    /// directories.
+    pub(crate) fn glob_files(
+        &self,
+        span: Span,
+        access: Access,
+        pattern: &str,
+        base: &str,
+    ) -> Result<Vec<(String, String)>> {
+        let (scheme, path) = match pattern.strip_prefix("file://") {
+            Some(path) => ("file://", path),
+            None if has_scheme(pattern) || base.starts_with("https:") || base.starts_with("package:") => {
+                let scheme = if has_scheme(pattern) { pattern } else { base };
+                let scheme = &scheme[..scheme.find(':').expect("a scheme ends with `:`")];
+                return Err(unsupported(span, &format!("glob patterns of `{scheme}:` URIs")));
+            }
+            None => ("", pattern),
+        };
+        let glob = Glob::new(path).map_err(|error| EvalError::new(span, error.message))?;
+        let segments: Vec<&str> = path.split('/').collect();
+        let literal = segments.iter().take_while(|segment| !glob::is_pattern(segment)).count().min(segments.len() - 1);
+        let written = segments[..literal].join("/");
+        let directory = match base.strip_prefix("file://") {
+            Some(base) => Path::new(base).parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => std::env::current_dir().unwrap_or_default(),
+        };
+        let directory = normalize(&directory.join(&written));
+        self.check_access(span, access, &format!("file://{}", directory.display()))?;
+
+        let recursive = path.contains("**");
+        let mut files = Vec::new();
+        let mut pending = vec![(directory, written, segments.len() - literal)];
+        while let Some((directory, written, depth)) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&directory) else { continue };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let path = if written.is_empty() { name } else { format!("{written}/{name}") };
+                let Ok(kind) = entry.file_type() else { continue };
+                if kind.is_dir() {
+                    if recursive || depth > 1 {
+                        pending.push((entry.path(), path, depth - 1));
+                    }
+                } else if entry.path().is_file() && glob.is_match(&path) {
+                    let Ok(canonical) = entry.path().canonicalize() else { continue };
+                    let uri = format!("file://{}", canonical.display());
+                    if self.check_access(span, access, &uri).is_ok() {
+                        files.push((format!("{scheme}{path}"), uri));
+                    }
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// The URI of an evaluated module, which its relative imports are resolved against.
+    pub(crate) fn module_uri(&self, module: &Rc<Obj<'a>>) -> String {
+        let modules = self.modules.borrow();
+        let uri = modules.iter().find(|(_, evaluated)| Rc::ptr_eq(evaluated, module)).map(|(uri, _)| uri.as_str());
+        uri.unwrap_or("repl:text").to_string()
+    }
+
+    /// Resolves an import like `@birds/pigeon.pkl` of a module of a dependency of the project, which is a package or
+    /// the directory of another project.
+    fn resolve_dependency(&self, span: Span, dependency: &str) -> Result<String> {
+        let (name, path) = dependency.split_once('/').unwrap_or((dependency, ""));
+        let Some(target) = self.options.dependencies.get(name) else {
+            return Err(EvalError::new(span, format!("there's no dependency named `{name}`")));
+        };
+        if target.starts_with("package:") {
+            self.resolve_package(span, &format!("{target}#/{path}"), "repl:text")
+        } else {
+            resolve(span, &format!("{}/{path}", target.trim_end_matches('/')), "repl:text")
+        }
+    }
+
+    /// Reads, parses, and declares the members of the module at the resolved `file:`, `https:`, or `package:` URI
+    /// `key`.
+    fn load(&self, span: Span, uri: &str, key: &str) -> Result<Rc<Obj<'a>>> {
+        let loading = self.loading.borrow();
+        if let Some(start) = loading.iter().position(|loading| loading == key) {
+            let cycle = loading[start..].iter().map(String::as_str).chain([key]);
+            let chain: Vec<String> = cycle.map(|uri| format!("`{uri}`")).collect();
+            let message = format!("module `{uri}` imports itself: {}", chain.join(" -> "));
+            return Err(EvalError::new(span, message).with_code(Code::CircularReference));
+        }
+        drop(loading);
+        let read_error = |error: String| EvalError::new(span, format!("can't read module `{uri}`: {error}"));
+        let prefetched = self.prefetched.borrow_mut().remove(key);
+        let source = if key == "pkl:Project" {
+            include_str!("Project.pkl").to_string()
+        } else if let Some(source) = prefetched {
+            source
+        } else if let Some(path) = key.strip_prefix("file://") {
+            std::fs::read_to_string(path).map_err(|error| read_error(error.to_string()))?
+        } else if key.starts_with("https:") {
+            let source = packages::download(key).map_err(read_error)?;
+            String::from_utf8(source).map_err(|_| read_error("it isn't valid UTF-8".to_string()))?
+        } else {
+            self.package_source(span, uri, key)?
+        };
+        let source = self.alloc.alloc_str(&source);
+        self.sources.borrow_mut().push((key.to_string(), source));
+
+        let result = pkl_parser::parse_module(self.alloc, source);
+        if let Some(diagnostic) = result.diagnostics.first() {
+            let position = LineIndex::new(source).line_col(diagnostic.span.start);
+            let message = format!("can't parse module `{uri}`: {position}: {}", diagnostic.message);
+            return Err(EvalError::new(span, message).with_code(Code::InvalidImport));
+        }
+
+        self.loading.borrow_mut().push(key.to_string());
+        let module = self.module_object(self.alloc.alloc(result.node), key);
+        self.loading.borrow_mut().pop();
+        module.map_err(|error| error.in_module(key))
+    }
+}
+
+/// Resolves the URI of an imported file or URL, relative to the URI of the importing module, to the canonical
+/// `file:` URI of the file, or the normalized URL.
+pub(crate) fn resolve(span: Span, uri: &str, base: &str) -> Result<String> {
+    if uri.starts_with("https:") || (base.starts_with("https:") && !has_scheme(uri)) {
+        return resolve_url(span, uri, base);
+    }
+    let Some(path) = file_path(uri, base) else {
+        let scheme = &uri[..uri.find(':').expect("a scheme ends with `:`")];
+        return Err(unsupported(span, &format!("imports of `{scheme}:` modules")));
+    };
+    let path = path.canonicalize().map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => EvalError::new(span, format!("can't find module `{uri}`")),
+        _ => EvalError::new(span, format!("can't read module `{uri}`: {error}")),
+    })?;
+    Ok(format!("file://{}", path.display()))
+}
+
+/// Resolves an `https:` URL, or a path relative to the URL of the importing module, removing its `.` and `..`
+/// segments.
+pub(crate) fn resolve_url(span: Span, uri: &str, base: &str) -> Result<String> {
+    let absolute = if uri.starts_with("https:") { uri } else { base };
+    let rest = absolute.strip_prefix("https://").ok_or_else(|| EvalError::new(span, format!("invalid URL `{uri}`")))?;
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let mut path = Path::new("/").join(path);
+    if !uri.starts_with("https:") {
+        path = path.parent().unwrap_or(Path::new("/")).join(uri);
+    }
+    Ok(format!("https://{authority}{}", normalize(&path).display()))
+}
+
+/// The normalized path of the file that `uri` names, a `file:` URI or a path relative to the module at `base`, which
+/// may not exist; `None` for a URI of another scheme.
+pub(crate) fn file_path(uri: &str, base: &str) -> Option<PathBuf> {
+    let path = match uri.strip_prefix("file://") {
+        Some(path) => PathBuf::from(path),
+        None if has_scheme(uri) => return None,
+        None => {
+            let directory = match base.strip_prefix("file://") {
+                Some(base) => Path::new(base).parent().map(Path::to_path_buf).unwrap_or_default(),
+                None => std::env::current_dir().unwrap_or_default(),
+            };
+            directory.join(uri)
+        }
+    };
+    Some(normalize(&path))
+}
+
+/// Removes the `.` and `..` segments of a path, without following links.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+pub(crate) fn is_math(object: &Obj) -> bool {
+    object.kind.class_name() == MATH
+}
+
+pub(crate) fn is_xml(object: &Obj) -> bool {
+    object.kind.class_name() == XML
+}
+
+/// The prototype of a class of a module built by the evaluator, like `json.Parser`.
+pub(crate) fn module_class<'a>(module: &Obj, name: &str) -> Option<Obj<'a>> {
+    match (module.kind.class_name(), name) {
+        (module @ ("pkl.json" | "pkl.yaml" | "pkl.toml"), "Parser") => {
+            let kind = ObjectKind::Typed(format!("{module}#Parser"));
+            Some(native_object(kind, vec![("useMapping", Val::Boolean(false))]))
+        }
+        (XML, "Renderer") => {
+            let mut properties = vec![
+                ("indent", string_val("  ")),
+                ("xmlVersion", string_val("1.0")),
+                ("rootElementName", string_val("root")),
+            ];
+            properties.push(converters());
+            Some(native_object(ObjectKind::Typed(format!("{XML}#Renderer")), properties))
+        }
+        _ => None,
+    }
+}
+
+/// The prototype of a class of `pkl:base` built by the evaluator, like `JsonRenderer`.
+pub(crate) fn base_class<'a>(name: &str) -> Option<Obj<'a>> {
+    let mut properties = match name {
+        "RenderDirective" => return Some(render_directive("")),
+        "JsonRenderer" | "PcfRenderer" => {
+            vec![("indent", string_val("  ")), ("omitNullProperties", Val::Boolean(false))]
+        }
+        "YamlRenderer" => vec![("isStream", Val::Boolean(false)), ("omitNullProperties", Val::Boolean(false))],
+        "PListRenderer" => Vec::new(),
+        "PropertiesRenderer" => {
+            vec![("omitNullProperties", Val::Boolean(true)), ("restrictCharset", Val::Boolean(false))]
+        }
+        _ => return None,
+    };
+    properties.push(converters());
+    Some(native_object(ObjectKind::Typed(name.to_string()), properties))
+}
+
+/// The `converters` of a renderer, which it has none of by default.
+fn converters<'a>() -> (&'static str, Val<'a>) {
+    ("converters", Val::Object(Rc::new(Obj::new(ObjectKind::Mapping, None, None))))
+}
+
+/// An object whose properties are already evaluated.
+/// A `Mapping` of values by `String` keys.
+pub(crate) fn mapping<'a>(span: Span, entries: Vec<(String, Val<'a>)>) -> Obj<'a> {
+    let mut object = Obj::new(ObjectKind::Mapping, None, None);
+    for (key, value) in entries {
+        object.members.push((Key::Entry(string_val(key)), Member { span, def: Def::Value(value) }));
+    }
+    object
+}
+
+pub(crate) fn native_object<'a>(kind: ObjectKind, properties: Vec<(&str, Val<'a>)>) -> Obj<'a> {
+    let mut object = Obj::new(kind, None, None);
+    for (name, value) in properties {
+        let member = Member { span: Span::new(0, 0), def: Def::Value(value) };
+        object.members.push((Key::Property(name.into()), member));
+    }
+    object
+}
+
+fn math_module<'a>() -> Obj<'a> {
+    native_object(
+        ObjectKind::Typed(MATH.to_string()),
+        vec![
+            ("minInt", Val::Int(i64::MIN)),
+            ("minInt8", Val::Int(i8::MIN.into())),
+            ("minInt16", Val::Int(i16::MIN.into())),
+            ("minInt32", Val::Int(i32::MIN.into())),
+            ("maxInt", Val::Int(i64::MAX)),
+            ("maxInt8", Val::Int(i8::MAX.into())),
+            ("maxInt16", Val::Int(i16::MAX.into())),
+            ("maxInt32", Val::Int(i32::MAX.into())),
+            ("maxUInt", Val::Int(i64::MAX)),
+            ("maxUInt8", Val::Int(u8::MAX.into())),
+            ("maxUInt16", Val::Int(u16::MAX.into())),
+            ("maxUInt32", Val::Int(u32::MAX.into())),
+            ("minFiniteFloat", Val::Float(f64::MIN)),
+            ("maxFiniteFloat", Val::Float(f64::MAX)),
+            // the smallest subnormal number, like Java's `Double.MIN_VALUE`
+            ("minPositiveFloat", Val::Float(f64::from_bits(1))),
+            ("infinity", Val::Float(f64::INFINITY)),
+            ("nan", Val::Float(f64::NAN)),
+            ("e", Val::Float(std::f64::consts::E)),
+            ("pi", Val::Float(std::f64::consts::PI)),
+        ],
+    )
+}
+
+/// Calls a method of `pkl:math`.
+pub(crate) fn math_method<'a>(call: &Call, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let unary: fn(f64) -> f64 = match call.name {
+        "exp" => f64::exp,
+        "sqrt" => f64::sqrt,
+        "cbrt" => f64::cbrt,
+        "log" => f64::ln,
+        "log2" => f64::log2,
+        "log10" => f64::log10,
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "asin" => f64::asin,
+        "acos" => f64::acos,
+        "atan" => f64::atan,
+        _ => return binary_math_method(call, args),
+    };
+    let [x] = call.args(args)?;
+    Ok(Some(Val::Float(unary(call.number(&x)?))))
+}
+
+fn binary_math_method<'a>(call: &Call, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let value = match call.name {
+        "atan2" => {
+            let [y, x] = call.args(args)?;
+            Val::Float(call.number(&y)?.atan2(call.number(&x)?))
+        }
+        "gcd" | "lcm" => {
+            let [x, y] = call.args(args)?;
+            let (x, y) = (call.int(&x)?, call.int(&y)?);
+            let result = if call.name == "gcd" { math::gcd(x, y) } else { math::lcm(x, y) };
+            Val::Int(result.map_err(|e| call.error(e))?)
+        }
+        "isPowerOfTwo" => {
+            let [x] = call.args(args)?;
+            Val::Boolean(match x {
+                Val::Int(x) => math::is_power_of_two(x),
+                x => math::is_float_power_of_two(call.number(&x)?),
+            })
+        }
+        // the smaller or larger number as it is, or `NaN` if either is `NaN`
+        "min" | "max" => {
+            let [x, y] = call.args(args)?;
+            let (a, b) = (call.number(&x)?, call.number(&y)?);
+            if a.is_nan() || b.is_nan() {
+                Val::Float(f64::NAN)
+            } else if (call.name == "min") == (a <= b) {
+                x
+            } else {
+                y
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+fn platform_module<'a>() -> Obj<'a> {
+    let object = |class: &str, properties| {
+        Val::Object(Rc::new(native_object(ObjectKind::Typed(class.to_string()), properties)))
+    };
+    let current = object(
+        "Platform",
+        vec![
+            ("language", object("Language", vec![("version", string_val(platform::LANGUAGE_VERSION))])),
+            (
+                "runtime",
+                object(
+                    "Runtime",
+                    vec![
+                        ("name", string_val(platform::RUNTIME_NAME)),
+                        ("version", string_val(platform::RUNTIME_VERSION)),
+                    ],
+                ),
+            ),
+            (
+                "operatingSystem",
+                object("OperatingSystem", vec![("name", string_val(platform::operating_system()))]),
+            ),
+            ("processor", object("Processor", vec![("architecture", string_val(platform::architecture()))])),
+        ],
+    );
+    native_object(ObjectKind::Typed(PLATFORM.to_string()), vec![("current", current)])
+}
+
+/// Calls a method of `pkl:xml`. Elements, comments, and inline values are dynamic objects flagged by a property like
+/// `_isXmlElement`, which the XML renderer writes as markup rather than as an element per property.
+pub(crate) fn xml_method<'a>(call: &Call, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let object = match call.name {
+        "Element" => {
+            let [name] = call.args(args)?;
+            call.string(&name)?;
+            let attributes = Val::Object(Rc::new(Obj::new(ObjectKind::Mapping, None, None)));
+            let properties = vec![
+                ("_isXmlElement", Val::Boolean(true)),
+                ("name", name),
+                ("attributes", attributes),
+                ("isBlockFormat", Val::Boolean(true)),
+            ];
+            native_object(ObjectKind::Dynamic, properties)
+        }
+        "Comment" => {
+            let [value] = call.args(args)?;
+            call.string(&value)?;
+            native_object(ObjectKind::Dynamic, vec![("_isXmlComment", Val::Boolean(true)), ("value", value)])
+        }
+        "CData" => {
+            let [text] = call.args(args)?;
+            // a `]]>` in the text ends one section and starts another
+            let text = call.string(&text)?.replace("]]>", "]]]]><![CDATA[>");
+            render_directive(&format!("<![CDATA[{text}]]>"))
+        }
+        "Inline" => {
+            let [value] = call.args(args)?;
+            native_object(ObjectKind::Dynamic, vec![("_isXmlInline", Val::Boolean(true)), ("value", value)])
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(Val::Object(Rc::new(object))))
+}
+
+/// A `RenderDirective` of `pkl:base`, whose text renderers write as is.
+pub(crate) fn render_directive<'a>(text: &str) -> Obj<'a> {
+    native_object(ObjectKind::Typed("RenderDirective".to_string()), vec![("text", string_val(text))])
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use oxc_allocator::Allocator;
+
+    use pkl_ast::Span;
+
+    use super::resolve;
+    use crate::evaluator::Evaluator;
+    use crate::value::Value;
+    use crate::{evaluate, evaluate_at, Error, EvaluatorOptions};
+
+    fn property(source: &str, name: &str) -> Value {
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{source}: {error:?}"));
+        module.as_object().unwrap().property(name).unwrap_or_else(|| panic!("{source}: no `{name}`")).clone()
+    }
+
+    #[test]
+    fn math() {
+        assert_eq!(property("import \"pkl:math\"\nx = math.maxInt8 + math.minInt8", "x"), Value::Int(-1));
+        assert_eq!(property("import \"pkl:math\" as m\nx = m.sqrt(16)", "x"), Value::Float(4.0));
+        assert_eq!(property("x = import(\"pkl:math\").gcd(12, 18)", "x"), Value::Int(6));
+        assert_eq!(property("import \"pkl:math\"\nx = math.max(2, 1.5)", "x"), Value::Int(2));
+        assert_eq!(property("import \"pkl:math\"\nx = math.isPowerOfTwo(0.5)", "x"), Value::Boolean(true));
+        assert_eq!(property("import \"pkl:math\"\nx = math.cos(math.pi)", "x"), Value::Float(-1.0));
+
+        let error = evaluate("x = import(\"pkl:mat\")").unwrap_err().to_string();
+        assert_eq!(error, "can't find module `pkl:mat`");
+        let error = evaluate("import \"pkl:math\"\nx = math.min(1, \"a\")").unwrap_err().to_string();
+        assert_eq!(error, "method `min` expects an argument of type `Number`, but got `String`");
+    }
+
+    #[test]
+    fn platform() {
+        let source = "import \"pkl:platform\"\nx = platform.current.runtime.name";
+        assert_eq!(property(source, "x"), Value::String("pkl-rs".into()));
+        let source = "import \"pkl:platform\"\nx = platform.current.operatingSystem.name.isEmpty";
+        assert_eq!(property(source, "x"), Value::Boolean(false));
+    }
+
+    #[test]
+    fn files() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-imports-{}", std::process::id()));
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(dir.join("counter.pkl"), "next = 1").unwrap();
+        fs::write(dir.join("lib/bird.pkl"), "import \"../counter.pkl\"\nname = \"pigeon\"\nid = counter.next").unwrap();
+        fs::write(dir.join("cycle.pkl"), "import \"cycle.pkl\"").unwrap();
+        fs::write(dir.join("broken.pkl"), "a = \n").unwrap();
+        let main = r#"
+            import "lib/bird.pkl"
+            import "./lib/../lib/bird.pkl" as again
+            x = "\(bird.name)\(again.id)"
+        "#;
+
+        let alloc = Allocator::default();
+        let module = alloc.alloc(pkl_parser::parse_module(&alloc, main).node);
+        let mut evaluator = Evaluator::new(&alloc);
+        let value = evaluator.evaluate_module(module, &format!("file://{}/main.pkl", dir.display())).unwrap();
+        assert_eq!(value.as_object().unwrap().property("x"), Some(&Value::String("pigeon1".into())));
+        // `bird.pkl` is evaluated once, and `counter.pkl` with it
+        assert_eq!(evaluator.modules.borrow().len(), 3);
+
+        let error = |source: &str| evaluate_at(source, &format!("file://{}/main.pkl", dir.display())).unwrap_err();
+        assert_eq!(error("import \"missing.pkl\"").to_string(), "can't find module `missing.pkl`");
+        let cycle = format!("`file://{}/cycle.pkl`", dir.display());
+        let message = format!("module `cycle.pkl` imports itself: {cycle} -> {cycle}");
+        assert_eq!(error("import \"cycle.pkl\"").to_string(), message);
+        let message = "can't parse module `broken.pkl`: 2:1: expected expression, found end of input";
+        assert_eq!(error("x = import(\"broken.pkl\")").to_string(), message);
+        let message = "imports of `ftp:` modules aren't supported yet";
+        assert_eq!(error("import \"ftp://example.com/a.pkl\"").to_string(), message);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn globs() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-imports-glob-{}", std::process::id()));
+        fs::create_dir_all(dir.join("configs/nested")).unwrap();
+        fs::write(dir.join("configs/a.pkl"), "name = \"a\"").unwrap();
+        fs::write(dir.join("configs/b.pkl"), "name = \"b\"").unwrap();
+        fs::write(dir.join("configs/notes.txt"), "").unwrap();
+        fs::write(dir.join("configs/nested/c.pkl"), "name = \"c\"").unwrap();
+        let uri = format!("file://{}/main.pkl", dir.display());
+        let names = |pattern: &str| {
+            let source = format!("import* \"{pattern}\" as configs\nx = configs.keys.toList()");
+            let module = evaluate_at(&source, &uri).unwrap();
+            let Some(Value::List(names)) = module.as_object().unwrap().property("x").cloned() else { panic!() };
+            names.into_iter().map(|name| format!("{name:?}")).collect::<Vec<_>>().join(" ")
+        };
+        assert_eq!(names("configs/*.pkl"), r#"String("configs/a.pkl") String("configs/b.pkl")"#);
+        assert_eq!(names("./configs/**/{b,c}.pkl"), r#"String("./configs/b.pkl") String("./configs/nested/c.pkl")"#);
+        let source = "x = import*(\"configs/*.pkl\")[\"configs/b.pkl\"].name";
+        let module = evaluate_at(source, &uri).unwrap();
+        assert_eq!(module.as_object().unwrap().property("x"), Some(&Value::String("b".into())));
+
+        let error = evaluate_at("import* \"configs/*.pkl\"", &uri).unwrap_err().to_string();
+        assert_eq!(error, "an `import*` clause needs a name, like `import* \"birds/*.pkl\" as birds`");
+        let error = evaluate_at("x = import*(\"https://example.com/*.pkl\")", &uri).unwrap_err().to_string();
+        assert_eq!(error, "glob patterns of `https:` URIs aren't supported yet");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn stack_frames() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-frames-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lib.pkl"), "function ratio(n) = n ~/ 0\nresult = ratio(total)\ntotal = 4\n").unwrap();
+        let root = format!("file://{}", dir.display());
+        let main = "import \"lib.pkl\"\nanswer = lib.result\n";
+        let Err(Error::Eval(error)) = evaluate_at(main, &format!("{root}/main.pkl")) else { panic!() };
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(error.uri, Some(format!("{root}/lib.pkl")));
+        let frames: Vec<String> = error.frames.iter().map(ToString::to_string).collect();
+        let expected = [
+            format!("in method `ratio` at {root}/lib.pkl:1:21"),
+            format!("in property `result` at {root}/lib.pkl:2:1"),
+            format!("in property `answer` at {root}/main.pkl:2:1"),
+        ];
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn allowed_modules() {
+        let evaluate = |source: &str, allowed_modules: &[&str]| {
+            let alloc = Allocator::default();
+            let module = alloc.alloc(pkl_parser::parse_module(&alloc, source).node);
+            let allowed_modules = allowed_modules.iter().map(|pattern| pattern.to_string()).collect();
+            let options = EvaluatorOptions { allowed_modules, ..EvaluatorOptions::default() };
+            Evaluator::with_options(&alloc, options).evaluate_module(module, "repl:text").map(|_| ())
+        };
+        assert_eq!(evaluate("import \"pkl:math\"", &["pkl:"]), Ok(()));
+        assert_eq!(evaluate("import \"pkl:math\"", &["pkl:(math|xml)$"]), Ok(()));
+        let error = evaluate("import \"pkl:json\"", &["pkl:(math|xml)$", "file:"]).unwrap_err();
+        assert_eq!(error.message, "refusing to import `pkl:json`: it doesn't match any allowed module pattern");
+        let error = evaluate("import \"https://example.com/birds.pkl\"", &["pkl:", "https://pkl\\.dev/"]).unwrap_err();
+        let message = "refusing to import `https://example.com/birds.pkl`: it doesn't match any allowed module pattern";
+        assert_eq!(error.message, message);
+    }
+
+    #[test]
+    fn urls() {
+        let resolve = |uri, base| resolve(Span::new(0, 0), uri, base).unwrap();
+        assert_eq!(resolve("https://example.com/a/../b.pkl", "repl:text"), "https://example.com/b.pkl");
+        assert_eq!(resolve("c.pkl", "https://example.com/a/b.pkl"), "https://example.com/a/c.pkl");
+        assert_eq!(resolve("../../c.pkl", "https://example.com/a/b.pkl"), "https://example.com/c.pkl");
+        assert_eq!(resolve("/c.pkl", "https://example.com/a/b.pkl"), "https://example.com/c.pkl");
+    }
+
+    #[test]
+    fn xml() {
+        let source = "import \"pkl:xml\"\nx = (xml.Element(\"a\")) { attributes { [\"id\"] = \"1\" }; \"text\" }";
+        let element = property(source, "x");
+        let element = element.as_object().unwrap();
+        assert_eq!(element.property("name"), Some(&Value::String("a".into())));
+        assert_eq!(element.property("attributes").unwrap().as_object().unwrap().entries.len(), 1);
+        assert_eq!(element.elements, vec![Value::String("text".into())]);
+
+        let source = "import \"pkl:xml\"\nx = xml.CData(\"a]]>b\").text";
+        assert_eq!(property(source, "x"), Value::String("<![CDATA[a]]]]><![CDATA[>b]]>".into()));
+        let source = "x = new RenderDirective { text = \"<br/>\" }.text";
+        assert_eq!(property(source, "x"), Value::String("<br/>".into()));
+    }
+}
@@ -0,0 +1,492 @@
+use std::rc::Rc;
+
+use pkl_ast::{ForGenerator, ModifierKind, ObjectBody, ObjectMember, ObjectMemberPredicate, ObjectSpread, Span};
+use pkl_diagnostics::Code;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::{check_assignable, duplicate, is_default, Evaluator};
+use crate::runtime::{Def, Env, Frame, Key, Member, Method, Obj, Val};
+use crate::value::ObjectKind;
+
+impl<'a> Evaluator<'a> {
+    /// Creates an object amending `parent` with the members of `body`, which is written in the scope `env`.
+    ///
+    /// The members aren't evaluated yet, except for the keys of entries, which decide what the object's members are,
+    /// and the conditions and iterables of generators, which decide which members the body has.
+    pub(crate) fn amend(&self, parent: Val<'a>, body: &'a ObjectBody<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        if let [param, ..] = body.params.as_slice() {
+            return Err(unsupported(param.span, "object body parameters"));
+        }
+        let Val::Object(parent) = parent else {
+            return Err(EvalError::new(body.span, format!("can't amend a value of type `{}`", parent.type_name())));
+        };
+
+        let mut layer = Obj::new(parent.kind.clone(), Some(parent.clone()), Some(env.clone()));
+        let mut predicates = Vec::new();
+        self.add_members(&mut layer, &parent, &body.members, env, None, &mut predicates)?;
+        // they're added last, so that the members the body defines itself take precedence
+        for (predicate, env, bindings) in predicates {
+            self.predicate(&mut layer, &parent, predicate, &env, bindings.as_ref())?;
+        }
+        Ok(Val::Object(Rc::new(layer)))
+    }
+
+    /// Adds the members written in a body to the layer amending `parent`. `env` is the scope they're written in, and
+    /// `bindings` are the variables of the generators around them, if they're in the body of one. Member predicates
+    /// are added to `predicates` instead.
+    fn add_members(
+        &self,
+        layer: &mut Obj<'a>,
+        parent: &Rc<Obj<'a>>,
+        members: &'a [ObjectMember<'a>],
+        env: &Rc<Env<'a>>,
+        bindings: Option<&Bindings<'a>>,
+        predicates: &mut Vec<Predicate<'a>>,
+    ) -> Result<()> {
+        for member in members {
+            match member {
+                ObjectMember::Property(property) => {
+                    let name: Rc<str> = property.name.name.into();
+                    let key = if property.modifiers.has(ModifierKind::Local) {
+                        Key::Local(name)
+                    } else if has_properties(&layer.kind) || is_default(&layer.kind, &name) {
+                        Key::Property(name)
+                    } else {
+                        let message = format!("an object of type `{}` can't have properties", layer.kind.class_name());
+                        return Err(EvalError::new(property.name.span, message));
+                    };
+                    if let (ObjectKind::Typed(class), Key::Property(_)) = (&layer.kind, &key) {
+                        if !self.has_member(parent, &key) {
+                            let message = format!("class `{class}` has no property `{}`", property.name.name);
+                            return Err(EvalError::new(property.name.span, message));
+                        }
+                    }
+                    check_assignable(parent, &key, property.name.span)?;
+                    let def = Def::Expr {
+                        value: property.value.as_ref(),
+                        bodies: &property.bodies,
+                        ty: None,
+                        modifiers: &property.modifiers.0,
+                        deprecated: None,
+                        bindings: bindings.cloned(),
+                    };
+                    if !define(layer, key, Member { span: property.name.span, def }) {
+                        return Err(duplicate(property.name.span, property.name.name));
+                    }
+                }
+                ObjectMember::Entry(entry) => {
+                    let key = self.eval_expr(&entry.key, env)?;
+                    let key = match (&layer.kind, key) {
+                        (ObjectKind::Listing, Val::Int(index)) => match usize::try_from(index) {
+                            Ok(index) if index < parent.element_count => Key::Element(index),
+                            _ => return Err(EvalError::new(entry.key.span(), "element index is out of range")),
+                        },
+                        (ObjectKind::Dynamic | ObjectKind::Mapping, key) => Key::Entry(key),
+                        (kind, _) => {
+                            let message = format!("an object of type `{}` can't have entries", kind.class_name());
+                            return Err(EvalError::new(entry.span, message));
+                        }
+                    };
+                    let (value, bodies) = (entry.value.as_ref(), &entry.bodies);
+                    let bindings = bindings.cloned();
+                    let def = Def::Expr { value, bodies, ty: None, modifiers: &[], deprecated: None, bindings };
+                    if !define(layer, key, Member { span: entry.key.span(), def }) {
+                        let error = EvalError::new(entry.key.span(), "duplicate definition of an entry");
+                        return Err(error.with_code(Code::DuplicateDefinition));
+                    }
+                }
+                ObjectMember::Element(element) => {
+                    if !matches!(layer.kind, ObjectKind::Dynamic | ObjectKind::Listing) {
+                        let message = format!("an object of type `{}` can't have elements", layer.kind.class_name());
+                        return Err(EvalError::new(element.span, message));
+                    }
+                    let (value, bindings) = (Some(&element.value), bindings.cloned());
+                    let def = Def::Expr { value, bodies: &[], ty: None, modifiers: &[], deprecated: None, bindings };
+                    let key = Key::Element(layer.element_count);
+                    layer.element_count += 1;
+                    layer.members.push((key, Member { span: element.span, def }));
+                }
+                ObjectMember::Method(method) if bindings.is_some() => {
+                    return Err(EvalError::new(method.span, "a generator can't define methods"));
+                }
+                ObjectMember::Method(method) => layer.methods.push(Method {
+                    span: method.span,
+                    name: method.name.name,
+                    local: true,
+                    type_params: &[],
+                    params: &method.params,
+                    body: Some(&method.body),
+                    deprecated: None,
+                }),
+                ObjectMember::MemberPredicate(predicate) => {
+                    predicates.push((predicate, env.clone(), bindings.cloned()));
+                }
+                ObjectMember::Spread(spread) => self.spread(layer, spread, env)?,
+                ObjectMember::For(generator) => self.generate(layer, parent, generator, env, bindings, predicates)?,
+                ObjectMember::When(generator) => {
+                    let body = match self.eval_expr(&generator.condition, env)? {
+                        Val::Boolean(true) => &generator.body,
+                        Val::Boolean(false) => match &generator.else_body {
+                            Some(body) => body,
+                            None => continue,
+                        },
+                        condition => {
+                            let ty = condition.type_name();
+                            let message = format!("expected a `Boolean` condition, but got `{ty}`");
+                            return Err(EvalError::new(generator.condition.span(), message));
+                        }
+                    };
+                    let bindings = bindings.cloned().unwrap_or_default();
+                    self.add_members(layer, parent, generator_members(body)?, env, Some(&bindings), predicates)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the members of the body of a `for` generator to a layer, once for each element or entry it iterates
+    /// over: the elements of lists, sets, and listings, by index, and the entries of maps and mappings. Dynamic
+    /// objects give their elements and then their entries.
+    fn generate(
+        &self,
+        layer: &mut Obj<'a>,
+        parent: &Rc<Obj<'a>>,
+        generator: &'a ForGenerator<'a>,
+        env: &Rc<Env<'a>>,
+        bindings: Option<&Bindings<'a>>,
+        predicates: &mut Vec<Predicate<'a>>,
+    ) -> Result<()> {
+        let indexed = |values: Vec<Val<'a>>| {
+            values.into_iter().enumerate().map(|(index, value)| (Val::Int(index as i64), value))
+        };
+        let pairs: Vec<_> = match self.eval_expr(&generator.iterable, env)? {
+            Val::List(values) | Val::Set(values) => indexed(values.to_vec()).collect(),
+            Val::Map(entries) => entries.to_vec(),
+            Val::Object(object) if !matches!(object.kind, ObjectKind::Typed(_)) => {
+                indexed(self.elements(&object)?).chain(self.entries(&object)?).collect()
+            }
+            value => {
+                let message = format!("can't iterate over a value of type `{}`", value.type_name());
+                return Err(EvalError::new(generator.iterable.span(), message));
+            }
+        };
+        let members = generator_members(&generator.body)?;
+
+        for (key, value) in pairs {
+            let params = generator.key.iter().chain([&generator.value]);
+            let args = if generator.key.is_some() { vec![key, value] } else { vec![value] };
+            let mut variables = Vec::new();
+            for (param, arg) in params.zip(args) {
+                if let Some(ty) = &param.ty {
+                    self.check_type(param.span, &arg, ty, env)?;
+                }
+                if let Some(name) = &param.name {
+                    variables.push((name.name, arg));
+                }
+            }
+            let mut all = bindings.map_or_else(Vec::new, |bindings| bindings.to_vec());
+            all.extend(variables.iter().cloned());
+            let env = Env::new(Frame::Bindings(variables), Some(env.clone()));
+            self.add_members(layer, parent, members, &env, Some(&Rc::new(all)), predicates)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a member predicate like `[[age > 2]] { ... }` to a layer, as a member for each element and entry of
+    /// `parent` whose value matches it. The predicate has the value as `this`, with its properties in scope.
+    ///
+    /// Unlike members written in the body, the members of the parent are evaluated right away, to match them.
+    fn predicate(
+        &self,
+        layer: &mut Obj<'a>,
+        parent: &Rc<Obj<'a>>,
+        predicate: &'a ObjectMemberPredicate<'a>,
+        env: &Rc<Env<'a>>,
+        bindings: Option<&Bindings<'a>>,
+    ) -> Result<()> {
+        let elements = self.elements(parent)?.into_iter().enumerate();
+        let elements = elements.map(|(index, value)| (Key::Element(index), value));
+        let entries = self.entries(parent)?.into_iter().map(|(key, value)| (Key::Entry(key), value));
+        for (key, value) in elements.chain(entries).collect::<Vec<_>>() {
+            let scope = Env::new(Frame::Constraint(value), Some(env.clone()));
+            let matches = match self.eval_expr(&predicate.predicate, &scope)? {
+                Val::Boolean(matches) => matches,
+                value => {
+                    let message = format!("expected a `Boolean` predicate, but got `{}`", value.type_name());
+                    return Err(EvalError::new(predicate.predicate.span(), message));
+                }
+            };
+            if matches && layer.own_member(&key).is_none() {
+                let (value, bodies, bindings) = (predicate.value.as_ref(), &predicate.bodies, bindings.cloned());
+                let def = Def::Expr { value, bodies, ty: None, modifiers: &[], deprecated: None, bindings };
+                layer.members.push((key, Member { span: predicate.span, def }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the members of a spread value to a layer: the elements of listings, lists, and sets, the entries of
+    /// mappings and maps, and every member of dynamic objects.
+    ///
+    /// Unlike members written in the body, they're evaluated right away.
+    fn spread(&self, layer: &mut Obj<'a>, spread: &'a ObjectSpread<'a>, env: &Rc<Env<'a>>) -> Result<()> {
+        let span = spread.value.span();
+        let (properties, entries, elements) = match self.eval_expr(&spread.value, env)? {
+            Val::Null if spread.nullable => return Ok(()),
+            Val::List(values) | Val::Set(values) => (Vec::new(), Vec::new(), values.to_vec()),
+            Val::Map(entries) => (Vec::new(), entries.to_vec(), Vec::new()),
+            Val::Object(object) if !matches!(object.kind, ObjectKind::Typed(_)) => {
+                (self.properties(&object)?, self.entries(&object)?, self.elements(&object)?)
+            }
+            value => {
+                let message = format!("can't spread a value of type `{}`", value.type_name());
+                return Err(EvalError::new(span, message));
+            }
+        };
+
+        let kind = &layer.kind;
+        let cant_have = [
+            (!properties.is_empty() && !has_properties(kind), "properties"),
+            (!entries.is_empty() && !matches!(kind, ObjectKind::Dynamic | ObjectKind::Mapping), "entries"),
+            (!elements.is_empty() && !matches!(kind, ObjectKind::Dynamic | ObjectKind::Listing), "elements"),
+        ];
+        if let Some((_, what)) = cant_have.iter().find(|(cant, _)| *cant) {
+            let message = format!("an object of type `{}` can't have {what}", kind.class_name());
+            return Err(EvalError::new(span, message));
+        }
+
+        for (name, value) in properties {
+            if let (ObjectKind::Typed(class), Some(parent)) = (&layer.kind, &layer.parent) {
+                if !self.has_member(parent, &Key::Property(name.clone())) {
+                    let error = EvalError::new(span, format!("class `{class}` has no property `{name}`"));
+                    return Err(error.with_code(Code::UnknownProperty));
+                }
+            }
+            if let Some(parent) = &layer.parent {
+                check_assignable(parent, &Key::Property(name.clone()), span)?;
+            }
+            if !define(layer, Key::Property(name.clone()), Member { span, def: Def::Value(value) }) {
+                return Err(duplicate(span, &name));
+            }
+        }
+        for (key, value) in entries {
+            if !define(layer, Key::Entry(key), Member { span, def: Def::Value(value) }) {
+                let error = EvalError::new(span, "duplicate definition of an entry");
+                return Err(error.with_code(Code::DuplicateDefinition));
+            }
+        }
+        for value in elements {
+            let key = Key::Element(layer.element_count);
+            layer.element_count += 1;
+            layer.members.push((key, Member { span, def: Def::Value(value) }));
+        }
+        Ok(())
+    }
+}
+
+/// The variables of the `for` generators around a member.
+type Bindings<'a> = Rc<Vec<(&'a str, Val<'a>)>>;
+
+/// A member predicate, with the scope and the generator variables it's written with.
+type Predicate<'a> = (&'a ObjectMemberPredicate<'a>, Rc<Env<'a>>, Option<Bindings<'a>>);
+
+/// Adds a member to a layer, unless the layer already defines it.
+fn define<'a>(layer: &mut Obj<'a>, key: Key<'a>, member: Member<'a>) -> bool {
+    if layer.own_member(&key).is_some() {
+        return false;
+    }
+    layer.members.push((key, member));
+    true
+}
+
+fn has_properties(kind: &ObjectKind) -> bool {
+    !matches!(kind, ObjectKind::Listing | ObjectKind::Mapping)
+}
+
+/// The members of the body of a generator, which can't have parameters.
+fn generator_members<'a>(body: &'a ObjectBody<'a>) -> Result<&'a [ObjectMember<'a>]> {
+    match body.params.as_slice() {
+        [param, ..] => Err(EvalError::new(param.span, "the body of a generator can't have parameters")),
+        [] => Ok(&body.members),
+    }
+}
+
+/// An error for a construct the evaluator can't evaluate yet.
+pub(crate) fn unsupported(span: Span, what: &str) -> EvalError {
+    EvalError::new(span, format!("{what} aren't supported yet")).with_code(Code::Unsupported)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate;
+    use crate::value::{Object, ObjectKind, Value};
+
+    fn object(source: &str, name: &str) -> Object {
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{source}: {error:?}"));
+        match module.as_object().unwrap().property(name) {
+            Some(Value::Object(object)) => object.clone(),
+            value => panic!("{name} is {value:?}"),
+        }
+    }
+
+    #[test]
+    fn dynamic_objects() {
+        let person = object("person { name = \"Pigeon\"; [\"tag\"] = 1; \"element\"; age = 3 }", "person");
+
+        assert_eq!(person.kind, ObjectKind::Dynamic);
+        assert_eq!(person.properties.keys().collect::<Vec<_>>(), ["name", "age"]);
+        assert_eq!(person.entry(&Value::String("tag".into())), Some(&Value::Int(1)));
+        assert_eq!(person.elements, [Value::String("element".into())]);
+    }
+
+    #[test]
+    fn amending() {
+        let source = "base { a = 1; b { c = 2 } }\nderived = (base) { b { d = c + 1 } }\nlocal x = 1\ny = x";
+        let derived = object(source, "derived");
+
+        assert_eq!(derived.property("a"), Some(&Value::Int(1)));
+        let Some(Value::Object(b)) = derived.property("b") else { panic!() };
+        assert_eq!(b.property("d"), Some(&Value::Int(3)));
+
+        let module = evaluate(source).unwrap();
+        assert_eq!(module.as_object().unwrap().property("x"), None);
+    }
+
+    #[test]
+    fn listings_and_mappings() {
+        let listing = object("base = new Listing { 1; 2 }\nl = (base) { [0] = 10; 3 }", "l");
+        assert_eq!(listing.elements, [Value::Int(10), Value::Int(2), Value::Int(3)]);
+
+        let mapping = object("m = new Mapping { [\"a\"] = 1 }", "m");
+        assert_eq!(mapping.kind, ObjectKind::Mapping);
+        let values = evaluate("m = new Mapping { [\"a\"] = 1; [\"b\"] = 2 }\nv = m.values").unwrap();
+        assert_eq!(values.as_object().unwrap().property("v"), Some(&Value::List(vec![Value::Int(1), Value::Int(2)])));
+
+        assert!(evaluate("l = new Listing { a = 1 }").is_err());
+        assert!(evaluate("m = new Mapping { 1 }").is_err());
+        assert!(evaluate("o { a = 1; a = 2 }").is_err());
+    }
+
+    #[test]
+    fn super_members() {
+        let source = "base { a = 1; [\"k\"] = 2 }\nderived = (base) { a = super.a + 1; [\"k\"] = super[\"k\"] + a }";
+        let derived = object(source, "derived");
+        assert_eq!(derived.property("a"), Some(&Value::Int(2)));
+        assert_eq!(derived.entry(&Value::String("k".into())), Some(&Value::Int(4)));
+    }
+
+    #[test]
+    fn mapping_defaults() {
+        let source = "m = new Mapping { default { port = 80 }; [\"a\"] { host = \"a\" }; [\"b\"] = 1 }";
+        let mapping = object(source, "m");
+        assert!(mapping.properties.is_empty());
+        let Some(Value::Object(a)) = mapping.entry(&Value::String("a".into())) else { panic!() };
+        assert_eq!(a.property("port"), Some(&Value::Int(80)));
+        assert_eq!(a.property("host"), Some(&Value::String("a".into())));
+
+        // a function is applied to the key or index first
+        let source = "m = new Mapping { default = (k) -> new Dynamic { name = k }; [\"x\"] {} }\n\
+                      l = new Listing { default = (i) -> new Dynamic { index = i }; new { a = 1 }; new { a = 2 } }";
+        let Some(Value::Object(x)) = object(source, "m").entry(&Value::String("x".into())).cloned() else { panic!() };
+        assert_eq!(x.property("name"), Some(&Value::String("x".into())));
+        let listing = object(source, "l");
+        let Value::Object(second) = &listing.elements[1] else { panic!() };
+        assert_eq!((second.property("index"), second.property("a")), (Some(&Value::Int(1)), Some(&Value::Int(2))));
+    }
+
+    #[test]
+    fn spreading() {
+        let source = "base = new Listing { 1; 2 }\n\
+                      l = new Listing { 0; ...base; ...List(3, 4); ...?null }\n\
+                      m = new Mapping { [\"a\"] = 1; ...Map(\"b\", 2) }\n\
+                      d { name = \"pigeon\"; [\"k\"] = 1; \"e\" }\n\
+                      merged { ...d; age = 3 }";
+        let listing = object(source, "l");
+        assert_eq!(listing.elements, [Value::Int(0), Value::Int(1), Value::Int(2), Value::Int(3), Value::Int(4)]);
+        let mapping = object(source, "m");
+        assert_eq!(mapping.entry(&Value::String("b".into())), Some(&Value::Int(2)));
+        let merged = object(source, "merged");
+        assert_eq!(merged.properties.keys().collect::<Vec<_>>(), ["name", "age"]);
+        assert_eq!(merged.entry(&Value::String("k".into())), Some(&Value::Int(1)));
+        assert_eq!(merged.elements, [Value::String("e".into())]);
+
+        let error = |source: &str| evaluate(source).unwrap_err().to_string();
+        assert_eq!(error("l = new Listing { ...null }"), "can't spread a value of type `Null`");
+        assert_eq!(error("l = new Listing { ...Map(1, 2) }"), "an object of type `Listing` can't have entries");
+        assert_eq!(error("m = new Mapping { [1] = 1; ...Map(1, 2) }"), "duplicate definition of an entry");
+        assert_eq!(error("d { a = 1 }\no { a = 2; ...d }"), "duplicate definition of `a`");
+    }
+
+    #[test]
+    fn generators() {
+        let source = "names = List(\"pigeon\", \"kiwi\")\n\
+                      birds = new Mapping {\n\
+                        for (i, name in names) { [name] { index = i; upper = name.toUpperCase() } }\n\
+                      }\n\
+                      l = new Listing { for (n in List(1, 2)) { for (m in Set(10, 20)) { n * m } } }\n\
+                      flag = true\n\
+                      d { when (flag) { a = 1 } else { b = 2 }; when (!flag) { c = 3 }\n\
+                        for (k, v in Map(\"x\", 1)) { [k] = v } }";
+        let birds = object(source, "birds");
+        let Some(Value::Object(kiwi)) = birds.entry(&Value::String("kiwi".into())) else { panic!() };
+        assert_eq!(kiwi.property("index"), Some(&Value::Int(1)));
+        assert_eq!(kiwi.property("upper"), Some(&Value::String("KIWI".into())));
+        let listing = object(source, "l");
+        assert_eq!(listing.elements, [10, 20, 20, 40].map(Value::Int));
+        let d = object(source, "d");
+        assert_eq!(d.properties.keys().collect::<Vec<_>>(), ["a"]);
+        assert_eq!(d.entry(&Value::String("x".into())), Some(&Value::Int(1)));
+
+        let error = |source: &str| evaluate(source).unwrap_err().to_string();
+        assert_eq!(error("d { for (x in 1) { x } }"), "can't iterate over a value of type `Int`");
+        assert_eq!(error("d { for (x in List(1, 2)) { a = x } }"), "duplicate definition of `a`");
+        assert_eq!(error("d { when (1) { a = 1 } }"), "expected a `Boolean` condition, but got `Int`");
+        assert_eq!(error("d { for (x: String in List(1)) { x } }"), "expected a value of type `String`, but got `Int`");
+    }
+
+    #[test]
+    fn member_predicates() {
+        let source = "birds = new Listing {\n\
+                        new Dynamic { name = \"pigeon\"; age = 1 }; new Dynamic { name = \"kiwi\"; age = 3 }\n\
+                      }\n\
+                      older = (birds) { [[age > 2]] { old = true } }\n\
+                      m = (new Mapping { [\"a\"] = 1; [\"b\"] = 2 }) { [[this == 2]] = 20; [\"b\"] = 30 }";
+        let older = object(source, "older");
+        let [Value::Object(pigeon), Value::Object(kiwi)] = older.elements.as_slice() else { panic!() };
+        assert_eq!((pigeon.property("old"), kiwi.property("old")), (None, Some(&Value::Boolean(true))));
+        assert_eq!(kiwi.property("name"), Some(&Value::String("kiwi".into())));
+        let mapping = object(source, "m");
+        assert_eq!(mapping.entry(&Value::String("a".into())), Some(&Value::Int(1)));
+        assert_eq!(mapping.entry(&Value::String("b".into())), Some(&Value::Int(30)));
+
+        let error = evaluate("l = (new Listing { 1 }) { [[this]] = 2 }").unwrap_err();
+        assert_eq!(error.to_string(), "expected a `Boolean` predicate, but got `Int`");
+    }
+
+    #[test]
+    fn typed_listings_and_mappings() {
+        let source = "class Bird {\n  name: String\n  legs = 2\n}\n\
+                      birds: Listing<Bird> = new { new { name = \"pigeon\" }; new { name = \"kiwi\"; legs = 1 } }\n\
+                      byName: Mapping<String, Bird> = new { [\"robin\"] { name = \"robin\" } }\n\
+                      more = new Listing<Bird> { default { legs = 3 }; new { name = \"tern\" } }\n\
+                      bird: Bird? = new { name = \"owl\" }";
+        let birds = object(source, "birds");
+        assert_eq!(birds.kind, ObjectKind::Listing);
+        let [Value::Object(pigeon), Value::Object(kiwi)] = birds.elements.as_slice() else { panic!() };
+        assert_eq!(pigeon.kind, ObjectKind::Typed("Bird".into()));
+        assert_eq!(pigeon.property("legs"), Some(&Value::Int(2)));
+        assert_eq!(kiwi.property("legs"), Some(&Value::Int(1)));
+
+        let Some(Value::Object(robin)) = object(source, "byName").entry(&Value::String("robin".into())).cloned() else {
+            panic!()
+        };
+        assert_eq!(robin.kind, ObjectKind::Typed("Bird".into()));
+        let more = object(source, "more");
+        let [Value::Object(tern)] = more.elements.as_slice() else { panic!() };
+        assert_eq!(tern.property("legs"), Some(&Value::Int(3)));
+        assert_eq!(object(source, "bird").kind, ObjectKind::Typed("Bird".into()));
+
+        let error = evaluate("class Bird { name: String }\nbirds: Listing<Bird> = new { new { name = 1 } }");
+        assert_eq!(error.unwrap_err().to_string(), "expected a value of type `String`, but got `Int`");
+    }
+}
@@ -0,0 +1,268 @@
+//! What an [`Evaluator`](crate::Evaluator) is allowed to do, and where it keeps what it downloads.
+//!
+//! The defaults suit evaluating trusted modules. To evaluate untrusted ones, narrow the allowed modules and resources,
+//! give the environment variables explicitly, confine files to a root directory, and set a timeout. The `evaluate`
+//! fuzz target of `fuzz/` evaluates arbitrary input like that, with `cargo fuzz run evaluate`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use pkl_ast::Span;
+use pkl_diagnostics::Code;
+use pkl_stdlib::regex::Regex;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::modules::normalize;
+
+/// How many expressions are evaluated between checks of the timeout.
+const TIMEOUT_CHECK_INTERVAL: u32 = 1024;
+
+/// How deeply calls and members can nest by default, which a release build evaluates well within the 8 MiB stack of a
+/// main thread. Debug builds take many times the stack for each level.
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Configures an [`Evaluator`](crate::Evaluator).
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::time::Duration;
+///
+/// use pkl_eval::EvaluatorOptions;
+///
+/// let options = EvaluatorOptions {
+///     allowed_modules: vec!["pkl:".to_string(), "repl:".to_string()],
+///     allowed_resources: vec!["env:".to_string()],
+///     environment_variables: Some(HashMap::from([("BIRD".to_string(), "pigeon".to_string())])),
+///     timeout: Some(Duration::from_secs(5)),
+///     ..EvaluatorOptions::default()
+/// };
+/// let module = pkl_eval::evaluate_with("bird = read(\"env:BIRD\")", "repl:text", options).unwrap();
+///
+/// assert_eq!(module.as_object().unwrap().property("bird"), Some(&pkl_eval::value::Value::String("pigeon".into())));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluatorOptions {
+    /// Regular expressions, which the URI of every imported module has to start with a match of, like `pkl:` or
+    /// `https://example\.com/`. By default, modules of the standard library, files, packages, and `https:` URLs
+    /// can be imported.
+    pub allowed_modules: Vec<String>,
+    /// Regular expressions, which the URI of every read resource has to start with a match of. By default,
+    /// environment variables, external properties, files, and `https:` URLs can be read, and the schemes of added
+    /// resource readers.
+    pub allowed_resources: Vec<String>,
+    /// Where downloaded packages are kept, `~/.pkl/cache` unless there's no home directory; without one, packages
+    /// are downloaded for every evaluation
+    pub cache_dir: Option<PathBuf>,
+    /// The properties read by `read("prop:name")`
+    pub external_properties: HashMap<String, String>,
+    /// The variables read by `read("env:NAME")`, or `None` for the environment of the process
+    pub environment_variables: Option<HashMap<String, String>>,
+    /// The directory that imported and read files have to be in, if any
+    pub root_dir: Option<PathBuf>,
+    /// How long an evaluation can take before it fails, if there's a limit, except on `wasm32-unknown-unknown`
+    pub timeout: Option<Duration>,
+    /// How deeply method and function calls and the members they read can nest before the evaluation fails, which
+    /// keeps runaway recursion from overflowing the stack
+    pub max_depth: usize,
+    /// The dependencies that imports like `@birds/pigeon.pkl` name, as `package:` URIs with their checksums or the
+    /// `file:` URIs of project directories; see [`Project::configure`](crate::project::Project::configure)
+    pub dependencies: HashMap<String, String>,
+    /// How many threads read the files and URLs that a module imports before it's evaluated, by default one for each
+    /// processor; with one, each module is read when it's first imported
+    pub threads: usize,
+    /// What's done when a property or method annotated with `@Deprecated` is used
+    pub deprecations: Deprecations,
+}
+
+/// What's done when an evaluated module uses a member annotated with `@Deprecated`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Deprecations {
+    /// Nothing
+    Ignore,
+    /// A warning like ``pkl: WARN: `create` is deprecated: use `make` (repl:text, line 3)``, with the code
+    /// [`DeprecatedWarning`](pkl_diagnostics::Code::DeprecatedWarning), is logged once for each place that uses the
+    /// member, to the logger of `trace`; see [`Evaluator::set_logger`](crate::Evaluator::set_logger)
+    #[default]
+    Warn,
+    /// The evaluation fails
+    Error,
+}
+
+impl Default for EvaluatorOptions {
+    fn default() -> Self {
+        EvaluatorOptions {
+            allowed_modules: ["pkl:", "repl:", "file:", "package:", "https:"].map(String::from).to_vec(),
+            allowed_resources: ["env:", "prop:", "file:", "https:"].map(String::from).to_vec(),
+            cache_dir: std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pkl").join("cache")),
+            external_properties: HashMap::new(),
+            environment_variables: None,
+            root_dir: None,
+            timeout: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            dependencies: HashMap::new(),
+            threads: std::thread::available_parallelism().map_or(1, usize::from),
+            deprecations: Deprecations::default(),
+        }
+    }
+}
+
+/// What's done with a URI, for checking that it's allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Import,
+    Read,
+}
+
+impl EvaluatorOptions {
+    /// Fails unless the options allow importing or reading the resolved `uri`.
+    pub(crate) fn check_access(&self, span: Span, access: Access, uri: &str) -> Result<()> {
+        let (verb, what, patterns) = match access {
+            Access::Import => ("import", "module", &self.allowed_modules),
+            Access::Read => ("read", "resource", &self.allowed_resources),
+        };
+        let refuse = |reason: String| {
+            EvalError::new(span, format!("refusing to {verb} `{uri}`: {reason}")).with_code(Code::AccessDenied)
+        };
+
+        let mut allowed = false;
+        for pattern in patterns {
+            let regex = Regex::new(&format!("^(?:{pattern})"))
+                .map_err(|error| EvalError::new(span, format!("invalid allowed {what} pattern: {error}")))?;
+            if regex.is_match(uri) {
+                allowed = true;
+                break;
+            }
+        }
+        if !allowed {
+            return Err(refuse(format!("it doesn't match any allowed {what} pattern")));
+        }
+
+        if let (Some(root), Some(path)) = (&self.root_dir, uri.strip_prefix("file://")) {
+            // links are followed, so they can't lead out of the root directory
+            let path = Path::new(path).canonicalize().unwrap_or_else(|_| normalize(Path::new(path)));
+            let root = root.canonicalize().unwrap_or_else(|_| normalize(root));
+            if !path.starts_with(&root) {
+                return Err(refuse(format!("it's outside of the root directory `{}`", root.display())));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Evaluator<'_> {
+    /// Fails unless the options allow importing or reading the resolved `uri`.
+    pub(crate) fn check_access(&self, span: Span, access: Access, uri: &str) -> Result<()> {
+        self.options.check_access(span, access, uri)
+    }
+
+    /// Starts the clock of the timeout, if there is one, for an evaluation starting now. There's no clock on
+    /// `wasm32-unknown-unknown`, so timeouts aren't enforced there.
+    pub(crate) fn start_clock(&self) {
+        if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+            return;
+        }
+        self.deadline.set(self.options.timeout.map(|timeout| Instant::now() + timeout));
+    }
+
+    /// Evaluates a call or member at `span` one level deeper than what's being evaluated, unless that's deeper than
+    /// the limit.
+    pub(crate) fn nested<T>(&self, span: Span, evaluate: impl FnOnce() -> Result<T>) -> Result<T> {
+        let depth = self.depth.get();
+        if depth >= self.options.max_depth {
+            let message = format!("calls and members are nested more than {} levels deep", self.options.max_depth);
+            return Err(EvalError::new(span, message).with_code(Code::RecursionLimit));
+        }
+        self.depth.set(depth + 1);
+        let result = evaluate();
+        self.depth.set(depth);
+        result
+    }
+
+    /// Fails once the evaluation has taken longer than the timeout. It's called for every evaluated expression, but
+    /// only looks at the clock now and then.
+    pub(crate) fn check_timeout(&self, span: Span) -> Result<()> {
+        let Some(deadline) = self.deadline.get() else { return Ok(()) };
+        let ticks = self.ticks.get().wrapping_add(1);
+        self.ticks.set(ticks);
+        if ticks.is_multiple_of(TIMEOUT_CHECK_INTERVAL) && Instant::now() >= deadline {
+            let timeout = self.options.timeout.unwrap_or_default();
+            let error = EvalError::new(span, format!("evaluation timed out after {timeout:?}"));
+            return Err(error.with_code(Code::Timeout));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::time::Duration;
+
+    use pkl_diagnostics::Code;
+
+    use crate::value::Value;
+    use crate::{evaluate_with, Error, EvaluatorOptions};
+
+    #[test]
+    fn allowed_resources_and_environment() {
+        let options = EvaluatorOptions {
+            allowed_resources: vec!["env:BIRD$".to_string()],
+            environment_variables: Some(HashMap::from([("BIRD".to_string(), "pigeon".to_string())])),
+            ..EvaluatorOptions::default()
+        };
+        let module = evaluate_with("bird = read(\"env:BIRD\")", "repl:text", options.clone()).unwrap();
+        assert_eq!(module.as_object().unwrap().property("bird"), Some(&Value::String("pigeon".into())));
+
+        let error = |source| evaluate_with(source, "repl:text", options.clone()).unwrap_err().to_string();
+        let message = "refusing to read `env:HOME`: it doesn't match any allowed resource pattern";
+        assert_eq!(error("x = read(\"env:HOME\")"), message);
+        let message = "refusing to read `file:///etc/hosts`: it doesn't match any allowed resource pattern";
+        assert_eq!(error("x = read?(\"file:///etc/hosts\")"), message);
+    }
+
+    #[test]
+    fn root_dir() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-root-{}", std::process::id()));
+        fs::create_dir_all(dir.join("root")).unwrap();
+        fs::write(dir.join("root/birds.pkl"), "name = \"pigeon\"").unwrap();
+        fs::write(dir.join("secret.txt"), "hunter2").unwrap();
+        let options = EvaluatorOptions { root_dir: Some(dir.join("root")), ..EvaluatorOptions::default() };
+        let uri = format!("file://{}/root/main.pkl", dir.display());
+
+        let module = evaluate_with("import \"birds.pkl\"\nname = birds.name", &uri, options.clone()).unwrap();
+        assert_eq!(module.as_object().unwrap().property("name"), Some(&Value::String("pigeon".into())));
+        let error = evaluate_with("x = read(\"../secret.txt\")", &uri, options).unwrap_err();
+        let root = dir.join("root").canonicalize().unwrap();
+        let message = format!(
+            "refusing to read `file://{}/secret.txt`: it's outside of the root directory `{}`",
+            dir.display(),
+            root.display()
+        );
+        assert_eq!(error.to_string(), message);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn timeout() {
+        let source = "x = \"a\".repeat(5000).chars.fold(0, (n, c) -> n + 1)";
+        let options = EvaluatorOptions { timeout: Some(Duration::ZERO), ..EvaluatorOptions::default() };
+        let error = evaluate_with(source, "repl:text", options).unwrap_err();
+        assert_eq!(error.to_string(), "evaluation timed out after 0ns");
+
+        let options = EvaluatorOptions { timeout: Some(Duration::from_secs(60)), ..EvaluatorOptions::default() };
+        assert!(evaluate_with(source, "repl:text", options).is_ok());
+    }
+
+    #[test]
+    fn max_depth() {
+        let options = EvaluatorOptions { max_depth: 20, ..EvaluatorOptions::default() };
+        let evaluate = |source| evaluate_with(source, "repl:text", options.clone());
+        let Err(Error::Eval(error)) = evaluate("function f(n) = f(n + 1)\nx = f(0)") else { panic!() };
+        assert_eq!(error.message, "calls and members are nested more than 20 levels deep");
+        assert_eq!(error.code, Some(Code::RecursionLimit));
+        assert!(evaluate("function f(n) = if (n == 0) 0 else f(n - 1)\nx = f(15)").is_ok());
+    }
+}
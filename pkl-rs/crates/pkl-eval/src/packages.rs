@@ -0,0 +1,458 @@
+//! `package:` imports, like `import "package://pkg.pkl-lang.org/pkl-pantry/pkl.toml@1.0.0#/toml.pkl"`, which read
+//! a module from a published package.
+//!
+//! A package URI names a package by its host, path, and version, and after the `#` the path of a module in it. Its
+//! metadata is a JSON document served at the `https:` URL with the same host and path, which says where the zip
+//! archive of the package's modules is and what its SHA-256 checksum is. A URI can also pin the checksum of the
+//! metadata itself, as in `package://example.com/birds@1.0.0::sha256:<checksum>#/birds.pkl`.
+//!
+//! Metadata and archives are kept in the cache directory, laid out like the official tools lay it out, so a package
+//! is only downloaded once: `package-2/example.com/birds@1.0.0/birds@1.0.0.json` and `.zip`. Checksums are verified
+//! whether a package is downloaded or cached. An archive can have at most 10,000 entries, decompressing to at most
+//! 256 MiB, so a small archive can't fill memory.
+//!
+//! Within a package, relative imports resolve to the package's other modules, and imports like `@colors/red.pkl`
+//! to the modules of the dependency the metadata calls `colors`.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use pkl_ast::Span;
+use pkl_stdlib::data::{self, Data};
+use sha2::{Digest, Sha256};
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::modules::normalize;
+use crate::options::Access;
+
+/// The largest metadata document or archive that is downloaded.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_DOWNLOAD: u64 = 64 * 1024 * 1024;
+
+/// The most files an archive can have.
+const MAX_FILES: usize = 10_000;
+
+/// The largest the files of an archive can be altogether, once decompressed.
+const MAX_UNZIPPED: u64 = 256 * 1024 * 1024;
+
+/// A fetched package: what's needed to resolve imports in it and read its modules.
+pub(crate) struct Package {
+    /// The URI of the package, without a checksum or module path
+    pub(crate) uri: String,
+    /// The SHA-256 checksum of its metadata, in hex
+    pub(crate) checksum: String,
+    /// The URIs of its dependencies by name, with their checksums
+    pub(crate) dependencies: Vec<(String, String)>,
+    /// The files of its archive by path, like `/lib/birds.pkl`
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Resolves an import in a package module at `base`, or of a `package:` URI, to the URI of the module without a
+    /// checksum, fetching its package once the URI is known to be allowed.
+    pub(crate) fn resolve_package(&self, span: Span, uri: &str, base: &str) -> Result<String> {
+        let absolute = if uri.starts_with("package:") {
+            uri.to_string()
+        } else {
+            let Some((package, path)) = base.split_once('#') else {
+                return Err(EvalError::new(span, format!("invalid package module URI `{base}`: it has no `#` path")));
+            };
+            match uri.strip_prefix('@') {
+                Some(dependency) => {
+                    let (name, path) = dependency.split_once('/').unwrap_or((dependency, ""));
+                    let package = self.package(span, &PackageUri::parse(span, package)?)?;
+                    let Some((_, dependency)) = package.dependencies.iter().find(|(dependency, _)| dependency == name)
+                    else {
+                        let message = format!("package `{}` has no dependency named `{name}`", package.uri);
+                        return Err(EvalError::new(span, message));
+                    };
+                    format!("{dependency}#/{path}")
+                }
+                None => {
+                    let directory = Path::new(path).parent().unwrap_or(Path::new("/"));
+                    format!("{package}#{}", normalize(&directory.join(uri)).display())
+                }
+            }
+        };
+
+        // checked before anything is downloaded or cached, not only once the import is resolved
+        self.check_access(span, Access::Import, &absolute)?;
+        let uri = PackageUri::parse(span, &absolute)?;
+        let package = self.package(span, &uri)?;
+        if !package.files.iter().any(|(path, _)| *path == uri.module) {
+            let message = format!("can't find module `{}` in package `{}`", uri.module, package.uri);
+            return Err(EvalError::new(span, message));
+        }
+        Ok(format!("{}#{}", package.uri, uri.module))
+    }
+
+    /// The source of the module of a package at `key`, as resolved by `resolve_package`.
+    pub(crate) fn package_source(&self, span: Span, uri: &str, key: &str) -> Result<String> {
+        let cant_find = || EvalError::new(span, format!("can't find module `{uri}`"));
+        let (package, path) = key.split_once('#').ok_or_else(cant_find)?;
+        let package = self.package(span, &PackageUri::parse(span, package)?)?;
+        let (_, source) = package.files.iter().find(|(file, _)| file == path).ok_or_else(cant_find)?;
+        String::from_utf8(source.clone())
+            .map_err(|_| EvalError::new(span, format!("can't read module `{uri}`: it isn't valid UTF-8")))
+    }
+
+    /// Fetches a package, or returns it if it's already been fetched and has the checksum `uri` pins, if any.
+    fn package(&self, span: Span, uri: &PackageUri) -> Result<Rc<Package>> {
+        let id = uri.package();
+        let cant_fetch = |message| EvalError::new(span, format!("can't fetch package `{id}`: {message}"));
+        if let Some((_, package)) = self.packages.borrow().iter().find(|(fetched, _)| *fetched == id) {
+            if let Some(expected) = &uri.checksum {
+                verify("metadata", &package.checksum, expected).map_err(cant_fetch)?;
+            }
+            return Ok(package.clone());
+        }
+        let package = fetch(uri, self.options.cache_dir.as_deref()).map_err(cant_fetch)?;
+        let package = Rc::new(package);
+        self.packages.borrow_mut().push((id, package.clone()));
+        Ok(package)
+    }
+}
+
+/// The parts of a URI like `package://example.com/birds@1.0.0::sha256:<checksum>#/birds.pkl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PackageUri {
+    /// The host and path up to the version, like `example.com/birds`
+    pub(crate) path: String,
+    pub(crate) version: String,
+    /// The SHA-256 checksum of the metadata, in hex
+    checksum: Option<String>,
+    /// The path of the module in the package, like `/birds.pkl`; empty for the package itself
+    module: String,
+}
+
+impl PackageUri {
+    pub(crate) fn parse(span: Span, uri: &str) -> Result<PackageUri> {
+        let invalid = |reason: &str| EvalError::new(span, format!("invalid package URI `{uri}`: {reason}"));
+        let rest = uri.strip_prefix("package://").ok_or_else(|| invalid("it doesn't start with `package://`"))?;
+        let (rest, module) = match rest.split_once('#') {
+            Some((rest, module)) if module.starts_with('/') => (rest, module.to_string()),
+            Some(_) => return Err(invalid("the module path after `#` has to start with `/`")),
+            None => (rest, String::new()),
+        };
+        let (rest, checksum) = match rest.split_once("::") {
+            Some((rest, checksum)) => {
+                let checksum =
+                    checksum.strip_prefix("sha256:").ok_or_else(|| invalid("only `sha256:` checksums are supported"))?;
+                (rest, Some(checksum.to_ascii_lowercase()))
+            }
+            None => (rest, None),
+        };
+        let (path, version) = rest.rsplit_once('@').ok_or_else(|| invalid("it has no `@` version"))?;
+        if !path.contains('/') || path.ends_with('/') {
+            return Err(invalid("it has no path after the host"));
+        }
+        // the path and version name the package's directory in the cache, which they mustn't escape
+        if path.split('/').any(|segment| matches!(segment, "" | "." | "..") || segment.contains('\\')) {
+            return Err(invalid("its path has an empty, `.`, or `..` segment, or a `\\`"));
+        }
+        if version.is_empty() || version.contains(['/', '\\']) {
+            return Err(invalid("its version is empty or has a `/` or `\\`"));
+        }
+        Ok(PackageUri { path: path.to_string(), version: version.to_string(), checksum, module })
+    }
+
+    /// The URI of the package, without a checksum or module path.
+    pub(crate) fn package(&self) -> String {
+        format!("package://{}@{}", self.path, self.version)
+    }
+
+    /// The last segment of the path, which the cached files are named after.
+    fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    fn cache_dir(&self, cache: &Path) -> PathBuf {
+        let mut dir = cache.join("package-2");
+        for segment in self.path.split('/') {
+            dir.push(segment);
+        }
+        dir.set_file_name(format!("{}@{}", self.name(), self.version));
+        dir
+    }
+}
+
+/// Reads a package from the cache, or downloads it into the cache, and verifies its checksums.
+pub(crate) fn fetch(uri: &PackageUri, cache: Option<&Path>) -> std::result::Result<Package, String> {
+    let stem = format!("{}@{}", uri.name(), uri.version);
+    let dir = cache.map(|cache| uri.cache_dir(cache));
+    let metadata_path = dir.as_ref().map(|dir| dir.join(format!("{stem}.json")));
+    let archive_path = dir.as_ref().map(|dir| dir.join(format!("{stem}.zip")));
+
+    let metadata_url = format!("https://{}@{}", uri.path, uri.version);
+    let (metadata, downloaded) = cached_or_download(metadata_path.as_deref(), &metadata_url)?;
+    let checksum = sha256(&metadata);
+    if let Some(expected) = &uri.checksum {
+        verify("metadata", &checksum, expected)?;
+    }
+    let text = std::str::from_utf8(&metadata).map_err(|_| "its metadata isn't valid UTF-8".to_string())?;
+    let metadata = data::json(text).map_err(|error| format!("invalid metadata: {error}"))?;
+    let zip_url = string(&metadata, &["packageZipUrl"])?;
+    let zip_checksum = string(&metadata, &["packageZipChecksums", "sha256"])?;
+
+    let (archive, archive_downloaded) = cached_or_download(archive_path.as_deref(), zip_url)?;
+    verify("archive", &sha256(&archive), zip_checksum)?;
+    // only verified files are kept
+    if let (Some(dir), Some(metadata_path), Some(archive_path)) = (&dir, &metadata_path, &archive_path) {
+        let store = || -> std::io::Result<()> {
+            std::fs::create_dir_all(dir)?;
+            if downloaded {
+                std::fs::write(metadata_path, text)?;
+            }
+            if archive_downloaded {
+                std::fs::write(archive_path, &archive)?;
+            }
+            Ok(())
+        };
+        store().map_err(|error| format!("can't write to the cache: {error}"))?;
+    }
+
+    let mut dependencies = Vec::new();
+    if let Some(Data::Map(entries)) = member(&metadata, &["dependencies"]) {
+        for (name, dependency) in entries {
+            let Data::String(name) = name else { continue };
+            let uri = string(dependency, &["uri"])?;
+            let dependency = match member(dependency, &["checksums", "sha256"]) {
+                Some(Data::String(checksum)) => format!("{uri}::sha256:{checksum}"),
+                _ => uri.to_string(),
+            };
+            dependencies.push((name.clone(), dependency));
+        }
+    }
+    Ok(Package { uri: uri.package(), checksum, dependencies, files: unzip(&archive, MAX_FILES, MAX_UNZIPPED)? })
+}
+
+/// The contents of a cached file, or else of a download, and whether it was downloaded.
+fn cached_or_download(path: Option<&Path>, url: &str) -> std::result::Result<(Vec<u8>, bool), String> {
+    if let Some(contents) = path.and_then(|path| std::fs::read(path).ok()) {
+        return Ok((contents, false));
+    }
+    Ok((download(url)?, true))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn download(url: &str) -> std::result::Result<Vec<u8>, String> {
+    let mut response = ureq::get(url).call().map_err(|error| format!("can't download `{url}`: {error}"))?;
+    response
+        .body_mut()
+        .with_config()
+        .limit(MAX_DOWNLOAD)
+        .read_to_vec()
+        .map_err(|error| format!("can't download `{url}`: {error}"))
+}
+
+/// There's no network access on WebAssembly; an embedder that has some can add a resource reader for `https:`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn download(url: &str) -> std::result::Result<Vec<u8>, String> {
+    Err(format!("can't download `{url}`: downloads aren't supported on WebAssembly"))
+}
+
+fn sha256(contents: &[u8]) -> String {
+    Sha256::digest(contents).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn verify(what: &str, actual: &str, expected: &str) -> std::result::Result<(), String> {
+    if actual != expected.to_ascii_lowercase() {
+        return Err(format!("the checksum of its {what} is {actual}, but {expected} was expected"));
+    }
+    Ok(())
+}
+
+/// The files of a zip archive, by their path from its root. An archive of more than `max_files` entries, or whose
+/// files decompress to more than `max_size` bytes altogether, is an error, whatever sizes its entries claim.
+fn unzip(archive: &[u8], max_files: usize, max_size: u64) -> std::result::Result<Vec<(String, Vec<u8>)>, String> {
+    let invalid = |error: zip::result::ZipError| format!("invalid archive: {error}");
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive)).map_err(invalid)?;
+    if archive.len() > max_files {
+        return Err(format!("the archive has {} entries, more than the limit of {max_files}", archive.len()));
+    }
+    let mut files = Vec::new();
+    let mut remaining = max_size;
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(invalid)?;
+        if file.is_dir() {
+            continue;
+        }
+        // paths that would escape the archive are ignored, like unpacking it would
+        let Some(path) = file.enclosed_name() else { continue };
+        let mut contents = Vec::new();
+        // reading one byte past what's left is how a file that's too large is noticed
+        let read = file.by_ref().take(remaining + 1).read_to_end(&mut contents);
+        read.map_err(|error| format!("invalid archive: {error}"))?;
+        remaining = remaining
+            .checked_sub(contents.len() as u64)
+            .ok_or_else(|| format!("the files of the archive are larger than the limit of {max_size} bytes"))?;
+        let path = path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>();
+        files.push((format!("/{}", path.join("/")), contents));
+    }
+    Ok(files)
+}
+
+fn member<'d>(data: &'d Data, path: &[&str]) -> Option<&'d Data> {
+    path.iter().try_fold(data, |data, name| match data {
+        Data::Map(entries) => entries.iter().find(|(key, _)| *key == Data::String(name.to_string())).map(|(_, v)| v),
+        _ => None,
+    })
+}
+
+fn string<'d>(data: &'d Data, path: &[&str]) -> std::result::Result<&'d str, String> {
+    match member(data, path) {
+        Some(Data::String(s)) => Ok(s),
+        _ => Err(format!("its metadata has no string `{}`", path.join("."))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::io::Write;
+
+    use oxc_allocator::Allocator;
+    use pkl_ast::Span;
+    use sha2::{Digest, Sha256};
+    use zip::write::SimpleFileOptions;
+
+    use super::PackageUri;
+    use crate::evaluator::Evaluator;
+    use crate::value::Value;
+    use crate::{Error, EvaluatorOptions};
+
+    fn checksum(contents: &[u8]) -> String {
+        Sha256::digest(contents).iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Puts a package in the cache, returning the checksum of its metadata.
+    fn publish(cache: &std::path::Path, path: &str, files: &[(&str, &str)], dependencies: &str) -> String {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, source) in files {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(source.as_bytes()).unwrap();
+        }
+        let archive = writer.finish().unwrap().into_inner();
+        let metadata = format!(
+            r#"{{"packageZipUrl": "https://example.com/archive.zip", "packageZipChecksums": {{"sha256": "{}"}},
+               "dependencies": {{{dependencies}}}}}"#,
+            checksum(&archive)
+        );
+        let uri = PackageUri::parse(Span::new(0, 0), &format!("package://{path}")).unwrap();
+        let dir = uri.cache_dir(cache);
+        fs::create_dir_all(&dir).unwrap();
+        let stem = format!("{}@{}", uri.name(), uri.version);
+        fs::write(dir.join(format!("{stem}.json")), &metadata).unwrap();
+        fs::write(dir.join(format!("{stem}.zip")), archive).unwrap();
+        checksum(metadata.as_bytes())
+    }
+
+    fn evaluate(source: &str, cache: &std::path::Path) -> Result<Value, Error> {
+        let alloc = Allocator::default();
+        let module = alloc.alloc(pkl_parser::parse_module(&alloc, source).node);
+        let options = EvaluatorOptions { cache_dir: Some(cache.to_path_buf()), ..EvaluatorOptions::default() };
+        let mut evaluator = Evaluator::with_options(&alloc, options);
+        Ok(evaluator.evaluate_module(module, "repl:text")?)
+    }
+
+    #[test]
+    fn archive_limits() {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for name in ["a.pkl", "b.pkl", "c.pkl"] {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(&[b' '; 1000]).unwrap();
+        }
+        let archive = writer.finish().unwrap().into_inner();
+        assert_eq!(super::unzip(&archive, 3, 3000).unwrap().len(), 3);
+        let error = super::unzip(&archive, 2, 3000).unwrap_err();
+        assert_eq!(error, "the archive has 3 entries, more than the limit of 2");
+        let error = super::unzip(&archive, 3, 2999).unwrap_err();
+        assert_eq!(error, "the files of the archive are larger than the limit of 2999 bytes");
+    }
+
+    #[test]
+    fn uris() {
+        let uri = PackageUri::parse(Span::new(0, 0), "package://example.com/a/birds@1.0.0::sha256:AB#/x.pkl").unwrap();
+        assert_eq!(uri.package(), "package://example.com/a/birds@1.0.0");
+        assert_eq!(uri.checksum.as_deref(), Some("ab"));
+        assert_eq!(uri.module, "/x.pkl");
+        let dir = uri.cache_dir(std::path::Path::new("/cache"));
+        assert_eq!(dir, std::path::Path::new("/cache/package-2/example.com/a/birds@1.0.0"));
+
+        let error = |uri| PackageUri::parse(Span::new(0, 0), uri).unwrap_err().to_string();
+        let message = "invalid package URI `package://example.com/birds#/x.pkl`: it has no `@` version";
+        assert_eq!(error("package://example.com/birds#/x.pkl"), message);
+        for uri in ["package://example.com/../../birds@1.0.0", "package://example.com//birds@1.0.0"] {
+            let reason = "its path has an empty, `.`, or `..` segment, or a `\\`";
+            assert_eq!(error(uri), format!("invalid package URI `{uri}`: {reason}"));
+        }
+        for uri in ["package://example.com/birds@1.0.0/../..", "package://example.com/birds@..\\..\\x"] {
+            assert_eq!(error(uri), format!("invalid package URI `{uri}`: its version is empty or has a `/` or `\\`"));
+        }
+    }
+
+    #[test]
+    fn imports() {
+        let cache = std::env::temp_dir().join(format!("pkl-eval-packages-{}", std::process::id()));
+        let colors = publish(&cache, "example.com/colors@2.0.0", &[("red.pkl", "hex = \"#ff0000\"")], "");
+        let dependencies = format!(
+            r#""colors": {{"uri": "package://example.com/colors@2.0.0", "checksums": {{"sha256": "{colors}"}}}}"#
+        );
+        let pigeon = "import \"@colors/red.pkl\"\nimport \"../common.pkl\"\nname = common.kind\ncolor = red.hex";
+        let files = [
+            ("common.pkl", "kind = \"bird\""),
+            ("birds.pkl", "import \"lib/pigeon.pkl\"\nname = pigeon.name"),
+            ("lib/pigeon.pkl", pigeon),
+        ];
+        let birds = publish(&cache, "example.com/birds@1.0.0", &files, &dependencies);
+
+        let source = format!(
+            r#"
+            import "package://example.com/birds@1.0.0::sha256:{birds}#/birds.pkl"
+            import "package://example.com/birds@1.0.0#/lib/pigeon.pkl"
+            name = birds.name
+            color = pigeon.color
+            "#
+        );
+        let module = evaluate(&source, &cache).unwrap();
+        let module = module.as_object().unwrap();
+        assert_eq!(module.property("name"), Some(&Value::String("bird".into())));
+        assert_eq!(module.property("color"), Some(&Value::String("#ff0000".into())));
+
+        let error = |source: &str| evaluate(source, &cache).unwrap_err().to_string();
+        let message = "can't find module `/owl.pkl` in package `package://example.com/birds@1.0.0`";
+        assert_eq!(error("import \"package://example.com/birds@1.0.0#/owl.pkl\""), message);
+        let message = format!(
+            "can't fetch package `package://example.com/birds@1.0.0`: the checksum of its metadata is {birds}, but 00 \
+             was expected"
+        );
+        assert_eq!(error("import \"package://example.com/birds@1.0.0::sha256:00#/birds.pkl\""), message);
+        // a package that's already been fetched is checked against the checksum of each import too
+        let source = format!(
+            "import \"package://example.com/birds@1.0.0::sha256:{birds}#/birds.pkl\"\n\
+             import \"package://example.com/birds@1.0.0::sha256:00#/common.pkl\""
+        );
+        assert_eq!(error(&source), message);
+        fs::remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn refused_packages_arent_fetched() {
+        let cache = std::env::temp_dir().join(format!("pkl-eval-refused-{}", std::process::id()));
+        let allowed_modules = vec!["repl:".to_string(), "file:".to_string()];
+        let options = EvaluatorOptions { cache_dir: Some(cache.clone()), allowed_modules, ..Default::default() };
+        let source = "import \"package://nonexistent.invalid/birds@1.0.0#/birds.pkl\"\nname = birds.name";
+        let error = crate::evaluate_with(source, "repl:text", options.clone()).unwrap_err().to_string();
+        let message = "refusing to import `package://nonexistent.invalid/birds@1.0.0#/birds.pkl`: it doesn't match \
+                       any allowed module pattern";
+        assert_eq!(error, message);
+
+        let dependencies = [("birds".to_string(), "package://nonexistent.invalid/birds@1.0.0".to_string())];
+        let options = EvaluatorOptions { dependencies: dependencies.into_iter().collect(), ..options };
+        let error = crate::evaluate_with("import \"@birds/birds.pkl\"", "repl:text", options).unwrap_err();
+        assert!(error.to_string().starts_with("refusing to import `package://nonexistent.invalid/birds@1.0.0#/"));
+        assert!(!cache.exists());
+    }
+}
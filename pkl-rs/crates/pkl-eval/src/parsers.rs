@@ -0,0 +1,147 @@
+//! The `Parser` classes of `pkl:json`, `pkl:yaml`, and `pkl:toml`, which read documents into Pkl values, as in
+//! `new json.Parser {}.parse(text)`. Reading itself is implemented by [`pkl_stdlib::data`].
+//!
+//! Arrays become `Listing`s, and maps become `Dynamic`s with a property per string key and an entry per other key,
+//! or `Mapping`s if the parser's `useMapping` is `true`.
+
+use std::rc::Rc;
+
+use pkl_ast::Span;
+use pkl_stdlib::data::{self, Data};
+
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::methods::{string_val, Call};
+use crate::runtime::{Def, Key, Member, Obj, Val};
+use crate::value::ObjectKind;
+
+impl<'a> Evaluator<'a> {
+    /// Calls a method of a `Parser`.
+    pub(crate) fn parser_method(
+        &self,
+        call: &Call,
+        parser: &Rc<Obj<'a>>,
+        args: Vec<Val<'a>>,
+    ) -> Result<Option<Val<'a>>> {
+        let module = parser.kind.class_name().trim_end_matches("#Parser");
+        let parse: fn(&str) -> pkl_stdlib::Result<Option<Data>> = match (module, call.name) {
+            ("pkl.json", "parse") => |text: &str| data::json(text).map(Some),
+            ("pkl.toml", "parse") => |text: &str| data::toml(text).map(Some),
+            ("pkl.yaml", "parse") => |text: &str| {
+                let mut documents = data::yaml(text)?;
+                match documents.len() {
+                    0 | 1 => Ok(documents.pop()),
+                    n => Err(pkl_stdlib::Error::new(format!("expected a single YAML document, but got {n}"))),
+                }
+            },
+            ("pkl.yaml", "parseAll") => |text: &str| data::yaml(text).map(|documents| Some(Data::List(documents))),
+            _ => return Ok(None),
+        };
+
+        let [source] = call.args(args)?;
+        let document = parse(call.string(&source)?).map_err(|e| call.error(e))?;
+        let use_mapping = match self.member(parser, &Key::Property("useMapping".into()))? {
+            Some(value) => call.boolean(&value)?,
+            None => false,
+        };
+        Ok(Some(match document {
+            // `parseAll` returns a `List` of documents rather than a `Listing`
+            Some(Data::List(documents)) if call.name == "parseAll" => {
+                Val::List(documents.into_iter().map(|document| data_val(document, use_mapping)).collect())
+            }
+            Some(document) => data_val(document, use_mapping),
+            None => Val::Null,
+        }))
+    }
+}
+
+pub(crate) fn is_parser(object: &Obj) -> bool {
+    matches!(object.kind.class_name(), "pkl.json#Parser" | "pkl.yaml#Parser" | "pkl.toml#Parser")
+}
+
+fn data_val<'a>(data: Data, use_mapping: bool) -> Val<'a> {
+    let member = |value| Member { span: Span::new(0, 0), def: Def::Value(value) };
+    match data {
+        Data::Null => Val::Null,
+        Data::Boolean(b) => Val::Boolean(b),
+        Data::Int(n) => Val::Int(n),
+        Data::Float(x) => Val::Float(x),
+        Data::String(s) => string_val(s),
+        Data::List(elements) => {
+            let mut listing = Obj::new(ObjectKind::Listing, None, None);
+            for (index, element) in elements.into_iter().enumerate() {
+                listing.members.push((Key::Element(index), member(data_val(element, use_mapping))));
+            }
+            listing.element_count = listing.members.len();
+            Val::Object(Rc::new(listing))
+        }
+        Data::Map(entries) => {
+            let kind = if use_mapping { ObjectKind::Mapping } else { ObjectKind::Dynamic };
+            let mut object = Obj::new(kind, None, None);
+            for (key, value) in entries {
+                let key = match key {
+                    Data::String(name) if !use_mapping => Key::Property(name.into()),
+                    key => Key::Entry(data_val(key, use_mapping)),
+                };
+                object.members.push((key, member(data_val(value, use_mapping))));
+            }
+            Val::Object(Rc::new(object))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate;
+    use crate::value::{ObjectKind, Value};
+
+    fn property(source: &str, name: &str) -> Value {
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{source}: {error:?}"));
+        module.as_object().unwrap().property(name).unwrap_or_else(|| panic!("{source}: no `{name}`")).clone()
+    }
+
+    #[test]
+    fn json() {
+        let source = r##"
+            import "pkl:json"
+            data = new json.Parser {}.parse(#"{"name": "pigeon", "tags": ["bird", 1.5], "home": {"city": null}}"#)
+            name = data.name
+            tag = data.tags[1]
+            mapped = new json.Parser { useMapping = true }.parse(#"{"a": {"b": 1}}"#)
+        "##;
+        assert_eq!(property(source, "name"), Value::String("pigeon".into()));
+        assert_eq!(property(source, "tag"), Value::Float(1.5));
+        let data = property(source, "data");
+        let tags = data.as_object().unwrap().property("tags").unwrap();
+        assert_eq!(tags.as_object().unwrap().kind, ObjectKind::Listing);
+        let mapped = property(source, "mapped");
+        let mapped = mapped.as_object().unwrap();
+        assert_eq!(mapped.kind, ObjectKind::Mapping);
+        let a = mapped.entry(&Value::String("a".into())).unwrap();
+        assert_eq!(a.as_object().unwrap().kind, ObjectKind::Mapping);
+
+        let error = evaluate("import \"pkl:json\"\nx = new json.Parser {}.parse(\"[1,\")").unwrap_err().to_string();
+        assert_eq!(error, "can't parse JSON at line 1, column 4: expected a value");
+    }
+
+    #[test]
+    fn yaml_and_toml() {
+        let source = r#"
+            import "pkl:yaml"
+            import "pkl:toml"
+            port = new yaml.Parser {}.parse("server:\n  port: 80\n").server.port
+            documents = new yaml.Parser {}.parseAll("a\n---\nb\n")
+            key = new yaml.Parser {}.parse("1: one")[1]
+            title = new toml.Parser {}.parse("[book]\ntitle = \"Pkl\"").book.title
+        "#;
+        assert_eq!(property(source, "port"), Value::Int(80));
+        let documents = Value::List(vec![Value::String("a".into()), Value::String("b".into())]);
+        assert_eq!(property(source, "documents"), documents);
+        assert_eq!(property(source, "key"), Value::String("one".into()));
+        assert_eq!(property(source, "title"), Value::String("Pkl".into()));
+
+        let source = "import \"pkl:yaml\"\nx = new yaml.Parser {}.parse(\"a\\n---\\nb\")";
+        let error = evaluate(source).unwrap_err().to_string();
+        assert_eq!(error, "expected a single YAML document, but got 2");
+    }
+}
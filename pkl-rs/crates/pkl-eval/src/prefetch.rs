@@ -0,0 +1,238 @@
+//! Reading the modules that a module imports before evaluating it, on several threads at once.
+//!
+//! Evaluation itself stays on one thread: values are reference counted, and syntax trees live in the arena of the
+//! evaluator. What can happen concurrently is the reading, which is most of the time it takes to import a module of
+//! a large repository of files or a URL. So before a module is evaluated, a pool of
+//! [`threads`](crate::EvaluatorOptions::threads) reads the files and URLs that it imports, parses the headers of
+//! those to find the modules they import in turn, and so on through the whole graph of imports. The sources are then
+//! there when the evaluator imports each module, which it still resolves, checks, and parses as it always does.
+//!
+//! Only what's allowed to be imported is read. A module that can't be read or resolved is left for the evaluator to
+//! report when it imports it, if it does.
+//!
+//! The same reading makes the [`ImportGraph`] of modules, for tools that need to know what a module depends on
+//! without evaluating it.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Condvar, Mutex};
+
+use oxc_allocator::Allocator;
+use pkl_ast::{ModuleHeader, Span};
+
+use crate::modules::{file_path, resolve};
+use crate::options::{Access, EvaluatorOptions};
+use crate::packages;
+
+#[derive(Default)]
+struct Work {
+    /// The URIs that are still to be read
+    pending: Vec<String>,
+    /// Every URI that's been queued, so that each is read once
+    seen: HashSet<String>,
+    /// How many threads are reading a module, which may find more to read
+    reading: usize,
+    sources: HashMap<String, String>,
+    /// The URIs each module that's been read imports
+    graph: HashMap<String, Vec<String>>,
+}
+
+impl Work {
+    /// Work that starts from the modules at `roots`, with the URIs that each imports.
+    fn new(roots: Vec<(String, Vec<String>)>) -> Self {
+        let mut work = Work::default();
+        for (uri, _) in &roots {
+            work.seen.insert(uri.clone());
+        }
+        for (uri, imports) in roots {
+            work.queue(&imports);
+            work.graph.insert(uri, imports);
+        }
+        work
+    }
+
+    fn queue(&mut self, imports: &[String]) {
+        for import in imports {
+            if self.seen.insert(import.clone()) {
+                self.pending.push(import.clone());
+            }
+        }
+    }
+}
+
+/// The graph of the imports of modules: the resolved URIs of the modules that each imports, amends, or extends.
+///
+/// Only the imports of files and URLs are in the graph, as they're what can change; see [`import_graph`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportGraph {
+    /// Each module by its URI, with the URIs of the modules it imports, in the order it imports them. A module that
+    /// can't be read imports nothing.
+    pub imports: BTreeMap<String, Vec<String>>,
+}
+
+/// The sources of the modules that the module at `base`, with `header`, imports, directly or through other modules,
+/// by their resolved URIs.
+pub(crate) fn prefetch(options: &EvaluatorOptions, header: &ModuleHeader, base: &str) -> HashMap<String, String> {
+    if options.threads <= 1 || cfg!(target_arch = "wasm32") {
+        return HashMap::new();
+    }
+    let roots = vec![(base.to_string(), imports(options, header, base))];
+    read_graph(options, roots, options.threads).sources
+}
+
+/// The import graph of the modules with the given URIs and sources, and of the modules they import in turn, which
+/// are read but not evaluated. Only the headers of modules are parsed, so a module with syntax errors in its header
+/// may be missing some of its imports.
+///
+/// Imports that are globs, of the standard library, or of packages and dependencies aren't in the graph, and neither
+/// are those that `options` don't allow.
+///
+/// ```
+/// use pkl_eval::EvaluatorOptions;
+///
+/// let index = ("file:///birds/index.pkl", "import \"pkl:math\"\nimport \"pigeon.pkl\"\n");
+/// let graph = pkl_eval::import_graph(&[index], &EvaluatorOptions::default());
+///
+/// assert_eq!(graph.imports["file:///birds/index.pkl"], ["file:///birds/pigeon.pkl"]);
+/// assert_eq!(graph.imports["file:///birds/pigeon.pkl"], Vec::<String>::new());
+/// ```
+pub fn import_graph(modules: &[(&str, &str)], options: &EvaluatorOptions) -> ImportGraph {
+    let roots = modules.iter().map(|(uri, source)| {
+        let alloc = Allocator::default();
+        let header = pkl_parser::parse_module_header(&alloc, source).node;
+        (uri.to_string(), imports(options, &header, uri))
+    });
+    let threads = if cfg!(target_arch = "wasm32") { 1 } else { options.threads.max(1) };
+    let graph = read_graph(options, roots.collect(), threads).graph;
+    ImportGraph { imports: graph.into_iter().collect() }
+}
+
+/// The URIs of the files and URLs that the module with `source` at `uri` imports, directly or through other modules,
+/// sorted, with those that can't be read among them. These are the modules that evaluating it reads, other than
+/// those of the standard library and packages, and what a tool has to watch to know when to evaluate it again.
+pub fn dependencies(source: &str, uri: &str, options: &EvaluatorOptions) -> Vec<String> {
+    let graph = import_graph(&[(uri, source)], options);
+    graph.imports.into_keys().filter(|module| module != uri).collect()
+}
+
+/// Reads the graph of the imports of the modules at `roots`, with the URIs that each imports, on `threads` threads.
+fn read_graph(options: &EvaluatorOptions, roots: Vec<(String, Vec<String>)>, threads: usize) -> Work {
+    let work = Work::new(roots);
+    if work.pending.is_empty() {
+        return work;
+    }
+    let work = Mutex::new(work);
+    let changed = Condvar::new();
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| read_pending(options, &work, &changed));
+        }
+    });
+    work.into_inner().unwrap_or_default()
+}
+
+/// Reads modules until there are none left to read, by any thread.
+fn read_pending(options: &EvaluatorOptions, work: &Mutex<Work>, changed: &Condvar) {
+    let Ok(mut state) = work.lock() else { return };
+    loop {
+        let Some(uri) = state.pending.pop() else {
+            if state.reading == 0 {
+                changed.notify_all();
+                return;
+            }
+            let Ok(waited) = changed.wait(state) else { return };
+            state = waited;
+            continue;
+        };
+        state.reading += 1;
+        drop(state);
+
+        let source = read(&uri);
+        let imports = source.as_deref().map_or_else(Vec::new, |source| {
+            let alloc = Allocator::default();
+            let header = pkl_parser::parse_module_header(&alloc, source).node;
+            imports(options, &header, &uri)
+        });
+
+        let Ok(mut locked) = work.lock() else { return };
+        locked.queue(&imports);
+        locked.graph.insert(uri.clone(), imports);
+        if let Some(source) = source {
+            locked.sources.insert(uri, source);
+        }
+        locked.reading -= 1;
+        changed.notify_all();
+        state = locked;
+    }
+}
+
+fn read(uri: &str) -> Option<String> {
+    match uri.strip_prefix("file://") {
+        Some(path) => std::fs::read_to_string(path).ok(),
+        None => String::from_utf8(packages::download(uri).ok()?).ok(),
+    }
+}
+
+/// The resolved URIs of the files and URLs that a module imports, amends, or extends, which it's allowed to.
+///
+/// Modules of the standard library, packages, and dependencies are left to the evaluator.
+fn imports(options: &EvaluatorOptions, header: &ModuleHeader, base: &str) -> Vec<String> {
+    if base.starts_with("package:") {
+        return Vec::new();
+    }
+    let extends = header.extends.iter().map(|extends| extends.uri.value);
+    let imports = header.imports.iter().filter(|import| !import.glob).map(|import| import.uri.value);
+    let uris = extends.chain(imports).filter(|uri| !uri.starts_with("pkl:") && !uri.starts_with('@'));
+    let uris = uris.filter(|uri| !uri.starts_with("package:"));
+    // a file that doesn't exist is imported by its path, as creating it changes what the module imports
+    let missing = |uri| file_path(uri, base).map(|path| format!("file://{}", path.display()));
+    let resolved = uris.filter_map(|uri| resolve(Span::new(0, 0), uri, base).ok().or_else(|| missing(uri)));
+    let allowed = |uri: &String| options.check_access(Span::new(0, 0), Access::Import, uri).is_ok();
+    resolved.filter(allowed).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::value::Value;
+    use crate::{evaluate_with, EvaluatorOptions};
+
+    #[test]
+    fn imports_are_read_ahead() {
+        let dir = std::env::temp_dir().join(format!("pkl-prefetch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("birds")).unwrap();
+        let mut main = String::new();
+        for i in 0..20 {
+            let name = format!("bird{i}");
+            fs::write(dir.join("birds").join(format!("{name}.pkl")), format!("import \"../shared.pkl\"\nage = {i}\n"))
+                .unwrap();
+            main.push_str(&format!("import \"birds/{name}.pkl\"\n"));
+        }
+        fs::write(dir.join("shared.pkl"), "x = 1\n").unwrap();
+        main.push_str("total = List(");
+        main.push_str(&(0..20).map(|i| format!("bird{i}.age")).collect::<Vec<_>>().join(", "));
+        main.push_str(").fold(0, (a, b) -> a + b)\n");
+
+        let uri = format!("file://{}", dir.join("main.pkl").display());
+        let sources = {
+            let alloc = oxc_allocator::Allocator::default();
+            let options = EvaluatorOptions::default();
+            let header = pkl_parser::parse_module_header(&alloc, &main).node;
+            super::prefetch(&EvaluatorOptions { threads: 4, ..options }, &header, &uri)
+        };
+        // the imports of the imports are read too, each once
+        assert_eq!(sources.len(), 21);
+
+        for threads in [1, 4] {
+            let options = EvaluatorOptions { threads, ..EvaluatorOptions::default() };
+            let module = evaluate_with(&main, &uri, options).unwrap();
+            assert_eq!(module.as_object().unwrap().property("total"), Some(&Value::Int(190)));
+        }
+
+        let options = EvaluatorOptions { threads: 1, ..EvaluatorOptions::default() };
+        let dependencies = super::dependencies(&main, &uri, &options);
+        assert_eq!(dependencies.len(), 21);
+        assert!(dependencies[0].ends_with("/birds/bird0.pkl") && dependencies[20].ends_with("/shared.pkl"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
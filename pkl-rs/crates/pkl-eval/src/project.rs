@@ -0,0 +1,497 @@
+//! Projects: directories with a `PklProject` file, which amends `pkl:Project` to say which package the project is
+//! published as, which dependencies its modules import by name, like `import "@birds/pigeon.pkl"`, and the settings
+//! its modules are evaluated with.
+//!
+//! A dependency is a package, or another project on disk. Resolving a project pins each of its dependencies, and
+//! theirs, to the newest version wanted of each major version, and records them in a `PklProject.deps.json` file
+//! beside the `PklProject` file, which [`Project::configure`] reads to evaluate the project's modules.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use pkl_eval::project::Project;
+//! use pkl_eval::EvaluatorOptions;
+//!
+//! let project = Project::load(Path::new("birds")).unwrap();
+//! let resolved = project.resolve(None).unwrap();
+//! std::fs::write(project.dir.join(pkl_eval::project::DEPS_FILE), resolved.to_json(&project.dir)).unwrap();
+//!
+//! let mut options = EvaluatorOptions::default();
+//! project.configure(&mut options).unwrap();
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use oxc_allocator::Allocator;
+use pkl_ast::Span;
+use pkl_stdlib::data::{self, Data};
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::log::Log;
+use crate::packages::{fetch, PackageUri};
+use crate::runtime::{Key, Obj, Val};
+use crate::value::{ObjectKind, Value};
+use crate::EvaluatorOptions;
+
+/// The name of the file that makes a directory a project.
+pub const PROJECT_FILE: &str = "PklProject";
+/// The name of the file that the resolved dependencies of a project are written to.
+pub const DEPS_FILE: &str = "PklProject.deps.json";
+
+const PROJECT: &str = "pkl.Project";
+
+/// An evaluated `PklProject` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    /// The canonical path of the directory of the `PklProject` file
+    pub dir: PathBuf,
+    /// The package the project is published as, if it is one
+    pub package: Option<ProjectPackage>,
+    /// The dependencies of the project, by the name its modules import them by
+    pub dependencies: Vec<(String, Dependency)>,
+    pub settings: EvaluatorSettings,
+}
+
+/// The package a project is published as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPackage {
+    pub name: String,
+    /// The URI of the package with its version, like `package://example.com/birds@1.0.0`
+    pub uri: String,
+    pub version: String,
+    pub package_zip_url: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dependency {
+    /// A published package, by its URI with a version
+    Remote { uri: String },
+    /// Another project, whose modules are imported from its directory
+    Local(Box<Project>),
+}
+
+/// The settings that a project's modules are evaluated with, where its `PklProject` file gives them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvaluatorSettings {
+    pub timeout: Option<std::time::Duration>,
+    pub allowed_modules: Option<Vec<String>>,
+    pub allowed_resources: Option<Vec<String>>,
+    pub external_properties: Option<HashMap<String, String>>,
+    pub env: Option<HashMap<String, String>>,
+    /// The root directory, resolved against the directory of the project
+    pub root_dir: Option<PathBuf>,
+}
+
+impl EvaluatorSettings {
+    /// Sets the options that the settings give.
+    pub fn apply(&self, options: &mut EvaluatorOptions) {
+        if let Some(timeout) = self.timeout {
+            options.timeout = Some(timeout);
+        }
+        if let Some(allowed_modules) = &self.allowed_modules {
+            options.allowed_modules = allowed_modules.clone();
+        }
+        if let Some(allowed_resources) = &self.allowed_resources {
+            options.allowed_resources = allowed_resources.clone();
+        }
+        if let Some(properties) = &self.external_properties {
+            options.external_properties = properties.clone();
+        }
+        if let Some(env) = &self.env {
+            options.environment_variables = Some(env.clone());
+        }
+        if let Some(root_dir) = &self.root_dir {
+            options.root_dir = Some(root_dir.clone());
+        }
+    }
+}
+
+impl Project {
+    /// Evaluates the `PklProject` file in `dir`, and those of the projects it depends on.
+    pub fn load(dir: &Path) -> std::result::Result<Project, EvalError> {
+        Project::load_with_logger(dir, |_| {})
+    }
+
+    /// Loads a project like [`Project::load`], sending what the `PklProject` files log to `logger`; see
+    /// [`Evaluator::set_logger`].
+    pub fn load_with_logger(dir: &Path, logger: impl Fn(&Log) + 'static) -> std::result::Result<Project, EvalError> {
+        let file = dir.join(PROJECT_FILE);
+        let dir = dir.canonicalize().map_err(|error| {
+            EvalError::new(Span::default(), format!("can't read project `{}`: {error}", file.display()))
+        })?;
+        let alloc = Allocator::default();
+        let mut evaluator = Evaluator::new(&alloc);
+        evaluator.set_logger(logger);
+        evaluator.start_clock();
+        evaluator.project(&dir).map_err(|error| evaluator.locate(error))
+    }
+
+    /// Sets the options to evaluate the modules of the project with: its settings, and the resolved dependencies
+    /// from its `PklProject.deps.json` file, which has to be there if it has any.
+    pub fn configure(&self, options: &mut EvaluatorOptions) -> std::result::Result<(), String> {
+        self.settings.apply(options);
+        if self.dependencies.is_empty() {
+            return Ok(());
+        }
+        let path = self.dir.join(DEPS_FILE);
+        let text = std::fs::read_to_string(&path).map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => {
+                format!("the dependencies of project `{}` aren't resolved into `{DEPS_FILE}`", self.dir.display())
+            }
+            _ => format!("can't read `{}`: {error}", path.display()),
+        })?;
+        let resolved = ResolvedDependencies::parse(&text, &self.dir)?;
+        for (name, dependency) in &self.dependencies {
+            let key = dependency.key()?;
+            let target = match resolved.dependencies.get(&key) {
+                Some(ResolvedDependency::Remote { uri, checksum }) => format!("{uri}::sha256:{checksum}"),
+                Some(ResolvedDependency::Local { path, .. }) => format!("file://{}", path.display()),
+                None => return Err(format!("dependency `{name}` isn't resolved in `{}`", path.display())),
+            };
+            options.dependencies.insert(name.clone(), target);
+        }
+        Ok(())
+    }
+
+    /// Resolves the dependencies of the project and theirs, downloading packages into `cache_dir` if there is one.
+    pub fn resolve(&self, cache_dir: Option<&Path>) -> std::result::Result<ResolvedDependencies, String> {
+        let mut resolved = BTreeMap::new();
+        let mut remote = Vec::new();
+        self.resolve_local(&mut resolved, &mut remote)?;
+
+        let mut versions: HashMap<String, String> = HashMap::new();
+        while let Some(uri) = remote.pop() {
+            let parsed = PackageUri::parse(Span::default(), &uri).map_err(|error| error.message)?;
+            let key = major_key(&parsed);
+            match resolved.get(&key) {
+                Some(ResolvedDependency::Local { .. }) => continue,
+                Some(ResolvedDependency::Remote { .. }) if !newer(&parsed.version, &versions[&key]) => continue,
+                _ => {}
+            }
+            let package = fetch(&parsed, cache_dir).map_err(|error| format!("can't fetch package `{uri}`: {error}"))?;
+            remote.extend(package.dependencies.iter().map(|(_, uri)| uri.clone()));
+            versions.insert(key.clone(), parsed.version);
+            resolved.insert(key, ResolvedDependency::Remote { uri: package.uri, checksum: package.checksum });
+        }
+        Ok(ResolvedDependencies { dependencies: resolved })
+    }
+
+    /// Adds the local dependencies to `resolved`, and the URIs of the remote ones, of this project and the projects
+    /// it depends on, to `remote`.
+    fn resolve_local(
+        &self,
+        resolved: &mut BTreeMap<String, ResolvedDependency>,
+        remote: &mut Vec<String>,
+    ) -> std::result::Result<(), String> {
+        for (_, dependency) in &self.dependencies {
+            match dependency {
+                Dependency::Remote { uri } => remote.push(uri.clone()),
+                Dependency::Local(project) => {
+                    let key = dependency.key()?;
+                    let uri = project.package.as_ref().map(|package| package.uri.clone()).unwrap_or_default();
+                    let local = ResolvedDependency::Local { uri, path: project.dir.clone() };
+                    if resolved.insert(key, local).is_none() {
+                        project.resolve_local(resolved, remote)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Dependency {
+    /// The package the dependency is resolved as, by its URI with only the major version, like
+    /// `package://example.com/birds@1`.
+    fn key(&self) -> std::result::Result<String, String> {
+        let uri = match self {
+            Dependency::Remote { uri } => uri,
+            Dependency::Local(project) => match &project.package {
+                Some(package) => &package.uri,
+                None => {
+                    let dir = project.dir.display();
+                    return Err(format!("project `{dir}` is a dependency, but it doesn't declare a `package`"));
+                }
+            },
+        };
+        let uri = PackageUri::parse(Span::default(), uri).map_err(|error| error.message)?;
+        Ok(major_key(&uri))
+    }
+}
+
+/// The dependencies of a project and theirs, by their URIs with only the major version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedDependencies {
+    pub dependencies: BTreeMap<String, ResolvedDependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedDependency {
+    /// A package, by its URI with its version, and the SHA-256 checksum of its metadata
+    Remote { uri: String, checksum: String },
+    /// A project on disk, by the URI of the package it's published as, and its canonical directory
+    Local { uri: String, path: PathBuf },
+}
+
+impl ResolvedDependencies {
+    /// The contents of a `PklProject.deps.json` file in `dir`, where the paths of local dependencies are relative.
+    pub fn to_json(&self, dir: &Path) -> String {
+        let mut json = String::from("{\n  \"schemaVersion\": 1,\n  \"resolvedDependencies\": {");
+        for (index, (key, dependency)) in self.dependencies.iter().enumerate() {
+            json.push_str(if index == 0 { "\n" } else { ",\n" });
+            json.push_str(&format!("    {}: {{\n", json_string(key)));
+            match dependency {
+                ResolvedDependency::Remote { uri, checksum } => {
+                    json.push_str("      \"type\": \"remote\",\n");
+                    json.push_str(&format!("      \"uri\": {},\n", json_string(&project_package(uri))));
+                    let checksum = json_string(checksum);
+                    json.push_str(&format!("      \"checksums\": {{\n        \"sha256\": {checksum}\n      }}\n"));
+                }
+                ResolvedDependency::Local { uri, path } => {
+                    json.push_str("      \"type\": \"local\",\n");
+                    json.push_str(&format!("      \"uri\": {},\n", json_string(&project_package(uri))));
+                    let path = json_string(&relative(dir, path).display().to_string());
+                    json.push_str(&format!("      \"path\": {path}\n"));
+                }
+            }
+            json.push_str("    }");
+        }
+        json.push_str(if self.dependencies.is_empty() { "}\n}\n" } else { "\n  }\n}\n" });
+        json
+    }
+
+    /// Reads the contents of a `PklProject.deps.json` file in `dir`.
+    pub fn parse(text: &str, dir: &Path) -> std::result::Result<ResolvedDependencies, String> {
+        let invalid = |reason: &str| format!("invalid `{DEPS_FILE}`: {reason}");
+        let data = data::json(text).map_err(|error| invalid(&error.to_string()))?;
+        if field(&data, "schemaVersion") != Some(&Data::Int(1)) {
+            return Err(invalid("its `schemaVersion` isn't 1"));
+        }
+        let Some(Data::Map(entries)) = field(&data, "resolvedDependencies") else {
+            return Err(invalid("it has no `resolvedDependencies` object"));
+        };
+        let mut dependencies = BTreeMap::new();
+        for (key, entry) in entries {
+            let (Data::String(key), Some(Data::String(uri))) = (key, field(entry, "uri")) else {
+                return Err(invalid("a dependency has no `uri`"));
+            };
+            let uri = uri.strip_prefix("project").unwrap_or(uri).to_string();
+            let dependency = match (field(entry, "type"), field(entry, "checksums"), field(entry, "path")) {
+                (Some(Data::String(kind)), Some(checksums), _) if kind == "remote" => {
+                    let Some(Data::String(checksum)) = field(checksums, "sha256") else {
+                        return Err(invalid(&format!("dependency `{key}` has no `sha256` checksum")));
+                    };
+                    ResolvedDependency::Remote { uri, checksum: checksum.clone() }
+                }
+                (Some(Data::String(kind)), _, Some(Data::String(path))) if kind == "local" => {
+                    ResolvedDependency::Local { uri, path: crate::modules::normalize(&dir.join(path)) }
+                }
+                _ => return Err(invalid(&format!("dependency `{key}` is neither remote nor local"))),
+            };
+            dependencies.insert(key.clone(), dependency);
+        }
+        Ok(ResolvedDependencies { dependencies })
+    }
+}
+
+impl<'a> Evaluator<'a> {
+    /// Evaluates the `PklProject` file in the canonical `dir`.
+    fn project(&self, dir: &Path) -> Result<Project> {
+        let uri = format!("file://{}", dir.join(PROJECT_FILE).display());
+        let module = self.import(Span::default(), &uri, "repl:text")?;
+        if module.kind != ObjectKind::Typed(PROJECT.to_string()) {
+            return Err(EvalError::new(Span::default(), format!("module `{uri}` doesn't amend `pkl:Project`")));
+        }
+        let Value::Object(value) = self.export(&Val::Object(module.clone()))? else {
+            unreachable!("modules are objects")
+        };
+
+        let package = match value.property("package") {
+            Some(Value::Object(package)) => Some(ProjectPackage {
+                name: text(package.property("name")).unwrap_or_default(),
+                uri: text(package.property("uri")).unwrap_or_default(),
+                version: text(package.property("version")).unwrap_or_default(),
+                package_zip_url: text(package.property("packageZipUrl")).unwrap_or_default(),
+            }),
+            _ => None,
+        };
+
+        let settings = match value.property("evaluatorSettings") {
+            Some(Value::Object(settings)) => EvaluatorSettings {
+                timeout: match settings.property("timeout") {
+                    Some(Value::Duration(duration)) => {
+                        let nanos = duration.value.as_f64() * duration.unit.nanos() as f64;
+                        Some(std::time::Duration::from_nanos(nanos.max(0.0) as u64))
+                    }
+                    _ => None,
+                },
+                allowed_modules: strings(settings.property("allowedModules")),
+                allowed_resources: strings(settings.property("allowedResources")),
+                external_properties: mapping(settings.property("externalProperties")),
+                env: mapping(settings.property("env")),
+                root_dir: text(settings.property("rootDir")).map(|root| crate::modules::normalize(&dir.join(root))),
+            },
+            _ => EvaluatorSettings::default(),
+        };
+
+        let mut dependencies = Vec::new();
+        if let Some(Val::Object(mapping)) = self.member(&module, &Key::Property("dependencies".into()))? {
+            for (name, dependency) in self.entries(&mapping)? {
+                let (Val::String(name), Val::Object(dependency)) = (name, dependency) else { continue };
+                dependencies.push((name.to_string(), self.dependency(&name, &dependency)?));
+            }
+        }
+        Ok(Project { dir: dir.to_path_buf(), package, dependencies, settings })
+    }
+
+    /// Reads a dependency of a project: a `RemoteDependency`, or the `PklProject` module of another project.
+    fn dependency(&self, name: &str, dependency: &Rc<Obj<'a>>) -> Result<Dependency> {
+        let imported = self.modules.borrow().iter().any(|(_, module)| Rc::ptr_eq(module, dependency));
+        if !imported {
+            let Some(Val::String(uri)) = self.member(dependency, &Key::Property("uri".into()))? else {
+                return Err(EvalError::new(Span::default(), format!("dependency `{name}` has no `uri`")));
+            };
+            return Ok(Dependency::Remote { uri: uri.to_string() });
+        }
+        let uri = self.module_uri(dependency);
+        let dir = uri.strip_prefix("file://").map(Path::new).filter(|path| path.ends_with(PROJECT_FILE));
+        let Some(dir) = dir.and_then(Path::parent) else {
+            let message = format!("dependency `{name}` is module `{uri}` rather than a `{PROJECT_FILE}` file");
+            return Err(EvalError::new(Span::default(), message));
+        };
+        Ok(Dependency::Local(Box::new(self.project(dir)?)))
+    }
+}
+
+/// The URI of a package with only its major version, like `package://example.com/birds@1`.
+fn major_key(uri: &PackageUri) -> String {
+    let major = uri.version.split('.').next().unwrap_or_default();
+    format!("package://{}@{major}", uri.path)
+}
+
+/// Whether a semantic version is newer than another, by its major, minor, and patch versions.
+fn newer(version: &str, than: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> {
+        let version = version.split(['-', '+']).next().unwrap_or_default();
+        version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+    };
+    parts(version) > parts(than)
+}
+
+/// The `projectpackage:` URI that a `PklProject.deps.json` file records a package by.
+fn project_package(uri: &str) -> String {
+    format!("project{uri}")
+}
+
+/// The path from the directory `from` to `to`, both canonical.
+fn relative(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path: PathBuf = from[common..].iter().map(|_| Component::ParentDir).collect();
+    path.extend(&to[common..]);
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    path
+}
+
+/// A JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn field<'d>(data: &'d Data, name: &str) -> Option<&'d Data> {
+    match data {
+        Data::Map(entries) => entries.iter().find(|(key, _)| *key == Data::String(name.to_string())).map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    match value {
+        Some(Value::String(text)) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+fn strings(value: Option<&Value>) -> Option<Vec<String>> {
+    let Some(Value::Object(listing)) = value else { return None };
+    Some(listing.elements.iter().filter_map(|element| text(Some(element))).collect())
+}
+
+fn mapping(value: Option<&Value>) -> Option<HashMap<String, String>> {
+    let Some(Value::Object(mapping)) = value else { return None };
+    let entries = mapping.entries.iter().filter_map(|(key, value)| Some((text(Some(key))?, text(Some(value))?)));
+    Some(entries.collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::evaluate_with;
+
+    #[test]
+    fn local_dependencies() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-project-{}", std::process::id()));
+        fs::create_dir_all(dir.join("birds")).unwrap();
+        fs::create_dir_all(dir.join("app")).unwrap();
+        let birds = "amends \"pkl:Project\"\npackage {\n  name = \"birds\"\n  \
+                     baseUri = \"package://example.com/birds\"\n  version = \"1.2.0\"\n  \
+                     packageZipUrl = \"https://example.com/birds.zip\"\n}";
+        fs::write(dir.join("birds/PklProject"), birds).unwrap();
+        fs::write(dir.join("birds/pigeon.pkl"), "name = \"pigeon\"").unwrap();
+        let app = "amends \"pkl:Project\"\ndependencies { [\"birds\"] = import(\"../birds/PklProject\") }\n\
+                   evaluatorSettings { env { [\"BIRD\"] = \"crow\" } }";
+        fs::write(dir.join("app/PklProject"), app).unwrap();
+
+        let project = Project::load(&dir.join("app")).unwrap();
+        let Dependency::Local(birds) = &project.dependencies[0].1 else { panic!() };
+        assert_eq!(birds.package.as_ref().unwrap().uri, "package://example.com/birds@1.2.0");
+        let resolved = project.resolve(None).unwrap();
+        let json = resolved.to_json(&project.dir);
+        assert!(json.contains("\"package://example.com/birds@1\": {\n      \"type\": \"local\""), "{json}");
+        assert!(json.contains("\"path\": \"../birds\""), "{json}");
+        assert_eq!(ResolvedDependencies::parse(&json, &project.dir), Ok(resolved));
+
+        fs::write(dir.join("app").join(DEPS_FILE), json).unwrap();
+        let mut options = EvaluatorOptions::default();
+        project.configure(&mut options).unwrap();
+        let uri = format!("file://{}/app/main.pkl", project.dir.parent().unwrap().display());
+        let source = "import \"@birds/pigeon.pkl\"\nname = pigeon.name\nbird = read(\"env:BIRD\")";
+        let module = evaluate_with(source, &uri, options).unwrap();
+        let module = module.as_object().unwrap();
+        assert_eq!(module.property("name"), Some(&Value::String("pigeon".into())));
+        assert_eq!(module.property("bird"), Some(&Value::String("crow".into())));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_projects() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-project-invalid-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(PROJECT_FILE), "amends \"pkl:Project\"\nbirds = 1").unwrap();
+        let error = Project::load(&dir).unwrap_err();
+        assert_eq!(error.message, "module `pkl.Project` has no property `birds`");
+        fs::write(dir.join(PROJECT_FILE), "name = 1").unwrap();
+        let error = Project::load(&dir).unwrap_err();
+        let uri = format!("file://{}", dir.canonicalize().unwrap().join(PROJECT_FILE).display());
+        assert_eq!(error.message, format!("module `{uri}` doesn't amend `pkl:Project`"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+//! The members of `Regex` and `RegexMatch`, and the members of `String` taking a `Regex`, like
+//! `"a1b2".replaceAll(Regex(#"\d"#), "_")`. Matching itself is implemented by [`pkl_stdlib::regex`].
+
+use std::rc::Rc;
+
+use pkl_stdlib::regex::{Match, Occurrence, Regex};
+
+use crate::collections::returned;
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::methods::{string_val, Call};
+use crate::runtime::Val;
+use crate::value::{Object, ObjectKind, Value};
+
+impl<'a> Evaluator<'a> {
+    /// Calls a method of a `Regex` or `RegexMatch`.
+    pub(crate) fn regex_method(&self, call: &Call, receiver: &Val<'a>, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+        let Val::Regex(regex) = receiver else { return Ok(None) };
+        let value = match call.name {
+            "matchEntire" => {
+                let [input] = call.args(args)?;
+                regex.match_entire(call.string(&input)?).map_or(Val::Null, match_val)
+            }
+            "findMatchesIn" => {
+                let [input] = call.args(args)?;
+                Val::List(regex.find_matches(call.string(&input)?).into_iter().map(match_val).collect())
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Calls a method of a `String` whose pattern is a `Regex`, or one of the `replace...Mapped` methods, which
+    /// take a `String` or a `Regex`.
+    pub(crate) fn regex_string_method(&self, call: &Call, s: &str, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+        let pattern = |value: &Val| match value {
+            Val::String(literal) => Ok(Rc::new(Regex::literal(literal))),
+            value => call.regex(value).cloned(),
+        };
+
+        let value = match call.name {
+            "matches" | "contains" | "startsWith" | "indexOf" | "lastIndexOf" | "split" => {
+                let [regex] = call.args(args)?;
+                let regex = call.regex(&regex)?;
+                match call.name {
+                    "matches" => Val::Boolean(regex.matches_entire(s)),
+                    "contains" => Val::Boolean(regex.is_match(s)),
+                    "startsWith" => Val::Boolean(regex.matches_prefix(s)),
+                    "indexOf" => Val::Int(regex.index_of(s).unwrap_or(-1)),
+                    "lastIndexOf" => Val::Int(regex.last_index_of(s).unwrap_or(-1)),
+                    _ => Val::List(regex.split(s).into_iter().map(string_val).collect()),
+                }
+            }
+            "replaceAll" | "replaceFirst" | "replaceLast" => {
+                let [regex, replacement] = call.args(args)?;
+                let (regex, replacement) = (call.regex(&regex)?, call.string(&replacement)?);
+                let occurrence = occurrence(call.name.trim_start_matches("replace"));
+                string_val(regex.replace(s, occurrence, replacement).map_err(|e| call.error(e))?)
+            }
+            "replaceAllMapped" | "replaceFirstMapped" | "replaceLastMapped" => {
+                let [regex, mapper] = call.args(args)?;
+                let (regex, mapper) = (pattern(&regex)?, call.function(&mapper)?);
+                let occurrence = occurrence(call.name.trim_start_matches("replace").trim_end_matches("Mapped"));
+                let replaced = regex.replace_mapped(s, occurrence, |found| {
+                    match self.apply(call.span, mapper, vec![match_val(found)])? {
+                        Val::String(replacement) => Ok(replacement.to_string()),
+                        value => Err(returned(call, "a `String`", &value)),
+                    }
+                });
+                string_val(replaced?)
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+}
+
+pub(crate) fn regex_property<'a>(receiver: &Val<'a>, name: &str) -> Option<Val<'a>> {
+    Some(match (receiver, name) {
+        (Val::Regex(regex), "pattern") => string_val(regex.pattern()),
+        (Val::Regex(regex), "groupCount") => Val::Int(regex.group_count()),
+        (Val::RegexMatch(found), "value") => string_val(found.value.as_str()),
+        (Val::RegexMatch(found), "start") => Val::Int(found.start),
+        (Val::RegexMatch(found), "end") => Val::Int(found.end),
+        (Val::RegexMatch(found), "groups") => {
+            Val::List(found.groups.iter().map(|group| group.clone().map_or(Val::Null, match_val)).collect())
+        }
+        _ => return None,
+    })
+}
+
+/// A `RegexMatch` as a value, which is an object with the properties of the match.
+pub(crate) fn export_match(found: &Match) -> Value {
+    let mut object = Object::new(ObjectKind::Typed("RegexMatch".to_string()));
+    object.properties.insert("value".to_string(), Value::String(found.value.clone()));
+    object.properties.insert("start".to_string(), Value::Int(found.start));
+    object.properties.insert("end".to_string(), Value::Int(found.end));
+    let groups = found.groups.iter().map(|group| group.as_ref().map_or(Value::Null, export_match));
+    object.properties.insert("groups".to_string(), Value::List(groups.collect()));
+    Value::Object(object)
+}
+
+fn match_val<'a>(found: Match) -> Val<'a> {
+    Val::RegexMatch(Rc::new(found))
+}
+
+fn occurrence(name: &str) -> Occurrence {
+    match name {
+        "All" => Occurrence::All,
+        "First" => Occurrence::First,
+        _ => Occurrence::Last,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate_expr;
+    use crate::value::Value;
+
+    fn eval(source: &str) -> Value {
+        evaluate_expr(source).unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    fn string(source: &str) -> String {
+        match eval(source) {
+            Value::String(s) => s,
+            value => panic!("{source}: {value:?}"),
+        }
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(eval(r##"Regex(#"\d+"#).pattern"##), Value::String(r"\d+".into()));
+        assert_eq!(eval(r#"Regex("(a)(b)?").groupCount"#), Value::Int(2));
+        let found = r#"Regex("a(b)?").findMatchesIn("xab a").map((m) -> "\(m.start)\(m.groups[1])").join(",")"#;
+        assert_eq!(string(found), "1b,4null");
+        assert_eq!(eval(r#"Regex("a+").matchEntire("aab")"#), Value::Null);
+        assert_eq!(string(r#"Regex("a+").matchEntire("aa").value"#), "aa");
+        assert_eq!(string(r#""\(Regex("a").findMatchesIn("a")[0])""#), "a");
+        assert_eq!(string(r#""\(Regex("a\"b"))""#), r#"Regex("a\"b")"#);
+
+        let error = evaluate_expr(r#"Regex("(")"#).unwrap_err().to_string();
+        assert_eq!(error, r#""(" isn't a valid regular expression: unclosed group"#);
+    }
+
+    #[test]
+    fn string_methods() {
+        assert_eq!(eval(r##""2024".matches(Regex(#"\d{4}"#))"##), Value::Boolean(true));
+        assert_eq!(eval(r##""a2024".matches(Regex(#"\d{4}"#))"##), Value::Boolean(false));
+        assert_eq!(eval(r##""a1b22".indexOf(Regex(#"\d"#))"##), Value::Int(1));
+        assert_eq!(string(r##""a1b22".replaceAll(Regex(#"(\d)"#), "<$1>")"##), "a<1>b<2><2>");
+        assert_eq!(string(r##""a1b22".replaceLast(Regex(#"\d+"#), "_")"##), "a1b_");
+        assert_eq!(eval(r##""a, b,c".split(Regex(#",\s*"#)).length"##), Value::Int(3));
+        assert_eq!(string(r#""a.b".replaceAllMapped(".", (m) -> m.value + m.value)"#), "a..b");
+        assert_eq!(string(r#""ab".replaceFirstMapped(Regex("[a-z]"), (m) -> m.value.toUpperCase())"#), "Ab");
+
+        let error = evaluate_expr(r#""ab".replaceAllMapped("a", (m) -> 1)"#).unwrap_err().to_string();
+        assert_eq!(error, "the function passed to `replaceAllMapped` must return a `String`, but returned `Int`");
+    }
+}
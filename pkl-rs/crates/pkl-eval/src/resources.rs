@@ -0,0 +1,289 @@
+//! `read` expressions, like `read("env:HOME")` or `read?("file:///etc/birds.txt")`, which read resources by URI.
+//!
+//! Environment variables are read with `env:` URIs, external properties with `prop:` URIs, and files and URLs with
+//! `file:` and `https:` URIs, or paths relative to the reading module. Embedders can read other schemes, like
+//! `secret:`, by adding a [`ResourceReader`] to the evaluator.
+//!
+//! `env:` and `prop:` resources read as `String`s, and others as `Resource` objects, with the `uri`, `text`, and
+//! `base64` of the resource.
+//!
+//! `read*` reads every file, environment variable, or external property matching a glob pattern, like
+//! `read*("env:PKL_*")`, into a `Mapping` by URI.
+
+use std::rc::Rc;
+
+use pkl_ast::uri::has_scheme;
+use pkl_ast::{ReadKind, Span};
+use pkl_stdlib::glob::Glob;
+use pkl_stdlib::string::base64;
+
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::methods::string_val;
+use crate::modules::{mapping, native_object, normalize, resolve_url};
+use crate::options::Access;
+use crate::packages::download;
+use crate::runtime::Val;
+use crate::value::ObjectKind;
+
+/// Reads the resources of a URI scheme, for `read` expressions.
+///
+/// ```
+/// use oxc_allocator::Allocator;
+/// use pkl_eval::{Evaluator, Resource, ResourceReader};
+///
+/// struct Secrets;
+///
+/// impl ResourceReader for Secrets {
+///     fn scheme(&self) -> &str {
+///         "secret"
+///     }
+///
+///     fn read(&self, uri: &str) -> Result<Option<Resource>, String> {
+///         Ok((uri == "secret:password").then(|| Resource::String("hunter2".to_string())))
+///     }
+/// }
+///
+/// let alloc = Allocator::default();
+/// let module = pkl_parser::parse_module(&alloc, r#"password = read("secret:password")"#).node;
+/// let mut evaluator = Evaluator::new(&alloc);
+/// evaluator.add_resource_reader(Secrets);
+/// let module = evaluator.evaluate_module(alloc.alloc(module), "repl:text").unwrap();
+///
+/// let password = module.as_object().unwrap().property("password");
+/// assert_eq!(password, Some(&pkl_eval::value::Value::String("hunter2".into())));
+/// ```
+pub trait ResourceReader {
+    /// The scheme of the URIs this reads, like `secret`, without the `:`.
+    fn scheme(&self) -> &str;
+
+    /// Reads the resource at a URI of this scheme, or nothing if there's no resource there, in which case `read`
+    /// fails and `read?` is `null`. Errors are messages, like `"the vault is sealed"`.
+    fn read(&self, uri: &str) -> std::result::Result<Option<Resource>, String>;
+}
+
+/// What a [`ResourceReader`] reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resource {
+    /// Read as a `String`, like environment variables are
+    String(String),
+    /// Read as a `Resource` object, like files are
+    Bytes(Vec<u8>),
+}
+
+impl<'a> Evaluator<'a> {
+    /// Evaluates a `read` or `read?` of `uri`, read in the module at `base`.
+    pub(crate) fn read(&self, span: Span, kind: ReadKind, uri: &str, base: &str) -> Result<Val<'a>> {
+        if kind == ReadKind::ReadGlob {
+            return self.read_glob(span, uri, base);
+        }
+        let uri = resolve(span, uri, base)?;
+        self.check_access(span, Access::Read, &uri)?;
+        let scheme = &uri[..uri.find(':').expect("resolved URIs have a scheme")];
+
+        let resource = if let Some(reader) = self.readers.iter().find(|reader| reader.scheme() == scheme) {
+            reader.read(&uri).map_err(|message| EvalError::new(span, format!("can't read `{uri}`: {message}")))?
+        } else {
+            match scheme {
+                "env" => match &self.options.environment_variables {
+                    Some(variables) => variables.get(&uri[4..]).cloned().map(Resource::String),
+                    None => std::env::var(&uri[4..]).ok().map(Resource::String),
+                },
+                "prop" => self.options.external_properties.get(&uri[5..]).cloned().map(Resource::String),
+                "file" => match std::fs::read(uri.strip_prefix("file://").unwrap_or(&uri[5..])) {
+                    Ok(bytes) => Some(Resource::Bytes(bytes)),
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(error) => return Err(EvalError::new(span, format!("can't read `{uri}`: {error}"))),
+                },
+                "https" => Some(Resource::Bytes(download(&uri).map_err(|message| EvalError::new(span, message))?)),
+                _ => return Err(EvalError::new(span, format!("there's no resource reader for `{scheme}:` URIs"))),
+            }
+        };
+
+        Ok(match (resource, kind) {
+            (Some(Resource::String(s)), _) => string_val(s),
+            (Some(Resource::Bytes(bytes)), _) => {
+                let properties = vec![
+                    ("uri", string_val(uri.as_str())),
+                    ("text", string_val(String::from_utf8_lossy(&bytes))),
+                    ("base64", string_val(base64(&bytes))),
+                ];
+                Val::Object(Rc::new(native_object(ObjectKind::Typed("Resource".to_string()), properties)))
+            }
+            (None, ReadKind::ReadOrNull) => Val::Null,
+            (None, _) => return Err(EvalError::new(span, format!("can't find resource `{uri}`"))),
+        })
+    }
+
+    /// Evaluates a `read*` of the resources matching a glob pattern into a `Mapping` of them by their URIs as the
+    /// pattern writes them: the files, environment variables, or external properties that can be read.
+    fn read_glob(&self, span: Span, pattern: &str, base: &str) -> Result<Val<'a>> {
+        let names = match pattern.split_once(':').map(|(scheme, _)| scheme) {
+            Some(scheme @ ("env" | "prop")) => {
+                let names: Vec<String> = match (scheme, &self.options.environment_variables) {
+                    ("env", Some(variables)) => variables.keys().cloned().collect(),
+                    ("env", None) => std::env::vars().map(|(name, _)| name).collect(),
+                    _ => self.options.external_properties.keys().cloned().collect(),
+                };
+                let glob = Glob::new(&pattern[scheme.len() + 1..]);
+                let glob = glob.map_err(|error| EvalError::new(span, error.message))?;
+                let mut uris: Vec<String> = names
+                    .into_iter()
+                    .filter(|name| glob.is_match(name))
+                    .map(|name| format!("{scheme}:{name}"))
+                    .filter(|uri| self.check_access(span, Access::Read, uri).is_ok())
+                    .collect();
+                uris.sort();
+                uris.into_iter().map(|uri| (uri.clone(), uri)).collect()
+            }
+            _ => self.glob_files(span, Access::Read, pattern, base)?,
+        };
+        let mut entries = Vec::new();
+        for (name, uri) in names {
+            entries.push((name, self.read(span, ReadKind::Read, &uri, base)?));
+        }
+        Ok(Val::Object(Rc::new(mapping(span, entries))))
+    }
+
+    /// Reads the resources of a scheme with `reader`, rather than the built-in reader of the scheme if there is one,
+    /// and allows reading them.
+    pub fn add_resource_reader(&mut self, reader: impl ResourceReader + 'static) {
+        self.options.allowed_resources.push(format!("{}:", reader.scheme().replace('.', "\\.")));
+        self.readers.insert(0, Box::new(reader));
+    }
+}
+
+/// Resolves the URI of a resource, relative to the URI of the reading module if it's a path.
+pub(crate) fn resolve(span: Span, uri: &str, base: &str) -> Result<String> {
+    if has_scheme(uri) {
+        return Ok(uri.to_string());
+    }
+    if base.starts_with("https:") {
+        return resolve_url(span, uri, base);
+    }
+    let directory = match base.strip_prefix("file://") {
+        Some(base) => std::path::Path::new(base).parent().map(std::path::Path::to_path_buf).unwrap_or_default(),
+        None => std::env::current_dir().unwrap_or_default(),
+    };
+    Ok(format!("file://{}", normalize(&directory.join(uri)).display()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use oxc_allocator::Allocator;
+
+    use super::{Resource, ResourceReader};
+    use crate::evaluator::Evaluator;
+    use crate::value::Value;
+    use crate::{evaluate, evaluate_at, evaluate_with, EvaluatorOptions};
+
+    struct Secrets;
+
+    impl ResourceReader for Secrets {
+        fn scheme(&self) -> &str {
+            "secret"
+        }
+
+        fn read(&self, uri: &str) -> Result<Option<Resource>, String> {
+            match uri {
+                "secret:password" => Ok(Some(Resource::String("hunter2".to_string()))),
+                "secret:sealed" => Err("the vault is sealed".to_string()),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    fn property(module: &Value, name: &str) -> Value {
+        module.as_object().unwrap().property(name).unwrap_or_else(|| panic!("no `{name}`")).clone()
+    }
+
+    #[test]
+    fn env_and_prop() {
+        let alloc = Allocator::default();
+        let source = r#"
+            path = read("env:PATH")
+            missing = read?("env:PKL_EVAL_MISSING_VARIABLE")
+            bird = read("prop:bird")
+        "#;
+        let module = alloc.alloc(pkl_parser::parse_module(&alloc, source).node);
+        let mut options = EvaluatorOptions::default();
+        options.external_properties.insert("bird".to_string(), "pigeon".to_string());
+        let module = Evaluator::with_options(&alloc, options).evaluate_module(module, "repl:text").unwrap();
+        assert_eq!(property(&module, "path"), Value::String(std::env::var("PATH").unwrap()));
+        assert_eq!(property(&module, "missing"), Value::Null);
+        assert_eq!(property(&module, "bird"), Value::String("pigeon".into()));
+
+        let error = evaluate("x = read(\"env:PKL_EVAL_MISSING_VARIABLE\")").unwrap_err();
+        assert_eq!(error.to_string(), "can't find resource `env:PKL_EVAL_MISSING_VARIABLE`");
+        let options = EvaluatorOptions { allowed_resources: vec!["vault:".to_string()], ..EvaluatorOptions::default() };
+        let error = evaluate_with("x = read(\"vault:a\")", "repl:text", options).unwrap_err();
+        assert_eq!(error.to_string(), "there's no resource reader for `vault:` URIs");
+    }
+
+    #[test]
+    fn files() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-resources-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("birds.txt"), "pigeon\n").unwrap();
+        let source = r#"
+            birds = read("birds.txt")
+            text = birds.text
+            base64 = birds.base64
+            missing = read?("owls.txt")
+        "#;
+        let module = evaluate_at(source, &format!("file://{}/main.pkl", dir.display())).unwrap();
+        assert_eq!(property(&module, "text"), Value::String("pigeon\n".into()));
+        assert_eq!(property(&module, "base64"), Value::String("cGlnZW9uCg==".into()));
+        assert_eq!(property(&module, "missing"), Value::Null);
+        let birds = property(&module, "birds");
+        let uri = format!("file://{}/birds.txt", dir.display());
+        assert_eq!(birds.as_object().unwrap().property("uri"), Some(&Value::String(uri)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn globs() {
+        let dir = std::env::temp_dir().join(format!("pkl-eval-resources-glob-{}", std::process::id()));
+        fs::create_dir_all(dir.join("jobs/nightly")).unwrap();
+        fs::write(dir.join("jobs/build.yml"), "build").unwrap();
+        fs::write(dir.join("jobs/test.yml"), "test").unwrap();
+        fs::write(dir.join("jobs/nightly/deploy.yml"), "deploy").unwrap();
+        let mut options = EvaluatorOptions::default();
+        options.external_properties.insert("bird.name".to_string(), "pigeon".to_string());
+        options.external_properties.insert("fish".to_string(), "cod".to_string());
+        let source = r#"
+            jobs = read*("jobs/*.yml").keys.toList()
+            all = read*("jobs/**.yml").values.map((job) -> job.text).join(",")
+            birds = read*("prop:bird.*").toMap()
+        "#;
+        let module = evaluate_with(source, &format!("file://{}/main.pkl", dir.display()), options.clone()).unwrap();
+        let jobs = ["jobs/build.yml", "jobs/test.yml"].map(|job| Value::String(job.into())).to_vec();
+        assert_eq!(property(&module, "jobs"), Value::List(jobs));
+        assert_eq!(property(&module, "all"), Value::String("build,deploy,test".into()));
+        let birds = vec![(Value::String("prop:bird.name".into()), Value::String("pigeon".into()))];
+        assert_eq!(property(&module, "birds"), Value::Map(birds));
+
+        options.root_dir = Some(dir.join("jobs/nightly"));
+        let error = evaluate_with("x = read*(\"jobs/*.yml\")", &format!("file://{}/main.pkl", dir.display()), options);
+        assert!(error.unwrap_err().to_string().contains("it's outside of the root directory"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn custom_readers() {
+        let alloc = Allocator::default();
+        let evaluate = |source: &'static str| {
+            let module = alloc.alloc(pkl_parser::parse_module(&alloc, source).node);
+            let mut evaluator = Evaluator::new(&alloc);
+            evaluator.add_resource_reader(Secrets);
+            evaluator.evaluate_module(module, "repl:text")
+        };
+        let module = evaluate("password = read(\"secret:password\")\nother = read?(\"secret:other\")").unwrap();
+        assert_eq!(property(&module, "password"), Value::String("hunter2".into()));
+        assert_eq!(property(&module, "other"), Value::Null);
+        let error = evaluate("x = read(\"secret:sealed\")").unwrap_err();
+        assert_eq!(error.message, "can't read `secret:sealed`: the vault is sealed");
+    }
+}
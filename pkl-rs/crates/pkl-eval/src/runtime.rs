@@ -0,0 +1,253 @@
+//! The representation of values during evaluation, where objects are evaluated lazily.
+//!
+//! An object is a chain of layers: every amendment `parent { ... }` creates a layer with the members of the body
+//! and a link to the parent. Members are evaluated on first access with `this` bound to the object they are read from
+//! (the receiver), not the layer that defines them, which is what makes amending late-bound: in `(base) { a = 2 }`,
+//! the members `base` derives from `a` see the new `a`. The result is memoized per receiver.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pkl_ast::{
+    Annotation, ClassDecl, Expr, Modifier, ModifierKind, ObjectBody, Parameter, Span, Type, TypeAlias, TypeParameter,
+};
+use pkl_stdlib::regex::{Match, Regex};
+
+use crate::value::{DataSize, Duration, ObjectKind};
+
+#[derive(Debug, Clone)]
+pub(crate) enum Val<'a> {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(Rc<str>),
+    Duration(Duration),
+    DataSize(DataSize),
+    List(Rc<[Val<'a>]>),
+    Set(Rc<[Val<'a>]>),
+    Map(Rc<[(Val<'a>, Val<'a>)]>),
+    Object(Rc<Obj<'a>>),
+    Function(Rc<Lambda<'a>>),
+    Class(Rc<Class<'a>>),
+    Regex(Rc<Regex>),
+    RegexMatch(Rc<Match>),
+}
+
+impl Val<'_> {
+    pub(crate) fn type_name(&self) -> &str {
+        match self {
+            Val::Null => "Null",
+            Val::Boolean(_) => "Boolean",
+            Val::Int(_) => "Int",
+            Val::Float(_) => "Float",
+            Val::String(_) => "String",
+            Val::Duration(_) => "Duration",
+            Val::DataSize(_) => "DataSize",
+            Val::List(_) => "List",
+            Val::Set(_) => "Set",
+            Val::Map(_) => "Map",
+            Val::Object(object) => object.kind.class_name(),
+            Val::Function(_) => "Function",
+            Val::Class(_) => "Class",
+            Val::Regex(_) => "Regex",
+            Val::RegexMatch(_) => "RegexMatch",
+        }
+    }
+}
+
+/// Identifies a member of an object.
+#[derive(Debug, Clone)]
+pub(crate) enum Key<'a> {
+    Property(Rc<str>),
+    /// A `local` property, which is only visible from the body that defines it
+    Local(Rc<str>),
+    Entry(Val<'a>),
+    /// An element, by its index in the whole object rather than in the layer that defines it
+    Element(usize),
+}
+
+/// One layer of an object: the members defined by a single object body (or a module).
+#[derive(Debug)]
+pub(crate) struct Obj<'a> {
+    pub(crate) kind: ObjectKind,
+    /// The object this layer amends
+    pub(crate) parent: Option<Rc<Obj<'a>>>,
+    /// The scope the body of this layer was written in, which its members are evaluated in
+    pub(crate) env: Option<Rc<Env<'a>>>,
+    pub(crate) members: Vec<(Key<'a>, Member<'a>)>,
+    pub(crate) methods: Vec<Method<'a>>,
+    /// The classes declared by a module
+    pub(crate) classes: Vec<&'a ClassDecl<'a>>,
+    /// The type aliases declared by a module
+    pub(crate) aliases: Vec<&'a TypeAlias<'a>>,
+    /// The class whose members the layer holds, if it's the layer of a class prototype
+    pub(crate) class: Option<&'a ClassDecl<'a>>,
+    /// The number of elements of the object, including those of its parents
+    pub(crate) element_count: usize,
+    /// Members evaluated with this object as the receiver
+    pub(crate) cache: RefCell<Vec<(CacheKey<'a>, Slot<'a>)>>,
+}
+
+impl<'a> Obj<'a> {
+    pub(crate) fn new(kind: ObjectKind, parent: Option<Rc<Obj<'a>>>, env: Option<Rc<Env<'a>>>) -> Self {
+        let element_count = parent.as_ref().map_or(0, |parent| parent.element_count);
+        Obj {
+            kind,
+            parent,
+            env,
+            members: Vec::new(),
+            methods: Vec::new(),
+            classes: Vec::new(),
+            aliases: Vec::new(),
+            class: None,
+            element_count,
+            cache: RefCell::default(),
+        }
+    }
+
+    /// The layers of the object, starting with this one.
+    pub(crate) fn layers(self: &Rc<Self>) -> impl Iterator<Item = &Rc<Obj<'a>>> {
+        std::iter::successors(Some(self), |layer| layer.parent.as_ref())
+    }
+
+    /// The member of this layer with the given key.
+    pub(crate) fn own_member(&self, key: &Key<'a>) -> Option<&Member<'a>> {
+        self.members.iter().find(|(k, _)| k.same(key)).map(|(_, member)| member)
+    }
+
+    /// The modifier of kind `kind` of a member, if this layer or a layer it amends declares the member with it.
+    pub(crate) fn modifier(&self, key: &Key<'a>, kind: ModifierKind) -> Option<&'a Modifier> {
+        std::iter::successors(Some(self), |layer| layer.parent.as_deref()).find_map(|layer| {
+            match layer.own_member(key)?.def {
+                Def::Expr { modifiers, .. } => modifiers.iter().find(|modifier| modifier.kind == kind),
+                Def::Value(_) => None,
+            }
+        })
+    }
+}
+
+impl<'a> Obj<'a> {
+    /// The `@Deprecated` annotation of a property, if this layer or a layer it amends declares it with one.
+    pub(crate) fn deprecation(&self, key: &Key<'a>) -> Option<&'a Annotation<'a>> {
+        std::iter::successors(Some(self), |layer| layer.parent.as_deref()).find_map(|layer| {
+            match layer.own_member(key)?.def {
+                Def::Expr { deprecated, .. } => deprecated,
+                Def::Value(_) => None,
+            }
+        })
+    }
+}
+
+impl<'a> Key<'a> {
+    pub(crate) fn same(&self, other: &Key<'a>) -> bool {
+        match (self, other) {
+            (Key::Property(a), Key::Property(b)) | (Key::Local(a), Key::Local(b)) => a == b,
+            (Key::Entry(a), Key::Entry(b)) => crate::equality::values_equal(a, b),
+            (Key::Element(a), Key::Element(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Member<'a> {
+    pub(crate) span: Span,
+    pub(crate) def: Def<'a>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Def<'a> {
+    /// `= value`, `{ ... }`, or both, where a missing value means amending the inherited one. Properties of classes
+    /// and modules also have the declared type, which gives the default value when there's nothing to inherit, and
+    /// modifiers like `hidden`.
+    Expr {
+        value: Option<&'a Expr<'a>>,
+        bodies: &'a [ObjectBody<'a>],
+        ty: Option<&'a Type<'a>>,
+        modifiers: &'a [Modifier],
+        /// The `@Deprecated` annotation of the property's declaration
+        deprecated: Option<&'a Annotation<'a>>,
+        /// The variables of the `for` generators a member is written in, which are in scope besides the layer's
+        bindings: Option<Rc<Vec<(&'a str, Val<'a>)>>>,
+    },
+    /// A value the evaluator provides itself, like the properties of built-in modules and the modules a module imports
+    Value(Val<'a>),
+}
+
+#[derive(Debug)]
+pub(crate) struct Method<'a> {
+    pub(crate) span: Span,
+    pub(crate) name: &'a str,
+    pub(crate) local: bool,
+    pub(crate) type_params: &'a [TypeParameter<'a>],
+    pub(crate) params: &'a [Parameter<'a>],
+    /// Missing for `abstract` and `external` methods
+    pub(crate) body: Option<&'a Expr<'a>>,
+    pub(crate) deprecated: Option<&'a Annotation<'a>>,
+}
+
+/// A lambda, along with the scope it was written in.
+#[derive(Debug)]
+pub(crate) struct Lambda<'a> {
+    pub(crate) params: &'a [Parameter<'a>],
+    pub(crate) body: &'a Expr<'a>,
+    pub(crate) env: Rc<Env<'a>>,
+}
+
+/// A class, as a value like `Bird` in `dynamic.toTyped(Bird)`.
+#[derive(Debug)]
+pub(crate) struct Class<'a> {
+    pub(crate) name: &'a str,
+    /// The declaration and the module declaring it, which its prototype is created in; missing for the classes of
+    /// `pkl:base`, like `Duration`
+    pub(crate) declared: Option<(&'a ClassDecl<'a>, Rc<Obj<'a>>)>,
+}
+
+/// A memoized member: which member, and for locals, the address of the layer defining it (two layers of an object
+/// can define locals with the same name).
+pub(crate) type CacheKey<'a> = (Key<'a>, usize);
+
+#[derive(Debug, Clone)]
+pub(crate) enum Slot<'a> {
+    /// The member is being evaluated, so reading it again means it refers to itself
+    Evaluating,
+    Done(Val<'a>),
+}
+
+/// A lexical scope, innermost first.
+#[derive(Debug)]
+pub(crate) struct Env<'a> {
+    pub(crate) frame: Frame<'a>,
+    pub(crate) parent: Option<Rc<Env<'a>>>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Frame<'a> {
+    /// Parameters of a method
+    Bindings(Vec<(&'a str, Val<'a>)>),
+    /// The body of an object: `this` is the receiver the members are evaluated for, and `layer` the layer of the
+    /// receiver that the body defines (whose locals are in scope)
+    Object { this: Rc<Obj<'a>>, layer: Rc<Obj<'a>> },
+    /// A type constraint, where `this` is the value being checked, whose properties and methods are in scope
+    Constraint(Val<'a>),
+}
+
+impl<'a> Env<'a> {
+    pub(crate) fn new(frame: Frame<'a>, parent: Option<Rc<Env<'a>>>) -> Rc<Self> {
+        Rc::new(Env { frame, parent })
+    }
+
+    /// The scopes, starting with this one.
+    pub(crate) fn scopes(self: &Rc<Self>) -> impl Iterator<Item = &Rc<Env<'a>>> {
+        std::iter::successors(Some(self), |env| env.parent.as_ref())
+    }
+
+    /// The receivers of the enclosing object bodies, innermost first.
+    pub(crate) fn receivers(self: &Rc<Self>) -> impl Iterator<Item = &Rc<Obj<'a>>> {
+        self.scopes().filter_map(|env| match &env.frame {
+            Frame::Object { this, .. } => Some(this),
+            Frame::Bindings(_) | Frame::Constraint(_) => None,
+        })
+    }
+}
@@ -0,0 +1,536 @@
+//! Checking values against the types that properties are declared with, like `port: Int(this > 0)`, and the types of
+//! `value is Type` and `value as Type`.
+//!
+//! A member is checked when it's first evaluated, against the nearest declaration of its property, so amending
+//! `new Bird { age = "old" }` fails if `Bird` declares `age: Int`. Constraints are evaluated with `this` bound to the
+//! value, and the value's own properties and methods in scope, as in `String(length > 3)`. Parameterized types check
+//! their elements and entries too: every element of a `Listing<Int>` has to be an `Int`.
+//!
+//! A class type like `Bird`, or `birds.Bird` for a class of the imported module `birds`, is the class declared by
+//! that module and its subclasses, not any class with the name. Naming a type that doesn't exist is an error. Type
+//! parameters, which aren't substituted, are the exception: they admit any value.
+
+use std::rc::Rc;
+
+use pkl_ast::{ClassDecl, ClassMember, Expr, NamedType, Span, Type, TypeParameter};
+use pkl_diagnostics::Code;
+
+use crate::class::{find_class, module_of};
+use crate::error::{EvalError, Result};
+use crate::evaluator::Evaluator;
+use crate::modules;
+use crate::runtime::{Def, Env, Frame, Key, Member, Obj, Val};
+use crate::value::ObjectKind;
+
+/// Other classes of `pkl:base`, which are checked by their name since modules can't declare them.
+const BASE_CLASSES: &[&str] = &[
+    "Annotation", "Bytes", "Deprecated", "FileOutput", "IntSeq", "ModuleInfo", "ModuleOutput", "Pair", "Resource",
+];
+
+/// Why a value doesn't have a type.
+enum Mismatch {
+    /// The value (or one of its elements) is of another type
+    Type { expected: String, actual: String },
+    /// The value has the type, but the constraint at the span is `false`
+    Constraint(Span),
+}
+
+impl<'a> Evaluator<'a> {
+    /// Checks the value of a member defined by `layer` against the type its property is declared with, if any.
+    pub(crate) fn check_member_type(
+        &self,
+        receiver: &Rc<Obj<'a>>,
+        layer: &Rc<Obj<'a>>,
+        key: &Key<'a>,
+        member: &Member<'a>,
+        value: &Val<'a>,
+    ) -> Result<()> {
+        let Some((ty, env)) = declared_type(receiver, layer, key, member) else { return Ok(()) };
+        let span = match member.def {
+            Def::Expr { value: Some(value), .. } => value.span(),
+            _ => member.span,
+        };
+        self.check_type(span, value, ty, &env)
+    }
+
+    /// Checks that the value at `span` has the type `ty`, which is written in the scope `env`.
+    pub(crate) fn check_type(&self, span: Span, value: &Val<'a>, ty: &'a Type<'a>, env: &Rc<Env<'a>>) -> Result<()> {
+        let error = match self.mismatch(value, ty, env)? {
+            None => return Ok(()),
+            Some(Mismatch::Type { expected, actual }) => {
+                let message = format!("expected a value of type `{expected}`, but got `{actual}`");
+                EvalError::new(span, message).with_code(Code::TypeMismatch)
+            }
+            Some(Mismatch::Constraint(constraint)) => self.constraint_error(span, value, ty, constraint),
+        };
+        Err(error.with_label(ty.span(), "the declared type"))
+    }
+
+    /// Whether a value has the type `ty`, for `value is Type`.
+    pub(crate) fn is_type(&self, value: &Val<'a>, ty: &'a Type<'a>, env: &Rc<Env<'a>>) -> Result<bool> {
+        Ok(self.mismatch(value, ty, env)?.is_none())
+    }
+
+    /// Evaluates `value as Type`, where the value at `span` is the operand: the value, if it has the type.
+    pub(crate) fn cast(&self, span: Span, value: Val<'a>, ty: &'a Type<'a>, env: &Rc<Env<'a>>) -> Result<Val<'a>> {
+        match self.mismatch(&value, ty, env)? {
+            None => Ok(value),
+            Some(Mismatch::Type { expected, actual }) => {
+                let mut message = format!("can't cast a value of type `{}` to `{}`", value.type_name(), text(ty));
+                // an element or entry is of the wrong type
+                if expected != text(ty) {
+                    message.push_str(&format!(": expected a value of type `{expected}`, but got `{actual}`"));
+                }
+                Err(EvalError::new(span, message).with_code(Code::TypeMismatch))
+            }
+            Some(Mismatch::Constraint(constraint)) => Err(self.constraint_error(span, &value, ty, constraint)),
+        }
+    }
+
+    fn constraint_error(&self, span: Span, value: &Val<'a>, ty: &Type, constraint: Span) -> EvalError {
+        let message = format!("{} doesn't satisfy the constraints of type `{}`", self.describe(value), text(ty));
+        let error = EvalError::new(span, message).with_code(Code::ConstraintViolation);
+        error.with_label(constraint, "this constraint is `false`")
+    }
+
+    /// Why `value` doesn't have the type `ty`, or `None` if it does.
+    fn mismatch(&self, value: &Val<'a>, ty: &'a Type<'a>, env: &Rc<Env<'a>>) -> Result<Option<Mismatch>> {
+        let is = |matches: bool| if matches { None } else { Some(type_mismatch(ty, value)) };
+
+        Ok(match ty {
+            Type::Unknown(_) => None,
+            Type::Nothing(_) => is(false),
+            Type::Module(_) => is(matches!(value, Val::Object(object) if object.kind == module_of(env).kind)),
+            Type::StringLiteral(literal) => is(matches!(value, Val::String(s) if **s == *literal.value)),
+            Type::Parenthesized(ty) => self.mismatch(value, &ty.inner, env)?,
+            Type::Nullable(nullable) => match value {
+                Val::Null => None,
+                value => match self.mismatch(value, &nullable.inner, env)? {
+                    Some(Mismatch::Type { .. }) => is(false),
+                    mismatch => mismatch,
+                },
+            },
+            Type::Union(union) => {
+                let mut satisfied = false;
+                for member in union.members.iter() {
+                    if self.mismatch(value, member, env)?.is_none() {
+                        satisfied = true;
+                        break;
+                    }
+                }
+                is(satisfied)
+            }
+            Type::Function(function) => {
+                is(matches!(value, Val::Function(lambda) if lambda.params.len() == function.params.len()))
+            }
+            Type::Constrained(constrained) => {
+                if let Some(mismatch) = self.mismatch(value, &constrained.base, env)? {
+                    return Ok(Some(mismatch));
+                }
+                let scope = Env::new(Frame::Constraint(value.clone()), Some(env.clone()));
+                for constraint in constrained.constraints.iter() {
+                    if !self.satisfies(constraint, &scope)? {
+                        return Ok(Some(Mismatch::Constraint(constraint.span())));
+                    }
+                }
+                None
+            }
+            Type::Named(named) => self.named_mismatch(value, ty, named, env)?,
+        })
+    }
+
+    fn named_mismatch(
+        &self,
+        value: &Val<'a>,
+        ty: &'a Type<'a>,
+        named: &'a NamedType<'a>,
+        env: &Rc<Env<'a>>,
+    ) -> Result<Option<Mismatch>> {
+        let is = |matches: bool| if matches { None } else { Some(type_mismatch(ty, value)) };
+        let [name] = named.name.parts.as_slice() else { return self.declared_mismatch(value, ty, named, env) };
+        let int_in = |min: i64, max: i64| is(matches!(value, Val::Int(n) if (min..=max).contains(n)));
+        let kind = |kind: ObjectKind| matches!(value, Val::Object(object) if object.kind == kind);
+
+        Ok(match name.name {
+            "Any" => None,
+            "Null" => is(matches!(value, Val::Null)),
+            "NonNull" => is(!matches!(value, Val::Null)),
+            "Boolean" => is(matches!(value, Val::Boolean(_))),
+            "Int" => is(matches!(value, Val::Int(_))),
+            "Float" => is(matches!(value, Val::Float(_))),
+            "Number" => is(matches!(value, Val::Int(_) | Val::Float(_))),
+            "Int8" => int_in(i8::MIN.into(), i8::MAX.into()),
+            "Int16" => int_in(i16::MIN.into(), i16::MAX.into()),
+            "Int32" => int_in(i32::MIN.into(), i32::MAX.into()),
+            "UInt8" => int_in(0, u8::MAX.into()),
+            "UInt16" => int_in(0, u16::MAX.into()),
+            "UInt32" => int_in(0, u32::MAX.into()),
+            "UInt" => int_in(0, i64::MAX),
+            "String" => is(matches!(value, Val::String(_))),
+            "Char" => is(matches!(value, Val::String(s) if s.chars().count() == 1)),
+            "Duration" => is(matches!(value, Val::Duration(_))),
+            "DataSize" => is(matches!(value, Val::DataSize(_))),
+            "Comparable" => {
+                is(matches!(value, Val::Int(_) | Val::Float(_) | Val::String(_) | Val::Duration(_) | Val::DataSize(_)))
+            }
+            "Regex" => is(matches!(value, Val::Regex(_))),
+            "RegexMatch" => is(matches!(value, Val::RegexMatch(_))),
+            "Function" => is(matches!(value, Val::Function(_))),
+            "Class" => is(matches!(value, Val::Class(_))),
+            "Function0" | "Function1" | "Function2" | "Function3" | "Function4" | "Function5" => {
+                let arity = name.name[8..].parse::<usize>().expect("the arity is a digit");
+                is(matches!(value, Val::Function(lambda) if lambda.params.len() == arity))
+            }
+            "Object" => is(matches!(value, Val::Object(_))),
+            "Typed" => is(matches!(value, Val::Object(object) if matches!(object.kind, ObjectKind::Typed(_)))),
+            "Dynamic" => is(kind(ObjectKind::Dynamic)),
+            "Listing" | "Mapping" | "List" | "Set" | "Collection" | "Map" => {
+                self.collection_mismatch(value, ty, name.name, &named.args, env)?
+            }
+            _ => self.declared_mismatch(value, ty, named, env)?,
+        })
+    }
+
+    /// Checks a type that names a type alias or class of a module, like `Bird`, or `birds.Bird` where `birds` is an
+    /// imported module, or else one of the other classes of the standard library.
+    fn declared_mismatch(
+        &self,
+        value: &Val<'a>,
+        ty: &'a Type<'a>,
+        named: &'a NamedType<'a>,
+        env: &Rc<Env<'a>>,
+    ) -> Result<Option<Mismatch>> {
+        let is = |matches: bool| if matches { None } else { Some(type_mismatch(ty, value)) };
+        let (module, scope, name) = match named.name.parts.as_slice() {
+            [name] => (module_of(env).clone(), env.clone(), name.name),
+            [module, name] => match self.resolve(module, env)? {
+                Some(Val::Object(module)) => {
+                    // the aliases of the module are written in its own scope
+                    let scope = Env::new(Frame::Object { this: module.clone(), layer: module.clone() }, None);
+                    (module, scope, name.name)
+                }
+                _ => return Err(unknown_type(named)),
+            },
+            _ => return Err(unknown_type(named)),
+        };
+
+        let alias = module.layers().find_map(|layer| layer.aliases.iter().find(|a| a.name.name == name));
+        if let Some(alias) = alias {
+            // the parameters of generic aliases aren't substituted, so only the others are checked
+            if !alias.type_params.is_empty() {
+                return Ok(None);
+            }
+            return Ok(match self.mismatch(value, &alias.ty, &scope)? {
+                Some(Mismatch::Type { .. }) => is(false),
+                mismatch => mismatch,
+            });
+        }
+        if let Some((_, class)) = find_class(&module, name) {
+            return Ok(is(matches!(value, Val::Object(object) if is_instance(object, class))));
+        }
+        if named.name.parts.len() == 2 {
+            // the classes of standard library modules, like `json.Parser`
+            return match modules::module_class(&module, name) {
+                Some(prototype) => Ok(is(value.type_name() == prototype.kind.class_name())),
+                None => Err(unknown_type(named)),
+            };
+        }
+        Ok(match name {
+            "Mixin" => is(matches!(value, Val::Function(lambda) if lambda.params.len() == 1)),
+            // every module is an instance of its own class
+            "Module" => is(matches!(value, Val::Object(object) if matches!(object.kind, ObjectKind::Typed(_)))),
+            "Renderer" | "ValueRenderer" => {
+                is(matches!(value, Val::Object(object) if object.kind.class_name().ends_with("Renderer")))
+            }
+            name if BASE_CLASSES.contains(&name) || modules::base_class(name).is_some() => {
+                is(value.type_name() == name)
+            }
+            name if is_type_parameter(&module, name, named.span) => None,
+            _ => return Err(unknown_type(named)),
+        })
+    }
+
+    /// Checks a collection type, like `List<Int>`, and the types of its elements and entries.
+    fn collection_mismatch(
+        &self,
+        value: &Val<'a>,
+        ty: &'a Type<'a>,
+        name: &str,
+        args: &'a [Type<'a>],
+        env: &Rc<Env<'a>>,
+    ) -> Result<Option<Mismatch>> {
+        let (elements, entries): (Vec<Val<'a>>, Vec<(Val<'a>, Val<'a>)>) = match (name, value) {
+            ("List" | "Collection", Val::List(elements)) | ("Set" | "Collection", Val::Set(elements)) => {
+                (elements.to_vec(), Vec::new())
+            }
+            ("Map", Val::Map(entries)) => (Vec::new(), entries.to_vec()),
+            ("Listing", Val::Object(object)) if object.kind == ObjectKind::Listing => {
+                if args.is_empty() {
+                    return Ok(None);
+                }
+                (self.elements(object)?, Vec::new())
+            }
+            ("Mapping", Val::Object(object)) if object.kind == ObjectKind::Mapping => {
+                if args.is_empty() {
+                    return Ok(None);
+                }
+                (Vec::new(), self.entries(object)?)
+            }
+            _ => return Ok(Some(type_mismatch(ty, value))),
+        };
+
+        if let [element] = args {
+            for value in &elements {
+                if let Some(mismatch) = self.mismatch(value, element, env)? {
+                    return Ok(Some(mismatch));
+                }
+            }
+        }
+        if let [key_type, value_type] = args {
+            for (key, value) in &entries {
+                if let Some(mismatch) = self.mismatch(key, key_type, env)? {
+                    return Ok(Some(mismatch));
+                }
+                if let Some(mismatch) = self.mismatch(value, value_type, env)? {
+                    return Ok(Some(mismatch));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Evaluates a type constraint, which has to be a `Boolean`.
+    fn satisfies(&self, constraint: &'a Expr<'a>, scope: &Rc<Env<'a>>) -> Result<bool> {
+        match self.eval_expr(constraint, scope)? {
+            Val::Boolean(satisfied) => Ok(satisfied),
+            value => {
+                let message = format!("a type constraint has to be a `Boolean`, but got `{}`", value.type_name());
+                Err(EvalError::new(constraint.span(), message))
+            }
+        }
+    }
+
+    /// A value as error messages mention it: simple values as they're written, and others by their type.
+    pub(crate) fn describe(&self, value: &Val<'a>) -> String {
+        match value {
+            Val::Null | Val::Boolean(_) | Val::Int(_) | Val::Float(_) | Val::Duration(_) | Val::DataSize(_) => {
+                format!("`{}`", self.stringify(Span::default(), value).unwrap_or_default())
+            }
+            Val::String(s) => format!("`{s:?}`"),
+            value => format!("a value of type `{}`", value.type_name()),
+        }
+    }
+}
+
+/// The type that the property of a member defined by `layer` is declared with, if it's declared with one, along
+/// with the scope the type is written in: the nearest declaration among `layer` and the layers it amends.
+pub(crate) fn declared_type<'a>(
+    receiver: &Rc<Obj<'a>>,
+    layer: &Rc<Obj<'a>>,
+    key: &Key<'a>,
+    member: &Member<'a>,
+) -> Option<(&'a Type<'a>, Rc<Env<'a>>)> {
+    let declared = |layer: &Rc<Obj<'a>>, member: &Member<'a>| match member.def {
+        Def::Expr { ty: Some(ty), .. } => {
+            let env = Env::new(Frame::Object { this: receiver.clone(), layer: layer.clone() }, layer.env.clone());
+            Some((ty, env))
+        }
+        _ => None,
+    };
+    match key {
+        Key::Property(_) => layer.layers().find_map(|layer| declared(layer, layer.own_member(key)?)),
+        Key::Local(_) => declared(layer, member),
+        Key::Entry(_) | Key::Element(_) => None,
+    }
+}
+
+fn type_mismatch(ty: &Type, value: &Val) -> Mismatch {
+    Mismatch::Type { expected: text(ty), actual: value.type_name().to_string() }
+}
+
+fn unknown_type(named: &NamedType) -> EvalError {
+    let name = named.name.parts.iter().map(|part| part.name).collect::<Vec<_>>().join(".");
+    EvalError::new(named.span, format!("can't find type `{name}`")).with_code(Code::UnknownClass)
+}
+
+/// Whether an object is an instance of `class` or one of its subclasses: whether the prototype of the class is one of
+/// its layers, which tells apart classes of different modules with the same name.
+fn is_instance<'a>(object: &Rc<Obj<'a>>, class: &ClassDecl<'a>) -> bool {
+    object.layers().any(|layer| layer.class.is_some_and(|prototype| std::ptr::eq(prototype, class)))
+}
+
+/// Whether `name` is a type parameter of a class, type alias, or method of `module` that the type at `span` is
+/// written in.
+fn is_type_parameter(module: &Rc<Obj>, name: &str, span: Span) -> bool {
+    let declares = |params: &[TypeParameter], declaration: Span| {
+        declaration.start <= span.start && span.end <= declaration.end && params.iter().any(|p| p.name.name == name)
+    };
+    module.layers().any(|layer| {
+        let classes = layer.classes.iter().any(|class| {
+            declares(&class.type_params, class.span)
+                || class.members.iter().any(|member| match member {
+                    ClassMember::Method(method) => declares(&method.type_params, method.span),
+                    ClassMember::Property(_) => false,
+                })
+        });
+        classes
+            || layer.aliases.iter().any(|alias| declares(&alias.type_params, alias.span))
+            || layer.methods.iter().any(|method| declares(method.type_params, method.span))
+    })
+}
+
+/// A type as it's written, except for its constraints.
+fn text(ty: &Type) -> String {
+    let join = |types: &[Type], separator: &str| types.iter().map(text).collect::<Vec<_>>().join(separator);
+    match ty {
+        Type::Unknown(_) => "unknown".to_string(),
+        Type::Nothing(_) => "nothing".to_string(),
+        Type::Module(_) => "module".to_string(),
+        Type::StringLiteral(literal) => format!("{:?}", literal.value),
+        Type::Named(named) => {
+            let name = named.name.parts.iter().map(|part| part.name).collect::<Vec<_>>().join(".");
+            if named.args.is_empty() {
+                name
+            } else {
+                format!("{name}<{}>", join(&named.args, ", "))
+            }
+        }
+        Type::Nullable(nullable) => format!("{}?", text(&nullable.inner)),
+        Type::Union(union) => {
+            let members = union.members.iter().enumerate().map(|(index, member)| {
+                let default = if union.default == Some(index) { "*" } else { "" };
+                format!("{default}{}", text(member))
+            });
+            members.collect::<Vec<_>>().join(" | ")
+        }
+        Type::Function(function) => format!("({}) -> {}", join(&function.params, ", "), text(&function.ret)),
+        Type::Constrained(constrained) => format!("{}(...)", text(&constrained.base)),
+        Type::Parenthesized(parenthesized) => format!("({})", text(&parenthesized.inner)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::value::Value;
+    use crate::{evaluate, Error, EvalError};
+
+    fn error(source: &str) -> EvalError {
+        match evaluate(source) {
+            Err(Error::Eval(error)) => error,
+            result => panic!("{source}: {result:?}"),
+        }
+    }
+
+    fn text(source: &str, span: pkl_ast::Span) -> &str {
+        &source[span.start..span.end]
+    }
+
+    #[test]
+    fn simple_types() {
+        let source = "name: String = \"pigeon\"\nage: Int? = null\nweight: Number = 1.5\n\
+                      kind: \"bird\" | \"fish\" = \"bird\"";
+        assert!(evaluate(source).is_ok());
+
+        let source = "name: String = 42";
+        let failure = error(source);
+        assert_eq!(failure.message, "expected a value of type `String`, but got `Int`");
+        assert_eq!(text(source, failure.span), "42");
+        assert_eq!(failure.labels, [(pkl_ast::Span::new(6, 12), "the declared type".to_string())]);
+
+        assert_eq!(error("age: Int? = \"old\"").message, "expected a value of type `Int?`, but got `String`");
+        let message = "expected a value of type `\"bird\" | \"fish\"`, but got `String`";
+        assert_eq!(error("kind: \"bird\" | \"fish\" = \"cat\"").message, message);
+        assert_eq!(error("b: UInt8 = 256").message, "expected a value of type `UInt8`, but got `Int`");
+    }
+
+    #[test]
+    fn constraints() {
+        let source = "port: Int(this > 0 && this < 65536) = 8080\nname: String(length > 3, !isEmpty) = \"pigeon\"";
+        assert!(evaluate(source).is_ok());
+
+        let source = "port: Int(this > 0 && this < 65536) = -1";
+        let failure = error(source);
+        assert_eq!(failure.message, "`-1` doesn't satisfy the constraints of type `Int(...)`");
+        assert_eq!(text(source, failure.span), "-1");
+        let labels: Vec<_> = failure.labels.iter().map(|(span, label)| (text(source, *span), label.as_str())).collect();
+        let constraint = ("this > 0 && this < 65536", "this constraint is `false`");
+        assert_eq!(labels, [constraint, ("Int(this > 0 && this < 65536)", "the declared type")]);
+        let report = failure.report();
+        assert_eq!(report.code, Some(pkl_diagnostics::Code::ConstraintViolation));
+        assert_eq!((report.primary.unwrap().span, report.labels.len()), (38..40, 2));
+
+        let source = "typealias Short = String(length < 4)\nname: Short = \"pigeon\"";
+        assert_eq!(error(source).message, "`\"pigeon\"` doesn't satisfy the constraints of type `Short`");
+        let message = "a type constraint has to be a `Boolean`, but got `Int`";
+        assert_eq!(error("x: Int(1) = 1").message, message);
+    }
+
+    #[test]
+    fn type_tests() {
+        let source = "open class Animal {}\nclass Bird extends Animal {}\nbird = new Bird {}\n\
+                      tests = List(bird is Animal, bird is Bird?, null is Bird?, 1 is Int|String, \"a\" is Int, \
+                      5 is Int(isOdd), bird is Dynamic)\n\
+                      animal = bird as Animal\n\
+                      number = 1 as Number";
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{error:?}"));
+        let module = module.as_object().unwrap();
+        let tests = [true, true, true, true, false, true, false].map(Value::Boolean);
+        assert_eq!(module.property("tests"), Some(&Value::List(tests.to_vec())));
+        assert_eq!(module.property("number"), Some(&Value::Int(1)));
+
+        assert_eq!(error("x = \"a\" as Int").message, "can't cast a value of type `String` to `Int`");
+        let message =
+            "can't cast a value of type `List` to `List<Int>`: expected a value of type `Int`, but got `String`";
+        assert_eq!(error("x = List(1, \"a\") as List<Int>").message, message);
+        let source = "x = 2 as Int(isOdd)";
+        let failure = error(source);
+        assert_eq!(failure.message, "`2` doesn't satisfy the constraints of type `Int(...)`");
+        assert_eq!(text(source, failure.span), "2");
+    }
+
+    #[test]
+    fn objects_and_collections() {
+        let source = "open class Animal { legs: Int }\nclass Bird extends Animal { name: String(!isEmpty) }\n\
+                      pet: Animal = new Bird { legs = 2; name = \"pigeon\" }\n\
+                      numbers: Listing<Int> = new Listing { 1; numbers[0] + 1 }\n\
+                      ages: Mapping<String, Int> = new Mapping { [\"pigeon\"] = 3 }\n\
+                      list: List<Int|String> = List(1, \"a\")";
+        let module = evaluate(source).unwrap_or_else(|error| panic!("{error:?}"));
+        let numbers = module.as_object().unwrap().property("numbers").unwrap();
+        assert_eq!(numbers.as_object().unwrap().elements, [Value::Int(1), Value::Int(2)]);
+
+        let message = "expected a value of type `Int`, but got `String`";
+        assert_eq!(error("class Bird { legs: Int }\nbird = new Bird { legs = \"two\" }").message, message);
+        assert_eq!(error("numbers: Listing<Int> = new Listing { 1; \"two\" }").message, message);
+        assert_eq!(error("m: Map<String, Int> = Map(\"a\", \"b\")").message, message);
+        let message = "expected a value of type `Bird`, but got `Dynamic`";
+        assert_eq!(error("class Bird {}\nbird: Bird = new Dynamic {}").message, message);
+        let message = "`\"\"` doesn't satisfy the constraints of type `String(...)`";
+        assert_eq!(error("class Bird { name: String(!isEmpty) }\nbird = new Bird { name = \"\" }").message, message);
+    }
+
+    #[test]
+    fn declared_types() {
+        assert_eq!(error("x: Strng = 1").message, "can't find type `Strng`");
+        assert_eq!(error("x = 1 is Foo").message, "can't find type `Foo`");
+        let source = "class Box<T> { value: T }\nbox = new Box { value = 1 }\nfunction id<T>(x: T): T = x\ny = id(1)";
+        assert!(evaluate(source).is_ok());
+
+        let dir = std::env::temp_dir().join(format!("pkl-eval-types-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("birds.pkl"), "class Bird { name = \"pigeon\" }\npigeon = new Bird {}").unwrap();
+        let uri = format!("file://{}/main.pkl", dir.display());
+        let evaluate = |source: &str| crate::evaluate_at(&format!("import \"birds.pkl\"\n{source}"), &uri);
+        let error = |source: &str| evaluate(source).unwrap_err().to_string();
+        // a class of the same name in another module is another class
+        let source = "class Bird { name = \"pigeon\" }\n\
+                      tests = List(1 is birds.Bird, birds.pigeon is birds.Bird, birds.pigeon is Bird)";
+        let module = evaluate(source).unwrap();
+        let tests = [false, true, false].map(Value::Boolean);
+        assert_eq!(module.as_object().unwrap().property("tests"), Some(&Value::List(tests.to_vec())));
+        assert_eq!(error("x = 1 as birds.Bird"), "can't cast a value of type `Int` to `birds.Bird`");
+        assert_eq!(error("d: birds.Bird = 5"), "expected a value of type `birds.Bird`, but got `Int`");
+        assert_eq!(error("x = 1 is birds.Owl"), "can't find type `birds.Owl`");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+//! `Duration` and `DataSize` values, written as a number with a unit like `5.min` or `4.gib`.
+//!
+//! Both are a magnitude and a unit, so they share their arithmetic: operands are first expressed in the smaller of
+//! their units, so `1.min + 30.s` is `90.s`. Magnitudes stay integers as long as the operations allow it.
+
+use std::cmp::Ordering;
+
+use pkl_ast::{BinaryExpr, BinaryOp, Span};
+
+use crate::error::{EvalError, Result};
+use crate::expr::{binary_op, overflow};
+use crate::methods::{string_val, Call};
+use crate::runtime::Val;
+use crate::value::{DataSize, DataSizeUnit, Duration, DurationUnit, Number};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Duration(DurationUnit),
+    DataSize(DataSizeUnit),
+}
+
+impl Unit {
+    /// The size of the unit in the smallest unit of its kind.
+    fn factor(self) -> i64 {
+        match self {
+            Unit::Duration(unit) => unit.nanos(),
+            Unit::DataSize(unit) => unit.bytes(),
+        }
+    }
+
+    fn same_kind(self, other: Unit) -> bool {
+        matches!((self, other), (Unit::Duration(_), Unit::Duration(_)) | (Unit::DataSize(_), Unit::DataSize(_)))
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Unit::Duration(unit) => unit.symbol(),
+            Unit::DataSize(unit) => unit.symbol(),
+        }
+    }
+}
+
+/// A `Duration` or `DataSize`.
+#[derive(Debug, Clone, Copy)]
+struct Quantity {
+    value: Number,
+    unit: Unit,
+}
+
+impl Quantity {
+    fn of(value: &Val) -> Option<Quantity> {
+        match value {
+            Val::Duration(duration) => Some(Quantity { value: duration.value, unit: Unit::Duration(duration.unit) }),
+            Val::DataSize(size) => Some(Quantity { value: size.value, unit: Unit::DataSize(size.unit) }),
+            _ => None,
+        }
+    }
+
+    fn into_val<'a>(self) -> Val<'a> {
+        match self.unit {
+            Unit::Duration(unit) => Val::Duration(Duration { value: self.value, unit }),
+            Unit::DataSize(unit) => Val::DataSize(DataSize { value: self.value, unit }),
+        }
+    }
+
+    /// The magnitude of the quantity in another unit of its kind.
+    fn convert(self, unit: Unit) -> Number {
+        let (from, to) = (self.unit.factor(), unit.factor());
+        if let Number::Int(value) = self.value {
+            let scaled = value as i128 * from as i128;
+            if scaled % to as i128 == 0 {
+                if let Ok(value) = i64::try_from(scaled / to as i128) {
+                    return Number::Int(value);
+                }
+            }
+        }
+        Number::Float(self.value.as_f64() * from as f64 / to as f64)
+    }
+
+    fn to_unit(self, unit: Unit) -> Quantity {
+        Quantity { value: self.convert(unit), unit }
+    }
+}
+
+/// Reads a unit property of a number, like the `min` of `5.min`.
+pub(crate) fn quantity<'a>(value: Number, symbol: &str) -> Option<Val<'a>> {
+    let unit = match (DurationUnit::from_symbol(symbol), DataSizeUnit::from_symbol(symbol)) {
+        (Some(unit), _) => Unit::Duration(unit),
+        (None, Some(unit)) => Unit::DataSize(unit),
+        (None, None) => return None,
+    };
+    Some(Quantity { value, unit }.into_val())
+}
+
+/// Applies an arithmetic or comparison operator with a `Duration` or `DataSize` operand, or returns `None` if the
+/// operator isn't defined for the operands.
+pub(crate) fn quantity_op<'a>(binary: &BinaryExpr, left: &Val<'a>, right: &Val<'a>) -> Result<Option<Val<'a>>> {
+    use BinaryOp::*;
+
+    let value = match (Quantity::of(left), Quantity::of(right)) {
+        (Some(l), Some(r)) if l.unit.same_kind(r.unit) => match binary.op {
+            Add | Sub | Lt | LtEq | Gt | GtEq => {
+                let unit = if l.unit.factor() <= r.unit.factor() { l.unit } else { r.unit };
+                match binary_op(binary, number_val(l.convert(unit)), number_val(r.convert(unit)))? {
+                    Val::Boolean(result) => Val::Boolean(result),
+                    value => Quantity { value: number(&value), unit }.into_val(),
+                }
+            }
+            Div => Val::Float(l.value.as_f64() * l.unit.factor() as f64 / (r.value.as_f64() * r.unit.factor() as f64)),
+            _ => return Ok(None),
+        },
+        (Some(l), None) if matches!(binary.op, Mul | Div | IntDiv | Rem) && is_number(right) => {
+            let value = binary_op(binary, number_val(l.value), right.clone())?;
+            Quantity { value: number(&value), unit: l.unit }.into_val()
+        }
+        (None, Some(r)) if binary.op == Mul && is_number(left) => {
+            let value = binary_op(binary, left.clone(), number_val(r.value))?;
+            Quantity { value: number(&value), unit: r.unit }.into_val()
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+/// `-quantity`.
+pub(crate) fn negate<'a>(span: Span, value: &Val<'a>) -> Result<Val<'a>> {
+    let quantity = Quantity::of(value).expect("only quantities are negated here");
+    let value = match quantity.value {
+        Number::Int(value) => Number::Int(value.checked_neg().ok_or_else(|| overflow(span))?),
+        Number::Float(value) => Number::Float(-value),
+    };
+    Ok(Quantity { value, ..quantity }.into_val())
+}
+
+/// Whether two quantities of the same kind are equal, whatever their units.
+pub(crate) fn quantities_equal(left: &Val, right: &Val) -> bool {
+    match (Quantity::of(left), Quantity::of(right)) {
+        (Some(l), Some(r)) if l.unit.same_kind(r.unit) => {
+            let unit = if l.unit.factor() <= r.unit.factor() { l.unit } else { r.unit };
+            match (l.convert(unit), r.convert(unit)) {
+                (Number::Int(l), Number::Int(r)) => l == r,
+                (l, r) => l.as_f64() == r.as_f64(),
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Orders two durations or two data sizes by the quantities they measure, or `None` for other values.
+pub(crate) fn compare_quantities(left: &Val, right: &Val) -> Option<Ordering> {
+    match (Quantity::of(left), Quantity::of(right)) {
+        (Some(l), Some(r)) if l.unit.same_kind(r.unit) => {
+            let unit = if l.unit.factor() <= r.unit.factor() { l.unit } else { r.unit };
+            match (l.convert(unit), r.convert(unit)) {
+                (Number::Int(l), Number::Int(r)) => Some(l.cmp(&r)),
+                (l, r) => Some(l.as_f64().partial_cmp(&r.as_f64()).unwrap_or(Ordering::Equal)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A duration in nanoseconds, or a data size in bytes.
+pub(crate) fn smallest_units(value: &Val) -> Option<f64> {
+    let quantity = Quantity::of(value)?;
+    Some(match quantity.value {
+        Number::Int(n) => (n as i128 * quantity.unit.factor() as i128) as f64,
+        Number::Float(n) => n * quantity.unit.factor() as f64,
+    })
+}
+
+pub(crate) fn quantity_property<'a>(value: &Val<'a>, name: &str) -> Option<Val<'a>> {
+    let quantity = Quantity::of(value)?;
+    Some(match (quantity.unit, name) {
+        (_, "value") => number_val(quantity.value),
+        (_, "unit") => string_val(quantity.unit.symbol()),
+        (_, "isPositive") => Val::Boolean(quantity.value.as_f64() >= 0.0),
+        (Unit::DataSize(unit), "isBinaryUnit") => Val::Boolean(unit.is_binary()),
+        (Unit::DataSize(unit), "isDecimalUnit") => Val::Boolean(unit.is_decimal()),
+        _ => return None,
+    })
+}
+
+pub(crate) fn quantity_method<'a>(call: &Call, value: &Val<'a>, args: Vec<Val<'a>>) -> Result<Option<Val<'a>>> {
+    let Some(quantity) = Quantity::of(value) else { return Ok(None) };
+    let value = match (quantity.unit, call.name) {
+        (_, "toUnit") => {
+            let [symbol] = call.args(args)?;
+            let symbol = call.string(&symbol)?;
+            let unit = match quantity.unit {
+                Unit::Duration(_) => DurationUnit::from_symbol(symbol).map(Unit::Duration),
+                Unit::DataSize(_) => DataSizeUnit::from_symbol(symbol).map(Unit::DataSize),
+            };
+            let Some(unit) = unit else {
+                let message = format!("`{symbol}` isn't a unit of `{}`", value.type_name());
+                return Err(EvalError::new(call.span, message));
+            };
+            quantity.to_unit(unit).into_val()
+        }
+        (_, "isBetween") => {
+            let [start, end] = call.args(args)?;
+            let in_base = |value: &Val| match Quantity::of(value) {
+                Some(other) if other.unit.same_kind(quantity.unit) => {
+                    Ok(other.value.as_f64() * other.unit.factor() as f64)
+                }
+                _ => Err(EvalError::new(call.span, format!("expected a `{}` argument", value.type_name()))),
+            };
+            let this = in_base(value)?;
+            Val::Boolean(in_base(&start)? <= this && this <= in_base(&end)?)
+        }
+        (Unit::DataSize(unit), "toBinaryUnit" | "toDecimalUnit") => {
+            let [] = call.args(args)?;
+            let binary = call.name == "toBinaryUnit";
+            let keep = unit == DataSizeUnit::Bytes || unit.is_binary() == binary;
+            let target = if keep { unit } else { counterpart(unit) };
+            quantity.to_unit(Unit::DataSize(target)).into_val()
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+/// The binary unit of the same order as a decimal unit, or the other way round, like `kib` for `kb`.
+fn counterpart(unit: DataSizeUnit) -> DataSizeUnit {
+    let index = DataSizeUnit::ALL.iter().position(|u| *u == unit).expect("every unit is listed");
+    // after bytes, each decimal unit is followed by the binary unit of the same order
+    if index % 2 == 1 {
+        DataSizeUnit::ALL[index + 1]
+    } else {
+        DataSizeUnit::ALL[index - 1]
+    }
+}
+
+fn number_val<'a>(value: Number) -> Val<'a> {
+    match value {
+        Number::Int(value) => Val::Int(value),
+        Number::Float(value) => Val::Float(value),
+    }
+}
+
+fn number(value: &Val) -> Number {
+    match value {
+        Val::Int(value) => Number::Int(*value),
+        Val::Float(value) => Number::Float(*value),
+        _ => unreachable!("arithmetic on numbers results in a number"),
+    }
+}
+
+fn is_number(value: &Val) -> bool {
+    matches!(value, Val::Int(_) | Val::Float(_))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluate_expr;
+    use crate::value::{DataSize, DataSizeUnit, Duration, DurationUnit, Number, Value};
+
+    fn eval(source: &str) -> Value {
+        evaluate_expr(source).unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    fn duration(value: Number, unit: DurationUnit) -> Value {
+        Value::Duration(Duration { value, unit })
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(eval("5.min"), duration(Number::Int(5), DurationUnit::Minutes));
+        assert_eq!(eval("1.min + 30.s"), duration(Number::Int(90), DurationUnit::Seconds));
+        assert_eq!(eval("1.5.h * 2"), duration(Number::Float(3.0), DurationUnit::Hours));
+        assert_eq!(eval("2 * 3.d"), duration(Number::Int(6), DurationUnit::Days));
+        assert_eq!(eval("1.h / 30.min"), Value::Float(2.0));
+        assert_eq!(eval("-(1.ms)"), duration(Number::Int(-1), DurationUnit::Millis));
+        assert_eq!(eval("1.min == 60.s && 1.min < 61.s"), Value::Boolean(true));
+        assert_eq!(eval("90.s.toUnit(\"min\")"), duration(Number::Float(1.5), DurationUnit::Minutes));
+        assert_eq!(eval("2.min.toUnit(\"s\").value"), Value::Int(120));
+        assert_eq!(eval("\"\\(2.5.s)\""), Value::String("2.5.s".into()));
+        assert_eq!(eval("1.s.isBetween(1.ms, 1.min)"), Value::Boolean(true));
+
+        let error = evaluate_expr("1.s + 1.mb").unwrap_err().to_string();
+        assert_eq!(error, "operator `+` isn't defined for `Duration` and `DataSize`");
+    }
+
+    #[test]
+    fn data_sizes() {
+        let size = |value, unit| Value::DataSize(DataSize { value, unit });
+        assert_eq!(eval("1.kib + 1.b"), size(Number::Int(1025), DataSizeUnit::Bytes));
+        assert_eq!(eval("2.gb.toBinaryUnit().unit"), Value::String("gib".into()));
+        assert_eq!(eval("1.mib.toDecimalUnit()"), size(Number::Float(1.048576), DataSizeUnit::Megabytes));
+        assert_eq!(eval("1.kib.isBinaryUnit && !1.kb.isBinaryUnit"), Value::Boolean(true));
+        assert_eq!(eval("1024.b == 1.kib"), Value::Boolean(true));
+    }
+}
@@ -0,0 +1,304 @@
+//! The values a Pkl program evaluates to.
+
+use std::fmt;
+
+use indexmap::IndexMap;
+
+/// A fully evaluated Pkl value.
+///
+/// Unlike the source it came from, a value has no unevaluated parts left: rendering it or reading it from Rust can't
+/// fail (except for functions, classes, and regexes, which have no data representation).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Duration(Duration),
+    DataSize(DataSize),
+    List(Vec<Value>),
+    /// The elements of a `Set`, in insertion order
+    Set(Vec<Value>),
+    /// The entries of a `Map`, in insertion order
+    Map(Vec<(Value, Value)>),
+    Object(Object),
+    /// A function value such as a lambda, of which only the number of parameters is kept
+    Function { arity: usize },
+    /// A class, of which only the name is kept
+    Class { name: String },
+    /// A `Regex`, of which only the pattern is kept
+    Regex { pattern: String },
+}
+
+impl Value {
+    /// The name of the Pkl class of this value, as used in error messages.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Value::Null => "Null",
+            Value::Boolean(_) => "Boolean",
+            Value::Int(_) => "Int",
+            Value::Float(_) => "Float",
+            Value::String(_) => "String",
+            Value::Duration(_) => "Duration",
+            Value::DataSize(_) => "DataSize",
+            Value::List(_) => "List",
+            Value::Set(_) => "Set",
+            Value::Map(_) => "Map",
+            Value::Object(object) => object.kind.class_name(),
+            Value::Function { .. } => "Function",
+            Value::Class { .. } => "Class",
+            Value::Regex { .. } => "Regex",
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Object> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of an object, which decides what members it may have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectKind {
+    /// Properties, entries, and elements alike
+    Dynamic,
+    /// Only elements
+    Listing,
+    /// Only entries
+    Mapping,
+    /// An instance of a class (or a module), with the properties the class declares
+    Typed(String),
+}
+
+impl ObjectKind {
+    pub fn class_name(&self) -> &str {
+        match self {
+            ObjectKind::Dynamic => "Dynamic",
+            ObjectKind::Listing => "Listing",
+            ObjectKind::Mapping => "Mapping",
+            ObjectKind::Typed(name) => name,
+        }
+    }
+}
+
+/// An evaluated object: properties, entries, and elements, each in definition order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub kind: ObjectKind,
+    pub properties: IndexMap<String, Value>,
+    pub entries: Vec<(Value, Value)>,
+    pub elements: Vec<Value>,
+}
+
+impl Object {
+    pub fn new(kind: ObjectKind) -> Self {
+        Object { kind, properties: IndexMap::new(), entries: Vec::new(), elements: Vec::new() }
+    }
+
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties.get(name)
+    }
+
+    pub fn entry(&self, key: &Value) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, value)| value)
+    }
+}
+
+/// A module's `output`, which says what evaluating the module produces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleOutput {
+    /// The value to render, `output.value`, which is the module itself unless the module sets it
+    pub value: Value,
+    /// The text to write as is, `output.text`, if the module sets it
+    pub text: Option<String>,
+    /// The renderer to render the value with, `output.renderer`, if the module sets it
+    pub renderer: Option<Object>,
+    /// The files of `output.files`, by their paths, which have no files of their own
+    pub files: Vec<(String, ModuleOutput)>,
+}
+
+/// The magnitude of a [`Duration`] or [`DataSize`], which keeps whether it was written as an integer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Int(value) => write!(f, "{value}"),
+            Number::Float(value) => f.write_str(&pkl_stdlib::number::float_to_string(*value)),
+        }
+    }
+}
+
+/// A quantity of time like `5.min`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Duration {
+    pub value: Number,
+    pub unit: DurationUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DurationUnit {
+    Nanos,
+    Micros,
+    Millis,
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+}
+
+impl DurationUnit {
+    pub const ALL: [DurationUnit; 7] = [
+        DurationUnit::Nanos,
+        DurationUnit::Micros,
+        DurationUnit::Millis,
+        DurationUnit::Seconds,
+        DurationUnit::Minutes,
+        DurationUnit::Hours,
+        DurationUnit::Days,
+    ];
+
+    /// The property the unit is written with, as in `5.min`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            DurationUnit::Nanos => "ns",
+            DurationUnit::Micros => "us",
+            DurationUnit::Millis => "ms",
+            DurationUnit::Seconds => "s",
+            DurationUnit::Minutes => "min",
+            DurationUnit::Hours => "h",
+            DurationUnit::Days => "d",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|unit| unit.symbol() == symbol)
+    }
+
+    /// The number of nanoseconds in the unit.
+    pub fn nanos(self) -> i64 {
+        match self {
+            DurationUnit::Nanos => 1,
+            DurationUnit::Micros => 1_000,
+            DurationUnit::Millis => 1_000_000,
+            DurationUnit::Seconds => 1_000_000_000,
+            DurationUnit::Minutes => 60 * 1_000_000_000,
+            DurationUnit::Hours => 60 * 60 * 1_000_000_000,
+            DurationUnit::Days => 24 * 60 * 60 * 1_000_000_000,
+        }
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.value, self.unit.symbol())
+    }
+}
+
+/// A quantity of data like `4.gib`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataSize {
+    pub value: Number,
+    pub unit: DataSizeUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DataSizeUnit {
+    Bytes,
+    Kilobytes,
+    Kibibytes,
+    Megabytes,
+    Mebibytes,
+    Gigabytes,
+    Gibibytes,
+    Terabytes,
+    Tebibytes,
+    Petabytes,
+    Pebibytes,
+}
+
+impl DataSizeUnit {
+    pub const ALL: [DataSizeUnit; 11] = [
+        DataSizeUnit::Bytes,
+        DataSizeUnit::Kilobytes,
+        DataSizeUnit::Kibibytes,
+        DataSizeUnit::Megabytes,
+        DataSizeUnit::Mebibytes,
+        DataSizeUnit::Gigabytes,
+        DataSizeUnit::Gibibytes,
+        DataSizeUnit::Terabytes,
+        DataSizeUnit::Tebibytes,
+        DataSizeUnit::Petabytes,
+        DataSizeUnit::Pebibytes,
+    ];
+
+    /// The property the unit is written with, as in `4.gib`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            DataSizeUnit::Bytes => "b",
+            DataSizeUnit::Kilobytes => "kb",
+            DataSizeUnit::Kibibytes => "kib",
+            DataSizeUnit::Megabytes => "mb",
+            DataSizeUnit::Mebibytes => "mib",
+            DataSizeUnit::Gigabytes => "gb",
+            DataSizeUnit::Gibibytes => "gib",
+            DataSizeUnit::Terabytes => "tb",
+            DataSizeUnit::Tebibytes => "tib",
+            DataSizeUnit::Petabytes => "pb",
+            DataSizeUnit::Pebibytes => "pib",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|unit| unit.symbol() == symbol)
+    }
+
+    /// The number of bytes in the unit.
+    pub fn bytes(self) -> i64 {
+        match self {
+            DataSizeUnit::Bytes => 1,
+            DataSizeUnit::Kilobytes => 1_000,
+            DataSizeUnit::Kibibytes => 1 << 10,
+            DataSizeUnit::Megabytes => 1_000_000,
+            DataSizeUnit::Mebibytes => 1 << 20,
+            DataSizeUnit::Gigabytes => 1_000_000_000,
+            DataSizeUnit::Gibibytes => 1 << 30,
+            DataSizeUnit::Terabytes => 1_000_000_000_000,
+            DataSizeUnit::Tebibytes => 1 << 40,
+            DataSizeUnit::Petabytes => 1_000_000_000_000_000,
+            DataSizeUnit::Pebibytes => 1 << 50,
+        }
+    }
+
+    /// Whether the unit is a power of 1024, like `kib`, rather than a power of 1000 (or bytes, which are both).
+    pub fn is_binary(self) -> bool {
+        self.bytes() % 1000 != 0 || self == DataSizeUnit::Bytes
+    }
+
+    /// Whether the unit is a power of 1000, like `kb` (or bytes, which are both).
+    pub fn is_decimal(self) -> bool {
+        self.bytes() % 1000 == 0 || self == DataSizeUnit::Bytes
+    }
+}
+
+impl fmt::Display for DataSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.value, self.unit.symbol())
+    }
+}
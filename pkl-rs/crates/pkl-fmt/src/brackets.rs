@@ -0,0 +1,62 @@
+//! Finding the `<` and `>` tokens that delimit type arguments and parameters rather than compare values.
+
+use std::collections::HashSet;
+
+use pkl_ast::visit::{walk, Visit};
+use pkl_ast::*;
+
+/// Collects the offsets of every `<` and `>` that belongs to a type argument or type parameter list.
+pub(crate) fn type_brackets(source: &str, module: &Module) -> HashSet<usize> {
+    let mut collector = TypeBrackets { source, offsets: HashSet::new() };
+    collector.visit_module(module);
+    collector.offsets
+}
+
+struct TypeBrackets<'s> {
+    source: &'s str,
+    offsets: HashSet<usize>,
+}
+
+impl TypeBrackets<'_> {
+    /// Records the first `<` after `after`, and the first `>` after `last_end`.
+    fn record(&mut self, after: usize, last_end: usize) {
+        if let Some(pos) = self.source[after..].find('<') {
+            self.offsets.insert(after + pos);
+        }
+        if let Some(pos) = self.source[last_end..].find('>') {
+            self.offsets.insert(last_end + pos);
+        }
+    }
+
+    fn record_params(&mut self, name: &Ident, params: &[TypeParameter]) {
+        if let Some(last) = params.last() {
+            self.record(name.span.end, last.span.end);
+        }
+    }
+}
+
+impl<'a> Visit<'a> for TypeBrackets<'_> {
+    fn visit_type(&mut self, ty: &Type<'a>) {
+        if let Type::Named(named) = ty {
+            if let Some(last) = named.args.last() {
+                self.record(named.name.span.end, last.span().end);
+            }
+        }
+        walk::walk_type(self, ty);
+    }
+
+    fn visit_class(&mut self, class: &ClassDecl<'a>) {
+        self.record_params(&class.name, &class.type_params);
+        walk::walk_class(self, class);
+    }
+
+    fn visit_type_alias(&mut self, alias: &TypeAlias<'a>) {
+        self.record_params(&alias.name, &alias.type_params);
+        walk::walk_type_alias(self, alias);
+    }
+
+    fn visit_class_method(&mut self, method: &ClassMethod<'a>) {
+        self.record_params(&method.name, &method.type_params);
+        walk::walk_class_method(self, method);
+    }
+}
@@ -0,0 +1,404 @@
+//! An opinionated formatter for Pkl source.
+//!
+//! The formatter works on the lossless token stream of [`Lexer::tokenize_with_trivia`] rather than the syntax tree,
+//! so it keeps every comment and never reorders or drops code. It only decides the whitespace between tokens:
+//!
+//! * lines are indented by two spaces per enclosing bracket, plus one level for lines continuing an expression;
+//! * tokens on a line are separated by a single space or none, depending on the tokens (`a + b`, `f(x)`, `x: Int`);
+//! * runs of blank lines are collapsed into one, and blank lines at the start and end of the file are removed;
+//! * trailing commas before a closing bracket are removed.
+//!
+//! Line breaks stay where they are, and the text of string literals and comments is kept as is (apart from trailing
+//! whitespace of comments). Every line ends the way most lines of the source do, with `\r\n` or `\n`, including the
+//! lines of multiline strings. The syntax tree is only consulted to tell the `<` and `>` of type arguments from
+//! comparisons.
+
+#![forbid(unsafe_code)]
+
+mod brackets;
+mod spacing;
+
+use std::collections::HashSet;
+
+use oxc_allocator::Allocator;
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_lexer::token::{Span, TokenKind};
+use pkl_lexer::trivia::TriviaKind;
+use pkl_lexer::Lexer;
+
+use crate::spacing::{continues_expression, is_continuation_start, is_operand_end, needs_space};
+
+const INDENT: &str = "  ";
+
+/// Formats a module, returning it unchanged if it doesn't parse.
+pub fn format_source(source: &str) -> String {
+    try_format(source).unwrap_or_else(|_| source.to_string())
+}
+
+/// Formats a module, or returns the problems that prevent it from being formatted.
+///
+/// Source with syntax errors isn't formatted, since the formatter couldn't be sure to keep its meaning.
+pub fn try_format(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let alloc = Allocator::default();
+    let result = pkl_parser::parse_module(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        return Err(result.diagnostics);
+    }
+
+    let type_brackets = brackets::type_brackets(source, &result.node);
+    let pieces = pieces(&alloc, source);
+
+    let mut formatter = Formatter {
+        source,
+        type_brackets,
+        out: String::with_capacity(source.len()),
+        line_indent: 0,
+        openers: Vec::new(),
+        last: None,
+        last_on_previous_line: None,
+        at_line_start: true,
+    };
+    formatter.format(&pieces);
+
+    Ok(with_line_endings(formatter.out, source))
+}
+
+/// Ends every line of `out` with the line ending most lines of `source` end with, which the formatter writes as
+/// `\n` and the text of multiline strings and comments keeps as it was.
+fn with_line_endings(out: String, source: &str) -> String {
+    let crlf = source.matches("\r\n").count();
+    let lf = source.matches('\n').count() - crlf;
+    let out = if out.contains("\r\n") { out.replace("\r\n", "\n") } else { out };
+    if crlf > lf {
+        out.replace('\n', "\r\n")
+    } else {
+        out
+    }
+}
+
+/// A significant part of the source: everything but whitespace.
+#[derive(Debug, Clone, Copy)]
+enum Piece {
+    Token(TokenKind, Span),
+    /// A comment, or a shebang line
+    Comment(Span),
+    /// Some number of line breaks
+    Newlines(usize),
+}
+
+fn pieces(alloc: &Allocator, source: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+
+    for token in Lexer::tokenize_with_trivia(alloc, source) {
+        for trivia in &token.leading {
+            match trivia.kind {
+                TriviaKind::Whitespace => {}
+                TriviaKind::Newline => match pieces.last_mut() {
+                    Some(Piece::Newlines(count)) => *count += 1,
+                    _ => pieces.push(Piece::Newlines(1)),
+                },
+                TriviaKind::LineComment | TriviaKind::BlockComment | TriviaKind::Shebang => {
+                    pieces.push(Piece::Comment(trivia.span));
+                }
+            }
+        }
+        if token.token.kind != TokenKind::Eof {
+            pieces.push(Piece::Token(token.token.kind, token.token.span));
+        }
+    }
+
+    pieces
+}
+
+/// What was written last, as far as spacing is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Last {
+    Token {
+        kind: TokenKind,
+        /// Whether a `-`, `*`, or `!` is a prefix operator
+        prefix: bool,
+        /// Whether a `<` or `>` delimits type arguments
+        type_bracket: bool,
+    },
+    Comment,
+}
+
+struct Formatter<'s> {
+    source: &'s str,
+    type_brackets: HashSet<usize>,
+    out: String,
+    /// Indentation level of the current line
+    line_indent: usize,
+    /// The indentation level of the line each unclosed bracket was opened on
+    openers: Vec<usize>,
+    last: Option<Last>,
+    /// The last token of the previous line, which decides whether the current line continues an expression
+    last_on_previous_line: Option<TokenKind>,
+    at_line_start: bool,
+}
+
+impl Formatter<'_> {
+    fn format(&mut self, pieces: &[Piece]) {
+        let mut newlines = 0;
+
+        for (index, piece) in pieces.iter().enumerate() {
+            let (kind, span) = match *piece {
+                Piece::Newlines(count) => {
+                    if !self.out.is_empty() {
+                        newlines = count.min(2);
+                    }
+                    continue;
+                }
+                Piece::Token(kind, span) => (Some(kind), span),
+                Piece::Comment(span) => (None, span),
+            };
+
+            if kind == Some(TokenKind::Comma) && self.closes_next(&pieces[index + 1..]) {
+                continue;
+            }
+
+            if newlines > 0 {
+                self.last_on_previous_line = match self.last {
+                    Some(Last::Token { kind, .. }) => Some(kind),
+                    _ => None,
+                };
+                for _ in 0..newlines {
+                    self.out.push('\n');
+                }
+                newlines = 0;
+                self.at_line_start = true;
+            }
+
+            let type_bracket = self.type_brackets.contains(&span.start);
+            let prefix = match kind {
+                Some(TokenKind::Bang) => true,
+                Some(TokenKind::Minus | TokenKind::Star) => !self.last.is_some_and(is_operand_end),
+                _ => false,
+            };
+
+            if self.at_line_start {
+                self.start_line(kind);
+            } else if let Some(last) = self.last {
+                if needs_space(last, kind, type_bracket) {
+                    self.out.push(' ');
+                }
+            }
+
+            let text = &self.source[span.start..span.end];
+            let is_comment = matches!(kind, None | Some(TokenKind::DocComment));
+            self.out.push_str(if is_comment { text.trim_end() } else { text });
+
+            match kind {
+                Some(TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace | TokenKind::InterpolationStart) => {
+                    self.openers.push(self.line_indent);
+                }
+                Some(TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace | TokenKind::InterpolationEnd) => {
+                    self.openers.pop();
+                }
+                _ => {}
+            }
+
+            self.last = Some(match kind {
+                Some(kind) => Last::Token { kind, prefix, type_bracket },
+                None => Last::Comment,
+            });
+        }
+
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Indents a new line starting with a token of the given kind (or a comment).
+    fn start_line(&mut self, kind: Option<TokenKind>) {
+        let closes = matches!(kind, Some(TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace));
+        let mut indent = match self.openers.last() {
+            Some(&base) if closes => base,
+            Some(&base) => base + 1,
+            None => 0,
+        };
+
+        let previous_continues = self.last_on_previous_line.is_some_and(continues_expression);
+        if !closes && (kind.is_some_and(is_continuation_start) || previous_continues) {
+            indent += 1;
+        }
+
+        for _ in 0..indent {
+            self.out.push_str(INDENT);
+        }
+        self.line_indent = indent;
+        self.at_line_start = false;
+    }
+
+    /// Whether the next token after (possibly) some comments and line breaks is a closing bracket, which makes a
+    /// comma before it a trailing comma.
+    fn closes_next(&self, rest: &[Piece]) -> bool {
+        for piece in rest {
+            match *piece {
+                Piece::Newlines(_) | Piece::Comment(_) => {}
+                Piece::Token(TokenKind::RParen | TokenKind::RBracket, _) => return true,
+                Piece::Token(TokenKind::Gt, span) => return self.type_brackets.contains(&span.start),
+                Piece::Token(..) => return false,
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Formats `source`, checking that formatting is idempotent and keeps the tokens of the source, apart from their
+    /// line endings.
+    fn format(source: &str) -> String {
+        let formatted = try_format(source).unwrap_or_else(|diagnostics| panic!("{source}: {diagnostics:?}"));
+        assert_eq!(try_format(&formatted).unwrap(), formatted, "not idempotent");
+
+        let alloc = Allocator::default();
+        let tokens = |text: &str| -> Vec<String> {
+            pieces(&alloc, text)
+                .into_iter()
+                .filter_map(|piece| match piece {
+                    Piece::Token(TokenKind::Comma, _) | Piece::Newlines(_) => None,
+                    Piece::Token(_, span) | Piece::Comment(span) => {
+                        Some(text[span.start..span.end].trim_end().replace("\r\n", "\n"))
+                    }
+                })
+                .collect()
+        };
+        assert_eq!(tokens(&formatted), tokens(source));
+
+        formatted
+    }
+
+    #[test]
+    fn indents_by_nesting() {
+        assert_eq!(
+            format("foo {\nbar {\n      baz = 1\n        }\n list = List(1,\n2)\n}"),
+            "foo {\n  bar {\n    baz = 1\n  }\n  list = List(1,\n    2)\n}\n"
+        );
+    }
+
+    #[test]
+    fn normalizes_spacing() {
+        assert_eq!(
+            format("x=1+2*-3\ny:Listing<String?> =new Listing<String?>{\"a\"}\nz = f( x , y )[0]!!.w"),
+            "x = 1 + 2 * -3\ny: Listing<String?> = new Listing<String?> { \"a\" }\nz = f(x, y)[0]!!.w\n"
+        );
+        assert_eq!(format("b = a<b&&!c\nt: *\"a\"|\"b\"\n"), "b = a < b && !c\nt: *\"a\" | \"b\"\n");
+        assert_eq!(
+            format("o {}\nl = (x)->x\nfunction f<T>(a:T):T=a"),
+            "o {}\nl = (x) -> x\nfunction f<T>(a: T): T = a\n"
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_collapses_blank_lines() {
+        assert_eq!(
+            format("\n\n// head   \nfoo = 1 // one\n\n\n\n/// doc\nbar = /* two */ 2\n\n"),
+            "// head\nfoo = 1 // one\n\n/// doc\nbar = /* two */ 2\n"
+        );
+    }
+
+    #[test]
+    fn continuation_lines() {
+        assert_eq!(
+            format("x = list\n.map((it) -> it)\n|> f\ny =\nnew {\na = 1\n}"),
+            "x = list\n  .map((it) -> it)\n  |> f\ny =\n  new {\n    a = 1\n  }\n"
+        );
+    }
+
+    #[test]
+    fn removes_trailing_commas() {
+        assert_eq!(format("x = f(1, 2,)\ny = f(\n1,\n2, // two\n)"), "x = f(1, 2)\ny = f(\n  1,\n  2 // two\n)\n");
+    }
+
+    #[test]
+    fn strings_are_kept_verbatim() {
+        let source = "s = \"\"\"\n      a \\(b   +c)\n      \"\"\"\nt = \"x  \\(1+2)\"\n";
+        assert_eq!(format(source), "s = \"\"\"\n      a \\(b + c)\n      \"\"\"\nt = \"x  \\(1 + 2)\"\n");
+    }
+
+    #[test]
+    fn line_endings() {
+        let source = "s = \"\"\"\r\n  a\r\n  \"\"\"\r\nx {\r\ny=1\r\n}\r\n";
+        let formatted = format(source);
+        assert_eq!(formatted, "s = \"\"\"\r\n  a\r\n  \"\"\"\r\nx {\r\n  y = 1\r\n}\r\n");
+        assert_eq!(format(&formatted), formatted);
+        // the line ending of most lines wins
+        assert_eq!(format("s = \"\"\"\r\n  a\n  \"\"\"\nx = 1\n"), "s = \"\"\"\n  a\n  \"\"\"\nx = 1\n");
+    }
+
+    /// A seeded generator of pseudo-random numbers (xorshift), so that a failing property test can be replayed.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Writes a random body member, taking its shape from `shape` and the spacing within and the indentation of its
+    /// lines from `space`.
+    fn member(shape: &mut Rng, space: &mut Rng, out: &mut String, depth: usize) {
+        const EXPRS: &[&[&str]] = &[
+            &["1", "+", "2", "*", "-", "3"],
+            &["f", "(", "x", ",", "y", ")"],
+            &["\"bird\""],
+            &["a", "??", "b", "|>", "c"],
+            &["new", "Listing", "{", "1", "2", "}"],
+            &["(", "x", ")", "->", "x", ".", "y"],
+        ];
+        out.push_str(["pigeon", "parrot", "`my bird`"][shape.below(3)]);
+        if depth < 3 && shape.below(3) == 0 {
+            out.push_str(" {");
+            for _ in 0..shape.below(3) {
+                newlines(shape, space, out);
+                member(shape, space, out, depth + 1);
+            }
+            newlines(shape, space, out);
+            out.push('}');
+        } else {
+            for token in ["="].iter().chain(EXPRS[shape.below(EXPRS.len())]) {
+                out.push_str(&" ".repeat(space.below(3) + 1));
+                out.push_str(token);
+            }
+        }
+    }
+
+    /// Ends a line and indents the next, leaving up to one blank line in between.
+    fn newlines(shape: &mut Rng, space: &mut Rng, out: &mut String) {
+        out.push_str(&"\n".repeat(shape.below(2) + 1));
+        out.push_str(&" ".repeat(space.below(8)));
+    }
+
+    /// Formatting random layouts of the same members is idempotent, keeps their tokens, and gives the same source
+    /// whatever the spacing and indentation.
+    #[test]
+    fn random_layouts() {
+        let mut seeds = Rng(0x5eed_cafe);
+        for _ in 0..200 {
+            let (shape, space) = (seeds.below(usize::MAX) as u64 | 1, seeds.below(usize::MAX) as u64 | 1);
+            let layout = |mut space: Rng| {
+                let (mut shape, mut out) = (Rng(shape), String::new());
+                for _ in 0..shape.below(4) + 1 {
+                    member(&mut shape, &mut space, &mut out, 0);
+                    newlines(&mut shape, &mut space, &mut out);
+                }
+                out
+            };
+            let (source, other) = (layout(Rng(space)), layout(Rng(space.rotate_left(17))));
+            assert_eq!(format(&source), format(&other), "{source:?}\n{other:?}");
+        }
+    }
+
+    #[test]
+    fn broken_source_is_left_alone() {
+        assert!(try_format("foo = (").is_err());
+        assert_eq!(format_source("foo = ("), "foo = (");
+    }
+}
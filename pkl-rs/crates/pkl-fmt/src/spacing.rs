@@ -0,0 +1,138 @@
+//! Deciding the whitespace between two tokens on the same line, and which lines continue the previous one.
+
+use pkl_lexer::token::TokenKind;
+
+use crate::Last;
+
+/// Whether a token of this kind can end an operand, so that a `-` or `*` after it is a binary operator and a `[`
+/// after it subscripts it.
+pub(crate) fn is_operand_end(last: Last) -> bool {
+    let Last::Token { kind, type_bracket, .. } = last else { return false };
+    match kind {
+        TokenKind::Gt => type_bracket,
+        kind => matches!(
+            kind,
+            TokenKind::Identifier
+                | TokenKind::IntLiteral
+                | TokenKind::FloatLiteral
+                | TokenKind::StringLiteral
+                | TokenKind::StringEnd
+                | TokenKind::True
+                | TokenKind::False
+                | TokenKind::Null
+                | TokenKind::This
+                | TokenKind::Outer
+                | TokenKind::Module
+                | TokenKind::Unknown
+                | TokenKind::Nothing
+                | TokenKind::RParen
+                | TokenKind::RBracket
+                | TokenKind::RBrace
+                | TokenKind::BangBang
+                | TokenKind::Question
+        ),
+    }
+}
+
+/// Whether to put a space between what was written last and a token of kind `next` (`None` for a comment).
+///
+/// `type_bracket` tells whether `next` is a `<` or `>` around type arguments.
+pub(crate) fn needs_space(last: Last, next: Option<TokenKind>, type_bracket: bool) -> bool {
+    let Some(next) = next else { return true };
+    let Last::Token { kind: last_kind, prefix, type_bracket: last_type_bracket } = last else { return true };
+
+    // nothing separates the parts of a string from its interpolations
+    if matches!(last_kind, TokenKind::StringStart | TokenKind::StringPart | TokenKind::InterpolationStart)
+        || matches!(next, TokenKind::InterpolationStart | TokenKind::InterpolationEnd)
+        || (last_kind == TokenKind::InterpolationEnd && matches!(next, TokenKind::StringPart | TokenKind::StringEnd))
+    {
+        return false;
+    }
+
+    if prefix
+        || (last_kind == TokenKind::Lt && last_type_bracket)
+        || matches!(
+            last_kind,
+            TokenKind::LParen
+                | TokenKind::LBracket
+                | TokenKind::At
+                | TokenKind::Spread
+                | TokenKind::SpreadQuestion
+                | TokenKind::Dot
+                | TokenKind::QuestionDot
+        )
+    {
+        return false;
+    }
+
+    match next {
+        TokenKind::RParen
+        | TokenKind::RBracket
+        | TokenKind::Comma
+        | TokenKind::Semicolon
+        | TokenKind::Dot
+        | TokenKind::QuestionDot
+        | TokenKind::BangBang
+        | TokenKind::Question
+        | TokenKind::Colon => false,
+        TokenKind::Lt | TokenKind::Gt if type_bracket => false,
+        TokenKind::LParen => !(matches!(
+            last_kind,
+            TokenKind::Identifier
+                | TokenKind::RParen
+                | TokenKind::RBracket
+                | TokenKind::Throw
+                | TokenKind::Trace
+                | TokenKind::Import
+                | TokenKind::ImportStar
+                | TokenKind::Read
+                | TokenKind::ReadQuestion
+                | TokenKind::ReadStar
+        ) || (last_kind == TokenKind::Gt && last_type_bracket)),
+        TokenKind::LBracket => !is_operand_end(last),
+        TokenKind::RBrace => last_kind != TokenKind::LBrace,
+        _ => true,
+    }
+}
+
+/// Whether a line starting with a token of this kind continues the expression on the line before it.
+pub(crate) fn is_continuation_start(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Dot
+            | TokenKind::QuestionDot
+            | TokenKind::PipeGt
+            | TokenKind::OrOr
+            | TokenKind::AndAnd
+            | TokenKind::QuestionQuestion
+            | TokenKind::Plus
+            | TokenKind::Pipe
+    )
+}
+
+/// Whether a line ending with a token of this kind is continued by the next line.
+pub(crate) fn continues_expression(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Eq
+            | TokenKind::Arrow
+            | TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Star
+            | TokenKind::Slash
+            | TokenKind::TildeSlash
+            | TokenKind::Percent
+            | TokenKind::StarStar
+            | TokenKind::EqEq
+            | TokenKind::NotEq
+            | TokenKind::LtEq
+            | TokenKind::GtEq
+            | TokenKind::AndAnd
+            | TokenKind::OrOr
+            | TokenKind::QuestionQuestion
+            | TokenKind::PipeGt
+            | TokenKind::Pipe
+            | TokenKind::Is
+            | TokenKind::As
+    )
+}
@@ -0,0 +1,321 @@
+//! Generates Rust types from the classes and typealiases of a Pkl module, like `pkl-gen-java` and `pkl-gen-kotlin`
+//! do for the JVM, so that Rust applications can deserialize their configuration with `pkl_eval::from_str`.
+//!
+//! Each class becomes a struct with a field for each of its properties, including the ones it inherits, and the
+//! module's own properties become a struct named after the module. `///` doc comments are kept. Types map to their
+//! Rust counterparts: `T?` to `Option<T>`, `Listing<T>` and `List<T>` to `Vec<T>`, `Set<T>` to `HashSet<T>`, and
+//! `Mapping<K, V>` and `Map<K, V>` to `HashMap<K, V>`. A union of string literals becomes an enum, and any other
+//! typealias a type alias. Constraints are dropped, since they were checked when the module was evaluated.
+//!
+//! Hidden and local properties and methods aren't rendered, so they're left out too. Types Rust has no counterpart
+//! for, like functions and other unions, are reported as diagnostics.
+//!
+//! `pkl-schema` goes the other way, generating Pkl classes and templates from Rust types.
+
+#![forbid(unsafe_code)]
+
+mod names;
+mod types;
+
+use std::collections::HashMap;
+
+use oxc_allocator::Allocator;
+use pkl_ast::{ClassDecl, ClassProperty, DocComment, Module, ModuleMember, ModifierKind, Type, TypeAlias};
+use pkl_lexer::diagnostic::Diagnostic;
+
+use names::{field_name, type_name};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenOptions {
+    /// The name of the struct for the module's own properties; without one, it's named after the module's
+    /// `module` clause, or `Module` without that
+    pub module_struct: Option<String>,
+    /// The traits the types derive besides `serde::Deserialize`
+    pub derives: Vec<String>,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        let derives = ["Debug", "Clone", "PartialEq"].map(String::from).to_vec();
+        GenOptions { module_struct: None, derives }
+    }
+}
+
+/// Generates Rust types from a module's source, or returns the problems that prevent it: syntax errors, and Pkl
+/// types Rust has no counterpart for.
+///
+/// ```
+/// let source = "/// A bird.\nclass Bird {\n  name: String\n  wingspan: Float?\n}";
+/// let rust = pkl_gen_rust::generate(source, &pkl_gen_rust::GenOptions::default()).unwrap();
+///
+/// assert!(rust.contains("/// A bird.\n#[derive(Debug, Clone, PartialEq, serde::Deserialize)]\npub struct Bird {\n"));
+/// assert!(rust.contains("    pub wingspan: Option<f64>,\n"));
+/// ```
+pub fn generate(source: &str, options: &GenOptions) -> Result<String, Vec<Diagnostic>> {
+    let alloc = Allocator::default();
+    let result = pkl_parser::parse_module(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        return Err(result.diagnostics);
+    }
+    generate_module(&result.node, options)
+}
+
+/// Generates Rust types from a parsed module.
+pub fn generate_module(module: &Module, options: &GenOptions) -> Result<String, Vec<Diagnostic>> {
+    let mut generator = Generator {
+        options,
+        classes: HashMap::new(),
+        aliases: HashMap::new(),
+        out: String::from("// Generated by `pkl-lang gen-rust`; don't edit it by hand.\n"),
+        enums: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    for member in &module.members {
+        match member {
+            ModuleMember::Class(class) => _ = generator.classes.insert(class.name.name, class),
+            ModuleMember::TypeAlias(alias) => _ = generator.aliases.insert(alias.name.name, alias),
+            ModuleMember::Property(_) | ModuleMember::Method(_) => {}
+        }
+    }
+
+    let properties: Vec<_> = module
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ModuleMember::Property(property) => Some(property),
+            _ => None,
+        })
+        .collect();
+    if !properties.is_empty() {
+        let name = match (&options.module_struct, &module.header.name) {
+            (Some(name), _) => name.clone(),
+            (None, Some(name)) => type_name(name.parts.last().map_or("Module", |part| part.name)),
+            (None, None) => "Module".to_string(),
+        };
+        let fields = properties.into_iter().map(|property| (property, property.ty.as_ref())).collect();
+        generator.struct_item(&name, module.header.doc.as_ref(), fields);
+    }
+    for member in &module.members {
+        match member {
+            ModuleMember::Class(class) => generator.class(class),
+            ModuleMember::TypeAlias(alias) => generator.alias(alias),
+            ModuleMember::Property(_) | ModuleMember::Method(_) => {}
+        }
+    }
+
+    if generator.diagnostics.is_empty() {
+        Ok(generator.out)
+    } else {
+        Err(generator.diagnostics)
+    }
+}
+
+struct Generator<'m, 'a> {
+    options: &'m GenOptions,
+    classes: HashMap<&'a str, &'m ClassDecl<'a>>,
+    aliases: HashMap<&'a str, &'m TypeAlias<'a>>,
+    out: String,
+    /// The enums for the unions of string literals in the last struct, with their variants, written after it
+    enums: Vec<(String, Vec<&'a str>)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'m, 'a> Generator<'m, 'a> {
+    fn class(&mut self, class: &'m ClassDecl<'a>) {
+        if !class.type_params.is_empty() {
+            let message = format!("can't generate a Rust type for the generic class `{}`", class.name.name);
+            self.diagnostics.push(Diagnostic::new(class.name.span, message));
+            return;
+        }
+        let Some(properties) = self.properties(class, &mut Vec::new()) else { return };
+        self.struct_item(&type_name(class.name.name), class.doc.as_ref(), properties);
+    }
+
+    /// The properties of a class, including the ones it inherits from the classes of the module, each with the type
+    /// it's declared with, which an override without a type keeps.
+    fn properties(
+        &mut self,
+        class: &'m ClassDecl<'a>,
+        seen: &mut Vec<&'a str>,
+    ) -> Option<Vec<(&'m ClassProperty<'a>, Option<&'m Type<'a>>)>> {
+        seen.push(class.name.name);
+        let mut properties = match &class.extends {
+            None => Vec::new(),
+            Some(Type::Named(parent)) => match parent.name.parts.as_slice() {
+                [name] if self.classes.contains_key(name.name) && !seen.contains(&name.name) => {
+                    self.properties(self.classes[name.name], seen)?
+                }
+                _ => {
+                    let parent = pkl_codegen::Codegen::new(Default::default()).build_type(class.extends.as_ref()?);
+                    let message = format!("can't generate a Rust type for a class extending `{parent}`");
+                    self.diagnostics.push(Diagnostic::new(class.name.span, message));
+                    return None;
+                }
+            },
+            Some(parent) => {
+                self.diagnostics.push(Diagnostic::new(parent.span(), "a class can only extend another class"));
+                return None;
+            }
+        };
+
+        for member in &class.members {
+            let pkl_ast::ClassMember::Property(property) = member else { continue };
+            match properties.iter_mut().find(|(inherited, _)| inherited.name.name == property.name.name) {
+                Some(inherited) => *inherited = (property, property.ty.as_ref().or(inherited.1)),
+                None => properties.push((property, property.ty.as_ref())),
+            }
+        }
+        Some(properties)
+    }
+
+    fn alias(&mut self, alias: &'m TypeAlias<'a>) {
+        let name = type_name(alias.name.name);
+        if !alias.type_params.is_empty() {
+            let message = format!("can't generate a Rust type for the generic typealias `{}`", alias.name.name);
+            self.diagnostics.push(Diagnostic::new(alias.name.span, message));
+            return;
+        }
+        self.out.push('\n');
+        if let Some(literals) = types::string_literals(&alias.ty) {
+            self.enum_item(&name, alias.doc.as_ref(), &literals);
+            return;
+        }
+        match self.rust_type(&alias.ty, &name) {
+            Ok(ty) => {
+                self.doc(alias.doc.as_ref(), "");
+                self.out.push_str(&format!("pub type {name} = {ty};\n"));
+                self.write_enums();
+            }
+            Err(diagnostic) => self.diagnostics.push(diagnostic),
+        }
+    }
+
+    fn struct_item(
+        &mut self,
+        name: &str,
+        doc: Option<&DocComment>,
+        properties: Vec<(&'m ClassProperty<'a>, Option<&'m Type<'a>>)>,
+    ) {
+        self.out.push('\n');
+        self.doc(doc, "");
+        self.derives();
+        self.out.push_str(&format!("pub struct {name} {{\n"));
+        for (property, ty) in properties {
+            let modifiers = &property.modifiers;
+            if modifiers.has(ModifierKind::Hidden) || modifiers.has(ModifierKind::Local) {
+                continue;
+            }
+            let Some(ty) = ty else {
+                let name = property.name.name;
+                let message = format!("add a type to the property `{name}` to generate a Rust type for it");
+                self.diagnostics.push(Diagnostic::new(property.name.span, message));
+                continue;
+            };
+            let ty = match self.rust_type(ty, &format!("{name}{}", type_name(property.name.name))) {
+                Ok(ty) => ty,
+                Err(diagnostic) => {
+                    self.diagnostics.push(diagnostic);
+                    continue;
+                }
+            };
+            self.doc(property.doc.as_ref(), "    ");
+            let field = field_name(property.name.name);
+            if field.trim_start_matches("r#") != property.name.name {
+                self.out.push_str(&format!("    #[serde(rename = {:?})]\n", property.name.name));
+            }
+            self.out.push_str(&format!("    pub {field}: {ty},\n"));
+        }
+        self.out.push_str("}\n");
+        self.write_enums();
+    }
+
+    fn write_enums(&mut self) {
+        for (name, literals) in std::mem::take(&mut self.enums) {
+            self.out.push('\n');
+            self.enum_item(&name, None, &literals);
+        }
+    }
+
+    fn enum_item(&mut self, name: &str, doc: Option<&DocComment>, literals: &[&str]) {
+        self.doc(doc, "");
+        self.derives();
+        self.out.push_str(&format!("pub enum {name} {{\n"));
+        for literal in literals {
+            let variant = type_name(literal);
+            if variant != *literal {
+                self.out.push_str(&format!("    #[serde(rename = {literal:?})]\n"));
+            }
+            self.out.push_str(&format!("    {variant},\n"));
+        }
+        self.out.push_str("}\n");
+    }
+
+    fn doc(&mut self, doc: Option<&DocComment>, indent: &str) {
+        for line in doc.iter().flat_map(|doc| doc.lines.iter()) {
+            let space = if line.is_empty() { "" } else { " " };
+            self.out.push_str(&format!("{indent}///{space}{line}\n"));
+        }
+    }
+
+    fn derives(&mut self) {
+        let derives = self.options.derives.iter().map(String::as_str);
+        let derives: Vec<&str> = derives.chain(["serde::Deserialize"]).collect();
+        self.out.push_str(&format!("#[derive({})]\n", derives.join(", ")));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generate, GenOptions};
+
+    fn gen(source: &str) -> String {
+        generate(source, &GenOptions::default()).unwrap_or_else(|diagnostics| panic!("{source}: {diagnostics:?}"))
+    }
+
+    #[test]
+    fn structs() {
+        let source = "module com.example.AppConfig\n\n\
+                      /// Where to listen.\nserver: Server\nhidden secret: String\n\n\
+                      open class Base {\n  /// In seconds.\n  timeout: Int(this > 0)\n}\n\n\
+                      class Server extends Base {\n  hostName: String\n  type: \"tcp\" | \"udp\"\n\
+                      ports: Listing<UInt16>\n  labels: Mapping<String, String?>\n  timeout = 5\n}";
+        let expected = "// Generated by `pkl-lang gen-rust`; don't edit it by hand.\n\n\
+                        #[derive(Debug, Clone, PartialEq, serde::Deserialize)]\npub struct AppConfig {\n\
+                        \x20   /// Where to listen.\n    pub server: Server,\n}\n\n\
+                        #[derive(Debug, Clone, PartialEq, serde::Deserialize)]\npub struct Base {\n\
+                        \x20   /// In seconds.\n    pub timeout: i64,\n}\n\n\
+                        #[derive(Debug, Clone, PartialEq, serde::Deserialize)]\npub struct Server {\n\
+                        \x20   pub timeout: i64,\n    #[serde(rename = \"hostName\")]\n    pub host_name: String,\n\
+                        \x20   pub r#type: ServerType,\n    pub ports: Vec<u16>,\n\
+                        \x20   pub labels: std::collections::HashMap<String, Option<String>>,\n}\n\n\
+                        #[derive(Debug, Clone, PartialEq, serde::Deserialize)]\npub enum ServerType {\n\
+                        \x20   #[serde(rename = \"tcp\")]\n    Tcp,\n    #[serde(rename = \"udp\")]\n    Udp,\n}\n";
+        assert_eq!(gen(source), expected);
+    }
+
+    #[test]
+    fn typealiases() {
+        let source = "/// A region.\ntypealias Region = \"us-east-1\" | \"eu-west-1\"\ntypealias Names = Set<String>";
+        let expected = "// Generated by `pkl-lang gen-rust`; don't edit it by hand.\n\n/// A region.\n\
+                        #[derive(Debug, Clone, PartialEq, serde::Deserialize)]\npub enum Region {\n\
+                        \x20   #[serde(rename = \"us-east-1\")]\n    UsEast1,\n\
+                        \x20   #[serde(rename = \"eu-west-1\")]\n    EuWest1,\n}\n\n\
+                        pub type Names = std::collections::HashSet<String>;\n";
+        assert_eq!(gen(source), expected);
+    }
+
+    #[test]
+    fn unsupported_types() {
+        let source = "class A {\n  f: (Int) -> Int\n  u: Int | String\n  x = 1\n}";
+        let diagnostics = generate(source, &GenOptions::default()).unwrap_err();
+        let messages: Vec<_> = diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "can't generate a Rust type for `(Int) -> Int`",
+                "can't generate a Rust type for `Int | String`",
+                "add a type to the property `x` to generate a Rust type for it",
+            ]
+        );
+    }
+}
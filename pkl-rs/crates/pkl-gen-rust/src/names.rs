@@ -0,0 +1,57 @@
+//! Turning Pkl names into Rust ones.
+
+/// Rust's keywords, which a field named after one is written as a raw identifier for
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct", "trait",
+    "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// The `snake_case` name of a field for a property, like `host_name` for `hostName`.
+pub(crate) fn field_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut field = String::with_capacity(name.len());
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !field.is_empty() && !field.ends_with('_') {
+                field.push('_');
+            }
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        // A word starts at an uppercase letter after a lowercase one, or before one, as in `HTTPServer`
+        let starts_word = c.is_uppercase()
+            && (previous.is_some_and(|p| p.is_lowercase() || p.is_numeric())
+                || previous.is_some_and(char::is_uppercase) && next.is_some_and(|n| n.is_lowercase()));
+        if starts_word && !field.ends_with('_') {
+            field.push('_');
+        }
+        field.extend(c.to_lowercase());
+    }
+    let field = field.trim_end_matches('_').to_string();
+    match field.as_str() {
+        "" => "_".to_string(),
+        "self" | "super" | "crate" => format!("{field}_"),
+        field if KEYWORDS.contains(&field) => format!("r#{field}"),
+        field if field.starts_with(|c: char| c.is_numeric()) => format!("_{field}"),
+        _ => field,
+    }
+}
+
+/// The `PascalCase` name of a type or variant, like `UsEast1` for `us-east-1`.
+pub(crate) fn type_name(name: &str) -> String {
+    let mut pascal = String::with_capacity(name.len());
+    for word in name.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+        let mut chars = word.chars();
+        pascal.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+        pascal.push_str(chars.as_str());
+    }
+    match pascal.as_str() {
+        "" => "Empty".to_string(),
+        "Self" => "Self_".to_string(),
+        pascal if pascal.starts_with(|c: char| c.is_numeric()) => format!("_{pascal}"),
+        _ => pascal,
+    }
+}
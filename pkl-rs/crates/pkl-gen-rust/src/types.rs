@@ -0,0 +1,88 @@
+use pkl_ast::{Type, UnionType};
+use pkl_lexer::diagnostic::Diagnostic;
+
+use crate::names::type_name;
+use crate::Generator;
+
+impl<'m, 'a> Generator<'m, 'a> {
+    /// The Rust type for a Pkl type, where `name` is what an enum for a union of string literals in it is called.
+    pub(crate) fn rust_type(&mut self, ty: &'m Type<'a>, name: &str) -> Result<String, Diagnostic> {
+        let unsupported = || {
+            let pkl = pkl_codegen::Codegen::new(Default::default()).build_type(ty);
+            Diagnostic::new(ty.span(), format!("can't generate a Rust type for `{pkl}`"))
+        };
+        Ok(match ty {
+            Type::StringLiteral(_) => "String".to_string(),
+            Type::Nullable(nullable) => format!("Option<{}>", self.rust_type(&nullable.inner, name)?),
+            Type::Constrained(constrained) => self.rust_type(&constrained.base, name)?,
+            Type::Parenthesized(parenthesized) => self.rust_type(&parenthesized.inner, name)?,
+            Type::Union(union) => match string_literals(ty) {
+                Some(literals) => {
+                    self.enums.push((name.to_string(), literals));
+                    name.to_string()
+                }
+                None => match nullable_union(union) {
+                    Some(ty) => format!("Option<{}>", self.rust_type(ty, name)?),
+                    None => return Err(unsupported()),
+                },
+            },
+            Type::Named(named) => {
+                let parts: Vec<&str> = named.name.parts.iter().map(|part| part.name).collect();
+                let mut args = Vec::new();
+                for arg in &named.args {
+                    args.push(self.rust_type(arg, name)?);
+                }
+                match (parts.as_slice(), args.as_slice()) {
+                    (["String"], []) => "String".to_string(),
+                    (["Boolean"], []) => "bool".to_string(),
+                    (["Int"], []) => "i64".to_string(),
+                    (["Int8"], []) => "i8".to_string(),
+                    (["Int16"], []) => "i16".to_string(),
+                    (["Int32"], []) => "i32".to_string(),
+                    (["UInt"], []) => "u64".to_string(),
+                    (["UInt8"], []) => "u8".to_string(),
+                    (["UInt16"], []) => "u16".to_string(),
+                    (["UInt32"], []) => "u32".to_string(),
+                    (["Float" | "Number"], []) => "f64".to_string(),
+                    // Deserialized as they're written in Pkl, like `5.min`
+                    (["Duration" | "DataSize"], []) => "String".to_string(),
+                    (["Listing" | "List" | "Collection"], [element]) => format!("Vec<{element}>"),
+                    (["Set"], [element]) => format!("std::collections::HashSet<{element}>"),
+                    (["Mapping" | "Map"], [key, value]) => format!("std::collections::HashMap<{key}, {value}>"),
+                    ([name], []) if self.classes.contains_key(name) || self.aliases.contains_key(name) => {
+                        type_name(name)
+                    }
+                    _ => return Err(unsupported()),
+                }
+            }
+            Type::Unknown(_) | Type::Nothing(_) | Type::Module(_) | Type::Function(_) => return Err(unsupported()),
+        })
+    }
+}
+
+/// The strings of a union of string literals, like `"tcp" | "udp"`.
+pub(crate) fn string_literals<'a>(ty: &Type<'a>) -> Option<Vec<&'a str>> {
+    match ty {
+        Type::Union(union) => union
+            .members
+            .iter()
+            .map(|member| match member {
+                Type::StringLiteral(literal) => Some(literal.value),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// The other member of a union with `Null`, like `String | Null`, which is the same as `String?`.
+fn nullable_union<'m, 'a>(union: &'m UnionType<'a>) -> Option<&'m Type<'a>> {
+    let is_null = |ty: &Type| match ty {
+        Type::Named(named) => matches!(named.name.parts.as_slice(), [name] if name.name == "Null"),
+        _ => false,
+    };
+    match union.members.as_slice() {
+        [ty, null] | [null, ty] if is_null(null) && !is_null(ty) => Some(ty),
+        _ => None,
+    }
+}
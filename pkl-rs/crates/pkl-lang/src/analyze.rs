@@ -0,0 +1,100 @@
+//! `pkl-lang analyze`, which reports what can be told about modules without evaluating them.
+//!
+//! `pkl-lang analyze imports` prints the graph of the imports of modules as JSON, for build systems to know which
+//! modules to evaluate again when files change:
+//!
+//! ```json
+//! {"imports":{"file:///birds/index.pkl":["file:///birds/pigeon.pkl"],"file:///birds/pigeon.pkl":[]}}
+//! ```
+//!
+//! Each module, the given ones and those they import in turn, is listed by its URI with the URIs of the files and
+//! URLs it imports, amends, or extends.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Subcommand};
+use pkl_eval::EvaluatorOptions;
+use pkl_stdlib::json::Json;
+
+use crate::Failure;
+
+/// Analyze Pkl modules without evaluating them
+#[derive(Debug, Args)]
+pub struct AnalyzeArgs {
+    #[command(subcommand)]
+    command: AnalyzeCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum AnalyzeCommand {
+    Imports(ImportsArgs),
+}
+
+/// Print the graph of the imports of modules as JSON
+#[derive(Debug, Args)]
+struct ImportsArgs {
+    /// The modules to start from
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+pub fn run(args: AnalyzeArgs) -> ExitCode {
+    let AnalyzeCommand::Imports(args) = args.command;
+    match imports(&args) {
+        Ok(graph) => {
+            println!("{graph}");
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn imports(args: &ImportsArgs) -> Result<Json, Failure> {
+    let mut modules = Vec::with_capacity(args.files.len());
+    for path in &args.files {
+        let name = path.display().to_string();
+        let source = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {name}: {err}"))?;
+        // imports are resolved relative to the module, so it's known by its canonical path
+        let path = path.canonicalize().map_err(|err| format!("couldn't read {name}: {err}"))?;
+        modules.push((format!("file://{}", path.display()), source));
+    }
+    let modules: Vec<(&str, &str)> = modules.iter().map(|(uri, source)| (uri.as_str(), source.as_str())).collect();
+    let graph = pkl_eval::import_graph(&modules, &EvaluatorOptions::default());
+    Ok(graph_json(&graph))
+}
+
+fn graph_json(graph: &pkl_eval::ImportGraph) -> Json {
+    let imports = graph.imports.iter().map(|(uri, imports)| {
+        (uri.clone(), Json::Array(imports.iter().map(|import| Json::from(import.as_str())).collect()))
+    });
+    Json::object([("imports", Json::Object(imports.collect()))])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn graph_as_json() {
+        let dir = std::env::temp_dir().join(format!("pkl-analyze-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.pkl"), "amends \"b.pkl\"\nimport \"c.pkl\"\n").unwrap();
+        std::fs::write(dir.join("b.pkl"), "import \"c.pkl\"\n").unwrap();
+        std::fs::write(dir.join("c.pkl"), "x = 1\n").unwrap();
+
+        let graph = imports(&ImportsArgs { files: vec![dir.join("a.pkl")] }).unwrap();
+        let uri = |name: &str| format!("file://{}", dir.canonicalize().unwrap().join(name).display());
+        let expected = format!(
+            "{{\"imports\":{{\"{a}\":[\"{b}\",\"{c}\"],\"{b}\":[\"{c}\"],\"{c}\":[]}}}}",
+            a = uri("a.pkl"),
+            b = uri("b.pkl"),
+            c = uri("c.pkl")
+        );
+        assert_eq!(graph.to_string(), expected);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,298 @@
+//! The syntax tree of a module as JSON, for `pkl-lang parse --dump ast --format json`.
+//!
+//! Nodes are written the way their `Debug` forms read: a struct or a tuple variant becomes an object with its name
+//! under `type`, and its fields by name, or under `value` for a single unnamed field or `values` for several. `None`
+//! is `null`, boxes and vectors are what they hold, and variants without fields are their names as strings.
+//! Integers are written exactly, and floats that aren't finite as the strings `inf`, `-inf`, and `NaN`.
+
+use pkl_ast::*;
+use pkl_stdlib::json::Json;
+
+/// A syntax tree node, or a value in one, that can be written as JSON.
+pub(crate) trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+/// A struct node, with its name under `type` and then its fields.
+fn node<'k>(name: &'k str, fields: impl IntoIterator<Item = (&'k str, Json)>) -> Json {
+    Json::object([("type", Json::from(name))].into_iter().chain(fields))
+}
+
+/// A variant with unnamed fields.
+fn variant(name: &str, mut values: std::vec::Vec<Json>) -> Json {
+    match values.len() {
+        1 => node(name, [("value", values.remove(0))]),
+        _ => node(name, [("values", Json::Array(values))]),
+    }
+}
+
+/// Implements [`ToJson`] for structs with a lifetime, writing every one of their named fields.
+macro_rules! structs {
+    ($($name:ident { $($field:ident),* })*) => {$(
+        impl ToJson for $name<'_> {
+            fn to_json(&self) -> Json {
+                node(stringify!($name), [$((stringify!($field), self.$field.to_json())),*])
+            }
+        }
+    )*};
+}
+
+/// Implements [`ToJson`] for enums without fields, as the names of their variants.
+macro_rules! names {
+    ($($name:ident)*) => {$(
+        impl ToJson for $name {
+            fn to_json(&self) -> Json {
+                Json::from(format!("{self:?}"))
+            }
+        }
+    )*};
+}
+
+structs! {
+    Ident { span, name }
+    QualifiedName { span, parts }
+    StringConstant { span, value }
+    DocComment { span, lines }
+    Annotation { span, name, body }
+    Module { span, header, members }
+    ModuleHeader { span, doc, annotations, modifiers, name, extends, imports }
+    ModuleExtends { span, kind, uri }
+    Import { span, glob, uri, alias }
+    ClassDecl { span, doc, annotations, modifiers, name, type_params, extends, members }
+    ClassProperty { span, doc, annotations, modifiers, name, ty, value, bodies }
+    ClassMethod { span, doc, annotations, modifiers, name, type_params, params, return_type, body }
+    TypeAlias { span, doc, annotations, modifiers, name, type_params, ty }
+    Parameter { span, name, ty }
+    TypeParameter { span, variance, name }
+    StringLiteral { span, multiline, parts }
+    MemberExpr { span, receiver, name, null_safe }
+    CallExpr { span, receiver, name, args, null_safe }
+    SuperExpr { span, name, args }
+    SubscriptExpr { span, receiver, index }
+    UnaryExpr { span, op, operand }
+    NonNullExpr { span, operand }
+    BinaryExpr { span, op, left, right }
+    TypeTestExpr { span, value, ty }
+    IfExpr { span, condition, then, otherwise }
+    LetExpr { span, param, value, body }
+    LambdaExpr { span, params, body }
+    NewExpr { span, ty, body }
+    AmendExpr { span, parent, body }
+    UnaryKeywordExpr { span, value }
+    ImportExpr { span, glob, uri }
+    ReadExpr { span, kind, uri }
+    ParenthesizedExpr { span, expr }
+    ObjectBody { span, params, members }
+    ObjectProperty { span, doc, modifiers, name, ty, value, bodies }
+    ObjectMethod { span, doc, modifiers, name, params, return_type, body }
+    ObjectEntry { span, key, value, bodies }
+    ObjectElement { span, value }
+    ObjectMemberPredicate { span, predicate, value, bodies }
+    ObjectSpread { span, nullable, value }
+    ForGenerator { span, key, value, iterable, body }
+    WhenGenerator { span, condition, body, else_body }
+    NamedType { span, name, args }
+    NullableType { span, inner }
+    UnionType { span, members, default }
+    FunctionType { span, params, ret }
+    ConstrainedType { span, base, constraints }
+    ParenthesizedType { span, inner }
+}
+
+names!(ModifierKind ExtendsKind Variance UnaryOp BinaryOp ReadKind);
+
+impl ToJson for Span {
+    fn to_json(&self) -> Json {
+        node("Span", [("start", self.start.to_json()), ("end", self.end.to_json())])
+    }
+}
+
+impl ToJson for Modifier {
+    fn to_json(&self) -> Json {
+        node("Modifier", [("span", self.span.to_json()), ("kind", self.kind.to_json())])
+    }
+}
+
+impl ToJson for Modifiers<'_> {
+    fn to_json(&self) -> Json {
+        variant("Modifiers", vec![self.0.to_json()])
+    }
+}
+
+impl ToJson for ModuleMember<'_> {
+    fn to_json(&self) -> Json {
+        match self {
+            ModuleMember::Class(class) => variant("Class", vec![class.to_json()]),
+            ModuleMember::TypeAlias(alias) => variant("TypeAlias", vec![alias.to_json()]),
+            ModuleMember::Property(property) => variant("Property", vec![property.to_json()]),
+            ModuleMember::Method(method) => variant("Method", vec![method.to_json()]),
+        }
+    }
+}
+
+impl ToJson for ClassMember<'_> {
+    fn to_json(&self) -> Json {
+        match self {
+            ClassMember::Property(property) => variant("Property", vec![property.to_json()]),
+            ClassMember::Method(method) => variant("Method", vec![method.to_json()]),
+        }
+    }
+}
+
+impl ToJson for Expr<'_> {
+    fn to_json(&self) -> Json {
+        let (name, values) = match self {
+            Expr::Null(span) => ("Null", vec![span.to_json()]),
+            Expr::Bool(span, b) => ("Bool", vec![span.to_json(), b.to_json()]),
+            Expr::Int(span, n) => ("Int", vec![span.to_json(), n.to_json()]),
+            Expr::Float(span, n) => ("Float", vec![span.to_json(), n.to_json()]),
+            Expr::String(literal) => ("String", vec![literal.to_json()]),
+            Expr::This(span) => ("This", vec![span.to_json()]),
+            Expr::Outer(span) => ("Outer", vec![span.to_json()]),
+            Expr::Module(span) => ("Module", vec![span.to_json()]),
+            Expr::Ident(ident) => ("Ident", vec![ident.to_json()]),
+            Expr::Member(expr) => ("Member", vec![expr.to_json()]),
+            Expr::Call(expr) => ("Call", vec![expr.to_json()]),
+            Expr::Super(expr) => ("Super", vec![expr.to_json()]),
+            Expr::Subscript(expr) => ("Subscript", vec![expr.to_json()]),
+            Expr::Unary(expr) => ("Unary", vec![expr.to_json()]),
+            Expr::NonNull(expr) => ("NonNull", vec![expr.to_json()]),
+            Expr::Binary(expr) => ("Binary", vec![expr.to_json()]),
+            Expr::Is(expr) => ("Is", vec![expr.to_json()]),
+            Expr::As(expr) => ("As", vec![expr.to_json()]),
+            Expr::If(expr) => ("If", vec![expr.to_json()]),
+            Expr::Let(expr) => ("Let", vec![expr.to_json()]),
+            Expr::Lambda(expr) => ("Lambda", vec![expr.to_json()]),
+            Expr::New(expr) => ("New", vec![expr.to_json()]),
+            Expr::Amend(expr) => ("Amend", vec![expr.to_json()]),
+            Expr::Throw(expr) => ("Throw", vec![expr.to_json()]),
+            Expr::Trace(expr) => ("Trace", vec![expr.to_json()]),
+            Expr::Import(expr) => ("Import", vec![expr.to_json()]),
+            Expr::Read(expr) => ("Read", vec![expr.to_json()]),
+            Expr::Parenthesized(expr) => ("Parenthesized", vec![expr.to_json()]),
+            Expr::Error(span) => ("Error", vec![span.to_json()]),
+        };
+        variant(name, values)
+    }
+}
+
+impl ToJson for StringPart<'_> {
+    fn to_json(&self) -> Json {
+        match self {
+            StringPart::Text(span, text) => variant("Text", vec![span.to_json(), text.to_json()]),
+            StringPart::Interpolation(expr) => variant("Interpolation", vec![expr.to_json()]),
+        }
+    }
+}
+
+impl ToJson for ObjectMember<'_> {
+    fn to_json(&self) -> Json {
+        match self {
+            ObjectMember::Property(member) => variant("Property", vec![member.to_json()]),
+            ObjectMember::Method(member) => variant("Method", vec![member.to_json()]),
+            ObjectMember::Entry(member) => variant("Entry", vec![member.to_json()]),
+            ObjectMember::Element(member) => variant("Element", vec![member.to_json()]),
+            ObjectMember::MemberPredicate(member) => variant("MemberPredicate", vec![member.to_json()]),
+            ObjectMember::Spread(member) => variant("Spread", vec![member.to_json()]),
+            ObjectMember::For(member) => variant("For", vec![member.to_json()]),
+            ObjectMember::When(member) => variant("When", vec![member.to_json()]),
+        }
+    }
+}
+
+impl ToJson for Type<'_> {
+    fn to_json(&self) -> Json {
+        match self {
+            Type::Unknown(span) => variant("Unknown", vec![span.to_json()]),
+            Type::Nothing(span) => variant("Nothing", vec![span.to_json()]),
+            Type::Module(span) => variant("Module", vec![span.to_json()]),
+            Type::StringLiteral(literal) => variant("StringLiteral", vec![literal.to_json()]),
+            Type::Named(ty) => variant("Named", vec![ty.to_json()]),
+            Type::Nullable(ty) => variant("Nullable", vec![ty.to_json()]),
+            Type::Union(ty) => variant("Union", vec![ty.to_json()]),
+            Type::Function(ty) => variant("Function", vec![ty.to_json()]),
+            Type::Constrained(ty) => variant("Constrained", vec![ty.to_json()]),
+            Type::Parenthesized(ty) => variant("Parenthesized", vec![ty.to_json()]),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Json {
+        self.as_ref().map_or(Json::Null, T::to_json)
+    }
+}
+
+impl<T: ToJson> ToJson for Box<'_, T> {
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<'_, T> {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(T::to_json).collect())
+    }
+}
+
+impl ToJson for &str {
+    fn to_json(&self) -> Json {
+        Json::from(*self)
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Json {
+        Json::Bool(*self)
+    }
+}
+
+impl ToJson for i64 {
+    fn to_json(&self) -> Json {
+        Json::Int(*self)
+    }
+}
+
+impl ToJson for usize {
+    fn to_json(&self) -> Json {
+        Json::from(*self)
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> Json {
+        if self.is_finite() {
+            Json::Float(*self)
+        } else {
+            Json::from(format!("{self:?}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+    use pkl_stdlib::json::Json;
+
+    use super::ToJson;
+
+    #[test]
+    fn syntax_trees() {
+        let alloc = Allocator::default();
+        let ast = pkl_parser::parse_expr(&alloc, "f(\"a\\n\\u{e9}\", null, 1.5, 9007199254740993, 1e999)").node;
+        let json = ast.to_json();
+        let call = json.get("value").unwrap();
+        assert_eq!(call.get("type").and_then(Json::as_str), Some("CallExpr"));
+        assert_eq!(call.get("receiver"), Some(&Json::Null));
+        let args = call.get("args").and_then(Json::as_array).unwrap();
+        let parts = args[0].get("value").and_then(|string| string.get("parts")).and_then(Json::as_array).unwrap();
+        assert_eq!(parts[0].get("values").and_then(Json::as_array).unwrap()[1], Json::from("a\né"));
+        let span = Json::object([("type", Json::from("Span")), ("start", Json::from(15)), ("end", Json::from(19))]);
+        assert_eq!(args[1], Json::object([("type", Json::from("Null")), ("value", span)]));
+        let value = |arg: &Json| arg.get("values").and_then(Json::as_array).unwrap()[1].clone();
+        assert_eq!(value(&args[2]), Json::Float(1.5));
+        assert_eq!(value(&args[3]), Json::Int(9007199254740993));
+        assert_eq!(value(&args[4]), Json::from("inf"));
+        assert!(json.to_string().contains("\"values\":[{\"type\":\"Span\",\"start\":26,\"end\":42},9007199254740993]"));
+    }
+}
@@ -0,0 +1,214 @@
+//! The cache of the output of `pkl-lang eval --cache`, and `pkl-lang cache clear`, which empties it.
+//!
+//! The rendered output of a module is kept in the `eval` directory of the cache directory, in a file named by a hash
+//! of everything the output can depend on: the version of `pkl-lang`, the module's URI and source, the evaluator
+//! options, how the output is rendered, and the [inputs](pkl_eval::inputs) of the module: the contents of every file
+//! it or the modules it imports can import or read, the URIs of the modules of packages, which can't change, and the
+//! values of the environment variables they can read. Evaluating the module again with the same of all of those
+//! writes the cached output without evaluating anything, so nothing is traced or warned about either.
+//!
+//! A module whose inputs can't all be known without evaluating it, like one that reads a URI it computes or imports
+//! a URL, isn't cached, with a warning saying why.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Subcommand};
+use pkl_eval::{EvalError, EvaluatorOptions};
+use sha2::{Digest, Sha256};
+
+use crate::Failure;
+
+/// The directory of the cache directory that outputs are kept in.
+const EVAL_DIR: &str = "eval";
+
+/// Manage the cache of evaluated outputs
+#[derive(Debug, Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheCommand {
+    Clear(ClearArgs),
+}
+
+/// Remove the outputs that `pkl-lang eval --cache` has kept, leaving downloaded packages
+#[derive(Debug, Args)]
+struct ClearArgs {
+    /// The cache directory, rather than `~/.pkl/cache`
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+}
+
+pub fn run(args: CacheArgs) -> ExitCode {
+    let CacheCommand::Clear(args) = args.command;
+    match clear(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Removes the directory of cached outputs, listing its path on standard output if there was one.
+fn clear(args: &ClearArgs) -> Result<(), Failure> {
+    let Some(cache_dir) = args.cache_dir.clone().or(EvaluatorOptions::default().cache_dir) else {
+        return Err(Failure::Message("there's no cache directory, as there's no home directory".to_string()));
+    };
+    let dir = cache_dir.join(EVAL_DIR);
+    if !dir.exists() {
+        return Ok(());
+    }
+    std::fs::remove_dir_all(&dir).map_err(|err| format!("couldn't remove {}: {err}", dir.display()))?;
+    println!("{}", dir.display());
+    Ok(())
+}
+
+/// Why the output of a module isn't cached.
+#[derive(Debug)]
+pub(crate) enum Uncached {
+    /// There's no cache directory, as there's no home directory
+    NoCacheDir,
+    /// What the module can read can't all be known without evaluating it, as the error says
+    UnknownInputs(EvalError),
+}
+
+/// Where the output of the module with `source` at `uri` is cached, when it's evaluated with `options` and rendered
+/// as `rendering` describes.
+pub(crate) fn path(
+    source: &str,
+    uri: &str,
+    options: &EvaluatorOptions,
+    rendering: &str,
+) -> Result<PathBuf, Uncached> {
+    let cache_dir = options.cache_dir.as_ref().ok_or(Uncached::NoCacheDir)?;
+    let inputs = pkl_eval::inputs(source, uri, options).map_err(Uncached::UnknownInputs)?;
+    let mut hash = Sha256::new();
+    let mut field = |name: &str, value: &[u8]| {
+        hash.update(format!("{name} {}\n", value.len()));
+        hash.update(value);
+    };
+    field("version", env!("CARGO_PKG_VERSION").as_bytes());
+    field("module", uri.as_bytes());
+    field("source", source.as_bytes());
+    field("options", options_key(options).as_bytes());
+    for file in &inputs.files {
+        field("file", file.as_bytes());
+        match std::fs::read(file.strip_prefix("file://").unwrap_or(file)) {
+            Ok(contents) => field("contents", &contents),
+            Err(_) => field("missing", b""),
+        }
+    }
+    for package in &inputs.packages {
+        field("package", package.as_bytes());
+    }
+    for name in &inputs.environment_variables {
+        field("env", name.as_bytes());
+        let value = match &options.environment_variables {
+            Some(variables) => variables.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        };
+        match value {
+            Some(value) => field("value", value.as_bytes()),
+            None => field("unset", b""),
+        }
+    }
+    field("rendering", rendering.as_bytes());
+    let key = hash.finalize().iter().fold(String::new(), |mut key, byte| {
+        let _ = write!(key, "{byte:02x}");
+        key
+    });
+    Ok(cache_dir.join(EVAL_DIR).join(key))
+}
+
+/// The cached output at `path`, if there is one.
+pub(crate) fn load(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+/// Keeps `output` at `path`. An output that can't be kept is evaluated again next time, so failing to write it
+/// isn't an error.
+pub(crate) fn store(path: &Path, output: &str) {
+    let Some(dir) = path.parent() else { return };
+    // written to a file of its own first, so that an evaluation at the same time never reads half an output
+    let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = std::fs::create_dir_all(dir).and_then(|()| std::fs::write(&partial, output));
+    if written.and_then(|()| std::fs::rename(&partial, path)).is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+}
+
+/// The evaluator options that can change the output of a module, in an order that doesn't depend on how they're
+/// stored. Where packages are kept, how long an evaluation can take, and how many threads read modules don't.
+fn options_key(options: &EvaluatorOptions) -> String {
+    format!(
+        "{:?} {:?} {:?} {:?} {:?} {} {:?} {:?}",
+        options.allowed_modules,
+        options.allowed_resources,
+        sorted(&options.external_properties),
+        options.environment_variables.as_ref().map(sorted),
+        options.root_dir,
+        options.max_depth,
+        sorted(&options.dependencies),
+        options.deprecations,
+    )
+}
+
+fn sorted(map: &HashMap<String, String>) -> BTreeMap<&String, &String> {
+    map.iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pkl_eval::EvaluatorOptions;
+
+    use super::Uncached;
+
+    #[test]
+    fn keys_follow_imports() {
+        let dir = std::env::temp_dir().join(format!("pkl-lang-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("bird.pkl"), "name = \"Pigeon\"\n").unwrap();
+        let uri = format!("file://{}", dir.join("main.pkl").display());
+        let source = "import \"bird.pkl\"\nname = bird.name\nb = import(\"other.pkl\")\nc = read?(\"c.txt\")\n";
+        let options = EvaluatorOptions { cache_dir: Some(dir.join("cache")), threads: 1, ..Default::default() };
+
+        let path = super::path(source, &uri, &options, "pcf").unwrap();
+        assert!(path.starts_with(dir.join("cache").join("eval")));
+        assert_eq!(super::load(&path), None);
+        super::store(&path, "name = \"Pigeon\"\n");
+        assert_eq!(super::load(&path).as_deref(), Some("name = \"Pigeon\"\n"));
+        assert_eq!(super::path(source, &uri, &options, "pcf").unwrap(), path);
+
+        // changing the module, an import, the options, or the rendering changes the key
+        assert_ne!(super::path("name = 1\n", &uri, &options, "pcf").unwrap(), path);
+        assert_ne!(super::path(source, &uri, &options, "json").unwrap(), path);
+        let mut properties = options.clone();
+        properties.external_properties.insert("bird".to_string(), "Pigeon".to_string());
+        assert_ne!(super::path(source, &uri, &properties, "pcf").unwrap(), path);
+        fs::write(dir.join("bird.pkl"), "name = \"Parrot\"\n").unwrap();
+        let parrot = super::path(source, &uri, &options, "pcf").unwrap();
+        assert_ne!(parrot, path);
+
+        // and so does changing what's imported or read in the body of the module, or creating a file it reads
+        fs::write(dir.join("other.pkl"), "x = 1\n").unwrap();
+        let other = super::path(source, &uri, &options, "pcf").unwrap();
+        assert_ne!(other, parrot);
+        fs::write(dir.join("c.txt"), "c").unwrap();
+        assert_ne!(super::path(source, &uri, &options, "pcf").unwrap(), other);
+
+        let computed = "a = read(\"env:\\(b)\")\nb = 1\n";
+        let Err(Uncached::UnknownInputs(error)) = super::path(computed, &uri, &options, "") else {
+            panic!("a read of a computed URI is cached");
+        };
+        assert_eq!(error.message, "the URI of this `read` is only known when the module is evaluated");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
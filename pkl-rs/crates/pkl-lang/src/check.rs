@@ -0,0 +1,125 @@
+//! `pkl-lang check`, which checks that modules evaluate, without rendering anything, for validating them in CI.
+//!
+//! Each module is parsed, the modules it imports are resolved, and it's evaluated in full, which checks the types and
+//! constraints of its properties, along with its `output`: the value, text, and files it would be rendered as.
+//! Nothing is rendered or written. The problems of the modules that fail are written to standard error, and the exit
+//! code is 0 if every module passes, and 1 otherwise.
+//!
+//! A module that passes is recorded in the cache directory, under a hash of the same things the output of
+//! `pkl-lang eval --cache` is kept by, so checking it again when neither it nor anything it can import or read has
+//! changed passes without evaluating it. A module whose inputs can't all be known without evaluating it is checked
+//! in full every time.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Args;
+
+use crate::eval::EvaluatorArgs;
+use crate::Failure;
+
+/// What modules that passed are recorded as, in place of a rendering
+const CHECKED: &str = "check";
+
+/// Check that Pkl modules evaluate, with the types and constraints of their properties, without rendering them
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    evaluator: EvaluatorArgs,
+
+    /// The cache directory that modules that passed are recorded in, rather than `~/.pkl/cache`
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Check every module in full, even one that passed before and hasn't changed, and don't record the ones that pass
+    #[arg(long)]
+    no_cache: bool,
+
+    /// The modules to check
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+pub fn run(args: CheckArgs) -> ExitCode {
+    if check(&args) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Checks each module, writing the problems of the ones that fail to standard error, and returns whether they all
+/// passed.
+fn check(args: &CheckArgs) -> bool {
+    let mut passed = true;
+    for file in &args.files {
+        if let Err(failure) = check_module(args, file) {
+            failure.print();
+            passed = false;
+        }
+    }
+    passed
+}
+
+fn check_module(args: &CheckArgs, file: &Path) -> Result<(), Failure> {
+    let name = file.display().to_string();
+    let source = std::fs::read_to_string(file).map_err(|err| format!("couldn't read {name}: {err}"))?;
+    // imports are resolved relative to the module, so it's known by its canonical path
+    let path = file.canonicalize().map_err(|err| format!("couldn't read {name}: {err}"))?;
+    let uri = format!("file://{}", path.display());
+    let mut options = args.evaluator.options(Some(file))?;
+    if let Some(dir) = &args.cache_dir {
+        options.cache_dir = Some(dir.clone());
+    }
+
+    let record = if args.no_cache { None } else { crate::cache::path(&source, &uri, &options, CHECKED).ok() };
+    if record.as_deref().is_some_and(Path::exists) {
+        return Ok(());
+    }
+    crate::eval::evaluate_output((&name, &source, &uri), options)?;
+    if let Some(record) = &record {
+        crate::cache::store(record, "");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use clap::Parser;
+
+    use super::{check, CheckArgs};
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: CheckArgs,
+    }
+
+    #[test]
+    fn checks_and_records_modules() {
+        let dir = std::env::temp_dir().join(format!("pkl-lang-check-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (bird, nest, cache) = (dir.join("bird.pkl"), dir.join("nest.pkl"), dir.join("cache"));
+        fs::write(&bird, "name: String = \"Pigeon\"\nlegs: Int(this > 0) = 2\n").unwrap();
+        fs::write(&nest, "import \"bird.pkl\"\noutput { files { [\"bird.json\"] { value = bird } } }\n").unwrap();
+        let args = |files: &[&std::path::Path]| {
+            let flags = ["check", "--no-project", "--cache-dir", cache.to_str().unwrap()];
+            Cli::parse_from(flags.into_iter().chain(files.iter().map(|file| file.to_str().unwrap()))).args
+        };
+
+        assert!(check(&args(&[&bird, &nest])));
+        let recorded = || fs::read_dir(cache.join("eval")).map_or(0, |entries| entries.count());
+        assert_eq!(recorded(), 2);
+        assert!(check(&args(&[&bird, &nest])));
+        assert_eq!(recorded(), 2);
+
+        // a module is checked again once a module it imports changes, and one that fails isn't recorded
+        fs::write(&bird, "name: String = \"Pigeon\"\nlegs: Int(this > 0) = 0\n").unwrap();
+        assert!(!check(&args(&[&nest])));
+        assert!(!check(&args(&[&bird])));
+        assert_eq!(recorded(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
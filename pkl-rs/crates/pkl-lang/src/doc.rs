@@ -0,0 +1,75 @@
+//! `pkl-lang doc`, which generates documentation for modules from their doc comments and declarations.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+use pkl_doc::{html, markdown, ModuleDoc};
+
+use crate::Failure;
+
+/// Generate documentation for Pkl modules
+#[derive(Debug, Args)]
+pub struct DocArgs {
+    /// The format of the pages
+    #[arg(short, long, value_enum, default_value_t = DocFormat::Html)]
+    format: DocFormat,
+
+    /// The directory to write a page for each module to, and an index of them
+    #[arg(short, long, value_name = "DIR")]
+    output_dir: PathBuf,
+
+    /// The modules to document
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DocFormat {
+    Html,
+    Markdown,
+}
+
+pub fn run(args: DocArgs) -> ExitCode {
+    match generate(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Writes the pages, listing the path of each on standard output.
+fn generate(args: &DocArgs) -> Result<(), Failure> {
+    let mut modules: Vec<ModuleDoc> = Vec::new();
+    for path in &args.files {
+        let name = path.display().to_string();
+        let source = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {name}: {err}"))?;
+        let module = pkl_doc::document(&source, &name);
+        let module = module.map_err(|diagnostics| Failure::diagnostics(&name, &source, &diagnostics))?;
+        if modules.iter().any(|other| other.name == module.name) {
+            return Err(format!("{name}: another module is named `{}` too", module.name).into());
+        }
+        modules.push(module);
+    }
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let (extension, index) = match args.format {
+        DocFormat::Html => ("html", html::index(&modules)),
+        DocFormat::Markdown => ("md", markdown::index(&modules)),
+    };
+    let page = |module| match args.format {
+        DocFormat::Html => html::module_page(module),
+        DocFormat::Markdown => markdown::module_page(module),
+    };
+    let dir = &args.output_dir;
+    std::fs::create_dir_all(dir).map_err(|err| format!("couldn't create {}: {err}", dir.display()))?;
+    let pages = modules.iter().map(|module| (format!("{}.{extension}", module.name), page(module)));
+    for (file, contents) in pages.chain([(format!("index.{extension}"), index)]) {
+        let path = dir.join(file);
+        std::fs::write(&path, contents).map_err(|err| format!("couldn't write {}: {err}", path.display()))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
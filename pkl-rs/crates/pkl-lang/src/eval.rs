@@ -0,0 +1,625 @@
+//! `pkl-lang eval`, which evaluates a module and renders it in an output format.
+//!
+//! The output is written to standard output, or the `--output` file, and diagnostics to standard error. With
+//! `--multiple-file-output-path`, the files of the module's `output.files` are written to a directory instead. The
+//! exit code is 0 on success, 1 if the module can't be read, parsed, evaluated, or rendered, and 2 for invalid
+//! arguments.
+//!
+//! A module in a project, a directory with a `PklProject` file, is evaluated with the project's dependencies and
+//! evaluator settings, which the options given on the command line override.
+//!
+//! With `--watch`, the module is evaluated again whenever it or a file it depends on changes, until interrupted.
+//!
+//! With `--cache`, the output is kept, and written again without evaluating the module as long as the module, what
+//! it can import and read, and the options are the same; see [`cache`](crate::cache).
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::{Args, ValueEnum};
+use oxc_allocator::Allocator;
+use pkl_diagnostics::{Renderer, Report, Severity};
+use pkl_eval::project::{Project, PROJECT_FILE};
+use pkl_eval::value::{ModuleOutput, Value};
+use pkl_eval::{Deprecations, Error, EvalError, Evaluator, EvaluatorOptions};
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_lexer::token::Span;
+use pkl_render::{
+    JsonOptions, JsonRenderer, PListRenderer, PcfOptions, PcfRenderer, PropertiesOptions, PropertiesRenderer,
+    XmlOptions, XmlRenderer, YamlOptions, YamlRenderer,
+};
+
+use crate::cache::Uncached;
+use crate::watch::Watcher;
+use crate::Failure;
+
+/// Evaluate a Pkl module and render its value
+///
+/// Object bodies with parameters, like `(function) { x -> ... }` to amend a function, aren't supported yet: evaluating
+/// one fails.
+#[derive(Debug, Args)]
+pub struct EvalArgs {
+    /// The output format, rather than the module's `output.renderer`; without either, the output is Pcf
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+
+    /// An expression to evaluate in the context of the module, like `output.text`, rather than the module itself;
+    /// strings are written as they are, and other values in the output format
+    #[arg(short = 'x', long)]
+    expression: Option<String>,
+
+    /// The file to write the output to, rather than standard output
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The directory to write the module's `output.files` to, each at its path relative to the directory
+    #[arg(short, long, value_name = "DIR", conflicts_with_all = ["output", "expression"])]
+    multiple_file_output_path: Option<PathBuf>,
+
+    /// Render the properties of objects and the entries of mappings and maps sorted by name and key, rather than in
+    /// the order they're declared in
+    #[arg(long)]
+    sort_keys: bool,
+
+    #[command(flatten)]
+    evaluator: EvaluatorArgs,
+
+    /// Keep running, and evaluate the module again whenever it or a module it imports changes, rewriting the output
+    #[arg(short, long, requires = "file")]
+    watch: bool,
+
+    /// Keep the output in the cache directory, and write the kept output rather than evaluating the module again if
+    /// neither it, what it can import and read, nor the options have changed
+    #[arg(long, overrides_with = "no_cache")]
+    cache: bool,
+
+    /// Evaluate the module even if its output is cached, and don't cache it
+    #[arg(long, overrides_with = "cache")]
+    no_cache: bool,
+
+    /// The module to evaluate; without one, it's read from standard input
+    file: Option<PathBuf>,
+}
+
+/// The options of the evaluator, which `pkl-lang eval` and `pkl-lang check` share
+#[derive(Debug, Args)]
+pub(crate) struct EvaluatorArgs {
+    /// An external property, read by `read("prop:name")`
+    #[arg(short = 'p', long = "property", value_name = "NAME=VALUE", value_parser = parse_property)]
+    properties: Vec<(String, String)>,
+
+    /// An environment variable, read by `read("env:NAME")`. Given any, they're the whole environment, added to the
+    /// project's `env`; without any, the environment of the process is read, unless the project sets its own
+    #[arg(long = "env-var", value_name = "NAME=VALUE", value_parser = parse_property)]
+    env_vars: Vec<(String, String)>,
+
+    /// Patterns of the URIs of the modules that can be imported, replacing the defaults
+    #[arg(long, value_delimiter = ',')]
+    allowed_modules: Option<Vec<String>>,
+
+    /// Patterns of the URIs of the resources that can be read, replacing the defaults
+    #[arg(long, value_delimiter = ',')]
+    allowed_resources: Option<Vec<String>>,
+
+    /// The directory that imported and read files have to be in
+    #[arg(long)]
+    root_dir: Option<PathBuf>,
+
+    /// How many seconds evaluation can take before it fails
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// How deeply method calls and the members they read can nest before evaluation fails
+    #[arg(long, value_name = "LEVELS", default_value_t = pkl_eval::DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+
+    /// What to do when the module uses a property or method annotated with `@Deprecated`
+    #[arg(long, value_enum, value_name = "ACTION", default_value_t = Deprecated::Warn)]
+    deprecated: Deprecated,
+
+    /// How many threads read the modules that are imported before evaluation, rather than one for each processor
+    #[arg(long, value_name = "COUNT")]
+    threads: Option<usize>,
+
+    /// The directory of the project whose dependencies and settings the module is evaluated with, rather than the
+    /// nearest one with a `PklProject` file above the module
+    #[arg(long, value_name = "DIR")]
+    project_dir: Option<PathBuf>,
+
+    /// Don't evaluate the module with the dependencies and settings of a project
+    #[arg(long, conflicts_with = "project_dir")]
+    no_project: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Deprecated {
+    Ignore,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Pcf,
+    Json,
+    Yaml,
+    Plist,
+    Properties,
+    Xml,
+}
+
+pub fn run(args: EvalArgs) -> ExitCode {
+    if args.watch {
+        watch(&args);
+    }
+    match eval_and_write(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn eval_and_write(args: &EvalArgs) -> Result<(), Failure> {
+    match &args.multiple_file_output_path {
+        Some(dir) => write_files(args, dir),
+        None => eval(args).and_then(|output| match &args.output {
+            Some(path) => Ok(write(path, &output)?),
+            None => {
+                let written = io::stdout().write_all(output.as_bytes()).and_then(|()| io::stdout().flush());
+                Ok(written.map_err(|err| format!("couldn't write output: {err}"))?)
+            }
+        }),
+    }
+}
+
+/// Evaluates the module whenever it, a module it imports, or its project's `PklProject` changes, until interrupted.
+///
+/// What's watched is worked out anew before each evaluation, so imports that are added or removed are picked up, and
+/// errors are reported without stopping: the next change evaluates the module again.
+fn watch(args: &EvalArgs) -> ! {
+    let mut failed = false;
+    loop {
+        let mut watcher = Watcher::new(watched(args));
+        let started = Instant::now();
+        match eval_and_write(args) {
+            Ok(()) => {
+                let again = if failed { " without errors again" } else { "" };
+                let elapsed = started.elapsed();
+                crate::status(&format!("evaluated in {elapsed:.0?}{again}; watching {} files", watcher.len()));
+                failed = false;
+            }
+            Err(failure) => {
+                failure.print();
+                crate::status(&format!("evaluation failed; watching {} files", watcher.len()));
+                failed = true;
+            }
+        }
+        let changed: Vec<String> = watcher.wait().iter().map(|path| path.display().to_string()).collect();
+        crate::status(&format!("{} changed; evaluating again", changed.join(", ")));
+    }
+}
+
+/// The files the module's output depends on: the module, the files it imports, and its project's `PklProject`.
+fn watched(args: &EvalArgs) -> Vec<PathBuf> {
+    let Some(file) = &args.file else { return Vec::new() };
+    let path = file.canonicalize().unwrap_or_else(|_| file.clone());
+    let mut paths = vec![path.clone()];
+    paths.extend(project_dir(args).map(|dir| dir.join(PROJECT_FILE)));
+    if let (Ok(source), Ok(options)) = (std::fs::read_to_string(&path), options(args)) {
+        let uri = format!("file://{}", path.display());
+        let dependencies = pkl_eval::dependencies(&source, &uri, &options);
+        paths.extend(dependencies.iter().filter_map(|uri| uri.strip_prefix("file://")).map(PathBuf::from));
+    }
+    paths
+}
+
+fn eval(args: &EvalArgs) -> Result<String, Failure> {
+    let (name, source, uri) = read_module(args)?;
+    let cached = if args.cache && !args.no_cache {
+        let rendering = format!("{:?} {:?} {}", args.format, args.expression, args.sort_keys);
+        match crate::cache::path(&source, &uri, &options(args)?, &rendering) {
+            Ok(path) => Some(path),
+            Err(uncached) => {
+                eprint!("{}", uncached_warning((&name, &source, &uri), &uncached, &crate::renderer()));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(output) = cached.as_deref().and_then(crate::cache::load) {
+        return Ok(output);
+    }
+    let output = evaluate(args, (&name, &source, &uri))?;
+    if let Some(path) = &cached {
+        crate::cache::store(path, &output);
+    }
+    Ok(output)
+}
+
+/// The warning that the output of the module isn't cached, and why.
+fn uncached_warning(module: (&str, &str, &str), uncached: &Uncached, renderer: &Renderer) -> String {
+    let message = "the output isn't cached";
+    match uncached {
+        Uncached::NoCacheDir => {
+            let report = Report::warning(message).with_note("there's no cache directory, as there's no home directory");
+            renderer.render(&report, module.0, module.1)
+        }
+        Uncached::UnknownInputs(error) => {
+            let mut report = Report { severity: Severity::Warning, message: message.to_string(), ..error.report() };
+            report.notes.insert(0, error.message.clone());
+            render(module, &report, error.uri.as_deref(), renderer)
+        }
+    }
+}
+
+/// Evaluates the module whose name, source, and URI are given, rendering the output.
+fn evaluate(args: &EvalArgs, (name, source, uri): (&str, &str, &str)) -> Result<String, Failure> {
+    let renderer = args.format.map(renderer);
+    let rendered = match &args.expression {
+        None => {
+            let mut output = evaluate_output((name, source, uri), options(args)?)?;
+            if args.sort_keys {
+                output.value = pkl_render::sort::sort_keys(output.value);
+            }
+            pkl_render::render_output(&output, renderer.as_deref())
+        }
+        Some(expression) => {
+            let module = (name, source, uri);
+            let log = |evaluator: &mut Evaluator| evaluator.set_logger(crate::log);
+            let value = evaluate_expression(options(args)?, &log, module, expression, &crate::renderer())
+                .map_err(Failure::Reports)?;
+            if let Value::String(s) = value {
+                return Ok(if s.ends_with('\n') { s } else { format!("{s}\n") });
+            }
+            let value = if args.sort_keys { pkl_render::sort::sort_keys(value) } else { value };
+            renderer.unwrap_or_else(|| self::renderer(Format::Pcf)).render(&value)
+        }
+    };
+    Ok(rendered.map_err(|error| format!("{name}: {error}"))?)
+}
+
+/// Evaluates the `output` of the module whose name, source, and URI are given, logging to standard error.
+pub(crate) fn evaluate_output(module: (&str, &str, &str), options: EvaluatorOptions) -> Result<ModuleOutput, Failure> {
+    let (name, source, uri) = module;
+    let alloc = Allocator::default();
+    let source = alloc.alloc_str(source);
+    let parsed = pkl_parser::parse_module(&alloc, source);
+    if !parsed.diagnostics.is_empty() {
+        return Err(Failure::diagnostics(name, source, &parsed.diagnostics));
+    }
+    let mut evaluator = Evaluator::with_options(&alloc, options);
+    evaluator.set_logger(crate::log);
+    evaluator.add_source(uri, source);
+    let output = evaluator.evaluate_output(alloc.alloc(parsed.node), uri);
+    output.map_err(|error| Failure::Reports(describe(module, &Error::Eval(error), &crate::renderer())))
+}
+
+/// Writes the files of the module's `output.files` to `dir`, listing the path of each on standard output.
+fn write_files(args: &EvalArgs, dir: &Path) -> Result<(), Failure> {
+    let (name, source, uri) = read_module(args)?;
+    let mut output = evaluate_output((&name, &source, &uri), options(args)?)?;
+    if output.files.is_empty() {
+        return Err(format!("{name} doesn't have any `output.files` to write").into());
+    }
+    if args.sort_keys {
+        for (_, file) in &mut output.files {
+            file.value = pkl_render::sort::sort_keys(std::mem::replace(&mut file.value, Value::Null));
+        }
+    }
+
+    let renderer = args.format.map(renderer);
+    let paths: Vec<&str> = output.files.iter().map(|(path, _)| path.as_str()).collect();
+    for (path, (_, file)) in file_paths(&paths)?.into_iter().zip(&output.files) {
+        let text = pkl_render::render_output(file, renderer.as_deref()).map_err(|error| format!("{name}: {error}"))?;
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| format!("couldn't create {}: {err}", parent.display()))?;
+        }
+        write(&path, &text)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// The relative path of each of the `output.files`, which has to stay inside the output directory, and can't be the
+/// same file as another one, or be inside it.
+fn file_paths(paths: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let mut checked: Vec<(&str, Vec<&str>)> = Vec::with_capacity(paths.len());
+    for &path in paths {
+        let parts: Vec<&str> = path.split('/').filter(|part| !matches!(*part, "" | ".")).collect();
+        if path.starts_with('/') || parts.is_empty() || parts.contains(&"..") {
+            return Err(format!("output file `{path}` isn't inside the output directory"));
+        }
+        for (other, other_parts) in &checked {
+            if *other_parts == parts {
+                return Err(format!("output files `{other}` and `{path}` are the same file"));
+            }
+            let (inner, outer) = if parts.len() > other_parts.len() { (path, *other) } else { (*other, path) };
+            if parts.starts_with(other_parts) || other_parts.starts_with(&parts) {
+                return Err(format!("output file `{inner}` is inside output file `{outer}`"));
+            }
+        }
+        checked.push((path, parts));
+    }
+    Ok(checked.into_iter().map(|(_, parts)| parts.iter().collect()).collect())
+}
+
+fn write(path: &Path, text: &str) -> Result<(), String> {
+    std::fs::write(path, text).map_err(|err| format!("couldn't write {}: {err}", path.display()))
+}
+
+/// The name, source, and URI of the module to evaluate.
+fn read_module(args: &EvalArgs) -> Result<(String, String, String), String> {
+    Ok(match &args.file {
+        Some(path) => {
+            let name = path.display().to_string();
+            let source = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {name}: {err}"))?;
+            // imports are resolved relative to the module, so it's known by its canonical path
+            let path = path.canonicalize().map_err(|err| format!("couldn't read {name}: {err}"))?;
+            (name, source, format!("file://{}", path.display()))
+        }
+        None => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source).map_err(|err| format!("couldn't read standard input: {err}"))?;
+            ("<stdin>".to_string(), source, "repl:text".to_string())
+        }
+    })
+}
+
+fn renderer(format: Format) -> Box<dyn pkl_render::Renderer> {
+    match format {
+        Format::Pcf => Box::new(PcfRenderer::new(PcfOptions::default())),
+        Format::Json => Box::new(JsonRenderer::new(JsonOptions::default())),
+        Format::Yaml => Box::new(YamlRenderer::new(YamlOptions::default())),
+        Format::Plist => Box::new(PListRenderer),
+        Format::Properties => Box::new(PropertiesRenderer::new(PropertiesOptions::default())),
+        Format::Xml => Box::new(XmlRenderer::new(XmlOptions::default())),
+    }
+}
+
+/// The name of the local property holding the `--expression`, which can't clash with the module's own.
+const EXPRESSION: &str = "`pkl-lang:expression`";
+
+/// Evaluates the `--expression` in the context of the module, whose name, source, and URI are given, by an evaluator
+/// with `options` that `configure` can add readers to.
+///
+/// The expression is appended to the module as a local property, so the positions of errors tell whether they're in
+/// the module or the expression.
+pub(crate) fn evaluate_expression(
+    options: EvaluatorOptions,
+    configure: &dyn Fn(&mut Evaluator),
+    module: (&str, &str, &str),
+    expression: &str,
+    renderer: &Renderer,
+) -> Result<Value, String> {
+    let (_, source, uri) = module;
+    let prefix = format!("{source}\nlocal {EXPRESSION} = ");
+    let combined = format!("{prefix}{expression}\n");
+    let describe = |error: Error| {
+        let in_expression = |span: Span| span.start >= prefix.len();
+        let shift = |span: Span| Span::new(span.start - prefix.len(), span.end - prefix.len());
+        // the frames of the expression, as the value of a property, are left out
+        let error = match error {
+            Error::Eval(mut error) => {
+                error.frames.retain(|frame| frame.span.end <= source.len());
+                Error::Eval(error)
+            }
+            error => error,
+        };
+        match error {
+            Error::Syntax(diagnostics) if diagnostics.iter().all(|diagnostic| in_expression(diagnostic.span)) => {
+                let diagnostics = diagnostics
+                    .into_iter()
+                    .map(|diagnostic| Diagnostic { span: shift(diagnostic.span), ..diagnostic })
+                    .collect();
+                describe(("<expression>", expression, uri), &Error::Syntax(diagnostics), renderer)
+            }
+            Error::Eval(error) if in_expression(error.span) => {
+                let labels = error.labels.iter().filter(|(span, _)| in_expression(*span));
+                let labels = labels.map(|(span, label)| (shift(*span), label.clone())).collect();
+                let shifted = EvalError { span: shift(error.span), labels, ..error };
+                describe(("<expression>", expression, uri), &Error::Eval(shifted), renderer)
+            }
+            error => describe(module, &error, renderer),
+        }
+    };
+
+    let alloc = Allocator::default();
+    let module = pkl_parser::parse_module(&alloc, &combined);
+    if !module.diagnostics.is_empty() {
+        return Err(describe(Error::Syntax(module.diagnostics)));
+    }
+    let expr = pkl_parser::parse_expr(&alloc, EXPRESSION).node;
+    let mut evaluator = Evaluator::with_options(&alloc, options);
+    configure(&mut evaluator);
+    evaluator.add_source(uri, alloc.alloc_str(&combined));
+    let value = evaluator.evaluate_expr_in(alloc.alloc(module.node), uri, alloc.alloc(expr));
+    value.map_err(|error| describe(Error::Eval(error)))
+}
+
+fn options(args: &EvalArgs) -> Result<EvaluatorOptions, Failure> {
+    args.evaluator.options(args.file.as_deref())
+}
+
+/// The directory of the project the module is in.
+fn project_dir(args: &EvalArgs) -> Option<PathBuf> {
+    args.evaluator.project_dir(args.file.as_deref())
+}
+
+impl EvaluatorArgs {
+    /// The options to evaluate `file`, or standard input, with.
+    pub(crate) fn options(&self, file: Option<&Path>) -> Result<EvaluatorOptions, Failure> {
+        let deprecations = match self.deprecated {
+            Deprecated::Ignore => Deprecations::Ignore,
+            Deprecated::Warn => Deprecations::Warn,
+            Deprecated::Error => Deprecations::Error,
+        };
+        let mut options = EvaluatorOptions { max_depth: self.max_depth, deprecations, ..EvaluatorOptions::default() };
+        if let Some(dir) = self.project_dir(file) {
+            let project = Project::load_with_logger(&dir, crate::log);
+            let project = project.map_err(|error| format!("{}: {error}", dir.join(PROJECT_FILE).display()))?;
+            project.configure(&mut options)?;
+        }
+        options.external_properties.extend(self.properties.iter().cloned());
+        if !self.env_vars.is_empty() {
+            options.environment_variables.get_or_insert_with(HashMap::new).extend(self.env_vars.iter().cloned());
+        }
+        if let Some(root_dir) = &self.root_dir {
+            options.root_dir = Some(root_dir.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            options.timeout = Some(Duration::from_secs(timeout));
+        }
+        if let Some(threads) = self.threads {
+            options.threads = threads;
+        }
+        if let Some(patterns) = &self.allowed_modules {
+            options.allowed_modules = patterns.clone();
+        }
+        if let Some(patterns) = &self.allowed_resources {
+            options.allowed_resources = patterns.clone();
+        }
+        Ok(options)
+    }
+
+    /// The directory of the project `file` is in: the given one, or the nearest one above it, or above the working
+    /// directory for standard input.
+    pub(crate) fn project_dir(&self, file: Option<&Path>) -> Option<PathBuf> {
+        if self.no_project {
+            return None;
+        }
+        if let Some(dir) = &self.project_dir {
+            return Some(dir.clone());
+        }
+        let start = match file {
+            Some(file) => file.canonicalize().ok()?.parent()?.to_path_buf(),
+            None => std::env::current_dir().ok()?,
+        };
+        start.ancestors().find(|dir| dir.join(PROJECT_FILE).is_file()).map(Path::to_path_buf)
+    }
+}
+
+fn parse_property(property: &str) -> Result<(String, String), String> {
+    match property.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected `NAME=VALUE`, but got `{property}`")),
+    }
+}
+
+/// The reports of an evaluation error of the module whose name, source, and URI are given, each showing where it
+/// happened, the other places it involves, and what was being evaluated.
+///
+/// An error in an imported module is shown with the source of that module, if it's a file that can still be read.
+pub(crate) fn describe(module: (&str, &str, &str), error: &Error, renderer: &Renderer) -> String {
+    match error {
+        Error::Eval(error) => render(module, &error.report(), error.uri.as_deref(), renderer),
+        error => error.reports().iter().map(|report| render(module, report, None, renderer)).collect(),
+    }
+}
+
+/// Renders `report`, about the module at `in_module` or else the evaluated `module`, with the lines of the module
+/// it's about.
+fn render(module: (&str, &str, &str), report: &Report, in_module: Option<&str>, renderer: &Renderer) -> String {
+    let (name, source, uri) = module;
+    match in_module.filter(|&imported| imported != uri) {
+        Some(imported) => {
+            let path = imported.strip_prefix("file://");
+            match path.and_then(|path| Some((path, std::fs::read_to_string(path).ok()?))) {
+                Some((path, source)) => renderer.render(report, path, &source),
+                None => renderer.render(&Report { primary: None, labels: Vec::new(), ..report.clone() }, imported, ""),
+            }
+        }
+        None => renderer.render(report, name, source),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use pkl_diagnostics::Renderer;
+    use pkl_eval::{Deprecations, Evaluator, EvaluatorOptions, Log};
+
+    use super::{evaluate_expression, file_paths, options, uncached_warning, EvalArgs};
+    use crate::cache::Uncached;
+
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: EvalArgs,
+    }
+
+    #[test]
+    fn output_file_paths() {
+        let paths = file_paths(&["a.json", "./deploy//b.yaml"]).unwrap();
+        assert_eq!(paths, [PathBuf::from("a.json"), PathBuf::from("deploy/b.yaml")]);
+
+        let error = |paths: &[&str]| file_paths(paths).unwrap_err();
+        assert_eq!(error(&["../a.json"]), "output file `../a.json` isn't inside the output directory");
+        assert_eq!(error(&["/etc/a.json"]), "output file `/etc/a.json` isn't inside the output directory");
+        assert_eq!(error(&["a.json", "./a.json"]), "output files `a.json` and `./a.json` are the same file");
+        assert_eq!(error(&["a/b.json", "a"]), "output file `a/b.json` is inside output file `a`");
+    }
+
+    #[test]
+    fn describes_errors_in_the_expression() {
+        let module = ("a.pkl", "x = 1\n", "repl:text");
+        let evaluate = |expression| {
+            evaluate_expression(EvaluatorOptions::default(), &|_| {}, module, expression, &Renderer::default())
+        };
+        let expected = "error[E0207]: division by zero\n --> <expression>:1:1\n  |\n1 | x ~/ 0\n  | ^^^^^^\n";
+        assert_eq!(evaluate("x ~/ 0").unwrap_err(), expected);
+        assert!(evaluate("x +").unwrap_err().starts_with("error[E0100]: expected expression, found end of input\n"));
+    }
+
+    #[test]
+    fn warns_of_deprecated_members() {
+        let parse = |args: &[&str]| <Cli as clap::Parser>::parse_from(["eval", "--no-project"].iter().chain(args)).args;
+        let module = ("a.pkl", "@Deprecated { message = \"use `b`\" }\na = 1\nb = 2\nc = a\n", "repl:text");
+        let logged = Rc::new(RefCell::new(String::new()));
+        let log = |evaluator: &mut Evaluator| {
+            let logged = logged.clone();
+            let render = |log: &Log| crate::render_log(log, &Renderer::default());
+            evaluator.set_logger(move |log| logged.borrow_mut().push_str(&render(log)));
+        };
+        let warn = options(&parse(&["--deprecated", "warn"])).unwrap();
+        evaluate_expression(warn, &log, module, "c", &Renderer::default()).unwrap();
+        let expected = "warning[E0219]: `a` is deprecated: use `b`\n --> repl:text:4:5\n  |\n4 | c = a\n  |     ^\n";
+        assert_eq!(*logged.borrow(), expected);
+
+        logged.borrow_mut().clear();
+        let ignore = options(&parse(&["--deprecated", "ignore"])).unwrap();
+        evaluate_expression(ignore, &log, module, "c", &Renderer::default()).unwrap();
+        assert_eq!(*logged.borrow(), "");
+    }
+
+    #[test]
+    fn warns_of_outputs_that_arent_cached() {
+        let module = ("a.pkl", "a = read(\"env:\\(b)\")\nb = 1\n", "repl:text");
+        let error = pkl_eval::inputs(module.1, module.2, &EvaluatorOptions::default()).unwrap_err();
+        let warning = uncached_warning(module, &Uncached::UnknownInputs(error), &Renderer::default());
+        assert!(warning.starts_with("warning: the output isn't cached\n --> a.pkl:1:10\n"), "{warning}");
+        assert!(warning.ends_with("= note: the URI of this `read` is only known when the module is evaluated\n"));
+    }
+
+    #[test]
+    fn properties_and_environment_variables() {
+        let parse = |args: &[&str]| <Cli as clap::Parser>::parse_from(["eval", "--no-project"].iter().chain(args)).args;
+        let args = parse(&["-p", "bird=pigeon", "--env-var", "HOME=/nest", "--env-var", "EMPTY="]);
+        let configured = options(&args).unwrap();
+        assert_eq!(configured.external_properties["bird"], "pigeon");
+        let variables = configured.environment_variables.unwrap();
+        assert_eq!((variables.len(), variables["HOME"].as_str(), variables["EMPTY"].as_str()), (2, "/nest", ""));
+        assert_eq!(options(&parse(&[])).unwrap().environment_variables, None);
+        assert_eq!(configured.deprecations, Deprecations::Warn);
+        assert_eq!(options(&parse(&["--deprecated", "error"])).unwrap().deprecations, Deprecations::Error);
+    }
+}
@@ -0,0 +1,91 @@
+//! `pkl-lang fmt`, which formats modules in place or checks that they are formatted.
+
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+
+use crate::Failure;
+
+/// Format Pkl modules
+#[derive(Debug, Args)]
+pub struct FmtArgs {
+    /// Don't write anything, but list the files that aren't formatted and fail if there are any
+    #[arg(long)]
+    check: bool,
+
+    /// The files to format; without any, a module is read from standard input and written to standard output
+    files: Vec<PathBuf>,
+}
+
+pub fn run(args: FmtArgs) -> ExitCode {
+    let result = if args.files.is_empty() { format_stdin(args.check) } else { format_files(&args.files, args.check) };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Formats a module from standard input, returning whether it was formatted already.
+fn format_stdin(check: bool) -> Result<bool, Failure> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source).map_err(|err| format!("couldn't read standard input: {err}"))?;
+    let formatted = format("<stdin>", &source)?;
+
+    if check {
+        if formatted != source {
+            println!("<stdin>");
+        }
+    } else {
+        io::stdout().write_all(formatted.as_bytes()).map_err(|err| format!("couldn't write output: {err}"))?;
+    }
+    Ok(formatted == source)
+}
+
+/// Formats each file, returning whether all of them were formatted already.
+///
+/// A file that can't be read or parsed is reported without stopping the others from being formatted.
+fn format_files(files: &[PathBuf], check: bool) -> Result<bool, Failure> {
+    let mut unchanged = true;
+    let mut failed = false;
+
+    for path in files {
+        let name = path.display().to_string();
+        let result = std::fs::read_to_string(path)
+            .map_err(|err| Failure::from(format!("couldn't read {name}: {err}")))
+            .and_then(|source| Ok((format(&name, &source)?, source)));
+        let (formatted, source) = match result {
+            Ok(pair) => pair,
+            Err(failure) => {
+                failure.print();
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+        unchanged = false;
+        if check {
+            println!("{name}");
+        } else if let Err(err) = std::fs::write(path, formatted) {
+            Failure::from(format!("couldn't write {name}: {err}")).print();
+            failed = true;
+        }
+    }
+
+    if failed {
+        return Err(Failure::Message("some files couldn't be formatted".to_string()));
+    }
+    Ok(unchanged || !check)
+}
+
+fn format(name: &str, source: &str) -> Result<String, Failure> {
+    pkl_fmt::try_format(source).map_err(|diagnostics| Failure::diagnostics(name, source, &diagnostics))
+}
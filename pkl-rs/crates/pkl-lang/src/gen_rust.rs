@@ -0,0 +1,53 @@
+//! `pkl-lang gen-rust`, which generates Rust types from the classes and typealiases of a module.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use pkl_gen_rust::GenOptions;
+
+use crate::Failure;
+
+/// Generate Rust structs and enums with serde derives from a Pkl module
+#[derive(Debug, Args)]
+pub struct GenRustArgs {
+    /// The module whose classes and typealiases to generate types for
+    module: PathBuf,
+
+    /// Write the generated code to a file rather than to standard output
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// The name of the struct for the module's own properties, rather than the last part of its `module` clause
+    #[arg(long, value_name = "NAME")]
+    module_struct: Option<String>,
+}
+
+pub fn run(args: GenRustArgs) -> ExitCode {
+    match generate(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn generate(args: &GenRustArgs) -> Result<(), Failure> {
+    let name = args.module.display().to_string();
+    let source = std::fs::read_to_string(&args.module).map_err(|err| format!("couldn't read {name}: {err}"))?;
+    let options = GenOptions { module_struct: args.module_struct.clone(), ..GenOptions::default() };
+    let rust = pkl_gen_rust::generate(&source, &options)
+        .map_err(|diagnostics| Failure::diagnostics(&name, &source, &diagnostics))?;
+
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rust).map_err(|err| format!("couldn't write {}: {err}", path.display()))?;
+            Ok(())
+        }
+        None => {
+            print!("{rust}");
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! `pkl-lang lint`, which reports the lints of modules: things that are allowed but likely mistakes, like imports
+//! that nothing uses.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use oxc_allocator::Allocator;
+use pkl_analysis::lint::{Level, LintConfig};
+use pkl_analysis::Analyzer;
+use pkl_diagnostics::Report;
+
+use crate::Failure;
+
+/// Report likely mistakes in Pkl modules
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// The level of a rule, like `unused-import=error`: `off`, `warning`, or `error`. Every rule is a warning
+    /// unless it's set otherwise. Can be repeated
+    #[arg(long = "rule", value_name = "RULE=LEVEL")]
+    rules: Vec<String>,
+
+    /// The modules to lint
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+pub fn run(args: LintArgs) -> ExitCode {
+    match lint(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Lints each file, printing its lints to standard error, and returns whether none of them is an error.
+///
+/// A module with syntax errors has those reported instead.
+fn lint(args: &LintArgs) -> Result<bool, Failure> {
+    let mut config = LintConfig::default();
+    for setting in &args.rules {
+        config.parse_setting(setting)?;
+    }
+
+    let renderer = crate::renderer();
+    let mut analyzer = Analyzer::default();
+    let mut passed = true;
+    for path in &args.files {
+        let name = path.display().to_string();
+        let source = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {name}: {err}"))?;
+        let alloc = Allocator::default();
+        let diagnostics = pkl_parser::parse_module(&alloc, &source).diagnostics;
+        if !diagnostics.is_empty() {
+            Failure::diagnostics(&name, &source, &diagnostics).print();
+            passed = false;
+            continue;
+        }
+
+        // imports are resolved relative to the module, so it's known by its canonical path
+        let path = path.canonicalize().map_err(|err| format!("couldn't read {name}: {err}"))?;
+        let uri = format!("file://{}", path.display());
+        analyzer.update(&uri, &source);
+        for lint in analyzer.lint(&uri, &config) {
+            let report = match lint.level {
+                Level::Error => {
+                    passed = false;
+                    Report::error(lint.message)
+                }
+                _ => Report::warning(lint.message),
+            };
+            let report = report
+                .with_primary(lint.span.start..lint.span.end, "")
+                .with_note(format!("this is the `{}` rule", lint.rule.name()));
+            eprint!("{}", renderer.render(&report, &name, &source));
+        }
+    }
+    Ok(passed)
+}
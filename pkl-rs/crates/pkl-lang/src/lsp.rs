@@ -0,0 +1,22 @@
+//! `pkl-lang lsp`, which runs the language server of [`pkl_lsp`] for an editor, over standard input and output.
+
+use std::io;
+use std::process::ExitCode;
+
+use clap::Args;
+
+use crate::Failure;
+
+/// Run a language server, which reports the problems of the documents an editor has open as they change
+#[derive(Debug, Args)]
+pub struct LspArgs {}
+
+pub fn run(_args: LspArgs) -> ExitCode {
+    match pkl_lsp::serve(&mut io::stdin().lock(), &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            Failure::Message(message).print();
+            ExitCode::FAILURE
+        }
+    }
+}
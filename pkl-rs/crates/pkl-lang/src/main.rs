@@ -1,20 +1,186 @@
-use pkl_lexer::{token::TokenKind, Lexer};
-use oxc_allocator::Allocator;
+use std::process::ExitCode;
+use std::sync::OnceLock;
 
-fn main() {
-    let alloc = Allocator::default();
+use clap::{Parser, Subcommand, ValueEnum};
+use pkl_diagnostics::{Format, Renderer, Report, Severity};
+use pkl_eval::Log;
+use pkl_lexer::diagnostic::Diagnostic;
 
-    let source = "`hello` + `world`";
+mod analyze;
+mod ast_json;
+mod cache;
+mod check;
+mod doc;
+mod eval;
+mod fmt;
+mod gen_rust;
+mod lint;
+mod lsp;
+mod msgpack;
+mod parse;
+mod project;
+mod repl;
+mod server;
+mod watch;
 
-    let mut lexer = Lexer::new(&alloc, source);
+/// Tools for working with Pkl configuration
+#[derive(Debug, Parser)]
+#[command(name = "pkl-lang", version)]
+struct Cli {
+    /// How diagnostics are written to standard error: as text, or as a line of JSON each, with their code, severity,
+    /// message, file, and spans
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value_t = DiagnosticsFormat::Text)]
+    diagnostics_format: DiagnosticsFormat,
 
-    while !lexer.source.is_at_end() {
-        let tok = lexer.next_token();
-        if tok.kind != TokenKind::Empty {
-            let span = tok.span;
-            let c = &source[span.start..span.end];
-            println!("{}", c);
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DiagnosticsFormat {
+    Text,
+    Json,
+}
+
+/// The format of diagnostics, set once from the command line
+static DIAGNOSTICS_FORMAT: OnceLock<Format> = OnceLock::new();
+
+/// The renderer of diagnostics for standard error, in the format given on the command line.
+pub(crate) fn renderer() -> Renderer {
+    let format = DIAGNOSTICS_FORMAT.get().copied().unwrap_or_default();
+    Renderer { format, ..Renderer::stderr() }
+}
+
+/// The renderer of diagnostics that are sent to clients rather than written to standard error: in the format given
+/// on the command line, without color.
+pub(crate) fn uncolored_renderer() -> Renderer {
+    Renderer { color: false, ..renderer() }
+}
+
+/// Writes a message about what a command is doing, like the files `eval --watch` is watching, to standard error.
+pub(crate) fn status(message: &str) {
+    eprint!("{}", renderer().render(&Report::new(Severity::Info, message), "", ""));
+}
+
+/// Writes a message logged while evaluating, like a warning about a deprecated member, to standard error.
+pub(crate) fn log(log: &Log) {
+    eprint!("{}", render_log(log, &renderer()));
+}
+
+/// A message logged while evaluating as a diagnostic, with the line of the module it's about.
+pub(crate) fn render_log(log: &Log, renderer: &Renderer) -> String {
+    let name = log.uri.strip_prefix("file://").unwrap_or(log.uri);
+    renderer.render(&log.report(), name, log.source.unwrap_or_default())
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    Analyze(analyze::AnalyzeArgs),
+    Cache(cache::CacheArgs),
+    Check(check::CheckArgs),
+    Doc(doc::DocArgs),
+    Eval(Box<eval::EvalArgs>),
+    Fmt(fmt::FmtArgs),
+    GenRust(gen_rust::GenRustArgs),
+    Lint(lint::LintArgs),
+    Lsp(lsp::LspArgs),
+    Parse(parse::ParseArgs),
+    Project(project::ProjectArgs),
+    Repl(repl::ReplArgs),
+    Server(server::ServerArgs),
+}
+
+/// Why a subcommand failed.
+#[derive(Debug)]
+pub(crate) enum Failure {
+    /// A problem outside of any module's source, like a file that can't be read
+    Message(String),
+    /// Problems in a module, rendered with the lines they're on, each under a heading of its own
+    Reports(String),
+}
+
+impl Failure {
+    /// The diagnostics of a module that can't be read, rendered for standard error.
+    pub(crate) fn diagnostics(name: &str, source: &str, diagnostics: &[Diagnostic]) -> Self {
+        let renderer = renderer();
+        let reports = diagnostics.iter().map(|diagnostic| renderer.render(&diagnostic.report(), name, source));
+        Failure::Reports(reports.collect())
+    }
+
+    pub(crate) fn print(&self) {
+        match self {
+            Failure::Message(message) if renderer().format == Format::Json => {
+                eprint!("{}", renderer().render(&Report::error(message), "", ""))
+            }
+            Failure::Message(message) => eprintln!("error: {message}"),
+            Failure::Reports(reports) => eprint!("{reports}"),
         }
     }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure::Message(message)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let format = match cli.diagnostics_format {
+        DiagnosticsFormat::Text => Format::Text,
+        DiagnosticsFormat::Json => Format::Json,
+    };
+    let _ = DIAGNOSTICS_FORMAT.set(format);
+    match cli.command {
+        Command::Analyze(args) => analyze::run(args),
+        Command::Cache(args) => cache::run(args),
+        Command::Check(args) => check::run(args),
+        Command::Doc(args) => doc::run(args),
+        Command::Eval(args) => eval::run(*args),
+        Command::Fmt(args) => fmt::run(args),
+        Command::GenRust(args) => gen_rust::run(args),
+        Command::Lint(args) => lint::run(args),
+        Command::Lsp(args) => lsp::run(args),
+        Command::Parse(args) => parse::run(args),
+        Command::Project(args) => project::run(args),
+        Command::Repl(args) => repl::run(args),
+        Command::Server(args) => server::run(args),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+    use pkl_diagnostics::{Format, Renderer};
+    use pkl_eval::{Log, LogLevel};
+    use pkl_lexer::token::Span;
 
-}
\ No newline at end of file
+    use super::{render_log, Cli, Command, DiagnosticsFormat};
+
+    #[test]
+    fn diagnostics_format_is_global() {
+        let cli = Cli::try_parse_from(["pkl-lang", "fmt", "--diagnostics-format", "json", "a.pkl"]).unwrap();
+        assert_eq!(cli.diagnostics_format, DiagnosticsFormat::Json);
+        assert!(matches!(cli.command, Command::Fmt(_)));
+
+        let cli = Cli::try_parse_from(["pkl-lang", "lint", "a.pkl"]).unwrap();
+        assert_eq!(cli.diagnostics_format, DiagnosticsFormat::Text);
+    }
+
+    #[test]
+    fn logs_are_diagnostics() {
+        let source = "a = trace(1 + 1)\n";
+        let log = Log {
+            level: LogLevel::Trace,
+            message: "1 + 1 = 2",
+            code: None,
+            uri: "file:///birds/a.pkl",
+            span: Span::new(10, 15),
+            source: Some(source),
+        };
+        let text = render_log(&log, &Renderer::default());
+        assert_eq!(text, "info: 1 + 1 = 2\n --> /birds/a.pkl:1:11\n  |\n1 | a = trace(1 + 1)\n  |           ^^^^^\n");
+        let json = render_log(&log, &Renderer { format: Format::Json, ..Renderer::default() });
+        assert!(json.starts_with(r#"{"severity":"info","code":null,"message":"1 + 1 = 2","file":"/birds/a.pkl","#));
+    }
+}
@@ -0,0 +1,248 @@
+//! The subset of MessagePack that the messages of `pkl-lang server` are made of: everything but extension types.
+
+use std::io::{self, Read, Write};
+
+/// A MessagePack value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    /// An unsigned integer too large for an `Int`
+    UInt(u64),
+    Float(f64),
+    Str(String),
+    Bin(Vec<u8>),
+    Array(Vec<Msg>),
+    Map(Vec<(Msg, Msg)>),
+}
+
+impl Msg {
+    pub fn str(s: &str) -> Msg {
+        Msg::Str(s.to_string())
+    }
+
+    /// The value of a key of a map.
+    pub fn get(&self, key: &str) -> Option<&Msg> {
+        match self {
+            Msg::Map(entries) => entries.iter().find(|(k, _)| matches!(k, Msg::Str(k) if k == key)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Msg::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Msg::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Msg]> {
+        match self {
+            Msg::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Encodes the value, in the shortest of the forms it can take.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Msg::Nil => out.push(0xc0),
+            Msg::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+            Msg::Int(n) if *n >= 0 => encode_uint(*n as u64, out),
+            Msg::Int(n) if *n >= -32 => out.push(*n as u8),
+            Msg::Int(n) if *n >= i8::MIN as i64 => out.extend([0xd0, *n as u8]),
+            Msg::Int(n) if *n >= i16::MIN as i64 => tagged(0xd1, &(*n as i16).to_be_bytes(), out),
+            Msg::Int(n) if *n >= i32::MIN as i64 => tagged(0xd2, &(*n as i32).to_be_bytes(), out),
+            Msg::Int(n) => tagged(0xd3, &n.to_be_bytes(), out),
+            Msg::UInt(n) => encode_uint(*n, out),
+            Msg::Float(x) => tagged(0xcb, &x.to_be_bytes(), out),
+            Msg::Str(s) => {
+                encode_len(s.len(), (0xa0, 32), [0xd9, 0xda, 0xdb], out);
+                out.extend_from_slice(s.as_bytes());
+            }
+            Msg::Bin(bytes) => {
+                encode_len(bytes.len(), (0, 0), [0xc4, 0xc5, 0xc6], out);
+                out.extend_from_slice(bytes);
+            }
+            Msg::Array(elements) => {
+                encode_len(elements.len(), (0x90, 16), [0, 0xdc, 0xdd], out);
+                elements.iter().for_each(|element| element.encode(out));
+            }
+            Msg::Map(entries) => {
+                encode_len(entries.len(), (0x80, 16), [0, 0xde, 0xdf], out);
+                for (key, value) in entries {
+                    key.encode(out);
+                    value.encode(out);
+                }
+            }
+        }
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        writer.write_all(&out)?;
+        writer.flush()
+    }
+
+    /// Reads the next value, or nothing at the end of the input.
+    pub fn read(reader: &mut dyn Read) -> io::Result<Option<Msg>> {
+        let mut marker = [0];
+        if reader.read(&mut marker)? == 0 {
+            return Ok(None);
+        }
+        decode(marker[0], reader).map(Some)
+    }
+}
+
+fn tagged(marker: u8, bytes: &[u8], out: &mut Vec<u8>) {
+    out.push(marker);
+    out.extend_from_slice(bytes);
+}
+
+fn encode_uint(n: u64, out: &mut Vec<u8>) {
+    match n {
+        0..=0x7f => out.push(n as u8),
+        0x80..=0xff => out.extend([0xcc, n as u8]),
+        0x100..=0xffff => tagged(0xcd, &(n as u16).to_be_bytes(), out),
+        0x1_0000..=0xffff_ffff => tagged(0xce, &(n as u32).to_be_bytes(), out),
+        _ => tagged(0xcf, &n.to_be_bytes(), out),
+    }
+}
+
+/// Encodes the length of a string, binary, array, or map: in the marker of its fixed form, if it has one and the
+/// length fits, or else after the marker for an 8, 16, or 32-bit length, where 0 means there's no such form.
+fn encode_len(len: usize, (fixed, limit): (u8, usize), markers: [u8; 3], out: &mut Vec<u8>) {
+    if len < limit {
+        out.push(fixed | len as u8);
+    } else if markers[0] != 0 && len <= 0xff {
+        out.extend([markers[0], len as u8]);
+    } else if len <= 0xffff {
+        tagged(markers[1], &(len as u16).to_be_bytes(), out);
+    } else {
+        tagged(markers[2], &(len as u32).to_be_bytes(), out);
+    }
+}
+
+fn decode(marker: u8, reader: &mut dyn Read) -> io::Result<Msg> {
+    Ok(match marker {
+        0x00..=0x7f => Msg::Int(marker as i64),
+        0x80..=0x8f => decode_map(marker as usize & 0x0f, reader)?,
+        0x90..=0x9f => decode_array(marker as usize & 0x0f, reader)?,
+        0xa0..=0xbf => decode_str(marker as usize & 0x1f, reader)?,
+        0xc0 => Msg::Nil,
+        0xc2 => Msg::Bool(false),
+        0xc3 => Msg::Bool(true),
+        0xc4 => Msg::Bin(bytes(uint(reader, 1)? as usize, reader)?),
+        0xc5 => Msg::Bin(bytes(uint(reader, 2)? as usize, reader)?),
+        0xc6 => Msg::Bin(bytes(uint(reader, 4)? as usize, reader)?),
+        0xca => Msg::Float(f32::from_bits(uint(reader, 4)? as u32) as f64),
+        0xcb => Msg::Float(f64::from_bits(uint(reader, 8)?)),
+        0xcc => Msg::Int(uint(reader, 1)? as i64),
+        0xcd => Msg::Int(uint(reader, 2)? as i64),
+        0xce => Msg::Int(uint(reader, 4)? as i64),
+        0xcf => match uint(reader, 8)? {
+            n if n <= i64::MAX as u64 => Msg::Int(n as i64),
+            n => Msg::UInt(n),
+        },
+        0xd0 => Msg::Int(uint(reader, 1)? as i8 as i64),
+        0xd1 => Msg::Int(uint(reader, 2)? as i16 as i64),
+        0xd2 => Msg::Int(uint(reader, 4)? as i32 as i64),
+        0xd3 => Msg::Int(uint(reader, 8)? as i64),
+        0xd9 => decode_str(uint(reader, 1)? as usize, reader)?,
+        0xda => decode_str(uint(reader, 2)? as usize, reader)?,
+        0xdb => decode_str(uint(reader, 4)? as usize, reader)?,
+        0xdc => decode_array(uint(reader, 2)? as usize, reader)?,
+        0xdd => decode_array(uint(reader, 4)? as usize, reader)?,
+        0xde => decode_map(uint(reader, 2)? as usize, reader)?,
+        0xdf => decode_map(uint(reader, 4)? as usize, reader)?,
+        0xe0..=0xff => Msg::Int(marker as i8 as i64),
+        _ => return Err(invalid(format!("unsupported MessagePack marker `{marker:#04x}`"))),
+    })
+}
+
+/// Reads a big-endian unsigned integer of `len` bytes.
+fn uint(reader: &mut dyn Read, len: usize) -> io::Result<u64> {
+    Ok(bytes(len, reader)?.iter().fold(0, |n, byte| n << 8 | *byte as u64))
+}
+
+fn bytes(len: usize, reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn decode_str(len: usize, reader: &mut dyn Read) -> io::Result<Msg> {
+    String::from_utf8(bytes(len, reader)?).map(Msg::Str).map_err(|_| invalid("a string isn't valid UTF-8".into()))
+}
+
+fn decode_array(len: usize, reader: &mut dyn Read) -> io::Result<Msg> {
+    let mut elements = Vec::with_capacity(len.min(1024));
+    for _ in 0..len {
+        elements.push(next(reader)?);
+    }
+    Ok(Msg::Array(elements))
+}
+
+fn decode_map(len: usize, reader: &mut dyn Read) -> io::Result<Msg> {
+    let mut entries = Vec::with_capacity(len.min(1024));
+    for _ in 0..len {
+        entries.push((next(reader)?, next(reader)?));
+    }
+    Ok(Msg::Map(entries))
+}
+
+/// Reads a value that has to be there, inside another one.
+fn next(reader: &mut dyn Read) -> io::Result<Msg> {
+    Msg::read(reader)?.ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "a message ends too early"))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Msg;
+
+    fn round_trip(msg: Msg) -> Vec<u8> {
+        let mut out = Vec::new();
+        msg.encode(&mut out);
+        assert_eq!(Msg::read(&mut out.as_slice()).unwrap(), Some(msg));
+        out
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(round_trip(Msg::Int(5)), [0x05]);
+        assert_eq!(round_trip(Msg::Int(-1)), [0xff]);
+        assert_eq!(round_trip(Msg::Int(300)), [0xcd, 0x01, 0x2c]);
+        assert_eq!(round_trip(Msg::Int(-200)), [0xd1, 0xff, 0x38]);
+        assert_eq!(round_trip(Msg::str("hi")), [0xa2, b'h', b'i']);
+        assert_eq!(round_trip(Msg::Array(vec![Msg::Nil, Msg::Bool(true)])), [0x92, 0xc0, 0xc3]);
+        assert_eq!(round_trip(Msg::Map(vec![(Msg::str("a"), Msg::Int(1))])), [0x81, 0xa1, b'a', 0x01]);
+        assert_eq!(round_trip(Msg::Bin(vec![1, 2])), [0xc4, 0x02, 0x01, 0x02]);
+        assert_eq!(round_trip(Msg::Str("x".repeat(40)))[..2], [0xd9, 40]);
+        round_trip(Msg::Float(1.5));
+        round_trip(Msg::UInt(u64::MAX));
+        round_trip(Msg::Int(i64::MIN));
+    }
+
+    #[test]
+    fn decoding() {
+        let mut input: &[u8] = &[0xca, 0x3f, 0xc0, 0x00, 0x00, 0xd0, 0x80];
+        assert_eq!(Msg::read(&mut input).unwrap(), Some(Msg::Float(1.5)));
+        assert_eq!(Msg::read(&mut input).unwrap(), Some(Msg::Int(-128)));
+        assert_eq!(Msg::read(&mut input).unwrap(), None);
+        assert!(Msg::read(&mut [0x92, 0x01].as_slice()).is_err());
+        assert!(Msg::read(&mut [0xc7].as_slice()).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! `pkl-lang parse`, which prints the tokens or the syntax tree of a module, for debugging the grammar and for tools
+//! that work with the parse.
+//!
+//! As text, tokens are listed one per line with where they are, and the syntax tree is printed indented, one line
+//! per node with its kind, where it is, and its names and literal values, under the field of its parent it's in.
+//! As JSON, tokens are objects with their kinds, byte offsets, and text, and the syntax tree is written as
+//! [`ast_json`](crate::ast_json) describes.
+//!
+//! The text form of the tree is also what the parser's snapshot tests compare: each module in `snapshots/` is
+//! checked against the tree and syntax errors in the `.tree` file next to it, so a change to the grammar shows up as
+//! a diff of those files. Running the tests with `UPDATE_SNAPSHOTS=1` rewrites them.
+
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+use oxc_allocator::Allocator;
+use pkl_lexer::line_index::LineIndex;
+use pkl_lexer::token::{Span, TokenKind, TokenValue};
+use pkl_lexer::Lexer;
+use pkl_stdlib::json::Json;
+
+use crate::ast_json::ToJson;
+use crate::Failure;
+
+/// Print the tokens or syntax tree of a Pkl module
+#[derive(Debug, Args)]
+pub struct ParseArgs {
+    /// What to print
+    #[arg(short, long, value_enum, default_value_t = Dump::Ast)]
+    dump: Dump,
+
+    /// How to print it
+    #[arg(short, long, value_enum, default_value_t = DumpFormat::Text)]
+    format: DumpFormat,
+
+    /// The module to parse; without one, it's read from standard input
+    file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Dump {
+    Tokens,
+    Ast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DumpFormat {
+    Text,
+    Json,
+}
+
+pub fn run(args: ParseArgs) -> ExitCode {
+    match parse(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Prints the dump to standard output, returning whether the module parsed without errors. The syntax errors are
+/// reported on standard error, after the dump of what the parser made of the module.
+fn parse(args: &ParseArgs) -> Result<bool, Failure> {
+    let (name, source) = match &args.file {
+        Some(path) => {
+            let name = path.display().to_string();
+            let source = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {name}: {err}"))?;
+            (name, source)
+        }
+        None => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source).map_err(|err| format!("couldn't read standard input: {err}"))?;
+            ("<stdin>".to_string(), source)
+        }
+    };
+
+    let alloc = Allocator::default();
+    let diagnostics = match args.dump {
+        Dump::Tokens => {
+            println!("{}", tokens(&alloc, &source, args.format));
+            Vec::new()
+        }
+        Dump::Ast => {
+            let result = pkl_parser::parse_module(&alloc, &source);
+            match args.format {
+                DumpFormat::Text => print!("{}", tree(&source, &result.node.to_json())),
+                DumpFormat::Json => println!("{}", result.node.to_json()),
+            }
+            result.diagnostics
+        }
+    };
+    if !diagnostics.is_empty() {
+        Failure::diagnostics(&name, &source, &diagnostics).print();
+    }
+    Ok(diagnostics.is_empty())
+}
+
+/// The syntax tree of `source`, written as JSON by [`ast_json`](crate::ast_json), as an indented tree.
+fn tree(source: &str, ast: &Json) -> String {
+    let mut out = String::new();
+    write_node(&mut out, &LineIndex::new(source), None, ast, 0);
+    out
+}
+
+/// Writes a line for a node, under the field `label` of its parent, and then its children indented below it.
+/// Variants holding a single node are written as that node.
+fn write_node(out: &mut String, lines: &LineIndex, label: Option<&str>, node: &Json, depth: usize) {
+    let Json::Object(fields) = node else { return };
+    if let [(_, _), (field, inner)] = fields.as_slice() {
+        if field == "value" && kind(inner).is_some_and(|kind| kind != "Span") {
+            return write_node(out, lines, label, inner, depth);
+        }
+    }
+
+    let mut line = format!("{:indent$}", "", indent = depth * 2);
+    if let Some(label) = label {
+        line.push_str(&format!("{label}: "));
+    }
+    line.push_str(kind(node).unwrap_or("?"));
+    let mut children = Vec::new();
+    let variant = fields.len() == 2;
+    for (name, value) in fields.iter().filter(|(name, _)| name != "type") {
+        // the unnamed fields of variants are written without names
+        let values = match (name.as_str(), value) {
+            ("values", Json::Array(values)) if variant => values.iter().map(|value| (None, value)).collect(),
+            ("value", value) if variant => vec![(None, value)],
+            (name, value) => vec![(Some(name), value)],
+        };
+        for (name, value) in values {
+            match value {
+                _ if is_empty(value) => {}
+                Json::Object(_) if kind(value) == Some("Span") => {
+                    let span = Span::new(int(value, "start"), int(value, "end"));
+                    let (start, end) = lines.span_line_cols(span);
+                    line.push_str(&format!(" {start}-{end}"));
+                }
+                Json::Object(_) => children.push((name.map(str::to_string), value)),
+                Json::Array(elements) if elements.iter().all(|element| matches!(element, Json::Object(_))) => {
+                    let name = name.unwrap_or("value");
+                    let elements = elements.iter().enumerate();
+                    children.extend(elements.map(|(index, element)| (Some(format!("{name}[{index}]")), element)));
+                }
+                Json::String(text) => line.push_str(&format!(" {}{text:?}", prefix(name))),
+                value => line.push_str(&format!(" {}{value}", prefix(name))),
+            }
+        }
+    }
+    out.push_str(&line);
+    out.push('\n');
+    for (label, child) in children {
+        write_node(out, lines, label.as_deref(), child, depth + 1);
+    }
+}
+
+/// Whether a value has nothing to write: it's `null`, an empty array, or a node with only such fields, like the
+/// `Modifiers` of a property without any.
+fn is_empty(value: &Json) -> bool {
+    match value {
+        Json::Null => true,
+        Json::Array(elements) => elements.is_empty(),
+        Json::Object(fields) => {
+            kind(value) != Some("Span") && fields.iter().all(|(name, value)| name == "type" || is_empty(value))
+        }
+        _ => false,
+    }
+}
+
+fn kind(node: &Json) -> Option<&str> {
+    node.get("type").and_then(Json::as_str)
+}
+
+fn prefix(name: Option<&str>) -> String {
+    name.map(|name| format!("{name}=")).unwrap_or_default()
+}
+
+fn int(node: &Json, name: &str) -> usize {
+    match node.get(name) {
+        Some(Json::Int(n)) => *n as usize,
+        _ => 0,
+    }
+}
+
+/// The tokens of `source`, up to but not including the end of the input.
+fn tokens(alloc: &Allocator, source: &str, format: DumpFormat) -> String {
+    let lines = LineIndex::new(source);
+    let tokens = Lexer::tokenize(alloc, source).into_iter().filter(|token| token.kind != TokenKind::Eof);
+    let tokens = tokens.map(|token| {
+        let text = &source[token.span.start..token.span.end];
+        let value = match token.value {
+            TokenValue::None => None,
+            TokenValue::Int(n) => Some(Json::Int(n)),
+            TokenValue::Float(n) => Some(Json::Float(n)),
+            TokenValue::Identifier(s) | TokenValue::String(s) => Some(Json::from(s)),
+        };
+        match format {
+            DumpFormat::Text => {
+                let (start, end) = lines.span_line_cols(token.span);
+                format!("{:<12} {:<24} {text:?}", format!("{start}-{end}"), format!("{:?}", token.kind))
+            }
+            DumpFormat::Json => {
+                let mut members = vec![
+                    ("kind", Json::from(format!("{:?}", token.kind))),
+                    ("start", Json::from(token.span.start)),
+                    ("end", Json::from(token.span.end)),
+                    ("text", Json::from(text)),
+                ];
+                members.extend(value.map(|value| ("value", value)));
+                Json::object(members).to_string()
+            }
+        }
+    });
+    let tokens: Vec<String> = tokens.collect();
+    match format {
+        DumpFormat::Text => tokens.join("\n"),
+        DumpFormat::Json => format!("[{}]", tokens.join(",")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn token_listings() {
+        let alloc = Allocator::default();
+        let text = tokens(&alloc, "a = 1\n", DumpFormat::Text);
+        assert_eq!(text.lines().next(), Some("1:1-1:2      Identifier               \"a\""));
+        let json = Json::parse(&tokens(&alloc, "a = 1\n", DumpFormat::Json)).unwrap();
+        let last = json.as_array().unwrap().last().unwrap().clone();
+        assert_eq!(
+            last,
+            Json::object([
+                ("kind", Json::from("IntLiteral")),
+                ("start", Json::from(4)),
+                ("end", Json::from(5)),
+                ("text", Json::from("1")),
+                ("value", Json::Int(1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn syntax_trees() {
+        let alloc = Allocator::default();
+        let source = "x = 1 + y\n";
+        let ast = pkl_parser::parse_module(&alloc, source).node.to_json();
+        let expected = "Module 1:1-2:1\n\
+                        \x20 header: ModuleHeader 1:1-1:1\n\
+                        \x20 members[0]: ClassProperty 1:1-1:10\n\
+                        \x20   name: Ident 1:1-1:2 name=\"x\"\n\
+                        \x20   value: BinaryExpr 1:5-1:10 op=\"Add\"\n\
+                        \x20     left: Int 1:5-1:6 1\n\
+                        \x20     right: Ident 1:9-1:10 name=\"y\"\n";
+        assert_eq!(tree(source, &ast), expected);
+
+        // a long chain is a line per operation and operand
+        let source = format!("x = {}1", "1 + ".repeat(200));
+        let ast = pkl_parser::parse_module(&alloc, &source).node.to_json();
+        assert_eq!(tree(&source, &ast).lines().count(), 405);
+    }
+
+    /// The tree of each module in `snapshots/`, followed by its syntax errors, matches its `.tree` file.
+    #[test]
+    fn snapshots() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+        let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+        let mut modules: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        modules.retain(|path| path.extension().is_some_and(|extension| extension == "pkl"));
+        modules.sort();
+        assert!(!modules.is_empty(), "no snapshots in {}", dir.display());
+
+        let mut mismatches = Vec::new();
+        for module in modules {
+            let source = std::fs::read_to_string(&module).unwrap();
+            let alloc = Allocator::default();
+            let result = pkl_parser::parse_module(&alloc, &source);
+            let mut actual = tree(&source, &result.node.to_json());
+            let lines = LineIndex::new(&source);
+            for diagnostic in &result.diagnostics {
+                let (start, end) = lines.span_line_cols(diagnostic.span);
+                actual.push_str(&format!("error {start}-{end}: {}\n", diagnostic.message));
+            }
+
+            let path = module.with_extension("tree");
+            let expected = std::fs::read_to_string(&path).unwrap_or_default();
+            if update {
+                std::fs::write(&path, &actual).unwrap();
+            } else if actual != expected {
+                let line = actual.lines().zip(expected.lines()).take_while(|(actual, expected)| actual == expected);
+                let line = line.count();
+                let (actual, expected) = (actual.lines().nth(line), expected.lines().nth(line));
+                mismatches.push(format!(
+                    "{}:{}\n  expected: {}\n  actual:   {}",
+                    path.display(),
+                    line + 1,
+                    expected.unwrap_or("<end>"),
+                    actual.unwrap_or("<end>")
+                ));
+            }
+        }
+        assert!(
+            mismatches.is_empty(),
+            "snapshots don't match, rerun with UPDATE_SNAPSHOTS=1 to update them:\n{}",
+            mismatches.join("\n")
+        );
+    }
+}
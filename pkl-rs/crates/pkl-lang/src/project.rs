@@ -0,0 +1,63 @@
+//! `pkl-lang project`, which works with projects: directories with a `PklProject` file.
+//!
+//! `pkl-lang project resolve` resolves the dependencies of each project, downloading the packages among them, and
+//! writes them to the project's `PklProject.deps.json`, which `pkl-lang eval` reads to import them.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Subcommand};
+use pkl_eval::project::{Project, DEPS_FILE, PROJECT_FILE};
+use pkl_eval::EvaluatorOptions;
+
+use crate::Failure;
+
+/// Work with Pkl projects
+#[derive(Debug, Args)]
+pub struct ProjectArgs {
+    #[command(subcommand)]
+    command: ProjectCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ProjectCommand {
+    Resolve(ResolveArgs),
+}
+
+/// Resolve the dependencies of projects into their `PklProject.deps.json` files
+#[derive(Debug, Args)]
+struct ResolveArgs {
+    /// The directory packages are downloaded to, rather than `~/.pkl/cache`
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// The directories of the projects; without any, the working directory
+    dirs: Vec<PathBuf>,
+}
+
+pub fn run(args: ProjectArgs) -> ExitCode {
+    let ProjectCommand::Resolve(args) = args.command;
+    match resolve(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            failure.print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Resolves each project, listing the path of each written file on standard output.
+fn resolve(args: &ResolveArgs) -> Result<(), Failure> {
+    let cache_dir = args.cache_dir.clone().or(EvaluatorOptions::default().cache_dir);
+    let dirs = if args.dirs.is_empty() { vec![PathBuf::from(".")] } else { args.dirs.clone() };
+    for dir in dirs {
+        let file = dir.join(PROJECT_FILE).display().to_string();
+        let project = Project::load_with_logger(&dir, crate::log).map_err(|error| format!("{file}: {error}"))?;
+        let resolved = project.resolve(cache_dir.as_deref()).map_err(|error| format!("{file}: {error}"))?;
+        let path = dir.join(DEPS_FILE);
+        std::fs::write(&path, resolved.to_json(&project.dir))
+            .map_err(|err| format!("couldn't write {}: {err}", path.display()))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
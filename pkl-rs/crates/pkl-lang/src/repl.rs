@@ -0,0 +1,306 @@
+//! `pkl-lang repl`, which evaluates definitions and expressions as they're typed.
+//!
+//! Input that defines module members or imports, like `name = "pigeon"` or `import "pkl:math"`, adds them to the
+//! session, replacing earlier definitions of the same names. Other input is an expression, evaluated in the context of
+//! the session's definitions and printed in Pcf. Input continues over several lines while it's incomplete, like an
+//! object whose `{` isn't closed yet.
+//!
+//! History is kept in `~/.pkl/repl-history`.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use oxc_allocator::Allocator;
+use pkl_ast::{Module, ModuleMember};
+use pkl_eval::{Error, Evaluator};
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_render::{PcfOptions, PcfRenderer, Renderer};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::Failure;
+
+const PROMPT: &str = "pkl> ";
+const CONTINUATION_PROMPT: &str = "...> ";
+const HELP: &str = "\
+Enter definitions, like `name = \"pigeon\"`, or expressions to evaluate, like `name.length`.
+
+:help          show this message
+:load <file>   add the imports and members of a module to the session
+:reset         forget every definition
+:quit          leave the REPL";
+
+/// The name of the local property holding an evaluated expression, which can't clash with the session's own.
+const EXPRESSION: &str = "`pkl-lang:expression`";
+
+/// Evaluate Pkl interactively
+#[derive(Debug, Args)]
+pub struct ReplArgs {}
+
+pub fn run(_args: ReplArgs) -> ExitCode {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            Failure::Message(format!("couldn't start the REPL: {err}")).print();
+            return ExitCode::FAILURE;
+        }
+    };
+    let history = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pkl").join("repl-history"));
+    if let Some(history) = &history {
+        // there's no history the first time
+        let _ = editor.load_history(history);
+    }
+
+    println!("Welcome to Pkl. Type :help for help.");
+    let mut session = Session::default();
+    let mut input = String::new();
+    loop {
+        let prompt = if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // Ctrl-C abandons the current input, and Ctrl-D the REPL
+            Err(ReadlineError::Interrupted) => {
+                input.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                Failure::Message(format!("couldn't read input: {err}")).print();
+                return ExitCode::FAILURE;
+            }
+        };
+
+        // an empty line ends incomplete input, to show what's wrong with it
+        let ends_input = !input.is_empty() && line.trim().is_empty();
+        if !ends_input {
+            if !input.is_empty() {
+                input.push('\n');
+            }
+            input.push_str(&line);
+            if input.trim().is_empty() || is_incomplete(&input) {
+                continue;
+            }
+        }
+
+        let _ = editor.add_history_entry(input.as_str());
+        match session.run(&input) {
+            Ok(Reply::Output(output)) => print!("{output}"),
+            Ok(Reply::Quit) => break,
+            Ok(Reply::Nothing) => {}
+            Err(message) => Failure::Message(message).print(),
+        }
+        input.clear();
+    }
+
+    if let Some(history) = &history {
+        if let Some(dir) = history.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(err) = editor.save_history(history) {
+            Failure::Message(format!("couldn't save the history: {err}")).print();
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// What the REPL does after some input.
+#[derive(Debug, PartialEq, Eq)]
+enum Reply {
+    /// Prints text ending in a newline
+    Output(String),
+    Nothing,
+    Quit,
+}
+
+/// The definitions entered so far.
+#[derive(Debug, Default)]
+struct Session {
+    /// The source of each import, by the name it defines, in the order they were entered
+    imports: Vec<(String, String)>,
+    /// The source of each member, by what it defines, like `property name`, in the order they were entered
+    members: Vec<(String, String)>,
+}
+
+impl Session {
+    /// Runs a command, adds definitions, or evaluates an expression.
+    fn run(&mut self, input: &str) -> Result<Reply, String> {
+        let input = input.trim();
+        if let Some(command) = input.strip_prefix(':') {
+            let (command, argument) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+            return match (command, argument.trim()) {
+                ("help", _) => Ok(Reply::Output(format!("{HELP}\n"))),
+                ("quit" | "q", _) => Ok(Reply::Quit),
+                ("reset", _) => {
+                    *self = Session::default();
+                    Ok(Reply::Nothing)
+                }
+                ("load", "") => Err("`:load` needs the file to load".to_string()),
+                ("load", path) => {
+                    let source = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {path}: {err}"))?;
+                    self.define(&source)?.ok_or_else(|| format!("{path} doesn't define anything"))
+                }
+                _ => Err(format!("unknown command `:{command}`; type :help for the commands")),
+            };
+        }
+
+        if let Some(reply) = self.define(input)? {
+            return Ok(reply);
+        }
+        self.evaluate(input)
+    }
+
+    /// Adds the imports and members defined by `input`, if it's a module that defines any, and evaluates the session
+    /// with them; they're only kept if that succeeds.
+    fn define(&mut self, input: &str) -> Result<Option<Reply>, String> {
+        let alloc = Allocator::default();
+        let module = pkl_parser::parse_module(&alloc, input);
+        let defines_nothing = module.node.header.imports.is_empty() && module.node.members.is_empty();
+        if !module.diagnostics.is_empty() || defines_nothing {
+            return Ok(None);
+        }
+        if module.node.header.name.is_some() || module.node.header.extends.is_some() {
+            return Err("a REPL session can't have a module clause, or an `amends` or `extends` clause".to_string());
+        }
+
+        let mut session = Session { imports: self.imports.clone(), members: self.members.clone() };
+        for (name, source) in definitions(input, &module.node) {
+            let definitions = if name.starts_with("import ") { &mut session.imports } else { &mut session.members };
+            definitions.retain(|(defined, _)| *defined != name);
+            definitions.push((name, source));
+        }
+        let source = session.source();
+        let alloc = Allocator::default();
+        let module = pkl_parser::parse_module(&alloc, &source);
+        if !module.diagnostics.is_empty() {
+            return Err(message(&Error::Syntax(module.diagnostics)));
+        }
+        let mut evaluator = Evaluator::new(&alloc);
+        evaluator.set_logger(crate::log);
+        evaluator.add_source("repl:text", alloc.alloc_str(&source));
+        evaluator.evaluate_module(alloc.alloc(module.node), "repl:text").map_err(|error| error.message)?;
+        *self = session;
+        Ok(Some(Reply::Nothing))
+    }
+
+    /// Evaluates an expression in the context of the session's definitions, rendering its value in Pcf.
+    fn evaluate(&self, input: &str) -> Result<Reply, String> {
+        let source = format!("{}\nlocal {EXPRESSION} = {input}\n", self.source());
+        let alloc = Allocator::default();
+        let module = pkl_parser::parse_module(&alloc, &source);
+        if !module.diagnostics.is_empty() {
+            // the expression is the only input that hasn't been parsed already
+            let expr = pkl_parser::parse_expr(&alloc, input);
+            let diagnostics = if expr.diagnostics.is_empty() { module.diagnostics } else { expr.diagnostics };
+            return Err(message(&Error::Syntax(diagnostics)));
+        }
+
+        let expr = pkl_parser::parse_expr(&alloc, EXPRESSION).node;
+        let mut evaluator = Evaluator::new(&alloc);
+        evaluator.set_logger(crate::log);
+        evaluator.add_source("repl:text", alloc.alloc_str(&source));
+        let value = evaluator
+            .evaluate_expr_in(alloc.alloc(module.node), "repl:text", alloc.alloc(expr))
+            .map_err(|error| error.message)?;
+        let rendered = PcfRenderer::new(PcfOptions::default()).render(&value).map_err(|error| error.to_string())?;
+        Ok(Reply::Output(rendered))
+    }
+
+    /// The source of a module with the session's definitions.
+    fn source(&self) -> String {
+        let imports = self.imports.iter().map(|(_, source)| source.as_str());
+        let members = self.members.iter().map(|(_, source)| source.as_str());
+        imports.chain(members).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// The source of each import and member of a module, by what it defines.
+fn definitions(source: &str, module: &Module) -> Vec<(String, String)> {
+    let text = |span: pkl_ast::Span| source[span.start..span.end].to_string();
+    let mut definitions = Vec::new();
+    for import in module.header.imports.iter() {
+        let name = match &import.alias {
+            Some(alias) => alias.name,
+            None => {
+                let name = import.uri.value.rsplit(['/', ':']).next().unwrap_or(import.uri.value);
+                name.strip_suffix(".pkl").unwrap_or(name)
+            }
+        };
+        definitions.push((format!("import {name}"), text(import.span)));
+    }
+    for member in module.members.iter() {
+        let name = match member {
+            ModuleMember::Class(class) => format!("class {}", class.name.name),
+            ModuleMember::TypeAlias(alias) => format!("typealias {}", alias.name.name),
+            ModuleMember::Property(property) => format!("property {}", property.name.name),
+            ModuleMember::Method(method) => format!("method {}", method.name.name),
+        };
+        definitions.push((name, text(member.span())));
+    }
+    definitions
+}
+
+/// Whether input ends before it's complete, so that more lines should be read: it doesn't parse as a module or an
+/// expression, and parsing it as one of them fails at its end.
+fn is_incomplete(input: &str) -> bool {
+    let alloc = Allocator::default();
+    let module = pkl_parser::parse_module(&alloc, input).diagnostics;
+    let expr = pkl_parser::parse_expr(&alloc, input).diagnostics;
+    let at_end = |diagnostics: &[Diagnostic]| diagnostics.iter().any(|diagnostic| diagnostic.span.start >= input.len());
+    !module.is_empty() && !expr.is_empty() && (at_end(&module) || at_end(&expr))
+}
+
+fn message(error: &Error) -> String {
+    match error {
+        Error::Syntax(diagnostics) => {
+            diagnostics.first().map_or_else(String::new, |diagnostic| diagnostic.message.clone())
+        }
+        Error::Eval(error) => error.message.clone(),
+        Error::Deserialize(error) => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn definitions_and_expressions() {
+        let mut session = Session::default();
+        assert_eq!(session.run("name = \"pigeon\""), Ok(Reply::Nothing));
+        assert_eq!(session.run("import \"pkl:math\"\nfunction double(n) = n * 2"), Ok(Reply::Nothing));
+        assert_eq!(session.run("double(name.length)"), Ok(Reply::Output("12\n".to_string())));
+        assert_eq!(session.run("name = \"crow\""), Ok(Reply::Nothing));
+        assert_eq!(session.run("name"), Ok(Reply::Output("\"crow\"\n".to_string())));
+        assert_eq!(session.run("math.max(1, 2)"), Ok(Reply::Output("2\n".to_string())));
+
+        // definitions that fail to evaluate aren't kept
+        let message = "operator `+` isn't defined for `Int` and `String`";
+        assert_eq!(session.run("name = 1 + \"a\""), Err(message.to_string()));
+        assert_eq!(session.run("name"), Ok(Reply::Output("\"crow\"\n".to_string())));
+        assert_eq!(session.run(":reset"), Ok(Reply::Nothing));
+        assert_eq!(session.run("name"), Err("can't find property `name`".to_string()));
+    }
+
+    #[test]
+    fn incomplete_input() {
+        assert!(is_incomplete("bird {"));
+        assert!(is_incomplete("bird {\n  name = "));
+        assert!(is_incomplete("List(1,"));
+        assert!(!is_incomplete("bird { name = \"pigeon\" }"));
+        assert!(!is_incomplete("1 + )"));
+    }
+
+    #[test]
+    fn commands() {
+        let mut session = Session::default();
+        assert_eq!(session.run(":quit"), Ok(Reply::Quit));
+        let message = "unknown command `:frobnicate`; type :help for the commands";
+        assert_eq!(session.run(":frobnicate"), Err(message.to_string()));
+        let path = std::env::temp_dir().join(format!("pkl-lang-repl-{}.pkl", std::process::id()));
+        std::fs::write(&path, "bird { name = \"pigeon\" }").unwrap();
+        assert_eq!(session.run(&format!(":load {}", path.display())), Ok(Reply::Nothing));
+        assert_eq!(session.run("bird.name"), Ok(Reply::Output("\"pigeon\"\n".to_string())));
+        std::fs::remove_file(path).unwrap();
+    }
+}
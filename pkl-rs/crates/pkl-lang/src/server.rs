@@ -0,0 +1,482 @@
+//! `pkl-lang server`, which evaluates modules for a client over the message passing protocol of `pkl server`, so that
+//! the language bindings made for the official evaluator, like pkl-go and pkl-swift, can use this one instead.
+//!
+//! Messages are MessagePack arrays of a code and a map, read from standard input and written to standard output one
+//! after another. A client creates evaluators with the options it wants, has them evaluate modules, whose values are
+//! sent back in Pkl's binary encoding, and closes them. Modules can read resources of the client's own schemes, which
+//! the server asks the client for while it evaluates them, and their `trace` messages are sent as log messages.
+//! Client module readers and module paths aren't supported: creating an evaluator with them fails.
+//!
+//! A request the server doesn't know is answered with an error, in a response with the code after the request's, the
+//! way the protocol numbers them, and the server goes on with the next message. A message without a request id that
+//! it doesn't know, like a response to a request it never made, is ignored.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::Duration;
+
+use clap::Args;
+use oxc_allocator::Allocator;
+use pkl_eval::value::{ModuleOutput, ObjectKind, Value};
+use pkl_eval::{Error, Evaluator, EvaluatorOptions, LogLevel, Resource, ResourceReader};
+
+use crate::eval::{describe, evaluate_expression};
+use crate::Failure;
+use crate::msgpack::Msg;
+
+const CREATE_EVALUATOR: i64 = 0x20;
+const CREATE_EVALUATOR_RESPONSE: i64 = 0x21;
+const CLOSE_EVALUATOR: i64 = 0x22;
+const EVALUATE: i64 = 0x23;
+const EVALUATE_RESPONSE: i64 = 0x24;
+const LOG: i64 = 0x25;
+const READ_RESOURCE: i64 = 0x26;
+const READ_RESOURCE_RESPONSE: i64 = 0x27;
+
+/// The levels of `Log` messages
+const LOG_TRACE: i64 = 0;
+const LOG_WARN: i64 = 1;
+
+/// Evaluate modules for a client over the message passing protocol of `pkl server`
+#[derive(Debug, Args)]
+pub struct ServerArgs {}
+
+pub fn run(_args: ServerArgs) -> ExitCode {
+    match serve(Box::new(io::stdin()), Box::new(io::stdout())) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            Failure::Message(message).print();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Handles the messages from `input` until it ends, writing the responses to `output`.
+fn serve(input: Box<dyn Read>, output: Box<dyn Write>) -> Result<(), String> {
+    let connection = Rc::new(Connection {
+        input: RefCell::new(input),
+        output: RefCell::new(output),
+        pending: RefCell::default(),
+        next_request_id: Cell::new(1),
+    });
+    let mut evaluators = HashMap::new();
+    let mut next_evaluator_id = 1;
+
+    while let Some((code, body)) = connection.receive()? {
+        let request_id = body.get("requestId").cloned().unwrap_or(Msg::Nil);
+        let evaluator_id = body.get("evaluatorId").and_then(Msg::as_int);
+        match code {
+            CREATE_EVALUATOR => {
+                let response = match configure(&body) {
+                    Ok(config) => {
+                        evaluators.insert(next_evaluator_id, config);
+                        next_evaluator_id += 1;
+                        ("evaluatorId", Msg::Int(next_evaluator_id - 1))
+                    }
+                    Err(message) => ("error", Msg::Str(message)),
+                };
+                connection.send(CREATE_EVALUATOR_RESPONSE, vec![("requestId", request_id), response])?;
+            }
+            CLOSE_EVALUATOR => _ = evaluator_id.and_then(|id| evaluators.remove(&id)),
+            EVALUATE => {
+                let id = evaluator_id.unwrap_or_default();
+                let result = match evaluators.get(&id) {
+                    Some(config) => evaluate(&connection, id, config, &body),
+                    None => Err(format!("there's no evaluator with the id `{id}`")),
+                };
+                let result = match result {
+                    Ok(value) => ("result", Msg::Bin(value)),
+                    Err(message) => ("error", Msg::Str(message)),
+                };
+                let body = vec![("requestId", request_id), ("evaluatorId", Msg::Int(id)), result];
+                connection.send(EVALUATE_RESPONSE, body)?;
+            }
+            code => {
+                let message = format!("unexpected message with the code `{code:#x}`");
+                match request_id {
+                    Msg::Nil => crate::status(&format!("ignoring an {message}")),
+                    request_id => {
+                        let mut body = vec![("requestId", request_id)];
+                        body.extend(evaluator_id.map(|id| ("evaluatorId", Msg::Int(id))));
+                        body.push(("error", Msg::Str(message)));
+                        connection.send(code + 1, body)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The messages going back and forth between the client and the server.
+struct Connection {
+    input: RefCell<Box<dyn Read>>,
+    output: RefCell<Box<dyn Write>>,
+    /// The messages that arrived while the server waited for the response to a request of its own, to handle next
+    pending: RefCell<VecDeque<(i64, Msg)>>,
+    next_request_id: Cell<i64>,
+}
+
+impl Connection {
+    fn send(&self, code: i64, body: Vec<(&str, Msg)>) -> Result<(), String> {
+        let body = body.into_iter().map(|(key, value)| (Msg::str(key), value)).collect();
+        let message = Msg::Array(vec![Msg::Int(code), Msg::Map(body)]);
+        message.write(&mut **self.output.borrow_mut()).map_err(|err| format!("couldn't write a message: {err}"))
+    }
+
+    /// The next message, with its code and body, or nothing once the client is done.
+    fn receive(&self) -> Result<Option<(i64, Msg)>, String> {
+        if let Some(message) = self.pending.borrow_mut().pop_front() {
+            return Ok(Some(message));
+        }
+        let message = Msg::read(&mut **self.input.borrow_mut());
+        match message.map_err(|err| format!("couldn't read a message: {err}"))? {
+            None => Ok(None),
+            Some(Msg::Array(mut parts)) if parts.len() == 2 && matches!(parts[1], Msg::Map(_)) => {
+                let body = parts.pop().expect("checked");
+                match parts[0] {
+                    Msg::Int(code) => Ok(Some((code, body))),
+                    _ => Err("expected a message to start with its code".to_string()),
+                }
+            }
+            Some(_) => Err("expected a message to be an array of a code and a map".to_string()),
+        }
+    }
+
+    /// Sends a request to the client and waits for the response, the `response` message with the same request id.
+    fn request(&self, code: i64, mut body: Vec<(&str, Msg)>, response: i64) -> Result<Msg, String> {
+        let request_id = self.next_request_id.get();
+        self.next_request_id.set(request_id + 1);
+        body.insert(0, ("requestId", Msg::Int(request_id)));
+        self.send(code, body)?;
+        loop {
+            let Some((code, body)) = self.receive_new()? else {
+                return Err("the client left before responding".to_string());
+            };
+            if code == response && body.get("requestId").and_then(Msg::as_int) == Some(request_id) {
+                return Ok(body);
+            }
+            self.pending.borrow_mut().push_back((code, body));
+        }
+    }
+
+    /// The next message from the client, leaving the pending ones for later.
+    fn receive_new(&self) -> Result<Option<(i64, Msg)>, String> {
+        let pending = std::mem::take(&mut *self.pending.borrow_mut());
+        let message = self.receive();
+        let mut current = self.pending.borrow_mut();
+        let arrived = std::mem::replace(&mut *current, pending);
+        current.extend(arrived);
+        message
+    }
+}
+
+/// What an evaluator created by the client does.
+struct Config {
+    options: EvaluatorOptions,
+    /// The schemes of the resources the client reads
+    readers: Vec<String>,
+}
+
+/// The configuration of an evaluator, from a `CreateEvaluator` message.
+fn configure(body: &Msg) -> Result<Config, String> {
+    let strings = |key: &str| -> Option<Vec<String>> {
+        let elements = body.get(key)?.as_array()?;
+        Some(elements.iter().filter_map(Msg::as_str).map(String::from).collect())
+    };
+    let string_map = |key: &str| -> Option<HashMap<String, String>> {
+        let Some(Msg::Map(entries)) = body.get(key) else { return None };
+        let entries = entries.iter().filter_map(|(key, value)| Some((key.as_str()?, value.as_str()?)));
+        Some(entries.map(|(key, value)| (key.to_string(), value.to_string())).collect())
+    };
+    let is_empty = |key: &str| body.get(key).and_then(Msg::as_array).is_none_or(<[_]>::is_empty);
+    if !is_empty("clientModuleReaders") {
+        return Err("client module readers aren't supported".to_string());
+    }
+    if !is_empty("modulePaths") {
+        return Err("module paths aren't supported".to_string());
+    }
+
+    let mut options = EvaluatorOptions::default();
+    if let Some(patterns) = strings("allowedModules") {
+        options.allowed_modules = patterns;
+    }
+    if let Some(patterns) = strings("allowedResources") {
+        options.allowed_resources = patterns;
+    }
+    options.environment_variables = string_map("env");
+    options.external_properties = string_map("properties").unwrap_or_default();
+    let timeout = body.get("timeoutSeconds").and_then(Msg::as_int);
+    options.timeout = timeout.map(|seconds| Duration::from_secs(seconds.max(0) as u64));
+    options.root_dir = body.get("rootDir").and_then(Msg::as_str).map(Into::into);
+    if let Some(dir) = body.get("cacheDir").and_then(Msg::as_str) {
+        options.cache_dir = Some(dir.into());
+    }
+
+    let readers = body.get("clientResourceReaders").and_then(Msg::as_array).unwrap_or_default();
+    let readers = readers.iter().filter_map(|reader| reader.get("scheme")?.as_str()).map(String::from).collect();
+    Ok(Config { options, readers })
+}
+
+/// Evaluates the module of an `Evaluate` message, or the expression in it, into the binary encoding of its value.
+fn evaluate(connection: &Rc<Connection>, evaluator_id: i64, config: &Config, body: &Msg) -> Result<Vec<u8>, String> {
+    let uri = body.get("moduleUri").and_then(Msg::as_str).ok_or("expected a `moduleUri`")?;
+    let source = match (body.get("moduleText").and_then(Msg::as_str), uri.strip_prefix("file://")) {
+        (Some(text), _) => text.to_string(),
+        (None, Some(path)) => std::fs::read_to_string(path).map_err(|err| format!("couldn't read {uri}: {err}"))?,
+        // the evaluator reads other modules itself, when they're imported
+        (None, None) => format!("amends {}", pkl_stdlib::string::quote(uri)),
+    };
+    let setup = |evaluator: &mut Evaluator| {
+        for scheme in &config.readers {
+            let reader = ClientReader { scheme: scheme.clone(), evaluator_id, connection: connection.clone() };
+            evaluator.add_resource_reader(reader);
+        }
+        let connection = connection.clone();
+        evaluator.set_logger(move |log| {
+            let level = match log.level {
+                LogLevel::Trace => LOG_TRACE,
+                LogLevel::Warn => LOG_WARN,
+            };
+            let body = vec![
+                ("evaluatorId", Msg::Int(evaluator_id)),
+                ("level", Msg::Int(level)),
+                ("message", Msg::str(log.message)),
+                ("frameUri", Msg::str(log.uri)),
+            ];
+            // a log message that can't be written isn't worth failing the evaluation for
+            _ = connection.send(LOG, body);
+        });
+    };
+
+    let value = match body.get("expr").and_then(Msg::as_str) {
+        None => evaluate_output(config, &setup, &source, uri)?.value,
+        Some("output.text") => {
+            let output = evaluate_output(config, &setup, &source, uri)?;
+            Value::String(pkl_render::render_output(&output, None).map_err(|error| format!("{uri}: {error}"))?)
+        }
+        Some("output.value") => evaluate_output(config, &setup, &source, uri)?.value,
+        Some(expr) => {
+            let module = (uri, source.as_str(), uri);
+            evaluate_expression(config.options.clone(), &setup, module, expr, &crate::uncolored_renderer())?
+        }
+    };
+    let mut out = Vec::new();
+    encode(&value, uri)?.encode(&mut out);
+    Ok(out)
+}
+
+fn evaluate_output(
+    config: &Config,
+    setup: &dyn Fn(&mut Evaluator),
+    source: &str,
+    uri: &str,
+) -> Result<ModuleOutput, String> {
+    let alloc = Allocator::default();
+    let source = alloc.alloc_str(source);
+    let module = pkl_parser::parse_module(&alloc, source);
+    if !module.diagnostics.is_empty() {
+        return Err(describe((uri, source, uri), &Error::Syntax(module.diagnostics), &crate::uncolored_renderer()));
+    }
+    let mut evaluator = Evaluator::with_options(&alloc, config.options.clone());
+    setup(&mut evaluator);
+    evaluator.add_source(uri, source);
+    let output = evaluator.evaluate_output(alloc.alloc(module.node), uri);
+    output.map_err(|error| describe((uri, source, uri), &Error::Eval(error), &crate::uncolored_renderer()))
+}
+
+/// Reads the resources of a scheme of the client's by asking the client for them.
+struct ClientReader {
+    scheme: String,
+    evaluator_id: i64,
+    connection: Rc<Connection>,
+}
+
+impl ResourceReader for ClientReader {
+    fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    fn read(&self, uri: &str) -> Result<Option<Resource>, String> {
+        let body = vec![("evaluatorId", Msg::Int(self.evaluator_id)), ("uri", Msg::str(uri))];
+        let response = self.connection.request(READ_RESOURCE, body, READ_RESOURCE_RESPONSE)?;
+        match (response.get("error").and_then(Msg::as_str), response.get("contents")) {
+            (Some(error), _) => Err(error.to_string()),
+            (None, Some(Msg::Bin(contents))) => Ok(Some(Resource::Bytes(contents.clone()))),
+            (None, _) => Ok(Some(Resource::Bytes(Vec::new()))),
+        }
+    }
+}
+
+/// A value in Pkl's binary encoding, where objects are described as coming from the module at `uri`.
+fn encode(value: &Value, uri: &str) -> Result<Msg, String> {
+    let all = |values: &[Value]| values.iter().map(|value| encode(value, uri)).collect::<Result<Vec<_>, _>>();
+    let entries = |entries: &[(Value, Value)]| {
+        let entry = |(key, value): &(Value, Value)| Ok::<_, String>((encode(key, uri)?, encode(value, uri)?));
+        entries.iter().map(entry).collect::<Result<Vec<_>, _>>()
+    };
+    Ok(match value {
+        Value::Null => Msg::Nil,
+        Value::Boolean(b) => Msg::Bool(*b),
+        Value::Int(n) => Msg::Int(*n),
+        Value::Float(x) => Msg::Float(*x),
+        Value::String(s) => Msg::str(s),
+        Value::Duration(duration) => {
+            Msg::Array(vec![Msg::Int(0x7), Msg::Float(duration.value.as_f64()), Msg::str(duration.unit.symbol())])
+        }
+        Value::DataSize(size) => {
+            Msg::Array(vec![Msg::Int(0x8), Msg::Float(size.value.as_f64()), Msg::str(size.unit.symbol())])
+        }
+        Value::Map(map) => Msg::Array(vec![Msg::Int(0x2), Msg::Map(entries(map)?)]),
+        Value::List(elements) => Msg::Array(vec![Msg::Int(0x4), Msg::Array(all(elements)?)]),
+        Value::Set(elements) => Msg::Array(vec![Msg::Int(0x6), Msg::Array(all(elements)?)]),
+        Value::Object(object) => match &object.kind {
+            ObjectKind::Mapping => Msg::Array(vec![Msg::Int(0x3), Msg::Map(entries(&object.entries)?)]),
+            ObjectKind::Listing => Msg::Array(vec![Msg::Int(0x5), Msg::Array(all(&object.elements)?)]),
+            kind => {
+                let mut members = Vec::new();
+                for (name, value) in &object.properties {
+                    members.push(Msg::Array(vec![Msg::Int(0x10), Msg::str(name), encode(value, uri)?]));
+                }
+                for (key, value) in &object.entries {
+                    members.push(Msg::Array(vec![Msg::Int(0x11), encode(key, uri)?, encode(value, uri)?]));
+                }
+                for (index, value) in object.elements.iter().enumerate() {
+                    members.push(Msg::Array(vec![Msg::Int(0x12), Msg::Int(index as i64), encode(value, uri)?]));
+                }
+                let module = if *kind == ObjectKind::Dynamic { "pkl:base" } else { uri };
+                Msg::Array(vec![Msg::Int(0x1), Msg::str(kind.class_name()), Msg::str(module), Msg::Array(members)])
+            }
+        },
+        Value::Regex { pattern } => Msg::Array(vec![Msg::Int(0xb), Msg::str(pattern)]),
+        Value::Class { name } => Msg::Array(vec![Msg::Int(0xc), Msg::str(name), Msg::str(uri)]),
+        Value::Function { .. } => return Err(format!("{uri}: can't send a value of type `Function` to the client")),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    use super::{encode, serve};
+    use crate::msgpack::Msg;
+
+    /// Output that the test can read after the server is done with it.
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn message(code: i64, body: Vec<(&str, Msg)>) -> Msg {
+        Msg::Array(vec![Msg::Int(code), Msg::Map(body.into_iter().map(|(k, v)| (Msg::str(k), v)).collect())])
+    }
+
+    fn messages(bytes: &[u8]) -> Vec<Msg> {
+        let mut input = bytes;
+        std::iter::from_fn(|| Msg::read(&mut input).unwrap()).collect()
+    }
+
+    #[test]
+    fn evaluating() {
+        let mut input = Vec::new();
+        message(0x20, vec![("requestId", Msg::Int(1)), ("allowedModules", Msg::Array(vec![Msg::str("repl:")]))])
+            .encode(&mut input);
+        let text = Msg::str("bird = \"pigeon\"\nage = trace(2)");
+        let evaluate = |expr: Msg| {
+            let uri = Msg::str("repl:text");
+            let body = vec![("requestId", Msg::Int(2)), ("evaluatorId", Msg::Int(1)), ("moduleUri", uri)];
+            message(0x23, [body, vec![("moduleText", text.clone()), ("expr", expr)]].concat())
+        };
+        evaluate(Msg::str("output.text")).encode(&mut input);
+        evaluate(Msg::str("bird.length")).encode(&mut input);
+        message(0x22, vec![("evaluatorId", Msg::Int(1))]).encode(&mut input);
+        evaluate(Msg::Nil).encode(&mut input);
+
+        let output = Shared::default();
+        serve(Box::new(std::io::Cursor::new(input)), Box::new(output.clone())).unwrap();
+        let responses = messages(&output.0.borrow());
+        assert_eq!(responses[0], message(0x21, vec![("requestId", Msg::Int(1)), ("evaluatorId", Msg::Int(1))]));
+        assert_eq!(responses[1].as_array().unwrap()[0], Msg::Int(0x25));
+        let result = |response: &Msg| match response.as_array().unwrap()[1].get("result") {
+            Some(Msg::Bin(bytes)) => Msg::read(&mut bytes.as_slice()).unwrap().unwrap(),
+            _ => panic!("{response:?}"),
+        };
+        assert_eq!(result(&responses[2]), Msg::str("bird = \"pigeon\"\nage = 2\n"));
+        assert_eq!(result(&responses[3]), Msg::Int(6));
+        let error = responses[4].as_array().unwrap()[1].get("error");
+        assert_eq!(error, Some(&Msg::str("there's no evaluator with the id `1`")));
+    }
+
+    #[test]
+    fn unknown_requests() {
+        let mut input = Vec::new();
+        message(0x2a, vec![("requestId", Msg::Int(1)), ("evaluatorId", Msg::Int(3))]).encode(&mut input);
+        message(0x27, vec![("contents", Msg::Bin(Vec::new()))]).encode(&mut input);
+        message(0x20, vec![("requestId", Msg::Int(2))]).encode(&mut input);
+
+        let output = Shared::default();
+        serve(Box::new(std::io::Cursor::new(input)), Box::new(output.clone())).unwrap();
+        let responses = messages(&output.0.borrow());
+        let error = Msg::str("unexpected message with the code `0x2a`");
+        let ids = vec![("requestId", Msg::Int(1)), ("evaluatorId", Msg::Int(3))];
+        assert_eq!(responses[0], message(0x2b, [ids, vec![("error", error)]].concat()));
+        assert_eq!(responses[1], message(0x21, vec![("requestId", Msg::Int(2)), ("evaluatorId", Msg::Int(1))]));
+        assert_eq!(responses.len(), 2);
+    }
+
+    #[test]
+    fn client_resources() {
+        let mut input = Vec::new();
+        let readers = Msg::Array(vec![Msg::Map(vec![(Msg::str("scheme"), Msg::str("secret"))])]);
+        message(0x20, vec![("requestId", Msg::Int(1)), ("clientResourceReaders", readers)]).encode(&mut input);
+        let text = Msg::str("password = read(\"secret:pw\").text");
+        let uri = Msg::str("repl:text");
+        let ids = vec![("requestId", Msg::Int(2)), ("evaluatorId", Msg::Int(1))];
+        message(0x23, [ids, vec![("moduleUri", uri), ("moduleText", text)]].concat()).encode(&mut input);
+        message(0x27, vec![("requestId", Msg::Int(1)), ("contents", Msg::Bin(b"hunter2".to_vec()))]).encode(&mut input);
+
+        let output = Shared::default();
+        serve(Box::new(std::io::Cursor::new(input)), Box::new(output.clone())).unwrap();
+        let responses = messages(&output.0.borrow());
+        let read = vec![("requestId", Msg::Int(1)), ("evaluatorId", Msg::Int(1)), ("uri", Msg::str("secret:pw"))];
+        let read = message(0x26, read);
+        assert_eq!(responses[1], read);
+        let Some(Msg::Bin(bytes)) = responses[2].as_array().unwrap()[1].get("result") else { panic!("{responses:?}") };
+        let module = Msg::read(&mut bytes.as_slice()).unwrap().unwrap();
+        let property = Msg::Array(vec![Msg::Int(0x10), Msg::str("password"), Msg::str("hunter2")]);
+        assert_eq!(module.as_array().unwrap()[3], Msg::Array(vec![property]));
+    }
+
+    #[test]
+    fn binary_encoding() {
+        let value = pkl_eval::evaluate(
+            "birds = new Listing { \"pigeon\" }\nages = new Mapping { [\"pigeon\"] = 2 }\ntimeout = 5.s\nbox {}",
+        ).unwrap();
+        let listing = Msg::Array(vec![Msg::Int(0x5), Msg::Array(vec![Msg::str("pigeon")])]);
+        let mapping = Msg::Array(vec![Msg::Int(0x3), Msg::Map(vec![(Msg::str("pigeon"), Msg::Int(2))])]);
+        let duration = Msg::Array(vec![Msg::Int(0x7), Msg::Float(5.0), Msg::str("s")]);
+        let dynamic = Msg::Array(vec![Msg::Int(0x1), Msg::str("Dynamic"), Msg::str("pkl:base"), Msg::Array(vec![])]);
+        let property = |name, value| Msg::Array(vec![Msg::Int(0x10), Msg::str(name), value]);
+        let Msg::Array(module) = encode(&value, "repl:text").unwrap() else { panic!() };
+        let members = vec![
+            property("birds", listing),
+            property("ages", mapping),
+            property("timeout", duration),
+            property("box", dynamic),
+        ];
+        assert_eq!(module[3], Msg::Array(members));
+    }
+}
@@ -0,0 +1,86 @@
+//! Watching files for changes, for `pkl-lang eval --watch`.
+//!
+//! Files are polled for their modification times, which works the same on every platform and for files that don't
+//! exist yet: a module that's missing is watched for being created.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How often the files are checked for changes.
+const POLL: Duration = Duration::from_millis(200);
+
+/// How long the files have to stay unchanged after a change before it's reported, so that an editor writing several
+/// files, or one file in several steps, causes one evaluation.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A set of files and when each was last modified, if it exists.
+pub(crate) struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    /// Watches `paths` for changes from how they are now.
+    pub(crate) fn new(paths: Vec<PathBuf>) -> Self {
+        let files = paths.into_iter().map(|path| (modified(&path), path)).map(|(time, path)| (path, time)).collect();
+        Watcher { files }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Waits until files change, and then until they stop changing, returning the ones that changed.
+    pub(crate) fn wait(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        loop {
+            std::thread::sleep(if changed.is_empty() { POLL } else { DEBOUNCE });
+            let changes = self.changes();
+            if changes.is_empty() && !changed.is_empty() {
+                return changed;
+            }
+            for path in changes {
+                if !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+    }
+
+    /// The files whose modification times differ from when they were last checked, which are updated.
+    fn changes(&mut self) -> Vec<PathBuf> {
+        let mut changes = Vec::new();
+        for (path, time) in &mut self.files {
+            let now = modified(path);
+            if now != *time {
+                *time = now;
+                changes.push(path.clone());
+            }
+        }
+        changes
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn changes_and_creations() {
+        let dir = std::env::temp_dir().join(format!("pkl-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (existing, missing) = (dir.join("a.pkl"), dir.join("b.pkl"));
+        std::fs::write(&existing, "a = 1\n").unwrap();
+        let mut watcher = Watcher::new(vec![existing.clone(), missing.clone()]);
+        assert!(watcher.changes().is_empty());
+
+        std::fs::write(&missing, "b = 1\n").unwrap();
+        assert_eq!(watcher.changes(), vec![missing.clone()]);
+        std::fs::remove_file(&existing).unwrap();
+        assert_eq!(watcher.wait(), vec![existing]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
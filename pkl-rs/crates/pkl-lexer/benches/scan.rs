@@ -0,0 +1,61 @@
+//! The throughput of the lexer on large generated modules, each heavy on one kind of text: run with
+//! `cargo bench -p pkl-lexer`.
+//!
+//! There's no benchmarking harness among the dependencies, so each module is lexed a number of times and the best
+//! time is reported.
+
+use std::time::{Duration, Instant};
+
+use oxc_allocator::Allocator;
+use pkl_lexer::Lexer;
+
+/// How many times each module is lexed.
+const RUNS: usize = 20;
+
+/// About how large each generated module is.
+const SIZE: usize = 8 << 20;
+
+fn generate(item: impl Fn(usize) -> String) -> String {
+    let mut source = String::with_capacity(SIZE + 1024);
+    let mut index = 0;
+    while source.len() < SIZE {
+        source.push_str(&item(index));
+        index += 1;
+    }
+    source
+}
+
+fn modules() -> Vec<(&'static str, String)> {
+    vec![
+        ("mixed", generate(|i| format!("class Bird{i} {{\n  name: String = \"bird \\({i})\"\n  age: Int = {i}\n}}\n"))),
+        ("whitespace", generate(|i| format!("{:pad$}x{i} ={:pad$}{i}\n", "", "", pad = 40))),
+        ("identifiers", generate(|i| format!("averyverylongpropertyname_{i} = anotherverylongname.member_{i}\n"))),
+        ("strings", generate(|i| format!("s{i} = \"{}\"\n", "lorem ipsum dolor sit amet, ".repeat(8)))),
+        ("comments", generate(|i| format!("// {}\n/* {} */\nc{i} = 1\n", "a comment ".repeat(8), "block ".repeat(16)))),
+    ]
+}
+
+fn lex(source: &str) -> usize {
+    let alloc = Allocator::default();
+    let mut lexer = Lexer::new(&alloc, source);
+    let mut tokens = 0;
+    while lexer.next_token().kind != pkl_lexer::token::TokenKind::Eof {
+        tokens += 1;
+    }
+    tokens
+}
+
+fn main() {
+    for (name, source) in modules() {
+        let mut best = Duration::MAX;
+        let mut tokens = 0;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            tokens = std::hint::black_box(lex(std::hint::black_box(&source)));
+            best = best.min(start.elapsed());
+        }
+        let throughput = source.len() as f64 / best.as_secs_f64() / (1 << 20) as f64;
+        let size = source.len() as f64 / (1 << 20) as f64;
+        println!("{name:>12}: {throughput:8.1} MiB/s ({tokens} tokens in {size:.1} MiB)");
+    }
+}
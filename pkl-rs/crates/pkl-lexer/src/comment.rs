@@ -0,0 +1,155 @@
+use pkl_diagnostics::Code;
+
+use crate::diagnostic::Diagnostic;
+use crate::scan;
+use crate::token::{Span, TokenKind};
+use crate::Lexer;
+
+impl<'a> Lexer<'a> {
+    /// Scans a `//` comment up to (but excluding) the end of its line.
+    ///
+    /// A comment starting with exactly three slashes is a doc comment, which documents the declaration that follows
+    /// it. Each line of a multi-line doc comment is its own [`TokenKind::DocComment`] token.
+    pub(super) fn line_comment_handler(&mut self) {
+        let is_doc_comment = self.source.peek_byte_at(2) == Some(b'/')
+            && self.source.peek_byte_at(3) != Some(b'/');
+
+        self.source.advance(2);
+        self.skip_to_line_end();
+
+        self.token.kind = if is_doc_comment {
+            TokenKind::DocComment
+        } else {
+            TokenKind::LineComment
+        };
+    }
+
+    /// Scans a `#!` line, which is only allowed at the very start of a file so that Pkl modules can be run as
+    /// scripts (`#!/usr/bin/env pkl eval`).
+    pub(super) fn shebang_handler(&mut self) {
+        self.source.advance(2);
+        self.skip_to_line_end();
+        self.token.kind = TokenKind::Shebang;
+    }
+
+    /// Consumes everything up to (but excluding) the next line terminator.
+    fn skip_to_line_end(&mut self) {
+        self.source.advance(scan::line(self.source.remaining().as_bytes()));
+    }
+
+    /// Scans a `/* ... */` comment. Block comments nest, so `/* a /* b */ c */` is a single comment.
+    ///
+    /// A comment that is still open at the end of the input is reported as a diagnostic, and the token extends to the
+    /// end of the input.
+    pub(super) fn block_comment_handler(&mut self) {
+        let start = self.source.pos();
+        let mut depth = 0usize;
+
+        while let Some(byte) = self.source.peek_byte() {
+            let next = self.source.peek_byte_at(1);
+
+            if byte == b'/' && next == Some(b'*') {
+                depth += 1;
+                self.source.advance(2);
+            } else if byte == b'*' && next == Some(b'/') {
+                depth -= 1;
+                self.source.advance(2);
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                // a `/` or `*` that doesn't open or close a comment is text itself
+                self.source.advance(scan::comment_text(self.source.remaining().as_bytes()).max(1));
+            }
+        }
+
+        if depth > 0 {
+            let span = Span::new(start, self.source.pos());
+            let diagnostic = Diagnostic::new(span, "unterminated block comment");
+            self.diagnostics.push(diagnostic.with_code(Code::UnterminatedBlockComment));
+        }
+
+        self.token.kind = TokenKind::BlockComment;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::lex;
+    use crate::token::{Span, TokenKind};
+    use crate::Lexer;
+    use oxc_allocator::Allocator;
+
+    #[test]
+    fn line_comments() {
+        assert_eq!(
+            lex("a // comment\r\n+ // another"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::LineComment, "// comment"),
+                (TokenKind::Plus, "+"),
+                (TokenKind::LineComment, "// another"),
+            ]
+        );
+    }
+
+    #[test]
+    fn doc_comments() {
+        assert_eq!(
+            lex("/// Docs\n/// more\n//// not docs\n///"),
+            vec![
+                (TokenKind::DocComment, "/// Docs"),
+                (TokenKind::DocComment, "/// more"),
+                (TokenKind::LineComment, "//// not docs"),
+                (TokenKind::DocComment, "///"),
+            ]
+        );
+        assert!(!TokenKind::DocComment.is_trivia());
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        assert_eq!(
+            lex("/* a /* b */ c */a/**/"),
+            vec![
+                (TokenKind::BlockComment, "/* a /* b */ c */"),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::BlockComment, "/**/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn comment_markers_inside_strings() {
+        assert_eq!(lex(r#""// /*""#), vec![(TokenKind::StringLiteral, r#""// /*""#)]);
+    }
+
+    #[test]
+    fn shebang_on_the_first_line() {
+        assert_eq!(
+            lex("#!/usr/bin/env pkl\nx"),
+            vec![(TokenKind::Shebang, "#!/usr/bin/env pkl"), (TokenKind::Identifier, "x")]
+        );
+        assert_eq!(
+            lex("x\n#!y"),
+            vec![
+                (TokenKind::Identifier, "x"),
+                (TokenKind::Error, "#"),
+                (TokenKind::Bang, "!"),
+                (TokenKind::Identifier, "y"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_reported() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, "a /* b /* c */");
+
+        lexer.by_ref().for_each(drop);
+
+        assert_eq!(lexer.diagnostics().len(), 1);
+        assert_eq!(lexer.diagnostics()[0].message, "unterminated block comment");
+        assert_eq!(lexer.diagnostics()[0].span, Span::new(2, 14));
+    }
+}
@@ -0,0 +1,36 @@
+use pkl_diagnostics::{Code, Report};
+
+use crate::token::Span;
+
+/// A problem found while lexing, such as an unterminated block comment.
+///
+/// The lexer never stops at a diagnostic: it records it and keeps producing tokens, so callers decide whether (and
+/// how) to report them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The source range the diagnostic refers to
+    pub span: Span,
+    pub message: String,
+    /// The kind of problem, if it's one the lexer or parser finds
+    pub code: Option<Code>,
+}
+
+impl Diagnostic {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Diagnostic {
+            span,
+            message: message.into(),
+            code: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: Code) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// The diagnostic as a report, for rendering with the source it's in.
+    pub fn report(&self) -> Report {
+        Report::error(&self.message).with_code(self.code).with_primary(self.span.start..self.span.end, "")
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
 use crate::Lexer;
+use crate::mode::LexMode;
+use crate::scan;
 use crate::token::TokenKind;
 
 
@@ -10,14 +12,14 @@ pub type ByteHandler = Option<for<'arena> fn(&mut Lexer<'arena>)>;
 /// Ref: <https://www.freecodecamp.org/news/ascii-table-hex-to-ascii-value-character-code-chart-2/>
 pub static BYTE_HANDLERS: [ByteHandler; 256] = [
 //   0    1    2    3    4    5    6    7    8    9    A    B    C    D    E    F   //
-    EOF, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 0 16
+    ___, ___, ___, ___, ___, ___, ___, ___, ___, SPS, NLN, ___, SPS, NLN, ___, ___, // 0 16
     ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 1 32
-    SPS, ___, ___, ___, IDN, ___, ___, ___, ___, ___, ___, PLS, ___, ___, ___, ___, // 2 48
-    ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 3 64
-    ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 4 80
-    ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 5 96
-    QUI, L_A, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, // 6 112
-    IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, ___, ___, ___, ___, ___, // 7
+    SPS, BNG, STR, HSH, IDN, PCT, AMP, ___, LPR, RPR, STA, PLS, CMA, MIN, DOT, SLH, // 2 48
+    NUM, NUM, NUM, NUM, NUM, NUM, NUM, NUM, NUM, NUM, COL, SMI, LSS, EQL, GTR, QST, // 3 64
+    AT_, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, // 4 80
+    IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, BTO, ___, BTC, ___, IDN, // 5 96
+    QUI, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, // 6 112
+    IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, BEO, PIP, BEC, TLD, ___, // 7
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 8
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 9
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // A
@@ -28,11 +30,12 @@ pub static BYTE_HANDLERS: [ByteHandler; 256] = [
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // F
 ];
 
+/// Bytes that can't start a token, which are reported as unexpected characters
 pub const ___: ByteHandler = None;
 
+/// Non-ASCII bytes, which may start a unicode identifier
 pub const UNI: ByteHandler = Some(|lex| {
-    lex.source.advance(1);
-    // TODO: Whatever this is
+    lex.unicode_handler();
 });
 
 /// `` ` ``
@@ -40,42 +43,325 @@ pub const UNI: ByteHandler = Some(|lex| {
 /// Stands for quoted identifier (used for identifiers that would conflict with keywords)
 pub const QUI: ByteHandler = Some(|lex| {
     lex.quoted_identifier_handler();
+});
 
-    lex.token.kind = TokenKind::Identifier;
+/// `"`
+pub const STR: ByteHandler = Some(|lex| {
+    lex.string_literal_handler();
 });
 
-/// `+`
-pub const PLS: ByteHandler = Some(|lex| {
-    if lex.next_eq('=') {
-        lex.index += 2;
-        lex.token.kind = TokenKind::PlusEq;
+/// `#`, which can only start a pound-delimited string like `#"..."#`, or a shebang line at the start of the file
+pub const HSH: ByteHandler = Some(|lex| {
+    let quote = lex.count_pounds(0);
+    if lex.source.pos() == 0 && lex.source.peek_byte_at(1) == Some(b'!') {
+        lex.shebang_handler();
+    } else if lex.source.peek_byte_at(quote) == Some(b'"') {
+        lex.string_literal_handler();
+    } else {
+        lex.unexpected_character();
+    }
+});
+
+/// Digits, which start a numeric literal
+pub const NUM: ByteHandler = Some(|lex| {
+    lex.numeric_literal_handler();
+});
+
+/// `.`, `...`, `...?`, or a float literal if followed by a digit (`.5`)
+pub const DOT: ByteHandler = Some(|lex| {
+    if lex.is_digit_at(1) {
+        lex.numeric_literal_handler();
+        return;
+    }
+
+    lex.bump();
+    lex.token.kind = if lex.source.peek_byte() == Some(b'.') && lex.source.peek_byte_at(1) == Some(b'.') {
+        lex.source.advance(2);
+        if lex.eat(b'?') {
+            TokenKind::SpreadQuestion
+        } else {
+            TokenKind::Spread
+        }
     } else {
+        TokenKind::Dot
+    };
+});
+
+/// `/`, or a `//` or `/* */` comment
+pub const SLH: ByteHandler = Some(|lex| match lex.source.peek_byte_at(1) {
+    Some(b'/') => lex.line_comment_handler(),
+    Some(b'*') => lex.block_comment_handler(),
+    _ => {
         lex.bump();
-        lex.token.kind = TokenKind::Plus;
+        lex.token.kind = TokenKind::Slash;
     }
 });
 
+/// `~/`
+pub const TLD: ByteHandler = Some(|lex| {
+    if lex.source.peek_byte_at(1) == Some(b'/') {
+        lex.source.advance(2);
+        lex.token.kind = TokenKind::TildeSlash;
+    } else {
+        lex.unexpected_character();
+    }
+});
 
-/// Identifiers, and special characters like `$` and `_`
-pub const IDN: ByteHandler = Some(|lex| {
-    lex.identifier_handler();
+/// `-` or `->`
+pub const MIN: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'>') {
+        TokenKind::Arrow
+    } else {
+        TokenKind::Minus
+    };
+});
 
-    lex.token.kind = TokenKind::Empty;
+/// `*` or `**`
+pub const STA: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'*') {
+        TokenKind::StarStar
+    } else {
+        TokenKind::Star
+    };
+});
+
+/// `%`
+pub const PCT: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::Percent;
+});
+
+/// `=` or `==`
+pub const EQL: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'=') {
+        TokenKind::EqEq
+    } else {
+        TokenKind::Eq
+    };
+});
+
+/// `!`, `!=`, or `!!`
+pub const BNG: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'=') {
+        TokenKind::NotEq
+    } else if lex.eat(b'!') {
+        TokenKind::BangBang
+    } else {
+        TokenKind::Bang
+    };
+});
+
+/// `<` or `<=`
+pub const LSS: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'=') {
+        TokenKind::LtEq
+    } else {
+        TokenKind::Lt
+    };
+});
+
+/// `>` or `>=`
+///
+/// `>>` is always two tokens, so that nested type arguments like `Listing<Listing<Int>>` close correctly.
+pub const GTR: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'=') {
+        TokenKind::GtEq
+    } else {
+        TokenKind::Gt
+    };
+});
+
+/// `&&`
+pub const AMP: ByteHandler = Some(|lex| {
+    if lex.source.peek_byte_at(1) == Some(b'&') {
+        lex.source.advance(2);
+        lex.token.kind = TokenKind::AndAnd;
+    } else {
+        lex.unexpected_character();
+    }
+});
+
+/// `|`, `||`, or `|>`
+pub const PIP: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'|') {
+        TokenKind::OrOr
+    } else if lex.eat(b'>') {
+        TokenKind::PipeGt
+    } else {
+        TokenKind::Pipe
+    };
+});
+
+/// `?`, `??`, or `?.`
+pub const QST: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'?') {
+        TokenKind::QuestionQuestion
+    } else if lex.eat(b'.') {
+        TokenKind::QuestionDot
+    } else {
+        TokenKind::Question
+    };
+});
+
+/// `[`
+pub const BTO: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::LBracket;
+});
+
+/// `]`
+pub const BTC: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::RBracket;
+});
+
+/// `{`
+pub const BEO: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::LBrace;
+});
+
+/// `}`
+pub const BEC: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::RBrace;
+});
+
+/// `,`
+pub const CMA: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::Comma;
+});
+
+/// `:`
+pub const COL: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::Colon;
+});
+
+/// `;`
+pub const SMI: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::Semicolon;
+});
+
+/// `@`
+pub const AT_: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = TokenKind::At;
+});
+
+/// `(`
+pub const LPR: ByteHandler = Some(|lex| {
+    if let Some(LexMode::Interpolation { depth }) = lex.modes.last_mut() {
+        *depth += 1;
+    }
+    lex.bump();
+    lex.token.kind = TokenKind::LParen;
+});
+
+/// `)`, which either closes a parenthesized expression or ends an interpolation
+pub const RPR: ByteHandler = Some(|lex| {
+    lex.bump();
+    match lex.modes.last_mut() {
+        Some(LexMode::Interpolation { depth: 0 }) => {
+            lex.modes.pop();
+            lex.token.kind = TokenKind::InterpolationEnd;
+        }
+        Some(LexMode::Interpolation { depth }) => {
+            *depth -= 1;
+            lex.token.kind = TokenKind::RParen;
+        }
+        _ => lex.token.kind = TokenKind::RParen,
+    }
 });
 
-/// Literal A
-pub const L_A: ByteHandler = Some(|lex| {
+/// `+` or `+=`
+pub const PLS: ByteHandler = Some(|lex| {
+    lex.bump();
+    lex.token.kind = if lex.eat(b'=') {
+        TokenKind::PlusEq
+    } else {
+        TokenKind::Plus
+    };
+});
+
+/// Identifiers and keywords, which start with a letter, `$`, or `_`
+pub const IDN: ByteHandler = Some(|lex| {
     lex.identifier_handler();
+});
 
-    lex.token.kind = TokenKind::Identifier;
+/// Line terminators: `\n`, `\r`, and `\r\n`
+///
+/// `\r\n` is consumed as a single terminator, so spans in Windows-authored files line up with the same source
+/// written with `\n` endings.
+pub const NLN: ByteHandler = Some(|lex| {
+    lex.token.kind = TokenKind::Empty;
+    if lex.source.peek_byte() == Some(b'\r') && lex.source.peek_byte_at(1) == Some(b'\n') {
+        lex.bump();
+    }
+    lex.bump();
 });
 
-/// Space
+/// Space, tab, and form feed, which are skipped as a run
 pub const SPS: ByteHandler = Some(|lex| {
     lex.token.kind = TokenKind::Empty;
-    lex.bump();
+    lex.source.advance(scan::whitespace(lex.source.remaining().as_bytes()));
 });
+#[cfg(test)]
+mod test {
+    use crate::test::lex;
+    use crate::token::TokenKind::*;
+
+    #[test]
+    fn operators_use_maximal_munch() {
+        let kinds: Vec<_> = lex("+ += - -> * ** / ~/ % == = != ! !! < <= > >= && || |> | ?? ?. ? ... ...? . @")
+            .into_iter()
+            .map(|(kind, _)| kind)
+            .collect();
 
-pub const EOF: ByteHandler = Some(|_lex| {
-    println!("End of file reached");
-});
\ No newline at end of file
+        assert_eq!(
+            kinds,
+            vec![
+                Plus, PlusEq, Minus, Arrow, Star, StarStar, Slash, TildeSlash, Percent, EqEq, Eq, NotEq, Bang,
+                BangBang, Lt, LtEq, Gt, GtEq, AndAnd, OrOr, PipeGt, Pipe, QuestionQuestion, QuestionDot, Question,
+                Spread, SpreadQuestion, Dot, At,
+            ]
+        );
+    }
+
+    #[test]
+    fn punctuation() {
+        let kinds: Vec<_> = lex("()[]{},:;").into_iter().map(|(kind, _)| kind).collect();
+
+        assert_eq!(kinds, vec![LParen, RParen, LBracket, RBracket, LBrace, RBrace, Comma, Colon, Semicolon]);
+    }
+
+    #[test]
+    fn adjacent_operators() {
+        assert_eq!(
+            lex("a!!.a?.a...a>>=a"),
+            vec![
+                (Identifier, "a"),
+                (BangBang, "!!"),
+                (Dot, "."),
+                (Identifier, "a"),
+                (QuestionDot, "?."),
+                (Identifier, "a"),
+                (Spread, "..."),
+                (Identifier, "a"),
+                (Gt, ">"),
+                (GtEq, ">="),
+                (Identifier, "a"),
+            ]
+        );
+    }
+}
@@ -1,34 +1,152 @@
+use pkl_diagnostics::Code;
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use crate::keyword::keyword_kind;
+use crate::scan;
+use crate::token::{TokenKind, TokenValue};
 use crate::Lexer;
 
+/// Whether `text` can be written as an identifier as is, i.e. without backticks.
+///
+/// That is the case if it follows the identifier rules of [`Lexer::identifier_handler`] and isn't a keyword.
+pub fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    let starts_identifier = match chars.next() {
+        Some(c) => c == '_' || c == '$' || is_xid_start(c),
+        None => false,
+    };
+
+    starts_identifier && chars.all(|c| c == '$' || is_xid_continue(c)) && keyword_kind(text).is_none()
+}
+
 impl<'a> Lexer<'a> {
+    /// Scans an identifier or keyword.
+    ///
+    /// Identifiers follow UAX #31: they start with an `XID_Start` character, `$`, or `_`, and continue with
+    /// `XID_Continue` characters or `$`. Runs of ASCII bytes are skipped at once, and only non-ASCII bytes are decoded.
+    ///
+    /// `import` and `read` directly followed by `*` (or `read` by `?`) lex as a single glob/nullable keyword.
     pub(super) fn identifier_handler(&mut self) {
-        while !self.source.is_at_end() {
-            let byte = self.read_byte();
-
-            if byte.is_ascii_alphanumeric() && (byte as char) != ' ' || byte == b'_' {
-                self.index += 1;
-                // println!("Byte: {}", byte as char);
-            } else {
-                break;
+        let start = self.source.pos();
+
+        loop {
+            self.source.advance(scan::identifier(self.source.remaining().as_bytes()));
+            match self.source.peek_char() {
+                Some(c) if !c.is_ascii() && is_xid_continue(c) => self.source.advance(c.len_utf8()),
+                _ => break,
             }
         }
+
+        let text = self.source.get_slice(start, self.source.pos());
+        let kind = keyword_kind(text).unwrap_or(TokenKind::Identifier);
+
+        if kind == TokenKind::Identifier {
+            self.token.value = TokenValue::Identifier(text);
+        }
+
+        self.token.kind = match kind {
+            TokenKind::Import if self.eat(b'*') => TokenKind::ImportStar,
+            TokenKind::Read if self.eat(b'*') => TokenKind::ReadStar,
+            TokenKind::Read if self.eat(b'?') => TokenKind::ReadQuestion,
+            kind => kind,
+        };
+    }
+
+    /// Handles a non-ASCII byte outside of strings and comments: either the start of a unicode identifier, or a
+    /// character that can't appear here, which is reported as a whole.
+    pub(super) fn unicode_handler(&mut self) {
+        match self.source.peek_char() {
+            Some(c) if is_xid_start(c) => self.identifier_handler(),
+            _ => self.unexpected_character(),
+        }
     }
 
+    /// Scans a backtick-quoted identifier, which may contain anything but backticks and line breaks.
+    ///
+    /// Quoted identifiers are how keywords (and otherwise invalid names) are used as identifiers, so they are never
+    /// keywords themselves. One that isn't closed before the end of its line is an error token.
     pub(super) fn quoted_identifier_handler(&mut self) {
-        // TODO: Make this function just call a string function (wait until char type shit)
+        let start = self.source.pos();
         self.bump();
-        while !self.source.is_at_end() {
-            let byte = self.read_byte();
-            if byte.is_ascii_alphanumeric() {
-                // self.source.advance(1);
-                self.index += 1;
-            } else {
-                break;
+
+        while let Some(byte) = self.source.peek_byte() {
+            match byte {
+                b'`' => {
+                    let name = self.source.get_slice(start + 1, self.source.pos());
+                    self.bump();
+                    self.token.kind = TokenKind::Identifier;
+                    self.token.value = TokenValue::Identifier(name);
+                    return;
+                }
+                b'\n' | b'\r' => break,
+                _ => self.bump(),
             }
+        }
 
+        self.error(Code::UnterminatedQuotedIdentifier, "unterminated quoted identifier");
+    }
+}
 
-        }
-        self.index += 1;
+#[cfg(test)]
+mod test {
+    use crate::test::lex;
+    use crate::token::{TokenKind, TokenValue};
+    use crate::Lexer;
+    use oxc_allocator::Allocator;
+
+    #[test]
+    fn unicode_identifiers() {
+        assert_eq!(
+            lex("größe 名前 ñ1 café_au_lait"),
+            vec![
+                (TokenKind::Identifier, "größe"),
+                (TokenKind::Identifier, "名前"),
+                (TokenKind::Identifier, "ñ1"),
+                (TokenKind::Identifier, "café_au_lait"),
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_identifiers() {
+        assert!(super::is_identifier("größe") && super::is_identifier("_x$1") && super::is_identifier("$"));
+        assert!(!super::is_identifier("") && !super::is_identifier("1x") && !super::is_identifier("a-b"));
+        assert!(!super::is_identifier("class") && !super::is_identifier("record"));
+    }
+
+    #[test]
+    fn non_identifier_characters_end_identifiers() {
+        // U+2192 RIGHTWARDS ARROW is neither XID_Start nor XID_Continue
+        assert_eq!(
+            lex("a→b"),
+            vec![(TokenKind::Identifier, "a"), (TokenKind::Error, "→"), (TokenKind::Identifier, "b")]
+        );
+    }
+
+    #[test]
+    fn combining_marks_continue_identifiers() {
+        // `e` followed by U+0301 COMBINING ACUTE ACCENT
+        assert_eq!(lex("cafe\u{301}"), vec![(TokenKind::Identifier, "cafe\u{301}")]);
+    }
+
+    #[test]
+    fn identifier_values() {
+        let alloc = Allocator::default();
+        let values: Vec<_> = Lexer::new(&alloc, "foo `class` class").map(|token| token.value).collect();
+
+        assert_eq!(
+            values,
+            vec![
+                TokenValue::Identifier("foo"),
+                TokenValue::Identifier("class"),
+                TokenValue::None,
+                TokenValue::None,
+            ]
+        );
+    }
 
+    #[test]
+    fn unterminated_quoted_identifier() {
+        assert_eq!(lex("`a b\nc"), vec![(TokenKind::Error, "`a b"), (TokenKind::Identifier, "c")]);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,125 @@
+//! Re-lexing a source after an edit, reusing the tokens the edit can't have changed.
+
+use oxc_allocator::Allocator;
+
+use crate::token::{Span, Token, TokenKind};
+use crate::Lexer;
+
+/// A change to a source text: the bytes in `start..old_end` were replaced by the ones now in `start..new_end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub old_end: usize,
+    pub new_end: usize,
+}
+
+impl Edit {
+    pub fn new(start: usize, old_end: usize, new_end: usize) -> Self {
+        Edit { start, old_end, new_end }
+    }
+
+    /// Where an offset at or after `old_end` in the old text ends up in the new one.
+    pub fn shift(&self, offset: usize) -> usize {
+        offset - self.old_end + self.new_end
+    }
+
+    pub fn shift_span(&self, span: Span) -> Span {
+        Span::new(self.shift(span.start), self.shift(span.end))
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Lexes `source` like [`Lexer::tokenize`], given the tokens of the text it was before `edit`.
+    ///
+    /// Tokens that end well before the edit are kept, and lexing starts after them. As soon as it produces a token
+    /// after the edit that the old text had at the same place, outside of any string, the rest of the old tokens are
+    /// reused, moved by the length the edit added or removed. So an edit only costs re-lexing the tokens around it.
+    pub fn relex(alloc: &'a Allocator, source: &'a str, old: &[Token<'a>], edit: Edit) -> Vec<Token<'a>> {
+        // whether the lexer was known to be outside of strings before each old token, which it no longer is once an
+        // error may or may not have ended a string
+        let mut top_level = Vec::with_capacity(old.len());
+        let mut depth = Some(0usize);
+        for token in old {
+            top_level.push(depth == Some(0));
+            depth = match (token.kind, depth) {
+                (TokenKind::StringStart, Some(depth)) => Some(depth + 1),
+                (TokenKind::StringEnd, Some(depth)) => depth.checked_sub(1),
+                (TokenKind::Error, Some(depth)) if depth > 0 => None,
+                (_, depth) => depth,
+            };
+        }
+
+        // the lexer may look a couple of characters past the end of a token, so a token is only kept if the token
+        // after it ends before the edit too
+        let kept = (1..old.len())
+            .take_while(|&i| old[i].span.end < edit.start)
+            .filter(|&i| top_level[i])
+            .last()
+            .unwrap_or(0);
+
+        let mut tokens = old[..kept].to_vec();
+        let mut lexer = Lexer::new(alloc, source);
+        lexer.source.set_pos(old[..kept].last().map_or(0, |token| token.span.end));
+
+        loop {
+            let at_top_level = lexer.modes.is_empty();
+            let token = lexer.next_significant_token();
+
+            if at_top_level && token.span.start >= edit.new_end {
+                let old_start = token.span.start - edit.new_end + edit.old_end;
+                if let Ok(i) = old.binary_search_by_key(&old_start, |old| old.span.start) {
+                    if top_level[i] && old[i].kind == token.kind && edit.shift_span(old[i].span) == token.span {
+                        tokens.extend(old[i..].iter().map(|&old| Token { span: edit.shift_span(old.span), ..old }));
+                        return tokens;
+                    }
+                }
+            }
+
+            tokens.push(token);
+            if token.kind == TokenKind::Eof {
+                return tokens;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Edit;
+    use crate::token::Token;
+    use crate::Lexer;
+    use oxc_allocator::Allocator;
+
+    fn summary(tokens: &[Token]) -> Vec<String> {
+        tokens.iter().map(|token| format!("{:?} {:?} {:?}", token.kind, token.span, token.value)).collect()
+    }
+
+    /// Checks that re-lexing after replacing `old[start..old_end]` with `text` matches lexing the new source.
+    fn check(old: &str, start: usize, old_end: usize, text: &str) {
+        let alloc = Allocator::default();
+        let new = format!("{}{text}{}", &old[..start], &old[old_end..]);
+        let old_tokens = Lexer::tokenize(&alloc, old);
+        let relexed = Lexer::relex(&alloc, &new, &old_tokens, Edit::new(start, old_end, start + text.len()));
+
+        assert_eq!(summary(&relexed), summary(&Lexer::tokenize(&alloc, &new)), "{new:?}");
+    }
+
+    #[test]
+    fn relexing_matches_lexing() {
+        let source = "foo = 1\nbar = \"a \\(b + 1) c\"\n// note\nbaz = foo + bar\n";
+        check(source, 6, 7, "42");
+        check(source, 9, 9, "x");
+        check(source, 4, 5, "");
+        check(source, 17, 17, "\")");
+        check(source, 0, source.len(), "x = 1");
+        check(source, source.len(), source.len(), "qux = 3");
+    }
+
+    #[test]
+    fn edits_opening_strings_relex_the_rest() {
+        let source = "a = 1\nb = 2\nc = \"x\"\nd = 4\n";
+        check(source, 10, 10, "\"");
+        check(source, 16, 17, "\\(");
+        check("a = 1\nb = \"\\(x\"\nc = 3\n", 13, 13, "\n");
+    }
+}
@@ -0,0 +1,114 @@
+use crate::token::TokenKind;
+
+/// Looks up the keyword spelled by `text`, if any.
+///
+/// The match is compiled into a decision on the length and bytes of `text`, so this stays cheap for the common case
+/// of identifiers that aren't keywords. Words that Pkl reserves for future use map to [`TokenKind::Reserved`].
+pub fn keyword_kind(text: &str) -> Option<TokenKind> {
+    let kind = match text.as_bytes() {
+        b"abstract" => TokenKind::Abstract,
+        b"amends" => TokenKind::Amends,
+        b"as" => TokenKind::As,
+        b"class" => TokenKind::Class,
+        b"const" => TokenKind::Const,
+        b"else" => TokenKind::Else,
+        b"extends" => TokenKind::Extends,
+        b"external" => TokenKind::External,
+        b"false" => TokenKind::False,
+        b"fixed" => TokenKind::Fixed,
+        b"for" => TokenKind::For,
+        b"function" => TokenKind::Function,
+        b"hidden" => TokenKind::Hidden,
+        b"if" => TokenKind::If,
+        b"import" => TokenKind::Import,
+        b"in" => TokenKind::In,
+        b"is" => TokenKind::Is,
+        b"let" => TokenKind::Let,
+        b"local" => TokenKind::Local,
+        b"module" => TokenKind::Module,
+        b"new" => TokenKind::New,
+        b"nothing" => TokenKind::Nothing,
+        b"null" => TokenKind::Null,
+        b"open" => TokenKind::Open,
+        b"out" => TokenKind::Out,
+        b"outer" => TokenKind::Outer,
+        b"read" => TokenKind::Read,
+        b"super" => TokenKind::Super,
+        b"this" => TokenKind::This,
+        b"throw" => TokenKind::Throw,
+        b"trace" => TokenKind::Trace,
+        b"true" => TokenKind::True,
+        b"typealias" => TokenKind::Typealias,
+        b"unknown" => TokenKind::Unknown,
+        b"when" => TokenKind::When,
+        b"case" | b"delete" | b"override" | b"protected" | b"public" | b"record" | b"switch" | b"vararg" => {
+            TokenKind::Reserved
+        }
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::lex;
+    use crate::token::TokenKind;
+
+    #[test]
+    fn keywords() {
+        assert_eq!(
+            lex("module amends foo.bar class open local"),
+            vec![
+                (TokenKind::Module, "module"),
+                (TokenKind::Amends, "amends"),
+                (TokenKind::Identifier, "foo"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Identifier, "bar"),
+                (TokenKind::Class, "class"),
+                (TokenKind::Open, "open"),
+                (TokenKind::Local, "local"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyword_prefixes_are_identifiers() {
+        assert_eq!(
+            lex("classes _if $this Null"),
+            vec![
+                (TokenKind::Identifier, "classes"),
+                (TokenKind::Identifier, "_if"),
+                (TokenKind::Identifier, "$this"),
+                (TokenKind::Identifier, "Null"),
+            ]
+        );
+    }
+
+    #[test]
+    fn glob_and_nullable_forms() {
+        assert_eq!(
+            lex("import* read* read? import *"),
+            vec![
+                (TokenKind::ImportStar, "import*"),
+                (TokenKind::ReadStar, "read*"),
+                (TokenKind::ReadQuestion, "read?"),
+                (TokenKind::Import, "import"),
+                (TokenKind::Star, "*"),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_identifiers_are_never_keywords() {
+        assert_eq!(
+            lex("`class` `a b-c`"),
+            vec![(TokenKind::Identifier, "`class`"), (TokenKind::Identifier, "`a b-c`")]
+        );
+    }
+
+    #[test]
+    fn reserved_words() {
+        assert_eq!(lex("switch"), vec![(TokenKind::Reserved, "switch")]);
+    }
+}
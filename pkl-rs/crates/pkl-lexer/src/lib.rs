@@ -1,12 +1,36 @@
+#![forbid(unsafe_code)]
+
 mod source;
+mod comment;
+pub mod diagnostic;
 mod handler;
-mod identifier;
+pub mod identifier;
+pub mod incremental;
+pub mod keyword;
+pub mod line_index;
+pub mod literal;
+mod lookahead;
+mod mode;
+mod numeric;
+mod scan;
+pub mod source_map;
+mod string;
 pub mod token;
+pub mod trivia;
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
 
 use handler::{ByteHandler, BYTE_HANDLERS};
 use oxc_allocator::Allocator;
+use pkl_diagnostics::Code;
+use crate::diagnostic::Diagnostic;
+use crate::mode::LexMode;
 use crate::source::Source;
-use crate::token::Token;
+use crate::token::{Span, Token, TokenKind};
+
+pub use lookahead::Checkpoint;
 
 // `Lexer` is a struct that holds a reference to an `Allocator` and a `Source` instance.
 ///
@@ -18,15 +42,20 @@ use crate::token::Token;
 /// * `alloc`: A reference to an `Allocator` instance.
 /// * `source`: A `Source` instance that represents the source code.
 pub struct Lexer<'alloc> {
-    /// Reference to the given `Allocator` instance.
-    #[allow(dead_code)]
+    /// Reference to the given `Allocator` instance, which holds token values that can't borrow from the source.
     pub(crate) alloc: &'alloc Allocator,
-    /// The source code to be tokenized.
-    pub source: Source,
-    /// The current index in the source code.
-    index: usize,
+    /// The source code to be tokenized, along with the current position in it.
+    pub source: Source<'alloc>,
+    /// Stack of nested string and interpolation modes. Empty while lexing top-level expressions.
+    modes: Vec<LexMode>,
+    /// Problems found so far
+    diagnostics: Vec<Diagnostic>,
+    /// Tokens that have been peeked at but not yet consumed by the iterator
+    lookahead: VecDeque<Token<'alloc>>,
+    /// Whether the iterator has yielded its `Eof` token
+    finished: bool,
 
-    pub token: Token,
+    pub token: Token<'alloc>,
 }
 
 #[allow(dead_code)]
@@ -45,34 +74,53 @@ impl<'a> Lexer<'a> {
         Lexer {
             alloc,
             source: Source::new(source),
-            index: 0,
+            modes: Vec::new(),
+            diagnostics: Vec::new(),
+            lookahead: VecDeque::new(),
+            finished: false,
             token: Token::default(),
         }
     }
 
     fn handler_from_byte(&self, byte: u8) -> ByteHandler {
-        unsafe { *(&BYTE_HANDLERS as *const ByteHandler).offset(byte as isize) }
-    }
-
-    fn __handler_index_from_byte(&self, byte: u8) -> usize {
-        byte as usize
+        BYTE_HANDLERS[byte as usize]
     }
 
+    /// Consumes the current character, which is a single byte in the common ASCII case.
     #[inline]
     fn bump(&mut self) {
-        self.index += 1;
+        match self.source.peek_byte() {
+            Some(byte) if byte.is_ascii() => self.source.advance(1),
+            _ => {
+                self.source.next_char();
+            }
+        }
     }
 
-    pub fn next_token(&mut self) -> Token {
-        let next_byte = self.read_byte();
-        if let Some(handler) = self.handler_from_byte(next_byte) {
-            self.token.span.start = self.index;
-            handler(self);
+    /// Lexes the next token, including whitespace (as [`TokenKind::Empty`]) and comments.
+    ///
+    /// Once the input is exhausted, every call returns a [`TokenKind::Eof`] token. No input makes it panic, and every
+    /// call before the end consumes some of it.
+    pub fn next_token(&mut self) -> Token<'a> {
+        self.token.span.start = self.source.pos();
+
+        if let Some(&LexMode::String(delimiter)) = self.modes.last() {
+            self.string_continuation_handler(delimiter);
+        } else if let Some(next_byte) = self.source.peek_byte() {
+            if let Some(handler) = self.handler_from_byte(next_byte) {
+                handler(self);
+            } else {
+                self.unexpected_character();
+            }
+        } else if !self.modes.is_empty() {
+            // the input ended inside an interpolation
+            self.modes.clear();
+            self.error(Code::UnterminatedInterpolation, "unterminated string interpolation");
         } else {
-            self.bump();
+            self.token.kind = TokenKind::Eof;
         }
 
-        self.token.span.end = self.index;
+        self.token.span.end = self.source.pos();
 
         let tok = self.token;
         self.token = Token::default();
@@ -80,6 +128,34 @@ impl<'a> Lexer<'a> {
         tok
     }
 
+    /// Problems found in the source so far, in the order they were found.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Records a problem with the current token, spanning from its start to the current position, and turns it into
+    /// a [`TokenKind::Error`] token.
+    fn error(&mut self, code: Code, message: impl Into<String>) {
+        let span = Span::new(self.token.span.start, self.source.pos());
+        self.diagnostics.push(Diagnostic::new(span, message).with_code(code));
+        self.token.kind = TokenKind::Error;
+    }
+
+    /// Moves text into the arena, unless it already borrows from the source.
+    fn intern(&self, text: Cow<'a, str>) -> &'a str {
+        match text {
+            Cow::Borrowed(text) => text,
+            Cow::Owned(text) => self.alloc.alloc_str(&text),
+        }
+    }
+
+    /// Consumes a character that can't start any token, and reports it.
+    fn unexpected_character(&mut self) {
+        let c = self.source.peek_char().unwrap_or_default();
+        self.bump();
+        self.error(Code::UnexpectedCharacter, format!("unexpected character {c:?}"));
+    }
+
     pub fn token_as_str(&self) -> &'a str {
         let start = self.token.span.start;
         let end = self.token.span.end;
@@ -87,14 +163,9 @@ impl<'a> Lexer<'a> {
         self.source.get_slice(start, end)
     }
 
-    pub fn read_byte(&mut self) -> u8 {
-        // unsafe { *self.source.ptr.add(self.index) }
-        self.source.advance(self.index)
-    }
-
-    pub fn next_eq(&mut self, c: char) -> bool {
-        if self.source.peek() == Some(c as u8) {
-            self.source.advance(1);
+    /// Consumes the current byte if it is `byte`, returning whether it was consumed.
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.source.peek_byte() == Some(byte) {
             self.bump();
             true
         } else {
@@ -103,15 +174,165 @@ impl<'a> Lexer<'a> {
     }
 
     pub fn is_at_end(&self) -> bool {
-        // don't use source.is_at_end() because it's not accurate
         self.source.is_at_end()
     }
 
-    pub fn peek(&self) -> u8 {
-        self.source.peek().unwrap()
+    /// Lexes `source` to completion, returning every token up to and including the final [`TokenKind::Eof`].
+    ///
+    /// Whitespace is dropped, like when iterating over a [`Lexer`]. Use a `Lexer` directly to also get the
+    /// diagnostics.
+    pub fn tokenize(alloc: &'a Allocator, source: &'a str) -> Vec<Token<'a>> {
+        Lexer::new(alloc, source).collect()
+    }
+}
+
+/// Iterating over a `Lexer` yields the tokens that matter to a parser: whitespace is skipped, but comments are kept.
+/// The last token is always a single [`TokenKind::Eof`].
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.finished {
+            return None;
+        }
+
+        let token = match self.lookahead.pop_front() {
+            Some(token) => token,
+            None => self.next_significant_token(),
+        };
+        self.finished = token.kind == TokenKind::Eof;
+
+        Some(token)
+    }
+}
+
+impl FusedIterator for Lexer<'_> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Lexes `source` to completion, returning every token but the final `Eof` along with its text.
+    ///
+    /// Comments are kept, since they are tested like any other token.
+    pub(crate) fn lex(source: &str) -> Vec<(TokenKind, &str)> {
+        let alloc = Allocator::default();
+        Lexer::tokenize(&alloc, source)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Eof)
+            .map(|token| (token.kind, &source[token.span.start..token.span.end]))
+            .collect()
+    }
+
+    #[test]
+    fn crlf_is_a_single_line_terminator() {
+        let alloc = Allocator::default();
+
+        let mut lexer = Lexer::new(&alloc, "\r\n\r");
+        let crlf = lexer.next_token();
+        let cr = lexer.next_token();
+
+        assert_eq!(crlf.kind, TokenKind::Empty);
+        assert_eq!((crlf.span.start, crlf.span.end), (0, 2));
+        assert_eq!((cr.span.start, cr.span.end), (2, 3));
+    }
+
+    #[test]
+    fn unexpected_characters_become_error_tokens() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, "a \0 ~ & b");
+        let kinds: Vec<_> = lexer.by_ref().map(|token| token.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Identifier,
+                TokenKind::Error,
+                TokenKind::Error,
+                TokenKind::Error,
+                TokenKind::Identifier,
+                TokenKind::Eof,
+            ]
+        );
+
+        let messages: Vec<_> = lexer.diagnostics().iter().map(|d| (d.span, d.message.as_str())).collect();
+        assert_eq!(
+            messages,
+            vec![
+                (Span::new(2, 3), "unexpected character '\\0'"),
+                (Span::new(4, 5), "unexpected character '~'"),
+                (Span::new(6, 7), "unexpected character '&'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn iteration_ends_with_a_single_eof() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, "a ");
+
+        assert_eq!(lexer.next().map(|token| token.kind), Some(TokenKind::Identifier));
+        let eof = lexer.next().unwrap();
+        assert_eq!(eof.kind, TokenKind::Eof);
+        assert_eq!(eof.span, Span::new(2, 2));
+        assert!(lexer.next().is_none());
+        assert!(lexer.next().is_none());
+    }
+
+    #[test]
+    fn input_ending_inside_an_interpolation() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, "\"\\(a");
+        let kinds: Vec<_> = lexer.by_ref().map(|token| token.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::StringStart,
+                TokenKind::InterpolationStart,
+                TokenKind::Identifier,
+                TokenKind::Error,
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(lexer.diagnostics()[0].message, "unterminated string interpolation");
+        assert_eq!(lexer.diagnostics()[0].code, Some(Code::UnterminatedInterpolation));
+    }
+
+    /// A seeded generator of pseudo-random numbers (xorshift), so that a failing property test can be replayed.
+    pub(crate) struct Rng(pub(crate) u64);
+
+    impl Rng {
+        pub(crate) fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        pub(crate) fn pick<'t, T>(&mut self, items: &'t [T]) -> &'t T {
+            &items[self.below(items.len())]
+        }
     }
 
-    fn consume_char(&mut self) -> u8 {
-        self.source.next_char().unwrap() as u8
+    /// Token streams of random pieces of source, separated by whitespace, lex into the tokens of the pieces lexed on
+    /// their own: no token's boundaries or kind depend on what's next to it.
+    #[test]
+    fn relexing_random_token_streams() {
+        const PIECES: &[&str] = &[
+            "pigeon", "`my bird`", "_", "$x", "class", "amends", "import*", "read?", "0", "42", "1_000", "0x1F",
+            "0b101", "0o17", "1.5", "2e10", "3.5e-2", ".5", "\"\"", "\"a\\n\\(x + 1)b\"", "#\"raw \\ \"#",
+            "\"\"\"\n  multi\n  \"\"\"", "+", "-", "*", "/", "~/", "%", "**", "==", "!=", "<", "<=", ">", ">=", "&&",
+            "||", "!", "!!", "??", "|>",
+            "?.", ".", "...", "...?", "->", "=", "(", ")", "[", "]", "[[", "]]", "{", "}", ",", ";", ":", "?", "@", "|",
+            "// line\n", "/* block */", "/// doc\n", "\n",
+        ];
+        let mut rng = Rng(0x5eed_cafe);
+        for _ in 0..1000 {
+            let pieces: Vec<&str> = (0..rng.below(12) + 1).map(|_| *rng.pick(PIECES)).collect();
+            let source = pieces.join(" ");
+            let expected: Vec<(TokenKind, &str)> = pieces.iter().flat_map(|piece| lex(piece)).collect();
+            assert_eq!(lex(&source), expected, "{source:?}");
+        }
     }
-}
\ No newline at end of file
+}
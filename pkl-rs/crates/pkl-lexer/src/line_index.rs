@@ -0,0 +1,270 @@
+//! Conversion between byte offsets and line/column positions.
+
+use std::fmt;
+
+use crate::token::Span;
+
+/// A position in a source file as a 0-based line and column.
+///
+/// Depending on where it came from, the column counts bytes ([`LineIndex::line_col`]), UTF-16 code units
+/// ([`LineIndex::line_col_utf16`]), the default of LSP clients, or the units of another [`PositionEncoding`]. It is
+/// displayed 1-based, the way editors and compilers print positions: `3:7`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.col + 1)
+    }
+}
+
+/// What the column of a [`LineCol`] counts, as negotiated by the `positionEncoding` of the LSP.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PositionEncoding {
+    /// Bytes of UTF-8
+    Utf8,
+    /// UTF-16 code units, of which characters outside the Basic Multilingual Plane take two
+    #[default]
+    Utf16,
+    /// Characters
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// The encoding by its name in the LSP, like `utf-16`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "utf-8" => Some(PositionEncoding::Utf8),
+            "utf-16" => Some(PositionEncoding::Utf16),
+            "utf-32" => Some(PositionEncoding::Utf32),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PositionEncoding::Utf8 => "utf-8",
+            PositionEncoding::Utf16 => "utf-16",
+            PositionEncoding::Utf32 => "utf-32",
+        }
+    }
+
+    /// How many units of the encoding a character takes.
+    fn width(self, c: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => c.len_utf8(),
+            PositionEncoding::Utf16 => c.len_utf16(),
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+}
+
+/// A non-ASCII character, whose width differs between encodings.
+#[derive(Debug, Clone, Copy)]
+struct WideChar {
+    /// Byte offset of the character from the start of its line
+    start: usize,
+    c: char,
+}
+
+impl WideChar {
+    /// How many more bytes the character takes than units of `encoding`.
+    fn narrowing(&self, encoding: PositionEncoding) -> usize {
+        self.c.len_utf8() - encoding.width(self.c)
+    }
+}
+
+/// The line structure of a source file, computed once so that offsets can be converted to line/column positions
+/// without rescanning the text.
+///
+/// `\n`, `\r\n`, and `\r` are all line terminators, the same as for the lexer.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// Offset of the first byte of every line; the first line starts at 0
+    line_starts: Vec<usize>,
+    /// The non-ASCII characters of every line, in order
+    wide_chars: Vec<Vec<WideChar>>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut wide_chars = vec![Vec::new()];
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                // the `\n` of a `\r\n` ends the line
+                '\r' if chars.peek().is_some_and(|&(_, next)| next == '\n') => {}
+                '\n' | '\r' => {
+                    line_starts.push(offset + 1);
+                    wide_chars.push(Vec::new());
+                }
+                c if !c.is_ascii() => {
+                    let line_start = line_starts[line_starts.len() - 1];
+                    wide_chars[line_starts.len() - 1].push(WideChar { start: offset - line_start, c });
+                }
+                _ => {}
+            }
+        }
+
+        LineIndex {
+            line_starts,
+            wide_chars,
+            len: text.len(),
+        }
+    }
+
+    /// Number of lines, which is one more than the number of line terminators.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The position of a byte offset, with the column in bytes. Offsets past the end are clamped to the end.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.len);
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+
+        LineCol {
+            line,
+            col: offset - self.line_starts[line],
+        }
+    }
+
+    /// The position of a byte offset, with the column in UTF-16 code units.
+    pub fn line_col_utf16(&self, offset: usize) -> LineCol {
+        self.line_col_in(offset, PositionEncoding::Utf16)
+    }
+
+    /// The position of a byte offset, with the column in the units of `encoding`. An offset inside a character is
+    /// the position of the character.
+    pub fn line_col_in(&self, offset: usize, encoding: PositionEncoding) -> LineCol {
+        let LineCol { line, col } = self.line_col(offset);
+        let mut converted = col;
+        for c in self.wide_chars[line].iter().take_while(|c| c.start < col) {
+            if c.start + c.c.len_utf8() > col {
+                converted -= col - c.start;
+                break;
+            }
+            converted -= c.narrowing(encoding);
+        }
+
+        LineCol { line, col: converted }
+    }
+
+    /// The start and end positions of a span, with columns in bytes.
+    pub fn span_line_cols(&self, span: Span) -> (LineCol, LineCol) {
+        (self.line_col(span.start), self.line_col(span.end))
+    }
+
+    /// The start and end positions of a span, with columns in UTF-16 code units.
+    pub fn span_line_cols_utf16(&self, span: Span) -> (LineCol, LineCol) {
+        self.span_line_cols_in(span, PositionEncoding::Utf16)
+    }
+
+    /// The start and end positions of a span, with columns in the units of `encoding`.
+    pub fn span_line_cols_in(&self, span: Span, encoding: PositionEncoding) -> (LineCol, LineCol) {
+        (self.line_col_in(span.start, encoding), self.line_col_in(span.end, encoding))
+    }
+
+    /// The byte offset of a position whose column is in bytes, or `None` if the line doesn't exist.
+    ///
+    /// Columns past the end of the line are not checked, so they point into the following lines.
+    pub fn offset(&self, position: LineCol) -> Option<usize> {
+        let start = *self.line_starts.get(position.line)?;
+        Some((start + position.col).min(self.len))
+    }
+
+    /// The byte offset of a position whose column is in UTF-16 code units, or `None` if the line doesn't exist.
+    pub fn offset_utf16(&self, position: LineCol) -> Option<usize> {
+        self.offset_in(position, PositionEncoding::Utf16)
+    }
+
+    /// The byte offset of a position whose column is in the units of `encoding`, or `None` if the line doesn't
+    /// exist. A column in the middle of a character, like between the two halves of a UTF-16 surrogate pair, is the
+    /// offset of the character.
+    pub fn offset_in(&self, position: LineCol, encoding: PositionEncoding) -> Option<usize> {
+        let mut widened = 0;
+        for c in self.wide_chars.get(position.line)? {
+            let start = c.start - widened;
+            if start >= position.col {
+                break;
+            }
+            if position.col < start + encoding.width(c.c) {
+                return self.offset(LineCol { line: position.line, col: c.start });
+            }
+            widened += c.narrowing(encoding);
+        }
+
+        self.offset(LineCol { line: position.line, col: position.col + widened })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn offsets_to_line_cols() {
+        let index = LineIndex::new("ab\ncd\r\n\re");
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_col(0), LineCol { line: 0, col: 0 });
+        assert_eq!(index.line_col(2), LineCol { line: 0, col: 2 });
+        assert_eq!(index.line_col(4), LineCol { line: 1, col: 1 });
+        assert_eq!(index.line_col(7), LineCol { line: 2, col: 0 });
+        assert_eq!(index.line_col(8), LineCol { line: 3, col: 0 });
+        assert_eq!(index.line_col(100), LineCol { line: 3, col: 1 });
+        assert_eq!(index.line_col(4).to_string(), "2:2");
+    }
+
+    #[test]
+    fn utf16_columns() {
+        // `é` is 2 bytes and 1 UTF-16 unit, `😀` is 4 bytes and 2 units
+        let text = "x\né😀 = 1";
+        let index = LineIndex::new(text);
+        let one = text.find('1').unwrap();
+
+        assert_eq!(index.line_col(one), LineCol { line: 1, col: 9 });
+        assert_eq!(index.line_col_utf16(one), LineCol { line: 1, col: 6 });
+        assert_eq!(index.offset_utf16(LineCol { line: 1, col: 6 }), Some(one));
+        assert_eq!(index.offset_utf16(LineCol { line: 1, col: 1 }), Some(4));
+        assert_eq!(index.offset(LineCol { line: 2, col: 0 }), None);
+    }
+
+    #[test]
+    fn encodings() {
+        // `é` is 2 bytes, `名` 3, and `😀` 4, which is 2 UTF-16 units; all are one character
+        let text = "aé名😀b
+😀😀";
+        let index = LineIndex::new(text);
+        let b = text.find('b').unwrap();
+        let columns = |offset| {
+            let encodings = [PositionEncoding::Utf8, PositionEncoding::Utf16, PositionEncoding::Utf32];
+            encodings.map(|encoding| index.line_col_in(offset, encoding).col)
+        };
+        assert_eq!(columns(b), [10, 5, 4]);
+        assert_eq!(columns(text.len()), [8, 4, 2]);
+        // inside the emoji, which is where it starts
+        assert_eq!(columns(b - 1), [6, 3, 3]);
+
+        for encoding in [PositionEncoding::Utf8, PositionEncoding::Utf16, PositionEncoding::Utf32] {
+            for offset in (0..=text.len()).filter(|&offset| text.is_char_boundary(offset)) {
+                let position = index.line_col_in(offset, encoding);
+                assert_eq!(index.offset_in(position, encoding), Some(offset), "{encoding:?} at {offset}");
+            }
+            let span = Span::new(b, text.len());
+            let (start, end) = index.span_line_cols_in(span, encoding);
+            assert_eq!((start.line, end.line), (0, 1));
+        }
+        // between the halves of the surrogate pair of the first emoji of the second line
+        assert_eq!(index.offset_in(LineCol { line: 1, col: 1 }, PositionEncoding::Utf16), Some(b + 2));
+        assert_eq!(index.offset_in(LineCol { line: 1, col: 3 }, PositionEncoding::Utf8), Some(b + 2));
+        assert_eq!(index.offset_in(LineCol { line: 1, col: 1 }, PositionEncoding::Utf32), Some(b + 6));
+        assert_eq!(PositionEncoding::from_name("utf-32").map(PositionEncoding::name), Some("utf-32"));
+    }
+}
@@ -0,0 +1,296 @@
+//! Helpers for turning the raw text of literal tokens into the values they denote.
+
+use std::borrow::Cow;
+use std::fmt;
+
+/// The delimiter style of a string literal.
+///
+/// Strings may be wrapped in any number of pound signs, e.g. `#"..."#` or `##"""..."""##`. Inside such a string,
+/// escapes and interpolations have to carry the same number of pounds (`\#n`, `\#(x)`), so a plain `\` or `"` is
+/// just text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StringDelimiter {
+    /// Number of `#` surrounding the quotes
+    pub pounds: usize,
+    /// Whether the string is delimited by `"""` rather than `"`
+    pub multiline: bool,
+}
+
+impl StringDelimiter {
+    /// Reads the delimiter from the text of a `StringLiteral` or `StringStart` token.
+    pub fn from_opening(text: &str) -> Self {
+        let pounds = text.bytes().take_while(|&byte| byte == b'#').count();
+
+        StringDelimiter {
+            pounds,
+            multiline: text[pounds..].starts_with("\"\"\""),
+        }
+    }
+
+    /// Number of quotes in the delimiter: 3 for multiline strings, otherwise 1.
+    pub fn quotes(&self) -> usize {
+        if self.multiline {
+            3
+        } else {
+            1
+        }
+    }
+
+    /// Width of the opening (or closing) delimiter in bytes, including pounds.
+    pub fn width(&self) -> usize {
+        self.quotes() + self.pounds
+    }
+}
+
+/// Replaces the escape sequences in the raw text of a string (or string part) by the characters they denote.
+///
+/// `pounds` is the number of pounds of the string's delimiter, which escapes have to repeat (`\#n` in `#"..."#`).
+/// Invalid escapes, which the lexer reports, are kept as they are. Text without escapes is returned as is.
+pub fn unescape(raw: &str, pounds: usize) -> Cow<'_, str> {
+    let escape = format!("\\{}", "#".repeat(pounds));
+    if !raw.contains(&escape) {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(pos) = rest.find(&escape) {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + escape.len()..];
+
+        let (c, len) = match rest.as_bytes().first() {
+            Some(b'n') => (Some('\n'), 1),
+            Some(b'r') => (Some('\r'), 1),
+            Some(b't') => (Some('\t'), 1),
+            Some(b'"') => (Some('"'), 1),
+            Some(b'\\') => (Some('\\'), 1),
+            Some(b'u') => unicode_escape(&rest[1..]).map_or((None, 0), |(c, len)| (Some(c), len + 1)),
+            _ => (None, 0),
+        };
+
+        match c {
+            Some(c) => out.push(c),
+            None => out.push_str(&escape),
+        }
+        rest = &rest[len..];
+    }
+
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Parses the `{...}` of a `\u{...}` escape, returning the character and the length of the braces and digits.
+fn unicode_escape(text: &str) -> Option<(char, usize)> {
+    let digits = text.strip_prefix('{')?;
+    let end = digits.find('}')?;
+    let c = u32::from_str_radix(&digits[..end], 16).ok().and_then(char::from_u32)?;
+
+    Some((c, end + 2))
+}
+
+/// Reasons the raw text of a multiline string can be malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultilineStringError {
+    /// Something other than a line break follows the opening `"""`.
+    ContentOnOpeningLine,
+    /// Something other than whitespace precedes the closing `"""` on its line.
+    ClosingDelimiterNotOnOwnLine,
+    /// A content line is indented less than the closing delimiter.
+    ///
+    /// `part` is the index of the raw part containing the line, and `offset` the byte offset of the line within it.
+    InsufficientIndentation { part: usize, offset: usize },
+}
+
+impl fmt::Display for MultilineStringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultilineStringError::ContentOnOpeningLine => {
+                write!(f, "the content of a multiline string must begin on a new line")
+            }
+            MultilineStringError::ClosingDelimiterNotOnOwnLine => {
+                write!(f, "the closing delimiter of a multiline string must be on its own line")
+            }
+            MultilineStringError::InsufficientIndentation { .. } => {
+                write!(f, "line must match or exceed the indentation of the closing delimiter")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultilineStringError {}
+
+/// Applies Pkl's multiline string layout rules to the raw text of a multiline string.
+///
+/// `parts` is the raw text between the delimiters, split at interpolations: a string with `n` interpolations has
+/// `n + 1` parts (some possibly empty), excluding the `"""` delimiters and the `\( ... )` interpolations themselves.
+///
+/// The rules are:
+///
+/// * the content starts on the line after the opening delimiter, and ends on the line before the closing delimiter;
+/// * the whitespace preceding the closing delimiter is the string's indentation, and is removed from the start of
+///   every content line (lines consisting only of whitespace may be indented less);
+/// * line breaks (`\n`, `\r\n`, or `\r`) are normalized to `\n`.
+///
+/// Escape sequences are left untouched, since an escaped `\n` must not be treated as a line break.
+///
+/// The rules don't depend on the delimiter's pounds, so they are the same for `"""` and `#"""` strings.
+pub fn strip_multiline_indent(parts: &[&str]) -> Result<Vec<String>, MultilineStringError> {
+    let first = parts.first().copied().unwrap_or_default();
+    let last_index = parts.len().saturating_sub(1);
+    let last = parts.last().copied().unwrap_or_default();
+
+    let opening_break = line_break_width(first).ok_or(MultilineStringError::ContentOnOpeningLine)?;
+
+    let closing_line_start = last
+        .rfind(['\n', '\r'])
+        .map(|pos| pos + 1)
+        .ok_or(MultilineStringError::ClosingDelimiterNotOnOwnLine)?;
+    let indent = &last[closing_line_start..];
+    if !indent.bytes().all(|byte| byte == b' ' || byte == b'\t') {
+        return Err(MultilineStringError::ClosingDelimiterNotOnOwnLine);
+    }
+
+    // the line break preceding the closing line isn't part of the content
+    let mut closing_break_start = closing_line_start - 1;
+    if last[..closing_break_start].ends_with('\r') && last[closing_break_start..].starts_with('\n') {
+        closing_break_start -= 1;
+    }
+
+    let mut stripped = Vec::with_capacity(parts.len());
+
+    for (index, part) in parts.iter().enumerate() {
+        let start = if index == 0 { opening_break } else { 0 };
+        let end = if index == last_index { closing_break_start } else { part.len() };
+        // `"""\n"""` has a single line break that both opens and closes the string
+        let text = if start < end { &part[start..end] } else { "" };
+
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        let mut at_line_start = index == 0;
+
+        loop {
+            if at_line_start {
+                if let Some(unindented) = rest.strip_prefix(indent) {
+                    rest = unindented;
+                } else {
+                    let line_end = rest.find(['\n', '\r']).unwrap_or(rest.len());
+                    let line = &rest[..line_end];
+                    let followed_by_break = line_end < rest.len() || index == last_index;
+
+                    if followed_by_break && indent.starts_with(line) {
+                        rest = &rest[line_end..];
+                    } else {
+                        return Err(MultilineStringError::InsufficientIndentation {
+                            part: index,
+                            offset: start + (text.len() - rest.len()),
+                        });
+                    }
+                }
+            }
+
+            match rest.find(['\n', '\r']) {
+                Some(pos) => {
+                    out.push_str(&rest[..pos]);
+                    out.push('\n');
+                    rest = &rest[pos + line_break_width(&rest[pos..]).unwrap_or(1)..];
+                    at_line_start = true;
+                }
+                None => {
+                    out.push_str(rest);
+                    break;
+                }
+            }
+        }
+
+        stripped.push(out);
+    }
+
+    Ok(stripped)
+}
+
+/// Width of the line break at the start of `text`, if it starts with one.
+fn line_break_width(text: &str) -> Option<usize> {
+    if text.starts_with("\r\n") {
+        Some(2)
+    } else if text.starts_with(['\n', '\r']) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strip(parts: &[&str]) -> Result<Vec<String>, MultilineStringError> {
+        strip_multiline_indent(parts)
+    }
+
+    #[test]
+    fn delimiter_from_opening() {
+        assert_eq!(StringDelimiter::from_opening("\"a"), StringDelimiter { pounds: 0, multiline: false });
+        assert_eq!(StringDelimiter::from_opening("##\"\"\"\n"), StringDelimiter { pounds: 2, multiline: true });
+        assert_eq!(StringDelimiter::from_opening("##\"\"\"\n").width(), 5);
+    }
+
+    #[test]
+    fn unescapes() {
+        assert_eq!(unescape("a\\tb\\\\\\\"", 0), "a\tb\\\"");
+        assert_eq!(unescape("\\u{1F600}\\u{41}", 0), "😀A");
+        assert_eq!(unescape("\\n\\#n", 1), "\\n\n");
+        assert_eq!(unescape("\\q \\u{110000}", 0), "\\q \\u{110000}");
+        assert!(matches!(unescape("plain", 0), Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn strips_closing_delimiter_indentation() {
+        assert_eq!(strip(&["\n    a\n      b\n    "]), Ok(vec!["a\n  b".to_string()]));
+    }
+
+    #[test]
+    fn empty_multiline_string() {
+        assert_eq!(strip(&["\n"]), Ok(vec![String::new()]));
+        assert_eq!(strip(&["\n  "]), Ok(vec![String::new()]));
+    }
+
+    #[test]
+    fn blank_lines_may_be_less_indented() {
+        assert_eq!(strip(&["\n    a\n\n  \n    b\n    "]), Ok(vec!["a\n\n\nb".to_string()]));
+    }
+
+    #[test]
+    fn normalizes_windows_line_endings() {
+        assert_eq!(strip(&["\r\n  a\r\n  b\r  "]), Ok(vec!["a\nb".to_string()]));
+    }
+
+    #[test]
+    fn interpolated_parts() {
+        // """
+        //   x = \(x) y
+        //   \(z)
+        //   """
+        let parts = ["\n  x = ", " y\n  ", "\n  "];
+
+        assert_eq!(
+            strip(&parts),
+            Ok(vec!["x = ".to_string(), " y\n".to_string(), String::new()])
+        );
+    }
+
+    #[test]
+    fn escapes_are_not_line_breaks() {
+        assert_eq!(strip(&["\n  a\\nb\n  "]), Ok(vec!["a\\nb".to_string()]));
+    }
+
+    #[test]
+    fn malformed_layouts() {
+        assert_eq!(strip(&["a\n"]), Err(MultilineStringError::ContentOnOpeningLine));
+        assert_eq!(strip(&["\n  a"]), Err(MultilineStringError::ClosingDelimiterNotOnOwnLine));
+        assert_eq!(
+            strip(&["\n    a\n  b\n    "]),
+            Err(MultilineStringError::InsufficientIndentation { part: 0, offset: 7 })
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::mode::LexMode;
+use crate::token::{Token, TokenKind};
+use crate::Lexer;
+
+/// A saved lexer state, created by [`Lexer::checkpoint`] and restored by [`Lexer::rewind`].
+///
+/// This lets a parser speculatively consume tokens (e.g. to tell a lambda's parameter list from a parenthesized
+/// expression) and back out without re-lexing the source from the start. A checkpoint holds everything that lexing
+/// changes: the position in the source, the stack of string and interpolation modes, the peeked tokens, the current
+/// [`Lexer::token`], and how many diagnostics there were.
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a> {
+    pos: usize,
+    modes: Vec<LexMode>,
+    lookahead: Vec<Token<'a>>,
+    token: Token<'a>,
+    diagnostics: usize,
+    finished: bool,
+}
+
+impl Checkpoint<'_> {
+    /// The byte offset in the source that lexing resumes from after rewinding, past the peeked tokens.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Returns the token the iterator will yield next, without consuming it.
+    pub fn peek(&mut self) -> Token<'a> {
+        self.peek_nth(0)
+    }
+
+    /// Returns the token `n` positions ahead of the iterator (the next one being `0`), without consuming anything.
+    ///
+    /// Peeked tokens are buffered, so the tokens in between are lexed only once. Past the end of the input this
+    /// returns [`TokenKind::Eof`] tokens. Calling [`Lexer::next_token`] directly bypasses the buffer, so it shouldn't
+    /// be mixed with peeking.
+    pub fn peek_nth(&mut self, n: usize) -> Token<'a> {
+        while self.lookahead.len() <= n {
+            let token = self.next_significant_token();
+            self.lookahead.push_back(token);
+        }
+
+        self.lookahead[n]
+    }
+
+    /// Saves the current state, so that everything lexed after this can be undone with [`Lexer::rewind`].
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint {
+            pos: self.source.pos(),
+            modes: self.modes.clone(),
+            lookahead: self.lookahead.iter().copied().collect(),
+            token: self.token,
+            diagnostics: self.diagnostics.len(),
+            finished: self.finished,
+        }
+    }
+
+    /// Restores a state saved by [`Lexer::checkpoint`]. Diagnostics reported since then are dropped, since the tokens
+    /// they belong to will be lexed (and reported) again.
+    pub fn rewind(&mut self, checkpoint: Checkpoint<'a>) {
+        self.source.set_pos(checkpoint.pos);
+        self.modes = checkpoint.modes;
+        self.lookahead = checkpoint.lookahead.into();
+        self.token = checkpoint.token;
+        self.diagnostics.truncate(checkpoint.diagnostics);
+        self.finished = checkpoint.finished;
+    }
+
+    /// Lexes the next token that isn't whitespace.
+    pub(crate) fn next_significant_token(&mut self) -> Token<'a> {
+        loop {
+            let token = self.next_token();
+            if token.kind != TokenKind::Empty {
+                return token;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::token::TokenKind;
+    use crate::Lexer;
+    use oxc_allocator::Allocator;
+
+    fn kinds(lexer: &mut Lexer) -> Vec<TokenKind> {
+        lexer.map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn peeking_does_not_consume() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, "a + b");
+
+        assert_eq!(lexer.peek().kind, TokenKind::Identifier);
+        assert_eq!(lexer.peek_nth(1).kind, TokenKind::Plus);
+        assert_eq!(lexer.peek_nth(5).kind, TokenKind::Eof);
+        assert_eq!(
+            kinds(&mut lexer),
+            vec![TokenKind::Identifier, TokenKind::Plus, TokenKind::Identifier, TokenKind::Eof]
+        );
+    }
+
+    #[test]
+    fn rewinding_restores_string_modes() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, r#"x "a\(b)c" ~"#);
+
+        lexer.next();
+        let peeked = lexer.peek();
+        let checkpoint = lexer.checkpoint();
+        assert_eq!(checkpoint.pos(), peeked.span.end);
+        let token = lexer.token;
+
+        let first = kinds(&mut lexer);
+        assert_eq!(lexer.diagnostics().len(), 1);
+
+        lexer.rewind(checkpoint);
+        assert_eq!((lexer.token.kind, lexer.token.span), (token.kind, token.span));
+        assert!(lexer.diagnostics().is_empty());
+        assert_eq!(kinds(&mut lexer), first);
+        assert_eq!(lexer.diagnostics().len(), 1);
+    }
+}
@@ -0,0 +1,18 @@
+use crate::literal::StringDelimiter;
+
+/// Lexing modes that can be nested inside each other.
+///
+/// The lexer starts out in normal expression mode, which isn't represented on the stack. Entering a string that
+/// contains an interpolation pushes [`LexMode::String`], and each `\(` inside that string pushes
+/// [`LexMode::Interpolation`], so e.g. `"a \("b \(c)")"` ends up with four modes at its deepest point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LexMode {
+    /// Inside the body of a string literal; the next token is a string part, an interpolation, or the string's end
+    String(StringDelimiter),
+    /// Inside `\( ... )`, lexing a regular expression
+    Interpolation {
+        /// Number of `(` opened inside the interpolation that haven't been closed yet. The `)` that arrives while
+        /// this is zero closes the interpolation itself.
+        depth: u32,
+    },
+}
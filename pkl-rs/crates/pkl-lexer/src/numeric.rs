@@ -0,0 +1,169 @@
+use pkl_diagnostics::Code;
+
+use crate::token::{TokenKind, TokenValue};
+use crate::Lexer;
+
+impl<'a> Lexer<'a> {
+    /// Scans a numeric literal starting at a digit, or at a `.` that is directly followed by one.
+    ///
+    /// Integers may be written in decimal, hex (`0x`), octal (`0o`), or binary (`0b`), and floats in decimal with an
+    /// optional fraction and exponent (`1.5`, `.5`, `1e10`, `2.5E-3`). Underscores may be used as digit separators
+    /// anywhere after the first digit. The parsed value is stored on the token, so that later stages don't have to
+    /// re-parse the text.
+    ///
+    /// A `.` that isn't followed by a digit isn't part of the literal, so `5.min` lexes as `5`, `.`, `min`.
+    ///
+    /// Integers that don't fit in an `i64`, radix prefixes that aren't directly followed by a digit (`0x`, `0x_FF`),
+    /// and exponents without digits (`1e`, `1e+`) are error tokens.
+    pub(super) fn numeric_literal_handler(&mut self) {
+        let start = self.source.pos();
+
+        if self.source.peek_byte() == Some(b'0') {
+            let radix = match self.source.peek_byte_at(1) {
+                Some(b'x' | b'X') => Some(16),
+                Some(b'o' | b'O') => Some(8),
+                Some(b'b' | b'B') => Some(2),
+                _ => None,
+            };
+
+            if let Some(radix) = radix {
+                self.source.advance(2);
+                let digits_start = self.source.pos();
+                self.skip_digits(radix);
+
+                let digits = self.source.get_slice(digits_start, self.source.pos()).replace('_', "");
+                if digits.is_empty() {
+                    self.error(Code::MissingDigits, "missing digits after the radix prefix");
+                } else if self.source.get_slice(digits_start, self.source.pos()).starts_with('_') {
+                    self.error(Code::MissingDigits, "a digit has to come before `_` separators after the radix prefix");
+                } else {
+                    self.int_literal(i64::from_str_radix(&digits, radix).ok());
+                }
+                return;
+            }
+        }
+
+        let mut is_float = false;
+
+        self.skip_digits(10);
+
+        if self.source.peek_byte() == Some(b'.') && self.is_digit_at(1) {
+            is_float = true;
+            self.bump();
+            self.skip_digits(10);
+        }
+
+        if let Some(b'e' | b'E') = self.source.peek_byte() {
+            let sign = matches!(self.source.peek_byte_at(1), Some(b'+' | b'-')) as usize;
+            let digits = self.is_digit_at(1 + sign);
+            self.source.advance(1 + sign);
+            if !digits {
+                return self.error(Code::MissingDigits, "missing digits in the exponent");
+            }
+            is_float = true;
+            self.skip_digits(10);
+        }
+
+        let text = self.source.get_slice(start, self.source.pos()).replace('_', "");
+
+        if is_float {
+            self.token.kind = TokenKind::FloatLiteral;
+            self.token.value = text.parse().map(TokenValue::Float).unwrap_or_default();
+        } else {
+            self.int_literal(text.parse().ok());
+        }
+    }
+
+    /// Finishes an integer literal, which is an error if its value didn't fit in an `i64`.
+    fn int_literal(&mut self, value: Option<i64>) {
+        match value {
+            Some(value) => {
+                self.token.kind = TokenKind::IntLiteral;
+                self.token.value = TokenValue::Int(value);
+            }
+            None => self.error(Code::IntegerTooLarge, "integer literal is too large"),
+        }
+    }
+
+    /// Skips digits of the given radix and `_` separators.
+    fn skip_digits(&mut self, radix: u32) {
+        while let Some(byte) = self.source.peek_byte() {
+            if (byte as char).is_digit(radix) || byte == b'_' {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Whether the byte `offset` bytes after the current position is a decimal digit.
+    pub(super) fn is_digit_at(&self, offset: usize) -> bool {
+        self.source.peek_byte_at(offset).is_some_and(|byte| byte.is_ascii_digit())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::lex;
+    use crate::token::{TokenKind, TokenValue};
+    use crate::Lexer;
+    use oxc_allocator::Allocator;
+
+    fn value_of(source: &str) -> (TokenKind, TokenValue<'static>) {
+        let alloc = Allocator::default();
+        let token = Lexer::new(&alloc, source).next_token();
+
+        assert_eq!(token.span.end, source.len(), "`{source}` wasn't lexed as a single token");
+        let value = match token.value {
+            TokenValue::Int(value) => TokenValue::Int(value),
+            TokenValue::Float(value) => TokenValue::Float(value),
+            _ => TokenValue::None,
+        };
+        (token.kind, value)
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(value_of("0"), (TokenKind::IntLiteral, TokenValue::Int(0)));
+        assert_eq!(value_of("42"), (TokenKind::IntLiteral, TokenValue::Int(42)));
+        assert_eq!(value_of("1_000_000"), (TokenKind::IntLiteral, TokenValue::Int(1_000_000)));
+        assert_eq!(value_of("0x1F"), (TokenKind::IntLiteral, TokenValue::Int(31)));
+        assert_eq!(value_of("0xff_ff"), (TokenKind::IntLiteral, TokenValue::Int(0xffff)));
+        assert_eq!(value_of("0o17"), (TokenKind::IntLiteral, TokenValue::Int(15)));
+        assert_eq!(value_of("0b1010"), (TokenKind::IntLiteral, TokenValue::Int(10)));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(value_of("1.5"), (TokenKind::FloatLiteral, TokenValue::Float(1.5)));
+        assert_eq!(value_of(".5"), (TokenKind::FloatLiteral, TokenValue::Float(0.5)));
+        assert_eq!(value_of("1e3"), (TokenKind::FloatLiteral, TokenValue::Float(1000.0)));
+        assert_eq!(value_of("2.5E-3"), (TokenKind::FloatLiteral, TokenValue::Float(0.0025)));
+        assert_eq!(value_of("1_0.2_5e+1"), (TokenKind::FloatLiteral, TokenValue::Float(102.5)));
+    }
+
+    #[test]
+    fn malformed_integers_are_errors() {
+        assert_eq!(value_of("9223372036854775808"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(value_of("0x1_0000_0000_0000_0000"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(value_of("0x"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(value_of("0b_"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(value_of("0x_FF"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(value_of("0b10_"), (TokenKind::IntLiteral, TokenValue::Int(2)));
+    }
+
+    #[test]
+    fn exponents_without_digits_are_errors() {
+        assert_eq!(value_of("1e"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(value_of("2.5E-"), (TokenKind::Error, TokenValue::None));
+        assert_eq!(lex("1e+x"), vec![(TokenKind::Error, "1e+"), (TokenKind::Identifier, "x")]);
+    }
+
+    #[test]
+    fn dot_without_fraction_ends_the_literal() {
+        assert_eq!(
+            lex("5.a"),
+            vec![(TokenKind::IntLiteral, "5"), (TokenKind::Dot, "."), (TokenKind::Identifier, "a")]
+        );
+    }
+}
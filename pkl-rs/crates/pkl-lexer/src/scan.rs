@@ -0,0 +1,87 @@
+//! Fast loops for the runs of bytes that the lexer skips over: whitespace, the rest of an identifier, and the text
+//! of strings and comments.
+//!
+//! Runs of whitespace and identifier characters are skipped with a table of the class of each byte, which is a
+//! single lookup per byte rather than a chain of comparisons. Strings and comments are scanned for the few bytes that
+//! can end them with `memchr`, which compares many bytes at once with the SIMD instructions of the target.
+//!
+//! Every function returns a length in bytes that ends before an ASCII byte or at the end of the text, so it always
+//! lands on a character boundary.
+
+/// Space, tab, and form feed
+const WHITESPACE: u8 = 1;
+/// ASCII letters, digits, `_`, and `$`
+const IDENTIFIER: u8 = 2;
+
+static CLASSES: [u8; 256] = {
+    let mut classes = [0; 256];
+    classes[b' ' as usize] = WHITESPACE;
+    classes[b'\t' as usize] = WHITESPACE;
+    classes[0x0c] = WHITESPACE;
+    let mut byte = 0;
+    while byte < 128 {
+        if (byte as u8).is_ascii_alphanumeric() || byte == b'_' as usize || byte == b'$' as usize {
+            classes[byte] = IDENTIFIER;
+        }
+        byte += 1;
+    }
+    classes
+};
+
+fn skip_class(text: &[u8], class: u8) -> usize {
+    text.iter().position(|&byte| CLASSES[byte as usize] & class == 0).unwrap_or(text.len())
+}
+
+/// The length of the run of spaces, tabs, and form feeds at the start of `text`.
+pub(crate) fn whitespace(text: &[u8]) -> usize {
+    skip_class(text, WHITESPACE)
+}
+
+/// The length of the run of ASCII identifier characters at the start of `text`, which stops at any non-ASCII byte.
+pub(crate) fn identifier(text: &[u8]) -> usize {
+    skip_class(text, IDENTIFIER)
+}
+
+/// The length of the text up to the next line terminator, or all of it.
+pub(crate) fn line(text: &[u8]) -> usize {
+    memchr::memchr2(b'\n', b'\r', text).unwrap_or(text.len())
+}
+
+/// The length of the text of a string up to the next byte that may end it or start an escape: a `"`, a `\`, and for
+/// a single-line string, a line terminator.
+pub(crate) fn string_text(text: &[u8], multiline: bool) -> usize {
+    if multiline {
+        return memchr::memchr2(b'"', b'\\', text).unwrap_or(text.len());
+    }
+    let end = memchr::memchr3(b'"', b'\\', b'\n', text).unwrap_or(text.len());
+    // a lone `\r` ends a line too, and is rare enough to look for separately
+    memchr::memchr(b'\r', &text[..end]).unwrap_or(end)
+}
+
+/// The length of the text of a block comment up to the next `/` or `*`, which may open or close a comment.
+pub(crate) fn comment_text(text: &[u8]) -> usize {
+    memchr::memchr2(b'/', b'*', text).unwrap_or(text.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn runs() {
+        assert_eq!(whitespace(b" \t\x0c x"), 4);
+        assert_eq!(whitespace(b"  "), 2);
+        assert_eq!(identifier(b"foo_$1 = 2"), 6);
+        assert_eq!(identifier("größe".as_bytes()), 2);
+        assert_eq!(line(b"// a\r\nb"), 4);
+        assert_eq!(comment_text(b"a b */"), 4);
+    }
+
+    #[test]
+    fn string_texts() {
+        assert_eq!(string_text(b"abc\\(x)\"", false), 3);
+        assert_eq!(string_text(b"abc\rdef\"", false), 3);
+        assert_eq!(string_text(b"abc\ndef\"", true), 7);
+        assert_eq!(string_text(b"abc", false), 3);
+    }
+}
@@ -1,23 +1,22 @@
 /// `Source` contains the source code of the program, and is consumed the by lexer
 /// The reason it's only stored in the lexer is because the lexer is the only consumer
 ///
-/// `Source` also provides a pointer to the current position in the source, which is used by the lexer for fast reads
+/// `Source` is a cursor over the source text: the text itself plus the byte offset of the current position. All reads
+/// are bounds-checked and return `None` past the end, so the lexer can look ahead freely without any unsafe code.
 ///
-/// # Under the hood (of the hood?)
-/// * `start` is a pointer to the start of the source
-/// * `end` is a pointer to the end of the source
-/// * `ptr` is a pointer to the current position in the source
-pub struct Source {
-    /// Pointer to
-    pub(crate) start: *const u8,
-    /// Pointer to the end of the source
-    pub(crate) end: *const u8,
-    /// Pointer to the current position in the source
-    pub(crate) ptr: *const u8,
+/// # Invariants
+/// * the position never exceeds the length of the text
+/// * the position always sits on a UTF-8 character boundary: a position inside a character is moved past it
+#[derive(Debug, Clone)]
+pub struct Source<'a> {
+    /// The whole source text
+    text: &'a str,
+    /// Byte offset of the current position
+    pos: usize,
 }
 
-impl Source {
-    /// Creates a new `Source` instance.
+impl<'a> Source<'a> {
+    /// Creates a new `Source` instance, positioned at the start of `source`.
     ///
     /// # Parameters
     ///
@@ -26,108 +25,92 @@ impl Source {
     /// # Returns
     ///
     /// A `Source` instance.
-    ///
-    /// # Safety
-    ///
-    /// This function is safe to call as it does not perform any unsafe operations.
-    /// However, the returned `Source` instance contains raw pointers that should be handled with care.
-    /// Misuse of these pointers can lead to undefined behavior.
-    pub fn new<'a>(source: &'a str) -> Self {
-        // create a pointer to the initial start of the source
-        let start = source.as_ptr();
-
-        // create a pointer to the end of the source
-        let end = unsafe { start.add(source.len()) };
-
-        // Return the `Source` instance
-        Source {
-            start,
-            end,
-            ptr: start,
-        }
+    pub fn new(source: &'a str) -> Self {
+        Source { text: source, pos: 0 }
     }
 
-
-
     /// Returns the entire source code as a string.
-    ///
-    /// # Safety
-    ///
-    /// This function is unsafe due to unchecked operations. Ensure the following:
-    /// * `Source` instance was created with a valid string.
-    /// * Pointers `start`, `end`, and `ptr` are untouched.
-    /// * Original string that `Source` was created with is intact.
-    ///
-    /// # Returns
-    ///
-    /// A string slice representing the entire source code.
-    pub unsafe fn get_whole_source<'a>(&self) -> &'a str {
-        // Calculate the length of the source
-        let len = self.end as usize - self.start as usize;
-        // Create a slice from the raw parts
-        std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.start, len))
+    pub fn get_whole_source(&self) -> &'a str {
+        self.text
     }
 
-    pub fn get_current_pos(&self) -> usize {
-        self.ptr as usize - self.start as usize
+    /// Length of the source in bytes
+    pub fn len(&self) -> usize {
+        self.text.len()
     }
 
-    pub fn advance(&mut self, index: usize) -> u8 {
-        let value = unsafe { *self.start.add(index) };
-        self.ptr = unsafe { self.start.add(index) };
-        value
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
     }
 
-    pub fn add(&mut self, index: usize) -> u8 {
-        unsafe { *self.ptr.add(index) }
+    /// Byte offset of the current position
+    pub fn pos(&self) -> usize {
+        self.pos
     }
 
-    pub fn current(&self) -> u8 {
-        unsafe { *self.ptr.as_ref().unwrap() }
+    /// Moves the current position to the byte offset `pos`, which should lie on a character boundary. Positions
+    /// past the end are clamped to the end, and positions inside a multi-byte character are moved to the end of the
+    /// character, so that the lexer always makes progress.
+    pub fn set_pos(&mut self, pos: usize) {
+        let mut pos = pos.min(self.text.len());
+        while !self.text.is_char_boundary(pos) {
+            pos += 1;
+        }
+        self.pos = pos;
+    }
+
+    /// Moves the current position forward by `bytes`, stopping at the end of the source.
+    pub fn advance(&mut self, bytes: usize) {
+        self.set_pos(self.pos + bytes);
     }
 
     pub fn is_at_end(&self) -> bool {
-        self.ptr >= self.end
+        self.pos >= self.text.len()
     }
 
-    pub fn peek(&self) -> Option<u8> {
-        if self.ptr < self.end {
-            let value = unsafe { *self.ptr.offset(1).as_ref().unwrap() };
+    /// Returns the byte at `index` (relative to the start of the source), or `None` if `index` is out of bounds.
+    pub fn byte_at(&self, index: usize) -> Option<u8> {
+        self.text.as_bytes().get(index).copied()
+    }
 
-            Some(value)
-        } else {
-            None
-        }
+    /// Returns the byte at the current position.
+    pub fn peek_byte(&self) -> Option<u8> {
+        self.byte_at(self.pos)
     }
 
-    pub fn get_slice<'a>(&self, start: usize, end: usize) -> &'a str {
-        let len = end - start;
-        unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.start.add(start), len)) }
+    /// Returns the byte `offset` bytes after the current position.
+    pub fn peek_byte_at(&self, offset: usize) -> Option<u8> {
+        self.byte_at(self.pos + offset)
     }
 
-    /// Get offset of the current position
-    pub fn offset(&self) -> u32 {
-        self.ptr as u32 - self.start as u32
+    /// Returns the text between the byte offsets `start` and `end`, or an empty string if the range is out of
+    /// bounds or doesn't fall on character boundaries.
+    pub fn get_slice(&self, start: usize, end: usize) -> &'a str {
+        self.text.get(start..end).unwrap_or_default()
     }
 
+    /// The part of the source that hasn't been consumed yet.
+    pub fn remaining(&self) -> &'a str {
+        self.text.get(self.pos..).unwrap_or_default()
+    }
 
+    /// Decodes and consumes the next character, advancing by its full UTF-8 length.
     pub fn next_char(&mut self) -> Option<char> {
-        if self.ptr < self.end {
-            let value = unsafe { *self.ptr.as_ref().unwrap() };
-            self.ptr = unsafe { self.ptr.add(1) };
-            Some(value as char)
-        } else {
-            None
-        }
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
     }
 
+    /// Decodes the next character without consuming it.
     pub fn peek_char(&self) -> Option<char> {
-        if self.ptr < self.end {
-            let value = unsafe { *self.ptr.as_ref().unwrap() };
-            Some(value as char)
-        } else {
-            None
-        }
+        self.remaining().chars().next()
+    }
+
+    /// Decodes the character after the next one without consuming anything.
+    pub fn peek_char2(&self) -> Option<char> {
+        let mut chars = self.remaining().chars();
+        chars.next();
+        chars.next()
     }
 }
 
@@ -136,10 +119,64 @@ mod test {
     use super::*;
 
     #[test]
-    fn new_source_ptr_works() {
+    fn new_source_starts_at_the_beginning() {
         let prgm = "123";
         let src = Source::new(prgm);
 
-        assert_eq!(prgm.as_ptr(), src.ptr);
+        assert_eq!(prgm.as_ptr(), src.get_whole_source().as_ptr());
+        assert_eq!(src.pos(), 0);
+    }
+
+    #[test]
+    fn reads_past_the_end_are_none() {
+        let mut src = Source::new("ab");
+
+        assert_eq!(src.peek_byte_at(1), Some(b'b'));
+        assert_eq!(src.peek_byte_at(2), None);
+
+        src.advance(5);
+        assert!(src.is_at_end());
+        assert_eq!(src.peek_byte(), None);
+        assert_eq!(src.peek_char(), None);
+        assert_eq!(src.remaining(), "");
+    }
+
+    #[test]
+    fn positions_and_slices_stay_in_bounds() {
+        let mut src = Source::new("a名b");
+
+        src.set_pos(2);
+        assert_eq!(src.pos(), 4);
+        assert_eq!(src.peek_char(), Some('b'));
+        assert_eq!(src.get_slice(0, 2), "");
+        assert_eq!(src.get_slice(3, 9), "");
+        assert_eq!(src.get_slice(1, 4), "名");
+    }
+
+    #[test]
+    fn next_char_decodes_multi_byte_characters() {
+        let mut src = Source::new("aé名😀");
+
+        assert_eq!(src.next_char(), Some('a'));
+        assert_eq!(src.next_char(), Some('é'));
+        assert_eq!(src.pos(), 3);
+        assert_eq!(src.next_char(), Some('名'));
+        assert_eq!(src.next_char(), Some('😀'));
+        assert_eq!(src.pos(), 10);
+        assert_eq!(src.next_char(), None);
+        assert!(src.is_at_end());
+    }
+
+    #[test]
+    fn peeking_does_not_consume() {
+        let mut src = Source::new("名😀");
+
+        assert_eq!(src.peek_char(), Some('名'));
+        assert_eq!(src.peek_char2(), Some('😀'));
+        assert_eq!(src.pos(), 0);
+
+        src.next_char();
+        assert_eq!(src.peek_char(), Some('😀'));
+        assert_eq!(src.peek_char2(), None);
     }
 }
@@ -0,0 +1,186 @@
+//! Bookkeeping for the source files of a program, so that spans from different modules can be told apart.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::line_index::{LineCol, LineIndex, PositionEncoding};
+use crate::token::Span;
+
+/// Identifies a file in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+/// A [`Span`] along with the file it is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileSpan {
+    pub file: FileId,
+    pub span: Span,
+}
+
+impl FileSpan {
+    pub fn new(file: FileId, span: Span) -> Self {
+        FileSpan { file, span }
+    }
+}
+
+/// A file added to a [`SourceMap`].
+#[derive(Debug)]
+pub struct SourceFile {
+    path: PathBuf,
+    text: String,
+    line_index: LineIndex,
+}
+
+impl SourceFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+}
+
+/// The contents of every file that takes part in an evaluation, keyed by path.
+///
+/// Paths are interned: adding the same path twice gives back the same [`FileId`], with the contents replaced (e.g.
+/// when an editor changes an open file).
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    ids: HashMap<PathBuf, FileId>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    /// Adds a file, or replaces the contents of the file that was added with the same path.
+    pub fn add(&mut self, path: impl Into<PathBuf>, text: impl Into<String>) -> FileId {
+        let path = path.into();
+        let text = text.into();
+        let line_index = LineIndex::new(&text);
+
+        if let Some(&id) = self.ids.get(&path) {
+            let file = &mut self.files[id.0 as usize];
+            file.text = text;
+            file.line_index = line_index;
+            return id;
+        }
+
+        let id = FileId(self.files.len() as u32);
+        self.ids.insert(path.clone(), id);
+        self.files.push(SourceFile { path, text, line_index });
+        id
+    }
+
+    /// The id of the file added with `path`, if any.
+    pub fn file_id(&self, path: &Path) -> Option<FileId> {
+        self.ids.get(path).copied()
+    }
+
+    /// Returns the file with the given id.
+    ///
+    /// # Panics
+    ///
+    /// If `id` belongs to a different `SourceMap`.
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files.iter().enumerate().map(|(index, file)| (FileId(index as u32), file))
+    }
+
+    /// The source text a span refers to.
+    pub fn text(&self, span: FileSpan) -> &str {
+        &self.file(span.file).text[span.span.start..span.span.end]
+    }
+
+    /// The position where a span starts, with the column in bytes.
+    pub fn line_col(&self, span: FileSpan) -> LineCol {
+        self.file(span.file).line_index.line_col(span.span.start)
+    }
+
+    /// The positions where a span starts and ends, with columns in the units of `encoding`.
+    pub fn line_cols_in(&self, span: FileSpan, encoding: PositionEncoding) -> (LineCol, LineCol) {
+        self.file(span.file).line_index.span_line_cols_in(span.span, encoding)
+    }
+
+    /// The span of a file between two positions with columns in the units of `encoding`, or `None` if either line
+    /// doesn't exist or the end comes before the start.
+    pub fn span_in(&self, file: FileId, start: LineCol, end: LineCol, encoding: PositionEncoding) -> Option<FileSpan> {
+        let index = &self.file(file).line_index;
+        let (start, end) = (index.offset_in(start, encoding)?, index.offset_in(end, encoding)?);
+        (start <= end).then(|| FileSpan::new(file, Span::new(start, end)))
+    }
+
+    /// Formats where a span starts as `path:line:col`, the way it is shown in diagnostics.
+    pub fn location(&self, span: FileSpan) -> Location<'_> {
+        Location {
+            path: &self.file(span.file).path,
+            position: self.line_col(span),
+        }
+    }
+}
+
+/// The starting position of a [`FileSpan`], displayed as `path:line:col`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'a> {
+    pub path: &'a Path,
+    pub position: LineCol,
+}
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn paths_are_interned() {
+        let mut map = SourceMap::new();
+        let a = map.add("a.pkl", "x = 1");
+        let b = map.add("b.pkl", "y = 2");
+
+        assert_ne!(a, b);
+        assert_eq!(map.add("a.pkl", "x = 3"), a);
+        assert_eq!(map.file(a).text(), "x = 3");
+        assert_eq!(map.file_id(Path::new("b.pkl")), Some(b));
+        assert_eq!(map.files().count(), 2);
+    }
+
+    #[test]
+    fn locations_name_the_file() {
+        let mut map = SourceMap::new();
+        map.add("a.pkl", "x = 1");
+        let b = map.add("dir/b.pkl", "foo {\n  bar = 2\n}");
+        let span = FileSpan::new(b, Span::new(8, 11));
+
+        assert_eq!(map.text(span), "bar");
+        assert_eq!(map.location(span).to_string(), "dir/b.pkl:2:3");
+    }
+
+    #[test]
+    fn positions_in_encodings() {
+        let mut map = SourceMap::new();
+        let file = map.add("a.pkl", "s = \"😀\" + x");
+        let span = FileSpan::new(file, Span::new(13, 14));
+        let (start, end) = map.line_cols_in(span, PositionEncoding::Utf16);
+        assert_eq!((start.col, end.col), (11, 12));
+        assert_eq!(map.span_in(file, start, end, PositionEncoding::Utf16), Some(span));
+        let (start, end) = map.line_cols_in(span, PositionEncoding::Utf32);
+        assert_eq!((start.col, end.col), (10, 11));
+        assert_eq!(map.span_in(file, end, start, PositionEncoding::Utf32), None);
+    }
+}
@@ -0,0 +1,414 @@
+use pkl_diagnostics::Code;
+
+use crate::diagnostic::Diagnostic;
+use crate::literal::{strip_multiline_indent, unescape, StringDelimiter};
+use crate::mode::LexMode;
+use crate::scan;
+use crate::token::{Span, TokenKind, TokenValue};
+use crate::Lexer;
+
+/// Where scanning a run of string text stopped.
+enum StringBodyEnd {
+    /// The closing delimiter was found (and consumed)
+    Closed,
+    /// A `\(` was found; it is left for the next token
+    Interpolation,
+    /// The line (for single-line strings) or the input ended before the string was closed
+    Unterminated,
+}
+
+impl<'a> Lexer<'a> {
+    /// Scans a string literal, starting at its opening delimiter: `"` or `"""`, optionally preceded by pounds.
+    ///
+    /// Escape sequences are consumed as a unit so that an escaped delimiter (`\"`) doesn't terminate the literal.
+    /// In a string delimited with pounds, only escapes, interpolations and closing quotes followed by the same number
+    /// of pounds are special.
+    /// Single-line strings can't contain line breaks, so an unterminated literal stops at the end of its line.
+    /// Multiline strings may span any number of lines and are only closed by `"""`. Either way, a string that isn't
+    /// closed ends in a [`TokenKind::Error`] token.
+    ///
+    /// A string without interpolations becomes a single [`TokenKind::StringLiteral`] covering both delimiters.
+    /// Otherwise this emits [`TokenKind::StringStart`] and enters [`LexMode::String`], so that the following calls to
+    /// `next_token` produce the interpolations and the remaining parts of the string.
+    ///
+    /// The content of each token, with escapes replaced, is stored as its [`TokenValue::String`]. The exception are
+    /// the parts of interpolated multiline strings, whose indentation can't be stripped until the whole string has been
+    /// lexed; see [`crate::literal::strip_multiline_indent`].
+    pub(super) fn string_literal_handler(&mut self) {
+        let start = self.source.pos();
+        let pounds = self.count_pounds(0);
+        let multiline =
+            self.source.peek_byte_at(pounds + 1) == Some(b'"') && self.source.peek_byte_at(pounds + 2) == Some(b'"');
+        let delimiter = StringDelimiter { pounds, multiline };
+
+        self.source.advance(delimiter.width());
+        let body_start = self.source.pos();
+
+        match self.string_body_handler(delimiter) {
+            StringBodyEnd::Closed => {
+                let raw = self.source.get_slice(body_start, self.source.pos() - delimiter.width());
+                self.token.kind = TokenKind::StringLiteral;
+
+                if !multiline {
+                    self.token.value = TokenValue::String(self.intern(unescape(raw, pounds)));
+                } else {
+                    match strip_multiline_indent(&[raw]) {
+                        Ok(content) => {
+                            let content = unescape(&content[0], pounds).into_owned();
+                            self.token.value = TokenValue::String(self.alloc.alloc_str(&content));
+                        }
+                        Err(error) => {
+                            let span = Span::new(start, self.source.pos());
+                            let diagnostic = Diagnostic::new(span, error.to_string());
+                            self.diagnostics.push(diagnostic.with_code(Code::InvalidMultilineString));
+                        }
+                    }
+                }
+            }
+            StringBodyEnd::Interpolation => {
+                self.modes.push(LexMode::String(delimiter));
+                self.token.kind = TokenKind::StringStart;
+                if !multiline {
+                    let raw = self.source.get_slice(body_start, self.source.pos());
+                    self.token.value = TokenValue::String(self.intern(unescape(raw, pounds)));
+                }
+            }
+            StringBodyEnd::Unterminated => self.error(Code::UnterminatedString, "unterminated string literal"),
+        }
+    }
+
+    /// Lexes the next token of a string that is being continued after an interpolation (or right before one).
+    pub(super) fn string_continuation_handler(&mut self, delimiter: StringDelimiter) {
+        if self.at_interpolation_start(delimiter) {
+            self.source.advance(delimiter.pounds + 2);
+            self.modes.push(LexMode::Interpolation { depth: 0 });
+            self.token.kind = TokenKind::InterpolationStart;
+            return;
+        }
+
+        let start = self.source.pos();
+
+        let (kind, end) = match self.string_body_handler(delimiter) {
+            StringBodyEnd::Closed => {
+                self.modes.pop();
+                (TokenKind::StringEnd, self.source.pos() - delimiter.width())
+            }
+            StringBodyEnd::Interpolation => (TokenKind::StringPart, self.source.pos()),
+            StringBodyEnd::Unterminated => {
+                self.modes.pop();
+                self.error(Code::UnterminatedString, "unterminated string literal");
+                return;
+            }
+        };
+
+        self.token.kind = kind;
+        if !delimiter.multiline {
+            let raw = self.source.get_slice(start, end);
+            self.token.value = TokenValue::String(self.intern(unescape(raw, delimiter.pounds)));
+        }
+    }
+
+    /// Scans string text until the closing delimiter, an interpolation, or (for single-line strings) the end of the
+    /// line.
+    fn string_body_handler(&mut self, delimiter: StringDelimiter) -> StringBodyEnd {
+        while let Some(byte) = self.source.peek_byte() {
+            match byte {
+                b'"' if self.at_closing_delimiter(delimiter) => {
+                    self.source.advance(delimiter.width());
+                    return StringBodyEnd::Closed;
+                }
+                b'\\' if self.at_interpolation_start(delimiter) => return StringBodyEnd::Interpolation,
+                b'\\' if self.count_pounds(1) >= delimiter.pounds => {
+                    self.escape_sequence_handler(delimiter)
+                }
+                b'\n' | b'\r' if !delimiter.multiline => return StringBodyEnd::Unterminated,
+                // a `"` or `\` that doesn't end the text is text itself
+                _ => {
+                    let text = scan::string_text(self.source.remaining().as_bytes(), delimiter.multiline);
+                    self.source.advance(text.max(1));
+                }
+            }
+        }
+
+        StringBodyEnd::Unterminated
+    }
+
+    fn at_closing_delimiter(&self, delimiter: StringDelimiter) -> bool {
+        let quotes = delimiter.quotes();
+
+        (0..quotes).all(|offset| self.source.peek_byte_at(offset) == Some(b'"'))
+            && self.count_pounds(quotes) >= delimiter.pounds
+    }
+
+    fn at_interpolation_start(&self, delimiter: StringDelimiter) -> bool {
+        self.source.peek_byte() == Some(b'\\')
+            && self.count_pounds(1) >= delimiter.pounds
+            && self.source.peek_byte_at(1 + delimiter.pounds) == Some(b'(')
+    }
+
+    /// Number of consecutive `#` starting `offset` bytes after the current position.
+    pub(super) fn count_pounds(&self, offset: usize) -> usize {
+        let mut count = 0;
+        while self.source.peek_byte_at(offset + count) == Some(b'#') {
+            count += 1;
+        }
+        count
+    }
+
+    /// Scans an escape sequence starting at the `\` (followed by the delimiter's pounds).
+    ///
+    /// Supported escapes are `\n`, `\r`, `\t`, `\"`, `\\` and `\u{...}` with one or more hex digits. Anything else is
+    /// reported, but doesn't end the string.
+    fn escape_sequence_handler(&mut self, delimiter: StringDelimiter) {
+        let start = self.source.pos();
+        self.source.advance(1 + delimiter.pounds);
+
+        let valid = match self.source.peek_byte() {
+            Some(b'n' | b'r' | b't' | b'"' | b'\\') => {
+                self.bump();
+                true
+            }
+            Some(b'u') => {
+                self.bump();
+                self.eat(b'{') && {
+                    let digits_start = self.source.pos();
+                    while self.source.peek_byte().is_some_and(|byte| byte.is_ascii_hexdigit()) {
+                        self.bump();
+                    }
+                    let digits = self.source.get_slice(digits_start, self.source.pos());
+                    self.eat(b'}') && u32::from_str_radix(digits, 16).ok().and_then(char::from_u32).is_some()
+                }
+            }
+            // a trailing backslash can't escape a line break or the end of input
+            Some(b'\n' | b'\r') | None => false,
+            Some(_) => {
+                self.bump();
+                false
+            }
+        };
+
+        if !valid {
+            let span = Span::new(start, self.source.pos());
+            self.diagnostics.push(Diagnostic::new(span, "invalid escape sequence").with_code(Code::InvalidEscape));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::lex;
+    use crate::token::{Span, TokenKind, TokenValue};
+    use crate::Lexer;
+    use oxc_allocator::Allocator;
+
+    #[test]
+    fn simple_string_literal() {
+        assert_eq!(lex(r#""hello""#), vec![(TokenKind::StringLiteral, r#""hello""#)]);
+    }
+
+    #[test]
+    fn escaped_quotes_do_not_terminate() {
+        let source = r#""say \"hi\" \\" + "\t\n\r""#;
+
+        assert_eq!(
+            lex(source),
+            vec![
+                (TokenKind::StringLiteral, r#""say \"hi\" \\""#),
+                (TokenKind::Plus, "+"),
+                (TokenKind::StringLiteral, r#""\t\n\r""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        let source = r#""\u{1F600} \u{e9}""#;
+
+        assert_eq!(lex(source), vec![(TokenKind::StringLiteral, source)]);
+    }
+
+    #[test]
+    fn non_ascii_contents() {
+        let source = "\"größe 名前\"";
+
+        assert_eq!(lex(source), vec![(TokenKind::StringLiteral, source)]);
+    }
+
+    #[test]
+    fn interpolated_string() {
+        assert_eq!(
+            lex(r#""hello \(a)!""#),
+            vec![
+                (TokenKind::StringStart, r#""hello "#),
+                (TokenKind::InterpolationStart, r"\("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::StringEnd, r#"!""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn parens_inside_interpolation() {
+        assert_eq!(
+            lex(r#""\((a) + (a))\(a)""#),
+            vec![
+                (TokenKind::StringStart, r#"""#),
+                (TokenKind::InterpolationStart, r"\("),
+                (TokenKind::LParen, "("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::Plus, "+"),
+                (TokenKind::LParen, "("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::InterpolationStart, r"\("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::StringEnd, r#"""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_interpolation() {
+        assert_eq!(
+            lex(r#""a \("b \(a) c") d""#),
+            vec![
+                (TokenKind::StringStart, r#""a "#),
+                (TokenKind::InterpolationStart, r"\("),
+                (TokenKind::StringStart, r#""b "#),
+                (TokenKind::InterpolationStart, r"\("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::StringEnd, r#" c""#),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::StringEnd, r#" d""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_interpolation_is_not_special() {
+        let source = r#""\\(a)""#;
+
+        assert_eq!(lex(source), vec![(TokenKind::StringLiteral, source)]);
+    }
+
+    #[test]
+    fn multiline_string() {
+        let source = "\"\"\"\n  a \"quoted\" \"\"\n  b\n  \"\"\" + \"\"";
+
+        assert_eq!(
+            lex(source),
+            vec![
+                (TokenKind::StringLiteral, "\"\"\"\n  a \"quoted\" \"\"\n  b\n  \"\"\""),
+                (TokenKind::Plus, "+"),
+                (TokenKind::StringLiteral, "\"\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn interpolated_multiline_string() {
+        let source = "\"\"\"\n  \\(a)\n  \"\"\"";
+
+        assert_eq!(
+            lex(source),
+            vec![
+                (TokenKind::StringStart, "\"\"\"\n  "),
+                (TokenKind::InterpolationStart, r"\("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::StringEnd, "\n  \"\"\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn pound_delimited_strings() {
+        let source = r###"#"a "quoted" \n \(a) \#t"# + ##"""
+  "#
+  """##"###;
+
+        assert_eq!(
+            lex(source),
+            vec![
+                (TokenKind::StringLiteral, r##"#"a "quoted" \n \(a) \#t"#"##),
+                (TokenKind::Plus, "+"),
+                (TokenKind::StringLiteral, "##\"\"\"\n  \"#\n  \"\"\"##"),
+            ]
+        );
+    }
+
+    #[test]
+    fn pound_delimited_interpolation() {
+        assert_eq!(
+            lex(r##"#"\(b) \#(a)"#"##),
+            vec![
+                (TokenKind::StringStart, r##"#"\(b) "##),
+                (TokenKind::InterpolationStart, r"\#("),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::StringEnd, "\"#"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_stops_at_line_end() {
+        assert_eq!(lex("\"abc\n"), vec![(TokenKind::Error, "\"abc")]);
+        assert_eq!(
+            lex("\"a\\(b) c\n"),
+            vec![
+                (TokenKind::StringStart, "\"a"),
+                (TokenKind::InterpolationStart, "\\("),
+                (TokenKind::Identifier, "b"),
+                (TokenKind::InterpolationEnd, ")"),
+                (TokenKind::Error, " c"),
+            ]
+        );
+    }
+
+    fn values<'a>(alloc: &'a Allocator, source: &'a str) -> Vec<TokenValue<'a>> {
+        Lexer::new(alloc, source)
+            .filter(|token| token.kind != TokenKind::Eof)
+            .map(|token| token.value)
+            .collect()
+    }
+
+    #[test]
+    fn string_values_are_unescaped() {
+        let alloc = Allocator::default();
+        let values = |source| values(&alloc, source);
+
+        assert_eq!(values(r#""a\tb""#), vec![TokenValue::String("a\tb")]);
+        assert_eq!(values(r##"#"\t\#t"#"##), vec![TokenValue::String("\\t\t")]);
+        assert_eq!(
+            values(r#""x\(1)\n\(2)y""#),
+            vec![
+                TokenValue::String("x"),
+                TokenValue::None,
+                TokenValue::Int(1),
+                TokenValue::None,
+                TokenValue::String("\n"),
+                TokenValue::None,
+                TokenValue::Int(2),
+                TokenValue::None,
+                TokenValue::String("y"),
+            ]
+        );
+        assert_eq!(values("\"\"\"\n  a\\u{41}\n  \"\"\""), vec![TokenValue::String("aA")]);
+    }
+
+    #[test]
+    fn invalid_escapes_are_reported() {
+        let alloc = Allocator::default();
+        let mut lexer = Lexer::new(&alloc, r#""\q \u{110000} \u{41}""#);
+
+        assert_eq!(lexer.next_token().kind, TokenKind::StringLiteral);
+        let diagnostics: Vec<_> = lexer.diagnostics().iter().map(|d| d.span).collect();
+        assert_eq!(diagnostics, vec![Span::new(1, 3), Span::new(4, 14)]);
+    }
+}
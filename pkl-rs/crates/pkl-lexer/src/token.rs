@@ -1,30 +1,242 @@
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// The smallest span containing both `self` and `other`.
+    pub fn cover(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TokenKind {
+    /// `+`
     Plus,
+    /// `+=`
     PlusEq,
+    /// `-`
+    Minus,
+    /// `*`
+    Star,
+    /// `/`
+    Slash,
+    /// `~/`, truncating division
+    TildeSlash,
+    /// `%`
+    Percent,
+    /// `**`
+    StarStar,
+    /// `==`
+    EqEq,
+    /// `!=`
+    NotEq,
+    /// `<`
+    Lt,
+    /// `<=`
+    LtEq,
+    /// `>`
+    Gt,
+    /// `>=`
+    GtEq,
+    /// `&&`
+    AndAnd,
+    /// `||`
+    OrOr,
+    /// `!`
+    Bang,
+    /// `??`
+    QuestionQuestion,
+    /// `?.`
+    QuestionDot,
+    /// `!!`
+    BangBang,
+    /// `|>`
+    PipeGt,
+    /// `->`
+    Arrow,
+    /// `=`
+    Eq,
+    /// `|`, used in union types
+    Pipe,
+    /// `[`
+    LBracket,
+    /// `]`
+    RBracket,
+    /// `{`
+    LBrace,
+    /// `}`
+    RBrace,
+    /// `,`
+    Comma,
+    /// `.`
+    Dot,
+    /// `:`
+    Colon,
+    /// `;`
+    Semicolon,
+    /// `?`, used in nullable types
+    Question,
+    /// `...`
+    Spread,
+    /// `...?`
+    SpreadQuestion,
+    /// `@`, which starts an annotation
+    At,
+
+    /// An identifier, either plain (`foo`) or quoted with backticks (`` `foo bar` ``)
     Identifier,
 
+    // Keywords
+    Abstract,
+    Amends,
+    As,
+    Class,
+    Const,
+    Else,
+    Extends,
+    External,
+    False,
+    Fixed,
+    For,
+    Function,
+    Hidden,
+    If,
+    Import,
+    /// `import*`
+    ImportStar,
+    In,
+    Is,
+    Let,
+    Local,
+    Module,
+    New,
+    Nothing,
+    Null,
+    Open,
+    Out,
+    Outer,
+    Read,
+    /// `read*`
+    ReadStar,
+    /// `read?`
+    ReadQuestion,
+    Super,
+    This,
+    Throw,
+    Trace,
+    True,
+    Typealias,
+    Unknown,
+    When,
+    /// A word reserved for future use, like `switch` or `record`, which can't be used as a plain identifier
+    Reserved,
+
+    /// A double-quoted string literal without interpolations, including both delimiters
+    StringLiteral,
+    /// The opening delimiter of an interpolated string and the text up to its first interpolation, e.g. `"a ` in
+    /// `"a \(b) c"`
+    StringStart,
+    /// Text between two interpolations of a string
+    StringPart,
+    /// The text after the last interpolation of a string and its closing delimiter, e.g. ` c"` in `"a \(b) c"`
+    StringEnd,
+    /// `\(`, which starts an interpolated expression inside a string
+    InterpolationStart,
+    /// The `)` that ends an interpolated expression
+    InterpolationEnd,
+    /// An integer literal in decimal, hex, octal, or binary notation
+    IntLiteral,
+    /// A decimal floating point literal
+    FloatLiteral,
+    /// `(`
+    LParen,
+    /// `)`
+    RParen,
+
+    /// A `//` comment, excluding the line break that ends it
+    LineComment,
+    /// A `/* ... */` comment, possibly containing nested block comments
+    BlockComment,
+    /// A single line of a `///` doc comment. Unlike other comments, doc comments aren't trivia, since they attach to
+    /// the declaration that follows them.
+    DocComment,
+    /// A `#!` line at the start of a file
+    Shebang,
+
+    /// Text that couldn't be lexed, such as an unexpected character or an unterminated string. The problem is
+    /// recorded as a [`crate::diagnostic::Diagnostic`] on the lexer.
+    Error,
+    /// The end of the input
+    Eof,
+
     #[default]
     Empty,
 }
 
+impl TokenKind {
+    /// Whether this kind is a keyword (including reserved words).
+    pub fn is_keyword(self) -> bool {
+        // keywords are declared contiguously, from `Abstract` to `Reserved`
+        (TokenKind::Abstract as u8..=TokenKind::Reserved as u8).contains(&(self as u8))
+    }
+
+    /// Whether tokens of this kind carry no meaning for the parser (whitespace, comments, and the shebang line).
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Empty | TokenKind::LineComment | TokenKind::BlockComment | TokenKind::Shebang
+        )
+    }
+}
+
+/// The value of a literal or identifier token, computed while lexing.
+///
+/// Text values borrow from the source when they can be taken from it verbatim, and are allocated in the lexer's
+/// arena otherwise (e.g. strings containing escapes), so they live as long as both.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TokenValue<'a> {
+    /// The token doesn't have a value: it is punctuation, a keyword, or an error
+    #[default]
+    None,
+    Int(i64),
+    Float(f64),
+    /// The name of an identifier, without backticks for quoted identifiers
+    Identifier(&'a str),
+    /// The content of a string literal or string part, with escape sequences replaced by the characters they denote.
+    ///
+    /// Parts of interpolated multiline strings are left raw, since their indentation can only be known once the
+    /// whole string has been lexed; see [`crate::literal::strip_multiline_indent`] and [`crate::literal::unescape`].
+    String(&'a str),
+}
+
 #[derive(Debug, Default, Clone, Copy)]
-pub struct Token {
+pub struct Token<'a> {
     pub kind: TokenKind,
     pub span: Span,
+    pub value: TokenValue<'a>,
 }
 
-impl Token {
+impl Token<'_> {
     pub fn new() -> Self {
         Token {
             kind: TokenKind::Empty,
             span: Span { start: 0, end: 0 },
+            value: TokenValue::None,
         }
     }
 }
\ No newline at end of file
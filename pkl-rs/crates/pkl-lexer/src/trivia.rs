@@ -0,0 +1,123 @@
+//! Lossless lexing, for tools like formatters that need to reproduce the whitespace and comments of a file.
+
+use oxc_allocator::Allocator;
+
+use crate::token::{Span, Token, TokenKind};
+use crate::Lexer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriviaKind {
+    /// A run of spaces, tabs, and form feeds
+    Whitespace,
+    /// A single line terminator (`\n`, `\r\n`, or `\r`)
+    Newline,
+    LineComment,
+    BlockComment,
+    /// A `#!` line at the start of the file
+    Shebang,
+}
+
+/// A piece of source text that carries no meaning for the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+/// A token along with the trivia that precedes it.
+///
+/// Doc comments aren't trivia, since they belong to the declaration that follows them; they are tokens of their own.
+#[derive(Debug, Clone, Default)]
+pub struct TokenWithTrivia<'a> {
+    pub leading: Vec<Trivia>,
+    pub token: Token<'a>,
+}
+
+impl<'a> Lexer<'a> {
+    /// Lexes the next significant token along with all the whitespace and comments before it.
+    ///
+    /// Taken together, the trivia and tokens returned until (and including) the [`TokenKind::Eof`] token cover the
+    /// whole source without gaps, so the source can be rebuilt from them. Like [`Lexer::next_token`], this bypasses
+    /// the lookahead buffer, so it shouldn't be mixed with peeking.
+    pub fn next_with_trivia(&mut self) -> TokenWithTrivia<'a> {
+        let mut leading: Vec<Trivia> = Vec::new();
+
+        loop {
+            let token = self.next_token();
+            let kind = match token.kind {
+                TokenKind::Empty => match self.source.byte_at(token.span.start) {
+                    Some(b'\n' | b'\r') => TriviaKind::Newline,
+                    _ => TriviaKind::Whitespace,
+                },
+                TokenKind::LineComment => TriviaKind::LineComment,
+                TokenKind::BlockComment => TriviaKind::BlockComment,
+                TokenKind::Shebang => TriviaKind::Shebang,
+                _ => return TokenWithTrivia { leading, token },
+            };
+
+            match leading.last_mut() {
+                Some(last) if kind == TriviaKind::Whitespace && last.kind == kind => last.span.end = token.span.end,
+                _ => leading.push(Trivia { kind, span: token.span }),
+            }
+        }
+    }
+
+    /// Lexes `source` to completion with [`Lexer::next_with_trivia`], up to and including the final
+    /// [`TokenKind::Eof`], whose leading trivia is whatever trails the last token.
+    pub fn tokenize_with_trivia(alloc: &'a Allocator, source: &'a str) -> Vec<TokenWithTrivia<'a>> {
+        let mut lexer = Lexer::new(alloc, source);
+        let mut tokens = Vec::new();
+
+        loop {
+            let token = lexer.next_with_trivia();
+            let is_eof = token.token.kind == TokenKind::Eof;
+            tokens.push(token);
+            if is_eof {
+                return tokens;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trivia_is_attached_to_the_following_token() {
+        let alloc = Allocator::default();
+        let tokens = Lexer::tokenize_with_trivia(&alloc, "a  /* b */\n// c\n  = 1 // d\n");
+        let kinds: Vec<_> = tokens
+            .iter()
+            .map(|t| (t.leading.iter().map(|trivia| trivia.kind).collect::<Vec<_>>(), t.token.kind))
+            .collect();
+
+        use TriviaKind::*;
+        assert_eq!(
+            kinds,
+            vec![
+                (vec![], TokenKind::Identifier),
+                (vec![Whitespace, BlockComment, Newline, LineComment, Newline, Whitespace], TokenKind::Eq),
+                (vec![Whitespace], TokenKind::IntLiteral),
+                (vec![Whitespace, LineComment, Newline], TokenKind::Eof),
+            ]
+        );
+    }
+
+    #[test]
+    fn trivia_and_tokens_cover_the_source() {
+        let source = "/// doc\nfoo {\r\n\tbar = \"x\\(y)\" /* */\n}\n";
+        let mut rebuilt = String::new();
+
+        let alloc = Allocator::default();
+
+        for token in Lexer::tokenize_with_trivia(&alloc, source) {
+            for trivia in &token.leading {
+                rebuilt.push_str(&source[trivia.span.start..trivia.span.end]);
+            }
+            rebuilt.push_str(&source[token.token.span.start..token.token.span.end]);
+        }
+
+        assert_eq!(rebuilt, source);
+    }
+}
@@ -0,0 +1,631 @@
+//! Completion, for `textDocument/completion`: the modules that an `import`, `amends`, or `extends` clause can name,
+//! the members of the value before a `.`, and otherwise the properties of the object being amended.
+//!
+//! The members of a value are found by evaluating it in the document's module if that works, and otherwise from the
+//! types that the document and the modules it imports declare, which is all there is to go on for values that depend
+//! on the object they are in. Of those modules, only the ones at `file:` URIs are read.
+
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use oxc_allocator::Allocator;
+use pkl_ast::uri::import_name;
+use pkl_ast::visit::{walk, Visit};
+use pkl_ast::{
+    ClassDecl, ClassMember, ClassProperty, DocComment, Expr, Module, ModuleMember, ObjectBody, ObjectEntry,
+    ObjectProperty, Type,
+};
+use pkl_eval::members::{self, Members, STANDARD_MODULES};
+use pkl_eval::{Evaluator, EvaluatorOptions};
+use pkl_lexer::token::{Span, Token, TokenKind};
+use pkl_lexer::Lexer;
+
+use crate::document::Document;
+use pkl_stdlib::json::Json;
+
+/// How long evaluating the value before a `.` can take. Completion has to keep up with typing, so a value that takes
+/// longer is given the members of its declared type instead.
+const EVALUATION_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How far types are followed through `extends` and `amends` clauses, and through the values that properties
+/// without a declared type are set to, which may go around in circles.
+const MAX_DEPTH: usize = 32;
+
+const KIND_METHOD: usize = 2;
+const KIND_MODULE: usize = 9;
+const KIND_PROPERTY: usize = 10;
+const KIND_FILE: usize = 17;
+const KIND_FOLDER: usize = 19;
+
+/// The items of a `textDocument/completion` response for the position at `offset`, each suggested once.
+pub fn completion(document: &Document, offset: usize) -> Vec<Json> {
+    let alloc = Allocator::default();
+    let source = alloc.alloc_str(&document.text);
+    let tokens: Vec<Token> = Lexer::new(&alloc, source)
+        .filter(|token| !token.kind.is_trivia() && token.kind != TokenKind::DocComment)
+        .collect();
+    // the token that the cursor is in or right after
+    let index = tokens.iter().rposition(|token| token.span.start < offset);
+
+    let items = match index {
+        Some(index) if import_uri(&tokens, index, offset) => {
+            let start = tokens[index].span.start + 1;
+            module_uris(document, Span::new(start, offset), &source[start..offset])
+        }
+        _ => {
+            let resolver = Resolver::new(&alloc);
+            let module = resolver.add(&document.uri, source);
+            match index.and_then(|index| member_dot(&tokens, index, offset)) {
+                Some(dot) => receiver_members(&resolver, module, dot),
+                None => body_properties(&resolver, module, offset),
+            }
+        }
+    };
+
+    let mut labels = Vec::new();
+    items
+        .into_iter()
+        .filter(|item| {
+            let label = item.get("label").and_then(Json::as_str).unwrap_or_default().to_string();
+            let new = !labels.contains(&label);
+            labels.push(label);
+            new
+        })
+        .collect()
+}
+
+/// Whether the cursor is inside the string of an `import`, `amends`, or `extends` clause, or of an `import`
+/// expression, which may not be closed yet.
+fn import_uri(tokens: &[Token], index: usize, offset: usize) -> bool {
+    let token = &tokens[index];
+    let inside = match token.kind {
+        TokenKind::StringLiteral => offset < token.span.end,
+        TokenKind::Error => offset <= token.span.end,
+        _ => false,
+    };
+    let kind = |back: usize| index.checked_sub(back).map(|index| tokens[index].kind);
+    let import = |kind| matches!(kind, Some(TokenKind::Import | TokenKind::ImportStar));
+    let clause = import(kind(1))
+        || matches!(kind(1), Some(TokenKind::Amends | TokenKind::Extends))
+        || kind(1) == Some(TokenKind::LParen) && import(kind(2));
+    inside && clause
+}
+
+/// The standard library modules and the `.pkl` files and directories whose URIs start with `typed`, replacing the
+/// text of `span`.
+fn module_uris(document: &Document, span: Span, typed: &str) -> Vec<Json> {
+    let item = |uri: &str, kind| {
+        let edit = Json::object([("range", document.range(span)), ("newText", Json::from(uri))]);
+        Json::object([("label", Json::from(uri)), ("kind", Json::from(kind)), ("textEdit", edit)])
+    };
+    let mut items: Vec<Json> =
+        STANDARD_MODULES.iter().filter(|uri| uri.starts_with(typed)).map(|uri| item(uri, KIND_MODULE)).collect();
+
+    let (dir, _) = typed.rsplit_once('/').map_or(("", typed), |(dir, name)| (&typed[..dir.len() + 1], name));
+    let Some(path) = document.uri.strip_prefix("file://") else { return items };
+    if dir.contains(':') || typed.contains(':') {
+        return items;
+    }
+    let Some(Ok(entries)) = PathBuf::from(path).parent().map(|parent| parent.join(dir).read_dir()) else {
+        return items;
+    };
+    let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let uri = format!("{dir}{name}");
+        if name.starts_with('.') || !uri.starts_with(typed) || entry.path() == Path::new(path) {
+            continue;
+        }
+        if entry.path().is_dir() {
+            items.push(item(&format!("{uri}/"), KIND_FOLDER));
+        } else if name.ends_with(".pkl") {
+            items.push(item(&uri, KIND_FILE));
+        }
+    }
+    items
+}
+
+/// The `.` or `?.` before the member name that the cursor is in or right after, or right before the cursor.
+fn member_dot(tokens: &[Token], index: usize, offset: usize) -> Option<Span> {
+    let token = &tokens[index];
+    let name = token.kind == TokenKind::Identifier || token.kind.is_keyword();
+    let dot = if name && offset <= token.span.end { index.checked_sub(1)? } else { index };
+    matches!(tokens[dot].kind, TokenKind::Dot | TokenKind::QuestionDot).then_some(tokens[dot].span)
+}
+
+fn receiver_members<'a>(resolver: &Resolver<'a>, module: &'a Parsed<'a>, dot: Span) -> Vec<Json> {
+    let mut finder = ReceiverFinder { dot, receiver: None };
+    finder.visit_module(module.ast);
+    let Some(expr) = finder.receiver.and_then(|span| resolver.reparse(module, span)) else { return Vec::new() };
+    if let Some(items) = evaluated_members(resolver, module, expr) {
+        return items;
+    }
+    let mut items = Vec::new();
+    if let Some(ty) = resolver.expr_type(module, expr) {
+        resolver.members(ty, false, &mut items);
+    }
+    items
+}
+
+/// The members of the value of `expr`, evaluated as if it were the value of a property of the module.
+fn evaluated_members<'a>(resolver: &Resolver<'a>, module: &'a Parsed<'a>, expr: &'a Expr<'a>) -> Option<Vec<Json>> {
+    let options = EvaluatorOptions { timeout: Some(EVALUATION_TIMEOUT), ..EvaluatorOptions::default() };
+    let mut evaluator = Evaluator::with_options(resolver.alloc, options);
+    evaluator.add_source(module.uri, module.source);
+    let value = evaluator.evaluate_expr_in(module.ast, module.uri, expr).ok()?;
+
+    let mut items = Vec::new();
+    if let Some(object) = value.as_object() {
+        for (name, value) in &object.properties {
+            items.push(item(name, KIND_PROPERTY, Some(value.type_name()), None));
+        }
+    }
+    builtin_members(members::class_members(value.type_name()), false, &mut items);
+    Some(items)
+}
+
+/// The properties of the innermost object being amended around the cursor, or of the module outside of any.
+fn body_properties<'a>(resolver: &Resolver<'a>, module: &'a Parsed<'a>, offset: usize) -> Vec<Json> {
+    let mut finder = OwnerFinder { offset, owner: None, direct_value: None, steps: Vec::new() };
+    finder.visit_module(module.ast);
+
+    let mut ty = Some(Ty::Module(module));
+    for step in finder.steps {
+        ty = match step {
+            Step::Class(name) => resolver.named_type(module, &[name]),
+            Step::Property(name) => ty.and_then(|ty| resolver.property_type(ty, name)),
+            Step::Type(names) => resolver.named_type(module, &names),
+            Step::Amend(span) => resolver.reparse(module, span).and_then(|expr| resolver.expr_type(module, expr)),
+            Step::Unknown => None,
+        };
+    }
+    let mut items = Vec::new();
+    if let Some(ty) = ty {
+        resolver.members(ty, true, &mut items);
+    }
+    items
+}
+
+fn item(label: &str, kind: usize, detail: Option<&str>, doc: Option<&DocComment>) -> Json {
+    let mut item = vec![("label", Json::from(label)), ("kind", Json::from(kind))];
+    if let Some(detail) = detail {
+        item.push(("detail", Json::from(detail)));
+    }
+    if let Some(doc) = doc {
+        item.push(("documentation", Json::from(doc.lines.join("\n"))));
+    }
+    Json::object(item)
+}
+
+fn builtin_members(members: Members, properties_only: bool, items: &mut Vec<Json>) {
+    items.extend(members.properties.iter().map(|name| item(name, KIND_PROPERTY, None, None)));
+    if !properties_only {
+        items.extend(members.methods.iter().map(|name| item(name, KIND_METHOD, None, None)));
+    }
+}
+
+/// Finds the receiver of the member access or method call with the `.` at `dot`.
+struct ReceiverFinder {
+    dot: Span,
+    receiver: Option<Span>,
+}
+
+impl<'a> Visit<'a> for ReceiverFinder {
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        let (receiver, name) = match expr {
+            Expr::Member(member) => (Some(&member.receiver), member.name.span),
+            Expr::Call(call) => (call.receiver.as_ref(), call.name.span),
+            _ => (None, self.dot),
+        };
+        if let Some(receiver) = receiver {
+            if receiver.span().end <= self.dot.start && self.dot.end <= name.start {
+                self.receiver = Some(receiver.span());
+            }
+        }
+        walk::walk_expr(self, expr);
+    }
+}
+
+/// Where the properties of an object body come from.
+enum Step<'a> {
+    /// The body of a class declaration
+    Class(&'a str),
+    /// The body or value of a property of the object the previous step is about
+    Property(&'a str),
+    /// `new Type { ... }`
+    Type(Vec<&'a str>),
+    /// `expr { ... }`
+    Amend(Span),
+    /// A body that isn't amending anything whose properties are known, like that of an entry
+    Unknown,
+}
+
+/// Finds the object bodies around the cursor, outermost first, and what each of them amends.
+struct OwnerFinder<'a> {
+    offset: usize,
+    /// What the next body around the cursor amends
+    owner: Option<Step<'a>>,
+    /// The value of the property around the cursor, which a `new { ... }` takes the type of
+    direct_value: Option<Span>,
+    steps: Vec<Step<'a>>,
+}
+
+impl OwnerFinder<'_> {
+    fn contains(&self, span: Span) -> bool {
+        span.start < self.offset && self.offset <= span.end
+    }
+}
+
+impl<'a> Visit<'a> for OwnerFinder<'a> {
+    fn visit_class(&mut self, class: &ClassDecl<'a>) {
+        if self.contains(class.span) {
+            self.steps.push(Step::Class(class.name.name));
+            walk::walk_class(self, class);
+        }
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty<'a>) {
+        if self.contains(property.span) {
+            self.owner = Some(Step::Property(property.name.name));
+            self.direct_value = property.value.as_ref().map(Expr::span);
+            walk::walk_class_property(self, property);
+        }
+    }
+
+    fn visit_object_property(&mut self, property: &ObjectProperty<'a>) {
+        if self.contains(property.span) {
+            self.owner = Some(Step::Property(property.name.name));
+            self.direct_value = property.value.as_ref().map(Expr::span);
+            walk::walk_object_property(self, property);
+        }
+    }
+
+    fn visit_object_entry(&mut self, entry: &ObjectEntry<'a>) {
+        if self.contains(entry.span) {
+            self.owner = Some(Step::Unknown);
+            walk::walk_object_entry(self, entry);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        if !self.contains(expr.span()) {
+            return;
+        }
+        match expr {
+            Expr::New(new) => match &new.ty {
+                Some(Type::Named(ty)) => {
+                    self.owner = Some(Step::Type(ty.name.parts.iter().map(|part| part.name).collect()));
+                }
+                // `name = new { ... }` amends the default value of the property's type
+                None if self.direct_value == Some(expr.span()) => {}
+                _ => self.owner = Some(Step::Unknown),
+            },
+            Expr::Amend(amend) => self.owner = Some(Step::Amend(amend.parent.span())),
+            _ => self.owner = Some(Step::Unknown),
+        }
+        self.direct_value = None;
+        walk::walk_expr(self, expr);
+    }
+
+    fn visit_object_body(&mut self, body: &ObjectBody<'a>) {
+        // inside the braces
+        if body.span.start < self.offset && self.offset < body.span.end {
+            self.steps.push(self.owner.take().unwrap_or(Step::Unknown));
+            walk::walk_object_body(self, body);
+        }
+    }
+}
+
+/// A module that completion looks into: the document, or a module that it imports or amends.
+struct Parsed<'a> {
+    uri: &'a str,
+    source: &'a str,
+    ast: &'a Module<'a>,
+}
+
+/// What the members of a value are known from.
+#[derive(Clone, Copy)]
+enum Ty<'a> {
+    /// An instance of a class declared in a module
+    Class(&'a ClassDecl<'a>, &'a Parsed<'a>),
+    Module(&'a Parsed<'a>),
+    /// A class of `pkl:base`, or a standard library module, whose members are built in
+    Builtin(&'a str),
+}
+
+impl<'a> Ty<'a> {
+    fn is(&self, other: &Ty<'a>) -> bool {
+        match (self, other) {
+            (Ty::Class(a, _), Ty::Class(b, _)) => std::ptr::eq(*a, *b),
+            (Ty::Module(a), Ty::Module(b)) => std::ptr::eq(*a, *b),
+            (Ty::Builtin(a), Ty::Builtin(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Finds the types of values from the declarations of the modules it has parsed.
+struct Resolver<'a> {
+    alloc: &'a Allocator,
+    modules: RefCell<Vec<&'a Parsed<'a>>>,
+    /// How deep `expr_type` has gone, to stop at properties whose values refer to each other
+    depth: Cell<usize>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(alloc: &'a Allocator) -> Self {
+        Resolver { alloc, modules: RefCell::new(Vec::new()), depth: Cell::new(0) }
+    }
+
+    fn add(&self, uri: &str, source: &'a str) -> &'a Parsed<'a> {
+        let ast = self.alloc.alloc(pkl_parser::parse_module(self.alloc, source).node);
+        let parsed = self.alloc.alloc(Parsed { uri: self.alloc.alloc_str(uri), source, ast });
+        self.modules.borrow_mut().push(parsed);
+        parsed
+    }
+
+    /// Parses the expression at `span` of a module again, on its own.
+    fn reparse(&self, module: &Parsed<'a>, span: Span) -> Option<&'a Expr<'a>> {
+        let result = pkl_parser::parse_expr(self.alloc, &module.source[span.start..span.end]);
+        result.diagnostics.is_empty().then(|| &*self.alloc.alloc(result.node))
+    }
+
+    /// The module at `uri`, relative to the module `base`, if it's a file.
+    fn load(&self, base: &Parsed<'a>, uri: &str) -> Option<&'a Parsed<'a>> {
+        let path = match uri.strip_prefix("file://") {
+            Some(path) => PathBuf::from(path),
+            None if uri.contains(':') => return None,
+            None => PathBuf::from(base.uri.strip_prefix("file://")?).parent()?.join(uri),
+        };
+        let uri = format!("file://{}", path.canonicalize().ok()?.display());
+        if let Some(module) = self.modules.borrow().iter().find(|module| module.uri == uri) {
+            return Some(module);
+        }
+        let source = std::fs::read_to_string(path).ok()?;
+        Some(self.add(&uri, self.alloc.alloc_str(&source)))
+    }
+
+    /// The module imported as `name` by `module`.
+    fn import(&self, module: &'a Parsed<'a>, name: &str) -> Option<Ty<'a>> {
+        let mut imports = module.ast.header.imports.iter().filter(|import| !import.glob);
+        let import = imports.find(|import| match &import.alias {
+            Some(alias) => alias.name == name,
+            None => import_name(import.uri.value) == name,
+        })?;
+        match import.uri.value.strip_prefix("pkl:") {
+            Some(name) => Some(Ty::Builtin(self.alloc.alloc_str(&format!("pkl.{name}")))),
+            None => self.load(module, import.uri.value).map(Ty::Module),
+        }
+    }
+
+    /// The type and the types it inherits from, through `extends` clauses of classes and `amends` and `extends`
+    /// clauses of modules, nearest first.
+    fn supertypes(&self, ty: Ty<'a>) -> Vec<Ty<'a>> {
+        let mut types = vec![ty];
+        while types.len() < MAX_DEPTH {
+            let parent = match types[types.len() - 1] {
+                Ty::Class(class, module) => class.extends.as_ref().and_then(|ty| self.type_of(module, ty)),
+                Ty::Module(module) => {
+                    let extends = module.ast.header.extends.as_ref();
+                    extends.and_then(|extends| self.load(module, extends.uri.value)).map(Ty::Module)
+                }
+                Ty::Builtin(_) => None,
+            };
+            match parent {
+                Some(parent) if !types.iter().any(|ty| ty.is(&parent)) => types.push(parent),
+                _ => break,
+            }
+        }
+        types
+    }
+
+    /// The type named by the parts of a qualified name, like `Bird` or `birds.Bird`, in `module`.
+    fn named_type(&self, module: &'a Parsed<'a>, names: &[&str]) -> Option<Ty<'a>> {
+        let name = match names {
+            [name] => *name,
+            [import, name] => {
+                return match self.import(module, import)? {
+                    Ty::Module(module) => self.named_type(module, &[name]),
+                    _ => None,
+                };
+            }
+            _ => return None,
+        };
+        for ty in self.supertypes(Ty::Module(module)) {
+            let Ty::Module(module) = ty else { continue };
+            for member in module.ast.members.iter() {
+                match member {
+                    ModuleMember::Class(class) if class.name.name == name => return Some(Ty::Class(class, module)),
+                    ModuleMember::TypeAlias(alias) if alias.name.name == name => return self.type_of(module, &alias.ty),
+                    _ => {}
+                }
+            }
+        }
+        let builtin = members::class_members(name) != members::class_members("");
+        (builtin || matches!(name, "Typed" | "Object")).then(|| Ty::Builtin(self.alloc.alloc_str(name)))
+    }
+
+    fn type_of(&self, module: &'a Parsed<'a>, ty: &'a Type<'a>) -> Option<Ty<'a>> {
+        match ty {
+            Type::Module(_) => Some(Ty::Module(module)),
+            Type::Named(ty) => {
+                let names: Vec<&str> = ty.name.parts.iter().map(|part| part.name).collect();
+                self.named_type(module, &names)
+            }
+            Type::Nullable(ty) => self.type_of(module, &ty.inner),
+            Type::Constrained(ty) => self.type_of(module, &ty.base),
+            Type::Parenthesized(ty) => self.type_of(module, &ty.inner),
+            _ => None,
+        }
+    }
+
+    /// The type of the property `name` of a value of type `ty`: its declared type, or else the type of its value.
+    fn property_type(&self, ty: Ty<'a>, name: &str) -> Option<Ty<'a>> {
+        for ty in self.supertypes(ty) {
+            let (properties, module) = match ty {
+                Ty::Class(class, module) => {
+                    let properties = class.members.iter().filter_map(|member| match member {
+                        ClassMember::Property(property) => Some(property),
+                        ClassMember::Method(_) => None,
+                    });
+                    (properties.collect::<Vec<_>>(), module)
+                }
+                Ty::Module(module) => {
+                    let properties = module.ast.members.iter().filter_map(|member| match member {
+                        ModuleMember::Property(property) => Some(property),
+                        _ => None,
+                    });
+                    (properties.collect(), module)
+                }
+                Ty::Builtin(_) => return None,
+            };
+            for property in properties.into_iter().filter(|property| property.name.name == name) {
+                let found = match (&property.ty, &property.value) {
+                    (Some(ty), _) => self.type_of(module, ty),
+                    (None, Some(value)) => self.expr_type(module, value),
+                    (None, None) => None,
+                };
+                if found.is_some() {
+                    return found;
+                }
+            }
+        }
+        None
+    }
+
+    /// The type of an expression in `module`, as far as it can be told without evaluating it.
+    fn expr_type(&self, module: &'a Parsed<'a>, expr: &'a Expr<'a>) -> Option<Ty<'a>> {
+        if self.depth.get() >= MAX_DEPTH {
+            return None;
+        }
+        self.depth.set(self.depth.get() + 1);
+        let ty = match expr {
+            Expr::Null(_) => Some(Ty::Builtin("Null")),
+            Expr::Bool(..) => Some(Ty::Builtin("Boolean")),
+            Expr::Int(..) => Some(Ty::Builtin("Int")),
+            Expr::Float(..) => Some(Ty::Builtin("Float")),
+            Expr::String(_) => Some(Ty::Builtin("String")),
+            Expr::Module(_) => Some(Ty::Module(module)),
+            Expr::Ident(ident) => {
+                self.import(module, ident.name).or_else(|| self.property_type(Ty::Module(module), ident.name))
+            }
+            Expr::Member(member) => {
+                self.expr_type(module, &member.receiver).and_then(|ty| self.property_type(ty, member.name.name))
+            }
+            Expr::New(new) => new.ty.as_ref().and_then(|ty| self.type_of(module, ty)),
+            Expr::Amend(amend) => self.expr_type(module, &amend.parent),
+            Expr::NonNull(expr) => self.expr_type(module, &expr.operand),
+            Expr::Parenthesized(expr) => self.expr_type(module, &expr.expr),
+            Expr::Import(import) if !import.glob => match import.uri.value.strip_prefix("pkl:") {
+                Some(name) => Some(Ty::Builtin(self.alloc.alloc_str(&format!("pkl.{name}")))),
+                None => self.load(module, import.uri.value).map(Ty::Module),
+            },
+            _ => None,
+        };
+        self.depth.set(self.depth.get() - 1);
+        ty
+    }
+
+    /// Adds the members of a value of type `ty` to `items`, its own before the ones it inherits.
+    fn members(&self, ty: Ty<'a>, properties_only: bool, items: &mut Vec<Json>) {
+        for ty in self.supertypes(ty) {
+            let (members, module): (Vec<_>, _) = match ty {
+                Ty::Class(class, module) => {
+                    let members = class.members.iter().map(|member| match member {
+                        ClassMember::Property(property) => Ok(property),
+                        ClassMember::Method(method) => Err((method.name.name, method.doc.as_ref())),
+                    });
+                    (members.collect(), module)
+                }
+                Ty::Module(module) => {
+                    let members = module.ast.members.iter().filter_map(|member| match member {
+                        ModuleMember::Property(property) => Some(Ok(property)),
+                        ModuleMember::Method(method) => Some(Err((method.name.name, method.doc.as_ref()))),
+                        _ => None,
+                    });
+                    (members.collect(), module)
+                }
+                Ty::Builtin(name) => {
+                    builtin_members(members::class_members(name), properties_only, items);
+                    continue;
+                }
+            };
+            for member in members {
+                match member {
+                    Ok(property) => {
+                        let ty = property.ty.as_ref().map(|ty| &module.source[ty.span().start..ty.span().end]);
+                        items.push(item(property.name.name, KIND_PROPERTY, ty, property.doc.as_ref()));
+                    }
+                    Err((name, doc)) if !properties_only => items.push(item(name, KIND_METHOD, None, doc)),
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::completion;
+    use crate::document::Document;
+    use pkl_stdlib::json::Json;
+
+    /// The labels of the completion items at the `|` in `text`.
+    fn labels(uri: &str, text: &str) -> Vec<String> {
+        let offset = text.find('|').unwrap();
+        let document = Document::new(uri, text.replacen('|', "", 1), None);
+        let items = completion(&document, offset);
+        items.iter().map(|item| item.get("label").and_then(Json::as_str).unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn properties_and_members() {
+        let source = "class Bird {\n\
+                      \x20 name: String\n\
+                      \x20 age: Int\n\
+                      }\n\
+                      bird: Bird = new {\n\
+                      \x20 |\n\
+                      }\n";
+        assert_eq!(labels("file:///a.pkl", source), ["name", "age"]);
+        let source = "class Bird { name: String }\nbird = new Bird { | }\n";
+        assert_eq!(labels("file:///a.pkl", source), ["name"]);
+
+        // `bird.name` can't be evaluated, so its members come from its type
+        let source = "class Bird { name: String }\nbird: Bird\nx = bird.name.|\n";
+        let members = labels("file:///a.pkl", source);
+        assert!(members.contains(&"length".to_string()) && members.contains(&"toUpperCase".to_string()));
+        let source = "import \"pkl:math\"\nx = math.p|";
+        assert!(labels("file:///a.pkl", source).contains(&"pi".to_string()));
+        let source = "bird { name = \"Pigeon\" age = 3 }\nx = bird.|";
+        assert_eq!(labels("file:///a.pkl", source)[..3], ["name", "age", "toList"]);
+    }
+
+    #[test]
+    fn imports_and_amended_modules() {
+        let dir = std::env::temp_dir().join(format!("pkl-lsp-completion-{}", std::process::id()));
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(dir.join("base.pkl"), "/// The port\nport: Int = 80\nhost: String\n").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        let uri = format!("file://{}/main.pkl", dir.display());
+
+        assert_eq!(labels(&uri, "import \"|"), [
+            "pkl:json",
+            "pkl:math",
+            "pkl:platform",
+            "pkl:toml",
+            "pkl:xml",
+            "pkl:yaml",
+            "base.pkl",
+            "lib/"
+        ]);
+        assert_eq!(labels(&uri, "amends \"pkl:m|\""), ["pkl:math"]);
+        assert_eq!(labels(&uri, "x = import(\"./b|"), ["./base.pkl"]);
+        assert_eq!(labels(&uri, "amends \"base.pkl\"\n\n|"), ["port", "host"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+//! The problems with a document: its syntax errors, or else the error evaluating it, such as a type mismatch, and its
+//! lints.
+
+use std::time::Duration;
+
+use oxc_allocator::Allocator;
+use pkl_analysis::lint::{Level, Lint};
+use pkl_eval::{Evaluator, EvaluatorOptions};
+use pkl_lexer::token::Span;
+
+use crate::document::Document;
+use pkl_stdlib::json::Json;
+
+/// How long evaluating a document can take, so that a module that never finishes doesn't hang the server.
+const EVALUATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SEVERITY_ERROR: usize = 1;
+const SEVERITY_WARNING: usize = 2;
+
+/// The diagnostics of a `textDocument/publishDiagnostics` notification for the document.
+///
+/// The parser recovers from syntax errors, so all of them are reported at once. A module without any is evaluated,
+/// which finds the errors that only show at runtime in Pkl, like values that don't match their types.
+pub fn diagnostics(document: &Document) -> Vec<Json> {
+    let alloc = Allocator::default();
+    let source = alloc.alloc_str(&document.text);
+    let result = pkl_parser::parse_module(&alloc, source);
+    if !result.diagnostics.is_empty() {
+        let diagnostics = result.diagnostics.iter();
+        let diagnostics = diagnostics.map(|diagnostic| to_json(document, diagnostic.span, &diagnostic.message, &[]));
+        return diagnostics.collect();
+    }
+
+    let options = EvaluatorOptions { timeout: Some(EVALUATION_TIMEOUT), ..EvaluatorOptions::default() };
+    let mut evaluator = Evaluator::with_options(&alloc, options);
+    evaluator.add_source(&document.uri, source);
+    match evaluator.evaluate_module(alloc.alloc(result.node), &document.uri) {
+        Ok(_) => Vec::new(),
+        Err(error) => vec![to_json(document, error.span, &error.message, &error.labels)],
+    }
+}
+
+/// The diagnostics of the lints of the document, which have the names of their rules as their codes.
+pub fn lints(document: &Document, lints: &[Lint]) -> Vec<Json> {
+    let lints = lints.iter().map(|lint| {
+        let severity = if lint.level == Level::Error { SEVERITY_ERROR } else { SEVERITY_WARNING };
+        Json::object([
+            ("range", document.range(lint.span)),
+            ("severity", Json::from(severity)),
+            ("code", Json::from(lint.rule.name())),
+            ("source", Json::from("pkl")),
+            ("message", Json::from(lint.message.as_str())),
+        ])
+    });
+    lints.collect()
+}
+
+fn to_json(document: &Document, span: Span, message: &str, labels: &[(Span, String)]) -> Json {
+    let mut diagnostic = vec![
+        ("range", document.range(span)),
+        ("severity", Json::from(SEVERITY_ERROR)),
+        ("source", Json::from("pkl")),
+        ("message", Json::from(message)),
+    ];
+    if !labels.is_empty() {
+        let related = labels.iter().map(|(span, label)| {
+            let location = Json::object([("uri", Json::from(document.uri.as_str())), ("range", document.range(*span))]);
+            Json::object([("location", location), ("message", Json::from(label.as_str()))])
+        });
+        diagnostic.push(("relatedInformation", Json::Array(related.collect())));
+    }
+    Json::object(diagnostic)
+}
+
+#[cfg(test)]
+mod test {
+    use super::diagnostics;
+    use crate::document::Document;
+    use pkl_stdlib::json::Json;
+
+    fn messages(text: &str) -> Vec<String> {
+        let document = Document::new("file:///a.pkl", text.to_string(), None);
+        let diagnostics = diagnostics(&document);
+        diagnostics.iter().map(|diagnostic| diagnostic.get("message").and_then(Json::as_str).unwrap().into()).collect()
+    }
+
+    #[test]
+    fn syntax_and_evaluation_errors() {
+        assert_eq!(messages("a = )\nb = ]\n").len(), 2);
+        assert_eq!(messages("a: Int = \"one\"\n").len(), 1);
+        assert!(messages("a: Int = 1\nb = a + 1\n").is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+//! The text of the documents the client has open, kept in sync with its edits.
+
+use pkl_lexer::line_index::{LineCol, LineIndex};
+use pkl_lexer::token::Span;
+
+use pkl_stdlib::json::Json;
+
+pub struct Document {
+    pub uri: String,
+    pub text: String,
+    pub version: Option<u64>,
+    /// The lines of `text`, to convert between byte offsets and the UTF-16 based positions of the protocol
+    pub lines: LineIndex,
+}
+
+impl Document {
+    pub fn new(uri: &str, text: String, version: Option<u64>) -> Self {
+        let lines = LineIndex::new(&text);
+        Document { uri: uri.to_string(), text, version, lines }
+    }
+
+    /// Applies a change of a `didChange` notification: either the new text of a range, or the whole new text.
+    pub fn change(&mut self, change: &Json) -> Result<(), String> {
+        let text = change.get("text").and_then(Json::as_str).ok_or("expected the `text` of a change")?;
+        match change.get("range") {
+            Some(range) => {
+                let span = self.span(range).ok_or("expected the `range` of a change to be in the document")?;
+                self.text.replace_range(span.start..span.end, text);
+            }
+            None => self.text = text.to_string(),
+        }
+        self.lines = LineIndex::new(&self.text);
+        Ok(())
+    }
+
+    /// The byte offset of a position, clamped to the end of its line.
+    pub fn offset(&self, position: &Json) -> Option<usize> {
+        let line = position.get("line")?.as_u64()? as usize;
+        let col = position.get("character")?.as_u64()? as usize;
+        let start = self.lines.offset(LineCol { line, col: 0 })?;
+        let end = self.lines.offset(LineCol { line: line + 1, col: 0 }).unwrap_or(self.text.len());
+        let line_end = start + self.text[start..end].trim_end_matches(['\n', '\r']).len();
+        let offset = self.lines.offset_utf16(LineCol { line, col })?.min(line_end);
+        // a position in the middle of a surrogate pair
+        Some((0..=offset).rev().find(|&offset| self.text.is_char_boundary(offset)).unwrap_or(0))
+    }
+
+    pub fn span(&self, range: &Json) -> Option<Span> {
+        let start = self.offset(range.get("start")?)?;
+        let end = self.offset(range.get("end")?)?;
+        (start <= end).then(|| Span::new(start, end))
+    }
+
+    pub fn range(&self, span: Span) -> Json {
+        range(&self.lines, span)
+    }
+}
+
+/// The position of an offset in a text with the lines `lines`, which may not be a document the client has open.
+pub fn position(lines: &LineIndex, offset: usize) -> Json {
+    let LineCol { line, col } = lines.line_col_utf16(offset);
+    Json::object([("line", Json::from(line)), ("character", Json::from(col))])
+}
+
+pub fn range(lines: &LineIndex, span: Span) -> Json {
+    Json::object([("start", position(lines, span.start)), ("end", position(lines, span.end))])
+}
+
+#[cfg(test)]
+mod test {
+    use pkl_lexer::token::Span;
+
+    use super::Document;
+    use pkl_stdlib::json::Json;
+
+    fn change(range: [u64; 4], text: &str) -> Json {
+        let position = |line: u64, character: u64| {
+            Json::object([("line", Json::Int(line as i64)), ("character", Json::Int(character as i64))])
+        };
+        let range = Json::object([("start", position(range[0], range[1])), ("end", position(range[2], range[3]))]);
+        Json::object([("range", range), ("text", Json::from(text))])
+    }
+
+    #[test]
+    fn changes_with_utf16_positions() {
+        let mut document = Document::new("file:///a.pkl", "a = \"😀\"\nb = 2\n".to_string(), Some(1));
+        // the emoji takes two UTF-16 code units
+        document.change(&change([0, 5, 0, 7], "x")).unwrap();
+        assert_eq!(document.text, "a = \"x\"\nb = 2\n");
+        document.change(&change([1, 4, 1, 99], "3 + 4")).unwrap();
+        assert_eq!(document.text, "a = \"x\"\nb = 3 + 4\n");
+        document.change(&Json::object([("text", Json::from("c = 1"))])).unwrap();
+        assert_eq!(document.text, "c = 1");
+
+        let range = document.range(Span::new(4, 5));
+        assert_eq!(range.to_string(), r#"{"start":{"line":0,"character":4},"end":{"line":0,"character":5}}"#);
+        assert!(document.change(&change([5, 0, 5, 0], "x")).is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! A language server for Pkl, which editors talk to over the Language Server Protocol.
+//!
+//! Messages are JSON-RPC requests, responses, and notifications, each preceded by a `Content-Length` header. The
+//! client sends the text of the documents it opens and every change to them, which it can send as the new text of a
+//! range, in the UTF-16 based positions of the protocol. After every change, the server publishes the document's
+//! diagnostics: its syntax errors, found by the parser's error recovery all at once, or else the error evaluating it,
+//! along with its lints. The levels of the lint rules can be set by the `lint` member of the `initializationOptions`,
+//! like `{"lint": {"unused-import": "off"}}`; settings that aren't understood are ignored.
+//!
+//! Requests about a document are answered from its current text:
+//!
+//! - `textDocument/semanticTokens/full` highlights it from the lexer's tokens
+//! - `textDocument/completion` suggests the properties of the object being amended, the members of the value before
+//!   a `.`, and the modules that an `import` can name
+//! - `textDocument/definition` and `textDocument/references` follow names to their declarations and back, across
+//!   the modules that the open documents import, amend, and extend
+//! - `textDocument/hover` shows the declaration of a name, with its declared or inferred type and its doc comment
+//! - `textDocument/documentSymbol` outlines its classes, typealiases, properties, and methods
+//!
+//! `workspace/symbol` searches the symbols of every `.pkl` file in the workspace folders, which are indexed once the
+//! client is initialized. The index follows the open documents as they change, and the other files as the client
+//! reports changes to them with `workspace/didChangeWatchedFiles`.
+
+#![forbid(unsafe_code)]
+
+mod completion;
+mod diagnostics;
+mod document;
+mod semantic_tokens;
+mod symbols;
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use document::Document;
+use pkl_stdlib::json::Json;
+use pkl_analysis::lint::LintConfig;
+use pkl_analysis::symbols::SymbolIndex;
+use pkl_analysis::{Analyzer, DefinitionSite};
+use pkl_lexer::token::Span;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// How the client sends changes to documents: as the new text of the ranges that changed
+const SYNC_INCREMENTAL: usize = 2;
+
+const LOG_ERROR: usize = 1;
+
+/// The type of a change to a watched file that deletes it
+const FILE_DELETED: u64 = 3;
+
+/// How many symbols `workspace/symbol` answers with at most
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+/// Handles the messages from `input` until it ends or the client says to exit, writing the responses and
+/// notifications of the server to `output`.
+pub fn serve(input: &mut dyn BufRead, output: &mut dyn Write) -> Result<(), String> {
+    let mut server = Server::default();
+    while let Some(body) = read_message(input)? {
+        let replies = match Json::parse(&body) {
+            Ok(message) => server.handle(&message),
+            Err(error) => vec![response(Json::Null, Err((PARSE_ERROR, error.message)))],
+        };
+        for reply in replies {
+            write_message(output, &reply)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The body of the next message, or nothing at the end of the input.
+fn read_message(input: &mut dyn BufRead) -> Result<Option<String>, String> {
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line).map_err(|err| format!("couldn't read a message: {err}"))? == 0 {
+            return Ok(None);
+        }
+        match line.trim_end().split_once(':') {
+            Some((name, value)) if name.eq_ignore_ascii_case("content-length") => len = value.trim().parse().ok(),
+            Some(_) => {}
+            None if line.trim_end().is_empty() => break,
+            None => return Err(format!("expected a header, found `{}`", line.trim_end())),
+        }
+    }
+
+    let len = len.ok_or("expected a `Content-Length` header")?;
+    let mut body = vec![0; len];
+    input.read_exact(&mut body).map_err(|err| format!("couldn't read a message: {err}"))?;
+    String::from_utf8(body).map(Some).map_err(|_| "a message isn't valid UTF-8".to_string())
+}
+
+fn write_message(output: &mut dyn Write, message: &Json) -> Result<(), String> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())
+        .and_then(|()| output.flush())
+        .map_err(|err| format!("couldn't write a message: {err}"))
+}
+
+/// A failed request: its error code and message.
+type ResponseError = (i64, String);
+
+fn response(id: Json, result: Result<Json, ResponseError>) -> Json {
+    let result = match result {
+        Ok(result) => ("result", result),
+        Err((code, message)) => {
+            ("error", Json::object([("code", Json::Int(code)), ("message", Json::from(message))]))
+        }
+    };
+    Json::object([("jsonrpc", Json::from("2.0")), ("id", id), result])
+}
+
+fn notification(method: &str, params: Json) -> Json {
+    Json::object([("jsonrpc", Json::from("2.0")), ("method", Json::from(method)), ("params", params)])
+}
+
+/// The state of a session with a client: the documents it has open, and the analysis of them and the modules they
+/// refer to.
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    analyzer: Analyzer,
+    /// The directories of the workspace folders
+    roots: Vec<PathBuf>,
+    /// The symbols of the files in the workspace folders, and of the open documents
+    symbols: SymbolIndex,
+    lint: LintConfig,
+    shut_down: bool,
+    exited: bool,
+}
+
+impl Server {
+    /// Handles a message from the client, returning the messages to send back: the response, if it's a request,
+    /// and the notifications it leads to.
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let params = message.get("params").unwrap_or(&Json::Null);
+        let mut replies = Vec::new();
+        match (message.get("id"), message.get("method").and_then(Json::as_str)) {
+            (Some(id), Some(method)) => replies.push(response(id.clone(), self.request(method, params))),
+            (None, Some(method)) => {
+                if let Err(message) = self.notification(method, params, &mut replies) {
+                    let params = Json::object([("type", Json::from(LOG_ERROR)), ("message", Json::from(message))]);
+                    replies.push(notification("window/logMessage", params));
+                }
+            }
+            // a response, while the server doesn't send requests
+            (_, None) => {}
+        }
+        replies
+    }
+
+    /// Whether the client has said to exit.
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, ResponseError> {
+        match method {
+            "initialize" => {
+                let folders = params.get("workspaceFolders").and_then(Json::as_array).unwrap_or_default();
+                let mut roots: Vec<&str> = folders.iter().filter_map(|f| f.get("uri").and_then(Json::as_str)).collect();
+                if roots.is_empty() {
+                    roots.extend(params.get("rootUri").and_then(Json::as_str));
+                }
+                self.roots = roots.iter().filter_map(|uri| uri.strip_prefix("file://")).map(PathBuf::from).collect();
+                let options = params.get("initializationOptions").and_then(|options| options.get("lint"));
+                if let Some(Json::Object(settings)) = options {
+                    for (rule, level) in settings {
+                        let Some(level) = level.as_str() else { continue };
+                        let _ = self.lint.parse_setting(&format!("{rule}={level}"));
+                    }
+                }
+                Ok(Json::object([
+                ("capabilities", capabilities()),
+                (
+                    "serverInfo",
+                    Json::object([("name", Json::from("pkl-lsp")), ("version", Json::from(env!("CARGO_PKG_VERSION")))]),
+                ),
+                ]))
+            }
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(Json::Null)
+            }
+            _ if self.shut_down => Err((INVALID_REQUEST, "the server has been shut down".to_string())),
+            "textDocument/completion" => {
+                let document = self.document(params)?;
+                let offset = params.get("position").and_then(|position| document.offset(position));
+                let offset = offset.ok_or((INVALID_PARAMS, "expected the `position` of a request".to_string()))?;
+                Ok(Json::Array(completion::completion(document, offset)))
+            }
+            "textDocument/definition" => {
+                let Some(site) = self.definition(params)? else { return Ok(Json::Null) };
+                Ok(self.location(&site.uri, site.name_span).unwrap_or(Json::Null))
+            }
+            "textDocument/hover" => {
+                let Some(site) = self.definition(params)? else { return Ok(Json::Null) };
+                let mut value = format!("```pkl\n{}\n```", self.analyzer.signature(&site));
+                if let Some(doc) = self.analyzer.doc(&site) {
+                    value = format!("{value}\n\n{doc}");
+                }
+                let contents = Json::object([("kind", Json::from("markdown")), ("value", Json::from(value))]);
+                Ok(Json::object([("contents", contents)]))
+            }
+            "textDocument/references" => {
+                let Some(site) = self.definition(params)? else { return Ok(Json::Array(Vec::new())) };
+                let context = params.get("context").and_then(|context| context.get("includeDeclaration"));
+                let mut references = Vec::new();
+                if context.and_then(Json::as_bool).unwrap_or(false) {
+                    references.push((site.uri.clone(), site.name_span));
+                }
+                references.extend(self.analyzer.references(&site));
+                Ok(Json::Array(references.iter().filter_map(|(uri, span)| self.location(uri, *span)).collect()))
+            }
+            "textDocument/documentSymbol" => {
+                let uri = self.document(params)?.uri.clone();
+                let module = self.analyzer.module(&uri).expect("open documents are analyzed");
+                Ok(symbols::document_symbols(module))
+            }
+            "workspace/symbol" => {
+                let query = params.get("query").and_then(Json::as_str).unwrap_or_default();
+                let found = self.symbols.search(query).into_iter().take(MAX_WORKSPACE_SYMBOLS).cloned();
+                let found: Vec<_> = found.collect();
+                let information = found.into_iter().filter_map(|symbol| {
+                    let mut information = vec![
+                        ("name", Json::from(symbol.name.as_str())),
+                        ("kind", Json::from(symbols::kind(symbol.kind))),
+                        ("location", self.location(&symbol.uri, symbol.span)?),
+                    ];
+                    if let Some(container) = &symbol.container {
+                        information.push(("containerName", Json::from(container.as_str())));
+                    }
+                    Some(Json::object(information))
+                });
+                Ok(Json::Array(information.collect()))
+            }
+            "textDocument/semanticTokens/full" => Ok(semantic_tokens::semantic_tokens(self.document(params)?)),
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method `{method}`"))),
+        }
+    }
+
+    /// The open document that the `textDocument` of a request's params refers to.
+    fn document(&self, params: &Json) -> Result<&Document, ResponseError> {
+        let uri = params.get("textDocument").and_then(|document| document.get("uri")).and_then(Json::as_str);
+        let uri = uri.ok_or((INVALID_PARAMS, "expected the `uri` of a `textDocument`".to_string()))?;
+        self.documents.get(uri).ok_or_else(|| (INVALID_PARAMS, format!("`{uri}` isn't open")))
+    }
+
+    /// The declaration of the name at the `position` of a request.
+    fn definition(&mut self, params: &Json) -> Result<Option<DefinitionSite>, ResponseError> {
+        let document = self.document(params)?;
+        let offset = params.get("position").and_then(|position| document.offset(position));
+        let offset = offset.ok_or((INVALID_PARAMS, "expected the `position` of a request".to_string()))?;
+        let uri = document.uri.clone();
+        Ok(self.analyzer.resolve(&uri, Span::new(offset, offset)))
+    }
+
+    fn location(&mut self, uri: &str, span: Span) -> Option<Json> {
+        let range = match self.documents.get(uri) {
+            Some(document) => document.range(span),
+            None => document::range(&self.analyzer.module(uri)?.lines, span),
+        };
+        Some(Json::object([("uri", Json::from(uri)), ("range", range)]))
+    }
+
+    /// Indexes the symbols of the module at `uri` again, reading it if it isn't open.
+    fn index(&mut self, uri: &str) {
+        match self.analyzer.module(uri) {
+            Some(module) => self.symbols.update(module),
+            None => self.symbols.remove(uri),
+        }
+    }
+
+    fn in_workspace(&self, uri: &str) -> bool {
+        uri.strip_prefix("file://").is_some_and(|path| self.roots.iter().any(|root| Path::new(path).starts_with(root)))
+    }
+
+    fn notification(&mut self, method: &str, params: &Json, replies: &mut Vec<Json>) -> Result<(), String> {
+        let uri = || -> Result<&str, String> {
+            let uri = params.get("textDocument").and_then(|document| document.get("uri")).and_then(Json::as_str);
+            uri.ok_or_else(|| format!("expected the `uri` of the document of `{method}`"))
+        };
+        let version = params.get("textDocument").and_then(|document| document.get("version")).and_then(Json::as_u64);
+
+        match method {
+            "exit" => self.exited = true,
+            "initialized" => {
+                let files: Vec<String> = self.roots.iter().flat_map(|root| symbols::workspace_files(root)).collect();
+                for uri in files {
+                    self.index(&uri);
+                }
+            }
+            "workspace/didChangeWatchedFiles" => {
+                let changes = params.get("changes").and_then(Json::as_array).unwrap_or_default();
+                for change in changes {
+                    let Some(uri) = change.get("uri").and_then(Json::as_str) else { continue };
+                    // an open document is analyzed from its text in the editor rather than from disk
+                    if self.documents.contains_key(uri) {
+                        continue;
+                    }
+                    self.analyzer.remove(uri);
+                    match change.get("type").and_then(Json::as_u64) {
+                        Some(FILE_DELETED) => self.symbols.remove(uri),
+                        _ => self.index(uri),
+                    }
+                }
+            }
+            "textDocument/didOpen" => {
+                let text = params.get("textDocument").and_then(|document| document.get("text")).and_then(Json::as_str);
+                let text = text.ok_or("expected the `text` of the document of `textDocument/didOpen`")?;
+                let document = Document::new(uri()?, text.to_string(), version);
+                self.analyzer.update(&document.uri, &document.text);
+                let mut diagnostics = diagnostics::diagnostics(&document);
+                diagnostics.extend(diagnostics::lints(&document, &self.analyzer.lint(&document.uri, &self.lint)));
+                replies.push(publish(&document, diagnostics));
+                let uri = document.uri.clone();
+                self.documents.insert(uri.clone(), document);
+                self.index(&uri);
+            }
+            "textDocument/didChange" => {
+                let uri = uri()?;
+                let document = self.documents.get_mut(uri).ok_or_else(|| format!("`{uri}` isn't open"))?;
+                let changes = params.get("contentChanges").and_then(Json::as_array).unwrap_or_default();
+                for change in changes {
+                    document.change(change)?;
+                }
+                document.version = version;
+                self.analyzer.update(uri, &document.text);
+                let mut diagnostics = diagnostics::diagnostics(document);
+                diagnostics.extend(diagnostics::lints(document, &self.analyzer.lint(uri, &self.lint)));
+                replies.push(publish(document, diagnostics));
+                self.index(uri);
+            }
+            "textDocument/didClose" => {
+                let uri = uri()?;
+                // the module is read from disk if another one still refers to it
+                self.analyzer.remove(uri);
+                if let Some(document) = self.documents.remove(uri) {
+                    replies.push(publish(&document, Vec::new()));
+                }
+                // what's on disk is indexed instead of what was in the editor
+                if self.in_workspace(uri) {
+                    self.index(uri);
+                } else {
+                    self.symbols.remove(uri);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn capabilities() -> Json {
+    let sync = Json::object([("openClose", Json::from(true)), ("change", Json::from(SYNC_INCREMENTAL))]);
+    let semantic_tokens = Json::object([("legend", semantic_tokens::legend()), ("full", Json::from(true))]);
+    let trigger_characters = Json::from(vec![Json::from("."), Json::from("\""), Json::from("/")]);
+    let completion = Json::object([("triggerCharacters", trigger_characters)]);
+    Json::object([
+        ("textDocumentSync", sync),
+        ("completionProvider", completion),
+        ("definitionProvider", Json::from(true)),
+        ("hoverProvider", Json::from(true)),
+        ("documentSymbolProvider", Json::from(true)),
+        ("workspaceSymbolProvider", Json::from(true)),
+        ("referencesProvider", Json::from(true)),
+        ("semanticTokensProvider", semantic_tokens),
+    ])
+}
+
+fn publish(document: &Document, diagnostics: Vec<Json>) -> Json {
+    let mut params = vec![("uri", Json::from(document.uri.as_str()))];
+    if let Some(version) = document.version {
+        params.push(("version", Json::Int(version as i64)));
+    }
+    params.push(("diagnostics", Json::Array(diagnostics)));
+    notification("textDocument/publishDiagnostics", Json::object(params))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message(text: &str) -> Json {
+        Json::parse(text).unwrap()
+    }
+
+    /// The messages of a `publishDiagnostics` notification.
+    fn published(reply: &Json) -> Vec<&str> {
+        assert_eq!(reply.get("method").and_then(Json::as_str), Some("textDocument/publishDiagnostics"));
+        let diagnostics = reply.get("params").and_then(|params| params.get("diagnostics")).unwrap();
+        diagnostics.as_array().unwrap().iter().map(|d| d.get("message").and_then(Json::as_str).unwrap()).collect()
+    }
+
+    #[test]
+    fn publishes_diagnostics() {
+        let mut server = Server::default();
+        let replies = server.handle(&message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#));
+        assert_eq!(replies[0].get("id"), Some(&Json::Int(1)));
+        assert!(replies[0].get("result").and_then(|result| result.get("capabilities")).is_some());
+
+        let replies = server.handle(&message(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":
+                {"uri":"file:///a.pkl","languageId":"pkl","version":1,"text":"a = 1 +\n"}}}"#,
+        ));
+        assert_eq!(published(&replies[0]), ["expected expression, found end of input"]);
+
+        let replies = server.handle(&message(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{
+                "textDocument":{"uri":"file:///a.pkl","version":2},
+                "contentChanges":[{"range":{"start":{"line":0,"character":7},"end":{"line":0,"character":7}},
+                "text":" 1"}]}}"#,
+        ));
+        assert!(published(&replies[0]).is_empty());
+
+        let replies = server.handle(&message(r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/rename","params":{}}"#));
+        assert_eq!(replies[0].get("error").and_then(|error| error.get("code")), Some(&Json::Int(-32601)));
+
+        server.handle(&message(r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#));
+        server.handle(&message(r#"{"jsonrpc":"2.0","method":"exit"}"#));
+        assert!(server.exited());
+    }
+
+    #[test]
+    fn publishes_lints() {
+        let mut server = Server::default();
+        server.handle(&message(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"initializationOptions":
+                {"lint":{"unused-local":"error","unused-import":"off"}}}}"#,
+        ));
+        let replies = server.handle(&message(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":
+                {"uri":"file:///a.pkl","languageId":"pkl","version":1,"text":"import \"pkl:math\"\nlocal x = 1\n"}}}"#,
+        ));
+        assert_eq!(published(&replies[0]), ["the local property `x` is never used"]);
+        let diagnostics = replies[0].get("params").and_then(|params| params.get("diagnostics")).unwrap();
+        let diagnostic = &diagnostics.as_array().unwrap()[0];
+        assert_eq!(diagnostic.get("severity"), Some(&Json::Int(1)));
+        assert_eq!(diagnostic.get("code").and_then(Json::as_str), Some("unused-local"));
+    }
+
+    #[test]
+    fn definition_hover_and_references() {
+        let mut server = Server::default();
+        server.handle(&message(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":
+                {"uri":"file:///a.pkl","languageId":"pkl","version":1,"text":"/// One.\nx = 1\ny = x + x\n"}}}"#,
+        ));
+        let request = |id: u32, method: &str, extra: &str| {
+            message(&format!(
+                r#"{{"jsonrpc":"2.0","id":{id},"method":"{method}","params":{{"textDocument":{{"uri":"file:///a.pkl"}},
+                    "position":{{"line":2,"character":8}}{extra}}}}}"#
+            ))
+        };
+        let replies = server.handle(&request(1, "textDocument/definition", ""));
+        let result = replies[0].get("result").unwrap().to_string();
+        assert_eq!(
+            result,
+            r#"{"uri":"file:///a.pkl","range":{"start":{"line":1,"character":0},"end":{"line":1,"character":1}}}"#
+        );
+        let replies = server.handle(&request(2, "textDocument/hover", ""));
+        let contents = replies[0].get("result").and_then(|result| result.get("contents")).unwrap();
+        assert_eq!(contents.get("value").and_then(Json::as_str), Some("```pkl\nx: Int\n```\n\nOne."));
+
+        let context = r#","context":{"includeDeclaration":true}"#;
+        let replies = server.handle(&request(3, "textDocument/references", context));
+        let locations = replies[0].get("result").and_then(Json::as_array).unwrap();
+        let start = |location: &Json| location.get("range").and_then(|range| range.get("start")).unwrap().to_string();
+        let starts: Vec<String> = locations.iter().map(start).collect();
+        assert_eq!(
+            starts,
+            [r#"{"line":1,"character":0}"#, r#"{"line":2,"character":4}"#, r#"{"line":2,"character":8}"#]
+        );
+    }
+
+    #[test]
+    fn workspace_symbols() {
+        let dir = std::env::temp_dir().join(format!("pkl-lsp-workspace-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("birds.pkl"), "class Bird { name: String }
+").unwrap();
+        let root = format!("file://{}", dir.display());
+
+        let mut server = Server::default();
+        let initialize = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"initialize","params":{{"rootUri":"{root}"}}}}"#);
+        server.handle(&message(&initialize));
+        server.handle(&message(r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#));
+        let search = |server: &mut Server, query: &str| -> Vec<String> {
+            let params = format!(r#"{{"query":"{query}"}}"#);
+            let request = format!(r#"{{"jsonrpc":"2.0","id":2,"method":"workspace/symbol","params":{params}}}"#);
+            let replies = server.handle(&message(&request));
+            let symbols = replies[0].get("result").and_then(Json::as_array).unwrap();
+            let name = |symbol: &Json| symbol.get("name").and_then(Json::as_str).unwrap().to_string();
+            symbols.iter().map(name).collect()
+        };
+        assert_eq!(search(&mut server, "bi"), ["Bird", "birds"]);
+        assert_eq!(search(&mut server, "nam"), ["name"]);
+
+        std::fs::write(dir.join("birds.pkl"), "class Parrot {}
+").unwrap();
+        let changed = format!(
+            r#"{{"jsonrpc":"2.0","method":"workspace/didChangeWatchedFiles","params":{{"changes":[
+                {{"uri":"{root}/birds.pkl","type":2}}]}}}}"#
+        );
+        server.handle(&message(&changed));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(search(&mut server, "p"), ["Parrot"]);
+    }
+
+    #[test]
+    fn framing() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#;
+        let input = format!("Content-Length: {}\r\n\r\n{body}Content-Length: 5\r\n\r\n{{oops", body.len());
+        let mut output = Vec::new();
+        serve(&mut input.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let (first, second) = output.split_once("}Content-Length").unwrap();
+        assert_eq!(first, "Content-Length: 38\r\n\r\n{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":null");
+        assert!(second.contains("\"code\":-32700"), "{second}");
+    }
+}
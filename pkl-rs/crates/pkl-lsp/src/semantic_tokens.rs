@@ -0,0 +1,163 @@
+//! Highlighting from the lexer's tokens, for `textDocument/semanticTokens/full`, which is more accurate than a
+//! TextMate grammar can be: a grammar can't tell where an interpolation inside a string ends, for one.
+
+use oxc_allocator::Allocator;
+use pkl_lexer::line_index::LineCol;
+use pkl_lexer::token::{Span, TokenKind};
+use pkl_lexer::Lexer;
+
+use crate::document::Document;
+use pkl_stdlib::json::Json;
+
+/// The token types, whose indices the tokens refer to, in the legend the server sends with its capabilities.
+const TOKEN_TYPES: [&str; 9] =
+    ["keyword", "modifier", "string", "operator", "number", "comment", "decorator", "type", "function"];
+const KEYWORD: u32 = 0;
+const MODIFIER: u32 = 1;
+const STRING: u32 = 2;
+const OPERATOR: u32 = 3;
+const NUMBER: u32 = 4;
+const COMMENT: u32 = 5;
+const DECORATOR: u32 = 6;
+const TYPE: u32 = 7;
+const FUNCTION: u32 = 8;
+
+/// The token modifiers, which the tokens refer to as bits of a set.
+const TOKEN_MODIFIERS: [&str; 2] = ["declaration", "documentation"];
+const DECLARATION: u32 = 1 << 0;
+const DOCUMENTATION: u32 = 1 << 1;
+
+pub fn legend() -> Json {
+    let strings = |strings: &[&str]| Json::Array(strings.iter().map(|&s| Json::from(s)).collect());
+    Json::object([("tokenTypes", strings(&TOKEN_TYPES)), ("tokenModifiers", strings(&TOKEN_MODIFIERS))])
+}
+
+/// The tokens of a document, in the relative encoding of the protocol: five numbers per token, which are the line of
+/// its start relative to the previous token's, its column (relative to the previous token's if it's on the same
+/// line), its length, its type, and its modifiers.
+///
+/// Tokens can't span lines, so those that do, like multiline strings and block comments, are split into one per line.
+pub fn semantic_tokens(document: &Document) -> Json {
+    let alloc = Allocator::default();
+    let mut data = Vec::new();
+    let mut previous = LineCol::default();
+    let mut prev_kind = TokenKind::Empty;
+    let mut in_annotation = false;
+
+    for token in Lexer::new(&alloc, &document.text) {
+        // the name of an annotation, which may be qualified, as in `@base.Deprecated`
+        in_annotation = match (token.kind, prev_kind) {
+            (TokenKind::At, _) => true,
+            (TokenKind::Identifier, TokenKind::At | TokenKind::Dot) | (TokenKind::Dot, TokenKind::Identifier) => {
+                in_annotation
+            }
+            _ => false,
+        };
+        let class = match token.kind {
+            _ if in_annotation => Some((DECORATOR, 0)),
+            TokenKind::Identifier => match prev_kind {
+                TokenKind::Class | TokenKind::Typealias => Some((TYPE, DECLARATION)),
+                TokenKind::Function => Some((FUNCTION, DECLARATION)),
+                _ => None,
+            },
+            kind => classify(kind),
+        };
+        prev_kind = token.kind;
+        let Some((ty, modifiers)) = class else { continue };
+
+        for segment in line_segments(document, token.span) {
+            let start = document.lines.line_col_utf16(segment.start);
+            let len = document.lines.line_col_utf16(segment.end).col - start.col;
+            let col = if start.line == previous.line { start.col - previous.col } else { start.col };
+            data.extend([start.line - previous.line, col, len, ty as usize, modifiers as usize]);
+            previous = start;
+        }
+    }
+
+    Json::object([("data", Json::Array(data.into_iter().map(Json::from).collect()))])
+}
+
+/// The type and modifiers of a token of a kind that is highlighted wherever it is, if any.
+fn classify(kind: TokenKind) -> Option<(u32, u32)> {
+    use TokenKind::*;
+
+    Some(match kind {
+        Abstract | Open | External | Local | Hidden | Fixed | Const => (MODIFIER, 0),
+        kind if kind.is_keyword() => (KEYWORD, 0),
+        StringLiteral | StringStart | StringPart | StringEnd => (STRING, 0),
+        // the `\(` and `)` around an interpolation, which set the code inside it apart from the string
+        InterpolationStart | InterpolationEnd => (OPERATOR, 0),
+        IntLiteral | FloatLiteral => (NUMBER, 0),
+        LineComment | BlockComment | Shebang => (COMMENT, 0),
+        DocComment => (COMMENT, DOCUMENTATION),
+        Plus | Minus | Star | Slash | TildeSlash | Percent | StarStar | EqEq | NotEq | Lt | LtEq | Gt | GtEq | AndAnd
+        | OrOr | Bang | QuestionQuestion | QuestionDot | BangBang | PipeGt | Arrow | Pipe => (OPERATOR, 0),
+        _ => return None,
+    })
+}
+
+/// The parts of a span on each of the lines it covers, without line terminators and leaving out empty parts.
+fn line_segments(document: &Document, span: Span) -> impl Iterator<Item = Span> + '_ {
+    let text = &document.text;
+    let lines = document.lines.line_col(span.start).line..=document.lines.line_col(span.end).line;
+    lines.filter_map(move |line| {
+        let line_start = document.lines.offset(LineCol { line, col: 0 })?;
+        let rest = &text[line_start..];
+        let line_end = line_start + rest.find(['\n', '\r']).unwrap_or(rest.len());
+        let segment = Span::new(span.start.max(line_start), span.end.min(line_end));
+        (segment.start < segment.end).then_some(segment)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The tokens of a document, with absolute positions: line, column, length, type, and modifiers.
+    fn tokens(text: &str) -> Vec<[usize; 5]> {
+        let document = Document::new("file:///a.pkl", text.to_string(), None);
+        let json = semantic_tokens(&document);
+        let data = json.get("data").and_then(Json::as_array).unwrap();
+        let data: Vec<usize> = data.iter().map(|n| n.as_u64().unwrap() as usize).collect();
+
+        let (mut line, mut col) = (0, 0);
+        data.chunks(5)
+            .map(|token| {
+                col = if token[0] == 0 { col + token[1] } else { token[1] };
+                line += token[0];
+                [line, col, token[2], token[3], token[4]]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn highlighting() {
+        let doc = DOCUMENTATION as usize;
+        let declaration = DECLARATION as usize;
+        assert_eq!(
+            tokens("/// Doc\n@Deprecated\nlocal class A { x = \"é\\(1)b\" }"),
+            [
+                [0, 0, 7, 5, doc],
+                [1, 0, 1, 6, 0],
+                [1, 1, 10, 6, 0],
+                [2, 0, 5, 1, 0],
+                [2, 6, 5, 0, 0],
+                [2, 12, 1, 7, declaration],
+                // `é` is a single UTF-16 code unit, but two bytes
+                [2, 20, 2, 2, 0],
+                [2, 22, 2, 3, 0],
+                [2, 24, 1, 4, 0],
+                [2, 25, 1, 3, 0],
+                [2, 26, 2, 2, 0],
+            ]
+        );
+        let decorator = |col| [0, col, 1, DECORATOR as usize, 0];
+        assert_eq!(tokens("@a.B\nfoo = 1"), [decorator(0), decorator(1), decorator(2), decorator(3), [1, 6, 1, 4, 0]]);
+    }
+
+    #[test]
+    fn multiline_tokens_are_split() {
+        let tokens = tokens("a = \"\"\"\n  x\n  \"\"\"\n/* one\ntwo */");
+        assert_eq!(tokens, [[0, 4, 3, 2, 0], [1, 0, 3, 2, 0], [2, 0, 5, 2, 0], [3, 0, 6, 5, 0], [4, 0, 6, 5, 0]]);
+    }
+}
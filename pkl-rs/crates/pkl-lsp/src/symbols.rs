@@ -0,0 +1,114 @@
+//! The outline of a document, for `textDocument/documentSymbol`, and the symbols of the workspace that
+//! `workspace/symbol` searches.
+
+use std::path::{Path, PathBuf};
+
+use pkl_analysis::scope::ModuleAnalysis;
+use pkl_analysis::symbols::Symbol;
+use pkl_analysis::SymbolKind;
+
+use crate::document;
+use pkl_stdlib::json::Json;
+
+/// How many files of a workspace are indexed at most, so that opening a huge directory doesn't take forever.
+const MAX_WORKSPACE_FILES: usize = 10_000;
+
+/// The outline of a module: the module itself, with its members inside it.
+pub fn document_symbols(module: &ModuleAnalysis) -> Json {
+    let children = module.symbols().iter().map(|symbol| document_symbol(module, symbol)).collect();
+    let range = document::range(&module.lines, module.span);
+    let selection = document::range(&module.lines, module.name_span);
+    Json::Array(vec![Json::object([
+        ("name", Json::from(module.name.as_str())),
+        ("kind", Json::from(kind(SymbolKind::Module))),
+        ("range", range),
+        ("selectionRange", selection),
+        ("children", Json::Array(children)),
+    ])])
+}
+
+fn document_symbol(module: &ModuleAnalysis, symbol: &Symbol) -> Json {
+    let children = symbol.children.iter().map(|child| document_symbol(module, child)).collect();
+    Json::object([
+        ("name", Json::from(symbol.name.as_str())),
+        ("kind", Json::from(kind(symbol.kind))),
+        ("range", document::range(&module.lines, symbol.span)),
+        ("selectionRange", document::range(&module.lines, symbol.name_span)),
+        ("children", Json::Array(children)),
+    ])
+}
+
+/// The number of the protocol's `SymbolKind` for a kind of declaration.
+pub fn kind(kind: SymbolKind) -> usize {
+    match kind {
+        SymbolKind::Module => 2,
+        SymbolKind::Import => 9,
+        SymbolKind::Class => 5,
+        SymbolKind::Method => 6,
+        SymbolKind::Property => 7,
+        SymbolKind::Parameter => 13,
+        SymbolKind::TypeAlias | SymbolKind::TypeParameter => 26,
+    }
+}
+
+/// The `file:` URIs of the `.pkl` files in a directory and the directories in it, leaving out hidden ones.
+pub fn workspace_files(root: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = directory.read_dir() else { continue };
+        let mut entries: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+        entries.sort();
+        for path in entries {
+            let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                directories.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "pkl") {
+                files.push(format!("file://{}", path.display()));
+                if files.len() == MAX_WORKSPACE_FILES {
+                    return files;
+                }
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn outline_and_workspace_files() {
+        let source = "module birds\nclass Bird {\n  name: String\n}\nbird: Bird\n";
+        let module = ModuleAnalysis::new("file:///birds.pkl", source);
+        let symbols = document_symbols(&module);
+        let root = &symbols.as_array().unwrap()[0];
+        let names = |symbol: &Json| -> Vec<String> {
+            let children = symbol.get("children").and_then(Json::as_array).unwrap();
+            children.iter().map(|child| child.get("name").and_then(Json::as_str).unwrap().to_string()).collect()
+        };
+        assert_eq!(root.get("name").and_then(Json::as_str), Some("birds"));
+        assert_eq!(names(root), ["Bird", "bird"]);
+        let class = &root.get("children").and_then(Json::as_array).unwrap()[0];
+        assert_eq!(names(class), ["name"]);
+        assert_eq!(
+            class.get("selectionRange").unwrap().to_string(),
+            r#"{"start":{"line":1,"character":6},"end":{"line":1,"character":10}}"#
+        );
+
+        let dir = std::env::temp_dir().join(format!("pkl-lsp-symbols-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::create_dir_all(dir.join(".hidden")).unwrap();
+        for file in ["a.pkl", "nested/b.pkl", ".hidden/c.pkl", "d.txt"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        let files = workspace_files(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let root = format!("file://{}", dir.display());
+        assert_eq!(files, [format!("{root}/a.pkl"), format!("{root}/nested/b.pkl")]);
+    }
+}
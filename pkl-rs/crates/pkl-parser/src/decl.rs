@@ -0,0 +1,509 @@
+use pkl_ast::*;
+use pkl_lexer::token::TokenKind;
+
+use crate::Parser;
+
+/// Whether a token is a modifier keyword.
+fn is_modifier(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Abstract
+            | TokenKind::Open
+            | TokenKind::External
+            | TokenKind::Local
+            | TokenKind::Hidden
+            | TokenKind::Fixed
+            | TokenKind::Const
+    )
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn parse_module(&mut self) -> Module<'a> {
+        let header = self.parse_module_header();
+        let mut members = self.ast.vec();
+
+        while !self.at(TokenKind::Eof) {
+            let (errors, start) = (self.diagnostics.len(), self.token.span.start);
+            if let Some(member) = self.parse_module_member() {
+                members.push(member);
+            }
+            if self.diagnostics.len() > errors {
+                self.synchronize(start);
+            }
+        }
+
+        Module { span: pkl_ast::Span::new(0, self.source.len()), header, members }
+    }
+
+    /// Parses the doc comment, modifiers, `module` clause, `amends`/`extends` clause, and imports of a module.
+    pub(crate) fn parse_module_header(&mut self) -> ModuleHeader<'a> {
+        let start = self.token.span.start;
+        let mut doc = None;
+        let mut annotations = self.ast.vec();
+        let mut modifiers = Modifiers(self.ast.vec());
+        let mut name = None;
+
+        // annotations and modifiers only belong to the header if they are followed by `module` (or, lacking
+        // modifiers, by `amends` or `extends`); otherwise they belong to the first member
+        let mut n = 0;
+        while self.nth(n) == TokenKind::At {
+            n = self.skip_annotation(n);
+        }
+        let modifiers_start = n;
+        while is_modifier(self.nth(n)) {
+            n += 1;
+        }
+        let owns_prefix = match self.nth(n) {
+            TokenKind::Module => true,
+            TokenKind::Amends | TokenKind::Extends => n == modifiers_start,
+            _ => false,
+        };
+        if owns_prefix {
+            doc = self.take_doc();
+            annotations = self.parse_annotations();
+            modifiers = self.parse_modifiers();
+        }
+
+        if self.eat(TokenKind::Module) {
+            name = Some(self.parse_qualified_name());
+        }
+
+        let extends = match self.token.kind {
+            TokenKind::Amends | TokenKind::Extends => {
+                let clause_start = self.token.span.start;
+                let kind = match self.bump().kind {
+                    TokenKind::Amends => ExtendsKind::Amends,
+                    _ => ExtendsKind::Extends,
+                };
+                let uri = self.parse_string_constant();
+                Some(ModuleExtends { span: self.span_from(clause_start), kind, uri })
+            }
+            _ => None,
+        };
+
+        let mut imports = self.ast.vec();
+        while matches!(self.token.kind, TokenKind::Import | TokenKind::ImportStar) {
+            let import_start = self.token.span.start;
+            let glob = self.bump().kind == TokenKind::ImportStar;
+            let uri = self.parse_string_constant();
+            let alias = if self.eat(TokenKind::As) { Some(self.parse_ident_or_missing()) } else { None };
+            imports.push(Import { span: self.span_from(import_start), glob, uri, alias });
+        }
+
+        ModuleHeader {
+            span: self.span_from(start),
+            doc,
+            annotations,
+            modifiers,
+            name,
+            extends,
+            imports,
+        }
+    }
+
+    /// Parses a member of a module, or skips a token that can't start one.
+    pub(crate) fn parse_module_member(&mut self) -> Option<ModuleMember<'a>> {
+        let start = self.token.span.start;
+        let doc = self.take_doc();
+        let annotations = self.parse_annotations();
+        let modifiers = self.parse_modifiers();
+
+        match self.token.kind {
+            TokenKind::Class => Some(ModuleMember::Class(self.parse_class(start, doc, annotations, modifiers))),
+            TokenKind::Typealias => {
+                Some(ModuleMember::TypeAlias(self.parse_typealias(start, doc, annotations, modifiers)))
+            }
+            TokenKind::Function => {
+                Some(ModuleMember::Method(self.parse_class_method(start, doc, annotations, modifiers)))
+            }
+            TokenKind::Identifier => {
+                Some(ModuleMember::Property(self.parse_class_property(start, doc, annotations, modifiers)))
+            }
+            _ => {
+                self.error_expected("module member");
+                self.bump();
+                None
+            }
+        }
+    }
+
+    /// Parses `class Name<T> extends Parent { members }` after its doc comment, annotations, and modifiers.
+    fn parse_class(
+        &mut self,
+        start: usize,
+        doc: Option<DocComment<'a>>,
+        annotations: Vec<'a, Annotation<'a>>,
+        modifiers: Modifiers<'a>,
+    ) -> ClassDecl<'a> {
+        self.expect(TokenKind::Class, "`class`");
+        let name = self.parse_ident_or_missing();
+        let type_params = self.parse_type_parameters();
+        let extends = if self.eat(TokenKind::Extends) { Some(self.parse_type()) } else { None };
+
+        let mut members = self.ast.vec();
+        if self.eat(TokenKind::LBrace) {
+            while !self.at(TokenKind::RBrace) && !self.at(TokenKind::Eof) {
+                let errors = self.diagnostics.len();
+                let member_start = self.token.span.start;
+                let doc = self.take_doc();
+                let annotations = self.parse_annotations();
+                let modifiers = self.parse_modifiers();
+
+                match self.token.kind {
+                    TokenKind::Function => {
+                        let method = self.parse_class_method(member_start, doc, annotations, modifiers);
+                        members.push(ClassMember::Method(method));
+                    }
+                    TokenKind::Identifier => {
+                        let property = self.parse_class_property(member_start, doc, annotations, modifiers);
+                        members.push(ClassMember::Property(property));
+                    }
+                    _ => {
+                        self.error_expected("property or method");
+                        self.bump();
+                    }
+                }
+                if self.diagnostics.len() > errors {
+                    self.synchronize(member_start);
+                }
+            }
+            self.expect(TokenKind::RBrace, "`}`");
+        }
+
+        ClassDecl {
+            span: self.span_from(start),
+            doc,
+            annotations,
+            modifiers,
+            name,
+            type_params,
+            extends,
+            members,
+        }
+    }
+
+    /// Parses `typealias Name<T> = Type` after its doc comment, annotations, and modifiers.
+    fn parse_typealias(
+        &mut self,
+        start: usize,
+        doc: Option<DocComment<'a>>,
+        annotations: Vec<'a, Annotation<'a>>,
+        modifiers: Modifiers<'a>,
+    ) -> TypeAlias<'a> {
+        self.expect(TokenKind::Typealias, "`typealias`");
+        let name = self.parse_ident_or_missing();
+        let type_params = self.parse_type_parameters();
+        self.expect(TokenKind::Eq, "`=`");
+        let ty = self.parse_type();
+
+        TypeAlias {
+            span: self.span_from(start),
+            doc,
+            annotations,
+            modifiers,
+            name,
+            type_params,
+            ty,
+        }
+    }
+
+    /// Parses `function name<T>(params): Type = body` after its doc comment, annotations, and modifiers.
+    ///
+    /// The body may be missing, which is only valid for `abstract` and `external` methods.
+    pub(crate) fn parse_class_method(
+        &mut self,
+        start: usize,
+        doc: Option<DocComment<'a>>,
+        annotations: Vec<'a, Annotation<'a>>,
+        modifiers: Modifiers<'a>,
+    ) -> ClassMethod<'a> {
+        self.expect(TokenKind::Function, "`function`");
+        let name = self.parse_ident_or_missing();
+        let type_params = self.parse_type_parameters();
+        let params = self.parse_parameter_list();
+        let return_type = if self.eat(TokenKind::Colon) { Some(self.parse_type()) } else { None };
+        let body = if self.eat(TokenKind::Eq) { Some(self.parse_expr()) } else { None };
+
+        ClassMethod {
+            span: self.span_from(start),
+            doc,
+            annotations,
+            modifiers,
+            name,
+            type_params,
+            params,
+            return_type,
+            body,
+        }
+    }
+
+    /// Parses any number of annotations like `@Deprecated { message = "..." }`.
+    fn parse_annotations(&mut self) -> Vec<'a, Annotation<'a>> {
+        let mut annotations = self.ast.vec();
+
+        while self.at(TokenKind::At) {
+            let start = self.bump().span.start;
+            let name = self.parse_qualified_name();
+            let body = if self.at(TokenKind::LBrace) { Some(self.parse_object_body()) } else { None };
+            annotations.push(Annotation { span: self.span_from(start), name, body });
+        }
+
+        annotations
+    }
+
+    /// The lookahead index just past the annotation starting at `self.nth(n)`.
+    fn skip_annotation(&mut self, mut n: usize) -> usize {
+        n += 1;
+        if self.nth(n) == TokenKind::Identifier {
+            n += 1;
+            while self.nth(n) == TokenKind::Dot && self.nth(n + 1) == TokenKind::Identifier {
+                n += 2;
+            }
+        }
+
+        if self.nth(n) == TokenKind::LBrace {
+            let mut depth = 0;
+            loop {
+                match self.nth(n) {
+                    TokenKind::LBrace => depth += 1,
+                    TokenKind::RBrace => depth -= 1,
+                    TokenKind::Eof => return n,
+                    _ => {}
+                }
+                n += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+
+        n
+    }
+
+    /// Parses `<T, in U, out V>` if present.
+    fn parse_type_parameters(&mut self) -> Vec<'a, TypeParameter<'a>> {
+        let mut params = self.ast.vec();
+        if !self.eat(TokenKind::Lt) {
+            return params;
+        }
+
+        loop {
+            let start = self.token.span.start;
+            let variance = match self.token.kind {
+                TokenKind::In => Some(Variance::In),
+                TokenKind::Out => Some(Variance::Out),
+                _ => None,
+            };
+            if variance.is_some() {
+                self.bump();
+            }
+            let name = self.parse_ident_or_missing();
+            params.push(TypeParameter { span: self.span_from(start), variance, name });
+
+            if !self.eat(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenKind::Gt, "`>`");
+
+        params
+    }
+
+    /// Parses a property of a class or module after its doc comment, annotations, and modifiers.
+    pub(crate) fn parse_class_property(
+        &mut self,
+        start: usize,
+        doc: Option<DocComment<'a>>,
+        annotations: Vec<'a, Annotation<'a>>,
+        modifiers: Modifiers<'a>,
+    ) -> ClassProperty<'a> {
+        let name = self.parse_ident_or_missing();
+        let ty = if self.eat(TokenKind::Colon) { Some(self.parse_type()) } else { None };
+
+        let mut value = None;
+        let mut bodies = self.ast.vec();
+        if self.eat(TokenKind::Eq) {
+            value = Some(self.parse_expr());
+        } else if self.at(TokenKind::LBrace) {
+            while self.at(TokenKind::LBrace) {
+                bodies.push(self.parse_object_body());
+            }
+        } else if ty.is_none() {
+            self.error_expected("`:`, `=`, or `{`");
+        }
+
+        ClassProperty {
+            span: self.span_from(start),
+            doc,
+            annotations,
+            modifiers,
+            name,
+            ty,
+            value,
+            bodies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+    use pkl_ast::*;
+
+    use crate::{parse_module, parse_module_header};
+
+    #[test]
+    fn full_header() {
+        let alloc = Allocator::default();
+        let source = r#"/// The app config
+open module com.example.App
+
+extends "base.pkl"
+
+import "pkl:json"
+import "./util.pkl" as u
+import* "configs/*.pkl"
+"#;
+        let result = parse_module_header(&alloc, source);
+        let header = result.node;
+
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert_eq!(header.doc.unwrap().lines.as_slice(), ["The app config"]);
+        assert!(header.modifiers.has(ModifierKind::Open));
+        let name: std::vec::Vec<_> = header.name.unwrap().parts.iter().map(|part| part.name).collect();
+        assert_eq!(name, ["com", "example", "App"]);
+
+        let extends = header.extends.unwrap();
+        assert_eq!((extends.kind, extends.uri.value), (ExtendsKind::Extends, "base.pkl"));
+
+        let imports: std::vec::Vec<_> = header
+            .imports
+            .iter()
+            .map(|import| (import.uri.value, import.glob, import.alias.map(|alias| alias.name)))
+            .collect();
+        assert_eq!(
+            imports,
+            [("pkl:json", false, None), ("./util.pkl", false, Some("u")), ("configs/*.pkl", true, None)]
+        );
+    }
+
+    #[test]
+    fn amends_without_module_clause() {
+        let alloc = Allocator::default();
+        let result = parse_module(&alloc, "amends \"template.pkl\"\n\nlocal x = 1\nname = \"a\"\n");
+        let module = result.node;
+
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert!(module.header.name.is_none());
+        assert_eq!(module.header.extends.unwrap().kind, ExtendsKind::Amends);
+        assert_eq!(module.members.len(), 2);
+        let ModuleMember::Property(local) = &module.members[0] else { unreachable!() };
+        assert!(local.modifiers.has(ModifierKind::Local));
+    }
+
+    #[test]
+    fn annotations() {
+        let alloc = Allocator::default();
+        let source = r#"@ModuleInfo { minPkl = "0.25" }
+module Example
+
+@Deprecated { message = "use `b`" }
+a = 1
+
+/// A thing
+@Unlisted
+@pkg.Custom
+class Thing {
+  @Deprecated hidden function f() = 1
+}
+"#;
+        let result = parse_module(&alloc, source);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let module = result.node;
+
+        let header: std::vec::Vec<_> = module.header.annotations.iter().map(|a| a.name.parts[0].name).collect();
+        assert_eq!(header, ["ModuleInfo"]);
+        assert_eq!(module.header.annotations[0].body.as_ref().unwrap().members.len(), 1);
+
+        let ModuleMember::Property(a) = &module.members[0] else { panic!() };
+        assert_eq!(a.annotations[0].name.parts[0].name, "Deprecated");
+
+        let ModuleMember::Class(thing) = &module.members[1] else { panic!() };
+        assert!(thing.doc.is_some());
+        let names: std::vec::Vec<_> = thing.annotations.iter().map(|a| a.name.parts.len()).collect();
+        assert_eq!(names, [1, 2]);
+        let ClassMember::Method(f) = &thing.members[0] else { panic!() };
+        assert!(f.modifiers.has(ModifierKind::Hidden) && f.annotations.len() == 1);
+    }
+
+    #[test]
+    fn annotations_on_first_member_stay_with_it() {
+        let alloc = Allocator::default();
+        let result = parse_module(&alloc, "@Deprecated { message = \"x\" } local a = 1");
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert!(result.node.header.annotations.is_empty());
+
+        let ModuleMember::Property(a) = &result.node.members[0] else { panic!() };
+        assert_eq!(a.annotations.len(), 1);
+        assert!(a.modifiers.has(ModifierKind::Local));
+    }
+
+    #[test]
+    fn classes_and_typealiases() {
+        let alloc = Allocator::default();
+        let source = r#"
+/// A person
+open class Person extends Base {
+  /// Their name
+  name: String
+  hidden age: Int = 42
+  function greet(other: Person): String = "hi \(other.name)"
+  abstract function pending()
+}
+
+abstract class Pair<out A, in B>
+
+typealias Labels<V> = Mapping<String, V>
+
+function double(x) = x * 2
+"#;
+        let result = parse_module(&alloc, source);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let members = &result.node.members;
+        assert_eq!(members.len(), 4);
+
+        let ModuleMember::Class(person) = &members[0] else { panic!() };
+        assert_eq!(person.name.name, "Person");
+        assert_eq!(person.doc.as_ref().unwrap().lines.as_slice(), ["A person"]);
+        assert!(person.modifiers.has(ModifierKind::Open));
+        assert!(person.extends.is_some());
+        assert_eq!(person.members.len(), 4);
+        let ClassMember::Property(name) = &person.members[0] else { panic!() };
+        assert_eq!(name.doc.as_ref().unwrap().lines.as_slice(), ["Their name"]);
+        assert!(name.ty.is_some() && name.value.is_none());
+        let ClassMember::Method(greet) = &person.members[2] else { panic!() };
+        assert_eq!((greet.name.name, greet.params.len()), ("greet", 1));
+        assert!(greet.return_type.is_some() && greet.body.is_some());
+        let ClassMember::Method(pending) = &person.members[3] else { panic!() };
+        assert!(pending.body.is_none());
+
+        let ModuleMember::Class(pair) = &members[1] else { panic!() };
+        let variances: std::vec::Vec<_> = pair.type_params.iter().map(|param| param.variance).collect();
+        assert_eq!(variances, [Some(Variance::Out), Some(Variance::In)]);
+        assert!(pair.members.is_empty());
+
+        let ModuleMember::TypeAlias(labels) = &members[2] else { panic!() };
+        assert_eq!((labels.name.name, labels.type_params.len()), ("Labels", 1));
+
+        let ModuleMember::Method(double) = &members[3] else { panic!() };
+        assert_eq!(double.span, pkl_ast::Span::new(source.find("function double").unwrap(), source.len() - 1));
+    }
+
+    #[test]
+    fn interpolated_import_uri_is_an_error() {
+        let alloc = Allocator::default();
+        let result = parse_module_header(&alloc, "import \"\\(x).pkl\"");
+
+        assert_eq!(result.diagnostics[0].message, "string constant may not contain interpolations");
+    }
+}
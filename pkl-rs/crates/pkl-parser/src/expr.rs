@@ -0,0 +1,668 @@
+use pkl_ast::*;
+use pkl_diagnostics::Code;
+use pkl_lexer::literal::{strip_multiline_indent, unescape, StringDelimiter};
+use pkl_lexer::token::{TokenKind, TokenValue};
+
+use crate::Parser;
+
+/// What an infix operator builds.
+#[derive(Clone, Copy)]
+enum Infix {
+    Binary(BinaryOp),
+    Is,
+    As,
+}
+
+/// Binding power of prefix `-` and `!`, which bind tighter than any infix operator.
+const PREFIX_POWER: u8 = 21;
+
+/// Returns the operator of an infix token with its left and right binding powers.
+///
+/// From loosest to tightest: `??` (right-associative), `|>`, `||`, `&&`, `==` and `!=`, `is` and `as`, comparisons,
+/// `+` and `-`, `*` `/` `~/` `%`, and `**` (right-associative).
+fn infix_power(kind: TokenKind) -> Option<(Infix, u8, u8)> {
+    use BinaryOp::*;
+
+    let (op, power) = match kind {
+        TokenKind::QuestionQuestion => return Some((Infix::Binary(NullCoalesce), 2, 1)),
+        TokenKind::PipeGt => (Infix::Binary(Pipe), 3),
+        TokenKind::OrOr => (Infix::Binary(Or), 5),
+        TokenKind::AndAnd => (Infix::Binary(And), 7),
+        TokenKind::EqEq => (Infix::Binary(Eq), 9),
+        TokenKind::NotEq => (Infix::Binary(NotEq), 9),
+        TokenKind::Is => (Infix::Is, 11),
+        TokenKind::As => (Infix::As, 11),
+        TokenKind::Lt => (Infix::Binary(Lt), 13),
+        TokenKind::LtEq => (Infix::Binary(LtEq), 13),
+        TokenKind::Gt => (Infix::Binary(Gt), 13),
+        TokenKind::GtEq => (Infix::Binary(GtEq), 13),
+        TokenKind::Plus => (Infix::Binary(Add), 15),
+        TokenKind::Minus => (Infix::Binary(Sub), 15),
+        TokenKind::Star => (Infix::Binary(Mul), 17),
+        TokenKind::Slash => (Infix::Binary(Div), 17),
+        TokenKind::TildeSlash => (Infix::Binary(IntDiv), 17),
+        TokenKind::Percent => (Infix::Binary(Rem), 17),
+        TokenKind::StarStar => return Some((Infix::Binary(Pow), 20, 19)),
+        _ => return None,
+    };
+
+    Some((op, power, power + 1))
+}
+
+impl<'a> Parser<'a> {
+    pub fn parse_expr(&mut self) -> Expr<'a> {
+        self.parse_expr_bp(0)
+    }
+
+    /// Parses an expression whose infix operators all bind tighter than `min_power`.
+    fn parse_expr_bp(&mut self, min_power: u8) -> Expr<'a> {
+        let start = self.token.span.start;
+        let depth = self.depth;
+        if !self.descend() {
+            self.depth = depth;
+            return Expr::Error(Span::new(start, start));
+        }
+        let mut left = self.parse_prefix();
+        let chained = self.chained;
+
+        while let Some((op, left_power, right_power)) = infix_power(self.token.kind) {
+            // a `-` starting a new line is an element of an object body, not a subtraction
+            if left_power < min_power || (self.at(TokenKind::Minus) && self.at_new_line()) {
+                break;
+            }
+            // a chain of operators like `1 + 1 + 1` is parsed by this loop rather than by recursion, so it isn't
+            // nested any deeper, only the right operand parsed below is; its length has a limit of its own
+            if !self.chain() {
+                break;
+            }
+            self.bump();
+
+            left = match op {
+                Infix::Binary(op) => {
+                    let right = self.parse_expr_bp(right_power);
+                    Expr::Binary(self.ast.boxed(BinaryExpr { span: self.span_from(start), op, left, right }))
+                }
+                Infix::Is | Infix::As => {
+                    let ty = self.parse_type();
+                    let test = self.ast.boxed(TypeTestExpr { span: self.span_from(start), value: left, ty });
+                    if let Infix::Is = op {
+                        Expr::Is(test)
+                    } else {
+                        Expr::As(test)
+                    }
+                }
+            };
+        }
+
+        self.depth = depth;
+        self.chained = chained;
+        left
+    }
+
+    fn parse_prefix(&mut self) -> Expr<'a> {
+        let start = self.token.span.start;
+        let op = match self.token.kind {
+            TokenKind::Minus => UnaryOp::Neg,
+            TokenKind::Bang => UnaryOp::Not,
+            _ => return self.parse_postfix(),
+        };
+
+        self.bump();
+        let operand = self.parse_expr_bp(PREFIX_POWER);
+        Expr::Unary(self.ast.boxed(UnaryExpr { span: self.span_from(start), op, operand }))
+    }
+
+    /// Parses a primary expression followed by member accesses, calls, subscripts, `!!`, and amending bodies.
+    fn parse_postfix(&mut self) -> Expr<'a> {
+        let start = self.token.span.start;
+        let depth = self.depth;
+        let mut expr = self.parse_primary();
+
+        loop {
+            expr = match self.token.kind {
+                TokenKind::Dot | TokenKind::QuestionDot => {
+                    let null_safe = self.bump().kind == TokenKind::QuestionDot;
+                    let name = self.parse_ident_or_missing();
+
+                    if self.at(TokenKind::LParen) && !self.at_new_line() {
+                        let args = self.parse_args();
+                        Expr::Call(self.ast.boxed(CallExpr {
+                            span: self.span_from(start),
+                            receiver: Some(expr),
+                            name,
+                            args,
+                            null_safe,
+                        }))
+                    } else {
+                        Expr::Member(self.ast.boxed(MemberExpr {
+                            span: self.span_from(start),
+                            receiver: expr,
+                            name,
+                            null_safe,
+                        }))
+                    }
+                }
+                TokenKind::LBracket if !self.at_new_line() => {
+                    self.bump();
+                    let index = self.parse_expr();
+                    self.expect(TokenKind::RBracket, "`]`");
+                    Expr::Subscript(self.ast.boxed(SubscriptExpr {
+                        span: self.span_from(start),
+                        receiver: Some(expr),
+                        index,
+                    }))
+                }
+                TokenKind::BangBang => {
+                    self.bump();
+                    Expr::NonNull(self.ast.boxed(NonNullExpr { span: self.span_from(start), operand: expr }))
+                }
+                // only parenthesized, `new`, and amend expressions can be amended directly
+                TokenKind::LBrace if matches!(expr, Expr::Parenthesized(_) | Expr::New(_) | Expr::Amend(_)) => {
+                    let body = self.parse_object_body();
+                    Expr::Amend(self.ast.boxed(AmendExpr { span: self.span_from(start), parent: expr, body }))
+                }
+                _ => {
+                    self.depth = depth;
+                    return expr;
+                }
+            };
+            // every access, call, or amendment nests the ones before it one level deeper
+            if !self.descend() {
+                self.depth = depth;
+                return expr;
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Expr<'a> {
+        let start = self.token.span.start;
+
+        match self.token.kind {
+            TokenKind::IntLiteral => {
+                let token = self.bump();
+                match token.value {
+                    TokenValue::Int(value) => Expr::Int(token.span, value),
+                    _ => Expr::Error(token.span),
+                }
+            }
+            TokenKind::FloatLiteral => {
+                let token = self.bump();
+                match token.value {
+                    TokenValue::Float(value) => Expr::Float(token.span, value),
+                    _ => Expr::Error(token.span),
+                }
+            }
+            TokenKind::True => Expr::Bool(self.bump().span, true),
+            TokenKind::False => Expr::Bool(self.bump().span, false),
+            TokenKind::Null => Expr::Null(self.bump().span),
+            TokenKind::This => Expr::This(self.bump().span),
+            TokenKind::Outer => Expr::Outer(self.bump().span),
+            TokenKind::Module => Expr::Module(self.bump().span),
+            TokenKind::StringLiteral | TokenKind::StringStart => Expr::String(self.parse_string()),
+            TokenKind::Identifier => {
+                let name = self.parse_ident_or_missing();
+                if self.at(TokenKind::LParen) && !self.at_new_line() {
+                    let args = self.parse_args();
+                    Expr::Call(self.ast.boxed(CallExpr {
+                        span: self.span_from(start),
+                        receiver: None,
+                        name,
+                        args,
+                        null_safe: false,
+                    }))
+                } else {
+                    Expr::Ident(name)
+                }
+            }
+            TokenKind::Super => self.parse_super(),
+            TokenKind::New => {
+                self.bump();
+                let ty = if self.at(TokenKind::LBrace) { None } else { Some(self.parse_type()) };
+                let body = self.parse_object_body();
+                Expr::New(self.ast.boxed(NewExpr { span: self.span_from(start), ty, body }))
+            }
+            TokenKind::LParen => {
+                if self.at_lambda() {
+                    return self.parse_lambda();
+                }
+                self.bump();
+                let expr = self.parse_expr();
+                self.expect(TokenKind::RParen, "`)`");
+                Expr::Parenthesized(self.ast.boxed(ParenthesizedExpr { span: self.span_from(start), expr }))
+            }
+            TokenKind::If => {
+                self.bump();
+                self.expect(TokenKind::LParen, "`(`");
+                let condition = self.parse_expr();
+                self.expect(TokenKind::RParen, "`)`");
+                let then = self.parse_expr();
+                self.expect(TokenKind::Else, "`else`");
+                let otherwise = self.parse_expr();
+                Expr::If(self.ast.boxed(IfExpr { span: self.span_from(start), condition, then, otherwise }))
+            }
+            TokenKind::Let => {
+                self.bump();
+                self.expect(TokenKind::LParen, "`(`");
+                let param = self.parse_parameter();
+                self.expect(TokenKind::Eq, "`=`");
+                let value = self.parse_expr();
+                self.expect(TokenKind::RParen, "`)`");
+                let body = self.parse_expr();
+                Expr::Let(self.ast.boxed(LetExpr { span: self.span_from(start), param, value, body }))
+            }
+            TokenKind::Throw | TokenKind::Trace => {
+                let is_throw = self.bump().kind == TokenKind::Throw;
+                self.expect(TokenKind::LParen, "`(`");
+                let value = self.parse_expr();
+                self.expect(TokenKind::RParen, "`)`");
+                let expr = self.ast.boxed(UnaryKeywordExpr { span: self.span_from(start), value });
+                if is_throw {
+                    Expr::Throw(expr)
+                } else {
+                    Expr::Trace(expr)
+                }
+            }
+            TokenKind::Import | TokenKind::ImportStar => {
+                let glob = self.bump().kind == TokenKind::ImportStar;
+                self.expect(TokenKind::LParen, "`(`");
+                let uri = self.parse_string_constant();
+                self.expect(TokenKind::RParen, "`)`");
+                Expr::Import(self.ast.boxed(ImportExpr { span: self.span_from(start), glob, uri }))
+            }
+            TokenKind::Read | TokenKind::ReadQuestion | TokenKind::ReadStar => {
+                let kind = match self.bump().kind {
+                    TokenKind::ReadQuestion => ReadKind::ReadOrNull,
+                    TokenKind::ReadStar => ReadKind::ReadGlob,
+                    _ => ReadKind::Read,
+                };
+                self.expect(TokenKind::LParen, "`(`");
+                let uri = self.parse_expr();
+                self.expect(TokenKind::RParen, "`)`");
+                Expr::Read(self.ast.boxed(ReadExpr { span: self.span_from(start), kind, uri }))
+            }
+            TokenKind::Error => Expr::Error(self.bump().span),
+            _ => {
+                self.error_expected("expression");
+                // leave closing delimiters to the construct they close
+                if matches!(
+                    self.token.kind,
+                    TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace | TokenKind::Eof
+                ) {
+                    Expr::Error(pkl_ast::Span::new(start, start))
+                } else {
+                    Expr::Error(self.bump().span)
+                }
+            }
+        }
+    }
+
+    /// Parses `super.name`, `super.name(args)`, or `super[index]`.
+    fn parse_super(&mut self) -> Expr<'a> {
+        let start = self.bump().span.start;
+
+        if self.eat(TokenKind::LBracket) {
+            let index = self.parse_expr();
+            self.expect(TokenKind::RBracket, "`]`");
+            let span = self.span_from(start);
+            return Expr::Subscript(self.ast.boxed(SubscriptExpr { span, receiver: None, index }));
+        }
+
+        self.expect(TokenKind::Dot, "`.` or `[` after `super`");
+        let name = self.parse_ident_or_missing();
+        let args = if self.at(TokenKind::LParen) && !self.at_new_line() { Some(self.parse_args()) } else { None };
+        Expr::Super(self.ast.boxed(SuperExpr { span: self.span_from(start), name, args }))
+    }
+
+    /// Parses a parenthesized, comma-separated argument list.
+    fn parse_args(&mut self) -> Vec<'a, Expr<'a>> {
+        self.expect(TokenKind::LParen, "`(`");
+        let mut args = self.ast.vec();
+
+        while !self.at(TokenKind::RParen) && !self.at(TokenKind::Eof) {
+            args.push(self.parse_expr());
+            if !self.eat(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.expect(TokenKind::RParen, "`)`");
+        args
+    }
+
+    /// Whether the `(` at the current token starts a lambda's parameter list, i.e. its matching `)` is followed by
+    /// `->`.
+    fn at_lambda(&mut self) -> bool {
+        // the current token is the `(`; once it's closed, the next token decides
+        let mut depth = 1usize;
+        let mut index = 0;
+
+        loop {
+            let token = self.lexer.peek_nth(index);
+            index += 1;
+            match token.kind {
+                kind if kind.is_trivia() || kind == TokenKind::DocComment => {}
+                kind if depth == 0 => return kind == TokenKind::Arrow,
+                TokenKind::LParen => depth += 1,
+                TokenKind::RParen => depth -= 1,
+                TokenKind::Eof => return false,
+                _ => {}
+            }
+        }
+    }
+
+    /// Parses `(params) -> body`.
+    fn parse_lambda(&mut self) -> Expr<'a> {
+        let start = self.token.span.start;
+        let params = self.parse_parameter_list();
+        self.expect(TokenKind::Arrow, "`->`");
+        let body = self.parse_expr();
+
+        Expr::Lambda(self.ast.boxed(LambdaExpr { span: self.span_from(start), params, body }))
+    }
+
+    /// Parses a parenthesized, comma-separated parameter list.
+    pub(crate) fn parse_parameter_list(&mut self) -> Vec<'a, Parameter<'a>> {
+        self.expect(TokenKind::LParen, "`(`");
+        let mut params = self.ast.vec();
+
+        while !self.at(TokenKind::RParen) && !self.at(TokenKind::Eof) {
+            params.push(self.parse_parameter());
+            if !self.eat(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.expect(TokenKind::RParen, "`)`");
+        params
+    }
+
+    /// Parses `name`, `name: Type`, or `_`.
+    pub(crate) fn parse_parameter(&mut self) -> Parameter<'a> {
+        let start = self.token.span.start;
+        let name = match self.token.value {
+            TokenValue::Identifier("_") if self.source.as_bytes()[start] != b'`' => {
+                self.bump();
+                None
+            }
+            _ => Some(self.parse_ident_or_missing()),
+        };
+        let ty = if self.eat(TokenKind::Colon) { Some(self.parse_type()) } else { None };
+
+        Parameter { span: self.span_from(start), name, ty }
+    }
+
+    /// Parses a string literal, which may be split into parts by interpolations.
+    fn parse_string(&mut self) -> Box<'a, StringLiteral<'a>> {
+        let first = self.bump();
+        let start = first.span.start;
+        let text = &self.source[first.span.start..first.span.end];
+        let delimiter = StringDelimiter::from_opening(text);
+        let mut parts = self.ast.vec();
+
+        if first.kind == TokenKind::StringLiteral {
+            let value = match first.value {
+                TokenValue::String(value) => value,
+                // a malformed multiline string, which the lexer already reported
+                _ => "",
+            };
+            if !value.is_empty() {
+                parts.push(StringPart::Text(first.span, value));
+            }
+            return self.ast.boxed(StringLiteral { span: first.span, multiline: delimiter.multiline, parts });
+        }
+
+        // the raw text of every part, for stripping the indentation of multiline strings
+        let mut raw = vec![(first.span, &text[delimiter.width()..], first.value)];
+
+        loop {
+            match self.token.kind {
+                TokenKind::InterpolationStart => {
+                    // an interpolation right after another one has no text in between
+                    if raw.len() == parts.len() {
+                        let at = self.token.span.start;
+                        raw.push((pkl_ast::Span::new(at, at), "", TokenValue::String("")));
+                    }
+                    self.bump();
+                    let expr = self.parse_expr();
+                    parts.push(StringPart::Interpolation(expr));
+                    if !self.expect(TokenKind::InterpolationEnd, "`)`") {
+                        self.skip_to_string_end();
+                        break;
+                    }
+                }
+                TokenKind::StringPart | TokenKind::StringEnd => {
+                    let token = self.bump();
+                    let mut text = &self.source[token.span.start..token.span.end];
+                    if token.kind == TokenKind::StringEnd {
+                        text = &text[..text.len() - delimiter.width()];
+                    }
+                    raw.push((token.span, text, token.value));
+                    if token.kind == TokenKind::StringEnd {
+                        break;
+                    }
+                }
+                // unterminated, which the lexer already reported
+                _ => break,
+            }
+        }
+
+        let texts: std::vec::Vec<_> = if delimiter.multiline {
+            let raw_texts: std::vec::Vec<_> = raw.iter().map(|(_, text, _)| *text).collect();
+            match strip_multiline_indent(&raw_texts) {
+                Ok(stripped) => {
+                    stripped.iter().map(|text| self.ast.str(&unescape(text, delimiter.pounds))).collect()
+                }
+                Err(error) => {
+                    self.error(Code::InvalidMultilineString, self.span_from(start), error.to_string());
+                    raw_texts
+                }
+            }
+        } else {
+            raw.iter()
+                .map(|(_, text, value)| match value {
+                    TokenValue::String(value) => *value,
+                    _ => *text,
+                })
+                .collect()
+        };
+
+        // interleave the texts with the interpolations, which are already in `parts`
+        let interpolations = std::mem::replace(&mut parts, self.ast.vec());
+        let mut texts = raw.iter().zip(texts);
+        if let Some(((span, ..), text)) = texts.next() {
+            if !text.is_empty() {
+                parts.push(StringPart::Text(*span, text));
+            }
+        }
+        for (interpolation, ((span, ..), text)) in interpolations.into_iter().zip(texts.by_ref()) {
+            parts.push(interpolation);
+            if !text.is_empty() {
+                parts.push(StringPart::Text(*span, text));
+            }
+        }
+
+        self.ast.boxed(StringLiteral { span: self.span_from(start), multiline: delimiter.multiline, parts })
+    }
+
+    /// Skips the rest of a string whose interpolation is malformed.
+    fn skip_to_string_end(&mut self) {
+        while !matches!(self.token.kind, TokenKind::StringEnd | TokenKind::Eof) {
+            self.bump();
+        }
+        self.eat(TokenKind::StringEnd);
+    }
+
+    /// Parses a string literal that may not contain interpolations, like a module URI.
+    pub(crate) fn parse_string_constant(&mut self) -> StringConstant<'a> {
+        let start = self.token.span.start;
+
+        if self.at(TokenKind::StringLiteral) {
+            let token = self.bump();
+            let value = match token.value {
+                TokenValue::String(value) => value,
+                _ => "",
+            };
+            return StringConstant { span: token.span, value };
+        }
+
+        if self.at(TokenKind::StringStart) {
+            let literal = self.parse_string();
+            self.error(Code::InterpolatedConstant, literal.span, "string constant may not contain interpolations");
+            return StringConstant { span: literal.span, value: "" };
+        }
+
+        self.error_expected("string literal");
+        StringConstant { span: pkl_ast::Span::new(start, start), value: "" }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::parse_expr;
+
+    /// Renders an expression as an S-expression, to check the structure of parsed trees.
+    pub(crate) fn sexp(expr: &Expr) -> String {
+        match expr {
+            Expr::Null(_) => "null".into(),
+            Expr::Bool(_, value) => value.to_string(),
+            Expr::Int(_, value) => value.to_string(),
+            Expr::Float(_, value) => format!("{value:?}"),
+            Expr::String(literal) => {
+                let parts: std::vec::Vec<_> = literal
+                    .parts
+                    .iter()
+                    .map(|part| match part {
+                        StringPart::Text(_, text) => format!("{text:?}"),
+                        StringPart::Interpolation(expr) => sexp(expr),
+                    })
+                    .collect();
+                format!("(str {})", parts.join(" "))
+            }
+            Expr::This(_) => "this".into(),
+            Expr::Outer(_) => "outer".into(),
+            Expr::Module(_) => "module".into(),
+            Expr::Ident(ident) => ident.name.into(),
+            Expr::Member(member) => {
+                let op = if member.null_safe { "?." } else { "." };
+                format!("({op} {} {})", sexp(&member.receiver), member.name.name)
+            }
+            Expr::Call(call) => {
+                let mut out = format!("(call {}", call.name.name);
+                if let Some(receiver) = &call.receiver {
+                    out = format!("(call {} {}", sexp(receiver), call.name.name);
+                }
+                for arg in call.args.iter() {
+                    out.push(' ');
+                    out.push_str(&sexp(arg));
+                }
+                out + ")"
+            }
+            Expr::Super(expr) => format!("(super {})", expr.name.name),
+            Expr::Subscript(expr) => match &expr.receiver {
+                Some(receiver) => format!("([] {} {})", sexp(receiver), sexp(&expr.index)),
+                None => format!("([] super {})", sexp(&expr.index)),
+            },
+            Expr::Unary(expr) => {
+                let op = if expr.op == UnaryOp::Neg { "-" } else { "!" };
+                format!("({op} {})", sexp(&expr.operand))
+            }
+            Expr::NonNull(expr) => format!("(!! {})", sexp(&expr.operand)),
+            Expr::Binary(expr) => format!("({} {} {})", expr.op.as_str(), sexp(&expr.left), sexp(&expr.right)),
+            Expr::Is(expr) => format!("(is {})", sexp(&expr.value)),
+            Expr::As(expr) => format!("(as {})", sexp(&expr.value)),
+            Expr::If(expr) => {
+                format!("(if {} {} {})", sexp(&expr.condition), sexp(&expr.then), sexp(&expr.otherwise))
+            }
+            Expr::Let(expr) => format!("(let {} {})", sexp(&expr.value), sexp(&expr.body)),
+            Expr::Lambda(expr) => format!("(lambda {} {})", expr.params.len(), sexp(&expr.body)),
+            Expr::New(expr) => format!("(new {})", expr.body.members.len()),
+            Expr::Amend(expr) => format!("(amend {} {})", sexp(&expr.parent), expr.body.members.len()),
+            Expr::Throw(expr) => format!("(throw {})", sexp(&expr.value)),
+            Expr::Trace(expr) => format!("(trace {})", sexp(&expr.value)),
+            Expr::Import(expr) => format!("(import {:?})", expr.uri.value),
+            Expr::Read(expr) => format!("(read {})", sexp(&expr.uri)),
+            Expr::Parenthesized(expr) => sexp(&expr.expr),
+            Expr::Error(_) => "error".into(),
+        }
+    }
+
+    fn parse(source: &str) -> String {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, source);
+        assert!(result.diagnostics.is_empty(), "{source}: {:?}", result.diagnostics);
+        sexp(&result.node)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(parse("1 + 2 * 3"), "(+ 1 (* 2 3))");
+        assert_eq!(parse("1 * 2 + 3"), "(+ (* 1 2) 3)");
+        assert_eq!(parse("a || b && c == d"), "(|| a (&& b (== c d)))");
+        assert_eq!(parse("a < b == c > d"), "(== (< a b) (> c d))");
+        assert_eq!(parse("a ?? b || c"), "(?? a (|| b c))");
+        assert_eq!(parse("a |> b ?? c"), "(?? (|> a b) c)");
+        assert_eq!(parse("(1 + 2) * 3"), "(* (+ 1 2) 3)");
+        assert_eq!(parse("a ~/ b % c"), "(% (~/ a b) c)");
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(parse("1 - 2 - 3"), "(- (- 1 2) 3)");
+        assert_eq!(parse("2 ** 3 ** 2"), "(** 2 (** 3 2))");
+        assert_eq!(parse("a ?? b ?? c"), "(?? a (?? b c))");
+    }
+
+    #[test]
+    fn unary_operators() {
+        assert_eq!(parse("-a * b"), "(* (- a) b)");
+        assert_eq!(parse("!a && b"), "(&& (! a) b)");
+        assert_eq!(parse("- -a.b"), "(- (- (. a b)))");
+        assert_eq!(parse("a!!.b"), "(. (!! a) b)");
+    }
+
+    #[test]
+    fn member_access_calls_and_subscripts() {
+        assert_eq!(parse("a.b.c"), "(. (. a b) c)");
+        assert_eq!(parse("a?.b"), "(?. a b)");
+        assert_eq!(parse("f(1, g(2))"), "(call f 1 (call g 2))");
+        assert_eq!(parse("list.map((x) -> x + 1)"), "(call list map (lambda 1 (+ x 1)))");
+        assert_eq!(parse("a[0][\"k\"]"), "([] ([] a 0) (str \"k\"))");
+        assert_eq!(parse("super.foo"), "(super foo)");
+        assert_eq!(parse("super[1]"), "([] super 1)");
+    }
+
+    #[test]
+    fn keyword_expressions() {
+        assert_eq!(parse("if (a) 1 else 2"), "(if a 1 2)");
+        assert_eq!(parse("let (x = 1) x + 1"), "(let 1 (+ x 1))");
+        assert_eq!(parse("throw(\"no\")"), "(throw (str \"no\"))");
+        assert_eq!(parse("import(\"a.pkl\").foo"), "(. (import \"a.pkl\") foo)");
+        assert_eq!(parse("read?(\"env:HOME\")"), "(read (str \"env:HOME\"))");
+        assert_eq!(parse("() -> 1"), "(lambda 0 1)");
+    }
+
+    #[test]
+    fn new_and_amend_expressions() {
+        assert_eq!(parse("new Listing<String> { \"a\" \"b\" }"), "(new 2)");
+        assert_eq!(parse("new { x = 1 }"), "(new 1)");
+        assert_eq!(parse("(foo) { bar = 1 } { baz = 2 }"), "(amend (amend foo 1) 1)");
+    }
+
+    #[test]
+    fn string_interpolation() {
+        assert_eq!(parse(r#""a\(b)c\("d")""#), r#"(str "a" b "c" (str "d"))"#);
+        assert_eq!(parse("\"\"\"\n  x\\(1)\n  y\n  \"\"\""), "(str \"x\" 1 \"\\ny\")");
+        assert_eq!(parse("\"\\(1)\\(2)!\""), "(str 1 2 \"!\")");
+        assert_eq!(parse(r#""\t""#), r#"(str "\t")"#);
+    }
+
+    #[test]
+    fn missing_operand() {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, "1 + )");
+
+        assert_eq!(sexp(&result.node), "(+ 1 error)");
+        assert_eq!(result.diagnostics[0].message, "expected expression, found `)`");
+    }
+}
@@ -0,0 +1,328 @@
+//! Reparsing a module after an edit, reusing the members the edit can't have changed.
+
+use oxc_allocator::Allocator;
+use pkl_ast::visit::{walk_mut, VisitMut};
+use pkl_ast::*;
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_lexer::incremental::Edit;
+use pkl_lexer::token::TokenKind;
+
+use crate::{parse_module, ParseResult, Parser};
+
+impl<'a> Parser<'a> {
+    /// Parses `source` like [`parse_module`], given the result of parsing the text it was before `edit`.
+    ///
+    /// Members of the module that end before the edit are kept as they are, and parsing starts after them. Once it
+    /// arrives at the start of a member that followed the edit in the old text, that member and the ones after it are
+    /// reused too, with their spans moved by the length the edit added or removed. Members with diagnostics aren't
+    /// reused, since the parser's recovery from an error may depend on what follows it.
+    pub fn reparse(
+        alloc: &'a Allocator,
+        source: &'a str,
+        old: ParseResult<Module<'a>>,
+        edit: Edit,
+    ) -> ParseResult<Module<'a>> {
+        let ParseResult { node: Module { header, members: old_members, .. }, diagnostics: old_diagnostics } = old;
+        let spans: std::vec::Vec<Span> = old_members.iter().map(ModuleMember::span).collect();
+        let docs: std::vec::Vec<Option<Span>> = old_members.iter().map(doc_span).collect();
+        let diagnosed = |range: std::ops::Range<usize>| {
+            old_diagnostics.iter().any(|diagnostic| range.contains(&diagnostic.span.start))
+        };
+
+        // like a token, a member is only kept if the one after it ends before the edit too, since the parser may have
+        // looked at the next few tokens to find out where it ends
+        let kept = (0..spans.len())
+            .take_while(|&i| i + 1 < spans.len() && spans[i + 1].end < edit.start)
+            .take_while(|&i| !diagnosed(spans[i].start..spans[i + 1].start))
+            .count();
+        if kept == 0 {
+            return parse_module(alloc, source);
+        }
+        let restart = spans[kept - 1].end;
+
+        let mut parser = Parser::starting_at(alloc, source, restart);
+        let mut old_members = old_members.into_iter();
+        let mut members = parser.ast.vec_from_iter(old_members.by_ref().take(kept));
+
+        // the old member the parser arrived at, from which on the old members are reused
+        let mut resumed = None;
+        while !parser.at(TokenKind::Eof) {
+            let start = parser.token.span.start;
+            let doc = parser.docs.first().map(|first| first.cover(*parser.docs.last().unwrap_or(first)));
+            resumed = start
+                .checked_sub(edit.new_end)
+                .filter(|&offset| offset > 0)
+                .and_then(|offset| spans.binary_search_by_key(&(edit.old_end + offset), |span| span.start).ok())
+                .filter(|&i| i >= kept && docs[i].map(|doc| edit.shift_span(doc)) == doc)
+                .filter(|&i| !diagnosed(spans[i].start..spans[i].start + 1));
+            if resumed.is_some() {
+                break;
+            }
+
+            let errors = parser.diagnostics.len();
+            if let Some(member) = parser.parse_module_member() {
+                members.push(member);
+            }
+            if parser.diagnostics.len() > errors {
+                parser.synchronize(start);
+            }
+        }
+
+        let mut end = usize::MAX;
+        let mut reused_diagnostics = std::vec::Vec::new();
+        if let Some(i) = resumed {
+            end = edit.shift(spans[i].start);
+            for mut member in old_members.skip(i - kept) {
+                Shift(edit).visit_module_member(&mut member);
+                members.push(member);
+            }
+            reused_diagnostics = old_diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.span.start >= spans[i].start)
+                .map(|diagnostic| Diagnostic { span: edit.shift_span(diagnostic.span), ..diagnostic.clone() })
+                .collect();
+        }
+
+        let module = Module { span: Span::new(0, source.len()), header, members };
+        let ParseResult { node, diagnostics: new_diagnostics } = parser.finish(module);
+        // the lexer may have looked ahead of where parsing stopped, so its diagnostics from there on are duplicates
+        let diagnostics = old_diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.span.start < restart)
+            .chain(new_diagnostics.into_iter().filter(|diagnostic| diagnostic.span.start <= end))
+            .chain(reused_diagnostics)
+            .collect();
+
+        ParseResult { node, diagnostics }
+    }
+}
+
+fn doc_span(member: &ModuleMember) -> Option<Span> {
+    let doc = match member {
+        ModuleMember::Class(class) => &class.doc,
+        ModuleMember::TypeAlias(alias) => &alias.doc,
+        ModuleMember::Property(property) => &property.doc,
+        ModuleMember::Method(method) => &method.doc,
+    };
+    doc.as_ref().map(|doc| doc.span)
+}
+
+/// Moves every span of a tree that followed an edit to where it is after the edit.
+struct Shift(Edit);
+
+impl Shift {
+    fn span(&self, span: &mut Span) {
+        *span = self.0.shift_span(*span);
+    }
+
+    fn declaration(&self, span: &mut Span, doc: &mut Option<DocComment>, modifiers: &mut Modifiers) {
+        self.span(span);
+        if let Some(doc) = doc {
+            self.span(&mut doc.span);
+        }
+        self.modifiers(modifiers);
+    }
+
+    fn modifiers(&self, modifiers: &mut Modifiers) {
+        for modifier in modifiers.0.iter_mut() {
+            self.span(&mut modifier.span);
+        }
+    }
+}
+
+impl<'a> VisitMut<'a> for Shift {
+    fn visit_annotation(&mut self, annotation: &mut Annotation<'a>) {
+        self.span(&mut annotation.span);
+        walk_mut::walk_annotation(self, annotation);
+    }
+
+    fn visit_class(&mut self, class: &mut ClassDecl<'a>) {
+        self.declaration(&mut class.span, &mut class.doc, &mut class.modifiers);
+        walk_mut::walk_class(self, class);
+    }
+
+    fn visit_class_property(&mut self, property: &mut ClassProperty<'a>) {
+        self.declaration(&mut property.span, &mut property.doc, &mut property.modifiers);
+        walk_mut::walk_class_property(self, property);
+    }
+
+    fn visit_class_method(&mut self, method: &mut ClassMethod<'a>) {
+        self.declaration(&mut method.span, &mut method.doc, &mut method.modifiers);
+        walk_mut::walk_class_method(self, method);
+    }
+
+    fn visit_type_alias(&mut self, alias: &mut TypeAlias<'a>) {
+        self.declaration(&mut alias.span, &mut alias.doc, &mut alias.modifiers);
+        walk_mut::walk_type_alias(self, alias);
+    }
+
+    fn visit_type_parameter(&mut self, param: &mut TypeParameter<'a>) {
+        self.span(&mut param.span);
+        walk_mut::walk_type_parameter(self, param);
+    }
+
+    fn visit_parameter(&mut self, param: &mut Parameter<'a>) {
+        self.span(&mut param.span);
+        walk_mut::walk_parameter(self, param);
+    }
+
+    fn visit_object_body(&mut self, body: &mut ObjectBody<'a>) {
+        self.span(&mut body.span);
+        walk_mut::walk_object_body(self, body);
+    }
+
+    fn visit_object_property(&mut self, property: &mut ObjectProperty<'a>) {
+        self.declaration(&mut property.span, &mut property.doc, &mut property.modifiers);
+        walk_mut::walk_object_property(self, property);
+    }
+
+    fn visit_object_method(&mut self, method: &mut ObjectMethod<'a>) {
+        self.declaration(&mut method.span, &mut method.doc, &mut method.modifiers);
+        walk_mut::walk_object_method(self, method);
+    }
+
+    fn visit_object_entry(&mut self, entry: &mut ObjectEntry<'a>) {
+        self.span(&mut entry.span);
+        walk_mut::walk_object_entry(self, entry);
+    }
+
+    fn visit_object_element(&mut self, element: &mut ObjectElement<'a>) {
+        self.span(&mut element.span);
+        walk_mut::walk_object_element(self, element);
+    }
+
+    fn visit_object_member_predicate(&mut self, predicate: &mut ObjectMemberPredicate<'a>) {
+        self.span(&mut predicate.span);
+        walk_mut::walk_object_member_predicate(self, predicate);
+    }
+
+    fn visit_object_spread(&mut self, spread: &mut ObjectSpread<'a>) {
+        self.span(&mut spread.span);
+        walk_mut::walk_object_spread(self, spread);
+    }
+
+    fn visit_for_generator(&mut self, generator: &mut ForGenerator<'a>) {
+        self.span(&mut generator.span);
+        walk_mut::walk_for_generator(self, generator);
+    }
+
+    fn visit_when_generator(&mut self, generator: &mut WhenGenerator<'a>) {
+        self.span(&mut generator.span);
+        walk_mut::walk_when_generator(self, generator);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr<'a>) {
+        match expr {
+            Expr::Null(span)
+            | Expr::Bool(span, _)
+            | Expr::Int(span, _)
+            | Expr::Float(span, _)
+            | Expr::This(span)
+            | Expr::Outer(span)
+            | Expr::Module(span)
+            | Expr::Error(span) => self.span(span),
+            // visited by `visit_string_literal` and `visit_ident`
+            Expr::String(_) | Expr::Ident(_) => {}
+            Expr::Member(expr) => self.span(&mut expr.span),
+            Expr::Call(expr) => self.span(&mut expr.span),
+            Expr::Super(expr) => self.span(&mut expr.span),
+            Expr::Subscript(expr) => self.span(&mut expr.span),
+            Expr::Unary(expr) => self.span(&mut expr.span),
+            Expr::NonNull(expr) => self.span(&mut expr.span),
+            Expr::Binary(expr) => self.span(&mut expr.span),
+            Expr::Is(expr) | Expr::As(expr) => self.span(&mut expr.span),
+            Expr::If(expr) => self.span(&mut expr.span),
+            Expr::Let(expr) => self.span(&mut expr.span),
+            Expr::Lambda(expr) => self.span(&mut expr.span),
+            Expr::New(expr) => self.span(&mut expr.span),
+            Expr::Amend(expr) => self.span(&mut expr.span),
+            Expr::Throw(expr) | Expr::Trace(expr) => self.span(&mut expr.span),
+            Expr::Import(expr) => {
+                self.span(&mut expr.span);
+                self.span(&mut expr.uri.span);
+            }
+            Expr::Read(expr) => self.span(&mut expr.span),
+            Expr::Parenthesized(expr) => self.span(&mut expr.span),
+        }
+        walk_mut::walk_expr(self, expr);
+    }
+
+    fn visit_string_literal(&mut self, literal: &mut StringLiteral<'a>) {
+        self.span(&mut literal.span);
+        for part in literal.parts.iter_mut() {
+            if let StringPart::Text(span, _) = part {
+                self.span(span);
+            }
+        }
+        walk_mut::walk_string_literal(self, literal);
+    }
+
+    fn visit_type(&mut self, ty: &mut Type<'a>) {
+        match ty {
+            Type::Unknown(span) | Type::Nothing(span) | Type::Module(span) => self.span(span),
+            Type::StringLiteral(literal) => self.span(&mut literal.span),
+            Type::Named(ty) => self.span(&mut ty.span),
+            Type::Nullable(ty) => self.span(&mut ty.span),
+            Type::Union(ty) => self.span(&mut ty.span),
+            Type::Function(ty) => self.span(&mut ty.span),
+            Type::Constrained(ty) => self.span(&mut ty.span),
+            Type::Parenthesized(ty) => self.span(&mut ty.span),
+        }
+        walk_mut::walk_type(self, ty);
+    }
+
+    fn visit_qualified_name(&mut self, name: &mut QualifiedName<'a>) {
+        self.span(&mut name.span);
+        walk_mut::walk_qualified_name(self, name);
+    }
+
+    fn visit_ident(&mut self, ident: &mut Ident<'a>) {
+        self.span(&mut ident.span);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Checks that reparsing after replacing `old[start..old_end]` with `text` matches parsing the new source.
+    fn check(old: &str, start: usize, old_end: usize, text: &str) {
+        let alloc = Allocator::default();
+        let new = format!("{}{text}{}", &old[..start], &old[old_end..]);
+        let edit = Edit::new(start, old_end, start + text.len());
+        let reparsed = Parser::reparse(&alloc, &new, parse_module(&alloc, old), edit);
+
+        assert_eq!(format!("{reparsed:#?}"), format!("{:#?}", parse_module(&alloc, &new)), "{new:?}");
+    }
+
+    #[test]
+    fn reparsing_matches_parsing() {
+        let source = "import \"a.pkl\"\n\
+                      \n\
+                      foo = 1\n\
+                      /// Bar.\n\
+                      bar: String = \"x \\(foo)\"\n\
+                      class Baz {\n\
+                      qux: Listing<Int>(!isEmpty)\n\
+                      }\n\
+                      typealias T = \"a\" | \"b\"\n\
+                      function f(x) = new { [x] = x + 1 }\n";
+        let bar = source.find("bar").unwrap();
+        check(source, bar - 9, bar - 9, "baz = 2\n");
+        check(source, bar + 16, bar + 17, "yz");
+        check(source, bar, bar, "/// More.\n");
+        let class = source.find("class").unwrap();
+        check(source, class + 12, class + 12, "a = 1\n");
+        check(source, class - 1, class, "");
+        check(source, source.len() - 5, source.len() - 2, "");
+    }
+
+    #[test]
+    fn errors_are_reported_once() {
+        let source = "a = 1\nb = 2\nc = (\nd = 4\ne = \"\ng = 5\n";
+        let d = source.find('d').unwrap();
+        check(source, d + 4, d + 5, "44");
+        check(source, d - 2, d - 1, ")");
+        check(source, source.len(), source.len(), "h = ]\n");
+    }
+}
@@ -0,0 +1,467 @@
+//! Parses Pkl source text into a [`pkl_ast`] tree.
+//!
+//! The parser pulls tokens from a [`Lexer`] one at a time and looks ahead through the lexer's buffer when it has to
+//! tell ambiguous constructs apart, like a lambda from a parenthesized expression. Problems are reported as
+//! [`Diagnostic`]s rather than aborting the parse.
+//!
+//! Every entry point takes arbitrary text, so untrusted input can be parsed: parsing always ends, and never panics.
+//! The fuzz targets of `fuzz/` check this, with `cargo fuzz run parse`.
+
+#![forbid(unsafe_code)]
+
+mod decl;
+mod expr;
+mod incremental;
+mod object;
+mod types;
+
+use oxc_allocator::Allocator;
+use pkl_ast::{AstBuilder, DocComment, Expr, Ident, Module, ModuleHeader};
+use pkl_diagnostics::Code;
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_lexer::token::{Span, Token, TokenKind, TokenValue};
+use pkl_lexer::Lexer;
+
+/// The result of parsing: the tree, along with every problem found in the source.
+///
+/// The tree is always complete; parts that couldn't be parsed are represented by error nodes.
+#[derive(Debug)]
+pub struct ParseResult<T> {
+    pub node: T,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Parses a single expression, such as the argument of `pkl eval -x`.
+pub fn parse_expr<'a>(alloc: &'a Allocator, source: &'a str) -> ParseResult<Expr<'a>> {
+    let mut parser = Parser::new(alloc, source);
+    let expr = parser.parse_expr();
+    if parser.token.kind != TokenKind::Eof {
+        parser.error_expected("end of input");
+    }
+
+    parser.finish(expr)
+}
+
+/// Parses a whole module.
+pub fn parse_module<'a>(alloc: &'a Allocator, source: &'a str) -> ParseResult<Module<'a>> {
+    parse_module_with_depth(alloc, source, DEFAULT_MAX_DEPTH)
+}
+
+/// Parses a whole module like [`parse_module`], whose expressions, types, and object bodies may be nested at most
+/// `max_depth` levels deep.
+pub fn parse_module_with_depth<'a>(alloc: &'a Allocator, source: &'a str, max_depth: usize) -> ParseResult<Module<'a>> {
+    let mut parser = Parser::new(alloc, source);
+    parser.max_depth = max_depth;
+    let module = parser.parse_module();
+
+    parser.finish(module)
+}
+
+/// Parses only the header of a module: its name, the module it amends or extends, and its imports.
+///
+/// This is much cheaper than parsing the whole module when only its dependencies are needed.
+pub fn parse_module_header<'a>(alloc: &'a Allocator, source: &'a str) -> ParseResult<ModuleHeader<'a>> {
+    let mut parser = Parser::new(alloc, source);
+    let header = parser.parse_module_header();
+
+    parser.finish(header)
+}
+
+/// How deeply expressions, types, and object bodies may be nested by default. Deeper sources are rejected, so that
+/// neither the parser nor whatever walks the tree it returns can overflow the stack.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// How many operators can be chained like `1 + 1 + 1`, counting those of the chains a chain is in. A chain doesn't
+/// nest the source any deeper, but the operations in it nest to the left in the tree, so a longer one is rejected for
+/// the sake of whatever walks the tree.
+const MAX_CHAINED: usize = 1024;
+
+pub struct Parser<'a> {
+    ast: AstBuilder<'a>,
+    source: &'a str,
+    lexer: Lexer<'a>,
+    /// The current token, which is never trivia
+    token: Token<'a>,
+    /// End of the previous token, used for spans of nodes and for detecting line breaks
+    prev_end: usize,
+    /// Spans of the doc comment lines directly preceding the current token
+    docs: Vec<Span>,
+    /// Doc comment lines found while looking for the next token
+    pending_docs: Vec<Span>,
+    diagnostics: Vec<Diagnostic>,
+    /// How deeply the node being parsed is nested
+    depth: usize,
+    max_depth: usize,
+    /// How many operators are chained in the expressions the node being parsed is in
+    chained: usize,
+    /// Whether the source was nested too deeply, after which the rest of it is skipped
+    too_deep: bool,
+    /// The indices in the lexer's lookahead of the tokens after the current one that [`Parser::nth`] has found, so
+    /// that looking far ahead one token at a time doesn't scan from the start every time
+    ahead: Vec<usize>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(alloc: &'a Allocator, source: &'a str) -> Self {
+        Parser::starting_at(alloc, source, 0)
+    }
+
+    /// Creates a parser that starts at byte `pos` of the source, which has to be between two tokens outside of any
+    /// string.
+    fn starting_at(alloc: &'a Allocator, source: &'a str, pos: usize) -> Self {
+        let mut lexer = Lexer::new(alloc, source);
+        lexer.source.set_pos(pos);
+        let mut parser = Parser {
+            ast: AstBuilder::new(alloc),
+            source,
+            lexer,
+            token: Token::default(),
+            prev_end: pos,
+            docs: Vec::new(),
+            pending_docs: Vec::new(),
+            diagnostics: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            chained: 0,
+            too_deep: false,
+            ahead: Vec::new(),
+        };
+        parser.token = parser.next_significant();
+        parser.docs = std::mem::take(&mut parser.pending_docs);
+        parser
+    }
+
+    /// Combines the parser's diagnostics with the lexer's, in source order.
+    fn finish<T>(mut self, node: T) -> ParseResult<T> {
+        let mut diagnostics = self.lexer.diagnostics().to_vec();
+        diagnostics.append(&mut self.diagnostics);
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+
+        ParseResult { node, diagnostics }
+    }
+
+    /// Pulls the next token that isn't trivia from the lexer, collecting doc comments along the way.
+    fn next_significant(&mut self) -> Token<'a> {
+        self.pending_docs.clear();
+        self.ahead.clear();
+
+        for token in self.lexer.by_ref() {
+            if token.kind == TokenKind::DocComment {
+                self.pending_docs.push(token.span);
+            } else if !token.kind.is_trivia() {
+                return token;
+            }
+        }
+
+        Token {
+            kind: TokenKind::Eof,
+            span: Span::new(self.source.len(), self.source.len()),
+            value: TokenValue::None,
+        }
+    }
+
+    /// Moves on to the next token, returning the current one.
+    fn bump(&mut self) -> Token<'a> {
+        let token = self.token;
+        self.prev_end = token.span.end;
+        if token.kind != TokenKind::Eof {
+            self.token = self.next_significant();
+            std::mem::swap(&mut self.docs, &mut self.pending_docs);
+        }
+        token
+    }
+
+    /// Takes the doc comment preceding the current token, if any.
+    fn take_doc(&mut self) -> Option<DocComment<'a>> {
+        let first = *self.docs.first()?;
+        let last = *self.docs.last()?;
+        let lines = self.ast.vec_from_iter(self.docs.drain(..).map(|span| {
+            let text = &self.source[span.start + 3..span.end];
+            text.strip_prefix(' ').unwrap_or(text)
+        }));
+
+        Some(DocComment { span: first.cover(last), lines })
+    }
+
+    /// The kind of the token `n` tokens after the current one (`0` being the current one).
+    fn nth(&mut self, n: usize) -> TokenKind {
+        if n == 0 {
+            return self.token.kind;
+        }
+
+        let mut index = self.ahead.last().map_or(0, |&last| last + 1);
+        while self.ahead.len() < n {
+            let token = self.lexer.peek_nth(index);
+            if token.kind == TokenKind::Eof {
+                return TokenKind::Eof;
+            }
+            if !token.kind.is_trivia() && token.kind != TokenKind::DocComment {
+                self.ahead.push(index);
+            }
+            index += 1;
+        }
+        self.lexer.peek_nth(self.ahead[n - 1]).kind
+    }
+
+    fn at(&self, kind: TokenKind) -> bool {
+        self.token.kind == kind
+    }
+
+    /// Consumes the current token if it is of the given kind.
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes a token of the given kind, or reports that it is missing.
+    fn expect(&mut self, kind: TokenKind, description: &str) -> bool {
+        if self.eat(kind) {
+            true
+        } else {
+            self.error_expected(description);
+            false
+        }
+    }
+
+    /// Whether a line break separates the current token from the previous one.
+    ///
+    /// Some constructs may not span lines, so that e.g. an element `(x)` on its own line isn't taken for a call.
+    fn at_new_line(&self) -> bool {
+        self.source[self.prev_end..self.token.span.start].contains(['\n', '\r'])
+    }
+
+    /// The span from `start` to the end of the previous token.
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.prev_end.max(start))
+    }
+
+    fn error(&mut self, code: Code, span: Span, message: impl Into<String>) {
+        // what's missing at the end of the input after a source that's too deep is a consequence of skipping it
+        if self.too_deep {
+            return;
+        }
+        self.diagnostics.push(Diagnostic::new(span, message).with_code(code));
+    }
+
+    /// Goes one level deeper into the tree, returning whether that's allowed. Past the limit, the rest of the source
+    /// is skipped with a single diagnostic, since parsing it would only nest deeper.
+    ///
+    /// The caller restores [`Parser::depth`] once the node it's parsing is done.
+    fn descend(&mut self) -> bool {
+        self.depth += 1;
+        if self.depth <= self.max_depth {
+            return true;
+        }
+        let message = format!("the source is nested too deeply, more than {} levels", self.max_depth);
+        self.skip_too_deep(message);
+        false
+    }
+
+    /// Chains one more operator, returning whether that's allowed. Past [`MAX_CHAINED`], the rest of the source is
+    /// skipped with a single diagnostic, like it is past the depth limit.
+    ///
+    /// The caller restores [`Parser::chained`] once the chain it's parsing is done.
+    fn chain(&mut self) -> bool {
+        self.chained += 1;
+        if self.chained <= MAX_CHAINED {
+            return true;
+        }
+        self.skip_too_deep(format!("the source chains too many operators, more than {MAX_CHAINED}"));
+        false
+    }
+
+    fn skip_too_deep(&mut self, message: String) {
+        self.error(Code::NestedTooDeeply, self.token.span, message);
+        self.too_deep = true;
+        while !self.at(TokenKind::Eof) {
+            self.bump();
+        }
+    }
+
+    /// Reports that something else was expected at the current token.
+    fn error_expected(&mut self, description: &str) {
+        let found = match self.token.kind {
+            TokenKind::Eof => "end of input".to_string(),
+            // the lexer has already reported it
+            TokenKind::Error => return,
+            _ => format!("`{}`", &self.source[self.token.span.start..self.token.span.end]),
+        };
+
+        self.error(Code::UnexpectedToken, self.token.span, format!("expected {description}, found {found}"));
+    }
+
+    /// Skips the rest of a member that failed to parse, so that the error doesn't cascade into the following members.
+    ///
+    /// Members are usually separated by line breaks, so this skips to the next token on a new line, jumping over
+    /// bracketed tokens as a whole. It also stops at a `}` that would close the enclosing body. A `)` or `]` that
+    /// doesn't match anything is skipped, since it can't start a member either. The member started at the offset
+    /// `start`; if nothing of it was consumed, its first token is skipped too, so that parsing always moves on.
+    fn synchronize(&mut self, start: usize) {
+        let mut depth = 0usize;
+        let mut stuck = self.token.span.start == start;
+
+        loop {
+            match self.token.kind {
+                TokenKind::Eof => return,
+                _ if stuck => stuck = false,
+                _ if depth == 0 && self.at_new_line() => return,
+                TokenKind::RBrace if depth == 0 => return,
+                TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => depth += 1,
+                TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
+    /// Parses an identifier, which may be a quoted one.
+    fn parse_ident(&mut self) -> Option<Ident<'a>> {
+        match self.token.value {
+            TokenValue::Identifier(name) if self.at(TokenKind::Identifier) => {
+                let span = self.bump().span;
+                Some(Ident { span, name })
+            }
+            _ => {
+                self.error_expected("identifier");
+                None
+            }
+        }
+    }
+
+    /// Parses an identifier, using an empty name at the current position if there isn't one.
+    fn parse_ident_or_missing(&mut self) -> Ident<'a> {
+        self.parse_ident().unwrap_or(Ident {
+            span: Span::new(self.token.span.start, self.token.span.start),
+            name: "",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_trailing_tokens() {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, "1 2");
+
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].message, "expected end of input, found `2`");
+        assert_eq!(result.diagnostics[0].code, Some(Code::UnexpectedToken));
+        assert_eq!(result.diagnostics[0].span, Span::new(2, 3));
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let alloc = Allocator::default();
+        let deep = format!("x = {}1{}\ny = 2", "(".repeat(10_000), ")".repeat(10_000));
+        let result = parse_module(&alloc, &deep);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].message, "the source is nested too deeply, more than 256 levels");
+        assert_eq!(result.diagnostics[0].code, Some(Code::NestedTooDeeply));
+
+        let source = "x { y { z = (1 + 2) as List<List<Int>> } }";
+        assert!(parse_module(&alloc, source).diagnostics.is_empty());
+        assert_eq!(parse_module_with_depth(&alloc, source, 4).diagnostics.len(), 1);
+        // long chains of accesses nest
+        let chain = format!("x = a{}", ".b".repeat(10_000));
+        assert_eq!(parse_module(&alloc, &chain).diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn limits_chains() {
+        let alloc = Allocator::default();
+        // a flat chain of operators doesn't nest, so it can be much longer than the depth limit
+        let chain = format!("x = 1{}
+y = true{}", " + 1".repeat(1000), " && true || false".repeat(500));
+        assert!(parse_module(&alloc, &chain).diagnostics.is_empty());
+        assert!(parse_module_with_depth(&alloc, &chain, 4).diagnostics.is_empty());
+
+        // up to a limit of its own, counting the chains it's in
+        let chain = format!("x = 1{}", " + 1".repeat(10_000));
+        let result = parse_module(&alloc, &chain);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].message, "the source chains too many operators, more than 1024");
+        assert_eq!(result.diagnostics[0].code, Some(Code::NestedTooDeeply));
+        let inner = format!("(1{})", " * 1".repeat(600));
+        assert!(parse_module(&alloc, &format!("x = 1{}", format!(" + {inner}").repeat(3))).diagnostics.is_empty());
+        let nested = format!("x = 1{} + {inner}", " + 1".repeat(600));
+        assert_eq!(parse_module(&alloc, &nested).diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn recovers_at_member_boundaries() {
+        let alloc = Allocator::default();
+        let source = "a = )\nb = 2\nc = foo(1 2) 3\nclass { x: Int }\nd = 4\n";
+        let result = parse_module(&alloc, source);
+        let names: std::vec::Vec<_> = result
+            .node
+            .members
+            .iter()
+            .filter_map(|member| match member {
+                pkl_ast::ModuleMember::Property(property) => Some(property.name.name),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(names, ["a", "b", "c", "d"]);
+        assert_eq!(result.diagnostics.len(), 3, "{:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn stray_delimiters_terminate() {
+        let alloc = Allocator::default();
+        for source in ["new { ) }", "new { x = 1 ] y = 2 }", "foo { ]] }\nbar = 1", "class A { ) }", ")"] {
+            let result = parse_module(&alloc, source);
+            assert!(!result.diagnostics.is_empty(), "{source}");
+        }
+        // a `]` that can't start a member, on a line of its own
+        assert!(!parse_expr(&alloc, "new\n]").diagnostics.is_empty());
+
+        let result = parse_expr(&alloc, "new { a = ]\n b = 2 }");
+        let Expr::New(new) = &result.node else { panic!() };
+        assert_eq!(new.body.members.len(), 2);
+        assert_eq!(result.diagnostics.len(), 1, "{:?}", result.diagnostics);
+    }
+
+    /// Parses pseudo-random sequences of tokens and other text, which mustn't panic or keep the parser from ending.
+    #[test]
+    fn arbitrary_input() {
+        let pieces = [
+            "\"", "#", "\"\"\"", "\\(", ")", "(", "{", "}", "[", "]", "[[", "]]", "\n", " ", "a", "1", "0x", "1.", "`",
+            "/*", "//", "///", "名", "\u{FEFF}", "=", "->", ".", "?.", "new", "class", "function", "import", "module",
+            "let", "if", "else", "when", "for", "in", "@", "<", ">", ":", ",", ";", "*", "-", "\\u{", "local", "1e+",
+            "\0",
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for _ in 0..2000 {
+            let source: String = (0..random() % 30).map(|_| pieces[random() % pieces.len()]).collect();
+            let alloc = Allocator::default();
+            parse_module(&alloc, &source);
+            parse_expr(&alloc, &source);
+        }
+        // looking ahead for what the annotations belong to takes one pass over them
+        let alloc = Allocator::default();
+        assert_eq!(parse_module(&alloc, &"@A ".repeat(100_000)).diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn lexer_diagnostics_are_included() {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, "\"abc");
+
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].message, "unterminated string literal");
+    }
+}
@@ -0,0 +1,372 @@
+use pkl_ast::*;
+use pkl_diagnostics::Code;
+use pkl_lexer::token::TokenKind;
+
+use crate::Parser;
+
+impl<'a> Parser<'a> {
+    /// Parses `{ members }`, optionally starting with the parameters of a default function: `{ key -> ... }`.
+    pub(crate) fn parse_object_body(&mut self) -> ObjectBody<'a> {
+        let start = self.token.span.start;
+        let depth = self.depth;
+        if !self.descend() {
+            self.depth = depth;
+            return ObjectBody { span: Span::new(start, start), params: self.ast.vec(), members: self.ast.vec() };
+        }
+        self.expect(TokenKind::LBrace, "`{`");
+
+        let mut params = self.ast.vec();
+        if self.at_object_body_params() {
+            loop {
+                params.push(self.parse_parameter());
+                if !self.eat(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::Arrow, "`->`");
+        }
+
+        let mut members = self.ast.vec();
+        while !self.at(TokenKind::RBrace) && !self.at(TokenKind::Eof) {
+            let (errors, member_start) = (self.diagnostics.len(), self.token.span.start);
+            members.push(self.parse_object_member());
+            if self.diagnostics.len() > errors {
+                self.synchronize(member_start);
+            }
+            while self.eat(TokenKind::Semicolon) {}
+        }
+        self.expect(TokenKind::RBrace, "`}`");
+        self.depth = depth;
+
+        ObjectBody { span: self.span_from(start), params, members }
+    }
+
+    /// Whether the body starts with a parameter list like `key, value ->`.
+    fn at_object_body_params(&mut self) -> bool {
+        let mut n = 0;
+        loop {
+            if self.nth(n) != TokenKind::Identifier {
+                return false;
+            }
+            match self.nth(n + 1) {
+                TokenKind::Arrow => return true,
+                TokenKind::Comma => n += 2,
+                _ => return false,
+            }
+        }
+    }
+
+    fn parse_object_member(&mut self) -> ObjectMember<'a> {
+        let start = self.token.span.start;
+
+        match self.token.kind {
+            TokenKind::For => return ObjectMember::For(self.parse_for_generator()),
+            TokenKind::When => return ObjectMember::When(self.parse_when_generator()),
+            TokenKind::Spread | TokenKind::SpreadQuestion => {
+                let nullable = self.at(TokenKind::SpreadQuestion);
+                self.bump();
+                let value = self.parse_expr();
+                return ObjectMember::Spread(ObjectSpread { span: self.span_from(start), nullable, value });
+            }
+            _ => {}
+        }
+
+        let doc = self.take_doc();
+        let modifiers = self.parse_modifiers();
+        for modifier in modifiers.0.iter().filter(|modifier| modifier.kind != ModifierKind::Local) {
+            let message = format!("object members can't be `{}`, only `local`", modifier.kind.as_str());
+            self.error(Code::InvalidModifier, modifier.span, message);
+        }
+
+        if self.at(TokenKind::Function) {
+            return ObjectMember::Method(self.parse_object_method(start, doc, modifiers));
+        }
+
+        if self.at(TokenKind::Identifier)
+            && (!modifiers.is_empty() || matches!(self.nth(1), TokenKind::Eq | TokenKind::LBrace | TokenKind::Colon))
+        {
+            let name = self.parse_ident_or_missing();
+            let ty = if self.eat(TokenKind::Colon) { Some(self.parse_type()) } else { None };
+            let (value, bodies) = self.parse_member_value();
+
+            return ObjectMember::Property(ObjectProperty {
+                span: self.span_from(start),
+                doc,
+                modifiers,
+                name,
+                ty,
+                value,
+                bodies,
+            });
+        }
+
+        if !modifiers.is_empty() {
+            self.error_expected("property or method name");
+        }
+
+        if self.at(TokenKind::LBracket) {
+            self.bump();
+
+            if self.eat(TokenKind::LBracket) {
+                let predicate = self.parse_expr();
+                self.expect(TokenKind::RBracket, "`]]`");
+                self.expect(TokenKind::RBracket, "`]]`");
+                let (value, bodies) = self.parse_member_value();
+
+                return ObjectMember::MemberPredicate(ObjectMemberPredicate {
+                    span: self.span_from(start),
+                    predicate,
+                    value,
+                    bodies,
+                });
+            }
+
+            let key = self.parse_expr();
+            self.expect(TokenKind::RBracket, "`]`");
+            let (value, bodies) = self.parse_member_value();
+
+            return ObjectMember::Entry(ObjectEntry { span: self.span_from(start), key, value, bodies });
+        }
+
+        let value = self.parse_expr();
+        ObjectMember::Element(ObjectElement { span: self.span_from(start), value })
+    }
+
+    /// Parses `for (key, value in iterable) { members }`, where the key is optional.
+    fn parse_for_generator(&mut self) -> ForGenerator<'a> {
+        let start = self.token.span.start;
+        self.expect(TokenKind::For, "`for`");
+        self.expect(TokenKind::LParen, "`(`");
+
+        let first = self.parse_parameter();
+        let (key, value) = if self.eat(TokenKind::Comma) {
+            (Some(first), self.parse_parameter())
+        } else {
+            (None, first)
+        };
+
+        self.expect(TokenKind::In, "`in`");
+        let iterable = self.parse_expr();
+        self.expect(TokenKind::RParen, "`)`");
+        let body = self.parse_object_body();
+
+        ForGenerator { span: self.span_from(start), key, value, iterable, body }
+    }
+
+    /// Parses `when (condition) { members } else { members }`, where the `else` branch is optional.
+    fn parse_when_generator(&mut self) -> WhenGenerator<'a> {
+        let start = self.token.span.start;
+        self.expect(TokenKind::When, "`when`");
+        self.expect(TokenKind::LParen, "`(`");
+        let condition = self.parse_expr();
+        self.expect(TokenKind::RParen, "`)`");
+        let body = self.parse_object_body();
+        let else_body = if self.eat(TokenKind::Else) { Some(self.parse_object_body()) } else { None };
+
+        WhenGenerator { span: self.span_from(start), condition, body, else_body }
+    }
+
+    /// Parses the `= value` or `{ ... } { ... }` defining a property, entry, or member predicate.
+    ///
+    /// A local property may also be declared with only a type, in which case neither is present.
+    fn parse_member_value(&mut self) -> (Option<Expr<'a>>, Vec<'a, ObjectBody<'a>>) {
+        let mut bodies = self.ast.vec();
+
+        if self.eat(TokenKind::Eq) {
+            return (Some(self.parse_expr()), bodies);
+        }
+
+        if !self.at(TokenKind::LBrace) {
+            self.error_expected("`=` or `{`");
+        }
+        while self.at(TokenKind::LBrace) {
+            bodies.push(self.parse_object_body());
+        }
+
+        (None, bodies)
+    }
+
+    /// Parses `function name(params): Type = body` after its modifiers.
+    fn parse_object_method(
+        &mut self,
+        start: usize,
+        doc: Option<DocComment<'a>>,
+        modifiers: Modifiers<'a>,
+    ) -> ObjectMethod<'a> {
+        self.expect(TokenKind::Function, "`function`");
+        let name = self.parse_ident_or_missing();
+        let params = self.parse_parameter_list();
+        let return_type = if self.eat(TokenKind::Colon) { Some(self.parse_type()) } else { None };
+        self.expect(TokenKind::Eq, "`=`");
+        let body = self.parse_expr();
+
+        ObjectMethod { span: self.span_from(start), doc, modifiers, name, params, return_type, body }
+    }
+
+    /// Parses any number of modifier keywords.
+    pub(crate) fn parse_modifiers(&mut self) -> Modifiers<'a> {
+        let mut modifiers = self.ast.vec();
+
+        loop {
+            let kind = match self.token.kind {
+                TokenKind::Abstract => ModifierKind::Abstract,
+                TokenKind::Open => ModifierKind::Open,
+                TokenKind::External => ModifierKind::External,
+                TokenKind::Local => ModifierKind::Local,
+                TokenKind::Hidden => ModifierKind::Hidden,
+                TokenKind::Fixed => ModifierKind::Fixed,
+                TokenKind::Const => ModifierKind::Const,
+                _ => return Modifiers(modifiers),
+            };
+            let span = self.bump().span;
+            modifiers.push(Modifier { span, kind });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+    use pkl_ast::*;
+
+    use crate::expr::test::sexp;
+    use crate::parse_expr;
+
+    /// Describes each member of the body of `new { ... }`.
+    fn members(source: &str) -> std::vec::Vec<String> {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, source);
+        assert!(result.diagnostics.is_empty(), "{source}: {:?}", result.diagnostics);
+        let Expr::New(new) = &result.node else { panic!("not a new expression: {source}") };
+
+        let value = |value: &Option<Expr>, bodies: &Vec<ObjectBody>| match value {
+            Some(value) => format!("= {}", sexp(value)),
+            None => format!("amended by {}", bodies.len()),
+        };
+
+        new.body
+            .members
+            .iter()
+            .map(|member| match member {
+                ObjectMember::Property(p) => {
+                    let local = if p.modifiers.has(ModifierKind::Local) { "local " } else { "" };
+                    format!("{local}{} {}", p.name.name, value(&p.value, &p.bodies))
+                }
+                ObjectMember::Method(m) => format!("function {}/{}", m.name.name, m.params.len()),
+                ObjectMember::Entry(e) => format!("[{}] {}", sexp(&e.key), value(&e.value, &e.bodies)),
+                ObjectMember::Element(e) => sexp(&e.value),
+                ObjectMember::MemberPredicate(p) => {
+                    format!("[[{}]] {}", sexp(&p.predicate), value(&p.value, &p.bodies))
+                }
+                ObjectMember::For(f) => {
+                    let key = f.key.as_ref().and_then(|key| key.name);
+                    let key = key.map_or(String::new(), |key| format!("{}, ", key.name));
+                    let value = f.value.name.map_or("_", |value| value.name);
+                    format!("for ({key}{value} in {}) {}", sexp(&f.iterable), f.body.members.len())
+                }
+                ObjectMember::When(w) => {
+                    let otherwise = w.else_body.as_ref();
+                    let otherwise = otherwise.map_or(String::new(), |body| format!(" else {}", body.members.len()));
+                    format!("when ({}) {}{otherwise}", sexp(&w.condition), w.body.members.len())
+                }
+                ObjectMember::Spread(s) => format!("{}{}", if s.nullable { "...?" } else { "..." }, sexp(&s.value)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn properties_entries_and_elements() {
+        let source = r#"new {
+            name = "x"
+            ["key"] = 1
+            42
+            -1
+            [[this > 1]] = 2
+        }"#;
+
+        assert_eq!(
+            members(source),
+            vec!["name = (str \"x\")", "[(str \"key\")] = 1", "42", "(- 1)", "[[(> this 1)]] = 2"]
+        );
+    }
+
+    #[test]
+    fn amending_members() {
+        assert_eq!(
+            members("new { foo { bar = 1 } { baz = 2 }; [\"k\"] { x = 1 } }"),
+            vec!["foo amended by 2", "[(str \"k\")] amended by 1"]
+        );
+    }
+
+    #[test]
+    fn local_members() {
+        assert_eq!(
+            members("new { local x: Int = 1 local function double(n) = n * 2 y = double(x) }"),
+            vec!["local x = 1", "function double/1", "y = (call double x)"]
+        );
+    }
+
+    #[test]
+    fn elements_on_separate_lines_are_not_calls() {
+        assert_eq!(members("new { foo\n(bar) }"), vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn generators() {
+        let source = r#"new {
+            for (x in xs) { x }
+            for (k, _ in map) { [k] = 1; [k + "!"] = 2 }
+            when (enabled) { name = "on" }
+            when (!enabled) { name = "off" } else { name = "on"; extra = true }
+        }"#;
+
+        assert_eq!(
+            members(source),
+            vec!["for (x in xs) 1", "for (k, _ in map) 2", "when (enabled) 1", "when ((! enabled)) 1 else 2"]
+        );
+    }
+
+    #[test]
+    fn modifiers_of_object_members() {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, "new { hidden x = 1; local y = 2 }");
+        let messages: std::vec::Vec<_> = result.diagnostics.iter().map(|diagnostic| &diagnostic.message).collect();
+        assert_eq!(messages, ["object members can't be `hidden`, only `local`"]);
+    }
+
+    #[test]
+    fn doc_comments_of_object_members() {
+        let alloc = Allocator::default();
+        let source = "new {\n  /// The name.\n  name = \"x\"\n  /// Doubles.\n  local function f(n) = n * 2\n}";
+        let result = parse_expr(&alloc, source);
+        let Expr::New(new) = &result.node else { unreachable!() };
+        let docs: std::vec::Vec<_> = new
+            .body
+            .members
+            .iter()
+            .map(|member| match member {
+                ObjectMember::Property(property) => property.doc.as_ref(),
+                ObjectMember::Method(method) => method.doc.as_ref(),
+                _ => None,
+            })
+            .map(|doc| doc.map(|doc| doc.lines.join("\n")))
+            .collect();
+        assert_eq!(docs, [Some("The name.".to_string()), Some("Doubles.".to_string())]);
+    }
+
+    #[test]
+    fn spreads() {
+        assert_eq!(members("new { ...xs; ...?maybe.ys\n1 }"), vec!["...xs", "...?(. maybe ys)", "1"]);
+    }
+
+    #[test]
+    fn default_function_parameters() {
+        let alloc = Allocator::default();
+        let result = parse_expr(&alloc, "new { key, value -> name = key }");
+        let Expr::New(new) = &result.node else { unreachable!() };
+
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(new.body.params.len(), 2);
+        assert_eq!(new.body.members.len(), 1);
+    }
+}
@@ -0,0 +1,226 @@
+use pkl_ast::*;
+use pkl_diagnostics::Code;
+use pkl_lexer::token::TokenKind;
+
+use crate::Parser;
+
+impl<'a> Parser<'a> {
+    /// Parses a type, which may be a union of several members like `"a" | *"b" | Int?`.
+    pub(crate) fn parse_type(&mut self) -> Type<'a> {
+        let depth = self.depth;
+        // `unknown` stands in for a type that's too deep, since the rest of the source is skipped anyway
+        let ty = if self.descend() { self.parse_union_type() } else { Type::Unknown(self.token.span) };
+        self.depth = depth;
+        ty
+    }
+
+    /// Parses a union type or the single type that isn't one.
+    fn parse_union_type(&mut self) -> Type<'a> {
+        let start = self.token.span.start;
+        let mut default = None;
+
+        if self.eat(TokenKind::Star) {
+            default = Some(0);
+        }
+        let first = self.parse_postfix_type();
+        if !self.at(TokenKind::Pipe) {
+            if default.is_some() {
+                let message = "only members of a union type may be marked as default";
+                self.error(Code::InvalidDefaultMember, Span::new(start, start + 1), message);
+            }
+            return first;
+        }
+
+        let mut members = self.ast.vec();
+        members.push(first);
+
+        while self.eat(TokenKind::Pipe) {
+            let star = self.token.span;
+            if self.eat(TokenKind::Star) {
+                if default.is_some() {
+                    self.error(Code::InvalidDefaultMember, star, "a union type may only have one default member");
+                }
+                default = Some(members.len());
+            }
+            members.push(self.parse_postfix_type());
+        }
+
+        Type::Union(self.ast.boxed(UnionType { span: self.span_from(start), members, default }))
+    }
+
+    /// Parses a type followed by any number of `?` and constraint lists like `(length > 3)`.
+    ///
+    /// Like call arguments, a constraint list has to start on the same line as the type it constrains.
+    fn parse_postfix_type(&mut self) -> Type<'a> {
+        let start = self.token.span.start;
+        let mut ty = self.parse_primary_type();
+
+        loop {
+            if self.eat(TokenKind::Question) {
+                ty = Type::Nullable(self.ast.boxed(NullableType { span: self.span_from(start), inner: ty }));
+            } else if self.at(TokenKind::LParen) && !self.at_new_line() {
+                self.bump();
+                let mut constraints = self.ast.vec();
+                while !self.at(TokenKind::RParen) && !self.at(TokenKind::Eof) {
+                    constraints.push(self.parse_expr());
+                    if !self.eat(TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(TokenKind::RParen, "`)`");
+
+                ty = Type::Constrained(self.ast.boxed(ConstrainedType {
+                    span: self.span_from(start),
+                    base: ty,
+                    constraints,
+                }));
+            } else {
+                return ty;
+            }
+        }
+    }
+
+    /// Parses `unknown`, `nothing`, `module`, a string literal type, a named type with optional type arguments, a
+    /// function type, or a parenthesized type.
+    fn parse_primary_type(&mut self) -> Type<'a> {
+        let start = self.token.span.start;
+
+        match self.token.kind {
+            TokenKind::Unknown => Type::Unknown(self.bump().span),
+            TokenKind::Nothing => Type::Nothing(self.bump().span),
+            TokenKind::Module => Type::Module(self.bump().span),
+            TokenKind::StringLiteral | TokenKind::StringStart => Type::StringLiteral(self.parse_string_constant()),
+            TokenKind::LParen => {
+                self.bump();
+                let mut params = self.ast.vec();
+                while !self.at(TokenKind::RParen) && !self.at(TokenKind::Eof) {
+                    params.push(self.parse_type());
+                    if !self.eat(TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(TokenKind::RParen, "`)`");
+
+                if params.len() == 1 && !self.at(TokenKind::Arrow) {
+                    let inner = params.pop().unwrap();
+                    let span = self.span_from(start);
+                    return Type::Parenthesized(self.ast.boxed(ParenthesizedType { span, inner }));
+                }
+
+                self.expect(TokenKind::Arrow, "`->`");
+                let ret = self.parse_type();
+                Type::Function(self.ast.boxed(FunctionType { span: self.span_from(start), params, ret }))
+            }
+            _ => {
+                let name = self.parse_qualified_name();
+                let mut args = self.ast.vec();
+
+                if self.eat(TokenKind::Lt) {
+                    loop {
+                        args.push(self.parse_type());
+                        if !self.eat(TokenKind::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(TokenKind::Gt, "`>`");
+                }
+
+                Type::Named(self.ast.boxed(NamedType { span: self.span_from(start), name, args }))
+            }
+        }
+    }
+
+    /// Parses a dot-separated name like `com.example.Foo`.
+    pub(crate) fn parse_qualified_name(&mut self) -> QualifiedName<'a> {
+        let start = self.token.span.start;
+        let mut parts = self.ast.vec();
+
+        parts.push(self.parse_ident_or_missing());
+        while self.at(TokenKind::Dot) && self.nth(1) == TokenKind::Identifier {
+            self.bump();
+            parts.push(self.parse_ident_or_missing());
+        }
+
+        QualifiedName { span: self.span_from(start), parts }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use oxc_allocator::Allocator;
+    use pkl_ast::*;
+
+    use crate::expr::test::sexp;
+    use crate::parse_expr;
+
+    /// Parses `x is <ty>` and describes the type.
+    fn ty(source: &str) -> String {
+        let alloc = Allocator::default();
+        let source = format!("x is {source}");
+        let result = parse_expr(&alloc, &source);
+        assert!(result.diagnostics.is_empty(), "{source}: {:?}", result.diagnostics);
+        let Expr::Is(test) = &result.node else { panic!("not a type test: {source}") };
+
+        describe(&test.ty)
+    }
+
+    fn describe(ty: &Type) -> String {
+        let list = |types: &Vec<Type>| types.iter().map(describe).collect::<std::vec::Vec<_>>().join(", ");
+
+        match ty {
+            Type::Unknown(_) => "unknown".to_string(),
+            Type::Nothing(_) => "nothing".to_string(),
+            Type::Module(_) => "module".to_string(),
+            Type::StringLiteral(literal) => format!("{:?}", literal.value),
+            Type::Named(named) if named.args.is_empty() => named.name.parts.iter().map(|part| part.name).collect(),
+            Type::Named(named) => format!("{}<{}>", named.name.parts[0].name, list(&named.args)),
+            Type::Nullable(nullable) => format!("{}?", describe(&nullable.inner)),
+            Type::Union(union) => {
+                let members = union.members.iter().enumerate().map(|(index, member)| {
+                    let star = if union.default == Some(index) { "*" } else { "" };
+                    format!("{star}{}", describe(member))
+                });
+                format!("[{}]", members.collect::<std::vec::Vec<_>>().join(" | "))
+            }
+            Type::Function(function) => format!("(({}) -> {})", list(&function.params), describe(&function.ret)),
+            Type::Constrained(constrained) => {
+                let constraints = constrained.constraints.iter().map(sexp).collect::<std::vec::Vec<_>>();
+                format!("{}({})", describe(&constrained.base), constraints.join(", "))
+            }
+            Type::Parenthesized(parenthesized) => format!("({})", describe(&parenthesized.inner)),
+        }
+    }
+
+    #[test]
+    fn named_and_parameterized_types() {
+        assert_eq!(ty("String?"), "String?");
+        assert_eq!(ty("Mapping<String, Listing<Int?>>"), "Mapping<String, Listing<Int?>>");
+        assert_eq!(ty("unknown"), "unknown");
+    }
+
+    #[test]
+    fn unions() {
+        assert_eq!(ty(r#""a" | *"b" | Int"#), r#"["a" | *"b" | Int]"#);
+        assert_eq!(ty("(String | Int)?"), "([String | Int])?");
+    }
+
+    #[test]
+    fn function_types() {
+        assert_eq!(ty("() -> Int"), "(() -> Int)");
+        assert_eq!(ty("(String, Int) -> Boolean?"), "((String, Int) -> Boolean?)");
+        assert_eq!(ty("(Int) -> (Int) -> Int"), "((Int) -> ((Int) -> Int))");
+    }
+
+    #[test]
+    fn constraints() {
+        assert_eq!(ty("String(length > 3, !isEmpty)"), "String((> length 3), (! isEmpty))");
+        assert_eq!(ty("Int(isPositive)?"), "Int(isPositive)?");
+    }
+
+    #[test]
+    fn misplaced_default_markers() {
+        let alloc = Allocator::default();
+        assert_eq!(parse_expr(&alloc, "x is *Int").diagnostics.len(), 1);
+        assert_eq!(parse_expr(&alloc, "x is *Int | *String").diagnostics.len(), 1);
+    }
+}
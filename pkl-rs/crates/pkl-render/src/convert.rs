@@ -0,0 +1,109 @@
+//! Converters that embedders register on a renderer, to turn values into ones the format can represent before they're
+//! rendered, like the `converters` of a Pkl renderer.
+//!
+//! A converter applies to the values of a class, or to those at the paths matching a pattern like `servers[*].port`;
+//! see [`pkl_eval::convert`] for how they match.
+
+use pkl_eval::convert::{self, Target};
+use pkl_eval::value::Value;
+
+use crate::{Renderer, Result};
+
+/// Turns a value into another before it's rendered.
+pub trait Converter {
+    fn convert(&self, value: Value) -> Value;
+}
+
+impl<F: Fn(Value) -> Value> Converter for F {
+    fn convert(&self, value: Value) -> Value {
+        self(value)
+    }
+}
+
+/// Converters, each for the values of a class or at the paths matching a pattern.
+///
+/// ```
+/// use pkl_eval::value::Value;
+/// use pkl_render::{Converters, JsonOptions, JsonRenderer, Renderer};
+///
+/// let seconds = |value: Value| match value {
+///     Value::Duration(duration) => Value::Float(duration.value.as_f64() * duration.unit.nanos() as f64 / 1e9),
+///     value => value,
+/// };
+/// let converters = Converters::new().class("Duration", seconds);
+/// let renderer = JsonRenderer::new(JsonOptions::default()).with_converters(converters);
+/// let module = pkl_eval::evaluate("timeout = 1.5.min").unwrap();
+///
+/// assert_eq!(renderer.render(&module).unwrap(), "{\n  \"timeout\": 90.0\n}\n");
+/// ```
+#[derive(Default)]
+pub struct Converters {
+    targets: Vec<Target>,
+    converters: Vec<Box<dyn Converter>>,
+}
+
+impl Converters {
+    pub fn new() -> Self {
+        Converters::default()
+    }
+
+    /// Adds a converter for the values of the class `name`, like `Duration` or a class declared by a module.
+    pub fn class(mut self, name: &str, converter: impl Converter + 'static) -> Self {
+        self.targets.push(Target::Class(name.to_string()));
+        self.converters.push(Box::new(converter));
+        self
+    }
+
+    /// Adds a converter for the values at the paths matching `pattern`, like `servers[*].port`.
+    pub fn path(mut self, pattern: &str, converter: impl Converter + 'static) -> Self {
+        self.targets.push(Target::path(pattern));
+        self.converters.push(Box::new(converter));
+        self
+    }
+
+    /// Converts a value and the values inside it.
+    pub fn apply(&self, value: Value) -> Value {
+        let converted = convert::convert::<()>(value, &self.targets, &mut |index, value| {
+            Ok(self.converters[index].convert(value))
+        });
+        converted.unwrap_or_else(|()| unreachable!("converters don't fail"))
+    }
+}
+
+/// A renderer that converts values before rendering them, made by [`Renderer::with_converters`].
+pub struct Converted<R> {
+    renderer: R,
+    converters: Converters,
+}
+
+impl<R> Converted<R> {
+    pub(crate) fn new(renderer: R, converters: Converters) -> Self {
+        Converted { renderer, converters }
+    }
+}
+
+impl<R: Renderer> Renderer for Converted<R> {
+    fn render(&self, value: &Value) -> Result<String> {
+        self.renderer.render(&self.converters.apply(value.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pkl_eval::value::Value;
+
+    use crate::{Converters, PcfOptions, PcfRenderer, Renderer};
+
+    #[test]
+    fn classes_and_paths() {
+        let module = pkl_eval::evaluate("port = 80\nservers { new { port = 8080 } }").unwrap();
+        let converters = Converters::new()
+            .class("Int", |value| match value {
+                Value::Int(n) => Value::String(n.to_string()),
+                value => value,
+            })
+            .path("servers[*].port", |_| Value::Null);
+        let pcf = PcfRenderer::new(PcfOptions::default()).with_converters(converters).render(&module).unwrap();
+        assert_eq!(pcf, "port = \"80\"\nservers {\n  new {\n    port = null\n  }\n}\n");
+    }
+}
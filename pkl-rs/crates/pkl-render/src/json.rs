@@ -0,0 +1,246 @@
+//! JSON output.
+//!
+//! Lists, sets, and listings are arrays; maps, mappings, and objects with properties are objects, whose keys have to
+//! be strings. `Duration`s, `DataSize`s, and the floats JSON has no syntax for (`NaN` and the infinities) are
+//! rejected.
+
+use pkl_eval::value::Value;
+
+use crate::{shape, unrenderable, Key, Path, RenderError, Renderer, Result, Shape};
+
+const FORMAT: &str = "JSON";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// The text of one level of indentation; with an empty indent, the output is on a single line
+    pub indent: String,
+    /// Whether properties whose value is `null` are left out
+    pub omit_null_properties: bool,
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        JsonOptions { indent: "  ".to_string(), omit_null_properties: false }
+    }
+}
+
+/// Renders values as JSON.
+///
+/// ```
+/// use pkl_render::{JsonOptions, JsonRenderer, Renderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"\ntags { \"bird\" }").unwrap();
+/// let json = JsonRenderer::new(JsonOptions::default()).render(&module).unwrap();
+///
+/// assert_eq!(json, "{\n  \"name\": \"pigeon\",\n  \"tags\": [\n    \"bird\"\n  ]\n}\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JsonRenderer {
+    options: JsonOptions,
+}
+
+impl JsonRenderer {
+    pub fn new(options: JsonOptions) -> Self {
+        JsonRenderer { options }
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn render(&self, value: &Value) -> Result<String> {
+        let mut writer = Writer { options: &self.options, out: String::new(), level: 0, path: Path::default() };
+        writer.value(value)?;
+        writer.out.push('\n');
+        Ok(writer.out)
+    }
+}
+
+struct Writer<'o> {
+    options: &'o JsonOptions,
+    out: String,
+    level: usize,
+    path: Path,
+}
+
+impl Writer<'_> {
+    fn value(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Null => self.out.push_str("null"),
+            Value::Boolean(b) => self.out.push_str(if *b { "true" } else { "false" }),
+            Value::Int(n) => self.out.push_str(&n.to_string()),
+            Value::Float(x) if x.is_finite() => self.out.push_str(&pkl_stdlib::number::float_to_string(*x)),
+            Value::String(s) => self.string(s),
+            Value::List(elements) | Value::Set(elements) => self.array(elements)?,
+            Value::Map(entries) => {
+                let members = entries.iter().map(|(key, value)| (Key::Entry(key), value)).collect();
+                self.object(members)?
+            }
+            Value::Object(object) => match shape(object, &self.path, FORMAT)? {
+                Shape::List(elements) => self.array(elements)?,
+                Shape::Map(members) => self.object(members)?,
+            },
+            Value::Float(x) => {
+                let x = pkl_stdlib::number::float_to_string(*x);
+                return Err(RenderError::new(&self.path, format!("can't render `{x}` as {FORMAT}")));
+            }
+            Value::Duration(_)
+            | Value::DataSize(_)
+            | Value::Function { .. }
+            | Value::Class { .. }
+            | Value::Regex { .. } => {
+                return Err(unrenderable(&self.path, value, FORMAT));
+            }
+        }
+        Ok(())
+    }
+
+    fn array(&mut self, elements: &[Value]) -> Result<()> {
+        self.out.push('[');
+        self.level += 1;
+        for (index, element) in elements.iter().enumerate() {
+            self.separator(index);
+            self.path.push_index(index);
+            self.value(element)?;
+            self.path.pop();
+        }
+        self.level -= 1;
+        self.close(elements.is_empty(), ']');
+        Ok(())
+    }
+
+    fn object(&mut self, members: Vec<(Key, &Value)>) -> Result<()> {
+        self.out.push('{');
+        self.level += 1;
+        let members = members.into_iter().filter(|(key, value)| {
+            !(self.options.omit_null_properties && matches!(key, Key::Property(_)) && **value == Value::Null)
+        });
+        let mut empty = true;
+        for (index, (key, value)) in members.enumerate() {
+            empty = false;
+            self.separator(index);
+            self.path.push(&key);
+            match key {
+                Key::Property(name) => self.string(name),
+                Key::Entry(Value::String(key)) => self.string(key),
+                Key::Entry(key) => {
+                    let message = format!("can't render a key of type `{}` as {FORMAT}", key.type_name());
+                    return Err(RenderError::new(&self.path, message));
+                }
+            }
+            self.out.push_str(if self.options.indent.is_empty() { ":" } else { ": " });
+            self.value(value)?;
+            self.path.pop();
+        }
+        self.level -= 1;
+        self.close(empty, '}');
+        Ok(())
+    }
+
+    /// Starts the line of an element or member of an array or object.
+    fn separator(&mut self, index: usize) {
+        if index > 0 {
+            self.out.push(',');
+        }
+        self.newline();
+    }
+
+    fn close(&mut self, empty: bool, bracket: char) {
+        if !empty {
+            self.newline();
+        }
+        self.out.push(bracket);
+    }
+
+    fn newline(&mut self) {
+        if !self.options.indent.is_empty() {
+            self.out.push('\n');
+            for _ in 0..self.level {
+                self.out.push_str(&self.options.indent);
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\u{8}' => self.out.push_str("\\b"),
+                '\u{c}' => self.out.push_str("\\f"),
+                c if c.is_control() => self.out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(source: &str, options: JsonOptions) -> Result<String> {
+        let module = pkl_eval::evaluate(source).unwrap_or_else(|error| panic!("{source}: {error}"));
+        JsonRenderer::new(options).render(&module)
+    }
+
+    #[test]
+    fn values() {
+        let source = r#"
+            a = 1
+            b = 1.5
+            c = "say \"hi\"\n\u{1}"
+            d = null
+            e = List(true, Map("k", Set()))
+            f = new Listing {}
+            g = new Mapping { ["x"] = 1.0e21 }
+            h = new Dynamic { ["y"] = 2; z = 3 }
+        "#;
+        let expected = r#"{
+  "a": 1,
+  "b": 1.5,
+  "c": "say \"hi\"\n\u0001",
+  "d": null,
+  "e": [
+    true,
+    {
+      "k": []
+    }
+  ],
+  "f": [],
+  "g": {
+    "x": 1.0E21
+  },
+  "h": {
+    "z": 3,
+    "y": 2
+  }
+}
+"#;
+        assert_eq!(render(source, JsonOptions::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn options() {
+        let options = JsonOptions { indent: String::new(), omit_null_properties: true };
+        let json = render("a = null\nb { 1; 2 }\nc = new Mapping { [\"d\"] = null }", options).unwrap();
+        assert_eq!(json, "{\"b\":[1,2],\"c\":{\"d\":null}}\n");
+
+        let options = JsonOptions { indent: "\t".to_string(), ..JsonOptions::default() };
+        assert_eq!(render("a { b = 1 }", options).unwrap(), "{\n\t\"a\": {\n\t\t\"b\": 1\n\t}\n}\n");
+    }
+
+    #[test]
+    fn unrepresentable_values() {
+        let error = |source| render(source, JsonOptions::default()).unwrap_err().to_string();
+        let message = "can't render a value of type `Duration` as JSON (at `servers[0].timeout`)";
+        assert_eq!(error("servers { new { timeout = 5.s } }"), message);
+        assert_eq!(error("m = Map(1, 2)"), "can't render a key of type `Int` as JSON (at `m[1]`)");
+        assert_eq!(error("x = -1.0 / 0"), "can't render `-Infinity` as JSON (at `x`)");
+        let message = "can't render a `Dynamic` with both elements and other members as JSON (at `o`)";
+        assert_eq!(error("o { a = 1; 2 }"), message);
+    }
+}
@@ -0,0 +1,197 @@
+//! Renders evaluated Pkl [`Value`]s in data formats, like `pkl eval --format json` does.
+//!
+//! Every format supports a different subset of Pkl's values: JSON has no `Duration`, for example, and its object
+//! keys can only be strings. Renderers reject the values their format can't represent with a [`RenderError`] saying
+//! where the value is, rather than rendering something lossy.
+
+#![forbid(unsafe_code)]
+
+pub mod convert;
+pub mod json;
+pub mod output;
+pub mod pcf;
+pub mod plist;
+pub mod properties;
+pub mod sort;
+pub mod xml;
+pub mod yaml;
+
+use std::fmt;
+
+use pkl_eval::value::{Object, ObjectKind, Value};
+
+pub use convert::{Converted, Converter, Converters};
+pub use json::{JsonOptions, JsonRenderer};
+pub use output::{render_output, renderer_of};
+pub use pcf::{PcfOptions, PcfRenderer};
+pub use plist::PListRenderer;
+pub use properties::{PropertiesOptions, PropertiesRenderer};
+pub use sort::Sorted;
+pub use xml::{XmlOptions, XmlRenderer};
+pub use yaml::{YamlOptions, YamlRenderer};
+
+/// Turns values into the text of a data format.
+pub trait Renderer {
+    /// Renders a value, typically a module, as a complete document ending with a line break.
+    fn render(&self, value: &Value) -> Result<String>;
+
+    /// This renderer, converting values with `converters` before rendering them.
+    fn with_converters(self, converters: Converters) -> Converted<Self>
+    where
+        Self: Sized,
+    {
+        Converted::new(self, converters)
+    }
+
+    /// This renderer, sorting the properties and entries of objects and maps before rendering them.
+    fn with_sorted_keys(self) -> Sorted<Self>
+    where
+        Self: Sized,
+    {
+        Sorted::new(self)
+    }
+}
+
+/// A value can't be represented in the format being rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderError {
+    pub message: String,
+    /// The properties, keys, and indices leading to the value from the rendered one, like `servers[0].timeout`
+    pub path: String,
+}
+
+impl RenderError {
+    pub fn new(path: &Path, message: impl Into<String>) -> Self {
+        RenderError { message: message.into(), path: path.to_string() }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} (at `{}`)", self.message, self.path)
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+pub type Result<T> = std::result::Result<T, RenderError>;
+
+/// The position of a value inside the rendered one, for error messages.
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Property(String),
+    Key(String),
+    Index(usize),
+}
+
+impl Path {
+    pub fn push_property(&mut self, name: &str) {
+        self.segments.push(Segment::Property(name.to_string()));
+    }
+
+    /// Pushes the key of an entry, as it reads in Pkl source.
+    pub fn push_key(&mut self, key: &Value) {
+        let key = match key {
+            Value::String(s) => pkl_stdlib::string::quote(s),
+            Value::Int(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
+            key => key.type_name().to_string(),
+        };
+        self.segments.push(Segment::Key(key));
+    }
+
+    pub fn push_index(&mut self, index: usize) {
+        self.segments.push(Segment::Index(index));
+    }
+
+    pub fn pop(&mut self) {
+        self.segments.pop();
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Property(name) if index == 0 => write!(f, "{name}")?,
+                Segment::Property(name) => write!(f, ".{name}")?,
+                Segment::Key(key) => write!(f, "[{key}]")?,
+                Segment::Index(i) => write!(f, "[{i}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How an object reads in formats that only have lists and maps.
+pub(crate) enum Shape<'v> {
+    List(&'v [Value]),
+    /// Properties and entries, in this order
+    Map(Vec<(Key<'v>, &'v Value)>),
+}
+
+pub(crate) enum Key<'v> {
+    Property(&'v str),
+    Entry(&'v Value),
+}
+
+/// Decides the shape of an object: a listing, or a dynamic object with elements only, is a list, and anything else a
+/// map. Objects with both elements and other members have no shape.
+pub(crate) fn shape<'v>(object: &'v Object, path: &Path, format: &str) -> Result<Shape<'v>> {
+    let has_members = !object.properties.is_empty() || !object.entries.is_empty();
+    if object.kind == ObjectKind::Listing || (!object.elements.is_empty() && !has_members) {
+        return Ok(Shape::List(&object.elements));
+    }
+    if !object.elements.is_empty() {
+        let class = object.kind.class_name();
+        let message = format!("can't render a `{class}` with both elements and other members as {format}");
+        return Err(RenderError::new(path, message));
+    }
+
+    let properties = object.properties.iter().map(|(name, value)| (Key::Property(name), value));
+    let entries = object.entries.iter().map(|(key, value)| (Key::Entry(key), value));
+    Ok(Shape::Map(properties.chain(entries).collect()))
+}
+
+impl Path {
+    /// Pushes the segment of a key of a [`Shape::Map`].
+    pub(crate) fn push(&mut self, key: &Key) {
+        match key {
+            Key::Property(name) => self.push_property(name),
+            Key::Entry(key) => self.push_key(key),
+        }
+    }
+}
+
+/// Escapes the characters that can't appear as is in the text of an XML element, or in an attribute value if
+/// `attribute`. Control characters other than whitespace are written as character references.
+pub(crate) fn escape_xml(s: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {
+                escaped.push_str(&format!("&#x{:X};", c as u32));
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The error for a value a format has no representation for.
+pub(crate) fn unrenderable(path: &Path, value: &Value, format: &str) -> RenderError {
+    RenderError::new(path, format!("can't render a value of type `{}` as {format}", value.type_name()))
+}
@@ -0,0 +1,173 @@
+//! Rendering a module's `output`, the way `pkl eval` does.
+//!
+//! A module can say how it's rendered: `output { renderer = new JsonRenderer {} }` picks the format, with the
+//! renderer's properties, like `indent`, as its options, and `output { value = ... }` renders something other than the
+//! module itself. An `output.text` is written as is.
+
+use pkl_eval::value::{ModuleOutput, Object, Value};
+
+use crate::{
+    JsonOptions, JsonRenderer, PListRenderer, Path, PcfOptions, PcfRenderer, PropertiesOptions, PropertiesRenderer,
+    RenderError, Renderer, Result, XmlOptions, XmlRenderer, YamlOptions, YamlRenderer,
+};
+
+/// Renders a module's output: its `output.text` if it sets one, or else its `output.value` rendered by `renderer`, or
+/// by its `output.renderer` without one, or as Pcf if it doesn't set a renderer either.
+///
+/// ```
+/// use pkl_eval::EvaluatorOptions;
+///
+/// let source = "name = \"pigeon\"\noutput { renderer = new JsonRenderer { indent = \"\" } }";
+/// let output = pkl_eval::evaluate_output_with(source, "repl:text", EvaluatorOptions::default()).unwrap();
+///
+/// assert_eq!(pkl_render::render_output(&output, None).unwrap(), "{\"name\":\"pigeon\"}\n");
+/// ```
+pub fn render_output(output: &ModuleOutput, renderer: Option<&dyn Renderer>) -> Result<String> {
+    if let Some(text) = &output.text {
+        return Ok(text.clone());
+    }
+    match (renderer, &output.renderer) {
+        (Some(renderer), _) => renderer.render(&output.value),
+        (None, Some(renderer)) => renderer_of(renderer)?.render(&output.value),
+        (None, None) => PcfRenderer::new(PcfOptions::default()).render(&output.value),
+    }
+}
+
+/// The renderer an `output.renderer` object describes, like `new YamlRenderer { isStream = true }`.
+pub fn renderer_of(renderer: &Object) -> Result<Box<dyn Renderer>> {
+    let mut path = Path::default();
+    path.push_property("output");
+    path.push_property("renderer");
+    let options = Options { renderer, path };
+
+    Ok(match renderer.kind.class_name() {
+        "JsonRenderer" => Box::new(JsonRenderer::new(JsonOptions {
+            indent: options.string("indent")?.unwrap_or_else(|| JsonOptions::default().indent),
+            omit_null_properties: options.boolean("omitNullProperties")?.unwrap_or_default(),
+        })),
+        "PcfRenderer" => Box::new(PcfRenderer::new(PcfOptions {
+            indent: options.string("indent")?.unwrap_or_else(|| PcfOptions::default().indent),
+            omit_null_properties: options.boolean("omitNullProperties")?.unwrap_or_default(),
+        })),
+        "YamlRenderer" => Box::new(YamlRenderer::new(YamlOptions {
+            is_stream: options.boolean("isStream")?.unwrap_or_default(),
+            omit_null_properties: options.boolean("omitNullProperties")?.unwrap_or_default(),
+        })),
+        "PListRenderer" => Box::new(PListRenderer),
+        "PropertiesRenderer" => Box::new(PropertiesRenderer::new(PropertiesOptions {
+            omit_null_properties: options.boolean("omitNullProperties")?.unwrap_or(true),
+            restrict_charset: options.boolean("restrictCharset")?.unwrap_or_default(),
+        })),
+        "pkl.xml#Renderer" => {
+            let defaults = XmlOptions::default();
+            Box::new(XmlRenderer::new(XmlOptions {
+                root_element_name: options.string("rootElementName")?.unwrap_or(defaults.root_element_name),
+                xml_version: options.string("xmlVersion")?.unwrap_or(defaults.xml_version),
+                indent: options.string("indent")?.unwrap_or(defaults.indent),
+            }))
+        }
+        class => return Err(RenderError::new(&options.path, format!("can't render with a `{class}`"))),
+    })
+}
+
+/// Reads the properties of a renderer as options.
+struct Options<'r> {
+    renderer: &'r Object,
+    path: Path,
+}
+
+impl Options<'_> {
+    fn string(&self, name: &str) -> Result<Option<String>> {
+        match self.renderer.property(name) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(value) => Err(self.expected(name, "String", value)),
+        }
+    }
+
+    fn boolean(&self, name: &str) -> Result<Option<bool>> {
+        match self.renderer.property(name) {
+            None => Ok(None),
+            Some(Value::Boolean(value)) => Ok(Some(*value)),
+            Some(value) => Err(self.expected(name, "Boolean", value)),
+        }
+    }
+
+    fn expected(&self, name: &str, expected: &str, value: &Value) -> RenderError {
+        let mut path = self.path.clone();
+        path.push_property(name);
+        RenderError::new(&path, format!("expected a `{expected}`, but got `{}`", value.type_name()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pkl_eval::value::ModuleOutput;
+    use pkl_eval::EvaluatorOptions;
+
+    use super::render_output;
+    use crate::{JsonOptions, JsonRenderer};
+
+    fn output(source: &str) -> ModuleOutput {
+        pkl_eval::evaluate_output_with(source, "repl:text", EvaluatorOptions::default())
+            .unwrap_or_else(|error| panic!("{source}: {error:?}"))
+    }
+
+    #[test]
+    fn renderers() {
+        let pcf = output("name = \"pigeon\"");
+        assert_eq!(render_output(&pcf, None).unwrap(), "name = \"pigeon\"\n");
+        let json = JsonRenderer::new(JsonOptions::default());
+        assert_eq!(render_output(&pcf, Some(&json)).unwrap(), "{\n  \"name\": \"pigeon\"\n}\n");
+
+        let yaml = output("birds { \"pigeon\" }\noutput {\n  renderer = new YamlRenderer {}\n  value = birds\n}");
+        assert_eq!(render_output(&yaml, None).unwrap(), "- pigeon\n");
+        let xml = "import \"pkl:xml\"\nname = \"pigeon\"\noutput { renderer = new xml.Renderer { indent = \"\" } }";
+        let xml = output(xml);
+        assert!(render_output(&xml, None).unwrap().ends_with("<root>\n<name>pigeon</name>\n</root>\n"));
+    }
+
+    #[test]
+    fn converters() {
+        let source = "timeout = 5.s\nname = \"pigeon\"\n\
+                      output {\n  renderer = new JsonRenderer {\n    converters {\n\
+                      [Duration] = (it) -> it.value\n      [\"name\"] = (it) -> it.toUpperCase()\n    }\n  }\n}";
+        let json = render_output(&output(source), None).unwrap();
+        assert_eq!(json, "{\n  \"timeout\": 5,\n  \"name\": \"PIGEON\"\n}\n");
+
+        let error = pkl_eval::evaluate_output_with(
+            "output { renderer = new JsonRenderer { converters { [1] = (it) -> it } } }",
+            "repl:text",
+            EvaluatorOptions::default(),
+        );
+        let message = "expected classes or paths as the keys of `converters`, but got `Int`";
+        assert_eq!(error.unwrap_err().to_string(), message);
+    }
+
+    #[test]
+    fn files() {
+        let source = "birds { \"pigeon\" }\n\
+                      output {\n  files {\n\
+                      [\"birds.json\"] { value = birds; renderer = new JsonRenderer { indent = \"\" } }\n\
+                      [\"README\"] { text = \"birds\" }\n  }\n}";
+        let output = output(source);
+        let [(json, birds), (readme, text)] = output.files.as_slice() else { panic!("{:?}", output.files) };
+        assert_eq!((json.as_str(), readme.as_str()), ("birds.json", "README"));
+        assert_eq!(render_output(birds, None).unwrap(), "[\"pigeon\"]\n");
+        assert_eq!(render_output(text, None).unwrap(), "birds");
+    }
+
+    #[test]
+    fn text_and_errors() {
+        let text = output("name = \"pigeon\"\noutput { text = \"hi \\(name)\" }");
+        assert_eq!(text.value.as_object().unwrap().property("output"), None);
+        assert_eq!(render_output(&text, None).unwrap(), "hi pigeon");
+
+        let wrong = output("output { renderer = new JsonRenderer { indent = 2 } }");
+        let message = "expected a `String`, but got `Int` (at `output.renderer.indent`)";
+        assert_eq!(render_output(&wrong, None).unwrap_err().to_string(), message);
+        let unknown = output("class Custom {}\noutput { renderer = new Custom {} }");
+        let message = "can't render with a `Custom` (at `output.renderer`)";
+        assert_eq!(render_output(&unknown, None).unwrap_err().to_string(), message);
+    }
+}
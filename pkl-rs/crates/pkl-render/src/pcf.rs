@@ -0,0 +1,320 @@
+//! Pcf output, the static subset of Pkl that `pkl eval` renders by default.
+//!
+//! A module renders as its properties, one per line, with objects written as amending blocks (`server { ... }`)
+//! rather than with `=`. Objects that aren't the value of a property or entry are written as `new { ... }`, and the
+//! classes of objects aren't kept. Strings of several lines are written as multi-line strings. Functions are the
+//! only values Pcf has no syntax for.
+
+use pkl_eval::value::{Object, Value};
+use pkl_lexer::identifier::is_identifier;
+
+use crate::{unrenderable, Path, Renderer, Result};
+
+const FORMAT: &str = "Pcf";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcfOptions {
+    /// The text of one level of indentation
+    pub indent: String,
+    /// Whether properties whose value is `null` are left out
+    pub omit_null_properties: bool,
+}
+
+impl Default for PcfOptions {
+    fn default() -> Self {
+        PcfOptions { indent: "  ".to_string(), omit_null_properties: false }
+    }
+}
+
+/// Renders values as Pcf.
+///
+/// ```
+/// use pkl_render::{PcfOptions, PcfRenderer, Renderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"\ntags = new Listing { \"bird\" }").unwrap();
+/// let pcf = PcfRenderer::new(PcfOptions::default()).render(&module).unwrap();
+///
+/// assert_eq!(pcf, "name = \"pigeon\"\ntags {\n  \"bird\"\n}\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PcfRenderer {
+    options: PcfOptions,
+}
+
+impl PcfRenderer {
+    pub fn new(options: PcfOptions) -> Self {
+        PcfRenderer { options }
+    }
+}
+
+impl Renderer for PcfRenderer {
+    fn render(&self, value: &Value) -> Result<String> {
+        let mut writer = Writer { options: &self.options, out: String::new(), level: 0, path: Path::default() };
+        match value {
+            Value::Object(object) => writer.members(object)?,
+            value => {
+                writer.value(value)?;
+                writer.out.push('\n');
+            }
+        }
+        Ok(writer.out)
+    }
+}
+
+struct Writer<'o> {
+    options: &'o PcfOptions,
+    out: String,
+    /// The indentation of the current line
+    level: usize,
+    path: Path,
+}
+
+impl Writer<'_> {
+    /// Writes the members of an object, one per line at the current level.
+    fn members(&mut self, object: &Object) -> Result<()> {
+        for (name, value) in &object.properties {
+            if self.options.omit_null_properties && *value == Value::Null {
+                continue;
+            }
+            self.indent();
+            self.name(name);
+            self.path.push_property(name);
+            self.member_value(value)?;
+            self.path.pop();
+        }
+        for (key, value) in &object.entries {
+            self.indent();
+            self.out.push('[');
+            self.value(key)?;
+            self.out.push(']');
+            self.path.push_key(key);
+            self.member_value(value)?;
+            self.path.pop();
+        }
+        for (index, element) in object.elements.iter().enumerate() {
+            self.indent();
+            self.path.push_index(index);
+            self.value(element)?;
+            self.path.pop();
+            self.out.push('\n');
+        }
+        Ok(())
+    }
+
+    /// Writes what follows the name or key of a property or entry, up to the end of its line.
+    fn member_value(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Object(object) => {
+                self.out.push(' ');
+                self.object(object)?;
+            }
+            value => {
+                self.out.push_str(" = ");
+                self.value(value)?;
+            }
+        }
+        self.out.push('\n');
+        Ok(())
+    }
+
+    fn value(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Null => self.out.push_str("null"),
+            Value::Boolean(b) => self.out.push_str(if *b { "true" } else { "false" }),
+            Value::Int(n) => self.out.push_str(&n.to_string()),
+            Value::Float(x) => self.out.push_str(&pkl_stdlib::number::float_to_string(*x)),
+            Value::String(s) => self.string(s),
+            Value::Duration(duration) => self.out.push_str(&duration.to_string()),
+            Value::DataSize(size) => self.out.push_str(&size.to_string()),
+            Value::Regex { pattern } => {
+                self.out.push_str("Regex(");
+                self.string(pattern);
+                self.out.push(')');
+            }
+            Value::List(elements) => self.collection("List", elements.iter())?,
+            Value::Set(elements) => self.collection("Set", elements.iter())?,
+            Value::Map(entries) => self.collection("Map", entries.iter().flat_map(|(key, value)| [key, value]))?,
+            Value::Object(object) => {
+                self.out.push_str("new ");
+                self.object(object)?;
+            }
+            Value::Function { .. } | Value::Class { .. } => return Err(unrenderable(&self.path, value, FORMAT)),
+        }
+        Ok(())
+    }
+
+    /// Writes a collection as a call of its constructor, like `List(1, 2)`.
+    fn collection<'v>(&mut self, class: &str, values: impl Iterator<Item = &'v Value>) -> Result<()> {
+        self.out.push_str(class);
+        self.out.push('(');
+        for (index, value) in values.enumerate() {
+            if index > 0 {
+                self.out.push_str(", ");
+            }
+            self.path.push_index(index);
+            self.value(value)?;
+            self.path.pop();
+        }
+        self.out.push(')');
+        Ok(())
+    }
+
+    /// Writes the body of an object, from its opening brace to its closing one.
+    fn object(&mut self, object: &Object) -> Result<()> {
+        let path = std::mem::take(&mut self.path);
+        let mut body = Writer { options: self.options, out: String::new(), level: self.level + 1, path };
+        let result = body.members(object);
+        self.path = body.path;
+        result?;
+
+        if body.out.is_empty() {
+            self.out.push_str("{}");
+        } else {
+            self.out.push_str("{\n");
+            self.out.push_str(&body.out);
+            self.indent();
+            self.out.push('}');
+        }
+        Ok(())
+    }
+
+    /// Writes a property name, in backticks if it isn't an identifier.
+    fn name(&mut self, name: &str) {
+        if is_identifier(name) {
+            self.out.push_str(name);
+        } else {
+            self.out.push('`');
+            self.out.push_str(name);
+            self.out.push('`');
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        if !s.contains('\n') {
+            self.out.push_str(&pkl_stdlib::string::quote(s));
+            return;
+        }
+
+        // The lines are indented one level deeper than the line the string starts on, as is the closing delimiter.
+        let mut indent = self.options.indent.repeat(self.level + 1);
+        self.out.push_str("\"\"\"");
+        for line in s.split('\n') {
+            self.out.push('\n');
+            if !line.is_empty() {
+                self.out.push_str(&indent);
+            }
+            let mut quotes = 0;
+            for c in line.chars() {
+                quotes = if c == '"' { quotes + 1 } else { 0 };
+                match c {
+                    '\\' => self.out.push_str("\\\\"),
+                    '\r' => self.out.push_str("\\r"),
+                    '\t' => self.out.push_str("\\t"),
+                    // Escaping every third quote keeps a run of them from closing the string.
+                    '"' if quotes == 3 => {
+                        self.out.push_str("\\\"");
+                        quotes = 0;
+                    }
+                    c if c.is_control() => self.out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                    c => self.out.push(c),
+                }
+            }
+        }
+        self.out.push('\n');
+        indent.push_str("\"\"\"");
+        self.out.push_str(&indent);
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.level {
+            self.out.push_str(&self.options.indent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(source: &str, options: PcfOptions) -> Result<String> {
+        let module = pkl_eval::evaluate(source).unwrap_or_else(|error| panic!("{source}: {error}"));
+        PcfRenderer::new(options).render(&module)
+    }
+
+    #[test]
+    fn values() {
+        let source = r#"
+            a = 1
+            b = -1.0 / 0
+            c = 5.min
+            d = List(1, "two", Map("k", 1.5.gb))
+            e = null
+            `my-name` = Regex("\\d")
+            f {}
+            g { h { i = true } }
+            j = new Mapping { ["k"] = 1; [2] { l = 3 } }
+            m = new Listing { 1; new { n = 2 } }
+            o = List(new Dynamic { p = 1 })
+        "#;
+        let expected = r#"a = 1
+b = -Infinity
+c = 5.min
+d = List(1, "two", Map("k", 1.5.gb))
+e = null
+`my-name` = Regex("\\d")
+f {}
+g {
+  h {
+    i = true
+  }
+}
+j {
+  ["k"] = 1
+  [2] {
+    l = 3
+  }
+}
+m {
+  1
+  new {
+    n = 2
+  }
+}
+o = List(new {
+  p = 1
+})
+"#;
+        assert_eq!(render(source, PcfOptions::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn strings() {
+        let source = r#"
+            a = "say \"hi\"\t\\(x)"
+            b { c = "one\n\ntwo \"\"\"\n" }
+        "#;
+        let expected = r#"a = "say \"hi\"\t\\(x)"
+b {
+  c = """
+    one
+
+    two ""\"
+
+    """
+}
+"#;
+        let pcf = render(source, PcfOptions::default()).unwrap();
+        assert_eq!(pcf, expected);
+        // The output is Pkl that evaluates to the same values.
+        assert_eq!(pkl_eval::evaluate(&pcf).unwrap(), pkl_eval::evaluate(source).unwrap());
+    }
+
+    #[test]
+    fn options() {
+        let options = PcfOptions { indent: "    ".to_string(), omit_null_properties: true };
+        assert_eq!(render("a = null\nb { c = null; d = 1 }", options).unwrap(), "b {\n    d = 1\n}\n");
+
+        let error = render("f = (x) -> x", PcfOptions::default()).unwrap_err().to_string();
+        assert_eq!(error, "can't render a value of type `Function` as Pcf (at `f`)");
+    }
+}
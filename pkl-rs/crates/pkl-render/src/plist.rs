@@ -0,0 +1,189 @@
+//! XML property list output, as read by Apple's tools.
+//!
+//! Lists and listings are `<array>`s, and maps and objects `<dict>`s, whose keys have to be strings. Property lists
+//! have no null, so properties whose value is `null` are left out and any other `null` is rejected, like `Duration`s
+//! and `DataSize`s are.
+
+use pkl_eval::value::Value;
+
+use crate::{escape_xml, shape, unrenderable, Key, Path, RenderError, Renderer, Result, Shape};
+
+const FORMAT: &str = "a property list";
+const INDENT: &str = "  ";
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+"#;
+
+/// Renders values as XML property lists.
+///
+/// ```
+/// use pkl_render::{PListRenderer, Renderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"").unwrap();
+/// let plist = PListRenderer.render(&module).unwrap();
+///
+/// assert!(plist.ends_with("<dict>\n  <key>name</key>\n  <string>pigeon</string>\n</dict>\n</plist>\n"));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PListRenderer;
+
+impl Renderer for PListRenderer {
+    fn render(&self, value: &Value) -> Result<String> {
+        let mut writer = Writer { out: HEADER.to_string(), path: Path::default() };
+        writer.value(value, 0)?;
+        writer.out.push_str("</plist>\n");
+        Ok(writer.out)
+    }
+}
+
+struct Writer {
+    out: String,
+    path: Path,
+}
+
+impl Writer {
+    /// Writes a value on lines of its own, indented for `level`.
+    fn value(&mut self, value: &Value, level: usize) -> Result<()> {
+        match value {
+            Value::Boolean(b) => self.line(level, if *b { "<true/>" } else { "<false/>" }),
+            Value::Int(n) => self.line(level, &format!("<integer>{n}</integer>")),
+            Value::Float(x) => self.line(level, &format!("<real>{}</real>", real(*x))),
+            Value::String(s) => self.line(level, &format!("<string>{}</string>", escape_xml(s, false))),
+            Value::List(elements) | Value::Set(elements) => self.array(elements, level)?,
+            Value::Map(entries) => {
+                self.dict(entries.iter().map(|(key, value)| (Key::Entry(key), value)).collect(), level)?
+            }
+            Value::Object(object) => match shape(object, &self.path, FORMAT)? {
+                Shape::List(elements) => self.array(elements, level)?,
+                Shape::Map(members) => self.dict(members, level)?,
+            },
+            Value::Null
+            | Value::Duration(_)
+            | Value::DataSize(_)
+            | Value::Function { .. }
+            | Value::Class { .. }
+            | Value::Regex { .. } => return Err(unrenderable(&self.path, value, FORMAT)),
+        }
+        Ok(())
+    }
+
+    fn array(&mut self, elements: &[Value], level: usize) -> Result<()> {
+        if elements.is_empty() {
+            self.line(level, "<array/>");
+            return Ok(());
+        }
+
+        self.line(level, "<array>");
+        for (index, element) in elements.iter().enumerate() {
+            self.path.push_index(index);
+            self.value(element, level + 1)?;
+            self.path.pop();
+        }
+        self.line(level, "</array>");
+        Ok(())
+    }
+
+    fn dict(&mut self, members: Vec<(Key, &Value)>, level: usize) -> Result<()> {
+        let is_null_property = |(key, value): &(Key, &Value)| matches!(key, Key::Property(_)) && **value == Value::Null;
+        let members: Vec<_> = members.into_iter().filter(|member| !is_null_property(member)).collect();
+        if members.is_empty() {
+            self.line(level, "<dict/>");
+            return Ok(());
+        }
+
+        self.line(level, "<dict>");
+        for (key, value) in members {
+            self.path.push(&key);
+            let key = match key {
+                Key::Property(name) => name,
+                Key::Entry(Value::String(key)) => key,
+                Key::Entry(key) => {
+                    let message = format!("can't render a key of type `{}` as {FORMAT}", key.type_name());
+                    return Err(RenderError::new(&self.path, message));
+                }
+            };
+            self.line(level + 1, &format!("<key>{}</key>", escape_xml(key, false)));
+            self.value(value, level + 1)?;
+            self.path.pop();
+        }
+        self.line(level, "</dict>");
+        Ok(())
+    }
+
+    fn line(&mut self, level: usize, text: &str) {
+        for _ in 0..level {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+}
+
+/// A float as the text of a `<real>`, which spells the special values the way Apple's parser reads them.
+fn real(x: f64) -> String {
+    if x.is_nan() {
+        "nan".to_string()
+    } else if x.is_infinite() {
+        if x > 0.0 { "+infinity" } else { "-infinity" }.to_string()
+    } else {
+        pkl_stdlib::number::float_to_string(x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(source: &str) -> Result<String> {
+        let module = pkl_eval::evaluate(source).unwrap_or_else(|error| panic!("{source}: {error}"));
+        PListRenderer.render(&module)
+    }
+
+    #[test]
+    fn values() {
+        let source = r#"
+            name = "<pigeon> & co"
+            age = 3
+            weight = 0.5
+            flies = true
+            missing = null
+            tags { "bird"; 1.0 / 0 }
+            home { city = "Paris" }
+            empty = new Listing {}
+        "#;
+        let expected = r#"<dict>
+  <key>name</key>
+  <string>&lt;pigeon&gt; &amp; co</string>
+  <key>age</key>
+  <integer>3</integer>
+  <key>weight</key>
+  <real>0.5</real>
+  <key>flies</key>
+  <true/>
+  <key>tags</key>
+  <array>
+    <string>bird</string>
+    <real>+infinity</real>
+  </array>
+  <key>home</key>
+  <dict>
+    <key>city</key>
+    <string>Paris</string>
+  </dict>
+  <key>empty</key>
+  <array/>
+</dict>
+</plist>
+"#;
+        assert_eq!(render(source).unwrap(), format!("{HEADER}{expected}"));
+    }
+
+    #[test]
+    fn unrepresentable_values() {
+        let error = |source| render(source).unwrap_err().to_string();
+        assert_eq!(error("a { null }"), "can't render a value of type `Null` as a property list (at `a[0]`)");
+        assert_eq!(error("a = Map(1, 2)"), "can't render a key of type `Int` as a property list (at `a[1]`)");
+        assert_eq!(error("a = 1.s"), "can't render a value of type `Duration` as a property list (at `a`)");
+    }
+}
@@ -0,0 +1,188 @@
+//! Java properties output, as read by `java.util.Properties.load`.
+//!
+//! Every property of a module is a `key=value` line, and the members of nested maps and objects are flattened into
+//! keys joined by dots, so `server { port = 80 }` renders as `server.port=80`. Only strings, numbers, and booleans can
+//! be values, and only strings, ints, and booleans can be keys; there is no notation for lists.
+
+use pkl_eval::value::Value;
+
+use crate::{shape, unrenderable, Key, Path, RenderError, Renderer, Result, Shape};
+
+const FORMAT: &str = "Java properties";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertiesOptions {
+    /// Whether properties whose value is `null` are left out, rather than rendered with an empty value
+    pub omit_null_properties: bool,
+    /// Whether characters outside of ASCII are written as `\uXXXX` escapes, for readers that expect ISO 8859-1
+    pub restrict_charset: bool,
+}
+
+impl Default for PropertiesOptions {
+    fn default() -> Self {
+        PropertiesOptions { omit_null_properties: true, restrict_charset: false }
+    }
+}
+
+/// Renders values as Java properties.
+///
+/// ```
+/// use pkl_render::{PropertiesOptions, PropertiesRenderer, Renderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"\nhome { city = \"Paris\" }").unwrap();
+/// let properties = PropertiesRenderer::new(PropertiesOptions::default()).render(&module).unwrap();
+///
+/// assert_eq!(properties, "name=pigeon\nhome.city=Paris\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PropertiesRenderer {
+    options: PropertiesOptions,
+}
+
+impl PropertiesRenderer {
+    pub fn new(options: PropertiesOptions) -> Self {
+        PropertiesRenderer { options }
+    }
+}
+
+impl Renderer for PropertiesRenderer {
+    fn render(&self, value: &Value) -> Result<String> {
+        let mut writer = Writer { options: &self.options, out: String::new(), path: Path::default() };
+        match writer.members(value)? {
+            Some(members) => writer.flatten(members, "")?,
+            None => return Err(unrenderable(&writer.path, value, FORMAT)),
+        }
+        Ok(writer.out)
+    }
+}
+
+struct Writer<'o> {
+    options: &'o PropertiesOptions,
+    out: String,
+    path: Path,
+}
+
+impl Writer<'_> {
+    /// The members of a value that is flattened into keys, or nothing for a value that isn't.
+    fn members<'v>(&self, value: &'v Value) -> Result<Option<Vec<(Key<'v>, &'v Value)>>> {
+        Ok(match value {
+            Value::Map(entries) => Some(entries.iter().map(|(key, value)| (Key::Entry(key), value)).collect()),
+            Value::Object(object) => match shape(object, &self.path, FORMAT)? {
+                Shape::Map(members) => Some(members),
+                Shape::List(_) => return Err(unrenderable(&self.path, value, FORMAT)),
+            },
+            _ => None,
+        })
+    }
+
+    /// Writes a line for each member, whose keys start with `prefix`.
+    fn flatten(&mut self, members: Vec<(Key, &Value)>, prefix: &str) -> Result<()> {
+        for (key, value) in members {
+            if self.options.omit_null_properties && matches!(key, Key::Property(_)) && *value == Value::Null {
+                continue;
+            }
+            self.path.push(&key);
+            let key = match key {
+                Key::Property(name) => format!("{prefix}{name}"),
+                Key::Entry(key) => format!("{prefix}{}", self.scalar(key, "key")?),
+            };
+            match self.members(value)? {
+                Some(members) => self.flatten(members, &format!("{key}."))?,
+                None => {
+                    let value = match value {
+                        Value::Null => String::new(),
+                        value => self.scalar(value, "value")?,
+                    };
+                    let (key, value) = (self.escape(&key, true), self.escape(&value, false));
+                    self.out.push_str(&format!("{key}={value}\n"));
+                }
+            }
+            self.path.pop();
+        }
+        Ok(())
+    }
+
+    /// The text of a key or value; `what` says which for the error message.
+    fn scalar(&self, value: &Value, what: &str) -> Result<String> {
+        Ok(match value {
+            Value::String(s) => s.clone(),
+            Value::Boolean(b) => b.to_string(),
+            Value::Int(n) => n.to_string(),
+            Value::Float(x) if what == "value" => pkl_stdlib::number::float_to_string(*x),
+            value => {
+                let message = format!("can't render a {what} of type `{}` as {FORMAT}", value.type_name());
+                return Err(RenderError::new(&self.path, message));
+            }
+        })
+    }
+
+    /// Escapes a key or value as `Properties.load` reads it back: keys end at the first unescaped space, `=`, or `:`,
+    /// and leading spaces of values are skipped.
+    fn escape(&self, s: &str, is_key: bool) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        for (index, c) in s.chars().enumerate() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                '\u{c}' => escaped.push_str("\\f"),
+                ' ' if is_key || index == 0 => escaped.push_str("\\ "),
+                '=' | ':' | '#' | '!' if is_key || index == 0 => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                c if c.is_control() || (self.options.restrict_charset && !c.is_ascii()) => {
+                    let mut units = [0; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        escaped.push_str(&format!("\\u{unit:04X}"));
+                    }
+                }
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(source: &str, options: PropertiesOptions) -> Result<String> {
+        let module = pkl_eval::evaluate(source).unwrap_or_else(|error| panic!("{source}: {error}"));
+        PropertiesRenderer::new(options).render(&module)
+    }
+
+    #[test]
+    fn flattening() {
+        let source = r#"
+            name = "pigeon"
+            missing = null
+            server { port = 80; hosts = new Mapping { ["a b"] = true; [1] = 1.5 } }
+            `x=y` = Map("k", Map(false, " #lead=ing "))
+        "#;
+        let expected = "\
+name=pigeon
+server.port=80
+server.hosts.a\\ b=true
+server.hosts.1=1.5
+x\\=y.k.false=\\ #lead=ing \n";
+        assert_eq!(render(source, PropertiesOptions::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn options() {
+        let options = PropertiesOptions { omit_null_properties: false, restrict_charset: true };
+        let properties = render("a = null\nb = \"caf\u{e9} \u{1F426}\\n\"", options).unwrap();
+        assert_eq!(properties, "a=\nb=caf\\u00E9 \\uD83D\\uDC26\\n\n");
+    }
+
+    #[test]
+    fn unrepresentable_values() {
+        let error = |source| render(source, PropertiesOptions::default()).unwrap_err().to_string();
+        assert_eq!(error("a { 1 }"), "can't render a value of type `Dynamic` as Java properties (at `a`)");
+        assert_eq!(error("a = List(1)"), "can't render a value of type `List` as Java properties (at `a`)");
+        assert_eq!(error("a = Map(1.5, 1)"), "can't render a key of type `Float` as Java properties (at `a[Float]`)");
+    }
+}
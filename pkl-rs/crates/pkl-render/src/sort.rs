@@ -0,0 +1,101 @@
+//! Rendering with the keys of objects and maps sorted, for output that's the same however a module orders its
+//! properties and entries.
+//!
+//! Without sorting, properties are rendered in the order they're declared in, with those of the amended object first,
+//! and entries in the order they're added, which is how Pkl renders them.
+
+use std::cmp::Ordering;
+
+use pkl_eval::value::Value;
+
+use crate::{Renderer, Result};
+
+/// A renderer that sorts the properties of objects by name, and the entries of objects and maps by key, before
+/// rendering, made by [`Renderer::with_sorted_keys`].
+///
+/// Keys of different classes are sorted `null` first, then Booleans, numbers, and strings, followed by any others in
+/// the order they were in.
+///
+/// ```
+/// use pkl_render::{PcfOptions, PcfRenderer, Renderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"\nage = 3").unwrap();
+/// let renderer = PcfRenderer::new(PcfOptions::default()).with_sorted_keys();
+///
+/// assert_eq!(renderer.render(&module).unwrap(), "age = 3\nname = \"pigeon\"\n");
+/// ```
+pub struct Sorted<R> {
+    renderer: R,
+}
+
+impl<R> Sorted<R> {
+    pub(crate) fn new(renderer: R) -> Self {
+        Sorted { renderer }
+    }
+}
+
+impl<R: Renderer> Renderer for Sorted<R> {
+    fn render(&self, value: &Value) -> Result<String> {
+        self.renderer.render(&sort_keys(value.clone()))
+    }
+}
+
+/// A value with the keys of the objects and maps inside it sorted.
+pub fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(mut object) => {
+            let properties = object.properties.drain(..).map(|(name, value)| (name, sort_keys(value)));
+            object.properties = properties.collect();
+            object.properties.sort_keys();
+            object.entries = sort_entries(object.entries);
+            object.elements = object.elements.into_iter().map(sort_keys).collect();
+            Value::Object(object)
+        }
+        Value::Map(entries) => Value::Map(sort_entries(entries)),
+        Value::List(elements) => Value::List(elements.into_iter().map(sort_keys).collect()),
+        Value::Set(elements) => Value::Set(elements.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+fn sort_entries(entries: Vec<(Value, Value)>) -> Vec<(Value, Value)> {
+    let mut entries: Vec<_> = entries.into_iter().map(|(key, value)| (key, sort_keys(value))).collect();
+    // a stable sort, so keys that don't compare keep their order
+    entries.sort_by(|(a, _), (b, _)| compare_keys(a, b));
+    entries
+}
+
+fn compare_keys(a: &Value, b: &Value) -> Ordering {
+    fn rank(key: &Value) -> u8 {
+        match key {
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::Int(_) | Value::Float(_) => 2,
+            Value::String(_) => 3,
+            _ => 4,
+        }
+    }
+    match (a, b) {
+        (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        (Value::Int(a), Value::Float(b)) => (*a as f64).total_cmp(b),
+        (Value::Float(a), Value::Int(b)) => a.total_cmp(&(*b as f64)),
+        (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{JsonOptions, JsonRenderer, Renderer};
+
+    #[test]
+    fn nested_keys() {
+        let source = "b { z = 1; y { [\"q\"] = 1; [\"p\"] = 2 } }\na = Map(\"y\", 1, \"x\", 2)";
+        let module = pkl_eval::evaluate(source).unwrap();
+        let json = JsonRenderer::new(JsonOptions { indent: String::new(), ..JsonOptions::default() });
+        let rendered = json.with_sorted_keys().render(&module).unwrap();
+        assert_eq!(rendered, "{\"a\":{\"x\":2,\"y\":1},\"b\":{\"y\":{\"p\":2,\"q\":1},\"z\":1}}\n");
+    }
+}
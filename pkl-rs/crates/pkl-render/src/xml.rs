@@ -0,0 +1,401 @@
+//! XML output, including the markup made by the `pkl:xml` module.
+//!
+//! A module renders as a root element with an element per property, whose content is the text of the property's
+//! value or, for maps and objects, again an element per member. The elements of lists and listings are wrapped in an
+//! element named after their type, like `<string>` or `<int>`.
+//!
+//! The values made by `pkl:xml` are written as what they stand for, wherever they are: `xml.Element("a")` as an `<a>`
+//! element with the `attributes` of the object and its elements as content, `xml.Comment` as a comment, and
+//! `xml.Inline` as the members of its value, without an element around them. The text of a `RenderDirective`, which
+//! is what `xml.CData` makes, is written as is.
+
+use pkl_eval::value::{Object, Value};
+
+use crate::{escape_xml, unrenderable, Path, RenderError, Renderer, Result};
+
+const FORMAT: &str = "XML";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlOptions {
+    /// The name of the element the rendered value is the content of
+    pub root_element_name: String,
+    /// The version in the XML declaration, `1.0` or `1.1`
+    pub xml_version: String,
+    /// The text of one level of indentation
+    pub indent: String,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        XmlOptions { root_element_name: "root".to_string(), xml_version: "1.0".to_string(), indent: "  ".to_string() }
+    }
+}
+
+/// Renders values as XML.
+///
+/// ```
+/// use pkl_render::{Renderer, XmlOptions, XmlRenderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"\ntags { \"bird\" }").unwrap();
+/// let xml = XmlRenderer::new(XmlOptions::default()).render(&module).unwrap();
+///
+/// let expected = "<name>pigeon</name>\n  <tags>\n    <string>bird</string>\n  </tags>\n</root>\n";
+/// assert!(xml.ends_with(expected));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct XmlRenderer {
+    options: XmlOptions,
+}
+
+impl XmlRenderer {
+    pub fn new(options: XmlOptions) -> Self {
+        XmlRenderer { options }
+    }
+}
+
+impl Renderer for XmlRenderer {
+    fn render(&self, value: &Value) -> Result<String> {
+        let header = format!("<?xml version=\"{}\" encoding=\"UTF-8\"?>\n", self.options.xml_version);
+        let mut writer = Writer { options: &self.options, out: header, path: Path::default(), compact: false };
+        writer.named(&self.options.root_element_name, value, 0)?;
+        Ok(writer.out)
+    }
+}
+
+/// A value made by `pkl:xml`, or a `RenderDirective`.
+enum Markup<'v> {
+    Element(&'v Object),
+    Comment(&'v str),
+    Inline(&'v Value),
+    Directive(&'v str),
+}
+
+fn markup(value: &Value) -> Option<Markup<'_>> {
+    let Value::Object(object) = value else { return None };
+    let flag = |name| object.property(name) == Some(&Value::Boolean(true));
+    let text = |name| match object.property(name) {
+        Some(Value::String(s)) => Some(s.as_str()),
+        _ => None,
+    };
+
+    if object.kind.class_name() == "RenderDirective" {
+        text("text").map(Markup::Directive)
+    } else if flag("_isXmlElement") {
+        Some(Markup::Element(object))
+    } else if flag("_isXmlComment") {
+        text("value").map(Markup::Comment)
+    } else if flag("_isXmlInline") {
+        object.property("value").map(Markup::Inline)
+    } else {
+        None
+    }
+}
+
+struct Writer<'o> {
+    options: &'o XmlOptions,
+    out: String,
+    path: Path,
+    /// Whether lines are being joined into one, for the content of an element that isn't in block format
+    compact: bool,
+}
+
+impl Writer<'_> {
+    /// Writes a property or entry as an element called `name`, unless its value is markup.
+    fn named(&mut self, name: &str, value: &Value, level: usize) -> Result<()> {
+        if let Some(markup) = markup(value) {
+            return self.markup(markup, level);
+        }
+
+        self.check_name(name)?;
+        match value {
+            Value::Null => self.line(level, &format!("<{name}/>")),
+            Value::List(_) | Value::Set(_) | Value::Map(_) | Value::Object(_) => {
+                if is_empty(value) {
+                    self.line(level, &format!("<{name}/>"));
+                } else {
+                    self.line(level, &format!("<{name}>"));
+                    self.content(value, level + 1)?;
+                    self.line(level, &format!("</{name}>"));
+                }
+            }
+            value => {
+                let text = self.text(value)?;
+                self.line(level, &format!("<{name}>{text}</{name}>"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the members of a collection or object.
+    fn content(&mut self, value: &Value, level: usize) -> Result<()> {
+        let (properties, entries, elements) = match value {
+            Value::List(elements) | Value::Set(elements) => (None, &[][..], &elements[..]),
+            Value::Map(entries) => (None, &entries[..], &[][..]),
+            Value::Object(object) => (Some(object), &object.entries[..], &object.elements[..]),
+            value => {
+                let text = self.text(value)?;
+                self.line(level, &text);
+                return Ok(());
+            }
+        };
+
+        for (name, value) in properties.iter().flat_map(|object| &object.properties) {
+            self.path.push_property(name);
+            self.named(name, value, level)?;
+            self.path.pop();
+        }
+        for (key, value) in entries {
+            self.path.push_key(key);
+            match key {
+                Value::String(key) => self.named(key, value, level)?,
+                key => {
+                    let message = format!("can't render a key of type `{}` as {FORMAT}", key.type_name());
+                    return Err(RenderError::new(&self.path, message));
+                }
+            }
+            self.path.pop();
+        }
+        for (index, element) in elements.iter().enumerate() {
+            self.path.push_index(index);
+            self.item(element, level)?;
+            self.path.pop();
+        }
+        Ok(())
+    }
+
+    /// Writes an element of a collection, in an element named after its type.
+    fn item(&mut self, value: &Value, level: usize) -> Result<()> {
+        if let Some(markup) = markup(value) {
+            return self.markup(markup, level);
+        }
+
+        let name = match value {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            // the simple name of the class, without the module it is declared in
+            value => value.type_name().rsplit(['.', '#']).next().unwrap_or_default(),
+        };
+        self.named(name, value, level)
+    }
+
+    fn markup(&mut self, markup: Markup, level: usize) -> Result<()> {
+        match markup {
+            Markup::Element(object) => self.element(object, level)?,
+            Markup::Comment(text) => {
+                if text.contains("--") {
+                    return Err(RenderError::new(&self.path, "an XML comment can't contain `--`"));
+                }
+                self.line(level, &format!("<!-- {text} -->"));
+            }
+            Markup::Inline(value) => self.content(value, level)?,
+            Markup::Directive(text) => self.line(level, text),
+        }
+        Ok(())
+    }
+
+    /// Writes an `xml.Element`, with its elements on lines of their own in block format, unless they're all text.
+    fn element(&mut self, object: &Object, level: usize) -> Result<()> {
+        let Some(Value::String(name)) = object.property("name") else {
+            return Err(RenderError::new(&self.path, "the name of an XML element has to be a `String`"));
+        };
+        self.check_name(name)?;
+
+        let mut start = format!("<{name}");
+        if let Some(attributes) = object.property("attributes").and_then(Value::as_object) {
+            let properties = attributes.properties.iter().map(|(name, value)| (name.as_str(), value));
+            let entries = attributes.entries.iter().filter_map(|(key, value)| match key {
+                Value::String(key) => Some((key.as_str(), value)),
+                _ => None,
+            });
+            for (attribute, value) in properties.chain(entries) {
+                self.check_name(attribute)?;
+                let value = match value {
+                    Value::String(s) => escape_xml(s, true),
+                    value => self.text(value)?,
+                };
+                start.push_str(&format!(" {attribute}=\"{value}\""));
+            }
+        }
+
+        let children = &object.elements;
+        if children.is_empty() {
+            self.line(level, &format!("{start}/>"));
+            return Ok(());
+        }
+        let is_text = |value: &Value| match value {
+            Value::Boolean(_) | Value::Int(_) | Value::Float(_) | Value::String(_) => true,
+            value => matches!(markup(value), Some(Markup::Directive(_))),
+        };
+        let block = object.property("isBlockFormat") != Some(&Value::Boolean(false));
+        let compact = self.compact || !block || children.iter().all(is_text);
+
+        let outer = self.compact;
+        self.start_line(level);
+        self.out.push_str(&start);
+        self.out.push('>');
+        self.compact = compact;
+        self.end_line();
+        for (index, child) in children.iter().enumerate() {
+            self.path.push_index(index);
+            match child {
+                Value::Null => {}
+                child if markup(child).is_some() || !is_text(child) => self.item(child, level + 1)?,
+                child => {
+                    let text = self.text(child)?;
+                    self.line(level + 1, &text);
+                }
+            }
+            self.path.pop();
+        }
+        self.start_line(level);
+        self.compact = outer;
+        self.out.push_str(&format!("</{name}>"));
+        self.end_line();
+        Ok(())
+    }
+
+    /// The escaped text of a value that is the content of an element.
+    fn text(&self, value: &Value) -> Result<String> {
+        Ok(match value {
+            Value::Boolean(b) => b.to_string(),
+            Value::Int(n) => n.to_string(),
+            Value::Float(x) => pkl_stdlib::number::float_to_string(*x),
+            Value::String(s) => escape_xml(s, false),
+            value => return Err(unrenderable(&self.path, value, FORMAT)),
+        })
+    }
+
+    fn check_name(&self, name: &str) -> Result<()> {
+        let mut chars = name.chars();
+        let starts_name = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':');
+        if starts_name && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.')) {
+            Ok(())
+        } else {
+            Err(RenderError::new(&self.path, format!("`{name}` isn't a valid XML name")))
+        }
+    }
+
+    fn line(&mut self, level: usize, text: &str) {
+        self.start_line(level);
+        self.out.push_str(text);
+        self.end_line();
+    }
+
+    fn start_line(&mut self, level: usize) {
+        if !self.compact {
+            for _ in 0..level {
+                self.out.push_str(&self.options.indent);
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        if !self.compact {
+            self.out.push('\n');
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::List(elements) | Value::Set(elements) => elements.is_empty(),
+        Value::Map(entries) => entries.is_empty(),
+        Value::Object(object) => {
+            object.properties.is_empty() && object.entries.is_empty() && object.elements.is_empty()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(source: &str) -> Result<String> {
+        let module = pkl_eval::evaluate(source).unwrap_or_else(|error| panic!("{source}: {error}"));
+        XmlRenderer::new(XmlOptions::default()).render(&module)
+    }
+
+    #[test]
+    fn values() {
+        let source = r#"
+            name = "pigeon & co"
+            age = 3
+            missing = null
+            tags { "bird"; 1; new Listing { true } }
+            home { city = "Paris" }
+            empty {}
+            prices = new Mapping { ["eur"] = 1.5 }
+        "#;
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+  <name>pigeon &amp; co</name>
+  <age>3</age>
+  <missing/>
+  <tags>
+    <string>bird</string>
+    <int>1</int>
+    <Listing>
+      <boolean>true</boolean>
+    </Listing>
+  </tags>
+  <home>
+    <city>Paris</city>
+  </home>
+  <empty/>
+  <prices>
+    <eur>1.5</eur>
+  </prices>
+</root>
+"#;
+        assert_eq!(render(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn markup() {
+        let source = r#"
+            import "pkl:xml"
+            order = (xml.Element("order")) {
+                attributes { ["id"] = "a\"1" }
+                (xml.Element("item")) { "nuts & seeds" }
+                xml.Comment("fresh")
+                xml.CData("<raw>")
+                new RenderDirective { text = "<br/>" }
+            }
+            line = (xml.Element("p")) {
+                isBlockFormat = false
+                "a "
+                (xml.Element("b")) { "bold" }
+                (xml.Element("br"))
+            }
+            extra = xml.Inline(new Dynamic { x = 1; y = 2 })
+        "#;
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+  <order id="a&quot;1">
+    <item>nuts &amp; seeds</item>
+    <!-- fresh -->
+    <![CDATA[<raw>]]>
+    <br/>
+  </order>
+  <p>a <b>bold</b><br/></p>
+  <x>1</x>
+  <y>2</y>
+</root>
+"#;
+        assert_eq!(render(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source| render(source).unwrap_err().to_string();
+        assert_eq!(error("m = Map(\"a b\", 1)"), "`a b` isn't a valid XML name (at `m[\"a b\"]`)");
+        assert_eq!(error("m = Map(1, 1)"), "can't render a key of type `Int` as XML (at `m[1]`)");
+        assert_eq!(error("d = 1.s"), "can't render a value of type `Duration` as XML (at `d`)");
+        let message = "an XML comment can't contain `--` (at `c`)";
+        assert_eq!(error("import \"pkl:xml\"\nc = xml.Comment(\"a--b\")"), message);
+    }
+}
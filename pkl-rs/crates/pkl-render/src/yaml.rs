@@ -0,0 +1,373 @@
+//! YAML output, in block style.
+//!
+//! Nested mappings are indented by two spaces, and the items of a sequence start at the indentation of the key the
+//! sequence is the value of. Strings are only quoted when they would otherwise read as something else, such as
+//! `"true"`, `"1.5"`, or `"a: b"`, including the words YAML 1.1 reads as booleans (`yes`, `off`, ...); strings of
+//! several lines are written as literal block scalars.
+
+use pkl_eval::value::Value;
+
+use crate::{shape, unrenderable, Key, Path, RenderError, Renderer, Result, Shape};
+
+const FORMAT: &str = "YAML";
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct YamlOptions {
+    /// Whether the rendered value is a list of documents, which are rendered one after the other, separated by `---`
+    pub is_stream: bool,
+    /// Whether properties whose value is `null` are left out
+    pub omit_null_properties: bool,
+}
+
+/// Renders values as YAML.
+///
+/// ```
+/// use pkl_render::{Renderer, YamlOptions, YamlRenderer};
+///
+/// let module = pkl_eval::evaluate("name = \"pigeon\"\ntags { \"bird\" }").unwrap();
+/// let yaml = YamlRenderer::new(YamlOptions::default()).render(&module).unwrap();
+///
+/// assert_eq!(yaml, "name: pigeon\ntags:\n- bird\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct YamlRenderer {
+    options: YamlOptions,
+}
+
+impl YamlRenderer {
+    pub fn new(options: YamlOptions) -> Self {
+        YamlRenderer { options }
+    }
+}
+
+impl Renderer for YamlRenderer {
+    fn render(&self, value: &Value) -> Result<String> {
+        let mut writer = Writer { options: &self.options, out: String::new(), path: Path::default() };
+        if !self.options.is_stream {
+            writer.document(value)?;
+            return Ok(writer.out);
+        }
+
+        let documents = match writer.node(value, 0)? {
+            Node::Sequence(documents) => documents,
+            Node::Scalar(_) if matches!(value, Value::List(_) | Value::Set(_) | Value::Object(_)) => &[],
+            _ => {
+                let message = format!("can't render a value of type `{}` as a YAML stream", value.type_name());
+                return Err(RenderError::new(&writer.path, message));
+            }
+        };
+        for (index, document) in documents.iter().enumerate() {
+            if index > 0 {
+                writer.out.push_str("---\n");
+            }
+            writer.path.push_index(index);
+            writer.document(document)?;
+            writer.path.pop();
+        }
+        Ok(writer.out)
+    }
+}
+
+/// A value as it is laid out: scalars (including empty collections) on the line of their key, and collections on
+/// the lines below.
+enum Node<'v> {
+    Scalar(String),
+    Sequence(&'v [Value]),
+    Mapping(Vec<(Key<'v>, &'v Value)>),
+}
+
+struct Writer<'o> {
+    options: &'o YamlOptions,
+    out: String,
+    path: Path,
+}
+
+impl Writer<'_> {
+    fn document(&mut self, value: &Value) -> Result<()> {
+        match self.node(value, 0)? {
+            Node::Scalar(scalar) => {
+                self.out.push_str(&scalar);
+                self.out.push('\n');
+            }
+            Node::Sequence(elements) => self.sequence(elements, 0, false)?,
+            Node::Mapping(members) => self.mapping(members, 0, false)?,
+        }
+        Ok(())
+    }
+
+    /// Classifies a value, where scalars are written as the value of something at `level`.
+    fn node<'v>(&self, value: &'v Value, level: usize) -> Result<Node<'v>> {
+        let node = match value {
+            Value::List(elements) | Value::Set(elements) => Node::Sequence(elements),
+            Value::Map(entries) => Node::Mapping(entries.iter().map(|(key, value)| (Key::Entry(key), value)).collect()),
+            Value::Object(object) => match shape(object, &self.path, FORMAT)? {
+                Shape::List(elements) => Node::Sequence(elements),
+                Shape::Map(members) => Node::Mapping(
+                    members
+                        .into_iter()
+                        .filter(|(key, value)| {
+                            !(self.options.omit_null_properties
+                                && matches!(key, Key::Property(_))
+                                && **value == Value::Null)
+                        })
+                        .collect(),
+                ),
+            },
+            value => return Ok(Node::Scalar(self.scalar(value, level)?)),
+        };
+
+        Ok(match node {
+            Node::Sequence([]) => Node::Scalar("[]".to_string()),
+            Node::Mapping(members) if members.is_empty() => Node::Scalar("{}".to_string()),
+            node => node,
+        })
+    }
+
+    /// Writes the items of a sequence at `level`, where the first one continues the current line if `inline`.
+    fn sequence(&mut self, elements: &[Value], level: usize, inline: bool) -> Result<()> {
+        for (index, element) in elements.iter().enumerate() {
+            if index > 0 || !inline {
+                self.indent(level);
+            }
+            self.out.push_str("- ");
+            self.path.push_index(index);
+            match self.node(element, level + 1)? {
+                Node::Scalar(scalar) => {
+                    self.out.push_str(&scalar);
+                    self.out.push('\n');
+                }
+                Node::Sequence(elements) => self.sequence(elements, level + 1, true)?,
+                Node::Mapping(members) => self.mapping(members, level + 1, true)?,
+            }
+            self.path.pop();
+        }
+        Ok(())
+    }
+
+    /// Writes the members of a mapping at `level`, where the first one continues the current line if `inline`.
+    fn mapping(&mut self, members: Vec<(Key, &Value)>, level: usize, inline: bool) -> Result<()> {
+        for (index, (key, value)) in members.into_iter().enumerate() {
+            if index > 0 || !inline {
+                self.indent(level);
+            }
+            self.path.push(&key);
+            let key = match key {
+                Key::Property(name) => string(name),
+                Key::Entry(key @ (Value::String(_) | Value::Int(_) | Value::Boolean(_))) => self.scalar(key, level)?,
+                Key::Entry(key) => {
+                    let message = format!("can't render a key of type `{}` as {FORMAT}", key.type_name());
+                    return Err(RenderError::new(&self.path, message));
+                }
+            };
+            self.out.push_str(&key);
+            self.out.push(':');
+            match self.node(value, level + 1)? {
+                Node::Scalar(scalar) => {
+                    self.out.push(' ');
+                    self.out.push_str(&scalar);
+                    self.out.push('\n');
+                }
+                Node::Sequence(elements) => {
+                    self.out.push('\n');
+                    self.sequence(elements, level, false)?;
+                }
+                Node::Mapping(members) => {
+                    self.out.push('\n');
+                    self.mapping(members, level + 1, false)?;
+                }
+            }
+            self.path.pop();
+        }
+        Ok(())
+    }
+
+    fn scalar(&self, value: &Value, level: usize) -> Result<String> {
+        Ok(match value {
+            Value::Null => "null".to_string(),
+            Value::Boolean(b) => b.to_string(),
+            Value::Int(n) => n.to_string(),
+            Value::Float(x) if x.is_nan() => ".nan".to_string(),
+            Value::Float(x) if x.is_infinite() => if *x > 0.0 { ".inf" } else { "-.inf" }.to_string(),
+            Value::Float(x) => pkl_stdlib::number::float_to_string(*x),
+            Value::String(s) => match block_scalar(s, level) {
+                Some(block) => block,
+                None => string(s),
+            },
+            value => return Err(unrenderable(&self.path, value, FORMAT)),
+        })
+    }
+
+    fn indent(&mut self, level: usize) {
+        for _ in 0..level {
+            self.out.push_str(INDENT);
+        }
+    }
+}
+
+/// A string on a single line, quoted if it has to be.
+fn string(s: &str) -> String {
+    if s.chars().any(|c| c.is_control()) {
+        double_quoted(s)
+    } else if needs_quotes(s) {
+        format!("'{}'", s.replace('\'', "''"))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Whether a string without control characters would read as something else than itself when left unquoted.
+fn needs_quotes(s: &str) -> bool {
+    const WORDS: [&str; 14] =
+        ["null", "~", "true", "false", "yes", "no", "on", "off", "y", "n", ".inf", "-.inf", "+.inf", ".nan"];
+    const INDICATORS: &str = ",[]{}#&*!|>'\"%@`";
+
+    let Some(first) = s.chars().next() else { return true };
+    let second = s.chars().nth(1);
+    WORDS.iter().any(|word| word.eq_ignore_ascii_case(s))
+        || looks_like_number(s)
+        || INDICATORS.contains(first)
+        || (matches!(first, '-' | '?' | ':') && second.is_none_or(|c| c == ' '))
+        || s.starts_with(' ')
+        || s.ends_with(' ')
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.starts_with("---")
+        || s.starts_with("...")
+}
+
+fn looks_like_number(s: &str) -> bool {
+    let unsigned = s.trim_start_matches(['-', '+']);
+    unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') && unsigned.chars().any(|c| c.is_ascii_digit())
+        || s.parse::<f64>().is_ok()
+}
+
+fn double_quoted(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\0' => quoted.push_str("\\0"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A string of several lines as a literal block scalar (`|`) whose lines are indented for `level`, unless the string
+/// has control characters a block can't hold.
+fn block_scalar(s: &str, level: usize) -> Option<String> {
+    if !s.contains('\n') || s.trim_end_matches('\n').is_empty() || s.chars().any(|c| c.is_control() && c != '\n') {
+        return None;
+    }
+
+    let trailing = s.len() - s.trim_end_matches('\n').len();
+    let (chomping, body) = match trailing {
+        0 => ("-", s),
+        1 => ("", &s[..s.len() - 1]),
+        _ => ("+", &s[..s.len() - 1]),
+    };
+    // a first line starting with a space would otherwise set the indentation of the block
+    let indentation = if s.starts_with(' ') { INDENT.len().to_string() } else { String::new() };
+
+    let mut block = format!("|{indentation}{chomping}");
+    for line in body.split('\n') {
+        block.push('\n');
+        if !line.is_empty() {
+            block.push_str(&INDENT.repeat(level.max(1)));
+            block.push_str(line);
+        }
+    }
+    Some(block)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(source: &str, options: YamlOptions) -> Result<String> {
+        let module = pkl_eval::evaluate(source).unwrap_or_else(|error| panic!("{source}: {error}"));
+        YamlRenderer::new(options).render(&module)
+    }
+
+    #[test]
+    fn block_style() {
+        let source = r#"
+            name = "pigeon"
+            diet { "seeds"; new Listing { 1; 2 }; new Dynamic { kind = "worm"; count = 2 } }
+            home { city = "Paris"; tags {} }
+            sizes = Map(1, 1.5, true, -0.0 / 0.0)
+            missing = null
+        "#;
+        let expected = "\
+name: pigeon
+diet:
+- seeds
+- - 1
+  - 2
+- kind: worm
+  count: 2
+home:
+  city: Paris
+  tags: {}
+sizes:
+  1: 1.5
+  true: .nan
+missing: null
+";
+        assert_eq!(render(source, YamlOptions::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn strings() {
+        let source = r#"
+            a = "true"
+            b = "1_000"
+            c = "it's: here"
+            d = "- x"
+            e = "tab\tbed"
+            f = "-x"
+            g = "line\nbreak\n"
+            h = new Listing { " indented\nblock" }
+            i = ""
+        "#;
+        let expected = "\
+a: 'true'
+b: '1_000'
+c: 'it''s: here'
+d: '- x'
+e: \"tab\\tbed\"
+f: -x
+g: |
+  line
+  break
+h:
+- |2-
+   indented
+  block
+i: ''
+";
+        assert_eq!(render(source, YamlOptions::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn streams() {
+        let options = YamlOptions { is_stream: true, omit_null_properties: true };
+        let module = pkl_eval::evaluate_expr("List(new Dynamic { a = 1; b = null }, List(2), \"three\")").unwrap();
+        let yaml = YamlRenderer::new(options.clone()).render(&module).unwrap();
+        assert_eq!(yaml, "a: 1\n---\n- 2\n---\nthree\n");
+
+        let error = render("a = 1", options).unwrap_err().to_string();
+        assert_eq!(error, "can't render a value of type `ModuleClass` as a YAML stream");
+        let error = render("a = 1.min", YamlOptions::default()).unwrap_err().to_string();
+        assert_eq!(error, "can't render a value of type `Duration` as YAML (at `a`)");
+    }
+}
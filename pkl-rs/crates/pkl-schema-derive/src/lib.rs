@@ -0,0 +1,124 @@
+//! `#[derive(Pkl)]`, which implements `pkl_schema::Pkl` the way the crate's docs describe: a struct with named fields
+//! as a class, and an enum of unit variants as a union of string literals, named like serde names them.
+
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as Tokens;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Fields, Lit, Meta, Token};
+
+#[proc_macro_derive(Pkl, attributes(serde))]
+pub fn derive_pkl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input).unwrap_or_else(|error| error.to_compile_error()).into()
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<Tokens> {
+    if !input.generics.params.is_empty() {
+        let message = "`Pkl` can't be derived for generic types, whose classes would all have the same name";
+        return Err(Error::new_spanned(&input.generics, message));
+    }
+    let ident = &input.ident;
+    let (name, _) = serde_attributes(&input.attrs)?;
+    let name = name.unwrap_or_else(|| ident.to_string());
+    let doc = doc_comment(&input.attrs);
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(Error::new_spanned(ident, "`Pkl` can only be derived for structs with named fields"));
+            };
+            let doc = doc.map(|doc| quote!(class.doc(#doc);));
+            let mut properties = Vec::new();
+            for field in &fields.named {
+                let (name, skip) = serde_attributes(&field.attrs)?;
+                if skip {
+                    continue;
+                }
+                let name = name.unwrap_or_else(|| field.ident.as_ref().expect("a named field").to_string());
+                let ty = &field.ty;
+                let doc = doc_comment(&field.attrs).map(|doc| quote!(.doc(#doc)));
+                properties.push(quote!(class.property::<#ty>(#name)? #doc;));
+            }
+            quote! {
+                schema.class(#name, |class| {
+                    #doc
+                    #(#properties)*
+                    ::core::result::Result::Ok(())
+                })
+            }
+        }
+        Data::Enum(data) => {
+            let mut values = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    let message = "`Pkl` can only be derived for enums whose variants are all units";
+                    return Err(Error::new_spanned(variant, message));
+                }
+                let (value, skip) = serde_attributes(&variant.attrs)?;
+                if !skip {
+                    values.push(value.unwrap_or_else(|| variant.ident.to_string()));
+                }
+            }
+            let doc = match doc {
+                Some(doc) => quote!(::core::option::Option::Some(#doc)),
+                None => quote!(::core::option::Option::None),
+            };
+            quote!(schema.string_union(#name, #doc, &[#(#values),*]))
+        }
+        Data::Union(_) => return Err(Error::new_spanned(ident, "`Pkl` can't be derived for unions")),
+    };
+
+    Ok(quote! {
+        impl ::pkl_schema::Pkl for #ident {
+            fn pkl_type(schema: &mut ::pkl_schema::Schema) -> ::pkl_schema::Result<::std::string::String> {
+                #body
+            }
+        }
+    })
+}
+
+/// The doc comment of an item, with the space after each `///` removed.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => string(&meta.value),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// The name in `#[serde(rename = "...")]`, and whether there's a `#[serde(skip)]`. Other serde attributes don't
+/// change what's deserialized from, or aren't followed.
+fn serde_attributes(attrs: &[Attribute]) -> syn::Result<(Option<String>, bool)> {
+    let (mut rename, mut skip) = (None, false);
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        for meta in attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+            match &meta {
+                Meta::NameValue(meta) if meta.path.is_ident("rename") => {
+                    let name = string(&meta.value);
+                    rename = Some(name.ok_or_else(|| Error::new_spanned(&meta.value, "expected a string"))?);
+                }
+                Meta::Path(path) if path.is_ident("skip") || path.is_ident("skip_deserializing") => skip = true,
+                _ => {}
+            }
+        }
+    }
+    Ok((rename, skip))
+}
+
+fn string(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(literal) => match &literal.lit {
+            Lit::Str(string) => Some(string.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
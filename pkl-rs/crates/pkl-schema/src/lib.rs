@@ -0,0 +1,462 @@
+//! Pkl schemas generated from Rust types, the other way around from `pkl-gen-rust`: a service that defines its
+//! configuration as Rust structs can hand its users a template to amend, typed like the structs are.
+//!
+//! A type names its Pkl counterpart by implementing [`Pkl`], which is meant to be derived. A struct with named fields
+//! derives it as a class with a property for each of its fields, and an enum of unit variants as a typealias of a
+//! union of string literals. Doc comments are kept, and `#[serde(rename = "...")]` and `#[serde(skip)]` are followed,
+//! so that the schema describes what the struct deserializes from. The primitive types, `String`, `Option`, `Box`,
+//! `Vec`, sets, and maps implement it already, as the Pkl types that `pkl-gen-rust` turns into them. Each class and
+//! typealias is declared once, however many types use it:
+//!
+//! ```
+//! use pkl_schema::Pkl;
+//!
+//! #[derive(Pkl)]
+//! struct Service {
+//!     /// The port to listen on
+//!     port: u16,
+//!     birds: Vec<Bird>,
+//! }
+//!
+//! /// A bird.
+//! #[derive(Pkl)]
+//! struct Bird {
+//!     name: String,
+//!     #[serde(rename = "wingspanCm")]
+//!     wingspan: Option<f64>,
+//! }
+//!
+//! let template = pkl_schema::template::<Service>().unwrap();
+//! let properties = "/// The port to listen on\nport: UInt16\n\nbirds: Listing<Bird>\n";
+//! assert!(template.starts_with(&format!("module Service\n\n{properties}")));
+//! assert!(template.ends_with("/// A bird.\nclass Bird {\n  name: String\n  wingspanCm: Float?\n}\n"));
+//! ```
+//!
+//! Types the derive doesn't cover, like ones whose Pkl type depends on type parameters, implement the trait by hand
+//! with [`Schema::class`] and [`Schema::string_union`], which is what the derive expands to.
+//!
+//! What's rendered is parsed back and printed by [`pkl_codegen`], so it's laid out like any other generated Pkl.
+
+#![forbid(unsafe_code)]
+
+// so that what the derive expands to names this crate the same way inside it
+extern crate self as pkl_schema;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use oxc_allocator::Allocator;
+use pkl_codegen::{Codegen, CodegenOptions};
+use pkl_lexer::identifier::is_identifier;
+
+pub use pkl_schema_derive::Pkl;
+
+/// A Rust type with a Pkl counterpart.
+///
+/// ```
+/// use pkl_schema::{Pkl, Result, Schema};
+///
+/// struct Bird {
+///     name: String,
+/// }
+///
+/// impl Pkl for Bird {
+///     fn pkl_type(schema: &mut Schema) -> Result<String> {
+///         schema.class("Bird", |class| {
+///             class.doc("A bird.");
+///             class.property::<String>("name")?.doc("What it's called");
+///             Ok(())
+///         })
+///     }
+/// }
+///
+/// let classes = pkl_schema::classes::<Bird>().unwrap();
+/// assert_eq!(classes, "/// A bird.\nclass Bird {\n  /// What it's called\n  name: String\n}\n");
+/// ```
+pub trait Pkl {
+    /// The Pkl type, like `String` or `Listing<Bird>`, declaring the classes and typealiases it names in `schema`.
+    fn pkl_type(schema: &mut Schema) -> Result<String>;
+}
+
+/// A type can't be declared in Pkl, like a class whose name is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    pub message: String,
+}
+
+impl SchemaError {
+    pub fn new(message: impl Into<String>) -> Self {
+        SchemaError { message: message.into() }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+pub type Result<T> = std::result::Result<T, SchemaError>;
+
+macro_rules! named {
+    ($($rust:ty => $pkl:literal,)*) => {
+        $(
+            impl Pkl for $rust {
+                fn pkl_type(_: &mut Schema) -> Result<String> {
+                    Ok($pkl.to_string())
+                }
+            }
+        )*
+    };
+}
+
+named! {
+    String => "String",
+    bool => "Boolean",
+    i8 => "Int8",
+    i16 => "Int16",
+    i32 => "Int32",
+    i64 => "Int",
+    isize => "Int",
+    u8 => "UInt8",
+    u16 => "UInt16",
+    u32 => "UInt32",
+    u64 => "UInt",
+    usize => "UInt",
+    f32 => "Float",
+    f64 => "Float",
+}
+
+impl<T: Pkl> Pkl for Option<T> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        let ty = T::pkl_type(schema)?;
+        if ty.ends_with('?') {
+            Ok(ty)
+        } else {
+            Ok(format!("{ty}?"))
+        }
+    }
+}
+
+impl<T: Pkl> Pkl for Box<T> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        T::pkl_type(schema)
+    }
+}
+
+impl<T: Pkl> Pkl for Vec<T> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        Ok(format!("Listing<{}>", T::pkl_type(schema)?))
+    }
+}
+
+impl<T: Pkl, S> Pkl for HashSet<T, S> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        Ok(format!("Set<{}>", T::pkl_type(schema)?))
+    }
+}
+
+impl<T: Pkl> Pkl for BTreeSet<T> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        Ok(format!("Set<{}>", T::pkl_type(schema)?))
+    }
+}
+
+impl<K: Pkl, V: Pkl, S> Pkl for HashMap<K, V, S> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        Ok(format!("Mapping<{}, {}>", K::pkl_type(schema)?, V::pkl_type(schema)?))
+    }
+}
+
+impl<K: Pkl, V: Pkl> Pkl for BTreeMap<K, V> {
+    fn pkl_type(schema: &mut Schema) -> Result<String> {
+        Ok(format!("Mapping<{}, {}>", K::pkl_type(schema)?, V::pkl_type(schema)?))
+    }
+}
+
+/// The classes and typealiases that types are declared as, each under a name of its own.
+#[derive(Debug, Default)]
+pub struct Schema {
+    /// In the order they were first named in, so that a class comes before the ones its properties use
+    declarations: Vec<Declaration>,
+}
+
+#[derive(Debug)]
+enum Declaration {
+    Class { name: String, doc: Option<String>, properties: Vec<Property> },
+    TypeAlias { name: String, doc: Option<String>, ty: String },
+}
+
+impl Declaration {
+    fn name(&self) -> &str {
+        match self {
+            Declaration::Class { name, .. } | Declaration::TypeAlias { name, .. } => name,
+        }
+    }
+}
+
+impl Schema {
+    /// Declares a class called `name`, unless there already is one, returning its type. Its doc comment and
+    /// properties are declared by `declare`.
+    ///
+    /// It's an error if `name` or the name of a property can't be written in Pkl, even in backticks.
+    pub fn class(&mut self, name: &str, declare: impl FnOnce(&mut Class) -> Result<()>) -> Result<String> {
+        let ty = pkl_name(name)?;
+        if self.declarations.iter().any(|declaration| declaration.name() == name) {
+            return Ok(ty);
+        }
+        // declared before its properties are, so that a class whose properties use it is only declared once
+        let index = self.declarations.len();
+        let (doc, properties) = (None, Vec::new());
+        self.declarations.push(Declaration::Class { name: name.to_string(), doc, properties });
+        let mut class = Class { schema: self, doc: None, properties: Vec::new() };
+        declare(&mut class)?;
+        let (doc, properties) = (class.doc, class.properties);
+        self.declarations[index] = Declaration::Class { name: name.to_string(), doc, properties };
+        Ok(ty)
+    }
+
+    /// Declares a typealias called `name` of the union of the string literals `values`, like an enum of unit
+    /// variants is, unless there already is one, returning its type.
+    ///
+    /// It's an error if `name` can't be written in Pkl, even in backticks, or if there are no `values`.
+    pub fn string_union(&mut self, name: &str, doc: Option<&str>, values: &[&str]) -> Result<String> {
+        let ty = pkl_name(name)?;
+        if values.is_empty() {
+            return Err(SchemaError::new(format!("`{name}` has no values, so it can't be a union")));
+        }
+        if !self.declarations.iter().any(|declaration| declaration.name() == name) {
+            let union: Vec<String> = values.iter().map(|value| string_literal(value)).collect();
+            let (doc, ty) = (doc.map(String::from), union.join(" | "));
+            self.declarations.push(Declaration::TypeAlias { name: name.to_string(), doc, ty });
+        }
+        Ok(ty)
+    }
+}
+
+/// A class being declared by [`Schema::class`].
+#[derive(Debug)]
+pub struct Class<'s> {
+    schema: &'s mut Schema,
+    doc: Option<String>,
+    properties: Vec<Property>,
+}
+
+impl Class<'_> {
+    /// Sets the doc comment of the class, which can have several lines.
+    pub fn doc(&mut self, doc: &str) -> &mut Self {
+        self.doc = Some(doc.to_string());
+        self
+    }
+
+    /// Adds a property called `name` of the Pkl type of `T`, returning it to document.
+    pub fn property<T: Pkl>(&mut self, name: &str) -> Result<&mut Property> {
+        let name = pkl_name(name)?;
+        let ty = T::pkl_type(self.schema)?;
+        self.properties.push(Property { name, ty, doc: None });
+        Ok(self.properties.last_mut().expect("just added"))
+    }
+}
+
+/// A property of a class.
+#[derive(Debug)]
+pub struct Property {
+    name: String,
+    ty: String,
+    doc: Option<String>,
+}
+
+impl Property {
+    /// Sets the doc comment of the property, which can have several lines.
+    pub fn doc(&mut self, doc: &str) -> &mut Self {
+        self.doc = Some(doc.to_string());
+        self
+    }
+}
+
+/// The source of a module declaring the Pkl type of `T` and every class and typealias it uses.
+pub fn classes<T: Pkl>() -> Result<String> {
+    let mut schema = Schema::default();
+    T::pkl_type(&mut schema)?;
+    let mut source = String::new();
+    for declaration in &schema.declarations {
+        write_declaration(&mut source, declaration);
+    }
+    format(&source)
+}
+
+/// The source of a template for the class `T` is declared as: a module with its doc comment and properties, and the
+/// classes and typealiases they use after them, for users to amend.
+///
+/// It's an error if `T` isn't declared as a class.
+pub fn template<T: Pkl>() -> Result<String> {
+    let mut schema = Schema::default();
+    let ty = T::pkl_type(&mut schema)?;
+    let Some(Declaration::Class { name, doc, properties }) = schema.declarations.first() else {
+        return Err(SchemaError::new(format!("`{ty}` isn't a class, so it can't be a template")));
+    };
+    let mut source = String::new();
+    write_doc(&mut source, doc.as_deref());
+    source.push_str(&format!("module {ty}\n", ty = pkl_name(name)?));
+    for property in properties {
+        write_property(&mut source, property);
+    }
+    // the class of the module is only declared too if its properties use it
+    let used = |declaration: &Declaration| match declaration {
+        Declaration::Class { properties, .. } => properties.iter().any(|property| names(&property.ty, &ty)),
+        Declaration::TypeAlias { .. } => false,
+    };
+    let skip = usize::from(!schema.declarations.iter().any(used));
+    for declaration in &schema.declarations[skip..] {
+        write_declaration(&mut source, declaration);
+    }
+    format(&source)
+}
+
+/// Writes a declaration, whose names were checked when it was declared.
+fn write_declaration(source: &mut String, declaration: &Declaration) {
+    match declaration {
+        Declaration::Class { name, doc, properties } => {
+            write_doc(source, doc.as_deref());
+            source.push_str(&format!("class {} {{\n", quote_name(name)));
+            for property in properties {
+                write_property(source, property);
+            }
+            source.push_str("}\n");
+        }
+        Declaration::TypeAlias { name, doc, ty } => {
+            write_doc(source, doc.as_deref());
+            source.push_str(&format!("typealias {} = {ty}\n", quote_name(name)));
+        }
+    }
+}
+
+fn write_property(source: &mut String, property: &Property) {
+    write_doc(source, property.doc.as_deref());
+    source.push_str(&format!("{}: {}\n", property.name, property.ty));
+}
+
+fn write_doc(source: &mut String, doc: Option<&str>) {
+    for line in doc.into_iter().flat_map(|doc| doc.split(['\n', '\r'])) {
+        source.push_str(&format!("/// {line}\n"));
+    }
+}
+
+/// Lays out the source of a module the way [`pkl_codegen`] does.
+fn format(source: &str) -> Result<String> {
+    let alloc = Allocator::default();
+    let result = pkl_parser::parse_module(&alloc, source);
+    if let Some(diagnostic) = result.diagnostics.first() {
+        return Err(SchemaError::new(format!("the generated schema doesn't parse: {}", diagnostic.message)));
+    }
+    Ok(Codegen::new(CodegenOptions::default()).build(&result.node))
+}
+
+/// A name as it's written in Pkl, in backticks if it isn't an identifier, or an error if it can't be written.
+fn pkl_name(name: &str) -> Result<String> {
+    if name.is_empty() || name.contains(['`', '\n', '\r']) {
+        return Err(SchemaError::new(format!("{name:?} can't be written as a name in Pkl")));
+    }
+    Ok(quote_name(name))
+}
+
+/// A name that [`pkl_name`] accepted, as it's written in Pkl.
+fn quote_name(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        format!("`{name}`")
+    }
+}
+
+/// Whether the type `ty` names the type `name`.
+fn names(ty: &str, name: &str) -> bool {
+    ty.split([' ', ',', '<', '>', '?']).any(|part| part == name)
+}
+
+fn string_literal(value: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '"' => literal.push_str("\\\""),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{Pkl, Result, Schema};
+    use pkl_gen_rust::GenOptions;
+
+    /// A tree,
+    /// with branches.
+    #[allow(dead_code)]
+    #[derive(Pkl)]
+    struct Tree {
+        kind: Kind,
+        children: Vec<Tree>,
+        /// What's written on it
+        labels: HashMap<String, Option<i32>>,
+        #[serde(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Pkl)]
+    enum Kind {
+        #[serde(rename = "oak")]
+        Oak,
+        #[serde(rename = "silver \"birch\"")]
+        Birch,
+    }
+
+    #[test]
+    fn schemas() {
+        let classes = super::classes::<Tree>().unwrap();
+        let expected = "/// A tree,\n/// with branches.\nclass Tree {\n  kind: Kind\n  children: Listing<Tree>\n\n  \
+                        /// What's written on it\n  labels: Mapping<String, Int32?>\n}\n\n\
+                        typealias Kind = \"oak\" | \"silver \\\"birch\\\"\"\n";
+        assert_eq!(classes, expected);
+
+        // a template of a class its properties use declares it too
+        let template = super::template::<Tree>().unwrap();
+        assert!(template.starts_with("/// A tree,\n/// with branches.\nmodule Tree\n\nkind: Kind\n"));
+        assert!(template.contains("\nclass Tree {\n"));
+
+        // and the schema generates the types it came from
+        let rust = pkl_gen_rust::generate(&classes, &GenOptions::default()).unwrap();
+        assert!(rust.contains("    pub labels: std::collections::HashMap<String, Option<i32>>,\n"));
+        assert!(rust.contains("pub enum Kind {\n"));
+    }
+
+    struct Nameless;
+
+    impl Pkl for Nameless {
+        fn pkl_type(schema: &mut Schema) -> Result<String> {
+            schema.class("Nameless", |class| {
+                class.property::<String>("")?;
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(super::classes::<Nameless>().unwrap_err().message, "\"\" can't be written as a name in Pkl");
+        let error = super::template::<Kind>().unwrap_err();
+        assert_eq!(error.message, "`Kind` isn't a class, so it can't be a template");
+        let error = Schema::default().string_union("Never", None, &[]).unwrap_err();
+        assert_eq!(error.message, "`Never` has no values, so it can't be a union");
+    }
+}
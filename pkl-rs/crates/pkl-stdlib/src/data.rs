@@ -0,0 +1,147 @@
+//! Reading JSON, YAML, and TOML documents, for the `Parser` classes of `pkl:json`, `pkl:yaml`, and `pkl:toml`.
+//!
+//! Documents are read into [`Data`], which the evaluator turns into Pkl values. Errors say where in the document
+//! parsing failed, by line and column.
+
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::json::Json;
+use crate::{Error, Result};
+
+/// The values of a data format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Data {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Data>),
+    /// The entries of a map or object, in document order
+    Map(Vec<(Data, Data)>),
+}
+
+/// Parses a JSON document.
+///
+/// Numbers without a fraction or exponent are ints, unless they don't fit in one.
+pub fn json(text: &str) -> Result<Data> {
+    Json::parse(text).map(json_data)
+}
+
+/// Parses the documents of a YAML stream.
+pub fn yaml(text: &str) -> Result<Vec<Data>> {
+    let documents = YamlLoader::load_from_str(text).map_err(|error| {
+        let marker = error.marker();
+        located("YAML", marker.line(), marker.col() + 1, error.info())
+    })?;
+    Ok(documents.into_iter().map(yaml_data).collect())
+}
+
+/// Parses a TOML document. Dates and times are read as the strings they are written as.
+pub fn toml(text: &str) -> Result<Data> {
+    let table = text.parse::<toml::Table>().map_err(|error| {
+        let offset = error.span().map_or(0, |span| span.start);
+        let (line, column) = line_column(text, offset);
+        located("TOML", line, column, error.message())
+    })?;
+    Ok(toml_data(toml::Value::Table(table)))
+}
+
+fn json_data(json: Json) -> Data {
+    match json {
+        Json::Null => Data::Null,
+        Json::Bool(b) => Data::Boolean(b),
+        Json::Int(n) => Data::Int(n),
+        Json::Float(x) => Data::Float(x),
+        Json::String(s) => Data::String(s),
+        Json::Array(elements) => Data::List(elements.into_iter().map(json_data).collect()),
+        Json::Object(members) => {
+            Data::Map(members.into_iter().map(|(key, value)| (Data::String(key), json_data(value))).collect())
+        }
+    }
+}
+
+fn yaml_data(yaml: Yaml) -> Data {
+    match yaml {
+        Yaml::Real(_) => Data::Float(yaml.as_f64().unwrap_or(f64::NAN)),
+        Yaml::Integer(n) => Data::Int(n),
+        Yaml::String(s) => Data::String(s),
+        Yaml::Boolean(b) => Data::Boolean(b),
+        Yaml::Array(elements) => Data::List(elements.into_iter().map(yaml_data).collect()),
+        Yaml::Hash(entries) => Data::Map(entries.into_iter().map(|(k, v)| (yaml_data(k), yaml_data(v))).collect()),
+        Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => Data::Null,
+    }
+}
+
+fn toml_data(value: toml::Value) -> Data {
+    match value {
+        toml::Value::String(s) => Data::String(s),
+        toml::Value::Integer(n) => Data::Int(n),
+        toml::Value::Float(x) => Data::Float(x),
+        toml::Value::Boolean(b) => Data::Boolean(b),
+        toml::Value::Datetime(datetime) => Data::String(datetime.to_string()),
+        toml::Value::Array(elements) => Data::List(elements.into_iter().map(toml_data).collect()),
+        toml::Value::Table(table) => {
+            Data::Map(table.into_iter().map(|(key, value)| (Data::String(key), toml_data(value))).collect())
+        }
+    }
+}
+
+pub(crate) fn located(format: &str, line: usize, column: usize, message: &str) -> Error {
+    Error::new(format!("can't parse {format} at line {line}, column {column}: {message}"))
+}
+
+/// The line and column, both starting at 1, of a byte offset; columns count characters.
+pub(crate) fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn string(s: &str) -> Data {
+        Data::String(s.to_string())
+    }
+
+    #[test]
+    fn json_documents() {
+        let data = json(r#" {"a": [1, -2.5e1, true, null], "b": "é🐦\n", "c": {}, "a": 9223372036854775808} "#);
+        let expected = Data::Map(vec![
+            (string("a"), Data::Float(9223372036854775808.0)),
+            (string("b"), string("\u{e9}\u{1F426}\n")),
+            (string("c"), Data::Map(Vec::new())),
+        ]);
+        assert_eq!(data.unwrap(), expected);
+        assert_eq!(json("[1, 2]").unwrap(), Data::List(vec![Data::Int(1), Data::Int(2)]));
+
+        let message = "can't parse JSON at line 2, column 8: invalid number";
+        assert_eq!(json("{\n  \"a\": 01\n}").unwrap_err().message, message);
+        let message = "can't parse JSON at line 1, column 6: expected `,` or `]`";
+        assert_eq!(json("[1, 2").unwrap_err().message, message);
+        let message = "can't parse JSON at line 1, column 4: expected the end of the document";
+        assert_eq!(json("{} x").unwrap_err().message, message);
+    }
+
+    #[test]
+    fn yaml_and_toml_documents() {
+        let documents = yaml("a: [1, .inf]\nb: &x {c: ~}\nd: *x\n---\n- yes\n").unwrap();
+        let map = Data::Map(vec![(string("c"), Data::Null)]);
+        let first = Data::Map(vec![
+            (string("a"), Data::List(vec![Data::Int(1), Data::Float(f64::INFINITY)])),
+            (string("b"), map.clone()),
+            (string("d"), map),
+        ]);
+        assert_eq!(documents, vec![first, Data::List(vec![string("yes")])]);
+        let message = "can't parse YAML at line 2, column 1: illegal placement of ':' indicator";
+        assert_eq!(yaml("a: [1\n: 2").unwrap_err().message, message);
+
+        let data = toml("b = 1\n[a]\nc = 1979-05-27\nd = [1.5]\n").unwrap();
+        let a = Data::Map(vec![(string("c"), string("1979-05-27")), (string("d"), Data::List(vec![Data::Float(1.5)]))]);
+        assert_eq!(data, Data::Map(vec![(string("b"), Data::Int(1)), (string("a"), a)]));
+        let message = "can't parse TOML at line 2, column 5: string values must be quoted, expected literal string";
+        assert_eq!(toml("a = 1\nb = \n").unwrap_err().message, message);
+    }
+}
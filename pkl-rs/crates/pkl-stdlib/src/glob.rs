@@ -0,0 +1,120 @@
+//! The glob patterns of `import*` and `read*`, like `configs/**.pkl`.
+//!
+//! `*` matches any characters but `/`, `**` any characters at all, `**/` any directories or none, and `?` one
+//! character but `/`. `[abc]`, `[a-z]`, and `[!abc]` match one character of a class, and `{json,yaml}` any of the
+//! alternatives, which can't nest. `\` escapes the character after it.
+
+use crate::{Error, Result};
+
+/// A compiled glob pattern.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    regex: regex::Regex,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Glob> {
+        let regex = regex::Regex::new(&format!(r"\A{}\z", translate(pattern)?))
+            .map_err(|error| Error::new(format!("invalid glob pattern `{pattern}`: {error}")))?;
+        Ok(Glob { regex })
+    }
+
+    /// Whether the whole of `path` matches the pattern.
+    pub fn is_match(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+}
+
+/// Whether a pattern has any characters with a special meaning, rather than being a path.
+pub fn is_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{', '\\'])
+}
+
+/// Translates a glob pattern to the syntax of the `regex` crate.
+fn translate(pattern: &str) -> Result<String> {
+    let invalid = |reason: &str| Error::new(format!("invalid glob pattern `{pattern}`: {reason}"));
+    let mut regex = String::new();
+    let mut in_alternatives = false;
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `a/**/b` matches `a/b` too
+                regex.push_str(if chars.next_if_eq(&'/').is_some() { "(?:.*/)?" } else { ".*" });
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '\\' => match chars.next() {
+                Some(escaped) => regex.push_str(&regex::escape(&escaped.to_string())),
+                None => return Err(invalid("it ends with `\\`")),
+            },
+            '[' => {
+                regex.push('[');
+                if chars.next_if_eq(&'!').is_some() {
+                    regex.push('^');
+                }
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    match c {
+                        ']' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' | '[' | '^' | '&' | '~' => regex.push_str(&format!("\\{c}")),
+                        c => regex.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(invalid("a `[` isn't closed"));
+                }
+                regex.push(']');
+            }
+            '{' if in_alternatives => return Err(invalid("alternatives in `{}` can't nest")),
+            '{' => {
+                in_alternatives = true;
+                regex.push_str("(?:");
+            }
+            ',' if in_alternatives => regex.push('|'),
+            '}' if in_alternatives => {
+                in_alternatives = false;
+                regex.push(')');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    if in_alternatives {
+        return Err(invalid("a `{` isn't closed"));
+    }
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        let glob = Glob::new("configs/*.{pkl,pcf}").unwrap();
+        assert!(glob.is_match("configs/birds.pkl"));
+        assert!(glob.is_match("configs/.pcf"));
+        assert!(!glob.is_match("configs/birds/pigeon.pkl"));
+        assert!(!glob.is_match("configs/birds.json"));
+
+        assert!(Glob::new("**.pkl").unwrap().is_match("a/b/c.pkl"));
+        assert!(Glob::new("a/**/b.pkl").unwrap().is_match("a/b.pkl"));
+        assert!(Glob::new("[!a-c]?.txt").unwrap().is_match("dx.txt"));
+        assert!(!Glob::new("[!a-c]?.txt").unwrap().is_match("bx.txt"));
+        assert!(Glob::new("a\\*.txt").unwrap().is_match("a*.txt"));
+        assert!(!Glob::new("a\\*.txt").unwrap().is_match("ab.txt"));
+        assert!(Glob::new("env:*").unwrap().is_match("env:HOME"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert_eq!(Glob::new("[ab").unwrap_err().message, "invalid glob pattern `[ab`: a `[` isn't closed");
+        let error = Glob::new("{a,{b}}").unwrap_err();
+        assert_eq!(error.message, "invalid glob pattern `{a,{b}}`: alternatives in `{}` can't nest");
+        assert!(!is_pattern("configs/birds.pkl"));
+    }
+}
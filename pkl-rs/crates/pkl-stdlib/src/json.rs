@@ -0,0 +1,429 @@
+//! JSON documents: reading them strictly, with errors that say where in the document reading failed, and writing
+//! them compactly. The messages of the language server protocol and the `--dump json` and `--format json` output of
+//! `pkl-lang` are JSON, and `pkl:json`'s `Parser` reads it into Pkl values through [`data::json`](crate::data::json).
+
+use std::fmt;
+
+use crate::data::{line_column, located};
+use crate::{Error, Result};
+
+/// A JSON value. Objects keep their members in order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// A number without a fraction or exponent that fits in an `i64`, which is kept exactly
+    Int(i64),
+    /// Any other number
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON document. Of duplicate keys of an object, like most readers, the last is kept.
+    pub fn parse(text: &str) -> Result<Json> {
+        let mut parser = JsonParser { text, pos: 0 };
+        parser.whitespace();
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error("expected the end of the document"));
+        }
+        Ok(value)
+    }
+
+    pub fn object<'k>(members: impl IntoIterator<Item = (&'k str, Json)>) -> Json {
+        Json::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    /// The value of a member of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value of a number that is a non-negative integer, like a line or a version.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Int(n) => u64::try_from(*n).ok(),
+            Json::Float(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        i64::try_from(n).map_or(Json::Float(n as f64), Json::Int)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Self {
+        Json::Float(n)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(elements: Vec<Json>) -> Self {
+        Json::Array(elements)
+    }
+}
+
+/// Writes the value without any whitespace. Numbers that aren't finite, which JSON can't write, are written as
+/// `null`.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Int(n) => write!(f, "{n}"),
+            Json::Float(n) if n.is_finite() => write!(f, "{n}"),
+            Json::Float(_) => f.write_str("null"),
+            Json::String(s) => write_string(s, f),
+            Json::Array(elements) => {
+                f.write_str("[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(members) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(key, f)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_string(s: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+struct JsonParser<'t> {
+    text: &'t str,
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self) -> Result<Json> {
+        let value = match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if !self.close('}') {
+                    loop {
+                        self.whitespace();
+                        if self.peek() != Some('"') {
+                            return Err(self.error("expected a string key"));
+                        }
+                        let key = self.string()?;
+                        self.whitespace();
+                        self.expect(':')?;
+                        self.whitespace();
+                        let value = self.value()?;
+                        match members.iter_mut().find(|(existing, _)| *existing == key) {
+                            // like most readers, keep the last of duplicate keys
+                            Some((_, existing)) => *existing = value,
+                            None => members.push((key, value)),
+                        }
+                        if !self.separator('}')? {
+                            break;
+                        }
+                    }
+                }
+                Json::Object(members)
+            }
+            Some('[') => {
+                self.pos += 1;
+                let mut elements = Vec::new();
+                if !self.close(']') {
+                    loop {
+                        self.whitespace();
+                        elements.push(self.value()?);
+                        if !self.separator(']')? {
+                            break;
+                        }
+                    }
+                }
+                Json::Array(elements)
+            }
+            Some('"') => Json::String(self.string()?),
+            Some('-' | '0'..='9') => self.number()?,
+            _ if self.keyword("true") => Json::Bool(true),
+            _ if self.keyword("false") => Json::Bool(false),
+            _ if self.keyword("null") => Json::Null,
+            _ => return Err(self.error("expected a value")),
+        };
+        Ok(value)
+    }
+
+    /// Skips the whitespace before a closing bracket and the bracket, if there is one; for empty objects and arrays.
+    fn close(&mut self, bracket: char) -> bool {
+        self.whitespace();
+        if self.peek() == Some(bracket) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    /// Reads the `,` before another member, returning `true`, or the closing bracket, returning `false`.
+    fn separator(&mut self, bracket: char) -> Result<bool> {
+        self.whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(c) if c == bracket => {
+                self.pos += 1;
+                Ok(false)
+            }
+            _ => Err(self.error(&format!("expected `,` or `{bracket}`"))),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.peek() else { return Err(self.error("unterminated string")) };
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.pos += 1;
+                            s.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    self.pos += 1;
+                    s.push(escaped);
+                }
+                c if c.is_control() => return Err(self.error("control characters in strings must be escaped")),
+                c => {
+                    self.pos += c.len_utf8();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    /// Reads the digits of a `\u` escape, and of the low surrogate following a high one.
+    fn unicode_escape(&mut self) -> Result<char> {
+        let high = self.hex()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"));
+        }
+        if !self.text[self.pos..].starts_with("\\u") {
+            return Err(self.error("expected the low surrogate of a surrogate pair"));
+        }
+        self.pos += 2;
+        let low = self.hex()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("expected the low surrogate of a surrogate pair"));
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).ok_or_else(|| self.error("invalid escape"))
+    }
+
+    fn hex(&mut self) -> Result<u32> {
+        let digits = self.text[self.pos..].get(..4).filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()));
+        let Some(digits) = digits else { return Err(self.error("expected four hexadecimal digits")) };
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("the digits are hexadecimal"))
+    }
+
+    fn number(&mut self) -> Result<Json> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        let digits = |pos: &mut usize| {
+            let from = *pos;
+            while bytes.get(*pos).is_some_and(u8::is_ascii_digit) {
+                *pos += 1;
+            }
+            *pos > from
+        };
+
+        let mut pos = self.pos;
+        if bytes[pos] == b'-' {
+            pos += 1;
+        }
+        let leading_zero = bytes.get(pos) == Some(&b'0') && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit);
+        if !digits(&mut pos) || leading_zero {
+            return Err(self.error("invalid number"));
+        }
+        let mut is_int = true;
+        if bytes.get(pos) == Some(&b'.') {
+            pos += 1;
+            is_int = false;
+            if !digits(&mut pos) {
+                self.pos = pos;
+                return Err(self.error("expected digits after the decimal point"));
+            }
+        }
+        if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+            pos += 1;
+            is_int = false;
+            if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+                pos += 1;
+            }
+            if !digits(&mut pos) {
+                self.pos = pos;
+                return Err(self.error("expected the digits of an exponent"));
+            }
+        }
+
+        self.pos = pos;
+        let text = &self.text[start..pos];
+        match text.parse::<i64>() {
+            Ok(n) if is_int => Ok(Json::Int(n)),
+            _ => Ok(Json::Float(text.parse().expect("JSON numbers are valid floats"))),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.text[self.pos..].starts_with(keyword) {
+            self.pos += keyword.len();
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expected `{c}`")));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let (line, column) = line_column(self.text, self.pos);
+        located("JSON", line, column, message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Json;
+
+    #[test]
+    fn parsing() {
+        let text = r#" {"a": [1, -2.5e1, true, null], "b\n": "é😀\"", "c": {}, "c": 9007199254740993} "#;
+        let json = Json::parse(text).unwrap();
+        let a = json.get("a").and_then(Json::as_array).unwrap();
+        assert_eq!(a, [Json::Int(1), Json::Float(-25.0), Json::Bool(true), Json::Null]);
+        assert_eq!(json.get("b\n").and_then(Json::as_str), Some("é😀\""));
+        assert_eq!(json.get("c"), Some(&Json::Int(9007199254740993)));
+        assert_eq!(Json::parse("9223372036854775808").unwrap(), Json::Float(9223372036854775808.0));
+
+        let message = "can't parse JSON at line 1, column 4: expected a value";
+        assert_eq!(Json::parse("[1,]").unwrap_err().message, message);
+        let message = "can't parse JSON at line 2, column 5: expected `:`";
+        assert_eq!(Json::parse("{\n\"a\" 1}").unwrap_err().message, message);
+        assert!(Json::parse("\"abc").is_err());
+    }
+
+    #[test]
+    fn writing() {
+        let json = Json::object([
+            ("id", Json::from(3)),
+            ("text", Json::from("a \"b\"\n\u{1}")),
+            ("list", Json::from(vec![Json::Null, Json::Float(0.5), Json::from(false), Json::Float(f64::NAN)])),
+            ("big", Json::Int(9007199254740993)),
+        ]);
+        let text = r#"{"id":3,"text":"a \"b\"\n\u0001","list":[null,0.5,false,null],"big":9007199254740993}"#;
+        assert_eq!(json.to_string(), text);
+        assert_eq!(Json::parse(text).unwrap().to_string(), text);
+    }
+}
@@ -0,0 +1,44 @@
+//! The built-in members of `pkl:base` classes, such as `String.split` and `Float.toFixed`, and of the other standard
+//! library modules, such as `pkl:math`.
+//!
+//! The functions here implement the semantics of the members on plain Rust values, independently of any evaluator:
+//! `pkl-eval` resolves a member like `"a,b".split(",")` and calls [`string::split`] with the receiver and arguments.
+//! Members that need the evaluator, such as `List.map` with its function argument, are implemented there.
+//!
+//! Indices and lengths of strings count characters (Unicode scalar values), not bytes. Operations that can fail on
+//! some arguments return [`Error`]s, which describe the problem but not where it happened.
+
+#![forbid(unsafe_code)]
+
+pub mod data;
+pub mod glob;
+pub mod json;
+pub mod math;
+pub mod number;
+pub mod platform;
+pub mod regex;
+pub mod string;
+
+use std::fmt;
+
+/// A member was called with arguments it isn't defined for, like `"abc".take(-1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Error { message: message.into() }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,51 @@
+//! Members of `pkl:math` that aren't plain `f64` functions.
+
+use crate::{Error, Result};
+
+/// `gcd(x, y)`: the greatest common divisor, which is never negative.
+pub fn gcd(x: i64, y: i64) -> Result<i64> {
+    let (mut a, mut b) = (x.unsigned_abs(), y.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let overflow = || Error::new(format!("the greatest common divisor of {x} and {y} doesn't fit into an `Int`"));
+    i64::try_from(a).map_err(|_| overflow())
+}
+
+/// `lcm(x, y)`: the least common multiple, which is never negative, and `0` if either number is `0`.
+pub fn lcm(x: i64, y: i64) -> Result<i64> {
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+    let overflow = || Error::new(format!("the least common multiple of {x} and {y} doesn't fit into an `Int`"));
+    let multiple = (x / gcd(x, y)?).checked_mul(y).ok_or_else(overflow)?;
+    multiple.checked_abs().ok_or_else(overflow)
+}
+
+/// `isPowerOfTwo(x)` for an `Int`.
+pub fn is_power_of_two(x: i64) -> bool {
+    x > 0 && x & (x - 1) == 0
+}
+
+/// `isPowerOfTwo(x)` for a `Float`, which includes negative powers like `0.25`.
+pub fn is_float_power_of_two(x: f64) -> bool {
+    const MANTISSA: u64 = (1 << 52) - 1;
+    x.is_normal() && x > 0.0 && x.to_bits() & MANTISSA == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        assert_eq!(gcd(12, -18).unwrap(), 6);
+        assert_eq!(gcd(0, 5).unwrap(), 5);
+        assert!(gcd(i64::MIN, 0).is_err());
+        assert_eq!(lcm(-4, 6).unwrap(), 12);
+        assert_eq!(lcm(0, 6).unwrap(), 0);
+        assert!(lcm(i64::MAX, i64::MAX - 1).is_err());
+        assert!(is_power_of_two(1024) && !is_power_of_two(0) && !is_power_of_two(6));
+        assert!(is_float_power_of_two(0.25) && !is_float_power_of_two(3.0) && !is_float_power_of_two(-2.0));
+    }
+}
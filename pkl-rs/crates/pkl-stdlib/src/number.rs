@@ -0,0 +1,111 @@
+//! Members of `Int` and `Float`.
+
+use crate::{Error, Result};
+
+/// `Float.toString()`: the shortest decimal representation that reads back as the same number, like `1.0`, `0.25`,
+/// or `1.0E21` for very large and very small numbers.
+pub fn float_to_string(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-3..1e7).contains(&magnitude) {
+        let text = format!("{value:e}");
+        let (mantissa, exponent) = text.split_once('e').expect("exponent notation has an `e`");
+        let dot = if mantissa.contains('.') { "" } else { ".0" };
+        return format!("{mantissa}{dot}E{exponent}");
+    }
+    let text = value.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        format!("{text}.0")
+    }
+}
+
+/// `Int.toRadixString(radix)`: the integer in a base between 2 and 36, with lower-case letters for digits above 9.
+pub fn to_radix_string(value: i64, radix: i64) -> Result<String> {
+    let radix = match u32::try_from(radix) {
+        Ok(radix @ 2..=36) => radix,
+        _ => return Err(Error::new(format!("expected a radix between 2 and 36, but got {radix}"))),
+    };
+
+    let mut digits = Vec::new();
+    let mut rest = value.unsigned_abs();
+    loop {
+        let digit = (rest % radix as u64) as u32;
+        digits.push(char::from_digit(digit, radix).expect("digit is below the radix"));
+        rest /= radix as u64;
+        if rest == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        digits.push('-');
+    }
+    Ok(digits.iter().rev().collect())
+}
+
+/// `toFixed(digits)`: the number rounded to between 0 and 20 fractional digits, which are all written out.
+pub fn to_fixed(value: f64, digits: i64) -> Result<String> {
+    match usize::try_from(digits) {
+        Ok(digits @ 0..=20) => Ok(format!("{value:.digits$}")),
+        _ => Err(Error::new(format!("expected between 0 and 20 fractional digits, but got {digits}"))),
+    }
+}
+
+/// `Float.round()`: the nearest integer, rounding halfway cases to the even one.
+pub fn round(value: f64) -> f64 {
+    value.round_ties_even()
+}
+
+/// `Float.toInt()`: the number with its fractional part dropped, if it fits an `Int`.
+pub fn to_int(value: f64) -> Result<i64> {
+    let truncated = value.trunc();
+    if truncated.is_finite() && truncated >= i64::MIN as f64 && truncated < i64::MAX as f64 {
+        Ok(truncated as i64)
+    } else {
+        Err(Error::new(format!("{} doesn't fit into an `Int`", float_to_string(value))))
+    }
+}
+
+/// `sign` of a `Float`: `-1.0`, `0.0`, or `1.0`, or `NaN` for `NaN`.
+pub fn float_sign(value: f64) -> f64 {
+    if value == 0.0 || value.is_nan() {
+        value
+    } else {
+        value.signum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn floats_as_strings() {
+        assert_eq!(float_to_string(1.0), "1.0");
+        assert_eq!(float_to_string(-0.25), "-0.25");
+        assert_eq!(float_to_string(1234567.0), "1234567.0");
+        assert_eq!(float_to_string(1e21), "1.0E21");
+        assert_eq!(float_to_string(1.5e-7), "1.5E-7");
+        assert_eq!(float_to_string(f64::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(to_radix_string(255, 16).unwrap(), "ff");
+        assert_eq!(to_radix_string(-5, 2).unwrap(), "-101");
+        assert_eq!(to_radix_string(i64::MIN, 2).unwrap().len(), 65);
+        assert!(to_radix_string(1, 37).is_err());
+        assert_eq!(to_fixed(2.345, 1).unwrap(), "2.3");
+        assert_eq!(to_fixed(2.0, 2).unwrap(), "2.00");
+        assert_eq!(round(2.5), 2.0);
+        assert_eq!(to_int(-2.9).unwrap(), -2);
+        assert!(to_int(f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! The values of `pkl:platform`, describing the platform a program runs on.
+
+/// The version of the Pkl language this implementation follows.
+pub const LANGUAGE_VERSION: &str = "0.25.0";
+
+/// The name of this implementation, which is the runtime of `pkl:platform`.
+pub const RUNTIME_NAME: &str = "pkl-rs";
+
+/// The version of this implementation.
+pub const RUNTIME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The name of the operating system, spelled the way the reference implementation spells it, like `macOS`.
+pub fn operating_system() -> &'static str {
+    match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "macOS",
+        "windows" => "Windows",
+        "freebsd" => "FreeBSD",
+        os => os,
+    }
+}
+
+/// The architecture of the processor, like `amd64` or `aarch64`.
+pub fn architecture() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "x86",
+        arch => arch,
+    }
+}
@@ -0,0 +1,368 @@
+//! `Regex` and `RegexMatch`, and the members of `String` taking a `Regex`.
+//!
+//! Pkl regular expressions use the syntax of Java's `java.util.regex`. Patterns are translated to the syntax of the
+//! `regex` crate where the two differ in spelling only (`\Q...\E` quoting, `\h`, and POSIX classes like
+//! `\p{Alpha}`); features the `regex` crate can't match in linear time, like lookaround and backreferences, are
+//! rejected. Positions in matches are character indices, like those of the other `String` members.
+
+use crate::{Error, Result};
+
+/// A compiled regular expression.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    pattern: String,
+    regex: regex::Regex,
+    /// The pattern anchored at both ends, for matching whole strings
+    entire: regex::Regex,
+    /// The pattern anchored at the start, for matching prefixes
+    prefix: regex::Regex,
+}
+
+/// A match of a [`Regex`], or of one of its capturing groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub value: String,
+    /// The character index of the start of the match
+    pub start: i64,
+    /// The character index after the end of the match
+    pub end: i64,
+    /// The matches of the capturing groups, starting with the whole match; empty for the match of a group
+    pub groups: Vec<Option<Match>>,
+}
+
+/// Which matches a replacement applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occurrence {
+    All,
+    First,
+    Last,
+}
+
+impl Regex {
+    /// `Regex(pattern)`.
+    pub fn new(pattern: &str) -> Result<Regex> {
+        Regex::compile(pattern, &translate(pattern)?)
+    }
+
+    /// A regular expression matching `literal` only, for the members that take a `String` or a `Regex`.
+    pub fn literal(literal: &str) -> Regex {
+        Regex::compile(literal, &regex::escape(literal)).expect("escaped text is a valid pattern")
+    }
+
+    fn compile(pattern: &str, translated: &str) -> Result<Regex> {
+        let compile = |source: &str| {
+            regex::Regex::new(source).map_err(|error| {
+                // the error of the `regex` crate quotes the pattern over several lines and ends with the reason
+                let text = error.to_string();
+                let reason = text.lines().last().unwrap_or_default().trim_start_matches("error: ").to_string();
+                Error::new(format!("{} isn't a valid regular expression: {reason}", crate::string::quote(pattern)))
+            })
+        };
+
+        Ok(Regex {
+            pattern: pattern.to_string(),
+            regex: compile(translated)?,
+            entire: compile(&format!(r"\A(?:{translated})\z"))?,
+            prefix: compile(&format!(r"\A(?:{translated})"))?,
+        })
+    }
+
+    /// `pattern`: the pattern as written.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// `groupCount`: the number of capturing groups.
+    pub fn group_count(&self) -> i64 {
+        self.regex.captures_len() as i64 - 1
+    }
+
+    /// `matchEntire(input)`: the match of the whole input, if the pattern matches all of it.
+    pub fn match_entire(&self, input: &str) -> Option<Match> {
+        self.entire.captures(input).map(|captures| to_match(input, &captures))
+    }
+
+    /// `findMatchesIn(input)`: the non-overlapping matches in the input, from left to right.
+    pub fn find_matches(&self, input: &str) -> Vec<Match> {
+        self.regex.captures_iter(input).map(|captures| to_match(input, &captures)).collect()
+    }
+
+    /// `String.matches(regex)`: whether the pattern matches the whole input.
+    pub fn matches_entire(&self, input: &str) -> bool {
+        self.entire.is_match(input)
+    }
+
+    /// `String.contains(regex)`.
+    pub fn is_match(&self, input: &str) -> bool {
+        self.regex.is_match(input)
+    }
+
+    /// `String.startsWith(regex)`.
+    pub fn matches_prefix(&self, input: &str) -> bool {
+        self.prefix.is_match(input)
+    }
+
+    /// `String.indexOf(regex)`: the character index of the first match.
+    pub fn index_of(&self, input: &str) -> Option<i64> {
+        self.regex.find(input).map(|found| char_index(input, found.start()))
+    }
+
+    /// `String.lastIndexOf(regex)`: the character index of the last match.
+    pub fn last_index_of(&self, input: &str) -> Option<i64> {
+        self.regex.find_iter(input).last().map(|found| char_index(input, found.start()))
+    }
+
+    /// `String.split(regex)`.
+    pub fn split(&self, input: &str) -> Vec<String> {
+        self.regex.split(input).map(String::from).collect()
+    }
+
+    /// `replaceAll`, `replaceFirst`, and `replaceLast` with a replacement string, in which `$n` and `${name}` refer
+    /// to capturing groups and `\` escapes the next character.
+    pub fn replace(&self, input: &str, occurrence: Occurrence, replacement: &str) -> Result<String> {
+        let parts = self.replacement(replacement)?;
+        self.replace_mapped(input, occurrence, |found| {
+            let mut text = String::new();
+            for part in &parts {
+                match part {
+                    Part::Literal(literal) => text.push_str(literal),
+                    Part::Group(index) => {
+                        if let Some(Some(group)) = found.groups.get(*index) {
+                            text.push_str(&group.value);
+                        }
+                    }
+                }
+            }
+            Ok(text)
+        })
+    }
+
+    /// `replaceAllMapped`, `replaceFirstMapped`, and `replaceLastMapped`: replaces matches with what `mapper` returns
+    /// for them.
+    pub fn replace_mapped<E>(
+        &self,
+        input: &str,
+        occurrence: Occurrence,
+        mut mapper: impl FnMut(Match) -> std::result::Result<String, E>,
+    ) -> std::result::Result<String, E> {
+        let found: Vec<_> = self.regex.captures_iter(input).collect();
+        let replaced: Vec<_> = match occurrence {
+            Occurrence::All => found.iter().collect(),
+            Occurrence::First => found.first().into_iter().collect(),
+            Occurrence::Last => found.last().into_iter().collect(),
+        };
+
+        let mut text = String::with_capacity(input.len());
+        let mut end = 0;
+        for captures in replaced {
+            let whole = captures.get(0).expect("group 0 is the whole match");
+            text.push_str(&input[end..whole.start()]);
+            text.push_str(&mapper(to_match(input, captures))?);
+            end = whole.end();
+        }
+        text.push_str(&input[end..]);
+        Ok(text)
+    }
+
+    /// Parses a replacement string into literal text and group references, the way Java does: `$` is followed by
+    /// as many digits as still form the number of a group.
+    fn replacement(&self, replacement: &str) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = replacement.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(escaped) => literal.push(escaped),
+                    None => return Err(Error::new("the replacement ends with a `\\`")),
+                },
+                '$' => {
+                    let index = match chars.peek() {
+                        Some('{') => {
+                            chars.next();
+                            let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                            let group = self.regex.capture_names().position(|n| n == Some(name.as_str()));
+                            group.ok_or_else(|| Error::new(format!("the pattern has no group named `{name}`")))?
+                        }
+                        Some(digit) if digit.is_ascii_digit() => {
+                            let mut index = 0;
+                            while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                                let next = index * 10 + digit as usize;
+                                if next >= self.regex.captures_len() && index > 0 {
+                                    break;
+                                }
+                                index = next;
+                                chars.next();
+                            }
+                            if index >= self.regex.captures_len() {
+                                return Err(Error::new(format!("the pattern has no group {index}")));
+                            }
+                            index
+                        }
+                        _ => return Err(Error::new("expected a group number or `{name}` after `$` in the replacement")),
+                    };
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    parts.push(Part::Group(index));
+                }
+                c => literal.push(c),
+            }
+        }
+        parts.push(Part::Literal(literal));
+        Ok(parts)
+    }
+}
+
+#[derive(Debug)]
+enum Part {
+    Literal(String),
+    Group(usize),
+}
+
+fn to_match(input: &str, captures: &regex::Captures) -> Match {
+    let group = |found: regex::Match| Match {
+        value: found.as_str().to_string(),
+        start: char_index(input, found.start()),
+        end: char_index(input, found.end()),
+        groups: Vec::new(),
+    };
+    let mut whole = group(captures.get(0).expect("group 0 is the whole match"));
+    whole.groups = captures.iter().map(|found| found.map(group)).collect();
+    whole
+}
+
+fn char_index(s: &str, byte: usize) -> i64 {
+    s[..byte].chars().count() as i64
+}
+
+const HORIZONTAL_SPACE: &str = r"\t \xA0\x{1680}\x{180E}\x{2000}-\x{200A}\x{202F}\x{205F}\x{3000}";
+
+/// The POSIX classes Java spells `\p{Name}`, which the `regex` crate spells `[[:name:]]`.
+const POSIX_CLASSES: [&str; 13] = [
+    "Lower", "Upper", "ASCII", "Alpha", "Digit", "Alnum", "Punct", "Graph", "Print", "Blank", "Cntrl", "XDigit",
+    "Space",
+];
+
+/// Translates a Java pattern to the syntax of the `regex` crate.
+fn translate(pattern: &str) -> Result<String> {
+    let unsupported = |feature: &str| Err(Error::new(format!("{feature} in regular expressions aren't supported")));
+
+    let mut translated = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    let mut class_depth = 0;
+    let mut in_repetition = false;
+    let mut after_quantifier = false;
+    while let Some(c) = chars.next() {
+        let mut quantifier = false;
+        match c {
+            '\\' => match chars.next() {
+                Some('Q') => {
+                    let mut literal = String::new();
+                    while let Some(c) = chars.next() {
+                        if c == '\\' && chars.peek() == Some(&'E') {
+                            chars.next();
+                            break;
+                        }
+                        literal.push(c);
+                    }
+                    translated.push_str(&regex::escape(&literal));
+                }
+                Some('h') => translated.push_str(&format!("[{HORIZONTAL_SPACE}]")),
+                Some('H') => translated.push_str(&format!("[^{HORIZONTAL_SPACE}]")),
+                Some(p @ ('p' | 'P')) if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    match POSIX_CLASSES.contains(&name.as_str()) {
+                        true if p == 'p' => translated.push_str(&format!("[[:{}:]]", name.to_lowercase())),
+                        true => translated.push_str(&format!("[[:^{}:]]", name.to_lowercase())),
+                        false => translated.push_str(&format!("\\{p}{{{name}}}")),
+                    }
+                }
+                Some('1'..='9') if class_depth == 0 => return unsupported("backreferences"),
+                Some(escaped) => {
+                    translated.push('\\');
+                    translated.push(escaped);
+                }
+                None => return Err(Error::new("the regular expression ends with a `\\`")),
+            },
+            '[' => {
+                class_depth += 1;
+                translated.push(c);
+            }
+            ']' if class_depth > 0 => {
+                class_depth -= 1;
+                translated.push(c);
+            }
+            '(' if class_depth == 0 && chars.peek() == Some(&'?') => {
+                chars.next();
+                let rest: String = chars.clone().take(2).collect();
+                if rest.starts_with(['=', '!']) || rest == "<=" || rest == "<!" {
+                    return unsupported("lookahead and lookbehind assertions");
+                }
+                if rest.starts_with('>') {
+                    return unsupported("atomic groups");
+                }
+                translated.push_str("(?");
+            }
+            '{' if class_depth == 0 => {
+                in_repetition = chars.peek().is_some_and(char::is_ascii_digit);
+                translated.push(c);
+            }
+            '*' | '+' | '?' | '}' if class_depth == 0 => {
+                if after_quantifier && c == '+' {
+                    return unsupported("possessive quantifiers");
+                }
+                // `?` after a quantifier makes it lazy rather than being another quantifier
+                quantifier = !(after_quantifier && c == '?') && (c != '}' || in_repetition);
+                in_repetition &= c != '}';
+                translated.push(c);
+            }
+            c => translated.push(c),
+        }
+        after_quantifier = quantifier;
+    }
+
+    Ok(translated)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn java_syntax() {
+        assert_eq!(translate(r"a\Q.*\E\h").unwrap(), format!(r"a\.\*[{HORIZONTAL_SPACE}]"));
+        assert_eq!(translate(r"\p{Alpha}+\P{Digit}\p{L}").unwrap(), r"[[:alpha:]]+[[:^digit:]]\p{L}");
+        let error = translate(r"a{2}+").unwrap_err();
+        assert_eq!(error.message, "possessive quantifiers in regular expressions aren't supported");
+        assert!(translate(r"a*?[+]").is_ok());
+        assert!(translate(r"x}+").is_ok());
+        assert!(translate(r"(?=a)").is_err());
+        assert!(translate(r"(a)\1").is_err());
+
+        let regex = Regex::new(r"(?<year>\d{4})-(\d\d)").unwrap();
+        assert_eq!(regex.group_count(), 2);
+        let error = Regex::new("(").unwrap_err();
+        assert_eq!(error.message, r#""(" isn't a valid regular expression: unclosed group"#);
+    }
+
+    #[test]
+    fn matching() {
+        let regex = Regex::new(r"(\w)(\d)?").unwrap();
+        let found = regex.find_matches("é1 b");
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[1].value.as_str(), found[1].start, found[1].end), ("b", 3, 4));
+        assert_eq!(found[1].groups[2], None);
+        assert!(Regex::new("a|ab").unwrap().matches_entire("ab"));
+        assert_eq!(regex.last_index_of("a b"), Some(2));
+    }
+
+    #[test]
+    fn replacements() {
+        let regex = Regex::new(r"(\d)(\d)?").unwrap();
+        assert_eq!(regex.replace("12 3", Occurrence::All, r"<$2$1>").unwrap(), "<21> <3>");
+        assert_eq!(regex.replace("12 3", Occurrence::Last, r"\$$10").unwrap(), "12 $30");
+        let error = regex.replace("1", Occurrence::First, "${num}").unwrap_err();
+        assert_eq!(error.message, "the pattern has no group named `num`");
+        assert!(regex.replace("1", Occurrence::First, "$3").is_err());
+    }
+}
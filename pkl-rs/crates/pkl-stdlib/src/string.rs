@@ -0,0 +1,267 @@
+//! Members of `String`.
+
+use crate::{Error, Result};
+
+/// `length`: the number of characters.
+pub fn length(s: &str) -> i64 {
+    s.chars().count() as i64
+}
+
+/// `isBlank`: whether the string is empty or consists of whitespace only.
+pub fn is_blank(s: &str) -> bool {
+    s.chars().all(char::is_whitespace)
+}
+
+/// `getOrNull(index)`: the character at `index`, as a one-character string.
+pub fn char_at(s: &str, index: i64) -> Option<String> {
+    let index = usize::try_from(index).ok()?;
+    s.chars().nth(index).map(String::from)
+}
+
+/// `substring(start, exclusiveEnd)`.
+pub fn substring(s: &str, start: i64, end: i64) -> Result<String> {
+    let length = length(s);
+    if start < 0 || start > end || end > length {
+        return Err(Error::new(format!("the range {start}..{end} is out of bounds for a string of length {length}")));
+    }
+    Ok(s.chars().skip(start as usize).take((end - start) as usize).collect())
+}
+
+/// `take(n)`: the first `n` characters, or the whole string if it is shorter.
+pub fn take(s: &str, n: i64) -> Result<String> {
+    Ok(s.chars().take(count(n)?).collect())
+}
+
+/// `drop(n)`: the string without its first `n` characters.
+pub fn drop(s: &str, n: i64) -> Result<String> {
+    Ok(s.chars().skip(count(n)?).collect())
+}
+
+/// `takeLast(n)`: the last `n` characters, or the whole string if it is shorter.
+pub fn take_last(s: &str, n: i64) -> Result<String> {
+    let skipped = (length(s) as usize).saturating_sub(count(n)?);
+    Ok(s.chars().skip(skipped).collect())
+}
+
+/// `dropLast(n)`: the string without its last `n` characters.
+pub fn drop_last(s: &str, n: i64) -> Result<String> {
+    let kept = (length(s) as usize).saturating_sub(count(n)?);
+    Ok(s.chars().take(kept).collect())
+}
+
+/// `indexOf(pattern)`: the character index of the first occurrence of `pattern`.
+pub fn index_of(s: &str, pattern: &str) -> Option<i64> {
+    s.find(pattern).map(|byte| length(&s[..byte]))
+}
+
+/// `lastIndexOf(pattern)`: the character index of the last occurrence of `pattern`.
+pub fn last_index_of(s: &str, pattern: &str) -> Option<i64> {
+    s.rfind(pattern).map(|byte| length(&s[..byte]))
+}
+
+/// `split(separator)`. An empty separator splits the string into its characters.
+pub fn split(s: &str, separator: &str) -> Vec<String> {
+    if separator.is_empty() {
+        return s.chars().map(String::from).collect();
+    }
+    s.split(separator).map(String::from).collect()
+}
+
+/// `replaceFirst(pattern, replacement)`.
+pub fn replace_first(s: &str, pattern: &str, replacement: &str) -> String {
+    s.replacen(pattern, replacement, 1)
+}
+
+/// `replaceLast(pattern, replacement)`.
+pub fn replace_last(s: &str, pattern: &str, replacement: &str) -> String {
+    match s.rfind(pattern) {
+        Some(start) => format!("{}{replacement}{}", &s[..start], &s[start + pattern.len()..]),
+        None => s.to_string(),
+    }
+}
+
+/// `capitalize()`: the string with its first character in upper case.
+pub fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `decapitalize()`: the string with its first character in lower case.
+pub fn decapitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `padStart(width, char)`: the string prefixed with `char` until it is `width` characters long.
+pub fn pad_start(s: &str, width: i64, pad: &str) -> Result<String> {
+    let padding = padding(s, width, pad)?;
+    Ok(format!("{padding}{s}"))
+}
+
+/// `padEnd(width, char)`: the string followed by `char` until it is `width` characters long.
+pub fn pad_end(s: &str, width: i64, pad: &str) -> Result<String> {
+    let padding = padding(s, width, pad)?;
+    Ok(format!("{s}{padding}"))
+}
+
+fn padding(s: &str, width: i64, pad: &str) -> Result<String> {
+    let mut chars = pad.chars();
+    let (Some(pad), None) = (chars.next(), chars.next()) else {
+        return Err(Error::new(format!("expected a single character to pad with, but got {pad:?}")));
+    };
+    let missing = usize::try_from(width - length(s)).unwrap_or(0);
+    Ok(std::iter::repeat_n(pad, missing).collect())
+}
+
+/// `repeat(n)`.
+pub fn repeat(s: &str, n: i64) -> Result<String> {
+    Ok(s.repeat(count(n)?))
+}
+
+/// `reverse()`.
+pub fn reverse(s: &str) -> String {
+    s.chars().rev().collect()
+}
+
+/// `toInt()`: parses a decimal integer with an optional sign, and `_` between digits.
+pub fn to_int(s: &str) -> Option<i64> {
+    let digits = without_separators(s)?;
+    digits.parse().ok()
+}
+
+/// `toFloat()`: parses a decimal number, `NaN`, or `Infinity`, with an optional sign and exponent.
+pub fn to_float(s: &str) -> Option<f64> {
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    match unsigned {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(sign * f64::INFINITY),
+        _ if unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') => {
+            without_separators(unsigned)?.parse::<f64>().ok().map(|value| sign * value)
+        }
+        _ => None,
+    }
+}
+
+/// `toBoolean()`: parses `true` or `false`, ignoring case.
+pub fn to_boolean(s: &str) -> Option<bool> {
+    if s.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if s.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// `base64`: bytes in the standard Base64 alphabet, padded with `=`.
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group =
+            chunk.iter().enumerate().fold(0u32, |group, (index, byte)| group | u32::from(*byte) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// The string as a Pkl string literal, like `"say \"hi\""`.
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Removes the `_`s separating digits, rejecting any that don't.
+fn without_separators(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    for (index, byte) in bytes.iter().enumerate() {
+        if *byte == b'_' {
+            let digit = |i: Option<usize>| {
+                i.and_then(|i| bytes.get(i)).is_some_and(|b| b.is_ascii_digit() || *b == b'_')
+            };
+            if index == 0 || !digit(index.checked_sub(1)) || !digit(Some(index + 1)) {
+                return None;
+            }
+        }
+    }
+    Some(s.replace('_', ""))
+}
+
+/// A number of characters, which can't be negative.
+fn count(n: i64) -> Result<usize> {
+    usize::try_from(n).map_err(|_| Error::new(format!("expected a non-negative count, but got {n}")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn indices_count_characters() {
+        assert_eq!(length("héllo"), 5);
+        assert_eq!(index_of("héllo", "l"), Some(2));
+        assert_eq!(last_index_of("héllo", "l"), Some(3));
+        assert_eq!(substring("héllo", 1, 3).unwrap(), "él");
+        assert_eq!(char_at("héllo", 1).as_deref(), Some("é"));
+        assert!(substring("abc", 2, 4).is_err());
+        assert_eq!(take_last("abc", 5).unwrap(), "abc");
+        assert_eq!(drop_last("abc", 1).unwrap(), "ab");
+        assert!(take("abc", -1).is_err());
+    }
+
+    #[test]
+    fn transformations() {
+        assert_eq!(split("a,b,,c", ","), ["a", "b", "", "c"]);
+        assert_eq!(split("ab", ""), ["a", "b"]);
+        assert_eq!(replace_last("a-b-c", "-", "+"), "a-b+c");
+        assert_eq!(capitalize("pigeon"), "Pigeon");
+        assert_eq!(pad_start("7", 3, "0").unwrap(), "007");
+        assert_eq!(pad_end("abcd", 3, " ").unwrap(), "abcd");
+        assert!(pad_start("7", 3, "00").is_err());
+        assert_eq!(quote("a \"b\"\n\\(c)\u{7}"), r#""a \"b\"\n\\(c)\u{7}""#);
+        assert_eq!(base64(b"pigeon"), "cGlnZW9u");
+        assert_eq!(base64(b"pigeons"), "cGlnZW9ucw==");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(to_int("-1_000"), Some(-1000));
+        assert_eq!(to_int("1__0"), Some(10));
+        assert_eq!(to_int("_1"), None);
+        assert_eq!(to_int("1.5"), None);
+        assert_eq!(to_float("1.5e3"), Some(1500.0));
+        assert_eq!(to_float("-Infinity"), Some(f64::NEG_INFINITY));
+        assert_eq!(to_float("inf"), None);
+        assert_eq!(to_boolean("TRUE"), Some(true));
+        assert_eq!(to_boolean("yes"), None);
+    }
+}
@@ -0,0 +1,131 @@
+//! JavaScript bindings for the lexer, parser, and evaluator, for web playgrounds and editor extensions that run in a
+//! browser. Build them with `wasm-pack build crates/pkl-wasm`.
+//!
+//! Tokens and diagnostics are returned as JSON, which is cheaper to hand over than JavaScript objects built one
+//! property at a time. Modules are evaluated without access to files, the network, or the environment: they can only
+//! import the standard library.
+//!
+//! ```js
+//! import { evaluate, parse } from "pkl-wasm";
+//!
+//! JSON.parse(parse("name = ")).diagnostics[0].message; // "expected expression, found end of input"
+//! evaluate("name = \"pigeon\"", "json"); // '{\n  "name": "pigeon"\n}\n'
+//! ```
+
+#![forbid(unsafe_code)]
+
+use oxc_allocator::Allocator;
+use pkl_eval::value::{Object, ObjectKind, Value};
+use pkl_eval::EvaluatorOptions;
+use pkl_lexer::diagnostic::Diagnostic;
+use pkl_lexer::token::Span;
+use pkl_lexer::Lexer;
+use pkl_render::{
+    JsonOptions, JsonRenderer, PListRenderer, PcfOptions, PcfRenderer, PropertiesOptions, PropertiesRenderer, Renderer,
+    XmlOptions, XmlRenderer, YamlOptions, YamlRenderer,
+};
+use wasm_bindgen::prelude::*;
+
+/// The tokens of a module, as a JSON array of objects with the `kind` of each token and the `start` and `end` of its
+/// span, in bytes.
+#[wasm_bindgen]
+pub fn tokenize(source: &str) -> String {
+    let alloc = Allocator::default();
+    let tokens = Lexer::tokenize(&alloc, source).into_iter().map(|token| {
+        let mut object = spanned(token.span);
+        object.properties.insert("kind".to_string(), Value::String(format!("{:?}", token.kind)));
+        Value::Object(object)
+    });
+    json(&Value::List(tokens.collect()))
+}
+
+/// The syntax errors of a module, as a JSON object whose `diagnostics` are objects with a `message`, and the `start`
+/// and `end` of the span it's about.
+#[wasm_bindgen]
+pub fn parse(source: &str) -> String {
+    let alloc = Allocator::default();
+    let result = pkl_parser::parse_module(&alloc, source);
+    let mut object = Object::new(ObjectKind::Dynamic);
+    object.properties.insert("diagnostics".to_string(), diagnostics(&result.diagnostics));
+    json(&Value::Object(object))
+}
+
+/// Evaluates a module and renders it in `format`, one of `pcf`, `json`, `yaml`, `plist`, `properties`, and `xml`, or
+/// as its `output` says without one. Errors are thrown with their messages.
+#[wasm_bindgen]
+pub fn evaluate(source: &str, format: Option<String>) -> Result<String, JsError> {
+    let renderer = format.as_deref().map(renderer).transpose().map_err(|message| JsError::new(&message))?;
+    let options = EvaluatorOptions {
+        allowed_modules: vec!["pkl:".to_string(), "repl:".to_string()],
+        allowed_resources: Vec::new(),
+        cache_dir: None,
+        environment_variables: Some(Default::default()),
+        ..EvaluatorOptions::default()
+    };
+    let output = pkl_eval::evaluate_output_with(source, "repl:text", options).map_err(|error| message(&error))?;
+    pkl_render::render_output(&output, renderer.as_deref()).map_err(|error| JsError::new(&error.to_string()))
+}
+
+fn renderer(format: &str) -> Result<Box<dyn Renderer>, String> {
+    Ok(match format {
+        "pcf" => Box::new(PcfRenderer::new(PcfOptions::default())),
+        "json" => Box::new(JsonRenderer::new(JsonOptions::default())),
+        "yaml" => Box::new(YamlRenderer::new(YamlOptions::default())),
+        "plist" => Box::new(PListRenderer),
+        "properties" => Box::new(PropertiesRenderer::new(PropertiesOptions::default())),
+        "xml" => Box::new(XmlRenderer::new(XmlOptions::default())),
+        format => return Err(format!("unknown output format `{format}`")),
+    })
+}
+
+/// The message of an evaluation error, with the position it's at.
+fn message(error: &pkl_eval::Error) -> JsError {
+    let (span, message) = match error {
+        pkl_eval::Error::Syntax(diagnostics) => match diagnostics.first() {
+            Some(diagnostic) => (Some(diagnostic.span), diagnostic.message.as_str()),
+            None => (None, "syntax error"),
+        },
+        pkl_eval::Error::Eval(error) => (Some(error.span), error.message.as_str()),
+        pkl_eval::Error::Deserialize(error) => (None, error.message.as_str()),
+    };
+    match span {
+        Some(span) => JsError::new(&format!("{}..{}: {message}", span.start, span.end)),
+        None => JsError::new(message),
+    }
+}
+
+fn diagnostics(diagnostics: &[Diagnostic]) -> Value {
+    let diagnostics = diagnostics.iter().map(|diagnostic| {
+        let mut object = spanned(diagnostic.span);
+        object.properties.insert("message".to_string(), Value::String(diagnostic.message.clone()));
+        Value::Object(object)
+    });
+    Value::List(diagnostics.collect())
+}
+
+/// An object with the `start` and `end` of a span.
+fn spanned(span: Span) -> Object {
+    let mut object = Object::new(ObjectKind::Dynamic);
+    object.properties.insert("start".to_string(), Value::Int(span.start as i64));
+    object.properties.insert("end".to_string(), Value::Int(span.end as i64));
+    object
+}
+
+fn json(value: &Value) -> String {
+    let renderer = JsonRenderer::new(JsonOptions { indent: String::new(), omit_null_properties: false });
+    let json = renderer.render(value).expect("tokens and diagnostics can be rendered");
+    json.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, tokenize};
+
+    #[test]
+    fn tokens_and_diagnostics() {
+        let tokens = tokenize("a = 1");
+        assert!(tokens.starts_with("[{\"start\":0,\"end\":1,\"kind\":\"Identifier\"}"), "{tokens}");
+        assert_eq!(parse("a = 1"), "{\"diagnostics\":[]}");
+        assert!(parse("a = ").contains("\"message\":"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Differential testing of `pkl-lexer` against the tree-sitter grammar of Pkl: both tokenize the same modules, and
+//! wherever they disagree on where a token starts or ends, or on what kind of token it is, it's reported.
+//!
+//! The two split some things differently without disagreeing, so both are brought to a common form first: a string
+//! literal is a single token from its opening delimiter to its closing one, or up to and from its interpolations,
+//! keywords and punctuation are compared by their text, and all kinds of comments are alike.
+
+use oxc_allocator::Allocator;
+use pkl_lexer::line_index::LineIndex;
+use pkl_lexer::token::{Span, TokenKind};
+use pkl_lexer::Lexer;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: Kind,
+    pub span: Span,
+}
+
+/// The kind of a token, as both tokenizers can tell it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Identifier,
+    Int,
+    Float,
+    /// Text of a string literal, with its delimiters
+    String,
+    Comment,
+    /// A keyword or punctuation, which is its text
+    Symbol(String),
+    /// Text that couldn't be tokenized
+    Error,
+}
+
+/// Where the tokenizers disagree: the tokens of each that cover the same text, but don't split it into the same
+/// tokens. One side is empty where only the other has tokens.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Disagreement {
+    pub lexer: Vec<Token>,
+    pub grammar: Vec<Token>,
+}
+
+impl Disagreement {
+    /// A line describing the disagreement, for a report on `source`.
+    pub fn describe(&self, source: &str) -> String {
+        let lines = LineIndex::new(source);
+        let span = self.lexer.iter().chain(&self.grammar).map(|token| token.span).reduce(Span::cover);
+        let (start, end) = lines.span_line_cols(span.unwrap_or_default());
+        let tokens = |tokens: &[Token]| -> String {
+            let text = |token: &Token| &source[token.span.start..token.span.end];
+            let tokens: Vec<String> =
+                tokens.iter().map(|token| format!("{:?} {:?}", token.kind, text(token))).collect();
+            if tokens.is_empty() {
+                "nothing".to_string()
+            } else {
+                tokens.join(", ")
+            }
+        };
+        format!("{start}-{end}: pkl-lexer has {}, tree-sitter has {}", tokens(&self.lexer), tokens(&self.grammar))
+    }
+}
+
+/// The tokens `pkl-lexer` lexes `source` into.
+pub fn lexer_tokens(source: &str) -> Vec<Token> {
+    let alloc = Allocator::default();
+    let tokens = Lexer::tokenize(&alloc, source).into_iter().filter(|token| token.kind != TokenKind::Eof);
+    let tokens = tokens.map(|token| {
+        let kind = match token.kind {
+            TokenKind::Identifier => Kind::Identifier,
+            TokenKind::IntLiteral => Kind::Int,
+            TokenKind::FloatLiteral => Kind::Float,
+            TokenKind::StringLiteral | TokenKind::StringStart | TokenKind::StringPart | TokenKind::StringEnd => {
+                Kind::String
+            }
+            TokenKind::LineComment | TokenKind::BlockComment | TokenKind::DocComment | TokenKind::Shebang => {
+                Kind::Comment
+            }
+            TokenKind::Error => Kind::Error,
+            _ => Kind::Symbol(source[token.span.start..token.span.end].to_string()),
+        };
+        Token { kind, span: token.span }
+    });
+    merge_strings(tokens)
+}
+
+/// The tokens of the tree-sitter grammar for `source`: the leaves of its syntax tree, with the pieces of string
+/// literals outside of their interpolations as string text, and the nodes it couldn't parse as errors.
+#[cfg(feature = "tree-sitter")]
+pub fn grammar_tokens(source: &str) -> Vec<Token> {
+    fn collect(node: tree_sitter::Node, source: &str, in_string: bool, tokens: &mut Vec<Token>) {
+        let span = Span::new(node.start_byte(), node.end_byte());
+        let kind = node.kind();
+        if node.is_error() {
+            return tokens.push(Token { kind: Kind::Error, span });
+        }
+        if node.child_count() == 0 {
+            let kind = match kind {
+                // missing nodes, which the grammar assumes to recover, have no text
+                _ if span.is_empty() => return,
+                "identifier" => Kind::Identifier,
+                "intLiteral" => Kind::Int,
+                "floatLiteral" => Kind::Float,
+                _ if kind.ends_with("Comment") => Kind::Comment,
+                _ if in_string => Kind::String,
+                _ => Kind::Symbol(source[span.start..span.end].to_string()),
+            };
+            return tokens.push(Token { kind, span });
+        }
+        let in_string = !kind.starts_with("interpolation") && (in_string || kind.ends_with("StringLiteral"));
+        for child in node.children(&mut node.walk()) {
+            collect(child, source, in_string, tokens);
+        }
+    }
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_pkl::language()).expect("the grammar isn't compatible with tree-sitter");
+    let tree = parser.parse(source, None).expect("tree-sitter didn't parse");
+    let mut tokens = Vec::new();
+    collect(tree.root_node(), source, false, &mut tokens);
+    merge_strings(tokens)
+}
+
+/// Merges the adjacent pieces of string literals into single tokens.
+fn merge_strings(tokens: impl IntoIterator<Item = Token>) -> Vec<Token> {
+    let mut merged: Vec<Token> = Vec::new();
+    for token in tokens {
+        let string = token.kind == Kind::String;
+        match merged.last_mut() {
+            Some(last) if string && last.kind == Kind::String && last.span.end == token.span.start => {
+                last.span.end = token.span.end;
+            }
+            _ => merged.push(token),
+        }
+    }
+    merged
+}
+
+/// Where `lexer` and `grammar`, tokens of the same source, disagree. After tokens that don't line up, the comparison
+/// picks up again where both have a token ending at the same offset.
+pub fn disagreements(lexer: &[Token], grammar: &[Token]) -> Vec<Disagreement> {
+    let mut disagreements = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < lexer.len() && j < grammar.len() {
+        let (a, b) = (&lexer[i], &grammar[j]);
+        if a.span == b.span {
+            if a.kind != b.kind {
+                disagreements.push(Disagreement { lexer: vec![a.clone()], grammar: vec![b.clone()] });
+            }
+            (i, j) = (i + 1, j + 1);
+        } else if a.span.end <= b.span.start {
+            disagreements.push(Disagreement { lexer: vec![a.clone()], grammar: Vec::new() });
+            i += 1;
+        } else if b.span.end <= a.span.start {
+            disagreements.push(Disagreement { lexer: Vec::new(), grammar: vec![b.clone()] });
+            j += 1;
+        } else {
+            // overlapping tokens: both sides take tokens until they end at the same offset
+            let (start_i, start_j) = (i, j);
+            (i, j) = (i + 1, j + 1);
+            loop {
+                let (a, b) = (lexer[i - 1].span.end, grammar[j - 1].span.end);
+                if a < b && i < lexer.len() {
+                    i += 1;
+                } else if a > b && j < grammar.len() {
+                    j += 1;
+                } else {
+                    break;
+                }
+            }
+            let (lexer, grammar) = (lexer[start_i..i].to_vec(), grammar[start_j..j].to_vec());
+            disagreements.push(Disagreement { lexer, grammar });
+        }
+    }
+    disagreements.extend(lexer[i..].iter().map(|a| Disagreement { lexer: vec![a.clone()], grammar: Vec::new() }));
+    disagreements.extend(grammar[j..].iter().map(|b| Disagreement { lexer: Vec::new(), grammar: vec![b.clone()] }));
+    disagreements
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn token(kind: Kind, start: usize, end: usize) -> Token {
+        Token { kind, span: Span::new(start, end) }
+    }
+
+    #[test]
+    fn strings_are_single_tokens() {
+        let source = "x = \"a \\(b) c\" // d";
+        let symbol = |text: &str| Kind::Symbol(text.to_string());
+        assert_eq!(
+            lexer_tokens(source),
+            [
+                token(Kind::Identifier, 0, 1),
+                token(symbol("="), 2, 3),
+                token(Kind::String, 4, 7),
+                token(symbol("\\("), 7, 9),
+                token(Kind::Identifier, 9, 10),
+                token(symbol(")"), 10, 11),
+                token(Kind::String, 11, 14),
+                token(Kind::Comment, 15, 19),
+            ]
+        );
+    }
+
+    #[test]
+    fn disagreements_pick_up_where_tokens_line_up() {
+        let lexer = [
+            token(Kind::Identifier, 0, 1),
+            token(Kind::Symbol("..".to_string()), 1, 3),
+            token(Kind::Int, 3, 4),
+            token(Kind::Int, 5, 6),
+        ];
+        let grammar = [
+            token(Kind::Identifier, 0, 1),
+            token(Kind::Symbol(".".to_string()), 1, 2),
+            token(Kind::Float, 2, 4),
+            token(Kind::Identifier, 5, 6),
+            token(Kind::Comment, 7, 9),
+        ];
+        assert_eq!(
+            disagreements(&lexer, &grammar),
+            [
+                Disagreement { lexer: lexer[1..3].to_vec(), grammar: grammar[1..3].to_vec() },
+                Disagreement { lexer: vec![lexer[3].clone()], grammar: vec![grammar[3].clone()] },
+                Disagreement { lexer: Vec::new(), grammar: vec![grammar[4].clone()] },
+            ]
+        );
+    }
+
+    /// Every module of the corpus is tokenized alike by both, or the disagreements are reported.
+    #[cfg(feature = "tree-sitter")]
+    #[test]
+    fn corpus() {
+        use std::path::{Path, PathBuf};
+
+        fn find_modules(dir: &Path, modules: &mut Vec<PathBuf>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    find_modules(&path, modules);
+                } else if path.extension().is_some_and(|extension| extension == "pkl") {
+                    modules.push(path);
+                }
+            }
+        }
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let dirs = match std::env::var_os("PKL_CORPUS") {
+            Some(dir) => vec![PathBuf::from(dir)],
+            None => vec![root.join("../crates/pkl-conformance/snippets"), root.join("../crates/pkl-lang/snapshots")],
+        };
+        let mut paths = Vec::new();
+        dirs.iter().for_each(|dir| find_modules(dir, &mut paths));
+        paths.sort();
+
+        let mut report = Vec::new();
+        for path in &paths {
+            let source = std::fs::read_to_string(path).unwrap();
+            for disagreement in disagreements(&lexer_tokens(&source), &grammar_tokens(&source)) {
+                report.push(format!("{}:{}", path.display(), disagreement.describe(&source)));
+            }
+        }
+        assert!(report.is_empty(), "{} disagreements in {} modules:\n{}", report.len(), paths.len(), report.join("\n"));
+    }
+}
@@ -0,0 +1,42 @@
+//! Evaluates arbitrary input as a module in a sandbox, which has to end without panicking, and within its timeout.
+//!
+//! The evaluator can import nothing but the module itself and the standard library, and read nothing: there are no
+//! environment variables, external properties, files, URLs, or cache directory within its reach. libFuzzer caps the
+//! memory of a run, so run the target with caps tighter than its defaults, like
+//! `cargo fuzz run evaluate -- -rss_limit_mb=512 -malloc_limit_mb=256`, and an input that makes the evaluator
+//! allocate without bound is reported as a crash.
+
+#![no_main]
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use libfuzzer_sys::fuzz_target;
+use pkl_eval::{Deprecations, EvaluatorOptions};
+
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How much longer than its timeout an evaluation can run, since the timeout is only checked every so many
+/// expressions, and not while the evaluated value is converted
+const GRACE: Duration = Duration::from_millis(500);
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data);
+    let options = EvaluatorOptions {
+        allowed_modules: vec!["pkl:".to_string(), "repl:".to_string()],
+        allowed_resources: Vec::new(),
+        cache_dir: None,
+        environment_variables: Some(HashMap::new()),
+        timeout: Some(TIMEOUT),
+        // sanitizers take more stack for each level than a release build
+        max_depth: 64,
+        threads: 1,
+        deprecations: Deprecations::Ignore,
+        ..EvaluatorOptions::default()
+    };
+
+    let started = Instant::now();
+    let _ = pkl_eval::evaluate_output_with(&source, "repl:text", options);
+    let elapsed = started.elapsed();
+    assert!(elapsed < TIMEOUT + GRACE, "the evaluation took {elapsed:?}, past its timeout of {TIMEOUT:?}");
+});
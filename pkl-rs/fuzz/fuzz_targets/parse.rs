@@ -0,0 +1,14 @@
+//! Parses arbitrary input with every entry point of the parser, which has to end without panicking.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oxc_allocator::Allocator;
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data);
+    let alloc = Allocator::default();
+    pkl_parser::parse_module(&alloc, &source);
+    pkl_parser::parse_module_header(&alloc, &source);
+    pkl_parser::parse_expr(&alloc, &source);
+});
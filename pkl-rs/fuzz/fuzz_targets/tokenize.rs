@@ -0,0 +1,16 @@
+//! Lexes arbitrary input, with and without trivia, which has to end without panicking.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oxc_allocator::Allocator;
+use pkl_lexer::Lexer;
+
+fuzz_target!(|data: &[u8]| {
+    // invalid UTF-8 is replaced, as `&str` is what the lexer takes
+    let source = String::from_utf8_lossy(data);
+    let alloc = Allocator::default();
+    let tokens = Lexer::tokenize(&alloc, &source);
+    assert!(tokens.iter().all(|token| source.get(token.span.start..token.span.end).is_some()));
+    Lexer::tokenize_with_trivia(&alloc, &source);
+});